@@ -9,8 +9,6 @@ pub enum GameError {
     AlreadyPending,
     #[error("Invalid game owner")]
     InvalidOwner,
-    #[error("Invalid VRF coordinator program")]
-    InvalidVrfCoordinator,
     #[error("Invalid VRF result account")]
     InvalidVrfResult,
     #[error("Invalid VRF request account")]
@@ -10,6 +10,7 @@ pub enum GameInstruction {
     /// 2. `[]` VRF subscription account
     /// 3. `[signer]` Payer for account creation
     /// 4. `[]` System program
+    /// 5. `[]` VRF coordinator program
     Initialize,
 
     /// Request a new random number
@@ -20,6 +21,7 @@ pub enum GameInstruction {
     /// 3. `[]` VRF subscription account
     /// 4. `[]` VRF coordinator program
     /// 5. `[]` System program
+    /// 6. `[]` VRF coordinator config account (PDA)
     RequestNewNumber,
 
     /// Consume randomness callback from VRF
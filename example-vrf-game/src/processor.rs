@@ -2,9 +2,10 @@ use {
     crate::{
         error::GameError,
         instruction::GameInstruction,
-        state::{GameState, VrfResult},
+        state::GameState,
     },
     borsh::{BorshDeserialize, BorshSerialize},
+    kamui_program::{cpi::load_vrf_result, error::VrfCoordinatorError, randomness::derive_in_range},
     solana_program::{
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
@@ -15,7 +16,6 @@ use {
         system_instruction,
         sysvar::{Sysvar, rent::Rent},
     },
-    std::str::FromStr,
 };
 
 pub fn process_instruction(
@@ -54,11 +54,13 @@ fn process_initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramR
     let subscription = next_account_info(accounts_iter)?;
     let payer = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
+    let vrf_coordinator = next_account_info(accounts_iter)?;
 
     msg!("Game Program: Owner: {}", owner.key);
     msg!("Game Program: Game state: {}", game_state.key);
     msg!("Game Program: Subscription: {}", subscription.key);
     msg!("Game Program: Payer: {}", payer.key);
+    msg!("Game Program: VRF coordinator: {}", vrf_coordinator.key);
 
     if !owner.is_signer {
         msg!("Game Program: Error - Missing owner signature");
@@ -81,15 +83,22 @@ fn process_initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramR
         return Err(ProgramError::InvalidSeeds);
     }
 
+    // The subscription must belong to the coordinator this game is bound to
+    if !vrf_coordinator.executable || subscription.owner != vrf_coordinator.key {
+        msg!("Game Program: Error - Subscription is not owned by the VRF coordinator");
+        return Err(VrfCoordinatorError::InvalidVrfCoordinator.into());
+    }
+
     let state = GameState {
         owner: *owner.key,
         subscription: *subscription.key,
+        vrf_coordinator: *vrf_coordinator.key,
         current_number: 0,
         is_pending: false,
     };
 
     // Create game state account
-    let space = 8 + borsh::to_vec(&state)?.len();
+    let space = GameState::LEN;
     let rent = Rent::get()?;
     let lamports = rent.minimum_balance(space);
 
@@ -130,6 +139,7 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
     let subscription = next_account_info(accounts_iter)?;
     let vrf_program = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
+    let coordinator_config = next_account_info(accounts_iter)?;
 
     msg!("Game Program: Owner: {}", owner.key);
     msg!("Game Program: Game state: {}", game_state.key);
@@ -169,6 +179,10 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
     if state.is_pending {
         return Err(GameError::AlreadyPending.into());
     }
+    if state.vrf_coordinator != *vrf_program.key {
+        msg!("Game Program: Error - Invalid VRF coordinator. Expected {}, got {}", state.vrf_coordinator, vrf_program.key);
+        return Err(VrfCoordinatorError::InvalidVrfCoordinator.into());
+    }
 
     // Create VRF request with a deterministic seed
     let seed = [0u8; 32]; // Use a deterministic seed for on-chain code
@@ -178,6 +192,7 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
         num_words: 1,
         minimum_confirmations: 1,
         callback_gas_limit: 200_000,
+        priority_fee: 0,
     };
 
     let request_ix_data = borsh::to_vec(&request_ix)?;
@@ -189,6 +204,7 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
                 solana_program::instruction::AccountMeta::new(*request_account.key, false),
                 solana_program::instruction::AccountMeta::new_readonly(*subscription.key, false),
                 solana_program::instruction::AccountMeta::new_readonly(solana_program::system_program::id(), false),
+                solana_program::instruction::AccountMeta::new_readonly(*coordinator_config.key, false),
             ],
             data: request_ix_data,
         },
@@ -197,6 +213,7 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
             request_account.clone(),
             subscription.clone(),
             system_program.clone(),
+            coordinator_config.clone(),
         ],
     )?;
 
@@ -251,34 +268,31 @@ fn process_consume_randomness(program_id: &Pubkey, accounts: &[AccountInfo]) ->
     }
 
     // Get VRF coordinator program ID
-    let vrf_coordinator_id = Pubkey::from_str("BfwfooykCSdb1vgu6FcP75ncUgdcdt4ciUaeaSLzxM4D").unwrap();
+    let vrf_coordinator_id = state.vrf_coordinator;
     msg!("Game Program: VRF coordinator ID: {}", vrf_coordinator_id);
 
     // Verify VRF result account owner
     if vrf_result.owner != &vrf_coordinator_id {
         msg!("Game Program: Error - Invalid VRF result owner. Expected {}, got {}", vrf_coordinator_id, vrf_result.owner);
-        return Err(GameError::InvalidVrfCoordinator.into());
+        return Err(VrfCoordinatorError::InvalidVrfCoordinator.into());
     }
 
     // Verify request account owner
     if request_account.owner != &vrf_coordinator_id {
         msg!("Game Program: Error - Invalid request account owner. Expected {}, got {}", vrf_coordinator_id, request_account.owner);
-        return Err(GameError::InvalidVrfCoordinator.into());
-    }
-
-    // Verify VRF result PDA
-    let (expected_vrf_result, _) = Pubkey::find_program_address(
-        &[b"vrf_result", state.owner.as_ref()],
-        &vrf_coordinator_id
-    );
-    if expected_vrf_result != *vrf_result.key {
-        msg!("Game Program: Error - Invalid VRF result PDA. Expected {}, got {}", expected_vrf_result, vrf_result.key);
-        return Err(GameError::InvalidVrfResult.into());
+        return Err(VrfCoordinatorError::InvalidVrfCoordinator.into());
     }
 
-    // Deserialize the VRF result with discriminator check
-    msg!("Game Program: Deserializing VRF result");
-    let vrf_result_data = VrfResult::try_deserialize(&vrf_result.data.borrow())?;
+    // Load the VRF result, checking its PDA, request, discriminator and layout version
+    msg!("Game Program: Loading VRF result");
+    let vrf_result_data = load_vrf_result(vrf_result, &vrf_coordinator_id, request_account.key).map_err(|err| {
+        msg!("Game Program: Error - Invalid VRF result account {} for request {}: {}", vrf_result.key, request_account.key, err);
+        if err == VrfCoordinatorError::InvalidVrfResult.into() {
+            ProgramError::from(GameError::InvalidVrfRequest)
+        } else {
+            ProgramError::from(GameError::InvalidVrfResult)
+        }
+    })?;
 
     // Ensure we have at least one randomness value
     if vrf_result_data.randomness.is_empty() {
@@ -286,14 +300,9 @@ fn process_consume_randomness(program_id: &Pubkey, accounts: &[AccountInfo]) ->
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Take the first 8 bytes of the first randomness value and convert to u64
-    let random_bytes = &vrf_result_data.randomness[0][0..8];
-    let random_value = u64::from_le_bytes(random_bytes.try_into().unwrap());
-    msg!("Game Program: Generated random value: {}", random_value);
-    
     // Update game state with new random number (1-100)
     let mut updated_state = state;
-    updated_state.current_number = ((random_value % 100) + 1) as u8;
+    updated_state.current_number = derive_in_range(&vrf_result_data.randomness[0], 0, 1, 100)? as u8;
     updated_state.is_pending = false;
     msg!("Game Program: New random number: {}", updated_state.current_number);
 
@@ -314,5 +323,6 @@ enum VrfCpi {
         num_words: u32,
         minimum_confirmations: u8,
         callback_gas_limit: u64,
+        priority_fee: u64,
     },
 } 
\ No newline at end of file
@@ -1,6 +1,6 @@
 use {
     borsh::{BorshDeserialize, BorshSerialize},
-    solana_program::{pubkey::Pubkey, program_error::ProgramError},
+    solana_program::pubkey::Pubkey,
 };
 
 /// State for the game
@@ -10,30 +10,20 @@ pub struct GameState {
     pub owner: Pubkey,
     /// The VRF subscription used by this game
     pub subscription: Pubkey,
+    /// The VRF coordinator program that serves this game
+    pub vrf_coordinator: Pubkey,
     /// The current random number (1-100)
     pub current_number: u8,
     /// Whether we're waiting for randomness
     pub is_pending: bool,
 }
 
-/// VRF result from the coordinator
-#[derive(BorshSerialize, BorshDeserialize)]
-pub struct VrfResult {
-    /// The randomness outputs
-    pub randomness: Vec<[u8; 64]>,
-    /// The VRF proof
-    pub proof: Vec<u8>,
-    /// Block number when proof was generated
-    pub proof_block: u64,
+impl GameState {
+    /// Account size, including the discriminator
+    pub const LEN: usize = 8 // discriminator
+        + 32                 // owner
+        + 32                 // subscription
+        + 32                 // vrf_coordinator
+        + 1                  // current_number
+        + 1;                 // is_pending
 }
-
-impl VrfResult {
-    pub fn try_deserialize(data: &[u8]) -> Result<Self, ProgramError> {
-        // Check discriminator
-        if data.len() < 8 || &data[0..8] != b"VRFRSLT\0" {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        // Skip discriminator and deserialize the rest
-        Self::try_from_slice(&data[8..]).map_err(|_| ProgramError::InvalidAccountData)
-    }
-} 
\ No newline at end of file
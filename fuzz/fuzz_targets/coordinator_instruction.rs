@@ -0,0 +1,19 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deserializes arbitrary instruction data for the coordinator and processes
+//! it without accounts, which must fail without panicking. Instructions that
+//! deserialize must serialize back to the same bytes.
+
+#![no_main]
+
+use kamui_program::{instruction::VrfCoordinatorInstruction, processor::Processor};
+use libfuzzer_sys::fuzz_target;
+use solana_program::pubkey::Pubkey;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(instruction) = VrfCoordinatorInstruction::unpack(data) {
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), data);
+    }
+    assert!(Processor::process(&Pubkey::default(), &[], data).is_err());
+});
@@ -0,0 +1,23 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parses arbitrary bytes as proofs in every layout. Accepted proofs must
+//! encode back to the same bytes.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mangekyou::kamui_vrf::ecvrf::{ECVRFBatchableProof, ECVRFProof};
+use mangekyou::kamui_vrf::VRFProof;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(proof) = ECVRFProof::from_bytes(data) {
+        assert_eq!(proof.to_bytes(), data);
+    }
+    if let Ok(proof) = ECVRFProof::from_bytes_legacy(data) {
+        assert_eq!(proof.to_bytes_legacy(), data);
+    }
+    if let Ok(proof) = ECVRFBatchableProof::from_bytes(data) {
+        assert_eq!(proof.to_bytes(), data);
+    }
+});
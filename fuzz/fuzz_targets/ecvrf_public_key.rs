@@ -0,0 +1,27 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parses arbitrary bytes as keys, and verifies a proof under any key that
+//! parses. Accepted keys must encode back to the same bytes.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mangekyou::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFPublicKey, KEY_LENGTH, PROOF_LENGTH};
+use mangekyou_core::ecvrf;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(pk) = ECVRFPublicKey::from_bytes(data) {
+        assert_eq!(pk.as_ref(), data);
+    }
+    if let Ok(kp) = ECVRFKeyPair::from_bytes(data) {
+        assert_eq!(kp.pk.as_ref(), &data[..KEY_LENGTH]);
+        assert_eq!(kp.sk.as_ref(), &data[KEY_LENGTH..]);
+    }
+    // public_key || proof || alpha
+    if data.len() >= KEY_LENGTH + PROOF_LENGTH {
+        let (public_key, rest) = data.split_at(KEY_LENGTH);
+        let (proof, alpha) = rest.split_at(PROOF_LENGTH);
+        let _ = ecvrf::verify(proof, alpha, public_key);
+    }
+});
@@ -0,0 +1,25 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs arbitrary instruction data through the standalone verifier program,
+//! the on-chain `VerifyVrfInput` path. Inputs that deserialize must serialize
+//! back to the same bytes, and only proofs that verify may succeed.
+
+#![no_main]
+
+use borsh::BorshDeserialize;
+use kamui_program::{instruction::VerifyVrfInput, verifier};
+use libfuzzer_sys::fuzz_target;
+use solana_program::pubkey::Pubkey;
+
+fuzz_target!(|data: &[u8]| {
+    let result = verifier::process_verify(&Pubkey::default(), &[], data);
+    match VerifyVrfInput::try_from_slice(data) {
+        Ok(input) => {
+            assert_eq!(borsh::to_vec(&input).unwrap(), data);
+            let output = verifier::verify_proof(&input.alpha_string, &input.proof_bytes, &input.public_key_bytes);
+            assert_eq!(result.is_ok(), output.is_ok());
+        }
+        Err(_) => assert!(result.is_err()),
+    }
+});
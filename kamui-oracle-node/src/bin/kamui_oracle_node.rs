@@ -0,0 +1,24 @@
+use {
+    clap::Parser,
+    kamui_oracle_node::{config::Config, node::Node},
+    std::path::PathBuf,
+};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Fulfill the randomness requests of a Kamui VRF coordinator", long_about = None)]
+struct Args {
+    /// TOML configuration of the node
+    #[arg(short, long)]
+    config: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let args = Args::parse();
+    let config = Config::load(&args.config)?;
+    // A remote signer is checked with a blocking request
+    let signer = tokio::task::block_in_place(|| config.vrf_signer())?;
+    Node::new(config, signer).await?.run().await?;
+    Ok(())
+}
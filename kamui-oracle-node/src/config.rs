@@ -0,0 +1,344 @@
+//! Configuration of the node, read from a TOML file:
+//!
+//! ```toml
+//! program_id = "4LqMDW5pMd6uGmTmL3a8cWbkzVGZ6D2Vx8EJAnPPoeSx"
+//! oracle_keypair = "oracle-keypair.json"
+//! callback_program = "5gSZAw9aDQYGJABr6guQqPRFzyX756BJoV3XWMQ5eBAJ"
+//! state_path = "oracle-state.json"
+//!
+//! [[endpoints]]
+//! rpc_url = "https://api.devnet.solana.com"
+//! ws_url = "wss://api.devnet.solana.com"
+//!
+//! [[endpoints]]
+//! rpc_url = "https://devnet.backup-rpc.example"
+//! ws_url = "wss://devnet.backup-rpc.example"
+//!
+//! [vrf_key]
+//! keystore = "oracle.json"
+//! password_file = "password.txt"
+//!
+//! [priority_fee]
+//! percentile = 75
+//! max_micro_lamports = 100000
+//!
+//! [retry]
+//! max_attempts = 8
+//! ```
+//!
+//! Keys are base58 and paths are relative to the working directory. Endpoints
+//! are listed in order of preference, see [crate::rpc]. Fields not shown
+//! above, and the sections other than `vrf_key`, have the defaults of
+//! [Config].
+
+use {
+    crate::error::{OracleNodeError, OracleNodeResult},
+    mangekyou::{
+        kamui_vrf::ecvrf::ECVRFPublicKey,
+        keystore::Keystore,
+        signer::{RemoteSigner, VrfSigner},
+    },
+    serde::{Deserialize, Deserializer},
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair},
+    },
+    std::{path::{Path, PathBuf}, str::FromStr, time::Duration},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    /// RPC endpoints, the first healthy one of which is used
+    pub endpoints: Vec<EndpointConfig>,
+    /// The coordinator program
+    #[serde(deserialize_with = "pubkey")]
+    pub program_id: Pubkey,
+    /// Solana keypair file of the oracle, which signs and pays for
+    /// fulfillments and is registered with the coordinator
+    pub oracle_keypair: PathBuf,
+    /// Where the VRF key is held
+    pub vrf_key: VrfKeyConfig,
+    /// State file of the requests in flight, see [crate::store]
+    pub state_path: PathBuf,
+    /// Program the fulfillments call back, whose `game_state` PDA of the
+    /// requester they pass. Defaults to the requester, for requests made by
+    /// the consumer program itself.
+    #[serde(default, deserialize_with = "optional_pubkey")]
+    pub callback_program: Option<Pubkey>,
+    /// Commitment requests are read at and fulfillments confirmed at
+    #[serde(default = "Config::default_commitment", deserialize_with = "commitment")]
+    pub commitment: CommitmentConfig,
+    /// Compute unit limit of fulfillment transactions. It must cover the
+    /// proof verification and the callback's `callback_gas_limit`.
+    #[serde(default = "Config::default_compute_unit_limit")]
+    pub compute_unit_limit: u32,
+    #[serde(default)]
+    pub priority_fee: PriorityFeeConfig,
+    /// Seconds between polls for pending requests and in-flight fulfillments
+    #[serde(default = "Config::default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    #[serde(default)]
+    pub retry: RetryConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EndpointConfig {
+    /// JSON RPC endpoint
+    pub rpc_url: String,
+    /// Websocket endpoint of the same node, which the coordinator's logs are
+    /// subscribed to at
+    pub ws_url: String,
+}
+
+/// The VRF key, in a password-encrypted keystore or behind a remote signer
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum VrfKeyConfig {
+    /// A keystore of `ecvrf-cli keygen --keystore`, with its password on the
+    /// first line of `password_file`
+    Keystore { keystore: PathBuf, password_file: PathBuf },
+    /// A signing service, see [RemoteSigner]
+    Remote {
+        remote_signer: String,
+        /// Hex encoded VRF public key the service must prove under
+        public_key: String,
+        #[serde(default)]
+        token: Option<String>,
+    },
+}
+
+/// Compute unit price of fulfillment transactions
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PriorityFeeConfig {
+    /// A fixed price in micro-lamports per compute unit, instead of one
+    /// estimated from recent fees
+    pub fixed_micro_lamports: Option<u64>,
+    /// Percentile of the recent prioritization fees paid for the
+    /// coordinator's accounts the estimate takes
+    pub percentile: u8,
+    /// Highest price paid, whatever the estimate
+    pub max_micro_lamports: u64,
+}
+
+impl Default for PriorityFeeConfig {
+    fn default() -> Self {
+        Self { fixed_micro_lamports: None, percentile: 75, max_micro_lamports: 1_000_000 }
+    }
+}
+
+/// How failed fulfillments are retried
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Failed submissions of a request after which it is given up
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled with every further failure
+    pub initial_backoff_ms: u64,
+    /// Longest wait between retries
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self { max_attempts: 5, initial_backoff_ms: 500, max_backoff_ms: 30_000 }
+    }
+}
+
+impl RetryConfig {
+    /// Wait before retrying a request that failed `attempts` times
+    pub fn backoff(&self, attempts: u32) -> Duration {
+        let exponent = attempts.saturating_sub(1).min(32);
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(1 << exponent).min(self.max_backoff_ms))
+    }
+}
+
+/// How endpoints are checked, see [crate::rpc]
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Seconds between health checks of every endpoint
+    pub interval_secs: u64,
+    /// Slots an endpoint may trail the most advanced one by and still count
+    /// as healthy
+    pub max_slot_lag: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self { interval_secs: 30, max_slot_lag: 50 }
+    }
+}
+
+impl Config {
+    fn default_commitment() -> CommitmentConfig {
+        CommitmentConfig::confirmed()
+    }
+
+    fn default_compute_unit_limit() -> u32 {
+        400_000
+    }
+
+    fn default_poll_interval_secs() -> u64 {
+        10
+    }
+
+    /// Reads the configuration at `path`
+    pub fn load(path: &Path) -> OracleNodeResult<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses a TOML configuration
+    pub fn parse(toml: &str) -> OracleNodeResult<Self> {
+        let config: Self = toml::from_str(toml).map_err(|e| OracleNodeError::Config(e.to_string()))?;
+        if config.endpoints.is_empty() {
+            return Err(OracleNodeError::Config("at least one endpoint is needed".to_string()));
+        }
+        if config.priority_fee.percentile > 100 {
+            return Err(OracleNodeError::Config("priority_fee.percentile must be at most 100".to_string()));
+        }
+        Ok(config)
+    }
+
+    /// Reads the oracle's keypair
+    pub fn oracle_keypair(&self) -> OracleNodeResult<Keypair> {
+        read_keypair_file(&self.oracle_keypair).map_err(|e| {
+            OracleNodeError::Config(format!("cannot read {}: {}", self.oracle_keypair.display(), e))
+        })
+    }
+
+    /// Opens the VRF key. A remote signer is checked to hold the configured
+    /// public key.
+    pub fn vrf_signer(&self) -> OracleNodeResult<Box<dyn VrfSigner + Send + Sync>> {
+        match &self.vrf_key {
+            VrfKeyConfig::Keystore { keystore, password_file } => {
+                let keystore = Keystore::load(keystore)?;
+                Ok(Box::new(keystore.unlock(&read_password(password_file)?)?))
+            }
+            VrfKeyConfig::Remote { remote_signer, public_key, token } => {
+                let public_key = hex::decode(public_key)
+                    .ok()
+                    .and_then(|bytes| ECVRFPublicKey::from_bytes(&bytes).ok())
+                    .ok_or_else(|| OracleNodeError::Config("invalid vrf_key.public_key".to_string()))?;
+                let mut signer = RemoteSigner::new(remote_signer, public_key);
+                if let Some(token) = token {
+                    signer = signer.with_token(token);
+                }
+                signer.check()?;
+                Ok(Box::new(signer))
+            }
+        }
+    }
+}
+
+/// The first line of the file at `path`, as `ecvrf-cli` reads passwords
+fn read_password(path: &Path) -> std::io::Result<Vec<u8>> {
+    let contents = std::fs::read(path)?;
+    let line = contents.split(|b| *b == b'\n').next().unwrap_or_default();
+    Ok(line.strip_suffix(b"\r").unwrap_or(line).to_vec())
+}
+
+fn pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let key = String::deserialize(deserializer)?;
+    Pubkey::from_str(&key).map_err(serde::de::Error::custom)
+}
+
+fn commitment<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CommitmentConfig, D::Error> {
+    let commitment = String::deserialize(deserializer)?;
+    CommitmentConfig::from_str(&commitment).map_err(serde::de::Error::custom)
+}
+
+fn optional_pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pubkey>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|key| Pubkey::from_str(&key).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        program_id = "4LqMDW5pMd6uGmTmL3a8cWbkzVGZ6D2Vx8EJAnPPoeSx"
+        oracle_keypair = "oracle-keypair.json"
+        state_path = "oracle-state.json"
+
+        [[endpoints]]
+        rpc_url = "http://localhost:8899"
+        ws_url = "ws://localhost:8900"
+
+        [vrf_key]
+        keystore = "oracle.json"
+        password_file = "password.txt"
+    "#;
+
+    #[test]
+    fn test_defaults() {
+        let config = Config::parse(CONFIG).unwrap();
+        assert_eq!(config.program_id.to_string(), "4LqMDW5pMd6uGmTmL3a8cWbkzVGZ6D2Vx8EJAnPPoeSx");
+        assert_eq!(config.endpoints.len(), 1);
+        assert!(matches!(config.vrf_key, VrfKeyConfig::Keystore { .. }));
+        assert_eq!(config.callback_program, None);
+        assert_eq!(config.commitment, CommitmentConfig::confirmed());
+        assert_eq!(config.compute_unit_limit, 400_000);
+        assert_eq!(config.priority_fee, PriorityFeeConfig::default());
+        assert_eq!(config.retry, RetryConfig::default());
+        assert_eq!(config.health, HealthConfig::default());
+    }
+
+    #[test]
+    fn test_remote_signer_and_overrides() {
+        let config = Config::parse(r#"
+            program_id = "4LqMDW5pMd6uGmTmL3a8cWbkzVGZ6D2Vx8EJAnPPoeSx"
+            oracle_keypair = "oracle-keypair.json"
+            state_path = "oracle-state.json"
+            callback_program = "5gSZAw9aDQYGJABr6guQqPRFzyX756BJoV3XWMQ5eBAJ"
+            commitment = "finalized"
+
+            [[endpoints]]
+            rpc_url = "http://primary:8899"
+            ws_url = "ws://primary:8900"
+
+            [[endpoints]]
+            rpc_url = "http://backup:8899"
+            ws_url = "ws://backup:8900"
+
+            [vrf_key]
+            remote_signer = "http://signer:8080"
+            public_key = "00"
+
+            [priority_fee]
+            fixed_micro_lamports = 5000
+
+            [retry]
+            max_attempts = 8
+        "#).unwrap();
+        assert_eq!(config.endpoints[1].rpc_url, "http://backup:8899");
+        assert!(matches!(&config.vrf_key, VrfKeyConfig::Remote { token: None, .. }));
+        assert!(config.callback_program.is_some());
+        assert_eq!(config.commitment, CommitmentConfig::finalized());
+        assert_eq!(config.priority_fee.fixed_micro_lamports, Some(5000));
+        assert_eq!(config.priority_fee.percentile, 75);
+        assert_eq!(config.retry.max_attempts, 8);
+        assert_eq!(config.retry.max_backoff_ms, 30_000);
+        assert!(matches!(config.vrf_signer(), Err(OracleNodeError::Config(_))));
+
+        let invalid = CONFIG.replace("4LqMDW5pMd6uGmTmL3a8cWbkzVGZ6D2Vx8EJAnPPoeSx", "not a key");
+        assert!(Config::parse(&invalid).is_err());
+        let no_endpoints = CONFIG.replace("[[endpoints]]", "[unused]");
+        assert!(matches!(Config::parse(&no_endpoints), Err(OracleNodeError::Config(_))));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let retry = RetryConfig::default();
+        assert_eq!(retry.backoff(1), Duration::from_millis(500));
+        assert_eq!(retry.backoff(2), Duration::from_millis(1000));
+        assert_eq!(retry.backoff(4), Duration::from_millis(4000));
+        assert_eq!(retry.backoff(7), Duration::from_millis(30_000));
+        assert_eq!(retry.backoff(100), Duration::from_millis(30_000));
+    }
+}
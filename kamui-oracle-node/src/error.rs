@@ -0,0 +1,45 @@
+use {
+    mangekyou::error::MangekyouError,
+    solana_client::{client_error::ClientError, nonblocking::pubsub_client::PubsubClientError},
+    solana_sdk::program_error::ProgramError,
+    thiserror::Error,
+};
+
+#[derive(Debug, Error)]
+pub enum OracleNodeError {
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+
+    // Boxed, as solana-client's errors are large
+    #[error("RPC error: {0}")]
+    Rpc(Box<ClientError>),
+
+    #[error("Websocket error: {0}")]
+    Pubsub(Box<PubsubClientError>),
+
+    #[error("VRF signer error: {0}")]
+    Signer(#[from] MangekyouError),
+
+    #[error("Invalid coordinator account: {0}")]
+    Account(#[from] ProgramError),
+
+    #[error("State file error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("State file error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl From<ClientError> for OracleNodeError {
+    fn from(error: ClientError) -> Self {
+        Self::Rpc(Box::new(error))
+    }
+}
+
+impl From<PubsubClientError> for OracleNodeError {
+    fn from(error: PubsubClientError) -> Self {
+        Self::Pubsub(Box::new(error))
+    }
+}
+
+pub type OracleNodeResult<T> = Result<T, OracleNodeError>;
@@ -0,0 +1,245 @@
+//! The `FulfillRandomness` transactions of the node, and when a request gets
+//! one.
+
+use {
+    kamui_program::{
+        event::event_authority_address,
+        instruction::VrfCoordinatorInstruction,
+        pda::{derive_config_pda, derive_oracle_config_pda, derive_vrf_result_pda},
+        state::{RandomnessRequest, RequestStatus},
+        verifier::VrfSuite,
+    },
+    solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+/// PDA seed prefix of the consumer state the coordinator passes to callbacks,
+/// followed by the requester
+pub const GAME_STATE_SEED: &[u8] = b"game_state";
+
+/// What the node knows of the coordinator's config
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CoordinatorFlags {
+    /// Whether the config account exists, in which case fulfillments pass it
+    /// so that the subscription pays the quoted fee
+    pub has_config: bool,
+    /// Whether events are emitted by self-invocation, which needs the event
+    /// authority and the program
+    pub cpi_events: bool,
+}
+
+/// Why a request is not fulfilled by the node now
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Skip {
+    /// Fulfilled, cancelled or expired, never to be fulfilled by the node
+    Closed,
+    /// Assigned to another oracle
+    OtherOracle,
+    /// Needs responses from several oracles, which the node does not collect
+    MultiOracle,
+    /// Not ready until the slot
+    NotReady(u64),
+}
+
+/// Slot from which `request` may be fulfilled: after its confirmations and
+/// its time lock
+pub fn ready_slot(request: &RandomnessRequest) -> u64 {
+    request.not_before_slot.max(request.request_block.saturating_add(request.confirmations as u64))
+}
+
+/// Whether `request` has expired at `slot`
+pub fn is_expired(request: &RandomnessRequest, slot: u64) -> bool {
+    request.expiration_slot != 0 && slot >= request.expiration_slot
+}
+
+/// Checks whether `oracle` should fulfill `request` at `slot`
+pub fn check(request: &RandomnessRequest, oracle: &Pubkey, slot: u64) -> Result<(), Skip> {
+    if request.status != RequestStatus::Pending || is_expired(request, slot) {
+        return Err(Skip::Closed);
+    }
+    if request.assigned_oracle != Pubkey::default() && request.assigned_oracle != *oracle {
+        return Err(Skip::OtherOracle);
+    }
+    if request.required_oracle_responses > 1 {
+        return Err(Skip::MultiOracle);
+    }
+    let ready = ready_slot(request);
+    if slot < ready {
+        return Err(Skip::NotReady(ready));
+    }
+    Ok(())
+}
+
+/// `FulfillRandomness` of the request at `request_key` by `oracle`, with the
+/// legacy encoding of the proof the coordinator takes. Consumer state is the
+/// `game_state` PDA of the requester under `callback_program`.
+#[allow(clippy::too_many_arguments)]
+pub fn fulfill_instruction(
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+    request_key: &Pubkey,
+    request: &RandomnessRequest,
+    callback_program: &Pubkey,
+    proof: Vec<u8>,
+    public_key: Vec<u8>,
+    flags: CoordinatorFlags,
+) -> Instruction {
+    let (vrf_result, _) = derive_vrf_result_pda(program_id, request_key);
+    let (oracle_config, _) = derive_oracle_config_pda(program_id, oracle);
+    let (game_state, _) = Pubkey::find_program_address(
+        &[GAME_STATE_SEED, request.requester.as_ref()],
+        callback_program,
+    );
+    let subscription = if request.subscription == Pubkey::default() {
+        AccountMeta::new_readonly(system_program::id(), false)
+    } else {
+        AccountMeta::new(request.subscription, false)
+    };
+    let mut accounts = vec![
+        AccountMeta::new(*oracle, true),
+        AccountMeta::new(*request_key, false),
+        AccountMeta::new(vrf_result, false),
+        AccountMeta::new_readonly(*callback_program, false),
+        subscription,
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*callback_program, false),
+        AccountMeta::new(game_state, false),
+        AccountMeta::new(oracle_config, false),
+    ];
+    if flags.has_config {
+        accounts.push(AccountMeta::new_readonly(derive_config_pda(program_id).0, false));
+    }
+    if flags.cpi_events {
+        accounts.push(AccountMeta::new_readonly(event_authority_address(program_id).0, false));
+        accounts.push(AccountMeta::new_readonly(*program_id, false));
+    }
+    Instruction::new_with_borsh(
+        *program_id,
+        &VrfCoordinatorInstruction::FulfillRandomness { proof, public_key, suite: VrfSuite::Ristretto255Sha512 },
+        accounts,
+    )
+}
+
+/// Compute budget instructions of a fulfillment: `compute_unit_limit` units at
+/// `micro_lamports` each, the price left out if zero
+pub fn compute_budget_instructions(compute_unit_limit: u32, micro_lamports: u64) -> Vec<Instruction> {
+    let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit)];
+    if micro_lamports > 0 {
+        instructions.push(ComputeBudgetInstruction::set_compute_unit_price(micro_lamports));
+    }
+    instructions
+}
+
+/// The `percentile` of recently paid prioritization `fees`, at most `max`
+pub fn priority_fee(mut fees: Vec<u64>, percentile: u8, max: u64) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let index = (fees.len() - 1) * percentile.min(100) as usize / 100;
+    fees[index].min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, borsh::BorshDeserialize};
+
+    fn request(oracle: Pubkey) -> RandomnessRequest {
+        RandomnessRequest {
+            subscription: Pubkey::new_unique(),
+            seed: [7; 32],
+            requester: Pubkey::new_unique(),
+            request_block: 100,
+            status: RequestStatus::Pending,
+            num_words: 1,
+            callback_gas_limit: 200_000,
+            nonce: 0,
+            commitment: [0; 32],
+            assigned_oracle: oracle,
+            not_before_slot: 0,
+            fee_auction: Default::default(),
+            expiration_slot: 0,
+            confirmations: 3,
+            required_oracle_responses: 1,
+            priority_fee: 0,
+            reserved_fee: 0,
+            reserved_native: 0,
+            id: Default::default(),
+            callback_data: vec![],
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let oracle = Pubkey::new_unique();
+        let mut request = request(oracle);
+        assert_eq!(check(&request, &oracle, 102), Err(Skip::NotReady(103)));
+        assert_eq!(check(&request, &oracle, 103), Ok(()));
+        assert_eq!(check(&request, &Pubkey::new_unique(), 103), Err(Skip::OtherOracle));
+
+        request.not_before_slot = 200;
+        assert_eq!(check(&request, &oracle, 103), Err(Skip::NotReady(200)));
+
+        request.expiration_slot = 150;
+        assert_eq!(check(&request, &oracle, 150), Err(Skip::Closed));
+
+        request.expiration_slot = 0;
+        request.assigned_oracle = Pubkey::default();
+        request.required_oracle_responses = 3;
+        assert_eq!(check(&request, &Pubkey::new_unique(), 200), Err(Skip::MultiOracle));
+
+        request.required_oracle_responses = 1;
+        assert_eq!(check(&request, &Pubkey::new_unique(), 200), Ok(()));
+        request.status = RequestStatus::Fulfilled;
+        assert_eq!(check(&request, &oracle, 200), Err(Skip::Closed));
+    }
+
+    #[test]
+    fn test_fulfill_instruction() {
+        let (program_id, oracle, request_key, callback) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let request = request(oracle);
+        let instruction = fulfill_instruction(
+            &program_id, &oracle, &request_key, &request, &callback, vec![1; 80], vec![2; 32],
+            CoordinatorFlags::default(),
+        );
+        assert_eq!(instruction.accounts.len(), 9);
+        assert!(instruction.accounts[0].is_signer && instruction.accounts[0].is_writable);
+        assert_eq!(instruction.accounts[2].pubkey, derive_vrf_result_pda(&program_id, &request_key).0);
+        assert_eq!(instruction.accounts[4].pubkey, request.subscription);
+        assert_eq!(instruction.accounts[8].pubkey, derive_oracle_config_pda(&program_id, &oracle).0);
+        match VrfCoordinatorInstruction::try_from_slice(&instruction.data).unwrap() {
+            VrfCoordinatorInstruction::FulfillRandomness { proof, public_key, suite } => {
+                assert_eq!((proof, public_key, suite), (vec![1; 80], vec![2; 32], VrfSuite::Ristretto255Sha512));
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+
+        let mut direct = request;
+        direct.subscription = Pubkey::default();
+        let flags = CoordinatorFlags { has_config: true, cpi_events: true };
+        let instruction = fulfill_instruction(
+            &program_id, &oracle, &request_key, &direct, &callback, vec![1; 80], vec![2; 32], flags,
+        );
+        assert_eq!(instruction.accounts.len(), 12);
+        assert_eq!(instruction.accounts[4].pubkey, system_program::id());
+        assert_eq!(instruction.accounts[9].pubkey, derive_config_pda(&program_id).0);
+        assert_eq!(instruction.accounts[10].pubkey, event_authority_address(&program_id).0);
+        assert_eq!(instruction.accounts[11].pubkey, program_id);
+    }
+
+    #[test]
+    fn test_priority_fee() {
+        assert_eq!(priority_fee(vec![], 75, 1000), 0);
+        assert_eq!(priority_fee(vec![40, 10, 30, 20, 50], 0, 1000), 10);
+        assert_eq!(priority_fee(vec![40, 10, 30, 20, 50], 50, 1000), 30);
+        assert_eq!(priority_fee(vec![40, 10, 30, 20, 50], 100, 1000), 50);
+        assert_eq!(priority_fee(vec![40, 10, 30, 20, 5000], 100, 1000), 1000);
+        assert_eq!(compute_budget_instructions(400_000, 0).len(), 1);
+        assert_eq!(compute_budget_instructions(400_000, 10).len(), 2);
+    }
+}
@@ -0,0 +1,25 @@
+//! Oracle daemon for the Kamui VRF coordinator.
+//!
+//! The node follows the coordinator in two ways. It subscribes to the
+//! coordinator's logs over websocket and picks `RandomnessRequested` events
+//! out of them, see [listener], and it polls for pending requests it may
+//! fulfill, which catches requests whose events it missed: those made while
+//! it was down, those of truncated logs and those of a coordinator emitting
+//! events by self-invocation. Once a request has its confirmations, the node
+//! proves its seed with the configured VRF key and submits
+//! `FulfillRandomness` with a compute budget and a priority fee, see
+//! [fulfill]. Requests in flight are persisted, see [store], so that a
+//! restarted node picks up where it stopped, and failed submissions are
+//! retried with exponential backoff. Several RPC endpoints may be configured,
+//! which the node fails over between, see [rpc].
+//!
+//! `kamui-oracle-node --config oracle.toml` runs the node with a
+//! [config::Config].
+
+pub mod config;
+pub mod error;
+pub mod fulfill;
+pub mod listener;
+pub mod node;
+pub mod rpc;
+pub mod store;
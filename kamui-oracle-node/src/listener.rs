@@ -0,0 +1,129 @@
+//! Follows the coordinator's logs over websocket and forwards the requests
+//! they announce.
+
+use {
+    futures_util::StreamExt,
+    kamui_program::event::VrfEvent,
+    log::{info, warn},
+    solana_client::{
+        nonblocking::pubsub_client::PubsubClient,
+        rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+    },
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey},
+    std::time::Duration,
+    tokio::sync::mpsc::UnboundedSender,
+};
+
+/// Longest wait before reconnecting after the subscription fails
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The request accounts of the `RandomnessRequested` events in `logs`
+pub fn requested_events(logs: &[String]) -> Vec<Pubkey> {
+    logs.iter()
+        .filter_map(|line| match VrfEvent::from_log(line)? {
+            VrfEvent::RandomnessRequested { request_id, .. } => Some(request_id),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Subscribes to the logs of transactions mentioning `program_id` and sends
+/// the requests they announce to `requests`. Whenever the subscription drops
+/// it moves on to the next of `ws_urls`, with backoff once every endpoint has
+/// failed in a row. Returns once `requests` is closed.
+pub async fn listen(
+    ws_urls: Vec<String>,
+    program_id: Pubkey,
+    commitment: CommitmentConfig,
+    requests: UnboundedSender<Pubkey>,
+) {
+    let mut backoff = Duration::from_secs(1);
+    let mut failures = 0;
+    let mut index = 0;
+    while !requests.is_closed() {
+        let ws_url = &ws_urls[index];
+        match subscribe(ws_url, program_id, commitment, &requests).await {
+            Ok(()) => {
+                warn!("Log subscription at {} closed, reconnecting", ws_url);
+                failures = 0;
+                backoff = Duration::from_secs(1);
+            }
+            Err(e) => {
+                warn!("Log subscription at {} failed: {}", ws_url, e);
+                failures += 1;
+            }
+        }
+        index = (index + 1) % ws_urls.len();
+        if failures >= ws_urls.len() {
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+async fn subscribe(
+    ws_url: &str,
+    program_id: Pubkey,
+    commitment: CommitmentConfig,
+    requests: &UnboundedSender<Pubkey>,
+) -> Result<(), crate::error::OracleNodeError> {
+    let client = PubsubClient::new(ws_url).await?;
+    let (mut logs, unsubscribe) = client
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![program_id.to_string()]),
+            RpcTransactionLogsConfig { commitment: Some(commitment) },
+        )
+        .await?;
+    info!("Subscribed to the logs of {}", program_id);
+    while let Some(response) = logs.next().await {
+        if response.value.err.is_some() {
+            continue;
+        }
+        for request in requested_events(&response.value.logs) {
+            if requests.send(request).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    drop(logs);
+    unsubscribe().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, base64::Engine, kamui_program::state::RequestId};
+
+    #[test]
+    fn test_requested_events() {
+        let request_id = Pubkey::new_unique();
+        let requested = VrfEvent::RandomnessRequested {
+            request_id,
+            id: RequestId::default(),
+            requester: Pubkey::new_unique(),
+            subscription: Pubkey::new_unique(),
+            seed: [1; 32],
+            priority_fee: 0,
+            slot: 10,
+            unix_timestamp: 0,
+        };
+        let fulfilled = VrfEvent::RandomnessFulfilled {
+            request_id,
+            id: RequestId::default(),
+            requester: Pubkey::new_unique(),
+            randomness: [2; 64],
+            slot: 11,
+            unix_timestamp: 0,
+        };
+        let log = |event: &VrfEvent| {
+            format!("Program log: VRF_EVENT:{}", base64::engine::general_purpose::STANDARD.encode(borsh::to_vec(event).unwrap()))
+        };
+        let logs = vec![
+            "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+            log(&requested),
+            log(&fulfilled),
+            "Program log: VRF_EVENT:not base64".to_string(),
+        ];
+        assert_eq!(requested_events(&logs), vec![request_id]);
+    }
+}
@@ -0,0 +1,361 @@
+//! The node's main loop: tracks the requests the listener and the polls find,
+//! and fulfills each once it is ready.
+
+use {
+    crate::{
+        config::Config,
+        error::{OracleNodeError, OracleNodeResult},
+        fulfill::{self, CoordinatorFlags, Skip},
+        listener,
+        rpc::RpcPool,
+        store::{now_ms, Stage, Store},
+    },
+    kamui_program::{
+        client::{assigned_oracle_filter, request_discriminator_filter, status_filter},
+        pda::{derive_config_pda, derive_oracle_config_pda},
+        state::{migrations::upgrade, CoordinatorConfig, OracleConfig, RandomnessRequest, RequestStatus},
+    },
+    log::{debug, info, warn},
+    mangekyou::signer::VrfSigner,
+    solana_account_decoder::UiDataSliceConfig,
+    solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    solana_sdk::{
+        account::Account,
+        pubkey::Pubkey,
+        signature::{Keypair, Signature, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    std::{str::FromStr, sync::Arc, time::Duration},
+    tokio::sync::mpsc,
+};
+
+pub struct Node {
+    config: Config,
+    rpc: RpcPool,
+    oracle: Keypair,
+    signer: Arc<dyn VrfSigner + Send + Sync>,
+    store: Store,
+    flags: CoordinatorFlags,
+}
+
+/// Where a submitted fulfillment is
+enum Submission {
+    /// Not seen yet, and may still land
+    InFlight,
+    /// Landed and failed
+    Failed,
+    /// Not seen, and its blockhash has expired
+    Expired,
+}
+
+impl Node {
+    /// Connects to the coordinator of `config` and checks that the oracle is
+    /// registered with the VRF key of `signer`
+    pub async fn new(config: Config, signer: Box<dyn VrfSigner + Send + Sync>) -> OracleNodeResult<Self> {
+        let mut rpc = RpcPool::new(&config.endpoints, config.commitment, &config.health);
+        rpc.check_health().await;
+        let oracle = config.oracle_keypair()?;
+        let program_id = config.program_id;
+
+        let oracle_config_key = derive_oracle_config_pda(&program_id, &oracle.pubkey()).0;
+        let oracle_config = match fetch_account(&mut rpc, oracle_config_key).await? {
+            Some(account) => upgrade::<OracleConfig>(&account.data)?.1,
+            None => {
+                return Err(OracleNodeError::Config(format!("oracle {} is not registered", oracle.pubkey())));
+            }
+        };
+        if oracle_config.vrf_key[..] != *signer.public_key().as_ref() {
+            return Err(OracleNodeError::Config(format!(
+                "oracle {} is registered with VRF key {}, not that of the configured signer",
+                oracle.pubkey(),
+                hex::encode(oracle_config.vrf_key),
+            )));
+        }
+        if !oracle_config.is_active {
+            warn!("Oracle {} is not active, its fulfillments will fail", oracle.pubkey());
+        }
+
+        let flags = match fetch_account(&mut rpc, derive_config_pda(&program_id).0).await? {
+            Some(account) => {
+                let config = upgrade::<CoordinatorConfig>(&account.data)?.1;
+                CoordinatorFlags { has_config: true, cpi_events: config.cpi_events }
+            }
+            None => CoordinatorFlags::default(),
+        };
+
+        let store = Store::open(&config.state_path)?;
+        info!(
+            "Oracle {} on coordinator {}, {} requests in flight",
+            oracle.pubkey(),
+            program_id,
+            store.len(),
+        );
+        Ok(Self { config, rpc, oracle, signer: Arc::from(signer), store, flags })
+    }
+
+    /// Runs until the process is stopped. RPC and submission failures are
+    /// logged and retried; only failures to persist the state end the node.
+    pub async fn run(mut self) -> OracleNodeResult<()> {
+        let (sender, mut requests) = mpsc::unbounded_channel();
+        tokio::spawn(listener::listen(
+            self.rpc.ws_urls(),
+            self.config.program_id,
+            self.config.commitment,
+            sender,
+        ));
+        let mut poll = tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs));
+        let mut health = tokio::time::interval(Duration::from_secs(self.config.health.interval_secs));
+        loop {
+            tokio::select! {
+                Some(request) = requests.recv() => {
+                    if self.store.insert(&request.to_string())? {
+                        info!("Request {} announced", request);
+                    }
+                    self.process_logged(&request).await?;
+                }
+                _ = poll.tick() => self.poll().await?,
+                _ = health.tick() => self.rpc.check_health().await,
+            }
+        }
+    }
+
+    /// Backfills the pending requests the oracle may fulfill, which catches
+    /// those whose events were missed, then processes every request in flight
+    async fn poll(&mut self) -> OracleNodeResult<()> {
+        match self.pending_requests().await {
+            Ok(requests) => {
+                for request in requests {
+                    if self.store.insert(&request.to_string())? {
+                        info!("Request {} found pending", request);
+                    }
+                }
+            }
+            Err(e) => warn!("Cannot fetch pending requests: {}", e),
+        }
+        let requests: Vec<String> = self.store.requests().map(|(request, _)| request.clone()).collect();
+        for request in requests {
+            match Pubkey::from_str(&request) {
+                Ok(request) => self.process_logged(&request).await?,
+                Err(_) => self.store.remove(&request)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Pending requests assigned to the oracle or to no oracle
+    async fn pending_requests(&mut self) -> OracleNodeResult<Vec<Pubkey>> {
+        let (program_id, commitment) = (self.config.program_id, self.config.commitment);
+        let mut requests = Vec::new();
+        for oracle in [self.oracle.pubkey(), Pubkey::default()] {
+            let accounts = self.rpc.call(move |rpc| async move {
+                let config = RpcProgramAccountsConfig {
+                    filters: Some(vec![
+                        request_discriminator_filter(),
+                        status_filter(RequestStatus::Pending),
+                        assigned_oracle_filter(&oracle),
+                    ]),
+                    account_config: RpcAccountInfoConfig {
+                        commitment: Some(commitment),
+                        // Only the addresses are needed
+                        data_slice: Some(UiDataSliceConfig { offset: 0, length: 0 }),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                };
+                rpc.get_program_accounts_with_config(&program_id, config).await
+            }).await?;
+            requests.extend(accounts.into_iter().map(|(address, _)| address));
+        }
+        Ok(requests)
+    }
+
+    /// [Self::process], logging the failures that are retried on the next poll
+    async fn process_logged(&mut self, request: &Pubkey) -> OracleNodeResult<()> {
+        match self.process(request).await {
+            Err(OracleNodeError::Io(e)) => Err(OracleNodeError::Io(e)),
+            Err(OracleNodeError::Json(e)) => Err(OracleNodeError::Json(e)),
+            Err(e) => {
+                warn!("Request {}: {}", request, e);
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// Moves `request` along: forgets it once it is no longer pending, waits
+    /// on a submitted fulfillment, and otherwise fulfills it if it is ready
+    /// and not backing off
+    async fn process(&mut self, request_key: &Pubkey) -> OracleNodeResult<()> {
+        let key = request_key.to_string();
+        let Some(in_flight) = self.store.get(&key).cloned() else {
+            return Ok(());
+        };
+        let request = match fetch_account(&mut self.rpc, *request_key).await? {
+            Some(account) if account.owner == self.config.program_id => upgrade::<RandomnessRequest>(&account.data)?.1,
+            _ => {
+                debug!("Request {} is closed", key);
+                return self.store.remove(&key);
+            }
+        };
+
+        if let Stage::Submitted { signature, last_valid_block_height } = &in_flight.stage {
+            if request.status != RequestStatus::Pending {
+                info!("Request {} fulfilled in {}", key, signature);
+                return self.store.remove(&key);
+            }
+            match self.submission(signature, *last_valid_block_height).await? {
+                Submission::InFlight => return Ok(()),
+                Submission::Failed => {
+                    let attempts = self.store.mark_failed(&key, &self.config.retry)?;
+                    warn!("Fulfillment of request {} in {} failed, attempt {}", key, signature, attempts);
+                }
+                Submission::Expired => {
+                    let attempts = self.store.mark_expired(&key)?;
+                    warn!("Fulfillment of request {} in {} expired, attempt {}", key, signature, attempts);
+                }
+            }
+        }
+
+        let slot = self.rpc.call(|rpc| async move { rpc.get_slot().await }).await?;
+        match fulfill::check(&request, &self.oracle.pubkey(), slot) {
+            Ok(()) => {}
+            Err(Skip::NotReady(ready)) => {
+                debug!("Request {} is ready at slot {}, now {}", key, ready, slot);
+                return Ok(());
+            }
+            Err(skip) => {
+                debug!("Request {} skipped: {:?}", key, skip);
+                return self.store.remove(&key);
+            }
+        }
+        let Some(in_flight) = self.store.get(&key) else {
+            return Ok(());
+        };
+        if in_flight.attempts >= self.config.retry.max_attempts {
+            warn!("Giving up request {} after {} failed fulfillments", key, in_flight.attempts);
+            return self.store.remove(&key);
+        }
+        if !in_flight.is_due(now_ms()) {
+            return Ok(());
+        }
+
+        match self.submit(request_key, &request).await {
+            Ok((signature, last_valid_block_height)) => {
+                info!("Request {} fulfillment sent in {}", key, signature);
+                self.store.mark_submitted(&key, signature.to_string(), last_valid_block_height)
+            }
+            Err(e) => {
+                let attempts = self.store.mark_failed(&key, &self.config.retry)?;
+                warn!(
+                    "Cannot fulfill request {}, attempt {}, retrying in {:?}: {}",
+                    key,
+                    attempts,
+                    self.config.retry.backoff(attempts),
+                    e,
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Where the transaction of `signature` is
+    async fn submission(&mut self, signature: &str, last_valid_block_height: u64) -> OracleNodeResult<Submission> {
+        let signature = Signature::from_str(signature)
+            .map_err(|e| OracleNodeError::Config(format!("invalid signature in the state file: {}", e)))?;
+        let status = self.rpc
+            .call(move |rpc| async move { rpc.get_signature_statuses(&[signature]).await })
+            .await?
+            .value
+            .pop()
+            .flatten();
+        Ok(match status {
+            Some(status) if status.err.is_some() => Submission::Failed,
+            Some(_) => Submission::InFlight,
+            None => {
+                let block_height = self.rpc.call(|rpc| async move { rpc.get_block_height().await }).await?;
+                if block_height > last_valid_block_height {
+                    Submission::Expired
+                } else {
+                    Submission::InFlight
+                }
+            }
+        })
+    }
+
+    /// Proves the seed of `request` and sends its fulfillment, returning the
+    /// transaction's signature and last valid block height. A blockhash that
+    /// expired before the transaction reached the leader is refreshed once.
+    async fn submit(&mut self, request_key: &Pubkey, request: &RandomnessRequest) -> OracleNodeResult<(Signature, u64)> {
+        let signer = self.signer.clone();
+        let seed = request.seed;
+        let proof = tokio::task::spawn_blocking(move || signer.prove(&seed))
+            .await
+            .map_err(|e| OracleNodeError::Io(std::io::Error::other(e)))??;
+
+        let program_id = self.config.program_id;
+        let callback_program = self.config.callback_program.unwrap_or(request.requester);
+        let mut instructions = fulfill::compute_budget_instructions(
+            self.config.compute_unit_limit,
+            self.priority_fee(request_key).await?,
+        );
+        instructions.push(fulfill::fulfill_instruction(
+            &program_id,
+            &self.oracle.pubkey(),
+            request_key,
+            request,
+            &callback_program,
+            proof.to_bytes_legacy(),
+            self.signer.public_key().as_ref().to_vec(),
+            self.flags,
+        ));
+
+        let commitment = self.config.commitment;
+        let mut refreshed = false;
+        loop {
+            let (blockhash, last_valid_block_height) = self.rpc
+                .call(move |rpc| async move { rpc.get_latest_blockhash_with_commitment(commitment).await })
+                .await?;
+            let transaction = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&self.oracle.pubkey()),
+                &[&self.oracle],
+                blockhash,
+            );
+            let sent = self.rpc
+                .call(move |rpc| {
+                    let transaction = transaction.clone();
+                    async move { rpc.send_transaction(&transaction).await }
+                })
+                .await;
+            match sent {
+                Err(e) if !refreshed && e.get_transaction_error() == Some(TransactionError::BlockhashNotFound) => {
+                    debug!("Blockhash {} expired, refreshing", blockhash);
+                    refreshed = true;
+                }
+                sent => return Ok((sent?, last_valid_block_height)),
+            }
+        }
+    }
+
+    /// Compute unit price of a fulfillment of `request_key`
+    async fn priority_fee(&mut self, request_key: &Pubkey) -> OracleNodeResult<u64> {
+        let fee = &self.config.priority_fee;
+        if let Some(micro_lamports) = fee.fixed_micro_lamports {
+            return Ok(micro_lamports);
+        }
+        let (percentile, max) = (fee.percentile, fee.max_micro_lamports);
+        let accounts = [self.config.program_id, *request_key];
+        let fees = self.rpc
+            .call(move |rpc| async move { rpc.get_recent_prioritization_fees(&accounts).await })
+            .await?;
+        Ok(fulfill::priority_fee(fees.into_iter().map(|fee| fee.prioritization_fee).collect(), percentile, max))
+    }
+}
+
+/// The account at `address`, or `None` if it does not exist
+async fn fetch_account(rpc: &mut RpcPool, address: Pubkey) -> OracleNodeResult<Option<Account>> {
+    let response = rpc
+        .call(move |rpc| async move { rpc.get_account_with_commitment(&address, rpc.commitment()).await })
+        .await?;
+    Ok(response.value)
+}
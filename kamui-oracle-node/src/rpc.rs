@@ -0,0 +1,206 @@
+//! RPC endpoints with failover.
+//!
+//! The node talks to the first healthy endpoint in the order of the
+//! configuration. An endpoint is unhealthy when `getHealth` fails or its slot
+//! trails the most advanced endpoint by more than `max_slot_lag`, and stays
+//! so until the next health check. A call that fails on the transport, or on
+//! a node reporting itself behind, marks the endpoint unhealthy and is
+//! retried on the next one; other errors, such as a failed simulation, are
+//! returned as they are.
+
+use {
+    crate::config::{EndpointConfig, HealthConfig},
+    log::{info, warn},
+    solana_client::{
+        client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+        nonblocking::rpc_client::RpcClient,
+        rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+        rpc_request::RpcError,
+    },
+    solana_sdk::commitment_config::CommitmentConfig,
+    std::{future::Future, sync::Arc},
+};
+
+struct Endpoint {
+    client: Arc<RpcClient>,
+    ws_url: String,
+    healthy: bool,
+}
+
+pub struct RpcPool {
+    endpoints: Vec<Endpoint>,
+    /// Index of the endpoint in use
+    current: usize,
+    max_slot_lag: u64,
+}
+
+impl RpcPool {
+    pub fn new(endpoints: &[EndpointConfig], commitment: CommitmentConfig, health: &HealthConfig) -> Self {
+        let endpoints = endpoints
+            .iter()
+            .map(|endpoint| Endpoint {
+                client: Arc::new(RpcClient::new_with_commitment(endpoint.rpc_url.clone(), commitment)),
+                ws_url: endpoint.ws_url.clone(),
+                healthy: true,
+            })
+            .collect();
+        Self { endpoints, current: 0, max_slot_lag: health.max_slot_lag }
+    }
+
+    /// The endpoint in use
+    pub fn client(&self) -> Arc<RpcClient> {
+        self.endpoints[self.current].client.clone()
+    }
+
+    /// Websocket endpoints, in order of preference
+    pub fn ws_urls(&self) -> Vec<String> {
+        self.endpoints.iter().map(|endpoint| endpoint.ws_url.clone()).collect()
+    }
+
+    /// Runs `call` on the endpoint in use, failing over to the next ones in
+    /// turn while it fails on the transport. Returns the last error if it
+    /// fails on every endpoint.
+    pub async fn call<T, F, Fut>(&mut self, call: F) -> ClientResult<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut tries = 0;
+        loop {
+            match call(self.client()).await {
+                Err(e) if is_endpoint_failure(&e) => {
+                    self.mark_unhealthy(&e);
+                    tries += 1;
+                    if tries == self.endpoints.len() {
+                        return Err(e);
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Takes the endpoint in use out of rotation until the next health check
+    /// and moves to the next endpoint, healthy if any is
+    fn mark_unhealthy(&mut self, error: &ClientError) {
+        let endpoint = &mut self.endpoints[self.current];
+        endpoint.healthy = false;
+        warn!("RPC endpoint {} failed: {}", endpoint.client.url(), error);
+        let len = self.endpoints.len();
+        let next = (1..len)
+            .map(|offset| (self.current + offset) % len)
+            .find(|&index| self.endpoints[index].healthy)
+            .unwrap_or((self.current + 1) % len);
+        self.switch_to(next);
+    }
+
+    /// Checks every endpoint and moves to the first healthy one
+    pub async fn check_health(&mut self) {
+        let mut slots = Vec::with_capacity(self.endpoints.len());
+        for endpoint in &self.endpoints {
+            let slot = match endpoint.client.get_health().await {
+                Ok(()) => endpoint.client.get_slot().await.ok(),
+                Err(_) => None,
+            };
+            slots.push(slot);
+        }
+        let healthy = healthy_endpoints(&slots, self.max_slot_lag);
+        for (endpoint, healthy) in self.endpoints.iter_mut().zip(&healthy) {
+            if endpoint.healthy != *healthy {
+                info!("RPC endpoint {} is {}", endpoint.client.url(), if *healthy { "healthy" } else { "unhealthy" });
+            }
+            endpoint.healthy = *healthy;
+        }
+        if let Some(first) = healthy.iter().position(|healthy| *healthy) {
+            self.switch_to(first);
+        }
+    }
+
+    fn switch_to(&mut self, index: usize) {
+        if index != self.current {
+            info!("Switching to RPC endpoint {}", self.endpoints[index].client.url());
+            self.current = index;
+        }
+    }
+}
+
+/// Which endpoints are healthy, given the slots of those that answered:
+/// those no more than `max_slot_lag` slots behind the most advanced
+pub fn healthy_endpoints(slots: &[Option<u64>], max_slot_lag: u64) -> Vec<bool> {
+    let highest = slots.iter().flatten().max().copied().unwrap_or_default();
+    slots
+        .iter()
+        .map(|slot| slot.is_some_and(|slot| slot.saturating_add(max_slot_lag) >= highest))
+        .collect()
+}
+
+/// Whether `error` is the endpoint's fault rather than the request's, so that
+/// another endpoint may succeed
+pub fn is_endpoint_failure(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_client::rpc_request::RpcResponseErrorData,
+        solana_sdk::transaction::TransactionError,
+    };
+
+    #[test]
+    fn test_healthy_endpoints() {
+        assert_eq!(healthy_endpoints(&[Some(100), Some(60), None, Some(50)], 50), [true, true, false, true]);
+        assert_eq!(healthy_endpoints(&[Some(100), Some(49)], 50), [true, false]);
+        assert_eq!(healthy_endpoints(&[None, None], 50), [false, false]);
+    }
+
+    #[test]
+    fn test_is_endpoint_failure() {
+        let io = ClientError::from(std::io::Error::other("connection refused"));
+        assert!(is_endpoint_failure(&io));
+        let unhealthy = ClientError::from(RpcError::RpcResponseError {
+            code: JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+            message: "Node is behind".to_string(),
+            data: RpcResponseErrorData::Empty,
+        });
+        assert!(is_endpoint_failure(&unhealthy));
+        let simulation = ClientError::from(TransactionError::BlockhashNotFound);
+        assert!(!is_endpoint_failure(&simulation));
+    }
+
+    #[tokio::test]
+    async fn test_call_fails_over() {
+        let endpoints = [
+            EndpointConfig { rpc_url: "http://primary:8899".to_string(), ws_url: "ws://primary:8900".to_string() },
+            EndpointConfig { rpc_url: "http://backup:8899".to_string(), ws_url: "ws://backup:8900".to_string() },
+        ];
+        let mut pool = RpcPool::new(&endpoints, CommitmentConfig::confirmed(), &HealthConfig::default());
+        let result = pool
+            .call(|rpc| async move {
+                if rpc.url().contains("primary") {
+                    Err(ClientError::from(std::io::Error::other("connection refused")))
+                } else {
+                    Ok(rpc.url())
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), "http://backup:8899");
+        assert_eq!(pool.client().url(), "http://backup:8899");
+
+        // Errors of the request itself are not retried elsewhere
+        let result: ClientResult<()> = pool
+            .call(|_| async { Err(ClientError::from(TransactionError::AccountInUse)) })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(pool.client().url(), "http://backup:8899");
+        assert_eq!(pool.ws_urls(), ["ws://primary:8900", "ws://backup:8900"]);
+    }
+}
@@ -0,0 +1,181 @@
+//! Requests in flight, persisted so that a restarted node neither loses a
+//! request it saw nor submits a second fulfillment while its first may
+//! still land.
+//!
+//! The state file is JSON, rewritten in full through a temporary file and a
+//! rename on every change, so a crash leaves either the old or the new state.
+
+use {
+    crate::{config::RetryConfig, error::OracleNodeResult},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::BTreeMap,
+        path::{Path, PathBuf},
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// Where the node is with a request
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// Seen and waiting for its confirmations or a submission
+    Pending,
+    /// Fulfillment sent in the transaction of `signature`, valid until
+    /// `last_valid_block_height`
+    Submitted { signature: String, last_valid_block_height: u64 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InFlight {
+    pub stage: Stage,
+    /// Failed submissions so far
+    pub attempts: u32,
+    /// Unix time in milliseconds before which the request is not retried
+    #[serde(default)]
+    pub retry_after_ms: u64,
+}
+
+impl InFlight {
+    fn new() -> Self {
+        Self { stage: Stage::Pending, attempts: 0, retry_after_ms: 0 }
+    }
+
+    /// Whether the request may be submitted at `now_ms`
+    pub fn is_due(&self, now_ms: u64) -> bool {
+        now_ms >= self.retry_after_ms
+    }
+}
+
+/// Milliseconds since the Unix epoch
+pub fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_millis() as u64)
+}
+
+/// The requests in flight, keyed by their base58 account address
+pub struct Store {
+    path: PathBuf,
+    requests: BTreeMap<String, InFlight>,
+}
+
+impl Store {
+    /// Opens the state file at `path`, empty if it does not exist
+    pub fn open(path: &Path) -> OracleNodeResult<Self> {
+        let requests = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { path: path.to_path_buf(), requests })
+    }
+
+    pub fn get(&self, request: &str) -> Option<&InFlight> {
+        self.requests.get(request)
+    }
+
+    pub fn requests(&self) -> impl Iterator<Item = (&String, &InFlight)> {
+        self.requests.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Adds `request` as pending, unless it is already in flight. Returns
+    /// whether it was added.
+    pub fn insert(&mut self, request: &str) -> OracleNodeResult<bool> {
+        if self.requests.contains_key(request) {
+            return Ok(false);
+        }
+        self.requests.insert(request.to_string(), InFlight::new());
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Records the transaction `request` was submitted in
+    pub fn mark_submitted(&mut self, request: &str, signature: String, last_valid_block_height: u64) -> OracleNodeResult<()> {
+        let in_flight = self.requests.entry(request.to_string()).or_insert_with(InFlight::new);
+        in_flight.stage = Stage::Submitted { signature, last_valid_block_height };
+        self.save()
+    }
+
+    /// Puts `request` back to pending after a failed submission, not to be
+    /// retried before the backoff of `retry`. Returns the number of failed
+    /// submissions.
+    pub fn mark_failed(&mut self, request: &str, retry: &RetryConfig) -> OracleNodeResult<u32> {
+        let in_flight = self.requests.entry(request.to_string()).or_insert_with(InFlight::new);
+        in_flight.stage = Stage::Pending;
+        in_flight.attempts += 1;
+        in_flight.retry_after_ms = now_ms() + retry.backoff(in_flight.attempts).as_millis() as u64;
+        let attempts = in_flight.attempts;
+        self.save()?;
+        Ok(attempts)
+    }
+
+    /// Puts `request` back to pending after its transaction expired unseen,
+    /// to be sent again at once with a fresh blockhash. Counts as a failed
+    /// submission, so that a request that never lands is given up.
+    pub fn mark_expired(&mut self, request: &str) -> OracleNodeResult<u32> {
+        let in_flight = self.requests.entry(request.to_string()).or_insert_with(InFlight::new);
+        in_flight.stage = Stage::Pending;
+        in_flight.attempts += 1;
+        let attempts = in_flight.attempts;
+        self.save()?;
+        Ok(attempts)
+    }
+
+    /// Forgets `request`, fulfilled or given up
+    pub fn remove(&mut self, request: &str) -> OracleNodeResult<()> {
+        if self.requests.remove(request).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> OracleNodeResult<()> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&self.requests)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("kamui-oracle-node-store-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state.json");
+        let _ = std::fs::remove_file(&path);
+
+        let mut store = Store::open(&path).unwrap();
+        assert!(store.is_empty());
+        assert!(store.insert("a").unwrap());
+        assert!(!store.insert("a").unwrap());
+        assert!(store.insert("b").unwrap());
+        store.mark_submitted("a", "sig".to_string(), 42).unwrap();
+        let retry = RetryConfig { initial_backoff_ms: 60_000, ..RetryConfig::default() };
+        assert_eq!(store.mark_failed("b", &retry).unwrap(), 1);
+        assert!(!store.get("b").unwrap().is_due(now_ms()));
+        assert_eq!(store.mark_expired("b").unwrap(), 2);
+
+        let mut store = Store::open(&path).unwrap();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get("a").unwrap().stage, Stage::Submitted { signature: "sig".to_string(), last_valid_block_height: 42 });
+        let b = store.get("b").unwrap();
+        assert_eq!((&b.stage, b.attempts), (&Stage::Pending, 2));
+        assert!(!b.is_due(now_ms()));
+
+        store.remove("a").unwrap();
+        assert_eq!(Store::open(&path).unwrap().requests().map(|(key, _)| key.as_str()).collect::<Vec<_>>(), ["b"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
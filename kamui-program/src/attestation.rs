@@ -0,0 +1,79 @@
+//! Attestations of fulfilled requests for consumers on other chains.
+//!
+//! `ExportAttestation` posts an [Attestation] of a fulfilled request to the
+//! bridge program in the coordinator config, using the message format of the
+//! Wormhole core bridge's `post_message`. The coordinator signs as the emitter
+//! at the [EMITTER_SEED] PDA, so receiving chains can check provenance by the
+//! emitter address.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::pubkey::Pubkey,
+};
+
+/// PDA seed of the emitter the coordinator posts messages as
+pub const EMITTER_SEED: &[u8] = b"emitter";
+
+/// Version of the [Attestation] layout
+pub const ATTESTATION_VERSION: u8 = 1;
+
+/// Index of `post_message` in the Wormhole core bridge instruction enum
+const POST_MESSAGE: u8 = 1;
+
+/// Payload describing a fulfilled request. Every field is fixed-size, so the
+/// encoding is the plain concatenation of the fields.
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct Attestation {
+    pub version: u8,
+    /// Address of the request account
+    pub request_id: Pubkey,
+    pub requester: Pubkey,
+    pub seed: [u8; 32],
+    /// The VRF output
+    pub output: [u8; 64],
+    /// `sha256(proof)`
+    pub proof_hash: [u8; 32],
+}
+
+#[derive(BorshSerialize)]
+struct PostMessageData {
+    nonce: u32,
+    payload: Vec<u8>,
+    consistency_level: u8,
+}
+
+/// Instruction data of a bridge `post_message` carrying `attestation`
+pub fn post_message_data(attestation: &Attestation, nonce: u32, consistency_level: u8) -> Vec<u8> {
+    let mut data = vec![POST_MESSAGE];
+    data.extend(borsh::to_vec(&PostMessageData {
+        nonce,
+        payload: borsh::to_vec(attestation).unwrap(),
+        consistency_level,
+    }).unwrap());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_post_message_layout() {
+        let attestation = Attestation {
+            version: ATTESTATION_VERSION,
+            request_id: Pubkey::new_unique(),
+            requester: Pubkey::new_unique(),
+            seed: [1; 32],
+            output: [2; 64],
+            proof_hash: [3; 32],
+        };
+        let payload_len = 1 + 32 + 32 + 32 + 64 + 32;
+        let data = post_message_data(&attestation, 7, 1);
+
+        assert_eq!(data[0], POST_MESSAGE);
+        assert_eq!(data[1..5], 7u32.to_le_bytes());
+        assert_eq!(data[5..9], (payload_len as u32).to_le_bytes());
+        assert_eq!(Attestation::try_from_slice(&data[9..9 + payload_len]).unwrap(), attestation);
+        assert_eq!(data[9 + payload_len..], [1]);
+    }
+}
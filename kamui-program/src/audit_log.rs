@@ -0,0 +1,145 @@
+//! Append-only log of admin actions, for monitoring tools and governance.
+//!
+//! Every admin instruction appends an [AdminAuditEntry] to the log at the
+//! [ADMIN_AUDIT_LOG_SEED] PDA, creating it on first use. The account holds a
+//! borsh-encoded [AdminAuditLog] after the account header, so it can be read
+//! whole with [AdminAuditLog::parse]. Entries are written in place and never
+//! rewritten, so appending costs the same however long the log grows.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    crate::{error::VrfCoordinatorError, loader::{check_data, AccountState}, state::{FeeSchedule, ACCOUNT_HEADER_LEN}},
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// PDA seed of the audit log
+pub const ADMIN_AUDIT_LOG_SEED: &[u8] = b"admin_audit_log";
+
+/// An admin action and its parameters
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub enum AdminAction {
+    InitializeCoordinator {
+        fee_schedule: FeeSchedule,
+        bridge_program: Pubkey,
+    },
+    RegisterOracle {
+        oracle_key: Pubkey,
+        vrf_key: [u8; 32],
+    },
+    DeactivateOracle {
+        oracle_key: Pubkey,
+    },
+    SetZkVerifyingKey {
+        /// `sha256` of the borsh-encoded key
+        key_hash: [u8; 32],
+    },
+    SetAdmin {
+        new_admin: Pubkey,
+    },
+    SetFeeSchedule {
+        fee_schedule: FeeSchedule,
+    },
+    SetConfirmations {
+        min_confirmations: u8,
+        max_confirmations: u8,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    SetOracleRotation {
+        oracles: Vec<Pubkey>,
+    },
+    SetCpiEvents {
+        enabled: bool,
+    },
+    SetMinPriorityFee {
+        min_priority_fee: u64,
+    },
+    SetDrandPublicKey {
+        /// `sha256` of the key
+        key_hash: [u8; 32],
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct AdminAuditEntry {
+    /// Signer that performed the action
+    pub actor: Pubkey,
+    pub slot: u64,
+    pub action: AdminAction,
+}
+
+/// Contents of the audit log account, oldest entry first
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct AdminAuditLog {
+    pub entries: Vec<AdminAuditEntry>,
+}
+
+impl AdminAuditLog {
+    /// Size of an empty log, including the account header
+    pub const HEADER_LEN: usize = ACCOUNT_HEADER_LEN + 4;
+
+    /// Deserializes the raw data of the audit log account
+    pub fn parse(data: &[u8]) -> Result<Self, ProgramError> {
+        check_data::<Self>(data)?;
+        Self::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl AccountState for AdminAuditLog {
+    const DISCRIMINATOR: [u8; 8] = *b"AUDITLOG";
+    const MIN_LEN: usize = Self::HEADER_LEN;
+}
+
+/// Writes `entry` into the last `entry.len()` bytes of `data`, which the
+/// caller has just grown by that much, and counts it in the header
+pub fn append_entry(data: &mut [u8], entry: &[u8]) -> Result<(), ProgramError> {
+    let count_range = ACCOUNT_HEADER_LEN..AdminAuditLog::HEADER_LEN;
+    let count = u32::from_le_bytes(data[count_range.clone()].try_into().unwrap())
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    data[count_range].copy_from_slice(&count.to_le_bytes());
+
+    let start = data.len().checked_sub(entry.len()).ok_or(VrfCoordinatorError::AccountTooSmall)?;
+    data[start..].copy_from_slice(entry);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_entries() {
+        let mut data = AdminAuditLog::DISCRIMINATOR.to_vec();
+        data.push(AdminAuditLog::VERSION);
+        data.extend(borsh::to_vec(&AdminAuditLog::default()).unwrap());
+        assert_eq!(data.len(), AdminAuditLog::HEADER_LEN);
+
+        let entries = vec![
+            AdminAuditEntry {
+                actor: Pubkey::new_unique(),
+                slot: 1,
+                action: AdminAction::RegisterOracle { oracle_key: Pubkey::new_unique(), vrf_key: [1; 32] },
+            },
+            AdminAuditEntry {
+                actor: Pubkey::new_unique(),
+                slot: 2,
+                action: AdminAction::DeactivateOracle { oracle_key: Pubkey::new_unique() },
+            },
+        ];
+        for entry in &entries {
+            let entry = borsh::to_vec(entry).unwrap();
+            data.resize(data.len() + entry.len(), 0);
+            append_entry(&mut data, &entry).unwrap();
+        }
+
+        assert_eq!(AdminAuditLog::parse(&data), Ok(AdminAuditLog { entries }));
+
+        data[0] ^= 1;
+        assert_eq!(AdminAuditLog::parse(&data), Err(VrfCoordinatorError::InvalidDiscriminator.into()));
+    }
+}
@@ -0,0 +1,31 @@
+use {
+    clap::Parser,
+    kamui_program::idl::build_idl,
+    solana_program::pubkey::Pubkey,
+    std::{fs, path::PathBuf},
+};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Generate the coordinator's Anchor IDL", long_about = None)]
+struct Args {
+    /// VRF Coordinator program ID the IDL is for
+    #[arg(short, long)]
+    program_id: Pubkey,
+
+    /// File to write the IDL to, instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let json = serde_json::to_string_pretty(&build_idl(&args.program_id))?;
+    match args.output {
+        Some(path) => {
+            fs::write(&path, json)?;
+            eprintln!("Wrote IDL to {}", path.display());
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
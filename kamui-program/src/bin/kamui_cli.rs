@@ -0,0 +1,53 @@
+use {
+    clap::{Parser, Subcommand},
+    kamui_program::snapshot::take_snapshot,
+    solana_client::rpc_client::RpcClient,
+    solana_program::pubkey::Pubkey,
+    std::{fs, path::PathBuf},
+};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Solana RPC URL
+    #[arg(short, long, default_value = "http://localhost:8899")]
+    url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Export every coordinator account as JSON
+    Snapshot {
+        /// VRF Coordinator program ID
+        #[arg(short, long)]
+        program_id: Pubkey,
+
+        /// File to write the snapshot to, instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let rpc = RpcClient::new(args.url);
+
+    match args.command {
+        Command::Snapshot { program_id, output } => {
+            let snapshot = take_snapshot(&rpc, &program_id)?;
+            let json = serde_json::to_string_pretty(&snapshot.to_json())?;
+            match output {
+                Some(path) => {
+                    fs::write(&path, json)?;
+                    eprintln!("Wrote {} accounts at slot {} to {}", snapshot.accounts.len(), snapshot.slot, path.display());
+                }
+                None => println!("{}", json),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use {
     kamui_program::mock_prover::MockProver,
     clap::Parser,
+    mangekyou::{kamui_vrf::ecvrf::ECVRFPublicKey, signer::RemoteSigner},
 };
 
 #[derive(Parser, Debug)]
@@ -13,12 +14,36 @@ struct Args {
     /// VRF Coordinator program ID
     #[arg(short, long)]
     keypair: String,
+
+    /// URL of a remote signer holding the VRF key, instead of a key in memory
+    #[arg(long, requires = "signer_public_key")]
+    remote_signer: Option<String>,
+
+    /// Hex encoded VRF public key the remote signer must prove under
+    #[arg(long, requires = "remote_signer")]
+    signer_public_key: Option<String>,
+
+    /// Bearer token for the remote signer
+    #[arg(long, requires = "remote_signer")]
+    signer_token: Option<String>,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
-    let mut mock_prover = MockProver::new().await;
+    let mut mock_prover = match (&args.remote_signer, &args.signer_public_key) {
+        (Some(url), Some(public_key)) => {
+            let public_key = ECVRFPublicKey::from_bytes(&hex::decode(public_key)?)?;
+            let mut signer = RemoteSigner::new(url, public_key);
+            if let Some(token) = &args.signer_token {
+                signer = signer.with_token(token);
+            }
+            signer.check()?;
+            println!("Using remote signer: {}", url);
+            MockProver::with_signer(Box::new(signer)).await
+        }
+        _ => MockProver::new().await,
+    };
     
     println!("Mock prover initialized with URL: {}", args.url);
     println!("Using keypair: {}", args.keypair);
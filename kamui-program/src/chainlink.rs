@@ -0,0 +1,80 @@
+//! Chainlink VRF v2-shaped requests and callbacks, for protocols ported from EVM.
+//!
+//! `RequestRandomWords` takes the parameters of `VRFCoordinatorV2.requestRandomWords`.
+//! The key hash selects the oracle, and the subscription account stands in for
+//! the numeric subscription ID. Its requests are fulfilled like any other. The
+//! callback instruction data is [FULFILL_RANDOM_WORDS_SELECTOR] followed by a
+//! borsh-encoded [FulfillRandomWords], mirroring `rawFulfillRandomWords(requestId, randomWords)`.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{hash::hashv, keccak, pubkey::Pubkey},
+};
+
+/// `bytes4(keccak256("rawFulfillRandomWords(uint256,uint256[])"))`
+pub const FULFILL_RANDOM_WORDS_SELECTOR: [u8; 4] = [0x1f, 0xe5, 0x43, 0xe3];
+
+/// Payload of the callback delivered to Chainlink-style consumers
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct FulfillRandomWords {
+    /// Address of the request account
+    pub request_id: Pubkey,
+    /// One 256-bit word per requested word
+    pub random_words: Vec<[u8; 32]>,
+}
+
+impl FulfillRandomWords {
+    /// Callback instruction data for `request_id`, truncating each 64-byte
+    /// word to the 256 bits a Chainlink consumer expects
+    pub fn callback_data(request_id: Pubkey, randomness: &[[u8; 64]]) -> Vec<u8> {
+        let payload = Self {
+            request_id,
+            random_words: randomness
+                .iter()
+                .map(|word| word[..32].try_into().unwrap())
+                .collect(),
+        };
+        let mut data = FULFILL_RANDOM_WORDS_SELECTOR.to_vec();
+        data.extend(borsh::to_vec(&payload).unwrap());
+        data
+    }
+}
+
+/// Key hash that identifies an oracle by its VRF public key, as in Chainlink
+pub fn key_hash(vrf_key: &[u8; 32]) -> [u8; 32] {
+    keccak::hash(vrf_key).to_bytes()
+}
+
+/// Seed of a `RequestRandomWords` request, which takes no seed of its own
+pub fn pre_seed(key_hash: &[u8; 32], requester: &Pubkey, subscription: &Pubkey, nonce: u64) -> [u8; 32] {
+    hashv(&[key_hash, requester.as_ref(), subscription.as_ref(), &nonce.to_le_bytes()]).to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_callback_data_layout() {
+        let request_id = Pubkey::new_unique();
+        let mut word = [0u8; 64];
+        word[..32].copy_from_slice(&[1; 32]);
+        word[32..].copy_from_slice(&[2; 32]);
+
+        let data = FulfillRandomWords::callback_data(request_id, &[word, [3; 64]]);
+        assert_eq!(data[..4], FULFILL_RANDOM_WORDS_SELECTOR);
+        assert_eq!(
+            FulfillRandomWords::try_from_slice(&data[4..]).unwrap(),
+            FulfillRandomWords {
+                request_id,
+                random_words: vec![[1; 32], [3; 32]],
+            }
+        );
+    }
+
+    #[test]
+    fn test_selector() {
+        let hash = keccak::hash(b"rawFulfillRandomWords(uint256,uint256[])");
+        assert_eq!(hash.as_ref()[..4], FULFILL_RANDOM_WORDS_SELECTOR);
+    }
+}
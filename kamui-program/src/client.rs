@@ -0,0 +1,113 @@
+//! `getProgramAccounts` queries over coordinator requests, for indexers and
+//! oracles. Built with the `client` feature.
+//!
+//! The filters match fields at the fixed offsets exported by
+//! [RandomnessRequest], so the RPC node returns only the requests asked for:
+//!
+//! ```ignore
+//! let pending = fetch_pending_requests(&rpc, &program_id, &subscription)?;
+//! ```
+
+// `ClientError` is solana-client's own error type
+#![allow(clippy::result_large_err)]
+
+use {
+    crate::{
+        loader::AccountState,
+        state::{RandomnessRequest, RequestStatus, ACCOUNT_HEADER_LEN},
+    },
+    borsh::BorshDeserialize,
+    solana_account_decoder::UiAccountEncoding,
+    solana_client::{
+        client_error::Result as ClientResult,
+        rpc_client::RpcClient,
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
+    solana_program::pubkey::Pubkey,
+};
+
+/// Matches request accounts at the current layout version
+pub fn request_discriminator_filter() -> RpcFilterType {
+    let mut header = RandomnessRequest::DISCRIMINATOR.to_vec();
+    header.push(RandomnessRequest::VERSION);
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, header))
+}
+
+/// Matches requests of `subscription`, or direct requests for the default key
+pub fn subscription_filter(subscription: &Pubkey) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(RandomnessRequest::SUBSCRIPTION_OFFSET, subscription.to_bytes().to_vec()))
+}
+
+/// Matches requests made by `requester`
+pub fn requester_filter(requester: &Pubkey) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(RandomnessRequest::REQUESTER_OFFSET, requester.to_bytes().to_vec()))
+}
+
+/// Matches requests in `status`
+pub fn status_filter(status: RequestStatus) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(RandomnessRequest::STATUS_OFFSET, vec![status as u8]))
+}
+
+/// Matches requests assigned to `oracle`
+pub fn assigned_oracle_filter(oracle: &Pubkey) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(RandomnessRequest::ASSIGNED_ORACLE_OFFSET, oracle.to_bytes().to_vec()))
+}
+
+/// Fetches the requests of the coordinator `program_id` matching all of `filters`
+pub fn fetch_requests(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    filters: Vec<RpcFilterType>,
+) -> ClientResult<Vec<(Pubkey, RandomnessRequest)>> {
+    let mut all_filters = vec![request_discriminator_filter()];
+    all_filters.extend(filters);
+    let accounts = rpc.get_program_accounts_with_config(program_id, RpcProgramAccountsConfig {
+        filters: Some(all_filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    })?;
+    accounts
+        .into_iter()
+        .map(|(address, account)| {
+            let request = RandomnessRequest::deserialize(&mut &account.data[ACCOUNT_HEADER_LEN..])?;
+            Ok((address, request))
+        })
+        .collect()
+}
+
+/// Fetches the pending requests of `subscription`
+pub fn fetch_pending_requests(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    subscription: &Pubkey,
+) -> ClientResult<Vec<(Pubkey, RandomnessRequest)>> {
+    fetch_requests(rpc, program_id, vec![
+        subscription_filter(subscription),
+        status_filter(RequestStatus::Pending),
+    ])
+}
+
+/// Fetches the pending requests assigned to `oracle`, which it should fulfill
+pub fn fetch_assigned_requests(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    oracle: &Pubkey,
+) -> ClientResult<Vec<(Pubkey, RandomnessRequest)>> {
+    fetch_requests(rpc, program_id, vec![
+        assigned_oracle_filter(oracle),
+        status_filter(RequestStatus::Pending),
+    ])
+}
+
+/// Fetches every request made by `requester`
+pub fn fetch_requester_requests(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    requester: &Pubkey,
+) -> ClientResult<Vec<(Pubkey, RandomnessRequest)>> {
+    fetch_requests(rpc, program_id, vec![requester_filter(requester)])
+}
@@ -0,0 +1,527 @@
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        hash::hashv,
+        instruction::{AccountMeta, Instruction},
+        msg,
+        program::{invoke, invoke_signed},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_instruction,
+        sysvar::{Sysvar, clock::Clock, rent::Rent},
+    },
+    crate::{
+        cpi::{load_subscription, load_vrf_result},
+        error::VrfCoordinatorError,
+        instruction::VrfCoordinatorInstruction,
+        loader::{load_mut, store, AccountState},
+        pda::derive_request_pda,
+    },
+};
+
+/// Discriminator of the round account, "CMTRVEAL" as bytes
+const ROUND_DISCRIMINATOR: [u8; 8] = *b"CMTRVEAL";
+
+/// Slots a player has to reveal once the randomness arrives, after which
+/// anyone may settle the round as forfeited
+pub const REVEAL_WINDOW_SLOTS: u64 = 150;
+
+/// Lifecycle of a commit-reveal round
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy)]
+pub enum RoundStatus {
+    /// The player committed and VRF randomness was requested
+    AwaitingRandomness,
+    /// The VRF output arrived and the player may reveal
+    AwaitingReveal,
+    /// The player revealed and the outcome is final
+    Settled,
+    /// The player did not reveal before the deadline and lost the round
+    Forfeited,
+}
+
+impl RoundStatus {
+    /// Whether the round is over, so that the player may start another
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Settled | Self::Forfeited)
+    }
+}
+
+/// State of a single commit-reveal round
+///
+/// The player first commits to `hash(salt)`. The VRF is requested over a seed derived from
+/// that commitment, so the oracle cannot pick the outcome: it does not know the salt, and the
+/// player is bound to the salt before the VRF output exists. The final outcome is
+/// `hash(salt || vrf_output)`.
+///
+/// The player can still compute the outcome before revealing and decline to reveal a losing
+/// round. A round not revealed by `reveal_deadline` is therefore settled as [RoundStatus::Forfeited]
+/// by anyone, which the game counts as a loss, so withholding the reveal never beats revealing.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RoundState {
+    /// The player of this round
+    pub player: Pubkey,
+    /// The VRF subscription used for this round
+    pub subscription: Pubkey,
+    /// The VRF coordinator program that serves this round
+    pub vrf_coordinator: Pubkey,
+    /// The coordinator request account for this round
+    pub request: Pubkey,
+    /// Commitment to the player's salt
+    pub commitment: [u8; 32],
+    /// The VRF output, once fulfilled
+    pub vrf_output: [u8; 64],
+    /// The combined outcome, once revealed
+    pub outcome: [u8; 32],
+    /// Current status of the round
+    pub status: RoundStatus,
+    /// Slot from which the round may be settled as forfeited, set when the
+    /// VRF output arrives
+    pub reveal_deadline: u64,
+}
+
+impl RoundState {
+    /// Account size, including the header
+    pub const LEN: usize = ROUND_DISCRIMINATOR.len()
+        + 1  // version
+        + 32 // player
+        + 32 // subscription
+        + 32 // vrf_coordinator
+        + 32 // request
+        + 32 // commitment
+        + 64 // vrf_output
+        + 32 // outcome
+        + 1  // status
+        + 8; // reveal_deadline
+}
+
+impl AccountState for RoundState {
+    const DISCRIMINATOR: [u8; 8] = ROUND_DISCRIMINATOR;
+    const MIN_LEN: usize = Self::LEN;
+}
+
+/// Instructions for the commit-reveal example
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum CommitRevealInstruction {
+    /// Commit to a salt and request VRF randomness over the commitment
+    /// Accounts expected:
+    /// 0. `[signer]` Player
+    /// 1. `[writable]` Round state account (PDA)
+    /// 2. `[writable]` VRF request account (PDA)
+    /// 3. `[writable]` VRF subscription account
+    /// 4. `[]` VRF coordinator program
+    /// 5. `[]` System program
+    /// 6. `[]` VRF coordinator config account (PDA)
+    Commit {
+        commitment: [u8; 32],
+    },
+
+    /// Consume randomness callback from VRF
+    /// Accounts expected:
+    /// 0. `[]` VRF result account
+    /// 1. `[]` VRF request account
+    /// 2. `[writable]` Round state account
+    ConsumeRandomness,
+
+    /// Reveal the salt and settle the round, before its reveal deadline
+    /// Accounts expected:
+    /// 0. `[signer]` Player
+    /// 1. `[writable]` Round state account
+    Reveal {
+        salt: [u8; 32],
+    },
+
+    /// Settle a round whose player did not reveal by the deadline as
+    /// forfeited. Anyone may call this.
+    /// Accounts expected:
+    /// 0. `[writable]` Round state account
+    SettleForfeit,
+}
+
+/// Commitment a player publishes for `salt`
+pub fn commitment_for(salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[b"commit", salt]).to_bytes()
+}
+
+/// VRF seed requested for a commitment, binding it to the player
+pub fn vrf_seed(player: &Pubkey, commitment: &[u8; 32]) -> [u8; 32] {
+    hashv(&[b"vrf_seed", player.as_ref(), commitment]).to_bytes()
+}
+
+/// Final outcome of a round from the revealed salt and the VRF output
+pub fn outcome(salt: &[u8; 32], vrf_output: &[u8; 64]) -> [u8; 32] {
+    hashv(&[b"outcome", salt, vrf_output]).to_bytes()
+}
+
+/// The round PDA; the coordinator invokes the callback with the `game_state` seed of the requester
+pub fn round_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"game_state", player.as_ref()], program_id)
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = CommitRevealInstruction::try_from_slice(instruction_data)?;
+
+    match instruction {
+        CommitRevealInstruction::Commit { commitment } => process_commit(program_id, accounts, commitment),
+        CommitRevealInstruction::ConsumeRandomness => process_consume_randomness(program_id, accounts),
+        CommitRevealInstruction::Reveal { salt } => process_reveal(program_id, accounts, salt),
+        CommitRevealInstruction::SettleForfeit => process_settle_forfeit(program_id, accounts),
+    }
+}
+
+fn load_round(program_id: &Pubkey, round: &AccountInfo) -> Result<RoundState, ProgramError> {
+    let state: RoundState = load_mut(round, program_id)?;
+
+    let (expected_round, _bump) = round_address(program_id, &state.player);
+    if expected_round != *round.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(state)
+}
+
+fn process_commit(program_id: &Pubkey, accounts: &[AccountInfo], commitment: [u8; 32]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let player = next_account_info(accounts_iter)?;
+    let round = next_account_info(accounts_iter)?;
+    let request_account = next_account_info(accounts_iter)?;
+    let subscription = next_account_info(accounts_iter)?;
+    let vrf_program = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    let coordinator_config = next_account_info(accounts_iter)?;
+
+    if !player.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !vrf_program.executable || subscription.owner != vrf_program.key {
+        return Err(VrfCoordinatorError::InvalidVrfCoordinator.into());
+    }
+
+    let (expected_round, bump) = round_address(program_id, player.key);
+    if expected_round != *round.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // A player may start a new round once the previous one is settled or forfeited
+    if round.data_len() > 0 {
+        let previous = load_round(program_id, round)?;
+        if !previous.status.is_final() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    // The coordinator derives the request account from the current subscription nonce
+    let nonce = load_subscription(subscription, vrf_program.key)?.nonce;
+    let (request_pda, _bump) = derive_request_pda(vrf_program.key, subscription.key, nonce);
+    if request_pda != *request_account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let state = RoundState {
+        player: *player.key,
+        subscription: *subscription.key,
+        vrf_coordinator: *vrf_program.key,
+        request: request_pda,
+        commitment,
+        vrf_output: [0; 64],
+        outcome: [0; 32],
+        status: RoundStatus::AwaitingRandomness,
+        reveal_deadline: 0,
+    };
+
+    if round.data_len() == 0 {
+        let space = RoundState::LEN;
+        let lamports = Rent::get()?.minimum_balance(space);
+        invoke_signed(
+            &system_instruction::create_account(
+                player.key,
+                round.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[player.clone(), round.clone(), system_program.clone()],
+            &[&[b"game_state", player.key.as_ref(), &[bump]]],
+        )?;
+    }
+    store(round, &state)?;
+
+    let request_ix = VrfCoordinatorInstruction::RequestRandomness {
+        seed: vrf_seed(player.key, &commitment),
+        callback_data: borsh::to_vec(&CommitRevealInstruction::ConsumeRandomness)?,
+        num_words: 1,
+        minimum_confirmations: 1,
+        callback_gas_limit: 200_000,
+        priority_fee: 0,
+    };
+    invoke(
+        &Instruction {
+            program_id: *vrf_program.key,
+            accounts: vec![
+                AccountMeta::new(*player.key, true),
+                AccountMeta::new(request_pda, false),
+                AccountMeta::new(*subscription.key, false),
+                AccountMeta::new_readonly(solana_program::system_program::id(), false),
+                AccountMeta::new_readonly(*coordinator_config.key, false),
+            ],
+            data: borsh::to_vec(&request_ix)?,
+        },
+        &[
+            player.clone(),
+            request_account.clone(),
+            subscription.clone(),
+            system_program.clone(),
+            coordinator_config.clone(),
+        ],
+    )?;
+
+    verbose_msg!("Commit-reveal: player {} committed, awaiting randomness", player.key);
+    Ok(())
+}
+
+fn process_consume_randomness(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vrf_result = next_account_info(accounts_iter)?;
+    let request_account = next_account_info(accounts_iter)?;
+    let round = next_account_info(accounts_iter)?;
+
+    let mut state = load_round(program_id, round)?;
+    if state.status != RoundStatus::AwaitingRandomness {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if *request_account.key != state.request {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Both accounts must come from the coordinator this round was opened with
+    if request_account.owner != &state.vrf_coordinator {
+        return Err(VrfCoordinatorError::InvalidVrfCoordinator.into());
+    }
+    let result = load_vrf_result(vrf_result, &state.vrf_coordinator, request_account.key)?;
+    state.vrf_output = *result.randomness.first().ok_or(ProgramError::InvalidAccountData)?;
+    state.status = RoundStatus::AwaitingReveal;
+    state.reveal_deadline = Clock::get()?.slot.saturating_add(REVEAL_WINDOW_SLOTS);
+    store(round, &state)
+}
+
+/// Settles the round with the player's salt. Only before the reveal deadline:
+/// the player knows the outcome as soon as the VRF output arrives, and a
+/// late reveal would let them hold back a losing round until it is forfeited
+/// anyway.
+fn process_reveal(program_id: &Pubkey, accounts: &[AccountInfo], salt: [u8; 32]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let player = next_account_info(accounts_iter)?;
+    let round = next_account_info(accounts_iter)?;
+
+    let mut state = load_round(program_id, round)?;
+    if !player.is_signer || state.player != *player.key {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if state.status != RoundStatus::AwaitingReveal {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if Clock::get()?.slot >= state.reveal_deadline {
+        msg!("Commit-reveal: the reveal deadline has passed");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if commitment_for(&salt) != state.commitment {
+        msg!("Commit-reveal: salt does not match the commitment");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    state.outcome = outcome(&salt, &state.vrf_output);
+    state.status = RoundStatus::Settled;
+    store(round, &state)?;
+
+    verbose_msg!("Commit-reveal: round settled for player {}", player.key);
+    Ok(())
+}
+
+fn process_settle_forfeit(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let round = next_account_info(accounts_iter)?;
+
+    let mut state = load_round(program_id, round)?;
+    if state.status != RoundStatus::AwaitingReveal {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if Clock::get()?.slot < state.reveal_deadline {
+        msg!("Commit-reveal: the player may still reveal");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    state.status = RoundStatus::Forfeited;
+    store(round, &state)?;
+
+    verbose_msg!("Commit-reveal: round forfeited by player {}", state.player);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{pda::derive_vrf_result_pda, state::{VrfResult, ACCOUNT_HEADER_LEN}, test_harness::TestHarness},
+        solana_sdk::account::Account,
+    };
+
+    #[test]
+    fn test_outcome_depends_on_salt_and_vrf_output() {
+        let player = Pubkey::new_unique();
+        let salt = [7u8; 32];
+        let commitment = commitment_for(&salt);
+
+        // The commitment hides the salt and the seed binds the player
+        assert_ne!(commitment, salt);
+        assert_ne!(vrf_seed(&player, &commitment), vrf_seed(&Pubkey::new_unique(), &commitment));
+
+        let vrf_output = [3u8; 64];
+        let result = outcome(&salt, &vrf_output);
+        assert_eq!(result, outcome(&salt, &vrf_output));
+        assert_ne!(result, outcome(&[8u8; 32], &vrf_output));
+        assert_ne!(result, outcome(&salt, &[4u8; 64]));
+    }
+
+    #[test]
+    fn test_round_state_roundtrip() {
+        let state = RoundState {
+            player: Pubkey::new_unique(),
+            subscription: Pubkey::new_unique(),
+            vrf_coordinator: Pubkey::new_unique(),
+            request: Pubkey::new_unique(),
+            commitment: commitment_for(&[1u8; 32]),
+            vrf_output: [2u8; 64],
+            outcome: [0u8; 32],
+            status: RoundStatus::AwaitingReveal,
+            reveal_deadline: 7,
+        };
+
+        assert_eq!(ACCOUNT_HEADER_LEN + borsh::to_vec(&state).unwrap().len(), RoundState::LEN);
+        let mut data = vec![0u8; RoundState::LEN];
+        data[0..8].copy_from_slice(&ROUND_DISCRIMINATOR);
+        state.serialize(&mut &mut data[ACCOUNT_HEADER_LEN..]).unwrap();
+
+        let deserialized = RoundState::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap();
+        assert_eq!(deserialized.player, state.player);
+        assert_eq!(deserialized.commitment, state.commitment);
+        assert_eq!(deserialized.vrf_output, state.vrf_output);
+        assert_eq!(deserialized.status, RoundStatus::AwaitingReveal);
+        assert_eq!(deserialized.reveal_deadline, 7);
+    }
+
+    /// Round of a player, awaiting the output of a VRF result account of
+    /// the coordinator, which the harness runs, and the commit-reveal program
+    struct RoundFixture {
+        harness: TestHarness,
+        program_id: Pubkey,
+        player: Pubkey,
+        round: Pubkey,
+        vrf_result: Pubkey,
+        request: Pubkey,
+    }
+
+    impl RoundFixture {
+        fn new(salt: &[u8; 32]) -> Self {
+            let coordinator = Pubkey::new_unique();
+            let (program_id, player, request) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+            let mut harness = TestHarness::new(coordinator);
+            harness.add_program(program_id, process_instruction);
+            harness.fund(player, 1_000_000_000);
+
+            let round = round_address(&program_id, &player).0;
+            harness.set_state(round, program_id, &RoundState {
+                player,
+                subscription: Pubkey::new_unique(),
+                vrf_coordinator: coordinator,
+                request,
+                commitment: commitment_for(salt),
+                vrf_output: [0; 64],
+                outcome: [0; 32],
+                status: RoundStatus::AwaitingRandomness,
+                reveal_deadline: 0,
+            });
+            let vrf_result = derive_vrf_result_pda(&coordinator, &request).0;
+            harness.set_state(vrf_result, coordinator, &VrfResult {
+                randomness: vec![[3; 64]],
+                proof: vec![],
+                proof_block: 0,
+                request,
+            });
+            harness.set_account(request, Account { lamports: 1, owner: coordinator, ..Account::default() });
+            Self { harness, program_id, player, round, vrf_result, request }
+        }
+
+        fn instruction(&self, instruction: CommitRevealInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+            Instruction::new_with_borsh(self.program_id, &instruction, accounts)
+        }
+
+        /// Delivers the VRF output at `slot`, as the coordinator's callback would
+        fn consume_randomness(&mut self, slot: u64) {
+            self.harness.warp_to_slot(slot);
+            let consume = self.instruction(CommitRevealInstruction::ConsumeRandomness, vec![
+                AccountMeta::new_readonly(self.vrf_result, false),
+                AccountMeta::new_readonly(self.request, false),
+                AccountMeta::new(self.round, false),
+            ]);
+            self.harness.process(&consume).unwrap();
+        }
+
+        fn reveal(&self, salt: [u8; 32]) -> Instruction {
+            self.instruction(CommitRevealInstruction::Reveal { salt }, vec![
+                AccountMeta::new_readonly(self.player, true),
+                AccountMeta::new(self.round, false),
+            ])
+        }
+
+        fn settle_forfeit(&self) -> Instruction {
+            self.instruction(CommitRevealInstruction::SettleForfeit, vec![AccountMeta::new(self.round, false)])
+        }
+    }
+
+    #[test]
+    fn test_reveal_before_deadline() {
+        let salt = [7; 32];
+        let mut fixture = RoundFixture::new(&salt);
+        fixture.consume_randomness(10);
+        let state: RoundState = fixture.harness.load(&fixture.round);
+        assert_eq!((state.status, state.reveal_deadline), (RoundStatus::AwaitingReveal, 10 + REVEAL_WINDOW_SLOTS));
+
+        let settle_forfeit = fixture.settle_forfeit();
+        assert_eq!(fixture.harness.process(&settle_forfeit), Err(ProgramError::InvalidAccountData));
+        let wrong_salt = fixture.reveal([8; 32]);
+        assert_eq!(fixture.harness.process(&wrong_salt), Err(ProgramError::InvalidArgument));
+
+        fixture.harness.warp_to_slot(state.reveal_deadline - 1);
+        let reveal = fixture.reveal(salt);
+        fixture.harness.process(&reveal).unwrap();
+        let state: RoundState = fixture.harness.load(&fixture.round);
+        assert_eq!((state.status, state.outcome), (RoundStatus::Settled, outcome(&salt, &[3; 64])));
+        fixture.harness.warp_to_slot(state.reveal_deadline);
+        assert_eq!(fixture.harness.process(&settle_forfeit), Err(ProgramError::InvalidAccountData));
+    }
+
+    #[test]
+    fn test_withheld_reveal_is_forfeited() {
+        let salt = [7; 32];
+        let mut fixture = RoundFixture::new(&salt);
+        fixture.consume_randomness(10);
+        let deadline = fixture.harness.load::<RoundState>(&fixture.round).reveal_deadline;
+
+        // A player who saw a losing outcome cannot hold it back and reveal later
+        fixture.harness.warp_to_slot(deadline);
+        let reveal = fixture.reveal(salt);
+        assert_eq!(fixture.harness.process(&reveal), Err(ProgramError::InvalidAccountData));
+
+        // Anyone may settle it, without a signature
+        let settle_forfeit = fixture.settle_forfeit();
+        fixture.harness.process(&settle_forfeit).unwrap();
+        let state: RoundState = fixture.harness.load(&fixture.round);
+        assert_eq!((state.status, state.outcome), (RoundStatus::Forfeited, [0; 32]));
+        assert!(state.status.is_final());
+        assert_eq!(fixture.harness.process(&settle_forfeit), Err(ProgramError::InvalidAccountData));
+    }
+}
@@ -0,0 +1,236 @@
+//! Checked reads of coordinator accounts, for consumer programs.
+//!
+//! Each helper checks that the account is owned by the coordinator and
+//! carries the type's discriminator and current layout version, as
+//! [load] does, that it sits at the address the coordinator derives for it,
+//! and returns the decoded state. Consumers should read coordinator accounts
+//! through these rather than decode them by hand.
+
+use {
+    crate::{
+        error::VrfCoordinatorError,
+        loader::load,
+        pda::{derive_direct_request_pda, derive_legacy_vrf_result_pda, derive_request_pda, derive_vrf_result_pda},
+        state::{RandomnessRequest, Subscription, VrfResult},
+    },
+    solana_program::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// Loads the subscription at `account`. Subscriptions are created at a
+/// keypair address chosen by their owner, so only the owner and data are
+/// checked.
+pub fn load_subscription(account: &AccountInfo, coordinator: &Pubkey) -> Result<Subscription, ProgramError> {
+    load(account, coordinator)
+}
+
+/// Loads the request at `account`, which must be at the address of the
+/// subscription or direct request it describes
+pub fn load_request(account: &AccountInfo, coordinator: &Pubkey) -> Result<RandomnessRequest, ProgramError> {
+    let request: RandomnessRequest = load(account, coordinator)?;
+    let (expected, _) = if request.subscription == Pubkey::default() {
+        derive_direct_request_pda(coordinator, &request.requester, &request.seed)
+    } else {
+        derive_request_pda(coordinator, &request.subscription, request.nonce)
+    };
+    if expected != *account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(request)
+}
+
+/// Loads the VRF result of the request at `request` from `account`, which
+/// must be at the address derived from the request and hold its words
+pub fn load_vrf_result(
+    account: &AccountInfo,
+    coordinator: &Pubkey,
+    request: &Pubkey,
+) -> Result<VrfResult, ProgramError> {
+    let (expected, _) = derive_vrf_result_pda(coordinator, request);
+    if expected != *account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let result: VrfResult = load(account, coordinator)?;
+    if !result.fulfills(request) {
+        return Err(VrfCoordinatorError::InvalidVrfResult.into());
+    }
+    Ok(result)
+}
+
+/// Loads the VRF result of the request at `request` from the result account
+/// its requester's requests shared before results were keyed by request, see
+/// [derive_legacy_vrf_result_pda]. Fails with
+/// [VrfCoordinatorError::InvalidVrfResult] once the account holds the words
+/// of another of the requester's requests.
+pub fn load_legacy_vrf_result(
+    account: &AccountInfo,
+    coordinator: &Pubkey,
+    request: &AccountInfo,
+) -> Result<VrfResult, ProgramError> {
+    let requester = load_request(request, coordinator)?.requester;
+    let (expected, _) = derive_legacy_vrf_result_pda(coordinator, &requester);
+    if expected != *account.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let result: VrfResult = load(account, coordinator)?;
+    if !result.fulfills(request.key) {
+        return Err(VrfCoordinatorError::InvalidVrfResult.into());
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            loader::store,
+            state::{FeeAuction, RequestId, RequestStatus, MINIMUM_CALLBACK_GAS_LIMIT, PROOF_LEN},
+        },
+        solana_program::clock::Epoch,
+    };
+
+    fn request() -> RandomnessRequest {
+        RandomnessRequest {
+            subscription: Pubkey::new_unique(),
+            seed: [1; 32],
+            requester: Pubkey::new_unique(),
+            callback_data: vec![],
+            request_block: 2,
+            status: RequestStatus::Pending,
+            num_words: 1,
+            callback_gas_limit: MINIMUM_CALLBACK_GAS_LIMIT,
+            nonce: 3,
+            commitment: [0; 32],
+            assigned_oracle: Pubkey::default(),
+            not_before_slot: 0,
+            fee_auction: FeeAuction::default(),
+            expiration_slot: 4,
+            confirmations: 0,
+            required_oracle_responses: 1,
+            priority_fee: 0,
+            reserved_fee: 0,
+            reserved_native: 0,
+            id: RequestId::default(),
+        }
+    }
+
+    #[test]
+    fn test_load_request_checks_its_address() {
+        let coordinator = Pubkey::new_unique();
+        let mut request = request();
+
+        let key = derive_request_pda(&coordinator, &request.subscription, request.nonce).0;
+        let (mut lamports, mut data) = (0, vec![0u8; RandomnessRequest::space(0)]);
+        let account = AccountInfo::new(
+            &key, false, true, &mut lamports, &mut data, &coordinator, false, Epoch::default(),
+        );
+        store(&account, &request).unwrap();
+        assert_eq!(load_request(&account, &coordinator).unwrap().nonce, 3);
+        assert_eq!(
+            load_request(&account, &Pubkey::new_unique()).unwrap_err(),
+            ProgramError::IncorrectProgramId
+        );
+
+        // The same request at another nonce's address
+        request.nonce = 4;
+        store(&account, &request).unwrap();
+        assert_eq!(load_request(&account, &coordinator).unwrap_err(), ProgramError::InvalidSeeds);
+
+        // Direct requests are keyed by the requester and seed
+        request.subscription = Pubkey::default();
+        let key = derive_direct_request_pda(&coordinator, &request.requester, &request.seed).0;
+        let (mut lamports, mut data) = (0, vec![0u8; RandomnessRequest::space(0)]);
+        let account = AccountInfo::new(
+            &key, false, true, &mut lamports, &mut data, &coordinator, false, Epoch::default(),
+        );
+        store(&account, &request).unwrap();
+        assert_eq!(load_request(&account, &coordinator).unwrap().requester, request.requester);
+
+        account.data.borrow_mut()[0] ^= 1;
+        assert_eq!(
+            load_request(&account, &coordinator).unwrap_err(),
+            VrfCoordinatorError::InvalidDiscriminator.into()
+        );
+    }
+
+    #[test]
+    fn test_load_vrf_result_checks_its_address() {
+        let coordinator = Pubkey::new_unique();
+        let request = Pubkey::new_unique();
+
+        let key = derive_vrf_result_pda(&coordinator, &request).0;
+        let (mut lamports, mut data) = (0, vec![0u8; VrfResult::space(1)]);
+        let account = AccountInfo::new(
+            &key, false, true, &mut lamports, &mut data, &coordinator, false, Epoch::default(),
+        );
+        let mut result = VrfResult {
+            randomness: vec![[1; 64]],
+            proof: vec![0; PROOF_LEN],
+            proof_block: 2,
+            request,
+        };
+        store(&account, &result).unwrap();
+
+        assert_eq!(load_vrf_result(&account, &coordinator, &request).unwrap().randomness, [[1; 64]]);
+        assert_eq!(
+            load_vrf_result(&account, &coordinator, &Pubkey::new_unique()).unwrap_err(),
+            ProgramError::InvalidSeeds
+        );
+        assert_eq!(
+            load_vrf_result(&account, &Pubkey::new_unique(), &request).unwrap_err(),
+            ProgramError::InvalidSeeds
+        );
+
+        result.request = Pubkey::new_unique();
+        store(&account, &result).unwrap();
+        assert_eq!(
+            load_vrf_result(&account, &coordinator, &request).unwrap_err(),
+            VrfCoordinatorError::InvalidVrfResult.into()
+        );
+    }
+
+    #[test]
+    fn test_load_legacy_vrf_result() {
+        let coordinator = Pubkey::new_unique();
+        let request = request();
+        let request_key = derive_request_pda(&coordinator, &request.subscription, request.nonce).0;
+        let (mut lamports, mut data) = (0, vec![0u8; RandomnessRequest::space(0)]);
+        let request_account = AccountInfo::new(
+            &request_key, false, true, &mut lamports, &mut data, &coordinator, false, Epoch::default(),
+        );
+        store(&request_account, &request).unwrap();
+
+        let key = derive_legacy_vrf_result_pda(&coordinator, &request.requester).0;
+        let (mut lamports, mut data) = (0, vec![0u8; VrfResult::space(1)]);
+        let account = AccountInfo::new(
+            &key, false, true, &mut lamports, &mut data, &coordinator, false, Epoch::default(),
+        );
+        let mut result = VrfResult {
+            randomness: vec![[1; 64]],
+            proof: vec![0; PROOF_LEN],
+            proof_block: 2,
+            request: request_key,
+        };
+        store(&account, &result).unwrap();
+        assert_eq!(load_legacy_vrf_result(&account, &coordinator, &request_account).unwrap().randomness, [[1; 64]]);
+
+        // The request-keyed address is not the legacy one
+        let request_keyed = derive_vrf_result_pda(&coordinator, &request_key).0;
+        let (mut lamports, mut data) = (0, account.data.borrow().to_vec());
+        let moved = AccountInfo::new(
+            &request_keyed, false, true, &mut lamports, &mut data, &coordinator, false, Epoch::default(),
+        );
+        assert_eq!(
+            load_legacy_vrf_result(&moved, &coordinator, &request_account).unwrap_err(),
+            ProgramError::InvalidSeeds
+        );
+
+        // Another of the requester's requests was fulfilled since
+        result.request = Pubkey::new_unique();
+        store(&account, &result).unwrap();
+        assert_eq!(
+            load_legacy_vrf_result(&account, &coordinator, &request_account).unwrap_err(),
+            VrfCoordinatorError::InvalidVrfResult.into()
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! On-chain verification of drand beacon rounds of the evmnet chain.
+//!
+//! Evmnet signs rounds with the `bls-bn254-unchained-on-g1` scheme: the
+//! group key is a BN254 G2 point and a round's signature the G1 point
+//! `sk * H(keccak256(round))`, with `H` the `BN254G1_XMD:KECCAK-256_SVDW_RO_`
+//! suite of RFC 9380. Unlike the BLS12-381 chains, this can be checked with
+//! the `alt_bn128` syscalls the Groth16 verifier uses, see
+//! [crate::groth16]. Points use the same big-endian EIP-196/197 encoding,
+//! which is also the one drand serves evmnet keys and signatures in.
+//!
+//! The hash to the curve needs a few exponentiations in the base field, so
+//! publishing a round costs a few hundred thousand compute units.
+
+use {
+    ark_bn254::Fq,
+    ark_ff::{BigInteger, Field, MontFp, One, PrimeField, Zero},
+    crate::{
+        error::VrfCoordinatorError,
+        groth16::negate_g1,
+        state::{DRAND_PUBLIC_KEY_LEN, DRAND_SIGNATURE_LEN},
+    },
+    solana_program::{
+        alt_bn128::prelude::{alt_bn128_addition, alt_bn128_pairing},
+        keccak::hashv,
+        program_error::ProgramError,
+    },
+};
+
+/// Domain separation tag evmnet hashes rounds to G1 under
+pub const DRAND_DST: &[u8] = b"BLS_SIG_BN254G1_XMD:KECCAK-256_SVDW_RO_NUL_";
+
+/// Generator of G2, in the encoding of [DRAND_PUBLIC_KEY_LEN]
+const G2_GENERATOR: [u8; DRAND_PUBLIC_KEY_LEN] = [
+    0x19, 0x8e, 0x93, 0x93, 0x92, 0x0d, 0x48, 0x3a, 0x72, 0x60, 0xbf, 0xb7, 0x31, 0xfb, 0x5d, 0x25,
+    0xf1, 0xaa, 0x49, 0x33, 0x35, 0xa9, 0xe7, 0x12, 0x97, 0xe4, 0x85, 0xb7, 0xae, 0xf3, 0x12, 0xc2,
+    0x18, 0x00, 0xde, 0xef, 0x12, 0x1f, 0x1e, 0x76, 0x42, 0x6a, 0x00, 0x66, 0x5e, 0x5c, 0x44, 0x79,
+    0x67, 0x43, 0x22, 0xd4, 0xf7, 0x5e, 0xda, 0xdd, 0x46, 0xde, 0xbd, 0x5c, 0xd9, 0x92, 0xf6, 0xed,
+    0x09, 0x06, 0x89, 0xd0, 0x58, 0x5f, 0xf0, 0x75, 0xec, 0x9e, 0x99, 0xad, 0x69, 0x0c, 0x33, 0x95,
+    0xbc, 0x4b, 0x31, 0x33, 0x70, 0xb3, 0x8e, 0xf3, 0x55, 0xac, 0xda, 0xdc, 0xd1, 0x22, 0x97, 0x5b,
+    0x12, 0xc8, 0x5e, 0xa5, 0xdb, 0x8c, 0x6d, 0xeb, 0x4a, 0xab, 0x71, 0x80, 0x8d, 0xcb, 0x40, 0x8f,
+    0xe3, 0xd1, 0xe7, 0x69, 0x0c, 0x43, 0xd3, 0x7b, 0x4c, 0xe6, 0xcc, 0x01, 0x66, 0xfa, 0x7d, 0xaa,
+];
+
+/// Keccak-256 absorbs 136-byte blocks, the `s_in_bytes` of expand_message_xmd
+const KECCAK_BLOCK_LEN: usize = 136;
+
+/// Bytes hashed into each field element, `ceil((254 + 128) / 8)`
+const FIELD_ELEMENT_LEN: usize = 48;
+
+/// Constants of the Shallue-van de Woestijne map of section 6.6.1 of RFC
+/// 9380 for `y^2 = x^3 + 3` with `Z = 1`: `g(Z)`, `-Z / 2`,
+/// `sqrt(-3 g(Z) Z^2)` of sign 0 and `-4 g(Z) / (3 Z^2)`
+const SVDW_C1: Fq = MontFp!("4");
+const SVDW_C2: Fq = MontFp!("10944121435919637611123202872628637544348155578648911831344518947322613104291");
+const SVDW_C3: Fq = MontFp!("8815841940592487685674414971303048083897117035520822607866");
+const SVDW_C4: Fq = MontFp!("7296080957279758407415468581752425029565437052432607887563012631548408736189");
+
+/// Checks that `signature` is the drand group's signature of `round` under
+/// `public_key`: `e(signature, g2) == e(H(keccak256(round)), public_key)`
+pub fn verify_round(
+    public_key: &[u8; DRAND_PUBLIC_KEY_LEN],
+    round: u64,
+    signature: &[u8; DRAND_SIGNATURE_LEN],
+) -> Result<(), ProgramError> {
+    let message = hash_to_g1(&hashv(&[&round.to_be_bytes()]).to_bytes())?;
+
+    let mut pairing_input = Vec::with_capacity(2 * 192);
+    for (g1, g2) in [(&negate_g1(signature), &G2_GENERATOR), (&message, public_key)] {
+        pairing_input.extend_from_slice(g1);
+        pairing_input.extend_from_slice(g2);
+    }
+    let result = alt_bn128_pairing(&pairing_input)
+        .map_err(|_| VrfCoordinatorError::InvalidDrandSignature)?;
+    if result.last() != Some(&1) || result[..31].iter().any(|&b| b != 0) {
+        return Err(VrfCoordinatorError::InvalidDrandSignature.into());
+    }
+    Ok(())
+}
+
+/// Hashes `message` to G1 under [DRAND_DST], encoded as a signature is
+pub fn hash_to_g1(message: &[u8]) -> Result<[u8; DRAND_SIGNATURE_LEN], ProgramError> {
+    let uniform = expand_message_xmd(message);
+    let [p0, p1] = [&uniform[..FIELD_ELEMENT_LEN], &uniform[FIELD_ELEMENT_LEN..]]
+        .map(|bytes| map_to_g1(Fq::from_be_bytes_mod_order(bytes)));
+    // G1 has cofactor 1, so the sum needs no clearing
+    let sum = alt_bn128_addition(&[&p0[..], &p1[..]].concat())
+        .map_err(|_| VrfCoordinatorError::InvalidDrandSignature)?;
+    sum.try_into().map_err(|_| VrfCoordinatorError::InvalidDrandSignature.into())
+}
+
+/// expand_message_xmd of section 5.3.1 of RFC 9380 with Keccak-256, to the
+/// bytes of two field elements
+fn expand_message_xmd(message: &[u8]) -> [u8; 2 * FIELD_ELEMENT_LEN] {
+    let dst_len = [DRAND_DST.len() as u8];
+    let len_in_bytes = (2 * FIELD_ELEMENT_LEN as u16).to_be_bytes();
+    let b0 = hashv(&[&[0; KECCAK_BLOCK_LEN], message, &len_in_bytes, &[0], DRAND_DST, &dst_len]).to_bytes();
+
+    let mut uniform = [0; 2 * FIELD_ELEMENT_LEN];
+    let mut bi = [0; 32];
+    for (i, chunk) in uniform.chunks_mut(32).enumerate() {
+        let mixed: [u8; 32] = std::array::from_fn(|j| b0[j] ^ bi[j]);
+        bi = hashv(&[&mixed, &[i as u8 + 1], DRAND_DST, &dst_len]).to_bytes();
+        chunk.copy_from_slice(&bi);
+    }
+    uniform
+}
+
+/// The Shallue-van de Woestijne map of section 6.6.1 of RFC 9380
+fn map_to_g1(u: Fq) -> [u8; DRAND_SIGNATURE_LEN] {
+    let tv1 = u.square() * SVDW_C1;
+    let tv2 = Fq::one() + tv1;
+    let tv1 = Fq::one() - tv1;
+    let tv3 = (tv1 * tv2).inverse().unwrap_or(Fq::zero());
+    let tv4 = u * tv1 * tv3 * SVDW_C3;
+    let x1 = SVDW_C2 - tv4;
+    let x2 = SVDW_C2 + tv4;
+    let x3 = (tv2.square() * tv3).square() * SVDW_C4 + Fq::one();
+
+    // g(x3) is always square, so one of the candidates lies on the curve
+    let curve = |x: Fq| (x.square() * x + Fq::from(3u8)).sqrt().map(|y| (x, y));
+    let (x, mut y) = curve(x1).or_else(|| curve(x2)).or_else(|| curve(x3))
+        .expect("the SvdW map always finds a point");
+    if sgn0(&u) != sgn0(&y) {
+        y = -y;
+    }
+
+    let mut point = [0; DRAND_SIGNATURE_LEN];
+    point[..32].copy_from_slice(&x.into_bigint().to_bytes_be());
+    point[32..].copy_from_slice(&y.into_bigint().to_bytes_be());
+    point
+}
+
+/// Sign of a field element, its parity
+fn sgn0(value: &Fq) -> bool {
+    value.into_bigint().is_odd()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use {
+        super::*,
+        ark_bn254::{Fr, G1Affine, G2Affine},
+        ark_ec::{AffineRepr, CurveGroup},
+    };
+
+    /// A drand group of a known secret key, signing rounds as evmnet does
+    pub(crate) struct TestBeacon(pub Fr);
+
+    impl TestBeacon {
+        pub(crate) fn public_key(&self) -> [u8; DRAND_PUBLIC_KEY_LEN] {
+            encode_g2(&(G2Affine::generator() * self.0).into_affine())
+        }
+
+        pub(crate) fn sign(&self, round: u64) -> [u8; DRAND_SIGNATURE_LEN] {
+            let message = hash_to_g1(&hashv(&[&round.to_be_bytes()]).to_bytes()).unwrap();
+            encode_g1(&(decode_g1(&message) * self.0).into_affine())
+        }
+    }
+
+    fn encode_g1(point: &G1Affine) -> [u8; DRAND_SIGNATURE_LEN] {
+        [point.x, point.y].map(|coordinate| coordinate.into_bigint().to_bytes_be()).concat().try_into().unwrap()
+    }
+
+    fn decode_g1(point: &[u8; DRAND_SIGNATURE_LEN]) -> G1Affine {
+        let [x, y] = [&point[..32], &point[32..]].map(Fq::from_be_bytes_mod_order);
+        G1Affine::new(x, y)
+    }
+
+    /// Imaginary part first, as in EIP-197
+    fn encode_g2(point: &G2Affine) -> [u8; DRAND_PUBLIC_KEY_LEN] {
+        [point.x.c1, point.x.c0, point.y.c1, point.y.c0]
+            .map(|coordinate| coordinate.into_bigint().to_bytes_be())
+            .concat()
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_g2_generator_encoding() {
+        assert_eq!(encode_g2(&G2Affine::generator()), G2_GENERATOR);
+    }
+
+    #[test]
+    fn test_svdw_constants() {
+        let [z, three] = [Fq::one(), Fq::from(3u8)];
+        let g_z = z.square() * z + three;
+        assert_eq!(SVDW_C1, g_z);
+        assert_eq!(SVDW_C2, -z / Fq::from(2u8));
+        assert_eq!(SVDW_C3.square(), -g_z * three * z.square());
+        assert!(!sgn0(&SVDW_C3));
+        assert_eq!(SVDW_C4, -Fq::from(4u8) * g_z / (three * z.square()));
+    }
+
+    #[test]
+    fn test_hash_to_g1_lands_on_the_curve() {
+        for message in [&b""[..], b"abc", &[7; 200]] {
+            let point = decode_g1(&hash_to_g1(message).unwrap());
+            assert!(point.is_on_curve() && !point.is_zero());
+            assert_ne!(hash_to_g1(message).unwrap(), hash_to_g1(b"other").unwrap());
+        }
+        // Both branches of the sign fix-up
+        for u in [Fq::from(1u8), Fq::from(2u8), -Fq::from(5u8)] {
+            let point = decode_g1(&map_to_g1(u));
+            assert!(point.is_on_curve());
+            assert_eq!(sgn0(&point.y), sgn0(&u));
+        }
+    }
+
+    #[test]
+    fn test_verify_round() {
+        let beacon = TestBeacon(Fr::from(0x5eed_u64));
+        let public_key = beacon.public_key();
+        let signature = beacon.sign(1_000);
+        verify_round(&public_key, 1_000, &signature).unwrap();
+
+        let invalid = Err(VrfCoordinatorError::InvalidDrandSignature.into());
+        assert_eq!(verify_round(&public_key, 1_001, &signature), invalid);
+        assert_eq!(verify_round(&TestBeacon(Fr::from(7u8)).public_key(), 1_000, &signature), invalid);
+        assert_eq!(verify_round(&public_key, 1_000, &beacon.sign(999)), invalid);
+        // Not a curve point
+        let mut garbled = signature;
+        garbled[63] ^= 1;
+        assert_eq!(verify_round(&public_key, 1_000, &garbled), invalid);
+    }
+}
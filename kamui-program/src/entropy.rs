@@ -0,0 +1,97 @@
+//! Mixing caller entropy with chain state into the seed of a request.
+//!
+//! A seed chosen by the caller alone lets the caller pick it, and one taken
+//! from chain state alone lets a block producer influence it. For
+//! `RequestRandomnessWithEntropy` the coordinator combines both into the
+//! request seed with [mix_alpha]:
+//!
+//! ```text
+//! seed = sha256("kamui-entropy-v1" || caller_entropy || request || nonce_le || slot_hash)
+//! ```
+//!
+//! where `request` is the request account address, `nonce_le` the little-endian
+//! subscription nonce the request is opened at, and `slot_hash` the newest
+//! entry of the `SlotHashes` sysvar when the request is made. The slot and hash
+//! used are reported in the `EntropyMixed` event, so a client can recompute
+//! the seed with the same function and check it against the request.
+
+use {
+    crate::error::VrfCoordinatorError,
+    solana_program::{
+        account_info::AccountInfo,
+        hash::hashv,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        sysvar::slot_hashes,
+    },
+};
+
+/// Domain separator of [mix_alpha], versioned so the formula can change
+pub const ENTROPY_DOMAIN: &[u8] = b"kamui-entropy-v1";
+
+/// The request seed for `caller_entropy`, see the module documentation
+pub fn mix_alpha(caller_entropy: &[u8; 32], request: &Pubkey, nonce: u64, slot_hash: &[u8; 32]) -> [u8; 32] {
+    hashv(&[
+        ENTROPY_DOMAIN,
+        caller_entropy,
+        request.as_ref(),
+        &nonce.to_le_bytes(),
+        slot_hash,
+    ])
+    .to_bytes()
+}
+
+/// Newest `(slot, hash)` entry of the `SlotHashes` sysvar account. The sysvar
+/// is too large to deserialize on-chain, so only its first entry is read from
+/// the raw bincode encoding: a u64 entry count, then `(u64 slot, [u8; 32]
+/// hash)` entries, newest first.
+pub fn recent_slot_hash(slot_hashes_account: &AccountInfo) -> Result<(u64, [u8; 32]), ProgramError> {
+    if !slot_hashes::check_id(slot_hashes_account.key) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    parse_recent_slot_hash(&slot_hashes_account.try_borrow_data()?)
+}
+
+fn parse_recent_slot_hash(data: &[u8]) -> Result<(u64, [u8; 32]), ProgramError> {
+    let entries = data.get(..8).ok_or(VrfCoordinatorError::AccountTooSmall)?;
+    if u64::from_le_bytes(entries.try_into().unwrap()) == 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let entry = data.get(8..48).ok_or(VrfCoordinatorError::AccountTooSmall)?;
+    let slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+    Ok((slot, entry[8..].try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_program::{hash::Hash, slot_hashes::SlotHashes},
+    };
+
+    #[test]
+    fn test_parse_matches_sysvar_encoding() {
+        let slot_hashes = SlotHashes::new(&[(41, Hash::new_from_array([2; 32])), (40, Hash::new_from_array([1; 32]))]);
+        // bincode layout of `SlotHashes`, as the runtime stores it
+        let mut data = (slot_hashes.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in slot_hashes.iter() {
+            data.extend(slot.to_le_bytes());
+            data.extend(hash.to_bytes());
+        }
+
+        let (slot, hash) = slot_hashes.first().unwrap();
+        assert_eq!(parse_recent_slot_hash(&data), Ok((*slot, hash.to_bytes())));
+        assert_eq!(parse_recent_slot_hash(&0u64.to_le_bytes()), Err(ProgramError::InvalidAccountData));
+        assert_eq!(parse_recent_slot_hash(&data[..20]), Err(VrfCoordinatorError::AccountTooSmall.into()));
+    }
+
+    #[test]
+    fn test_mix_alpha_binds_every_input() {
+        let request = Pubkey::new_unique();
+        let seed = mix_alpha(&[1; 32], &request, 7, &[2; 32]);
+        assert_ne!(seed, mix_alpha(&[3; 32], &request, 7, &[2; 32]));
+        assert_ne!(seed, mix_alpha(&[1; 32], &Pubkey::new_unique(), 7, &[2; 32]));
+        assert_ne!(seed, mix_alpha(&[1; 32], &request, 8, &[2; 32]));
+        assert_ne!(seed, mix_alpha(&[1; 32], &request, 7, &[3; 32]));
+    }
+}
@@ -53,6 +53,87 @@ pub enum VrfCoordinatorError {
 
     #[error("Invalid request parameters")]
     InvalidRequestParameters,
+
+    #[error("Oracle is not authorized for this request")]
+    UnauthorizedOracle,
+
+    #[error("Program is paused")]
+    ProgramPaused,
+
+    #[error("Invalid account discriminator")]
+    InvalidDiscriminator,
+
+    #[error("Account too small")]
+    AccountTooSmall,
+
+    #[error("Invalid range")]
+    InvalidRange,
+
+    #[error("Randomness not revealed")]
+    RandomnessNotRevealed,
+
+    #[error("Invalid zk proof")]
+    InvalidZkProof,
+
+    #[error("Signer is not the coordinator admin")]
+    InvalidAdmin,
+
+    #[error("Request is time-locked until a later slot")]
+    RequestTimeLocked,
+
+    #[error("Schedule is not due yet")]
+    ScheduleNotDue,
+
+    #[error("Alpha string is too long")]
+    AlphaTooLong,
+
+    #[error("Request has not expired yet")]
+    RequestNotExpired,
+
+    #[error("Invalid consumer")]
+    InvalidConsumer,
+
+    #[error("Subscription has too many consumers")]
+    TooManyConsumers,
+
+    #[error("Oracle rotation has too many oracles")]
+    TooManyOracles,
+
+    #[error("Oracle already responded to this request")]
+    DuplicateOracleResponse,
+
+    #[error("Account must be migrated with MigrateAccount")]
+    AccountNotMigrated,
+
+    #[error("Token account is not the subscription vault's")]
+    InvalidTokenAccount,
+
+    #[error("Events are emitted by CPI but the event accounts are missing")]
+    MissingEventAccounts,
+
+    #[error("Priority fee is below the coordinator's minimum")]
+    PriorityFeeTooLow,
+
+    #[error("Subscription has reached its request rate limit")]
+    RateLimited,
+
+    #[error("Weights are empty, all zero, or sum past u64::MAX")]
+    InvalidWeights,
+
+    #[error("VRF result account holds the words of another request")]
+    InvalidVrfResult,
+
+    #[error("Beacon round is not after the last round of the stream")]
+    StaleBeaconRound,
+
+    #[error("Drand round signature does not verify under the configured group key")]
+    InvalidDrandSignature,
+
+    #[error("No drand group key is configured")]
+    DrandPublicKeyNotSet,
+
+    #[error("Account is not owned by the VRF coordinator the consumer is bound to")]
+    InvalidVrfCoordinator,
 }
 
 impl From<VrfCoordinatorError> for ProgramError {
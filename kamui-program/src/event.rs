@@ -1,42 +1,415 @@
+//! Events the coordinator emits, and the two ways it emits them.
+//!
+//! By default an event is logged base64-encoded after `VRF_EVENT:`. Logs may
+//! be truncated, so a coordinator whose config sets `cpi_events` emits them
+//! the way Anchor's `emit_cpi` does instead: it invokes itself, signed by the
+//! [EVENT_AUTHORITY_SEED] PDA, with [EVENT_IX_TAG], the event's
+//! [event_discriminator] and its borsh-encoded fields as instruction data.
+//! Indexers then read events from the inner instructions of the transaction,
+//! which are never truncated. Instructions emit this way when they end with
+//! the event authority and the coordinator program, see [EventEmitter::split].
+
 use {
     borsh::{BorshDeserialize, BorshSerialize},
-    solana_program::{msg, pubkey::Pubkey},
+    crate::{error::VrfCoordinatorError, state::{CoordinatorConfig, RequestId}},
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        hash::hash,
+        instruction::{AccountMeta, Instruction},
+        msg,
+        program::invoke_signed,
+        pubkey::Pubkey,
+    },
     base64::Engine,
 };
 
+/// PDA seed of the authority event self-invocations are signed by, the seed
+/// Anchor's `emit_cpi` uses
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// Leading bytes of the instruction data of an event self-invocation,
+/// Anchor's `EVENT_IX_TAG_LE`. No coordinator instruction starts with them.
+pub const EVENT_IX_TAG: [u8; 8] = 0x1d9acb512ea545e4u64.to_le_bytes();
+
+/// Names of the [VrfEvent] variants, in declaration order
+pub const EVENT_NAMES: &[&str] = &[
+    "RandomnessRequested",
+    "RandomnessFulfilled",
+    "SubscriptionCreated",
+    "SubscriptionFunded",
+    "RequestCancelled",
+    "OracleRegistered",
+    "OracleDeactivated",
+    "RequestExpired",
+    "CallbackFailed",
+    "SubscriptionWithdrawn",
+    "ConfigUpdated",
+    "AuditDiscrepancy",
+    "DrandRoundPublished",
+    "FeeCharged",
+    "EntropyMixed",
+    "ConsumerAdded",
+    "ConsumerRemoved",
+    "SubscriptionOwnershipTransferRequested",
+    "SubscriptionOwnershipTransferred",
+    "OracleResponseSubmitted",
+    "AccountMigrated",
+    "RateLimitsUpdated",
+    "OracleFeesClaimed",
+];
+
+/// Discriminator of the event called `name`, the first 8 bytes of
+/// `sha256("event:<name>")` as Anchor derives it. It does not change as
+/// variants are added to [VrfEvent].
+pub fn event_discriminator(name: &str) -> [u8; 8] {
+    hash(format!("event:{}", name).as_bytes()).to_bytes()[..8].try_into().unwrap()
+}
+
+/// Address and bump of the coordinator's event authority
+pub fn event_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], program_id)
+}
+
+/// Events emitted by the coordinator. Every event carries the slot and Unix
+/// timestamp of the clock at the time it was emitted. Events about a request
+/// carry its account as `request_id` and its [RequestId] as `id`.
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
 pub enum VrfEvent {
     RandomnessRequested {
         request_id: Pubkey,
+        id: RequestId,
         requester: Pubkey,
         subscription: Pubkey,
         seed: [u8; 32],
+        /// Tip escrowed for the fulfilling oracle
+        priority_fee: u64,
+        slot: u64,
+        unix_timestamp: i64,
     },
     RandomnessFulfilled {
         request_id: Pubkey,
+        id: RequestId,
         requester: Pubkey,
         randomness: [u8; 64],
+        slot: u64,
+        unix_timestamp: i64,
     },
     SubscriptionCreated {
         subscription: Pubkey,
         owner: Pubkey,
         min_balance: u64,
+        slot: u64,
+        unix_timestamp: i64,
     },
     SubscriptionFunded {
         subscription: Pubkey,
         funder: Pubkey,
         amount: u64,
+        slot: u64,
+        unix_timestamp: i64,
     },
     RequestCancelled {
         request_id: Pubkey,
+        id: RequestId,
+        subscription: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    OracleRegistered {
+        oracle_config: Pubkey,
+        oracle_key: Pubkey,
+        vrf_key: [u8; 32],
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    OracleDeactivated {
+        oracle_config: Pubkey,
+        oracle_key: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    /// A pending request passed its deadline without being fulfilled
+    RequestExpired {
+        request_id: Pubkey,
+        id: RequestId,
+        subscription: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    /// The consumer callback for a request was skipped, for lack of the
+    /// compute units it asked for. A callback that fails fails the fulfillment.
+    CallbackFailed {
+        request_id: Pubkey,
+        id: RequestId,
+        callback_program: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    SubscriptionWithdrawn {
+        subscription: Pubkey,
+        owner: Pubkey,
+        amount: u64,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    ConfigUpdated {
+        subscription: Pubkey,
+        backstop_timeout_slots: u64,
+        backstop_bonus: u64,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    /// A subscription audit found the balance or nonce inconsistent with its history
+    AuditDiscrepancy {
+        subscription: Pubkey,
+        balance: u64,
+        /// Balance implied by the history counters, or `None` if they underflow
+        expected_balance: Option<u64>,
+        /// Total amount ever funded
+        total_funded: u64,
+        /// Tokens and lamports the subscription's token account and vault
+        /// hold above its rent-exempt reserve, balances and unclaimed fees
+        held: u64,
+        nonce: u64,
+        audited_nonce: u64,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    /// An oracle relayed a drand beacon round
+    DrandRoundPublished {
+        round: u64,
+        randomness: [u8; 32],
+        oracle: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    /// A subscription paid for a fulfilled request
+    FeeCharged {
+        request_id: Pubkey,
+        id: RequestId,
+        subscription: Pubkey,
+        /// The quoted fee less the rebate
+        fee: u64,
+        callback_units_used: u64,
+        /// Rebate for callback units left unused
+        rebate: u64,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    /// Emitted when a request seed is mixed from caller entropy, see [crate::entropy]
+    EntropyMixed {
+        request_id: Pubkey,
+        id: RequestId,
+        /// Slot of the slot hash that was mixed in
+        slot_hash_slot: u64,
+        slot_hash: [u8; 32],
+        seed: [u8; 32],
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    ConsumerAdded {
+        subscription: Pubkey,
+        consumer: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    ConsumerRemoved {
+        subscription: Pubkey,
+        consumer: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    SubscriptionOwnershipTransferRequested {
+        subscription: Pubkey,
+        owner: Pubkey,
+        new_owner: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    SubscriptionOwnershipTransferred {
+        subscription: Pubkey,
+        previous_owner: Pubkey,
+        new_owner: Pubkey,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    OracleResponseSubmitted {
+        request_id: Pubkey,
+        id: RequestId,
+        oracle: Pubkey,
+        responses: u8,
+        required: u8,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    AccountMigrated {
+        account: Pubkey,
+        from_version: u8,
+        to_version: u8,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    RateLimitsUpdated {
         subscription: Pubkey,
+        max_requests_per_slot: u32,
+        max_pending_requests: u32,
+        slot: u64,
+        unix_timestamp: i64,
+    },
+    /// An oracle was paid accrued fees from a subscription's vault
+    OracleFeesClaimed {
+        oracle: Pubkey,
+        subscription: Pubkey,
+        amount: u64,
+        slot: u64,
+        unix_timestamp: i64,
     },
 }
 
 impl VrfEvent {
-    pub fn emit(&self) {
+    /// Emits the event the way `events` does
+    pub fn emit(&self, events: &EventEmitter) -> ProgramResult {
+        events.emit(self)
+    }
+
+    /// Logs the event after `VRF_EVENT:`
+    pub fn log(&self) {
         let data = borsh::to_vec(self).unwrap();
         let b64 = base64::engine::general_purpose::STANDARD.encode(&data);
         msg!("VRF_EVENT:{}", b64);
     }
+
+    /// Decodes a log line written by [Self::log], or returns `None` if it is
+    /// not one. Transaction logs carry it after `Program log: `.
+    pub fn from_log(line: &str) -> Option<Self> {
+        let line = line.strip_prefix("Program log: ").unwrap_or(line);
+        let b64 = line.strip_prefix("VRF_EVENT:")?.trim();
+        let data = base64::engine::general_purpose::STANDARD.decode(b64).ok()?;
+        Self::try_from_slice(&data).ok()
+    }
+
+    /// Instruction data of the self-invocation that emits the event
+    pub fn cpi_data(&self) -> Vec<u8> {
+        let data = borsh::to_vec(self).unwrap();
+        let mut cpi_data = EVENT_IX_TAG.to_vec();
+        cpi_data.extend(event_discriminator(EVENT_NAMES[data[0] as usize]));
+        cpi_data.extend(&data[1..]);
+        cpi_data
+    }
+
+    /// Decodes the instruction data of an event self-invocation, or returns
+    /// `None` if it is not one
+    pub fn from_cpi_data(data: &[u8]) -> Option<Self> {
+        let data = data.strip_prefix(&EVENT_IX_TAG[..])?;
+        let (discriminator, fields) = (data.get(..8)?, &data[8..]);
+        let variant = EVENT_NAMES.iter().position(|name| event_discriminator(name)[..] == *discriminator)?;
+        let mut event = vec![variant as u8];
+        event.extend(fields);
+        Self::try_from_slice(&event).ok()
+    }
+}
+
+/// Emits the events of an instruction, by self-invocation if it carries the
+/// event accounts and to the log otherwise
+pub struct EventEmitter<'a, 'b> {
+    /// Event authority, its bump and the coordinator program account
+    cpi: Option<(&'b AccountInfo<'a>, u8, &'b AccountInfo<'a>)>,
+}
+
+impl<'a, 'b> EventEmitter<'a, 'b> {
+    /// Splits the event authority and the coordinator program off the end of
+    /// an instruction's `accounts`, returning the accounts before them
+    pub fn split(program_id: &Pubkey, accounts: &'b [AccountInfo<'a>]) -> (&'b [AccountInfo<'a>], Self) {
+        if let [rest @ .., authority, program] = accounts {
+            if program.key == program_id {
+                let (expected_authority, bump) = event_authority_address(program_id);
+                if *authority.key == expected_authority {
+                    return (rest, Self { cpi: Some((authority, bump, program)) });
+                }
+            }
+        }
+        (accounts, Self { cpi: None })
+    }
+
+    /// Whether events are emitted by self-invocation
+    pub fn is_cpi(&self) -> bool {
+        self.cpi.is_some()
+    }
+
+    /// Checks that the instruction carries the event accounts if `config`
+    /// asks for events by self-invocation
+    pub fn check_config(&self, config: &CoordinatorConfig) -> ProgramResult {
+        if config.cpi_events && !self.is_cpi() {
+            msg!("VRF Coordinator: Error - Events are emitted by CPI, pass the event authority and the program");
+            return Err(VrfCoordinatorError::MissingEventAccounts.into());
+        }
+        Ok(())
+    }
+
+    pub fn emit(&self, event: &VrfEvent) -> ProgramResult {
+        let Some((authority, bump, program)) = self.cpi else {
+            event.log();
+            return Ok(());
+        };
+        invoke_signed(
+            &Instruction {
+                program_id: *program.key,
+                accounts: vec![AccountMeta::new_readonly(*authority.key, true)],
+                data: event.cpi_data(),
+            },
+            &[authority.clone(), program.clone()],
+            &[&[EVENT_AUTHORITY_SEED, &[bump]]],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpi_data_round_trip() {
+        // Anchor writes the leading bytes of `sha256("anchor:event")` little-endian
+        let mut tag = hash(b"anchor:event").to_bytes()[..8].to_vec();
+        tag.reverse();
+        assert_eq!(EVENT_IX_TAG[..], tag);
+
+        let event = VrfEvent::ConsumerAdded {
+            subscription: Pubkey::new_unique(),
+            consumer: Pubkey::new_unique(),
+            slot: 3,
+            unix_timestamp: 4,
+        };
+        let data = event.cpi_data();
+        assert_eq!(data[8..16], event_discriminator("ConsumerAdded"));
+        assert_eq!(data[16..], borsh::to_vec(&event).unwrap()[1..]);
+        assert_eq!(
+            borsh::to_vec(&VrfEvent::from_cpi_data(&data).unwrap()).unwrap(),
+            borsh::to_vec(&event).unwrap()
+        );
+
+        assert!(VrfEvent::from_cpi_data(&data[8..]).is_none());
+        assert!(VrfEvent::from_cpi_data(&data[..20]).is_none());
+    }
+
+    #[test]
+    fn test_from_log() {
+        let event = VrfEvent::ConsumerRemoved {
+            subscription: Pubkey::new_unique(),
+            consumer: Pubkey::new_unique(),
+            slot: 5,
+            unix_timestamp: 6,
+        };
+        let b64 = base64::engine::general_purpose::STANDARD.encode(borsh::to_vec(&event).unwrap());
+        for line in [format!("VRF_EVENT:{}", b64), format!("Program log: VRF_EVENT:{}", b64)] {
+            assert_eq!(
+                borsh::to_vec(&VrfEvent::from_log(&line).unwrap()).unwrap(),
+                borsh::to_vec(&event).unwrap()
+            );
+        }
+        assert!(VrfEvent::from_log(&format!("Program log: {}", b64)).is_none());
+        assert!(VrfEvent::from_log("Program log: VRF_EVENT:not base64").is_none());
+    }
 } 
\ No newline at end of file
@@ -3,33 +3,24 @@ use {
     solana_program::{
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
-        msg,
         program::{invoke, invoke_signed},
         pubkey::Pubkey,
         system_instruction,
-        system_program,
         program_error::ProgramError,
         sysvar::{Sysvar, rent::Rent},
     },
-    std::str::FromStr,
     crate::{
+        cpi::{load_subscription, load_vrf_result},
+        error::VrfCoordinatorError,
         instruction::VrfCoordinatorInstruction,
-        state::{VrfResult, Subscription},
+        loader::{load_mut, store, AccountState},
+        pda::derive_request_pda,
+        randomness::derive_in_range,
     },
 };
 
 #[cfg(feature = "mock")]
-use {
-    solana_program::{
-        account_info::AccountInfo,
-        entrypoint::ProgramResult,
-        msg,
-        program::invoke,
-        pubkey::Pubkey,
-        system_program,
-    },
-    rand,
-};
+use rand;
 
 /// State for the game
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -38,12 +29,30 @@ pub struct GameState {
     pub owner: Pubkey,
     /// The VRF subscription used by this game
     pub subscription: Pubkey,
+    /// The VRF coordinator program that serves this game
+    pub vrf_coordinator: Pubkey,
     /// The current random number (1-100)
     pub current_number: u8,
     /// Whether we're waiting for randomness
     pub is_pending: bool,
 }
 
+impl GameState {
+    /// Account size, including the header
+    pub const LEN: usize = 8 // discriminator
+        + 1                  // version
+        + 32                 // owner
+        + 32                 // subscription
+        + 32                 // vrf_coordinator
+        + 1                  // current_number
+        + 1;                 // is_pending
+}
+
+impl AccountState for GameState {
+    const DISCRIMINATOR: [u8; 8] = *b"GAMESTAT";
+    const MIN_LEN: usize = Self::LEN;
+}
+
 /// Instructions for the game
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum GameInstruction {
@@ -52,7 +61,9 @@ pub enum GameInstruction {
     /// 0. `[signer]` Game owner
     /// 1. `[writable]` Game state account (PDA)
     /// 2. `[]` VRF subscription account
-    /// 3. `[]` System program
+    /// 3. `[signer]` Payer for account creation
+    /// 4. `[]` System program
+    /// 5. `[]` VRF coordinator program
     Initialize,
 
     /// Request a new random number
@@ -63,6 +74,7 @@ pub enum GameInstruction {
     /// 3. `[]` VRF subscription account
     /// 4. `[]` VRF coordinator program
     /// 5. `[]` System program
+    /// 6. `[]` VRF coordinator config account (PDA)
     RequestNewNumber,
 
     /// Consume randomness callback from VRF
@@ -94,6 +106,7 @@ fn process_initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramR
     let subscription = next_account_info(accounts_iter)?;
     let payer = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
+    let vrf_coordinator = next_account_info(accounts_iter)?;
 
     if !owner.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
@@ -111,15 +124,21 @@ fn process_initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramR
         return Err(ProgramError::InvalidSeeds);
     }
 
+    // The subscription must belong to the coordinator this game is bound to
+    if !vrf_coordinator.executable || subscription.owner != vrf_coordinator.key {
+        return Err(VrfCoordinatorError::InvalidVrfCoordinator.into());
+    }
+
     let state = GameState {
         owner: *owner.key,
         subscription: *subscription.key,
+        vrf_coordinator: *vrf_coordinator.key,
         current_number: 0,
         is_pending: false,
     };
 
     // Create game state account
-    let space = 8 + borsh::to_vec(&state)?.len();
+    let space = GameState::LEN;
     let rent = Rent::get()?;
     let lamports = rent.minimum_balance(space);
 
@@ -135,10 +154,7 @@ fn process_initialize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramR
         &[&[b"game_state", owner.key.as_ref(), &[bump]]],
     )?;
 
-    // Write discriminator and state
-    let mut data = game_state.try_borrow_mut_data()?;
-    data[0..8].copy_from_slice(&[71, 65, 77, 69, 83, 84, 65, 84]); // "GAMESTAT" as bytes
-    state.serialize(&mut &mut data[8..])?;
+    store(game_state, &state)?;
 
     Ok(())
 }
@@ -151,16 +167,12 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
     let subscription = next_account_info(accounts_iter)?;
     let vrf_program = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
+    let coordinator_config = next_account_info(accounts_iter)?;
 
     if !owner.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Verify game state account owner
-    if game_state.owner != program_id {
-        return Err(ProgramError::IllegalOwner);
-    }
-
     // Verify game state PDA
     let (expected_game_state, _bump) = Pubkey::find_program_address(
         &[b"game_state", owner.key.as_ref()],
@@ -170,34 +182,22 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
         return Err(ProgramError::InvalidSeeds);
     }
 
-    // Verify discriminator
-    let data = game_state.data.borrow();
-    if data[0..8] != [71, 65, 77, 69, 83, 84, 65, 84] {  // "GAMESTAT" as bytes
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    let mut state = GameState::try_from_slice(&data[8..])?;  // Skip discriminator
+    let mut state: GameState = load_mut(game_state, program_id)?;
     if state.owner != *owner.key {
         return Err(ProgramError::InvalidAccountData);
     }
     if state.is_pending {
         return Err(ProgramError::InvalidAccountData);
     }
+    if state.vrf_coordinator != *vrf_program.key {
+        return Err(VrfCoordinatorError::InvalidVrfCoordinator.into());
+    }
 
-    // Read the subscription account to get the current nonce
-    let subscription_data = subscription.try_borrow_data()?;
-    let subscription_state = Subscription::try_from_slice(&subscription_data[8..])?;
-    let next_nonce = subscription_state.nonce.checked_add(1).unwrap();
+    // Read the subscription account to get the nonce the coordinator will use
+    let nonce = load_subscription(subscription, vrf_program.key)?.nonce;
 
     // Derive the request account PDA
-    let (request_pda, _bump) = Pubkey::find_program_address(
-        &[
-            b"request",
-            subscription.key.as_ref(),
-            &next_nonce.to_le_bytes(),
-        ],
-        vrf_program.key
-    );
+    let (request_pda, _bump) = derive_request_pda(vrf_program.key, subscription.key, nonce);
 
     if request_pda != *request_account.key {
         return Err(ProgramError::InvalidSeeds);
@@ -211,12 +211,10 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
         num_words: 1,
         minimum_confirmations: 1,
         callback_gas_limit: 200_000,
+        priority_fee: 0,
     };
 
-    // Add discriminator bytes for VrfCoordinatorInstruction
-    let mut request_ix_data = vec![0u8; 8];
-    request_ix_data[0..8].copy_from_slice(b"VRFREQST");
-    request_ix_data.extend(borsh::to_vec(&request_ix)?);
+    let request_ix_data = borsh::to_vec(&request_ix)?;
 
     invoke(
         &solana_program::instruction::Instruction {
@@ -224,8 +222,9 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
             accounts: vec![
                 solana_program::instruction::AccountMeta::new(*owner.key, true),
                 solana_program::instruction::AccountMeta::new(request_pda, false),
-                solana_program::instruction::AccountMeta::new_readonly(*subscription.key, false),
+                solana_program::instruction::AccountMeta::new(*subscription.key, false),
                 solana_program::instruction::AccountMeta::new_readonly(solana_program::system_program::id(), false),
+                solana_program::instruction::AccountMeta::new_readonly(*coordinator_config.key, false),
             ],
             data: request_ix_data,
         },
@@ -234,13 +233,13 @@ fn process_request_number(program_id: &Pubkey, accounts: &[AccountInfo]) -> Prog
             request_account.clone(),
             subscription.clone(),
             system_program.clone(),
+            coordinator_config.clone(),
         ],
     )?;
 
     // Update and write back game state
     state.is_pending = true;
-    let mut data = game_state.try_borrow_mut_data()?;
-    state.serialize(&mut &mut data[8..])?;
+    store(game_state, &state)?;
 
     Ok(())
 }
@@ -254,19 +253,8 @@ pub fn process_consume_randomness(
     let request_account = next_account_info(accounts_iter)?;
     let game_state = next_account_info(accounts_iter)?;
 
-    // Verify game state account owner
-    if game_state.owner != program_id {
-        return Err(ProgramError::IllegalOwner);
-    }
-
-    // Verify discriminator
-    let data = game_state.data.borrow();
-    if data[0..8] != [71, 65, 77, 69, 83, 84, 65, 84] {  // "GAMESTAT" as bytes
-        return Err(ProgramError::InvalidAccountData);
-    }
-
     // Deserialize the game state first to get the owner
-    let state = GameState::try_from_slice(&data[8..])?;
+    let mut state: GameState = load_mut(game_state, program_id)?;
 
     // Verify game state PDA
     let (expected_game_state, _bump) = Pubkey::find_program_address(
@@ -278,47 +266,25 @@ pub fn process_consume_randomness(
     }
 
     // Get VRF coordinator program ID
-    let vrf_coordinator_id = Pubkey::from_str("29wLw7e3ZsxrMBorrm37abTyzX9wUesxy1tiBmwDqrso").unwrap();
-
-    // Verify VRF result account owner
-    if vrf_result.owner != &vrf_coordinator_id {
-        return Err(ProgramError::IllegalOwner);
-    }
+    let vrf_coordinator_id = state.vrf_coordinator;
 
     // Verify request account owner
     if request_account.owner != &vrf_coordinator_id {
-        return Err(ProgramError::IllegalOwner);
-    }
-
-    // Verify VRF result PDA
-    let (expected_vrf_result, _) = Pubkey::find_program_address(
-        &[b"vrf_result", request_account.key.as_ref()],
-        &vrf_coordinator_id
-    );
-    if expected_vrf_result != *vrf_result.key {
-        return Err(ProgramError::InvalidSeeds);
+        return Err(VrfCoordinatorError::InvalidVrfCoordinator.into());
     }
 
-    // Deserialize the VRF result
-    let vrf_result_data = VrfResult::try_from_slice(&vrf_result.data.borrow()[8..])?;
+    // Load the VRF result, which the coordinator derives from the request
+    let vrf_result_data = load_vrf_result(vrf_result, &vrf_coordinator_id, request_account.key)?;
 
     // Ensure we have at least one randomness value
     if vrf_result_data.randomness.is_empty() {
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Take the first 8 bytes of the first randomness value and convert to u64
-    let random_bytes = &vrf_result_data.randomness[0][0..8];
-    let random_value = u64::from_le_bytes(random_bytes.try_into().unwrap());
-    
     // Update game state with new random number (1-100)
-    let mut state = state;  // Make state mutable
-    state.current_number = ((random_value % 100) + 1) as u8;
+    state.current_number = derive_in_range(&vrf_result_data.randomness[0], 0, 1, 100)? as u8;
     state.is_pending = false;
-
-    // Write back the updated state (skip discriminator)
-    let mut data = game_state.try_borrow_mut_data()?;
-    state.serialize(&mut &mut data[8..])?;
+    store(game_state, &state)?;
 
     Ok(())
 }
@@ -345,39 +311,35 @@ pub fn consume_randomness(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state::ACCOUNT_HEADER_LEN;
     use solana_sdk::{signature::Keypair, signer::Signer};
     use anyhow::Result;
-    use std::str::FromStr;
 
     #[tokio::test]
     async fn test_game_flow() -> Result<()> {
-        // Use a fixed program ID for testing
-        let program_id = Pubkey::from_str("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS").unwrap();
-
         // Create test accounts
         let payer = Keypair::new();
         let subscription = Keypair::new();
-        let (game_state, _bump) = Pubkey::find_program_address(
-            &[b"game_state", payer.pubkey().as_ref()],
-            &program_id
-        );
 
         // Initialize game state
         let state = GameState {
             owner: payer.pubkey(),
             subscription: subscription.pubkey(),
+            vrf_coordinator: Pubkey::new_unique(),
             current_number: 0,
             is_pending: false,
         };
 
         // Verify the state can be serialized and deserialized
-        let mut data = vec![0u8; 8 + borsh::to_vec(&state)?.len()];
+        assert_eq!(ACCOUNT_HEADER_LEN + borsh::to_vec(&state)?.len(), GameState::LEN);
+        let mut data = [0u8; GameState::LEN];
         data[0..8].copy_from_slice(b"GAMESTAT");
-        state.serialize(&mut &mut data[8..])?;
+        state.serialize(&mut &mut data[ACCOUNT_HEADER_LEN..])?;
         
-        let deserialized_state = GameState::try_from_slice(&data[8..])?;
+        let deserialized_state = GameState::try_from_slice(&data[ACCOUNT_HEADER_LEN..])?;
         assert_eq!(deserialized_state.owner, state.owner);
         assert_eq!(deserialized_state.subscription, state.subscription);
+        assert_eq!(deserialized_state.vrf_coordinator, state.vrf_coordinator);
         assert_eq!(deserialized_state.current_number, state.current_number);
         assert_eq!(deserialized_state.is_pending, state.is_pending);
 
@@ -0,0 +1,127 @@
+//! Golden ECVRF vectors for the proof byte layout accepted on-chain.
+//!
+//! Each entry was produced by `mangekyou::kamui_vrf::ecvrf` and is stored in
+//! the exact format oracles submit in `FulfillRandomness`: an 80-byte proof laid
+//! out as `gamma (32) || c (16) || s (32)`. If a library change alters this
+//! ordering, the encoding of any component or the output derivation, these
+//! tests fail instead of deployed verifiers silently rejecting real proofs.
+
+/// A known (secret key, input, proof, output) tuple.
+pub struct VrfFixture {
+    /// Hex-encoded 32-byte secret key
+    pub secret_key: &'static str,
+    /// Hex-encoded 32-byte compressed Ristretto public key
+    pub public_key: &'static str,
+    /// Hex-encoded VRF input (alpha string)
+    pub alpha: &'static str,
+    /// Hex-encoded 80-byte proof in on-chain layout
+    pub proof: &'static str,
+    /// Hex-encoded 64-byte VRF output
+    pub output: &'static str,
+}
+
+/// Length of a proof in the on-chain layout.
+pub const PROOF_LEN: usize = 80;
+
+pub const VRF_FIXTURES: &[VrfFixture] = &[
+    VrfFixture {
+        secret_key: "d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f",
+        public_key: "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23",
+        alpha: "",
+        proof: "8c82cf41de9774fe50f16f6851aa3338580ddea4ac5824d1a124e8d2f84ced4431fb8d6b65d8a50de2121eb49c4167411a34a6a4c347cfe0d9a1f71a33d9cc64940d916c2022e086f525a8637f320606",
+        output: "62defb8cd92656c00fc776bd1d0ab211816eb8144aebfda33f23c0cc1afc0844433ca55f326ad4ed4cd42525bcbab3c7625d16d6a8afcf25fe36004268f29f15",
+    },
+    VrfFixture {
+        secret_key: "d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f",
+        public_key: "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23",
+        alpha: "48656c6c6f2c20776f726c6421",
+        proof: "3439231f67a987e9cfe15031675501c709847ad8b2d17f3135559b38df93225b9d4de4b0de69ec61d1ee3cec810e02afd2d2e737d795600ff78d99343b7c0ce74b4e2ab7af5d0be0df6617300c7e9502",
+        output: "54abbeefdaadac14e4f79a3135c9d072567e9a7e335dd9eb677df6e1f0999dec2d32a4e8df0560bd8f712de6c2f80e058ae09f4dfa82a78c5868874d966f8856",
+    },
+    VrfFixture {
+        secret_key: "0101010101010101010101010101010101010101010101010101010101010101",
+        public_key: "3e440469a098036d89ffb2d77a4542928f2f74c2b5769da7480736ace829dc10",
+        alpha: "0000000000000000000000000000000000000000000000000000000000000000",
+        proof: "cc4cd82c7b75f1f2092d10a51dd3bd3530f9b222e784a8ecf6766c178260881c2505977f49469d7d503bc7d8aed579a79acfa1d085581ab53384bd585bf2190a974622579c4ff189417e8d38c533d607",
+        output: "4434db8b60f160830020af5830b73f61cb6ed7e3e39c97ce27b9dc6f0deebb2f717f4aa9757ce3417d54ab7948c54b981c0253bef1b2dc21fa59cf1532a3d784",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        mangekyou::kamui_vrf::{
+            ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey},
+            VRFKeyPair,
+            VRFProof,
+        },
+        sha2::{Digest, Sha512},
+    };
+
+    #[test]
+    fn test_fixtures_verify() {
+        for fixture in VRF_FIXTURES {
+            let alpha = hex::decode(fixture.alpha).unwrap();
+            let proof_bytes = hex::decode(fixture.proof).unwrap();
+            assert_eq!(proof_bytes.len(), PROOF_LEN);
+
+            let public_key = ECVRFPublicKey::from_bytes(&hex::decode(fixture.public_key).unwrap()).unwrap();
+            let proof = ECVRFProof::from_bytes_legacy(&proof_bytes).unwrap();
+            let output: [u8; 64] = hex::decode(fixture.output).unwrap().try_into().unwrap();
+
+            assert!(proof.verify_output(&alpha, &public_key, &output).is_ok());
+            assert_eq!(proof.to_bytes_legacy(), proof_bytes);
+        }
+    }
+
+    #[test]
+    fn test_fixtures_are_reproducible() {
+        for fixture in VRF_FIXTURES {
+            let secret_key = ECVRFPrivateKey::from_bytes(&hex::decode(fixture.secret_key).unwrap()).unwrap();
+            let keypair = ECVRFKeyPair::from(secret_key);
+            assert_eq!(hex::encode(keypair.pk.as_ref()), fixture.public_key);
+
+            let (output, proof) = keypair.output(&hex::decode(fixture.alpha).unwrap());
+            assert_eq!(hex::encode(proof.to_bytes_legacy()), fixture.proof);
+            assert_eq!(hex::encode(output), fixture.output);
+        }
+    }
+
+    #[test]
+    fn test_gamma_is_first_component() {
+        // The output is SHA-512(suite || 0x03 || gamma || 0x00), so it pins gamma to bytes 0..32
+        for fixture in VRF_FIXTURES {
+            let proof_bytes = hex::decode(fixture.proof).unwrap();
+            let mut hasher = Sha512::new();
+            hasher.update(b"sol_vrf");
+            hasher.update([0x03]);
+            hasher.update(&proof_bytes[0..32]);
+            hasher.update([0x00]);
+            assert_eq!(hex::encode(hasher.finalize()), fixture.output);
+        }
+    }
+
+    #[test]
+    fn test_reordered_components_rejected() {
+        let fixture = &VRF_FIXTURES[1];
+        let alpha = hex::decode(fixture.alpha).unwrap();
+        let proof_bytes = hex::decode(fixture.proof).unwrap();
+        let public_key = ECVRFPublicKey::from_bytes(&hex::decode(fixture.public_key).unwrap()).unwrap();
+
+        // gamma || s || c
+        let mut reordered = proof_bytes[0..32].to_vec();
+        reordered.extend_from_slice(&proof_bytes[48..80]);
+        reordered.extend_from_slice(&proof_bytes[32..48]);
+        let proof = ECVRFProof::from_bytes_legacy(&reordered).unwrap();
+        assert!(proof.verify(&alpha, &public_key).is_err());
+
+        // Any flipped bit in c or s must fail
+        for index in [32, 47, 48, 79] {
+            let mut tampered = proof_bytes.clone();
+            tampered[index] ^= 0x01;
+            let proof = ECVRFProof::from_bytes_legacy(&tampered).unwrap();
+            assert!(proof.verify(&alpha, &public_key).is_err());
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Running coordinator administration through on-chain governance.
+//!
+//! The admin authority in the coordinator config is only ever checked as a
+//! signer, so it may be a program derived address such as an SPL Governance
+//! governance account or its native treasury: when a proposal executes, the
+//! governance program signs for them. Hand the authority over with
+//! `SetAdmin`, then fee changes (`SetFeeSchedule`), oracle registration and
+//! the other admin instructions can be run as proposals.
+//!
+//! Nothing in an admin instruction needs a keypair signature at execution
+//! time:
+//!
+//! - Every admin instruction takes an optional trailing payer for the rent of
+//!   the accounts it creates and for the audit log's growth. A governance
+//!   account holds data and cannot fund a system transfer, so proposals pass
+//!   the native treasury, see [native_treasury_address], as payer.
+//! - `RegisterOracle` creates the oracle config at the PDA of
+//!   [crate::state::OracleConfig::SEED] and the oracle key, which the
+//!   coordinator signs for.
+
+use solana_program::{pubkey, pubkey::Pubkey};
+
+/// Program ID of the SPL Governance instance behind the Realms app. DAOs may
+/// run their own deployment.
+pub const SPL_GOVERNANCE_PROGRAM_ID: Pubkey = pubkey!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+
+/// Address of the native SOL treasury of `governance`, a system account the
+/// governance program signs for when executing proposals
+pub fn native_treasury_address(governance_program_id: &Pubkey, governance: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"native-treasury", governance.as_ref()], governance_program_id).0
+}
@@ -0,0 +1,211 @@
+//! Groth16 verification over BN254 with the `alt_bn128` syscalls, for the
+//! zk fulfillment mode.
+//!
+//! In this mode the oracle evaluates ECVRF off-chain and proves in a Groth16
+//! circuit that the output is the correct evaluation for the seed under its
+//! VRF key. The coordinator checks the circuit's proof instead of the ECVRF
+//! proof, at a flat cost of three scalar multiplications and one pairing
+//! check, whatever the VRF suite. Points use the big-endian encoding of the
+//! EVM precompiles (EIP-196/197), so proofs from snarkjs or gnark verifiers
+//! for Ethereum can be submitted unchanged.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    crate::{error::VrfCoordinatorError, loader::AccountState, state::ACCOUNT_HEADER_LEN},
+    solana_program::{
+        alt_bn128::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing},
+        hash::hash,
+        program_error::ProgramError,
+    },
+};
+
+/// PDA seed of the verifying key account
+pub const ZK_VERIFYING_KEY_SEED: &[u8] = b"zk_verifying_key";
+
+/// Public inputs of the ECVRF circuit: the VRF key, the seed and the output
+pub const ZK_PUBLIC_INPUTS: usize = 3;
+
+/// Modulus of the BN254 base field, big-endian
+const FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// A Groth16 proof, with G1 points as 64 bytes and G2 points as 128 bytes
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct Groth16Proof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+/// Verifying key of the ECVRF circuit, stored at the [ZK_VERIFYING_KEY_SEED] PDA
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct Groth16VerifyingKey {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    /// One point per public input, after the constant term
+    pub ic: [[u8; 64]; ZK_PUBLIC_INPUTS + 1],
+}
+
+impl Groth16VerifyingKey {
+    /// Account size, including the header
+    pub const LEN: usize = ACCOUNT_HEADER_LEN
+        + 64                            // alpha_g1
+        + 128 * 3                       // beta_g2, gamma_g2, delta_g2
+        + 64 * (ZK_PUBLIC_INPUTS + 1);  // ic
+
+    /// Checks `proof` against `public_inputs`, which must be reduced field elements
+    pub fn verify(
+        &self,
+        proof: &Groth16Proof,
+        public_inputs: &[[u8; 32]; ZK_PUBLIC_INPUTS],
+    ) -> Result<(), ProgramError> {
+        let prepared_inputs = self.prepare_inputs(public_inputs)?;
+
+        // e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+        let mut pairing_input = Vec::with_capacity(4 * 192);
+        for (g1, g2) in [
+            (&negate_g1(&proof.a), &proof.b),
+            (&self.alpha_g1, &self.beta_g2),
+            (&prepared_inputs, &self.gamma_g2),
+            (&proof.c, &self.delta_g2),
+        ] {
+            pairing_input.extend_from_slice(g1);
+            pairing_input.extend_from_slice(g2);
+        }
+        let result = alt_bn128_pairing(&pairing_input)
+            .map_err(|_| VrfCoordinatorError::InvalidZkProof)?;
+        if result.last() != Some(&1) || result[..31].iter().any(|&b| b != 0) {
+            return Err(VrfCoordinatorError::InvalidZkProof.into());
+        }
+        Ok(())
+    }
+
+    /// `ic[0] + sum(public_inputs[i] * ic[i + 1])`
+    pub fn prepare_inputs(&self, public_inputs: &[[u8; 32]; ZK_PUBLIC_INPUTS]) -> Result<[u8; 64], ProgramError> {
+        let mut acc = self.ic[0];
+        for (input, point) in public_inputs.iter().zip(&self.ic[1..]) {
+            let product = alt_bn128_multiplication(&[&point[..], &input[..]].concat())
+                .map_err(|_| VrfCoordinatorError::InvalidZkProof)?;
+            let sum = alt_bn128_addition(&[&acc[..], &product[..]].concat())
+                .map_err(|_| VrfCoordinatorError::InvalidZkProof)?;
+            acc.copy_from_slice(&sum);
+        }
+        Ok(acc)
+    }
+}
+
+impl AccountState for Groth16VerifyingKey {
+    const DISCRIMINATOR: [u8; 8] = *b"ZKVERKEY";
+    const MIN_LEN: usize = Self::LEN;
+}
+
+/// Public inputs for a fulfillment by the oracle with `vrf_key`. Each input is
+/// a hash truncated to 253 bits, so it is always below the scalar field modulus.
+pub fn public_inputs(vrf_key: &[u8; 32], seed: &[u8; 32], output: &[u8; 64]) -> [[u8; 32]; ZK_PUBLIC_INPUTS] {
+    [&vrf_key[..], &seed[..], &output[..]].map(|value| {
+        let mut input = hash(value).to_bytes();
+        input[0] &= 0x1f;
+        input
+    })
+}
+
+/// Negates a G1 point by replacing `y` with `q - y`
+pub(crate) fn negate_g1(point: &[u8; 64]) -> [u8; 64] {
+    let mut negated = *point;
+    if point[32..].iter().all(|&b| b == 0) {
+        return negated;
+    }
+    let mut borrow = 0u16;
+    for i in (0..32).rev() {
+        let diff = FIELD_MODULUS[i] as u16 + 0x100 - point[32 + i] as u16 - borrow;
+        negated[32 + i] = diff as u8;
+        borrow = if diff < 0x100 { 1 } else { 0 };
+    }
+    negated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The BN254 G1 generator
+    fn g1() -> [u8; 64] {
+        let mut point = [0u8; 64];
+        point[31] = 1;
+        point[63] = 2;
+        point
+    }
+
+    /// The BN254 G2 generator
+    const G2: [u8; 128] = [
+        0x19, 0x8e, 0x93, 0x93, 0x92, 0x0d, 0x48, 0x3a, 0x72, 0x60, 0xbf, 0xb7, 0x31, 0xfb, 0x5d, 0x25,
+        0xf1, 0xaa, 0x49, 0x33, 0x35, 0xa9, 0xe7, 0x12, 0x97, 0xe4, 0x85, 0xb7, 0xae, 0xf3, 0x12, 0xc2,
+        0x18, 0x00, 0xde, 0xef, 0x12, 0x1f, 0x1e, 0x76, 0x42, 0x6a, 0x00, 0x66, 0x5e, 0x5c, 0x44, 0x79,
+        0x67, 0x43, 0x22, 0xd4, 0xf7, 0x5e, 0xda, 0xdd, 0x46, 0xde, 0xbd, 0x5c, 0xd9, 0x92, 0xf6, 0xed,
+        0x09, 0x06, 0x89, 0xd0, 0x58, 0x5f, 0xf0, 0x75, 0xec, 0x9e, 0x99, 0xad, 0x69, 0x0c, 0x33, 0x95,
+        0xbc, 0x4b, 0x31, 0x33, 0x70, 0xb3, 0x8e, 0xf3, 0x55, 0xac, 0xda, 0xdc, 0xd1, 0x22, 0x97, 0x5b,
+        0x12, 0xc8, 0x5e, 0xa5, 0xdb, 0x8c, 0x6d, 0xeb, 0x4a, 0xab, 0x71, 0x80, 0x8d, 0xcb, 0x40, 0x8f,
+        0xe3, 0xd1, 0xe7, 0x69, 0x0c, 0x43, 0xd3, 0x7b, 0x4c, 0xe6, 0xcc, 0x01, 0x66, 0xfa, 0x7d, 0xaa,
+    ];
+
+    fn mul_g1(scalar: u8) -> [u8; 64] {
+        let mut input = [0u8; 96];
+        input[..64].copy_from_slice(&g1());
+        input[95] = scalar;
+        alt_bn128_multiplication(&input).unwrap().try_into().unwrap()
+    }
+
+    fn add_g1(p: &[u8; 64], q: &[u8; 64]) -> [u8; 64] {
+        alt_bn128_addition(&[&p[..], &q[..]].concat()).unwrap().try_into().unwrap()
+    }
+
+    /// A key with beta = gamma = delta = the G2 generator, for which
+    /// `A = alpha + vk_x + C` satisfies the verification equation
+    fn key_and_proof(inputs: &[[u8; 32]; ZK_PUBLIC_INPUTS]) -> (Groth16VerifyingKey, Groth16Proof) {
+        let vk = Groth16VerifyingKey {
+            alpha_g1: mul_g1(3),
+            beta_g2: G2,
+            gamma_g2: G2,
+            delta_g2: G2,
+            ic: [mul_g1(5), mul_g1(7), mul_g1(11), mul_g1(13)],
+        };
+        let c = mul_g1(2);
+        let a = add_g1(&add_g1(&vk.alpha_g1, &vk.prepare_inputs(inputs).unwrap()), &c);
+        (vk, Groth16Proof { a, b: G2, c })
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let inputs = public_inputs(&[1; 32], &[2; 32], &[3; 64]);
+        let (vk, proof) = key_and_proof(&inputs);
+        assert_eq!(vk.verify(&proof, &inputs), Ok(()));
+
+        let other_output = public_inputs(&[1; 32], &[2; 32], &[4; 64]);
+        assert_eq!(vk.verify(&proof, &other_output), Err(VrfCoordinatorError::InvalidZkProof.into()));
+
+        let mut tampered = proof.clone();
+        tampered.c = mul_g1(9);
+        assert_eq!(vk.verify(&tampered, &inputs), Err(VrfCoordinatorError::InvalidZkProof.into()));
+    }
+
+    #[test]
+    fn test_negate_g1() {
+        let point = mul_g1(4);
+        let negated = negate_g1(&point);
+        assert_ne!(negated, point);
+        assert_eq!(add_g1(&point, &negated), [0; 64]);
+        assert_eq!(negate_g1(&[0; 64]), [0; 64]);
+    }
+
+    #[test]
+    fn test_key_len() {
+        let (vk, _) = key_and_proof(&public_inputs(&[0; 32], &[0; 32], &[0; 64]));
+        assert_eq!(ACCOUNT_HEADER_LEN + borsh::to_vec(&vk).unwrap().len(), Groth16VerifyingKey::LEN);
+    }
+}
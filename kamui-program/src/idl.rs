@@ -0,0 +1,722 @@
+//! Anchor IDL of the coordinator, for clients built with Anchor. Built with
+//! the `idl` feature; `cargo run --bin gen-idl --features gen-idl` writes the
+//! result of [build_idl] as JSON.
+//!
+//! The IDL is in Anchor's 0.30 format with the coordinator's own
+//! discriminators in place of Anchor's hashes: an instruction is tagged by its
+//! one-byte index in [VrfCoordinatorInstruction], an account by its
+//! [AccountState::DISCRIMINATOR] followed by its layout version. Events keep
+//! Anchor's discriminators, see [event_discriminator].
+//! Argument, account and event types are read from the borsh schema of the
+//! program's own types, so they follow the wire format as it changes.
+//!
+//! Two differences from an Anchor program remain:
+//!
+//! - Accounts an instruction takes optionally, or in any order after its
+//!   fixed ones, such as the coordinator stats account, are left out of its
+//!   account list and passed as remaining accounts, see the instruction's
+//!   documentation.
+//! - Logged events are base64-encoded after `VRF_EVENT:` rather than as
+//!   `Program data:`, so they are decoded with the IDL's event types but not
+//!   picked up by Anchor's event parser. Events emitted by self-invocation,
+//!   see [crate::event], are laid out as Anchor's `emit_cpi` lays them out.
+
+use {
+    crate::{
+        audit_log::{AdminAuditLog, ADMIN_AUDIT_LOG_SEED},
+        error::VrfCoordinatorError,
+        event::{event_discriminator, VrfEvent},
+        groth16::{Groth16VerifyingKey, ZK_VERIFYING_KEY_SEED},
+        instruction::VrfCoordinatorInstruction,
+        loader::AccountState,
+        pda::{REQUEST_SEED, VRF_RESULT_SEED},
+        state::{
+            BeaconStream, CoordinatorConfig, CoordinatorStats, DrandRound, OracleConfig, OracleResponses, RandomnessRequest,
+            RequestPool, Schedule, Subscription, VrfResult, DIRECT_REQUEST_SEED, DRAND_ROUND_SEED, SUBSCRIPTION_VAULT_SEED,
+        },
+        switchboard::{RandomnessAccountData, RANDOMNESS_SEED},
+    },
+    borsh::{
+        schema::{Declaration, Definition, Fields},
+        BorshSchema,
+    },
+    serde_json::{json, Value},
+    solana_program::{pubkey::Pubkey, system_program, sysvar},
+    std::collections::{BTreeMap, BTreeSet},
+};
+
+/// Version of Anchor's IDL specification the IDL follows
+pub const IDL_SPEC: &str = "0.1.0";
+
+/// Seed of a PDA account of an instruction
+#[derive(Clone)]
+enum Seed {
+    Const(&'static [u8]),
+    /// Address of another account of the instruction
+    Account(&'static str),
+    /// Field of another account of the instruction: account, field, account type
+    Field(&'static str, &'static str, &'static str),
+    /// Instruction argument
+    Arg(&'static str),
+}
+
+impl Seed {
+    fn to_json(&self) -> Value {
+        match self {
+            Self::Const(value) => json!({ "kind": "const", "value": value }),
+            Self::Account(path) => json!({ "kind": "account", "path": path }),
+            Self::Field(account, field, account_type) => json!({
+                "kind": "account",
+                "path": format!("{}.{}", account, field),
+                "account": account_type,
+            }),
+            Self::Arg(path) => json!({ "kind": "arg", "path": path }),
+        }
+    }
+}
+
+/// Fixed account of an instruction
+#[derive(Clone)]
+struct Account {
+    name: &'static str,
+    writable: bool,
+    signer: bool,
+    address: Option<Pubkey>,
+    seeds: &'static [Seed],
+}
+
+impl Account {
+    const fn new(name: &'static str) -> Self {
+        Self { name, writable: false, signer: false, address: None, seeds: &[] }
+    }
+
+    const fn writable(mut self) -> Self {
+        self.writable = true;
+        self
+    }
+
+    const fn signer(mut self) -> Self {
+        self.signer = true;
+        self
+    }
+
+    const fn pda(mut self, seeds: &'static [Seed]) -> Self {
+        self.seeds = seeds;
+        self
+    }
+
+    const fn address(mut self, address: Pubkey) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    fn to_json(&self) -> Value {
+        let mut value = json!({ "name": self.name });
+        if self.writable {
+            value["writable"] = json!(true);
+        }
+        if self.signer {
+            value["signer"] = json!(true);
+        }
+        if let Some(address) = self.address {
+            value["address"] = json!(address.to_string());
+        }
+        if !self.seeds.is_empty() {
+            value["pda"] = json!({ "seeds": self.seeds.iter().map(Seed::to_json).collect::<Vec<_>>() });
+        }
+        value
+    }
+}
+
+const SYSTEM_PROGRAM: Account = Account::new("system_program").address(system_program::ID);
+const CONFIG: Account = Account::new("config").pda(&[Seed::Const(CoordinatorConfig::SEED)]);
+const AUDIT_LOG: Account = Account::new("audit_log").writable().pda(&[Seed::Const(ADMIN_AUDIT_LOG_SEED)]);
+const REQUEST_SEEDS: &[Seed] = &[
+    Seed::Const(REQUEST_SEED),
+    Seed::Account("subscription"),
+    Seed::Field("subscription", "nonce", "Subscription"),
+];
+const VRF_RESULT_SEEDS: &[Seed] = &[Seed::Const(VRF_RESULT_SEED), Seed::Account("request")];
+const ORACLE_CONFIG_SEEDS: &[Seed] = &[Seed::Const(OracleConfig::SEED), Seed::Account("oracle")];
+
+const REQUEST_ACCOUNTS: &[Account] = &[
+    Account::new("requester").signer(),
+    Account::new("request").writable().pda(REQUEST_SEEDS),
+    Account::new("subscription").writable(),
+    SYSTEM_PROGRAM,
+];
+
+const FULFILL_ACCOUNTS: &[Account] = &[
+    Account::new("oracle").writable().signer(),
+    Account::new("request").writable(),
+    Account::new("vrf_result").writable().pda(VRF_RESULT_SEEDS),
+    Account::new("callback_program"),
+    Account::new("subscription").writable(),
+    SYSTEM_PROGRAM,
+    Account::new("game_program"),
+    Account::new("game_state").writable(),
+    Account::new("oracle_config").writable().pda(ORACLE_CONFIG_SEEDS),
+];
+
+const ADMIN_ACCOUNTS: &[Account] = &[
+    Account::new("admin").writable().signer(),
+    CONFIG.writable(),
+    SYSTEM_PROGRAM,
+    AUDIT_LOG,
+];
+
+/// Fixed accounts of the instruction `name`, a variant of [VrfCoordinatorInstruction]
+fn instruction_accounts(name: &str) -> Vec<Account> {
+    match name {
+        "CreateSubscription" => vec![
+            Account::new("owner").signer(),
+            Account::new("subscription").writable(),
+            SYSTEM_PROGRAM,
+        ],
+        "FundSubscription" => vec![
+            Account::new("funder").signer(),
+            Account::new("subscription").writable(),
+            Account::new("funder_token").writable(),
+            Account::new("subscription_token").writable(),
+            Account::new("token_program").address(spl_token::ID),
+        ],
+        "RequestRandomness"
+        | "RequestRandomnessAt"
+        | "RequestRandomnessWithAlpha"
+        | "RequestRandomnessOpenMarket"
+        | "RequestRandomnessThreshold" => REQUEST_ACCOUNTS.to_vec(),
+        "FulfillRandomness" => FULFILL_ACCOUNTS.to_vec(),
+        "CancelRequest" => vec![
+            Account::new("owner").writable().signer(),
+            Account::new("request").writable(),
+            Account::new("subscription").writable(),
+        ],
+        "RegisterOracle" | "DeactivateOracle" => vec![
+            Account::new("admin").writable().signer(),
+            if name == "RegisterOracle" { CONFIG } else { CONFIG.writable() },
+            Account::new("oracle_config")
+                .writable()
+                .pda(&[Seed::Const(OracleConfig::SEED), Seed::Arg("oracle_key")]),
+            SYSTEM_PROGRAM,
+            AUDIT_LOG,
+        ],
+        "SetBackstopPolicy" | "RequestSubscriptionOwnershipTransfer" | "SetRateLimits" => vec![
+            Account::new("owner").signer(),
+            Account::new("subscription").writable(),
+        ],
+        "AuditSubscription" => vec![
+            Account::new("subscription").writable(),
+            Account::new("vault").pda(&[Seed::Const(SUBSCRIPTION_VAULT_SEED), Seed::Account("subscription")]),
+            Account::new("subscription_token"),
+        ],
+        "AppendWords" => vec![
+            Account::new("oracle").writable().signer(),
+            Account::new("request").writable(),
+            Account::new("vrf_result").writable().pda(VRF_RESULT_SEEDS),
+            Account::new("subscription"),
+            SYSTEM_PROGRAM,
+            Account::new("oracle_config").pda(ORACLE_CONFIG_SEEDS),
+        ],
+        "FinalizeFulfillment" => vec![
+            Account::new("oracle").writable().signer(),
+            Account::new("request").writable(),
+            Account::new("vrf_result").pda(VRF_RESULT_SEEDS),
+            Account::new("subscription").writable(),
+            Account::new("game_program"),
+            Account::new("game_state").writable(),
+            Account::new("oracle_config").writable().pda(ORACLE_CONFIG_SEEDS),
+        ],
+        "InitializeCoordinator"
+        | "SetAdmin"
+        | "SetFeeSchedule"
+        | "SetConfirmations"
+        | "SetPaused"
+        | "SetOracleRotation"
+        | "SetCpiEvents"
+        | "SetMinPriorityFee"
+        | "SetDrandPublicKey" => ADMIN_ACCOUNTS.to_vec(),
+        "GetRequestFee" => vec![CONFIG],
+        "RequestRandomWords" => [
+            REQUEST_ACCOUNTS,
+            &[Account::new("oracle_config"), CONFIG],
+        ]
+        .concat(),
+        "InitRandomnessAccount" => vec![
+            Account::new("requester").writable().signer(),
+            Account::new("randomness")
+                .writable()
+                .pda(&[Seed::Const(RANDOMNESS_SEED), Seed::Account("requester")]),
+            SYSTEM_PROGRAM,
+        ],
+        "PublishDrandRound" => vec![
+            Account::new("oracle").writable().signer(),
+            Account::new("drand_round")
+                .writable()
+                .pda(&[Seed::Const(DRAND_ROUND_SEED), Seed::Arg("round")]),
+            SYSTEM_PROGRAM,
+            Account::new("oracle_config").pda(ORACLE_CONFIG_SEEDS),
+            CONFIG,
+        ],
+        "ExportAttestation" => vec![
+            Account::new("request"),
+            Account::new("vrf_result").pda(VRF_RESULT_SEEDS),
+            CONFIG,
+            Account::new("bridge_program"),
+        ],
+        "FulfillRandomnessZk" => [
+            FULFILL_ACCOUNTS,
+            &[Account::new("verifying_key").pda(&[Seed::Const(ZK_VERIFYING_KEY_SEED)])],
+        ]
+        .concat(),
+        "SetZkVerifyingKey" => vec![
+            Account::new("admin").writable().signer(),
+            CONFIG,
+            Account::new("verifying_key")
+                .writable()
+                .pda(&[Seed::Const(ZK_VERIFYING_KEY_SEED)]),
+            SYSTEM_PROGRAM,
+            AUDIT_LOG,
+        ],
+        "CreateSchedule" => vec![
+            Account::new("owner").writable().signer(),
+            Account::new("schedule")
+                .writable()
+                .pda(&[Seed::Const(Schedule::SEED), Seed::Account("owner"), Seed::Arg("seed")]),
+            Account::new("subscription"),
+            SYSTEM_PROGRAM,
+        ],
+        "CrankSchedule" => vec![
+            Account::new("cranker").writable().signer(),
+            Account::new("schedule").writable(),
+            Account::new("request").writable().pda(REQUEST_SEEDS),
+            Account::new("subscription").writable(),
+            SYSTEM_PROGRAM,
+            CONFIG,
+        ],
+        "CancelSchedule" => vec![
+            Account::new("owner").writable().signer(),
+            Account::new("schedule").writable(),
+        ],
+        "CreateBeaconStream" => vec![
+            Account::new("owner").writable().signer(),
+            Account::new("beacon_stream")
+                .writable()
+                .pda(&[Seed::Const(BeaconStream::SEED), Seed::Account("owner")]),
+            SYSTEM_PROGRAM,
+        ],
+        "CloseBeaconStream" => vec![
+            Account::new("owner").writable().signer(),
+            Account::new("beacon_stream").writable(),
+        ],
+        "RequestRandomnessDirect" => vec![
+            Account::new("requester").writable().signer(),
+            Account::new("request")
+                .writable()
+                .pda(&[Seed::Const(DIRECT_REQUEST_SEED), Seed::Account("requester"), Seed::Arg("seed")]),
+            CONFIG,
+            SYSTEM_PROGRAM,
+        ],
+        "InitializeStats" => vec![
+            Account::new("payer").writable().signer(),
+            Account::new("stats").writable().pda(&[Seed::Const(CoordinatorStats::SEED)]),
+            SYSTEM_PROGRAM,
+        ],
+        "RequestRandomnessWithEntropy" => [
+            REQUEST_ACCOUNTS,
+            &[Account::new("slot_hashes").address(sysvar::slot_hashes::ID)],
+        ]
+        .concat(),
+        "ExpireRequest" => vec![
+            Account::new("request").writable(),
+            Account::new("subscription").writable(),
+            Account::new("requester").writable(),
+        ],
+        "AddConsumer" => vec![
+            Account::new("owner").writable().signer(),
+            Account::new("subscription").writable(),
+            SYSTEM_PROGRAM,
+        ],
+        "RemoveConsumer" => vec![
+            Account::new("owner").writable().signer(),
+            Account::new("subscription").writable(),
+        ],
+        "AcceptSubscriptionOwnership" => vec![
+            Account::new("new_owner").signer(),
+            Account::new("subscription").writable(),
+        ],
+        "FulfillRandomnessBatch" => vec![
+            Account::new("oracle").writable().signer(),
+            Account::new("oracle_config").writable().pda(ORACLE_CONFIG_SEEDS),
+            SYSTEM_PROGRAM,
+        ],
+        "InitializeRequestPool" => vec![
+            Account::new("payer").writable().signer(),
+            Account::new("request_pool")
+                .writable()
+                .pda(&[Seed::Const(RequestPool::SEED), Seed::Account("subscription")]),
+            Account::new("subscription"),
+            SYSTEM_PROGRAM,
+        ],
+        "SubmitOracleResponse" => [
+            FULFILL_ACCOUNTS,
+            &[Account::new("oracle_responses")
+                .writable()
+                .pda(&[Seed::Const(OracleResponses::SEED), Seed::Account("request")])],
+        ]
+        .concat(),
+        "MigrateAccount" => vec![
+            Account::new("payer").writable().signer(),
+            Account::new("account").writable(),
+            SYSTEM_PROGRAM,
+        ],
+        "FundSubscriptionNative" => vec![
+            Account::new("funder").writable().signer(),
+            Account::new("subscription").writable(),
+            Account::new("vault")
+                .writable()
+                .pda(&[Seed::Const(SUBSCRIPTION_VAULT_SEED), Seed::Account("subscription")]),
+            SYSTEM_PROGRAM,
+        ],
+        "ClaimOracleFeesNative" => vec![
+            Account::new("oracle").writable().signer(),
+            Account::new("oracle_config").writable().pda(ORACLE_CONFIG_SEEDS),
+            Account::new("subscription"),
+            Account::new("vault")
+                .writable()
+                .pda(&[Seed::Const(SUBSCRIPTION_VAULT_SEED), Seed::Account("subscription")]),
+            SYSTEM_PROGRAM,
+        ],
+        "WithdrawSubscriptionNative" => vec![
+            Account::new("owner").writable().signer(),
+            Account::new("subscription").writable(),
+            Account::new("vault")
+                .writable()
+                .pda(&[Seed::Const(SUBSCRIPTION_VAULT_SEED), Seed::Account("subscription")]),
+            SYSTEM_PROGRAM,
+        ],
+        "ClaimOracleFees" => vec![
+            Account::new("oracle").signer(),
+            Account::new("oracle_config").writable().pda(ORACLE_CONFIG_SEEDS),
+            Account::new("subscription"),
+            Account::new("subscription_token").writable(),
+            Account::new("oracle_token").writable(),
+            Account::new("vault").pda(&[Seed::Const(SUBSCRIPTION_VAULT_SEED), Seed::Account("subscription")]),
+            Account::new("token_program").address(spl_token::ID),
+        ],
+        "WithdrawSubscription" => vec![
+            Account::new("owner").signer(),
+            Account::new("subscription").writable(),
+            Account::new("subscription_token").writable(),
+            Account::new("owner_token").writable(),
+            Account::new("vault").pda(&[Seed::Const(SUBSCRIPTION_VAULT_SEED), Seed::Account("subscription")]),
+            Account::new("token_program").address(spl_token::ID),
+        ],
+        _ => panic!("No IDL accounts for instruction {}", name),
+    }
+}
+
+/// Every coordinator error, in code order
+const ERRORS: &[VrfCoordinatorError] = &[
+    VrfCoordinatorError::InvalidInstruction,
+    VrfCoordinatorError::NotRentExempt,
+    VrfCoordinatorError::InsufficientBalance,
+    VrfCoordinatorError::InvalidSubscriptionOwner,
+    VrfCoordinatorError::InvalidRequestStatus,
+    VrfCoordinatorError::InvalidOracleSigner,
+    VrfCoordinatorError::InvalidVrfProof,
+    VrfCoordinatorError::RequestAlreadyFulfilled,
+    VrfCoordinatorError::InsufficientConfirmations,
+    VrfCoordinatorError::InvalidRequestConfirmations,
+    VrfCoordinatorError::InvalidCallbackGasLimit,
+    VrfCoordinatorError::InvalidNumberOfWords,
+    VrfCoordinatorError::InvalidOracle,
+    VrfCoordinatorError::InvalidCommitment,
+    VrfCoordinatorError::CallbackFailed,
+    VrfCoordinatorError::RequestExpired,
+    VrfCoordinatorError::InvalidRequestParameters,
+    VrfCoordinatorError::UnauthorizedOracle,
+    VrfCoordinatorError::ProgramPaused,
+    VrfCoordinatorError::InvalidDiscriminator,
+    VrfCoordinatorError::AccountTooSmall,
+    VrfCoordinatorError::InvalidRange,
+    VrfCoordinatorError::RandomnessNotRevealed,
+    VrfCoordinatorError::InvalidZkProof,
+    VrfCoordinatorError::InvalidAdmin,
+    VrfCoordinatorError::RequestTimeLocked,
+    VrfCoordinatorError::ScheduleNotDue,
+    VrfCoordinatorError::AlphaTooLong,
+    VrfCoordinatorError::RequestNotExpired,
+    VrfCoordinatorError::InvalidConsumer,
+    VrfCoordinatorError::TooManyConsumers,
+    VrfCoordinatorError::TooManyOracles,
+    VrfCoordinatorError::DuplicateOracleResponse,
+    VrfCoordinatorError::AccountNotMigrated,
+    VrfCoordinatorError::InvalidTokenAccount,
+    VrfCoordinatorError::MissingEventAccounts,
+    VrfCoordinatorError::PriorityFeeTooLow,
+    VrfCoordinatorError::RateLimited,
+    VrfCoordinatorError::InvalidWeights,
+    VrfCoordinatorError::InvalidVrfResult,
+    VrfCoordinatorError::StaleBeaconRound,
+    VrfCoordinatorError::InvalidDrandSignature,
+    VrfCoordinatorError::DrandPublicKeyNotSet,
+    VrfCoordinatorError::InvalidVrfCoordinator,
+];
+
+/// `RequestRandomness` as `request_randomness`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.char_indices() {
+        if c.is_ascii_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// Borsh schema definitions of every type in the IDL
+struct Schema {
+    definitions: BTreeMap<Declaration, Definition>,
+}
+
+impl Schema {
+    fn add<T: BorshSchema>(&mut self) {
+        T::add_definitions_recursively(&mut self.definitions);
+    }
+
+    /// Variants of the enum `declaration`: discriminant, name, and fields
+    fn variants(&self, declaration: &str) -> Vec<(i64, &str, &Fields)> {
+        let Some(Definition::Enum { variants, .. }) = self.definitions.get(declaration) else {
+            panic!("{} is not an enum", declaration);
+        };
+        variants
+            .iter()
+            .map(|(discriminant, name, variant)| match &self.definitions[variant] {
+                Definition::Struct { fields } => (*discriminant, name.as_str(), fields),
+                _ => panic!("Variant {} is not a struct", variant),
+            })
+            .collect()
+    }
+
+    /// Anchor type of `declaration`
+    fn idl_type(&self, declaration: &str) -> Value {
+        match (declaration, self.definitions.get(declaration)) {
+            ("String", _) => json!("string"),
+            ("Pubkey", _) => json!("pubkey"),
+            (_, Some(Definition::Primitive(_))) => json!(declaration),
+            (_, Some(Definition::Sequence { length_width: 0, length_range, elements })) => {
+                json!({ "array": [self.idl_type(elements), length_range.end()] })
+            }
+            (_, Some(Definition::Sequence { elements, .. })) if elements == "u8" => json!("bytes"),
+            (_, Some(Definition::Sequence { elements, .. })) => json!({ "vec": self.idl_type(elements) }),
+            (_, Some(Definition::Enum { variants, .. })) if declaration.starts_with("Option<") => {
+                json!({ "option": self.idl_type(&variants[1].2) })
+            }
+            _ => json!({ "defined": { "name": declaration } }),
+        }
+    }
+
+    /// Anchor fields of a struct or enum variant, `None` if it has none
+    fn idl_fields(&self, fields: &Fields) -> Option<Value> {
+        match fields {
+            Fields::NamedFields(fields) => Some(json!(fields
+                .iter()
+                .map(|(name, declaration)| json!({ "name": name, "type": self.idl_type(declaration) }))
+                .collect::<Vec<_>>())),
+            Fields::UnnamedFields(fields) => {
+                Some(json!(fields.iter().map(|declaration| self.idl_type(declaration)).collect::<Vec<_>>()))
+            }
+            Fields::Empty => None,
+        }
+    }
+
+    /// Anchor struct type named `name` with `fields`
+    fn idl_struct(&self, name: &str, fields: &Fields) -> Value {
+        let mut ty = json!({ "kind": "struct" });
+        if let Some(fields) = self.idl_fields(fields) {
+            ty["fields"] = fields;
+        }
+        json!({ "name": name, "type": ty })
+    }
+
+    /// Anchor definitions of the structs and enums other types refer to,
+    /// leaving out `excluded` enums, whose variants are listed elsewhere
+    fn idl_types(&self, excluded: &[&str]) -> Vec<Value> {
+        let variant_structs: BTreeSet<&str> = self
+            .definitions
+            .values()
+            .filter_map(|definition| match definition {
+                Definition::Enum { variants, .. } => Some(variants.iter().map(|(_, _, variant)| variant.as_str())),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        self.definitions
+            .iter()
+            .filter(|(declaration, _)| {
+                let declaration = declaration.as_str();
+                declaration != "Pubkey"
+                    && !declaration.starts_with("Option<")
+                    && !excluded.contains(&declaration)
+                    && !variant_structs.contains(declaration)
+            })
+            .filter_map(|(declaration, definition)| match definition {
+                Definition::Struct { fields } => Some(self.idl_struct(declaration, fields)),
+                Definition::Enum { .. } => Some(json!({
+                    "name": declaration,
+                    "type": {
+                        "kind": "enum",
+                        "variants": self.variants(declaration).into_iter().map(|(_, name, fields)| {
+                            let mut variant = json!({ "name": name });
+                            if let Some(fields) = self.idl_fields(fields) {
+                                variant["fields"] = fields;
+                            }
+                            variant
+                        }).collect::<Vec<_>>(),
+                    },
+                })),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Anchor account entry of `T`
+fn idl_account<T: AccountState + BorshSchema>(schema: &mut Schema) -> Value {
+    schema.add::<T>();
+    let mut discriminator = T::DISCRIMINATOR.to_vec();
+    discriminator.push(T::VERSION);
+    json!({ "name": T::declaration(), "discriminator": discriminator })
+}
+
+/// The coordinator's Anchor IDL, for a deployment at `program_id`
+pub fn build_idl(program_id: &Pubkey) -> Value {
+    let mut schema = Schema { definitions: BTreeMap::new() };
+    schema.add::<VrfCoordinatorInstruction>();
+    schema.add::<VrfEvent>();
+    let accounts = vec![
+        idl_account::<Subscription>(&mut schema),
+        idl_account::<RandomnessRequest>(&mut schema),
+        idl_account::<VrfResult>(&mut schema),
+        idl_account::<OracleConfig>(&mut schema),
+        idl_account::<CoordinatorConfig>(&mut schema),
+        idl_account::<DrandRound>(&mut schema),
+        idl_account::<Schedule>(&mut schema),
+        idl_account::<BeaconStream>(&mut schema),
+        idl_account::<CoordinatorStats>(&mut schema),
+        idl_account::<RequestPool>(&mut schema),
+        idl_account::<OracleResponses>(&mut schema),
+        idl_account::<Groth16VerifyingKey>(&mut schema),
+        idl_account::<AdminAuditLog>(&mut schema),
+        idl_account::<RandomnessAccountData>(&mut schema),
+    ];
+
+    let instruction = VrfCoordinatorInstruction::declaration();
+    let instructions: Vec<Value> = schema
+        .variants(&instruction)
+        .into_iter()
+        .map(|(discriminant, name, fields)| {
+            let args = match fields {
+                Fields::NamedFields(fields) => fields
+                    .iter()
+                    .map(|(arg, declaration)| json!({ "name": arg, "type": schema.idl_type(declaration) }))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            json!({
+                "name": snake_case(name),
+                "discriminator": [discriminant as u8],
+                "accounts": instruction_accounts(name).iter().map(Account::to_json).collect::<Vec<_>>(),
+                "args": args,
+            })
+        })
+        .collect();
+
+    let event = VrfEvent::declaration();
+    let event_variants = schema.variants(&event);
+    let events: Vec<Value> = event_variants
+        .iter()
+        .map(|(_, name, _)| json!({ "name": name, "discriminator": event_discriminator(name) }))
+        .collect();
+    let mut types: Vec<Value> = event_variants
+        .iter()
+        .map(|(_, name, fields)| schema.idl_struct(name, fields))
+        .collect();
+    types.extend(schema.idl_types(&[&instruction, &event]));
+
+    let errors: Vec<Value> = ERRORS
+        .iter()
+        .map(|error| json!({ "code": *error as u32, "name": format!("{:?}", error), "msg": error.to_string() }))
+        .collect();
+
+    json!({
+        "address": program_id.to_string(),
+        "metadata": {
+            "name": "kamui_program",
+            "version": env!("CARGO_PKG_VERSION"),
+            "spec": IDL_SPEC,
+            "description": "Kamui VRF coordinator",
+        },
+        "instructions": instructions,
+        "accounts": accounts,
+        "events": events,
+        "errors": errors,
+        "types": types,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::event::EVENT_NAMES};
+
+    fn find<'a>(values: &'a Value, name: &str) -> &'a Value {
+        values.as_array().unwrap().iter().find(|value| value["name"] == name).unwrap()
+    }
+
+    #[test]
+    fn test_idl_matches_wire_format() {
+        let idl = build_idl(&Pubkey::new_unique());
+
+        let instructions = idl["instructions"].as_array().unwrap();
+        assert_eq!(instructions[0]["name"], "create_subscription");
+        assert_eq!(instructions[0]["args"], json!([
+            { "name": "min_balance", "type": "u64" },
+            { "name": "confirmations", "type": "u8" },
+        ]));
+        let submit = find(&idl["instructions"], "submit_oracle_response");
+        let data = borsh::to_vec(&VrfCoordinatorInstruction::SubmitOracleResponse { proof: vec![] }).unwrap();
+        assert_eq!(submit["discriminator"], json!([data[0]]));
+        assert_eq!(submit["args"], json!([{ "name": "proof", "type": "bytes" }]));
+        assert_eq!(submit["accounts"][9]["name"], "oracle_responses");
+
+        let request = &find(&idl["instructions"], "request_randomness")["accounts"][1];
+        assert_eq!(request["pda"]["seeds"][2], json!({
+            "kind": "account",
+            "path": "subscription.nonce",
+            "account": "Subscription",
+        }));
+        let zk = find(&idl["instructions"], "fulfill_randomness_zk");
+        assert_eq!(zk["args"][0]["type"], json!({ "array": ["u8", 64] }));
+        assert_eq!(zk["args"][1]["type"], json!({ "defined": { "name": "Groth16Proof" } }));
+
+        assert_eq!(find(&idl["accounts"], "Subscription")["discriminator"], json!(b"SUBSCRIP\x03"));
+        let request_type = find(&idl["types"], "RandomnessRequest");
+        assert_eq!(find(&request_type["type"]["fields"], "status")["type"], json!({ "defined": { "name": "RequestStatus" } }));
+        assert_eq!(find(&idl["types"], "RequestStatus")["type"]["kind"], "enum");
+
+        for (event, name) in idl["events"].as_array().unwrap().iter().zip(EVENT_NAMES) {
+            assert_eq!(event["name"], *name);
+            assert_eq!(event["discriminator"], json!(event_discriminator(name)));
+        }
+        assert_eq!(idl["events"].as_array().unwrap().len(), EVENT_NAMES.len());
+        let oracle_response = find(&idl["types"], "OracleResponseSubmitted");
+        assert_eq!(find(&oracle_response["type"]["fields"], "oracle")["type"], "pubkey");
+
+        let names: BTreeSet<&str> = idl["types"].as_array().unwrap().iter().map(|ty| ty["name"].as_str().unwrap()).collect();
+        assert_eq!(names.len(), idl["types"].as_array().unwrap().len());
+        for (code, error) in idl["errors"].as_array().unwrap().iter().enumerate() {
+            assert_eq!(error["code"], code);
+        }
+    }
+}
@@ -1,5 +1,10 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
+use crate::{
+    groth16::{Groth16Proof, Groth16VerifyingKey},
+    state::{FeeAuction, FeeSchedule, DRAND_PUBLIC_KEY_LEN, DRAND_SIGNATURE_LEN},
+    verifier::VrfSuite,
+};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct VerifyVrfInput {
@@ -8,7 +13,11 @@ pub struct VerifyVrfInput {
     pub public_key_bytes: Vec<u8>,
 }
 
+/// Instructions of the coordinator. Any of them may be followed by the event
+/// authority (PDA) and the coordinator program, to emit its events by
+/// self-invocation rather than to the log, see [crate::event].
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
 pub enum VrfCoordinatorInstruction {
     /// Create a new subscription
     /// Accounts expected:
@@ -20,64 +29,696 @@ pub enum VrfCoordinatorInstruction {
         confirmations: u8,
     },
 
-    /// Fund a subscription
+    /// Fund a subscription with wrapped SOL, transferred to the token account
+    /// of its vault, see [crate::state::subscription_token_address]
     /// Accounts expected:
     /// 0. `[signer]` Funder
     /// 1. `[writable]` Subscription account
-    /// 2. `[]` System program
+    /// 2. `[writable]` Funder's token account
+    /// 3. `[writable]` Subscription's token account
+    /// 4. `[]` Token program
     FundSubscription {
         amount: u64,
     },
 
-    /// Request randomness
+    /// Request randomness. It may be fulfilled once `minimum_confirmations`
+    /// slots, or the subscription's confirmations if more, have elapsed.
+    /// A nonzero `priority_fee` is a tip for the fulfilling oracle, at least
+    /// the config's `min_priority_fee`, which oracles may order requests by.
+    /// It is escrowed from the subscription balance until the request is
+    /// fulfilled, and refunded if it is cancelled or expires. So is the fee
+    /// quoted by the config's fee schedule, or the subscription's
+    /// `min_balance` if more, which the fulfillment charges the actual fee
+    /// against.
     /// Accounts expected:
-    /// 0. `[signer]` Requester
+    /// 0. `[signer]` Requester, the subscription owner or an approved consumer
     /// 1. `[writable]` Request account (PDA)
-    /// 2. `[]` Subscription account
+    /// 2. `[writable]` Subscription account
     /// 3. `[]` System program
+    /// 4. `[]` (Optional) Oracle config of the oracle assigned to this request,
+    ///    refused once the coordinator has an oracle rotation
+    ///
+    /// 4+. `[]` Coordinator config account (PDA), in any order with the other
+    ///     trailing accounts. Requests are refused while the coordinator is
+    ///     paused, and `minimum_confirmations` must be within its bounds.
     RequestRandomness {
         seed: [u8; 32],
         callback_data: Vec<u8>,
         num_words: u32,
         minimum_confirmations: u8,
         callback_gas_limit: u64,
+        priority_fee: u64,
     },
 
-    /// Fulfill randomness request
+    /// Fulfill randomness request with the oracle's ECVRF proof over the
+    /// request seed, checked against the VRF key registered for the oracle,
+    /// which `public_key` must be. The proof is in `suite`, the coordinator's
+    /// own Ristretto suite or RFC 9381 ECVRF-EDWARDS25519-SHA512-TAI for
+    /// oracles registered with an Ed25519 VRF key. The words are expanded from
+    /// the proof's output. A request that no longer matches the commitment it
+    /// was opened with is refused with `InvalidCommitment`. When the
+    /// transaction has fewer compute units left than the request's
+    /// `callback_gas_limit`, the request is fulfilled without the callback and
+    /// marked `CallbackSkipped`.
     /// Accounts expected:
-    /// 0. `[signer]` Oracle
+    /// 0. `[signer, writable]` Oracle, collects the fee of a direct request
     /// 1. `[writable]` Request account
     /// 2. `[writable]` VRF result account (PDA)
     /// 3. `[]` Callback program
-    /// 4. `[]` System program
+    /// 4. `[writable]` Subscription account, or the system program for a direct request
+    /// 5. `[]` System program
+    /// 6. `[]` Game program
+    /// 7. `[writable]` Game state account
+    /// 8. `[writable]` Oracle config account (PDA) of the signing oracle
+    /// 9. `[writable]` (Optional) Requester's randomness account, see [crate::switchboard]
+    ///
+    /// 9+. `[]` Coordinator config account (PDA), in any order with the other
+    ///     trailing accounts, which are known by their address or type. The
+    ///     subscription pays the quoted fee, less a rebate for unused callback
+    ///     compute units. Only a direct or open market request may leave it out.
+    /// 9+. `[writable]` Oracle config account of the assigned oracle, required
+    ///     from a backstop oracle taking over the request and counted a failure
+    ///     against the assigned oracle's reputation
+    /// 9+. `[writable]` (Optional) Request pool account (PDA), see `InitializeRequestPool`
     FulfillRandomness {
         proof: Vec<u8>,
         public_key: Vec<u8>,
+        suite: VrfSuite,
     },
 
-    /// Cancel a request
+    /// Cancel a pending request. One whose words are being appended may only expire.
     /// Accounts expected:
-    /// 0. `[signer]` Request owner
+    /// 0. `[signer, writable]` Subscription owner, or the requester of a direct
+    ///    request, receives the request account's rent and any unspent fee
     /// 1. `[writable]` Request account
+    /// 2. `[writable]` Subscription account, or the system program for a direct request
     CancelRequest,
 
     /// Register a new oracle
     /// Accounts expected:
-    /// 0. `[signer]` Admin
-    /// 1. `[writable]` Oracle config account (PDA)
-    /// 2. `[]` System program
+    /// 0. `[signer, writable]` Coordinator admin, pays for the oracle config unless a payer is passed
+    /// 1. `[]` Coordinator config account (PDA)
+    /// 2. `[writable]` Oracle config account: the PDA of
+    ///    [crate::state::OracleConfig::SEED] and `oracle_key`
+    /// 3. `[]` System program
+    /// 4. `[writable]` Admin audit log (PDA), see [crate::audit_log]
+    /// 5. `[signer, writable]` (Optional) Payer, see [crate::governance]
     RegisterOracle {
         oracle_key: Pubkey,
         vrf_key: [u8; 32],
     },
 
-    /// Deactivate an oracle
+    /// Deactivate an oracle, dropping it from the oracle rotation
     /// Accounts expected:
-    /// 0. `[signer]` Admin
-    /// 1. `[writable]` Oracle config account
+    /// 0. `[signer, writable]` Coordinator admin
+    /// 1. `[writable]` Coordinator config account (PDA), writable if the oracle is in the rotation
+    /// 2. `[writable]` Oracle config account (PDA)
+    /// 3. `[]` System program
+    /// 4. `[writable]` Admin audit log (PDA), see [crate::audit_log]
+    /// 5. `[signer, writable]` (Optional) Payer, see [crate::governance]
     DeactivateOracle {
         oracle_key: Pubkey,
     },
+
+    /// Configure when and for what bonus a backstop oracle may fulfill overdue requests
+    /// Accounts expected:
+    /// 0. `[signer]` Subscription owner
+    /// 1. `[writable]` Subscription account
+    SetBackstopPolicy {
+        timeout_slots: u64,
+        bonus: u64,
+    },
+
+    /// Check a subscription's balances against what its vault and token
+    /// account hold, against its total funding and against its history of
+    /// debits and releases, and its nonce against the last audit. Emits
+    /// `AuditDiscrepancy` if any is inconsistent. Anyone may call this.
+    /// Accounts expected:
+    /// 0. `[writable]` Subscription account
+    /// 1. `[]` Subscription vault (PDA)
+    /// 2. `[]` Subscription's token account, which need not exist
+    AuditSubscription,
+
+    /// Deliver the next `num_words` words of a request's result, for requests
+    /// too large to fulfill in one transaction. The first call creates or
+    /// resizes the VRF result account and records the proof, which must
+    /// verify as for `FulfillRandomness` in the Ristretto suite; a call with a
+    /// different proof starts over. Complete with `FinalizeFulfillment`.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Oracle, pays for the VRF result account
+    /// 1. `[writable]` Request account
+    /// 2. `[writable]` VRF result account (PDA)
+    /// 3. `[]` Subscription account, or the system program for a direct request
+    /// 4. `[]` System program
+    /// 5. `[]` Oracle config account of the signing oracle
+    AppendWords {
+        proof: Vec<u8>,
+        num_words: u32,
+    },
+
+    /// Complete a request whose words were all delivered with `AppendWords`
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Oracle, collects the fee of a direct request
+    /// 1. `[writable]` Request account
+    /// 2. `[]` VRF result account (PDA)
+    /// 3. `[writable]` Subscription account, or the system program for a direct request
+    /// 4. `[]` Game program
+    /// 5. `[writable]` Game state account
+    /// 6. `[writable]` Oracle config account of the signing oracle
+    /// 7. `[writable]` (Optional) Requester's randomness account, see [crate::switchboard]
+    ///
+    /// 7+. `[]` Coordinator config account (PDA), as for `FulfillRandomness`
+    /// 7+. `[writable]` Oracle config account of the assigned oracle, as for `FulfillRandomness`
+    FinalizeFulfillment,
+
+    /// Create the coordinator config with the signer as admin
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Admin, pays for the config account unless a payer is passed
+    /// 1. `[writable]` Coordinator config account (PDA)
+    /// 2. `[]` System program
+    /// 3. `[writable]` Admin audit log (PDA), see [crate::audit_log]
+    /// 4. `[signer, writable]` (Optional) Payer, see [crate::governance]
+    InitializeCoordinator {
+        fee_schedule: FeeSchedule,
+        bridge_program: Pubkey,
+    },
+
+    /// Quote the fee of a request under the current fee schedule. The fee is
+    /// returned as a little-endian u64 in the return data.
+    /// Accounts expected:
+    /// 0. `[]` Coordinator config account (PDA)
+    GetRequestFee {
+        num_words: u32,
+        callback_gas_limit: u64,
+        priority: u8,
+    },
+
+    /// Request randomness with the parameters of Chainlink VRF v2's
+    /// `requestRandomWords`. The callback receives `FulfillRandomWords`, see
+    /// [crate::chainlink]. The request account address is returned in the
+    /// return data.
+    /// Accounts expected:
+    /// 0. `[signer]` Requester
+    /// 1. `[writable]` Request account (PDA)
+    /// 2. `[writable]` Subscription account, whose address is `sub_id`
+    /// 3. `[]` System program
+    /// 4. `[]` Oracle config of the oracle whose VRF key hashes to `key_hash`
+    /// 5. `[]` Coordinator config account (PDA), as for `RequestRandomness`
+    RequestRandomWords {
+        key_hash: [u8; 32],
+        sub_id: Pubkey,
+        request_confirmations: u16,
+        callback_gas_limit: u32,
+        num_words: u32,
+    },
+
+    /// Create the signer's Switchboard-style randomness account, see
+    /// [crate::switchboard]
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Requester, pays for the account
+    /// 1. `[writable]` Randomness account (PDA)
+    /// 2. `[]` System program
+    InitRandomnessAccount,
+
+    /// Relay a round of the drand evmnet beacon. Its BLS signature is
+    /// verified against the config's `drand_public_key`, see [crate::drand],
+    /// and the randomness derived from it on-chain. Verifying costs a few
+    /// hundred thousand compute units.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Oracle, pays for the round account
+    /// 1. `[writable]` Drand round account (PDA)
+    /// 2. `[]` System program
+    /// 3. `[]` Oracle config account of the signing oracle
+    /// 4. `[]` Coordinator config account (PDA)
+    ///
+    /// 5.. For each beacon stream the round is written into, see
+    ///     `CreateBeaconStream`:
+    ///     - `[writable]` Beacon stream account
+    ///     - `[]` Callback program, if the stream has one
+    ///     - `[writable]` Callback account, if the stream has one
+    PublishDrandRound {
+        round: u64,
+        signature: [u8; DRAND_SIGNATURE_LEN],
+    },
+
+    /// Post an attestation of a fulfilled request to the configured bridge
+    /// program, see [crate::attestation]. Anyone may call this.
+    /// Accounts expected:
+    /// 0. `[]` Request account
+    /// 1. `[]` VRF result account (PDA) holding the request's result
+    /// 2. `[]` Coordinator config account (PDA)
+    /// 3. `[]` Bridge program
+    ///
+    /// 4.. Accounts of the bridge's `post_message`, in its order, including
+    ///     the coordinator's emitter PDA
+    ExportAttestation {
+        nonce: u32,
+        consistency_level: u8,
+    },
+
+    /// Fulfill a request with a Groth16 proof that `output` is the oracle's
+    /// ECVRF output for the request seed, in place of the ECVRF proof, see
+    /// [crate::groth16]. The proof stored in the result is zeroed.
+    /// Accounts expected:
+    /// 0-8. As for `FulfillRandomness`
+    /// 9. `[]` Verifying key account (PDA)
+    /// 10. `[writable]` (Optional) Requester's randomness account, see [crate::switchboard]
+    /// 10+. `[]` Coordinator config account (PDA), as for `FulfillRandomness`
+    /// 10+. `[writable]` Oracle config account of the assigned oracle, as for `FulfillRandomness`
+    FulfillRandomnessZk {
+        output: [u8; 64],
+        proof: Box<Groth16Proof>,
+    },
+
+    /// Set the verifying key of the circuit checked by `FulfillRandomnessZk`,
+    /// creating its account on first use
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Coordinator admin, pays for the key account unless a payer is passed
+    /// 1. `[]` Coordinator config account (PDA)
+    /// 2. `[writable]` Verifying key account (PDA)
+    /// 3. `[]` System program
+    /// 4. `[writable]` Admin audit log (PDA), see [crate::audit_log]
+    /// 5. `[signer, writable]` (Optional) Payer, see [crate::governance]
+    SetZkVerifyingKey {
+        verifying_key: Box<Groth16VerifyingKey>,
+    },
+
+    /// Request randomness that may not be fulfilled before `not_before_slot`,
+    /// so it is revealed no earlier than a scheduled time
+    /// Accounts expected:
+    /// As for `RequestRandomness`
+    RequestRandomnessAt {
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        not_before_slot: u64,
+    },
+
+    /// Create a schedule that opens a request for the signer every
+    /// `interval_slots`, starting at `start_slot` or now if that has passed
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner, the requester of the scheduled requests,
+    ///    who must be the subscription owner or an approved consumer
+    /// 1. `[writable]` Schedule account (PDA)
+    /// 2. `[]` Subscription account
+    /// 3. `[]` System program
+    CreateSchedule {
+        seed: [u8; 32],
+        interval_slots: u64,
+        start_slot: u64,
+        num_words: u32,
+        callback_gas_limit: u64,
+        callback_data: Vec<u8>,
+    },
+
+    /// Open the schedule's next request once it is due. Anyone may call this.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Cranker, pays for the request account
+    /// 1. `[writable]` Schedule account
+    /// 2. `[writable]` Request account (PDA)
+    /// 3. `[writable]` Subscription account
+    /// 4. `[]` System program
+    /// 5. `[]` Coordinator config account (PDA), refusing the request while paused
+    CrankSchedule,
+
+    /// Close a schedule, returning its rent to the owner
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner
+    /// 1. `[writable]` Schedule account
+    CancelSchedule,
+
+    /// Request randomness without a subscription. The requester pays the
+    /// quoted fee in lamports, held in the request account until the
+    /// fulfilling oracle collects it. Fulfill and cancel as usual, passing the
+    /// system program in place of the subscription account.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Requester, pays the fee and the request account's rent
+    /// 1. `[writable]` Request account (PDA of [crate::state::DIRECT_REQUEST_SEED], requester, seed)
+    /// 2. `[]` Coordinator config account (PDA), refusing the request while paused
+    /// 3. `[]` System program
+    /// 4. `[]` (Optional) Oracle config of the oracle assigned to this request,
+    ///    refused once the coordinator has an oracle rotation
+    RequestRandomnessDirect {
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        callback_gas_limit: u64,
+    },
+
+    /// Create the coordinator stats account, see [crate::state::CoordinatorStats].
+    /// Request, fulfillment and cancellation instructions update it when it
+    /// is passed after their other accounts, in any position among the
+    /// optional ones. Anyone may call this.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[writable]` Stats account (PDA)
+    /// 2. `[]` System program
+    InitializeStats,
+
+    /// Request randomness for an alpha string of up to
+    /// [crate::state::MAXIMUM_ALPHA_LEN] bytes, such as a match ID and its
+    /// participants, instead of a fixed seed. The request seed is the
+    /// commitment [crate::state::RandomnessRequest::alpha_seed] to it.
+    /// Accounts expected:
+    /// As for `RequestRandomness`
+    RequestRandomnessWithAlpha {
+        alpha: Vec<u8>,
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+    },
+
+    /// Request randomness for a seed mixed from `entropy`, the request
+    /// address, the subscription nonce and the newest slot hash, see
+    /// [crate::entropy]
+    /// Accounts expected:
+    /// 0-3. As for `RequestRandomness`
+    /// 4. `[]` SlotHashes sysvar
+    /// 5. `[]` (Optional) Oracle config of the oracle assigned to this request,
+    ///    refused once the coordinator has an oracle rotation
+    /// 5+. `[]` Coordinator config account (PDA), as for `RequestRandomness`
+    RequestRandomnessWithEntropy {
+        entropy: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+    },
+
+    /// Request randomness in open market mode: the subscription pays the
+    /// fulfilling oracle the price of `fee_auction` at the fulfillment slot in
+    /// place of the fee schedule, see [crate::state::FeeAuction]. The highest
+    /// price is escrowed from the subscription balance as the quoted fee of
+    /// `RequestRandomness` would be.
+    /// Accounts expected:
+    /// As for `RequestRandomness`
+    RequestRandomnessOpenMarket {
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        fee_auction: FeeAuction,
+    },
+
+    /// Hand the coordinator admin authority to `new_admin`, which may be a
+    /// governance PDA, see [crate::governance]
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Coordinator admin, pays for the audit log unless a payer is passed
+    /// 1. `[writable]` Coordinator config account (PDA)
+    /// 2. `[]` System program
+    /// 3. `[writable]` Admin audit log (PDA), see [crate::audit_log]
+    /// 4. `[signer, writable]` (Optional) Payer
+    SetAdmin {
+        new_admin: Pubkey,
+    },
+
+    /// Replace the fee schedule requests are charged under
+    /// Accounts expected:
+    /// As for `SetAdmin`
+    SetFeeSchedule {
+        fee_schedule: FeeSchedule,
+    },
+
+    /// Expire a request still pending, or with its words only partly
+    /// appended, at its `expiration_slot`, refunding it as `CancelRequest`
+    /// does. Anyone may call it.
+    /// Accounts expected:
+    /// 0. `[writable]` Request account
+    /// 1. `[writable]` Subscription account, or the system program for a direct request
+    /// 2. `[writable]` Requester, receives the request account's rent and any unspent fee
+    ExpireRequest,
+
+    /// Bound the confirmations requests may ask for
+    /// Accounts expected:
+    /// As for `SetAdmin`
+    SetConfirmations {
+        min_confirmations: u8,
+        max_confirmations: u8,
+    },
+
+    /// Pause or resume new requests. Pending requests may still be fulfilled,
+    /// cancelled and expired.
+    /// Accounts expected:
+    /// As for `SetAdmin`
+    SetPaused {
+        paused: bool,
+    },
+
+    /// Approve `consumer` to open requests against the subscription, which
+    /// grows to fit it. Only the owner and approved consumers may request.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Subscription owner, pays the rent for the growth
+    /// 1. `[writable]` Subscription account
+    /// 2. `[]` System program
+    AddConsumer {
+        consumer: Pubkey,
+    },
+
+    /// Revoke `consumer`. Pending requests it opened may still be fulfilled.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Subscription owner, receives the freed rent
+    /// 1. `[writable]` Subscription account
+    RemoveConsumer {
+        consumer: Pubkey,
+    },
+
+    /// Offer the subscription to `new_owner`, who takes it over with
+    /// `AcceptSubscriptionOwnership`. A later offer replaces this one; the
+    /// default key withdraws it.
+    /// Accounts expected:
+    /// 0. `[signer]` Subscription owner
+    /// 1. `[writable]` Subscription account
+    RequestSubscriptionOwnershipTransfer {
+        new_owner: Pubkey,
+    },
+
+    /// Accept a pending ownership transfer
+    /// Accounts expected:
+    /// 0. `[signer]` New owner the subscription was offered to
+    /// 1. `[writable]` Subscription account
+    AcceptSubscriptionOwnership,
+
+    /// Fulfill several requests in one transaction, each as `FulfillRandomness`
+    /// does, with the oracle's ECVRF proof over its seed in the Ristretto
+    /// suite. `proofs` holds one proof per request, at most
+    /// [crate::state::MAXIMUM_BATCH_FULFILLMENTS].
+    /// The batch fails as a whole if any request does. The optional trailing
+    /// accounts are shared by every request, so a backstop oracle taking over
+    /// a request fulfills it with `FulfillRandomness`.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Oracle, collects the fees of direct requests
+    /// 1. `[writable]` Oracle config account (PDA) of the signing oracle
+    /// 2. `[]` System program
+    /// 3. `[writable]` Request account of the first request
+    /// 4. `[writable]` Its VRF result account (PDA)
+    /// 5. `[writable]` Its subscription account, or the system program for a direct request
+    /// 6. `[]` Its game program
+    /// 7. `[writable]` Its game state account
+    ///
+    /// 8+. The same five accounts for each further request, in the order of `proofs`
+    /// 8+. `[]` Coordinator config and optional stats accounts (PDA), after the last request
+    FulfillRandomnessBatch {
+        proofs: Vec<Vec<u8>>,
+    },
+
+    /// Create the request pool of a subscription, see [crate::state::RequestPool].
+    /// Request instructions list their request in it, and fulfillment,
+    /// cancellation and expiry drop it, when the pool is passed after their
+    /// other accounts, in any position among the optional ones. Anyone may
+    /// call this.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[writable]` Request pool account (PDA)
+    /// 2. `[]` Subscription account
+    /// 3. `[]` System program
+    InitializeRequestPool,
+
+    /// Replace the oracle rotation, see
+    /// [crate::state::CoordinatorConfig::rotation_oracle]. Requests opened
+    /// without an oracle config are assigned to an oracle of the rotation by
+    /// their address, and other oracles may only fulfill them as a backstop,
+    /// once the subscription's backstop timeout has elapsed. While the
+    /// rotation is not empty, requests may not name their oracle. An empty
+    /// rotation lets any oracle fulfill them. The config grows or shrinks to
+    /// fit the rotation.
+    /// Accounts expected:
+    /// As for `SetAdmin`, the admin or payer funding the config's growth
+    SetOracleRotation {
+        oracles: Vec<Pubkey>,
+    },
+
+    /// Request randomness derived from the ECVRF outputs of
+    /// `required_oracle_responses` distinct oracles, at most
+    /// [crate::state::MAXIMUM_ORACLE_RESPONSES], so that no single compromised
+    /// oracle key decides it. Oracles answer with `SubmitOracleResponse`, and
+    /// the request is not assigned to any one of them.
+    /// Accounts expected:
+    /// As for `RequestRandomness`
+    RequestRandomnessThreshold {
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        required_oracle_responses: u8,
+    },
+
+    /// Answer a threshold request with the oracle's ECVRF proof over its seed,
+    /// checked against its registered VRF key and recorded in the request's
+    /// responses account, see [crate::state::OracleResponses]. The response
+    /// completing the quorum fulfills the request as `FulfillRandomness` does,
+    /// with the hash of every recorded output, and that oracle collects the fee.
+    /// Accounts expected:
+    /// 0-8. As for `FulfillRandomness`
+    /// 9. `[writable]` Oracle responses account (PDA), created by the first response
+    /// 10+. The trailing accounts of `FulfillRandomness`
+    SubmitOracleResponse {
+        proof: Vec<u8>,
+    },
+
+    /// Rewrite a coordinator account written at an older layout at its type's
+    /// current one, see [crate::state::migrations]. Accounts at an older
+    /// layout are refused by every other instruction until migrated. Anyone
+    /// may migrate an account, and migrating a current one does nothing.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Payer funding the account's growth
+    /// 1. `[writable]` Account to migrate
+    /// 2. `[]` System program
+    MigrateAccount,
+
+    /// Fund a subscription with plain lamports, held in its vault, without
+    /// wrapped SOL or token accounts. Fees are charged to the SPL
+    /// balance first and to the native balance once it runs out. The first
+    /// funding also pays the vault's rent-exempt reserve.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Funder
+    /// 1. `[writable]` Subscription account
+    /// 2. `[writable]` Subscription vault (PDA)
+    /// 3. `[]` System program
+    FundSubscriptionNative {
+        amount: u64,
+    },
+
+    /// Emit events by self-invocation rather than to the log, see
+    /// [crate::event]. While enabled, requests and fulfillments that pass the
+    /// config must end with the event accounts.
+    /// Accounts expected:
+    /// As for `SetAdmin`
+    SetCpiEvents {
+        enabled: bool,
+    },
+
+    /// Set the smallest nonzero `priority_fee` a request may tip
+    /// Accounts expected:
+    /// As for `SetAdmin`
+    SetMinPriorityFee {
+        min_priority_fee: u64,
+    },
+
+    /// Limit how many requests a subscription may open in one slot and have
+    /// pending at once, zero for no limit. Requests past either limit fail
+    /// with `RateLimited`.
+    /// Accounts expected:
+    /// 0. `[signer]` Subscription owner
+    /// 1. `[writable]` Subscription account
+    SetRateLimits {
+        max_requests_per_slot: u32,
+        max_pending_requests: u32,
+    },
+
+    /// Create the signer's beacon stream, see [crate::state::BeaconStream].
+    /// Each drand round published with the stream is written into it and,
+    /// unless `callback_program` is the default pubkey, passed to the
+    /// callback program with `callback_data` and the accounts:
+    /// 0. `[]` Beacon stream account
+    /// 1. `[]` Drand round account
+    /// 2. `[writable]` Callback account
+    ///
+    /// The callback is skipped when fewer than `callback_gas_limit` compute
+    /// units are left.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner, pays for the stream account
+    /// 1. `[writable]` Beacon stream account (PDA)
+    /// 2. `[]` System program
+    CreateBeaconStream {
+        callback_program: Pubkey,
+        callback_account: Pubkey,
+        callback_gas_limit: u64,
+        callback_data: Vec<u8>,
+    },
+
+    /// Close a beacon stream, returning its rent to the owner
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner
+    /// 1. `[writable]` Beacon stream account
+    CloseBeaconStream,
+
+    /// Pay an oracle `amount` of its accrued fees in lamports from a
+    /// subscription's vault, out of what the subscription was charged from
+    /// its native balance: the vault's lamports above its reserve and the
+    /// `native_balance`. An oracle may claim from any subscription, and the
+    /// claims of all oracles together never exceed what subscriptions were
+    /// charged. Deactivated oracles may still claim.
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Oracle, receives the fees
+    /// 1. `[writable]` Oracle config account (PDA)
+    /// 2. `[]` Subscription account
+    /// 3. `[writable]` Subscription vault (PDA)
+    /// 4. `[]` System program
+    ClaimOracleFeesNative {
+        amount: u64,
+    },
+
+    /// Withdraw `amount` of a subscription's native balance from its vault
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Subscription owner, receives the lamports
+    /// 1. `[writable]` Subscription account
+    /// 2. `[writable]` Subscription vault (PDA)
+    /// 3. `[]` System program
+    WithdrawSubscriptionNative {
+        amount: u64,
+    },
+
+    /// Pay an oracle `amount` of its accrued fees in tokens from a
+    /// subscription's vault, out of what the subscription was charged from
+    /// its SPL balance: the vault token account's amount above the
+    /// `balance`. As `ClaimOracleFeesNative` otherwise.
+    /// Accounts expected:
+    /// 0. `[signer]` Oracle
+    /// 1. `[writable]` Oracle config account (PDA)
+    /// 2. `[]` Subscription account
+    /// 3. `[writable]` Subscription token account, the vault's associated token account
+    /// 4. `[writable]` Oracle token account, receives the fees
+    /// 5. `[]` Subscription vault (PDA)
+    /// 6. `[]` SPL Token program
+    ClaimOracleFees {
+        amount: u64,
+    },
+
+    /// Withdraw `amount` of a subscription's SPL balance from its vault's
+    /// token account
+    /// Accounts expected:
+    /// 0. `[signer]` Subscription owner
+    /// 1. `[writable]` Subscription account
+    /// 2. `[writable]` Subscription token account, the vault's associated token account
+    /// 3. `[writable]` Owner token account, receives the tokens
+    /// 4. `[]` Subscription vault (PDA)
+    /// 5. `[]` SPL Token program
+    WithdrawSubscription {
+        amount: u64,
+    },
+
+    /// Set the group key of the drand evmnet chain `PublishDrandRound`
+    /// verifies rounds against
+    /// Accounts expected:
+    /// As for `SetAdmin`
+    SetDrandPublicKey {
+        public_key: [u8; DRAND_PUBLIC_KEY_LEN],
+    },
 }
 
 impl VrfCoordinatorInstruction {
@@ -7,13 +7,50 @@ use {
     },
 };
 
+/// `msg!` that only logs when built with the `verbose-logs` feature. Events and
+/// error messages are logged regardless.
+macro_rules! verbose_msg {
+    ($($arg:tt)+) => {
+        if cfg!(feature = "verbose-logs") {
+            ::solana_program::msg!($($arg)+);
+        }
+    };
+}
+
+pub mod attestation;
+pub mod audit_log;
+pub mod chainlink;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod commit_reveal_consumer;
+pub mod cpi;
+pub mod drand;
+pub mod entropy;
 pub mod error;
 pub mod event;
+pub mod example_consumer;
+pub mod governance;
+pub mod groth16;
+#[cfg(feature = "idl")]
+pub mod idl;
+#[cfg(test)]
+mod fixtures;
 pub mod instruction;
+pub mod loader;
+pub mod pda;
 pub mod processor;
+pub mod randomness;
+pub mod receipt;
+#[cfg(feature = "client")]
+pub mod snapshot;
 pub mod state;
+pub mod switchboard;
+#[cfg(test)]
+mod test_harness;
+pub mod verifier;
 pub mod mock_prover;
 
+#[cfg(not(feature = "no-entrypoint"))]
 entrypoint!(process_instruction);
 
 pub fn process_instruction(
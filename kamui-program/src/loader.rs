@@ -0,0 +1,246 @@
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    bytemuck::Pod,
+    crate::{error::VrfCoordinatorError, state::{ACCOUNT_HEADER_LEN, DISCRIMINATOR_LEN}},
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+/// State stored in an account as an 8-byte discriminator and a one-byte
+/// layout version followed by the borsh-serialized struct
+pub trait AccountState: BorshSerialize + BorshDeserialize {
+    /// Tag identifying the type of the account
+    const DISCRIMINATOR: [u8; 8];
+    /// Version of the type's layout. Bump it with every change to the layout
+    /// and teach [crate::state::migrations] to upgrade accounts from the
+    /// previous one; accounts at another version are refused until migrated.
+    const VERSION: u8 = 1;
+    /// Smallest valid account size, including the discriminator
+    const MIN_LEN: usize;
+}
+
+/// Checks that `data` is large enough and carries `T`'s discriminator and
+/// layout version, without deserializing it
+pub fn check_data<T: AccountState>(data: &[u8]) -> ProgramResult {
+    if data.len() < ACCOUNT_HEADER_LEN {
+        return Err(VrfCoordinatorError::AccountTooSmall.into());
+    }
+    if data[..DISCRIMINATOR_LEN] != T::DISCRIMINATOR {
+        return Err(VrfCoordinatorError::InvalidDiscriminator.into());
+    }
+    // Checked before the size, since accounts at an older layout may be smaller
+    if data[DISCRIMINATOR_LEN] != T::VERSION {
+        msg!("VRF Coordinator: Error - Account layout version {} is not the current {}",
+            data[DISCRIMINATOR_LEN], T::VERSION);
+        return Err(VrfCoordinatorError::AccountNotMigrated.into());
+    }
+    if data.len() < T::MIN_LEN {
+        return Err(VrfCoordinatorError::AccountTooSmall.into());
+    }
+    Ok(())
+}
+
+/// Checks that `account` is owned by `owner` and its data with [check_data]
+pub fn check<T: AccountState>(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if account.owner != owner {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    check_data::<T>(&account.try_borrow_data()?)
+}
+
+/// Deserializes `account` as `T` after checking it with [check]
+pub fn load<T: AccountState>(account: &AccountInfo, owner: &Pubkey) -> Result<T, ProgramError> {
+    check::<T>(account, owner)?;
+    let data = account.try_borrow_data()?;
+    T::deserialize(&mut &data[ACCOUNT_HEADER_LEN..]).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// Like [load], and additionally requires `account` to be writable so the
+/// value can be written back with [store]
+pub fn load_mut<T: AccountState>(account: &AccountInfo, owner: &Pubkey) -> Result<T, ProgramError> {
+    if !account.is_writable {
+        return Err(ProgramError::InvalidArgument);
+    }
+    load(account, owner)
+}
+
+/// Writes `value` and its header to `account`
+pub fn store<T: AccountState>(account: &AccountInfo, value: &T) -> ProgramResult {
+    let mut data = account.try_borrow_mut_data()?;
+    if data.len() < T::MIN_LEN {
+        return Err(VrfCoordinatorError::AccountTooSmall.into());
+    }
+    data[..DISCRIMINATOR_LEN].copy_from_slice(&T::DISCRIMINATOR);
+    data[DISCRIMINATOR_LEN] = T::VERSION;
+    value.serialize(&mut &mut data[ACCOUNT_HEADER_LEN..])
+        .map_err(|_| VrfCoordinatorError::AccountTooSmall.into())
+}
+
+/// Fixed-size leading fields of an [AccountState], read and written in place
+/// without deserializing the rest of the account. The header is a packed `Pod`
+/// struct laid out as the borsh encoding of those fields, so both views of the
+/// account agree.
+pub trait AccountHeader: Pod {
+    /// Account the header is the leading fields of
+    type State: AccountState;
+}
+
+/// Copies the header of `account` out of its data after checking the account
+/// as `H::State` with [check]
+pub fn load_header<H: AccountHeader>(account: &AccountInfo, owner: &Pubkey) -> Result<H, ProgramError> {
+    check::<H::State>(account, owner)?;
+    let data = account.try_borrow_data()?;
+    Ok(bytemuck::pod_read_unaligned(&data[ACCOUNT_HEADER_LEN..ACCOUNT_HEADER_LEN + std::mem::size_of::<H>()]))
+}
+
+/// Like [load_header], and additionally requires `account` to be writable so
+/// the header can be written back with [store_header]
+pub fn load_header_mut<H: AccountHeader>(account: &AccountInfo, owner: &Pubkey) -> Result<H, ProgramError> {
+    if !account.is_writable {
+        return Err(ProgramError::InvalidArgument);
+    }
+    load_header(account, owner)
+}
+
+/// Writes `header` over the leading fields of `account`, leaving the rest of
+/// its data as is
+pub fn store_header<H: AccountHeader>(account: &AccountInfo, header: &H) -> ProgramResult {
+    let mut data = account.try_borrow_mut_data()?;
+    if data.len() < H::State::MIN_LEN {
+        return Err(VrfCoordinatorError::AccountTooSmall.into());
+    }
+    data[ACCOUNT_HEADER_LEN..ACCOUNT_HEADER_LEN + std::mem::size_of::<H>()].copy_from_slice(bytemuck::bytes_of(header));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::state::{Subscription, SubscriptionHeader},
+        solana_program::clock::Epoch,
+    };
+
+    fn subscription() -> Subscription {
+        Subscription {
+            owner: Pubkey::new_unique(),
+            balance: 1,
+            min_balance: 2,
+            confirmations: 3,
+            nonce: 4,
+            backstop_timeout_slots: 5,
+            backstop_bonus: 6,
+            total_funded: 7,
+            total_credited: 8,
+            total_debited: 9,
+            audited_nonce: 4,
+            pending_owner: Pubkey::default(),
+            native_balance: 10,
+            max_requests_per_slot: 0,
+            max_pending_requests: 0,
+            last_request_slot: 0,
+            requests_in_slot: 0,
+            pending_requests: 0,
+            consumers: vec![],
+        }
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![0u8; Subscription::LEN];
+        let account = AccountInfo::new(
+            &key, false, true, &mut lamports, &mut data, &program_id, false, Epoch::default(),
+        );
+
+        let value = subscription();
+        store(&account, &value).unwrap();
+        assert_eq!(account.data.borrow()[0..8], *b"SUBSCRIP");
+        assert_eq!(account.data.borrow()[8], Subscription::VERSION);
+        let loaded: Subscription = load_mut(&account, &program_id).unwrap();
+        assert_eq!(loaded.owner, value.owner);
+        assert_eq!(loaded.nonce, value.nonce);
+
+        // Wrong owner
+        assert_eq!(
+            load::<Subscription>(&account, &Pubkey::new_unique()).unwrap_err(),
+            ProgramError::IncorrectProgramId
+        );
+
+        // Outdated layout
+        account.data.borrow_mut()[8] = 0;
+        assert_eq!(
+            load::<Subscription>(&account, &program_id).unwrap_err(),
+            VrfCoordinatorError::AccountNotMigrated.into()
+        );
+
+        // Wrong discriminator
+        account.data.borrow_mut()[0] ^= 1;
+        assert_eq!(
+            load::<Subscription>(&account, &program_id).unwrap_err(),
+            VrfCoordinatorError::InvalidDiscriminator.into()
+        );
+    }
+
+    #[test]
+    fn test_header_updates_in_place() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut value = subscription();
+        value.consumers = vec![Pubkey::new_unique()];
+        let mut data = vec![0u8; Subscription::space(1)];
+        let account = AccountInfo::new(
+            &key, false, true, &mut lamports, &mut data, &program_id, false, Epoch::default(),
+        );
+        store(&account, &value).unwrap();
+
+        let mut header: SubscriptionHeader = load_header_mut(&account, &program_id).unwrap();
+        assert_eq!(header.owner, value.owner);
+        assert_eq!({ header.nonce }, value.nonce);
+        assert_eq!(header.pending_owner, value.pending_owner);
+        assert!(header.is_consumer(&account.data.borrow(), &value.owner));
+        assert!(header.is_consumer(&account.data.borrow(), &value.consumers[0]));
+        assert!(!header.is_consumer(&account.data.borrow(), &Pubkey::new_unique()));
+        header.balance = 10;
+        store_header(&account, &header).unwrap();
+
+        let loaded: Subscription = load(&account, &program_id).unwrap();
+        assert_eq!(loaded.balance, 10);
+        assert_eq!(loaded.nonce, value.nonce);
+        assert_eq!(loaded.consumers, value.consumers);
+    }
+
+    #[test]
+    fn test_load_rejects_short_and_readonly_accounts() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![0u8; Subscription::LEN - 1];
+        data[..DISCRIMINATOR_LEN].copy_from_slice(&Subscription::DISCRIMINATOR);
+        data[DISCRIMINATOR_LEN] = Subscription::VERSION;
+        let account = AccountInfo::new(
+            &key, false, false, &mut lamports, &mut data, &program_id, false, Epoch::default(),
+        );
+
+        assert_eq!(
+            load::<Subscription>(&account, &program_id).unwrap_err(),
+            VrfCoordinatorError::AccountTooSmall.into()
+        );
+        assert_eq!(
+            load_mut::<Subscription>(&account, &program_id).unwrap_err(),
+            ProgramError::InvalidArgument
+        );
+        assert_eq!(
+            store(&account, &subscription()).unwrap_err(),
+            VrfCoordinatorError::AccountTooSmall.into()
+        );
+    }
+}
@@ -4,16 +4,16 @@ use {
         instruction::VrfCoordinatorInstruction,
         event::VrfEvent,
         state::{OracleConfig, RandomnessRequest, RequestStatus, VrfResult},
+        verifier::VrfSuite,
     },
     borsh::{BorshDeserialize, BorshSerialize},
     mangekyou::kamui_vrf::{
-        ecvrf::{ECVRFKeyPair, ECVRFProof},
-        VRFProof,
-        VRFKeyPair,
+        ecvrf::ECVRFKeyPair,
         KamuiVrf,
         KamuiVrfProof,
         KamuiVrfPublicKey,
     },
+    mangekyou::signer::VrfSigner,
     solana_program::{
         instruction::{AccountMeta, Instruction},
         pubkey::Pubkey,
@@ -29,12 +29,13 @@ use {
         transaction::Transaction,
         hash::Hash,
     },
-    base64::Engine,
 };
 
 #[cfg(feature = "mock")]
 pub struct MockProver {
-    pub keypair: ECVRFKeyPair,
+    /// Proves the requests this prover fulfills, with a key in memory or
+    /// held by a remote signer
+    pub signer: Box<dyn VrfSigner + Send + Sync>,
     pub program_id: Pubkey,
     pub banks_client: BanksClient,
     pub payer: Keypair,
@@ -45,6 +46,11 @@ pub struct MockProver {
 #[cfg(feature = "mock")]
 impl MockProver {
     pub async fn new() -> Self {
+        let keypair = ECVRFKeyPair::from_seed(&[0u8; 32]);
+        Self::with_signer(Box::new(keypair)).await
+    }
+
+    pub async fn with_signer(signer: Box<dyn VrfSigner + Send + Sync>) -> Self {
         let program_id = Pubkey::new_unique();
         let program_test = ProgramTest::new(
             "kamui_program",
@@ -53,10 +59,9 @@ impl MockProver {
         );
 
         let (banks_client, payer, recent_blockhash) = program_test.start().await;
-        let keypair = ECVRFKeyPair::from_bytes(&[0u8; 32]).unwrap();
 
         Self {
-            keypair,
+            signer,
             program_id,
             banks_client,
             payer,
@@ -66,13 +71,7 @@ impl MockProver {
     }
 
     pub fn parse_vrf_event(log_msg: &str) -> Option<VrfEvent> {
-        if !log_msg.starts_with("VRF_EVENT:") {
-            return None;
-        }
-
-        let base64_data = log_msg.trim_start_matches("VRF_EVENT:").trim();
-        let event_data = base64::engine::general_purpose::STANDARD.decode(base64_data).ok()?;
-        VrfEvent::try_from_slice(&event_data).ok()
+        VrfEvent::from_log(log_msg)
     }
 
     pub async fn process_randomness_request(
@@ -82,9 +81,9 @@ impl MockProver {
         seed: [u8; 32],
     ) -> Result<(), Box<dyn std::error::Error>> {
         // Generate VRF proof
-        let proof = self.keypair.prove(&seed);
-        let proof_bytes = <ECVRFProof as VRFProof<64>>::to_bytes(&proof);
-        let public_key = self.keypair.pk.as_ref().to_vec();
+        let proof = self.signer.prove(&seed)?;
+        let proof_bytes = proof.to_bytes_legacy();
+        let public_key = self.signer.public_key().as_ref().to_vec();
 
         // Create VRF result account
         let vrf_result = Keypair::new();
@@ -108,6 +107,7 @@ impl MockProver {
             data: borsh::to_vec(&VrfCoordinatorInstruction::FulfillRandomness {
                 proof: proof_bytes,
                 public_key,
+                suite: VrfSuite::default(),
             })?,
         };
 
@@ -0,0 +1,65 @@
+//! Addresses of the coordinator's program-derived accounts.
+//!
+//! The coordinator, consumers and oracles derive these the same way, so they
+//! should call these helpers rather than spell out the seeds. Each returns
+//! the address and its bump, as [Pubkey::find_program_address] does. The
+//! seeds are re-exported here for programs that sign for or list them.
+
+use {
+    crate::state::{CoordinatorConfig, OracleConfig},
+    solana_program::pubkey::Pubkey,
+};
+
+pub use crate::state::{DIRECT_REQUEST_SEED, SUBSCRIPTION_VAULT_SEED};
+
+/// PDA seed prefix of a subscription's requests, followed by the subscription
+/// and the request's nonce
+pub const REQUEST_SEED: &[u8] = b"request";
+
+/// PDA seed prefix of a request's VRF result, followed by the request
+/// account
+pub const VRF_RESULT_SEED: &[u8] = b"vrf_result";
+
+/// Address and bump of the request `subscription` opens at `nonce`, the
+/// subscription's nonce when the request is made
+pub fn derive_request_pda(program_id: &Pubkey, subscription: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REQUEST_SEED, subscription.as_ref(), &nonce.to_le_bytes()], program_id)
+}
+
+/// Address and bump of the direct request `requester` opens for `seed`
+pub fn derive_direct_request_pda(program_id: &Pubkey, requester: &Pubkey, seed: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DIRECT_REQUEST_SEED, requester.as_ref(), seed], program_id)
+}
+
+/// Address and bump of the account the fulfillment of the request at
+/// `request` writes its randomness to. Each request has its own result, so
+/// concurrent requests of one requester never overwrite each other's words.
+pub fn derive_vrf_result_pda(program_id: &Pubkey, request: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VRF_RESULT_SEED, request.as_ref()], program_id)
+}
+
+/// Address and bump of the result account `requester`'s requests shared
+/// before results were keyed by request. Fulfillments no longer write there;
+/// results left at it are read with [load_legacy_vrf_result] and upgraded
+/// in place by `MigrateAccount`.
+///
+/// [load_legacy_vrf_result]: crate::cpi::load_legacy_vrf_result
+pub fn derive_legacy_vrf_result_pda(program_id: &Pubkey, requester: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VRF_RESULT_SEED, requester.as_ref()], program_id)
+}
+
+/// Address and bump of the config of the oracle `oracle_key`
+pub fn derive_oracle_config_pda(program_id: &Pubkey, oracle_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OracleConfig::SEED, oracle_key.as_ref()], program_id)
+}
+
+/// Address and bump of the coordinator config
+pub fn derive_config_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CoordinatorConfig::SEED], program_id)
+}
+
+/// Address and bump of the vault of `subscription`, see
+/// [SUBSCRIPTION_VAULT_SEED]
+pub fn derive_subscription_vault_pda(program_id: &Pubkey, subscription: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SUBSCRIPTION_VAULT_SEED, subscription.as_ref()], program_id)
+}
@@ -1,34 +1,82 @@
 use {
-    borsh::{BorshDeserialize, BorshSerialize},
+    borsh::BorshDeserialize,
     crate::{
         instruction::VrfCoordinatorInstruction,
-        state::{RandomnessRequest, RequestStatus, Subscription, VrfResult, OracleConfig},
-        event::VrfEvent,
+        state::{RandomnessRequest, RandomnessRequestHeader, RequestId, RequestStatus, Subscription, SubscriptionHeader, VrfResult, OracleConfig, CoordinatorConfig, CoordinatorStats, PooledRequest, RequestPool, OracleResponses, FeeAuction, FeeSchedule, DrandRound, BeaconEntry, BeaconStream, Schedule, migrations, BEACON_STREAM_CAPACITY, DEFAULT_BACKSTOP_TIMEOUT_SLOTS, DIRECT_REQUEST_SEED, DISCRIMINATOR_LEN, FUNDING_MINT, DRAND_PUBLIC_KEY_LEN, DRAND_ROUND_SEED, DRAND_SIGNATURE_LEN, MAXIMUM_BATCH_FULFILLMENTS, MAXIMUM_ORACLE_RESPONSES, MAXIMUM_REQUEST_CONFIRMATIONS, MINIMUM_REQUEST_CONFIRMATIONS, PROOF_LEN, SUBSCRIPTION_VAULT_SEED, subscription_token_address, subscription_vault_address},
+        event::{event_authority_address, EventEmitter, VrfEvent, EVENT_IX_TAG},
         error::VrfCoordinatorError,
+        loader::{check, load, load_header, load_header_mut, load_mut, store, store_header},
+        pda::{
+            derive_config_pda, derive_direct_request_pda, derive_oracle_config_pda, derive_request_pda,
+            derive_subscription_vault_pda, derive_vrf_result_pda, REQUEST_SEED, VRF_RESULT_SEED,
+        },
+        randomness::expand_word,
+        drand,
+        entropy,
+        chainlink::{self, FulfillRandomWords, FULFILL_RANDOM_WORDS_SELECTOR},
+        switchboard::{RandomnessAccountData, RANDOMNESS_SEED},
+        attestation::{self, Attestation, ATTESTATION_VERSION, EMITTER_SEED},
+        audit_log::{self, AdminAction, AdminAuditEntry, AdminAuditLog, ADMIN_AUDIT_LOG_SEED},
+        groth16::{self, Groth16Proof, Groth16VerifyingKey, ZK_VERIFYING_KEY_SEED},
+        verifier::VrfSuite,
     },
+    mangekyou_core::ecvrf,
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        compute_units::sol_remaining_compute_units,
         entrypoint::ProgramResult,
+        hash::hash,
         instruction::{AccountMeta, Instruction},
         msg,
-        program::{invoke, invoke_signed},
+        program::{invoke, invoke_signed, set_return_data},
+        program_pack::Pack,
         program_error::ProgramError,
         pubkey::Pubkey,
         system_instruction,
-        sysvar::{rent::Rent, Sysvar},
+        sysvar::{clock::Clock, rent::Rent, Sysvar},
     },
 };
+use spl_associated_token_account::get_associated_token_address;
 use spl_token::instruction as token_instruction;
 
 pub struct Processor;
 
+/// Accounts per request in `FulfillRandomnessBatch`
+const BATCH_ENTRY_ACCOUNTS: usize = 5;
+
+/// Optional trailing accounts of the fulfillment instructions, told apart by
+/// address or account type
+struct OptionalAccounts<'a, 'b> {
+    config: Option<CoordinatorConfig>,
+    stats_account: Option<&'b AccountInfo<'a>>,
+    randomness_account: Option<&'b AccountInfo<'a>>,
+    assigned_oracle_config: Option<&'b AccountInfo<'a>>,
+    request_pool: Option<&'b AccountInfo<'a>>,
+}
+
+/// What a single-transaction fulfillment carries to prove its output
+enum FulfillmentProof {
+    /// The ECVRF proof itself, with the public key the oracle proved under
+    /// and the suite it proved in
+    Ecvrf { proof: Vec<u8>, public_key: Vec<u8>, suite: VrfSuite },
+    /// The output, with a Groth16 proof that it is the ECVRF output
+    Zk { output: [u8; 64], proof: Box<Groth16Proof> },
+    /// One oracle's ECVRF proof towards a threshold request
+    Threshold { proof: Vec<u8> },
+}
+
 impl Processor {
     pub fn process(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         instruction_data: &[u8],
     ) -> ProgramResult {
-        msg!("VRF Coordinator: Processing instruction");
+        verbose_msg!("VRF Coordinator: Processing instruction");
+        if instruction_data.starts_with(&EVENT_IX_TAG) {
+            return Self::process_event_cpi(program_id, accounts);
+        }
+        let (accounts, emitter) = EventEmitter::split(program_id, accounts);
+        let events = &emitter;
         let instruction = VrfCoordinatorInstruction::try_from_slice(instruction_data)
             .map_err(|e| {
                 msg!("VRF Coordinator: Failed to deserialize instruction: {}", e);
@@ -42,37 +90,314 @@ impl Processor {
                 num_words,
                 minimum_confirmations,
                 callback_gas_limit,
+                priority_fee,
+            } => {
+                verbose_msg!("VRF Coordinator: RequestRandomness - seed: {:?}, num_words: {}, min_confirmations: {}, gas_limit: {}, priority_fee: {}", 
+                    seed, num_words, minimum_confirmations, callback_gas_limit, priority_fee);
+                Self::process_request_randomness(program_id, accounts, seed, callback_data, num_words, minimum_confirmations, callback_gas_limit, 0, priority_fee, FeeAuction::default(), 1, events)
+            }
+            VrfCoordinatorInstruction::RequestRandomnessAt {
+                seed,
+                callback_data,
+                num_words,
+                minimum_confirmations,
+                callback_gas_limit,
+                not_before_slot,
+            } => {
+                verbose_msg!("VRF Coordinator: RequestRandomnessAt - seed: {:?}, num_words: {}, not_before_slot: {}", 
+                    seed, num_words, not_before_slot);
+                Self::process_request_randomness(program_id, accounts, seed, callback_data, num_words, minimum_confirmations, callback_gas_limit, not_before_slot, 0, FeeAuction::default(), 1, events)
+            }
+            VrfCoordinatorInstruction::RequestRandomnessDirect { seed, callback_data, num_words, callback_gas_limit } => {
+                verbose_msg!("VRF Coordinator: RequestRandomnessDirect - seed: {:?}, num_words: {}, gas_limit: {}",
+                    seed, num_words, callback_gas_limit);
+                Self::process_request_randomness_direct(program_id, accounts, seed, callback_data, num_words, callback_gas_limit, events)
+            }
+            VrfCoordinatorInstruction::RequestRandomnessWithAlpha {
+                alpha,
+                callback_data,
+                num_words,
+                minimum_confirmations,
+                callback_gas_limit,
+            } => {
+                verbose_msg!("VRF Coordinator: RequestRandomnessWithAlpha - alpha: {} bytes, num_words: {}",
+                    alpha.len(), num_words);
+                let seed = RandomnessRequest::alpha_seed(&alpha)?;
+                Self::process_request_randomness(program_id, accounts, seed, callback_data, num_words, minimum_confirmations, callback_gas_limit, 0, 0, FeeAuction::default(), 1, events)
+            }
+            VrfCoordinatorInstruction::RequestRandomnessWithEntropy {
+                entropy,
+                callback_data,
+                num_words,
+                minimum_confirmations,
+                callback_gas_limit,
+            } => {
+                verbose_msg!("VRF Coordinator: RequestRandomnessWithEntropy - num_words: {}", num_words);
+                Self::process_request_randomness_with_entropy(
+                    program_id, accounts, entropy, callback_data, num_words, minimum_confirmations, callback_gas_limit, events)
+            }
+            VrfCoordinatorInstruction::RequestRandomnessOpenMarket {
+                seed,
+                callback_data,
+                num_words,
+                minimum_confirmations,
+                callback_gas_limit,
+                fee_auction,
+            } => {
+                verbose_msg!("VRF Coordinator: RequestRandomnessOpenMarket - seed: {:?}, price: {} to {} over {} slots",
+                    seed, fee_auction.start_price, fee_auction.end_price, fee_auction.duration_slots);
+                Self::process_request_randomness_open_market(
+                    program_id, accounts, seed, callback_data, num_words, minimum_confirmations, callback_gas_limit, fee_auction, events)
+            }
+            VrfCoordinatorInstruction::InitializeStats => {
+                verbose_msg!("VRF Coordinator: InitializeStats");
+                Self::process_initialize_stats(program_id, accounts)
+            }
+            VrfCoordinatorInstruction::RequestRandomWords {
+                key_hash,
+                sub_id,
+                request_confirmations,
+                callback_gas_limit,
+                num_words,
+            } => {
+                verbose_msg!("VRF Coordinator: RequestRandomWords - key_hash: {:?}, sub_id: {}, confirmations: {}, gas_limit: {}, num_words: {}", 
+                    key_hash, sub_id, request_confirmations, callback_gas_limit, num_words);
+                Self::process_request_random_words(program_id, accounts, key_hash, sub_id, request_confirmations, callback_gas_limit, num_words, events)
+            }
+            VrfCoordinatorInstruction::InitRandomnessAccount => {
+                verbose_msg!("VRF Coordinator: InitRandomnessAccount");
+                Self::process_init_randomness_account(program_id, accounts)
+            }
+            VrfCoordinatorInstruction::PublishDrandRound { round, signature } => {
+                verbose_msg!("VRF Coordinator: PublishDrandRound - round: {}", round);
+                Self::process_publish_drand_round(program_id, accounts, round, signature, events)
+            }
+            VrfCoordinatorInstruction::ExportAttestation { nonce, consistency_level } => {
+                verbose_msg!("VRF Coordinator: ExportAttestation - nonce: {}, consistency_level: {}", 
+                    nonce, consistency_level);
+                Self::process_export_attestation(program_id, accounts, nonce, consistency_level)
+            }
+            VrfCoordinatorInstruction::FulfillRandomness { proof, public_key, suite } => {
+                verbose_msg!("VRF Coordinator: FulfillRandomness - proof length: {}, public_key length: {}, suite: {:?}", 
+                    proof.len(), public_key.len(), suite);
+                Self::process_fulfill_randomness(program_id, accounts, proof, public_key, suite, events)
+            }
+            VrfCoordinatorInstruction::FulfillRandomnessZk { output, proof } => {
+                verbose_msg!("VRF Coordinator: FulfillRandomnessZk - output: {:?}", &output[..8]);
+                Self::process_fulfill_randomness_zk(program_id, accounts, output, proof, events)
+            }
+            VrfCoordinatorInstruction::SetZkVerifyingKey { verifying_key } => {
+                verbose_msg!("VRF Coordinator: SetZkVerifyingKey");
+                Self::process_set_zk_verifying_key(program_id, accounts, &verifying_key)
+            }
+            VrfCoordinatorInstruction::AppendWords { proof, num_words } => {
+                verbose_msg!("VRF Coordinator: AppendWords - proof length: {}, num_words: {}", 
+                    proof.len(), num_words);
+                Self::process_append_words(program_id, accounts, proof, num_words)
+            }
+            VrfCoordinatorInstruction::FinalizeFulfillment => {
+                verbose_msg!("VRF Coordinator: FinalizeFulfillment");
+                Self::process_finalize_fulfillment(program_id, accounts, events)
+            }
+            VrfCoordinatorInstruction::InitializeCoordinator { fee_schedule, bridge_program } => {
+                verbose_msg!("VRF Coordinator: InitializeCoordinator - fee_schedule: {:?}, bridge_program: {}", 
+                    fee_schedule, bridge_program);
+                Self::process_initialize_coordinator(program_id, accounts, fee_schedule, bridge_program)
+            }
+            VrfCoordinatorInstruction::GetRequestFee { num_words, callback_gas_limit, priority } => {
+                verbose_msg!("VRF Coordinator: GetRequestFee - num_words: {}, gas_limit: {}, priority: {}", 
+                    num_words, callback_gas_limit, priority);
+                Self::process_get_request_fee(program_id, accounts, num_words, callback_gas_limit, priority)
+            }
+            VrfCoordinatorInstruction::CreateSchedule {
+                seed,
+                interval_slots,
+                start_slot,
+                num_words,
+                callback_gas_limit,
+                callback_data,
+            } => {
+                verbose_msg!("VRF Coordinator: CreateSchedule - interval_slots: {}, start_slot: {}, num_words: {}", 
+                    interval_slots, start_slot, num_words);
+                Self::process_create_schedule(program_id, accounts, seed, interval_slots, start_slot, num_words, callback_gas_limit, callback_data)
+            }
+            VrfCoordinatorInstruction::CrankSchedule => {
+                verbose_msg!("VRF Coordinator: CrankSchedule");
+                Self::process_crank_schedule(program_id, accounts, events)
+            }
+            VrfCoordinatorInstruction::CancelSchedule => {
+                verbose_msg!("VRF Coordinator: CancelSchedule");
+                Self::process_cancel_schedule(program_id, accounts)
+            }
+            VrfCoordinatorInstruction::CreateBeaconStream {
+                callback_program,
+                callback_account,
+                callback_gas_limit,
+                callback_data,
             } => {
-                msg!("VRF Coordinator: RequestRandomness - seed: {:?}, num_words: {}, min_confirmations: {}, gas_limit: {}", 
-                    seed, num_words, minimum_confirmations, callback_gas_limit);
-                Self::process_request_randomness(program_id, accounts, seed, callback_data, num_words, minimum_confirmations, callback_gas_limit)
+                verbose_msg!("VRF Coordinator: CreateBeaconStream - callback_program: {}, callback_gas_limit: {}",
+                    callback_program, callback_gas_limit);
+                Self::process_create_beacon_stream(program_id, accounts, callback_program, callback_account, callback_gas_limit, callback_data)
             }
-            VrfCoordinatorInstruction::FulfillRandomness { proof, public_key } => {
-                msg!("VRF Coordinator: FulfillRandomness - proof length: {}, public_key length: {}", 
-                    proof.len(), public_key.len());
-                Self::process_fulfill_randomness(program_id, accounts, proof, public_key)
+            VrfCoordinatorInstruction::CloseBeaconStream => {
+                verbose_msg!("VRF Coordinator: CloseBeaconStream");
+                Self::process_close_beacon_stream(program_id, accounts)
             }
             VrfCoordinatorInstruction::CreateSubscription { min_balance, confirmations } => {
-                msg!("VRF Coordinator: CreateSubscription - min_balance: {}, confirmations: {}", 
+                verbose_msg!("VRF Coordinator: CreateSubscription - min_balance: {}, confirmations: {}", 
                     min_balance, confirmations);
-                Self::process_create_subscription(program_id, accounts, min_balance, confirmations)
+                Self::process_create_subscription(program_id, accounts, min_balance, confirmations, events)
             }
             VrfCoordinatorInstruction::FundSubscription { amount } => {
-                msg!("VRF Coordinator: FundSubscription - amount: {}", amount);
-                Self::process_fund_subscription(accounts, amount)
+                verbose_msg!("VRF Coordinator: FundSubscription - amount: {}", amount);
+                Self::process_fund_subscription(program_id, accounts, amount, events)
             }
             VrfCoordinatorInstruction::CancelRequest => {
-                msg!("VRF Coordinator: CancelRequest");
-                Self::process_cancel_request(accounts)
+                verbose_msg!("VRF Coordinator: CancelRequest");
+                Self::process_cancel_request(program_id, accounts, events)
             }
             VrfCoordinatorInstruction::RegisterOracle { oracle_key, vrf_key } => {
-                msg!("VRF Coordinator: RegisterOracle - oracle_key: {}, vrf_key: {:?}", 
+                verbose_msg!("VRF Coordinator: RegisterOracle - oracle_key: {}, vrf_key: {:?}", 
                     oracle_key, vrf_key);
-                Self::process_register_oracle(program_id, accounts, oracle_key, vrf_key)
+                Self::process_register_oracle(program_id, accounts, oracle_key, vrf_key, events)
             }
             VrfCoordinatorInstruction::DeactivateOracle { oracle_key } => {
-                msg!("VRF Coordinator: DeactivateOracle - oracle_key: {}", oracle_key);
-                Self::process_deactivate_oracle(program_id, accounts, oracle_key)
+                verbose_msg!("VRF Coordinator: DeactivateOracle - oracle_key: {}", oracle_key);
+                Self::process_deactivate_oracle(program_id, accounts, oracle_key, events)
+            }
+            VrfCoordinatorInstruction::SetBackstopPolicy { timeout_slots, bonus } => {
+                verbose_msg!("VRF Coordinator: SetBackstopPolicy - timeout_slots: {}, bonus: {}", 
+                    timeout_slots, bonus);
+                Self::process_set_backstop_policy(program_id, accounts, timeout_slots, bonus, events)
+            }
+            VrfCoordinatorInstruction::SetRateLimits { max_requests_per_slot, max_pending_requests } => {
+                verbose_msg!("VRF Coordinator: SetRateLimits - max_requests_per_slot: {}, max_pending_requests: {}",
+                    max_requests_per_slot, max_pending_requests);
+                Self::process_set_rate_limits(program_id, accounts, max_requests_per_slot, max_pending_requests, events)
+            }
+            VrfCoordinatorInstruction::AuditSubscription => {
+                verbose_msg!("VRF Coordinator: AuditSubscription");
+                Self::process_audit_subscription(program_id, accounts, events)
+            }
+            VrfCoordinatorInstruction::SetAdmin { new_admin } => {
+                verbose_msg!("VRF Coordinator: SetAdmin - new_admin: {}", new_admin);
+                Self::process_update_config(program_id, accounts, |config| {
+                    config.admin = new_admin;
+                    AdminAction::SetAdmin { new_admin }
+                })
+            }
+            VrfCoordinatorInstruction::SetFeeSchedule { fee_schedule } => {
+                verbose_msg!("VRF Coordinator: SetFeeSchedule - fee_schedule: {:?}", fee_schedule);
+                Self::process_update_config(program_id, accounts, |config| {
+                    config.fee_schedule = fee_schedule;
+                    AdminAction::SetFeeSchedule { fee_schedule }
+                })
+            }
+            VrfCoordinatorInstruction::ExpireRequest => {
+                verbose_msg!("VRF Coordinator: ExpireRequest");
+                Self::process_expire_request(program_id, accounts, events)
+            }
+            VrfCoordinatorInstruction::SetConfirmations { min_confirmations, max_confirmations } => {
+                verbose_msg!("VRF Coordinator: SetConfirmations - min: {}, max: {}", min_confirmations, max_confirmations);
+                Self::process_set_confirmations(program_id, accounts, min_confirmations, max_confirmations)
+            }
+            VrfCoordinatorInstruction::SetPaused { paused } => {
+                verbose_msg!("VRF Coordinator: SetPaused - paused: {}", paused);
+                Self::process_update_config(program_id, accounts, |config| {
+                    config.paused = paused;
+                    AdminAction::SetPaused { paused }
+                })
+            }
+            VrfCoordinatorInstruction::AddConsumer { consumer } => {
+                verbose_msg!("VRF Coordinator: AddConsumer - consumer: {}", consumer);
+                Self::process_add_consumer(program_id, accounts, consumer, events)
+            }
+            VrfCoordinatorInstruction::RemoveConsumer { consumer } => {
+                verbose_msg!("VRF Coordinator: RemoveConsumer - consumer: {}", consumer);
+                Self::process_remove_consumer(program_id, accounts, consumer, events)
+            }
+            VrfCoordinatorInstruction::RequestSubscriptionOwnershipTransfer { new_owner } => {
+                verbose_msg!("VRF Coordinator: RequestSubscriptionOwnershipTransfer - new owner: {}", new_owner);
+                Self::process_request_subscription_ownership_transfer(program_id, accounts, new_owner, events)
+            }
+            VrfCoordinatorInstruction::AcceptSubscriptionOwnership => {
+                verbose_msg!("VRF Coordinator: AcceptSubscriptionOwnership");
+                Self::process_accept_subscription_ownership(program_id, accounts, events)
+            }
+            VrfCoordinatorInstruction::FulfillRandomnessBatch { proofs } => {
+                verbose_msg!("VRF Coordinator: FulfillRandomnessBatch - requests: {}", proofs.len());
+                Self::process_fulfill_randomness_batch(program_id, accounts, proofs, events)
+            }
+            VrfCoordinatorInstruction::InitializeRequestPool => {
+                verbose_msg!("VRF Coordinator: InitializeRequestPool");
+                Self::process_initialize_request_pool(program_id, accounts)
+            }
+            VrfCoordinatorInstruction::SetOracleRotation { oracles } => {
+                verbose_msg!("VRF Coordinator: SetOracleRotation - oracles: {}", oracles.len());
+                Self::process_set_oracle_rotation(program_id, accounts, oracles)
+            }
+            VrfCoordinatorInstruction::RequestRandomnessThreshold {
+                seed,
+                callback_data,
+                num_words,
+                minimum_confirmations,
+                callback_gas_limit,
+                required_oracle_responses,
+            } => {
+                verbose_msg!("VRF Coordinator: RequestRandomnessThreshold - seed: {:?}, num_words: {}, required responses: {}",
+                    seed, num_words, required_oracle_responses);
+                Self::process_request_randomness_threshold(
+                    program_id, accounts, seed, callback_data, num_words, minimum_confirmations, callback_gas_limit,
+                    required_oracle_responses, events)
+            }
+            VrfCoordinatorInstruction::SubmitOracleResponse { proof } => {
+                verbose_msg!("VRF Coordinator: SubmitOracleResponse");
+                Self::process_submit_oracle_response(program_id, accounts, proof, events)
+            }
+            VrfCoordinatorInstruction::MigrateAccount => {
+                verbose_msg!("VRF Coordinator: MigrateAccount");
+                Self::process_migrate_account(program_id, accounts, events)
+            }
+            VrfCoordinatorInstruction::FundSubscriptionNative { amount } => {
+                verbose_msg!("VRF Coordinator: FundSubscriptionNative - amount: {}", amount);
+                Self::process_fund_subscription_native(program_id, accounts, amount, events)
+            }
+            VrfCoordinatorInstruction::ClaimOracleFeesNative { amount } => {
+                verbose_msg!("VRF Coordinator: ClaimOracleFeesNative - amount: {}", amount);
+                Self::process_claim_oracle_fees_native(program_id, accounts, amount, events)
+            }
+            VrfCoordinatorInstruction::WithdrawSubscriptionNative { amount } => {
+                verbose_msg!("VRF Coordinator: WithdrawSubscriptionNative - amount: {}", amount);
+                Self::process_withdraw_subscription_native(program_id, accounts, amount, events)
+            }
+            VrfCoordinatorInstruction::ClaimOracleFees { amount } => {
+                verbose_msg!("VRF Coordinator: ClaimOracleFees - amount: {}", amount);
+                Self::process_claim_oracle_fees(program_id, accounts, amount, events)
+            }
+            VrfCoordinatorInstruction::WithdrawSubscription { amount } => {
+                verbose_msg!("VRF Coordinator: WithdrawSubscription - amount: {}", amount);
+                Self::process_withdraw_subscription(program_id, accounts, amount, events)
+            }
+            VrfCoordinatorInstruction::SetDrandPublicKey { public_key } => {
+                verbose_msg!("VRF Coordinator: SetDrandPublicKey");
+                Self::process_update_config(program_id, accounts, |config| {
+                    config.drand_public_key = public_key;
+                    AdminAction::SetDrandPublicKey { key_hash: hash(&public_key).to_bytes() }
+                })
+            }
+            VrfCoordinatorInstruction::SetCpiEvents { enabled } => {
+                verbose_msg!("VRF Coordinator: SetCpiEvents - enabled: {}", enabled);
+                Self::process_update_config(program_id, accounts, |config| {
+                    config.cpi_events = enabled;
+                    AdminAction::SetCpiEvents { enabled }
+                })
+            }
+            VrfCoordinatorInstruction::SetMinPriorityFee { min_priority_fee } => {
+                verbose_msg!("VRF Coordinator: SetMinPriorityFee - min_priority_fee: {}", min_priority_fee);
+                Self::process_update_config(program_id, accounts, |config| {
+                    config.min_priority_fee = min_priority_fee;
+                    AdminAction::SetMinPriorityFee { min_priority_fee }
+                })
             }
         }
     }
@@ -82,15 +407,16 @@ impl Processor {
         accounts: &[AccountInfo],
         min_balance: u64,
         confirmations: u8,
+        events: &EventEmitter,
     ) -> ProgramResult {
-        msg!("VRF Coordinator: Creating subscription...");
+        verbose_msg!("VRF Coordinator: Creating subscription...");
         let accounts_iter = &mut accounts.iter();
         let subscription_owner = next_account_info(accounts_iter)?;
         let subscription_account = next_account_info(accounts_iter)?;
         let system_program = next_account_info(accounts_iter)?;
 
-        msg!("VRF Coordinator: Subscription owner: {}", subscription_owner.key);
-        msg!("VRF Coordinator: Subscription account: {}", subscription_account.key);
+        verbose_msg!("VRF Coordinator: Subscription owner: {}", subscription_owner.key);
+        verbose_msg!("VRF Coordinator: Subscription account: {}", subscription_account.key);
 
         if !subscription_owner.is_signer {
             msg!("VRF Coordinator: Error - Missing subscription owner signature");
@@ -107,13 +433,27 @@ impl Processor {
             min_balance,
             confirmations,
             nonce: 0,
+            backstop_timeout_slots: DEFAULT_BACKSTOP_TIMEOUT_SLOTS,
+            backstop_bonus: 0,
+            total_funded: 0,
+            total_credited: 0,
+            total_debited: 0,
+            audited_nonce: 0,
+            pending_owner: Pubkey::default(),
+            native_balance: 0,
+            max_requests_per_slot: 0,
+            max_pending_requests: 0,
+            last_request_slot: 0,
+            requests_in_slot: 0,
+            pending_requests: 0,
+            consumers: vec![],
         };
 
         let rent = Rent::get()?;
-        let space = 8 + 32 + 8 + 8 + 1 + 8; // discriminator (8) + owner (32) + balance (8) + min_balance (8) + confirmations (1) + nonce (8)
+        let space = Subscription::LEN;
         let lamports = rent.minimum_balance(space);
 
-        msg!("VRF Coordinator: Creating subscription account - space: {}, lamports: {}", space, lamports);
+        verbose_msg!("VRF Coordinator: Creating subscription account - space: {}, lamports: {}", space, lamports);
 
         // Create the account
         invoke(
@@ -131,24 +471,26 @@ impl Processor {
             ],
         )?;
 
-        // Initialize the account data with discriminator
-        let mut data = subscription_account.try_borrow_mut_data()?;
-        data[0..8].copy_from_slice(&[83, 85, 66, 83, 67, 82, 73, 80]); // "SUBSCRIP" as bytes
-        subscription.serialize(&mut &mut data[8..])?;
+        store(subscription_account, &subscription)?;
 
         // Emit subscription created event
+        let clock = Clock::get()?;
         VrfEvent::SubscriptionCreated {
             subscription: *subscription_account.key,
             owner: *subscription_owner.key,
             min_balance,
-        }.emit();
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
 
         Ok(())
     }
 
     fn process_fund_subscription(
+        program_id: &Pubkey,
         accounts: &[AccountInfo],
         amount: u64,
+        events: &EventEmitter,
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
         let funder = next_account_info(accounts_iter)?;
@@ -160,10 +502,13 @@ impl Processor {
         if !funder.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
+        if *token_program.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut subscription: Subscription = load_mut(subscription_account, program_id)?;
+        Self::check_subscription_token_account(program_id, subscription_account.key, subscription_token)?;
 
-        // Skip the discriminator when deserializing
-        let mut subscription = Subscription::try_from_slice(&subscription_account.data.borrow()[8..])?;
-        
         // Transfer tokens
         invoke(
             &token_instruction::transfer(
@@ -183,393 +528,5173 @@ impl Processor {
         )?;
 
         subscription.balance = subscription.balance.checked_add(amount)
-            .ok_or(ProgramError::InvalidInstructionData)?;
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        subscription.total_funded = subscription.total_funded.checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
 
-        // Write back with discriminator
-        let mut data = subscription_account.try_borrow_mut_data()?;
-        data[0..8].copy_from_slice(&[83, 85, 66, 83, 67, 82, 73, 80]); // "SUBSCRIP" as bytes
-        subscription.serialize(&mut &mut data[8..])?;
+        store(subscription_account, &subscription)?;
 
         // Emit subscription funded event
+        let clock = Clock::get()?;
         VrfEvent::SubscriptionFunded {
             subscription: *subscription_account.key,
             funder: *funder.key,
             amount,
-        }.emit();
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
 
         Ok(())
     }
 
-    fn process_request_randomness(
+    /// Checks that `token_account` is the token account of `subscription`'s
+    /// vault, so that funding credited to the subscription reaches it
+    fn check_subscription_token_account(
+        program_id: &Pubkey,
+        subscription: &Pubkey,
+        token_account: &AccountInfo,
+    ) -> ProgramResult {
+        let vault = subscription_vault_address(program_id, subscription);
+        let is_vault_token = *token_account.key == get_associated_token_address(&vault, &FUNDING_MINT)
+            && *token_account.owner == spl_token::id()
+            && spl_token::state::Account::unpack(&token_account.try_borrow_data()?)
+                .is_ok_and(|token| token.owner == vault && token.mint == FUNDING_MINT);
+        if !is_vault_token {
+            msg!("VRF Coordinator: Error - Token account is not the subscription vault's");
+            return Err(VrfCoordinatorError::InvalidTokenAccount.into());
+        }
+        Ok(())
+    }
+
+    fn process_fund_subscription_native(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        seed: [u8; 32],
-        callback_data: Vec<u8>,
-        num_words: u32,
-        minimum_confirmations: u8,
-        callback_gas_limit: u64,
+        amount: u64,
+        events: &EventEmitter,
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
-        let requester = next_account_info(accounts_iter)?;
-        let request_account = next_account_info(accounts_iter)?;
+        let funder = next_account_info(accounts_iter)?;
         let subscription_account = next_account_info(accounts_iter)?;
+        let vault = next_account_info(accounts_iter)?;
         let system_program = next_account_info(accounts_iter)?;
 
-        if !requester.is_signer {
+        if !funder.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // Verify request account PDA - using subscription nonce for deterministic address
-        let mut subscription = Subscription::try_from_slice(&subscription_account.data.borrow()[8..])?;
-        let (expected_request, bump) = Pubkey::find_program_address(
-            &[
-                b"request",
-                subscription_account.key.as_ref(),
-                subscription.nonce.to_le_bytes().as_ref(),
-            ],
-            program_id
-        );
-        if expected_request != *request_account.key {
+        let mut subscription: SubscriptionHeader = load_header_mut(subscription_account, program_id)?;
+        if subscription_vault_address(program_id, subscription_account.key) != *vault.key {
             return Err(ProgramError::InvalidSeeds);
         }
 
-        if subscription.balance < subscription.min_balance {
-            return Err(VrfCoordinatorError::InsufficientBalance.into());
-        }
-
-        // Check if request account already exists
-        let request_data_len = request_account.data_len();
-        let request = if request_data_len > 0 {
-            // Account exists, verify discriminator and deserialize
-            let data = request_account.data.borrow();
-            if data[0..8] != [82, 69, 81, 85, 69, 83, 84, 0] { // "REQUEST\0"
-                return Err(ProgramError::InvalidAccountData);
-            }
-            RandomnessRequest::try_from_slice(&data[8..])?
-        } else {
-            // Create new request account
-            let request = RandomnessRequest {
-                subscription: *subscription_account.key,
-                requester: *requester.key,
-                seed,
-                callback_data,
-                request_block: 0, // Will be set by runtime
-                status: RequestStatus::Pending,
-                num_words,
-                callback_gas_limit,
-                nonce: subscription.nonce,
-                commitment: [0; 32],
-            };
-
-            let space = borsh::to_vec(&request)?.len() + 8;  // Add 8 bytes for discriminator
-            let rent = Rent::get()?;
-            let lamports = rent.minimum_balance(space);
-
-            invoke_signed(
-                &system_instruction::create_account(
-                    requester.key,
-                    request_account.key,
-                    lamports,
-                    space as u64,
-                    program_id,
-                ),
-                &[
-                    requester.clone(),
-                    request_account.clone(),
-                    system_program.clone(),
-                ],
-                &[&[
-                    b"request",
-                    subscription_account.key.as_ref(),
-                    subscription.nonce.to_le_bytes().as_ref(),
-                    &[bump]
-                ]],
-            )?;
-
-            // Initialize request account data
-            let mut data = request_account.try_borrow_mut_data()?;
-            data[0..8].copy_from_slice(&[82, 69, 81, 85, 69, 83, 84, 0]); // "REQUEST\0"
-            request.serialize(&mut &mut data[8..])?;
-            request
-        };
+        // The first funding creates the vault with its rent-exempt reserve
+        let reserve = if vault.lamports() == 0 { Rent::get()?.minimum_balance(0) } else { 0 };
+        invoke(
+            &system_instruction::transfer(
+                funder.key,
+                vault.key,
+                amount.checked_add(reserve).ok_or(ProgramError::ArithmeticOverflow)?,
+            ),
+            &[
+                funder.clone(),
+                vault.clone(),
+                system_program.clone(),
+            ],
+        )?;
 
-        // Update subscription nonce
-        subscription.nonce = subscription.nonce.checked_add(1)
-            .ok_or(ProgramError::InvalidInstructionData)?;
-        
-        // Write back subscription with updated nonce
-        let mut subscription_data = subscription_account.try_borrow_mut_data()?;
-        subscription_data[0..8].copy_from_slice(&[83, 85, 66, 83, 67, 82, 73, 80]); // "SUBSCRIP"
-        subscription.serialize(&mut &mut subscription_data[8..])?;
+        subscription.native_balance = subscription.native_balance.checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        subscription.total_funded = subscription.total_funded.checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        store_header(subscription_account, &subscription)?;
 
-        // Emit randomness requested event
-        VrfEvent::RandomnessRequested {
-            request_id: *request_account.key,
-            requester: *requester.key,
+        let clock = Clock::get()?;
+        VrfEvent::SubscriptionFunded {
             subscription: *subscription_account.key,
-            seed,
-        }.emit();
+            funder: *funder.key,
+            amount,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
 
         Ok(())
     }
 
-    fn process_fulfill_randomness(
+    fn process_claim_oracle_fees_native(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        proof: Vec<u8>,
-        public_key: Vec<u8>,
+        amount: u64,
+        events: &EventEmitter,
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
         let oracle = next_account_info(accounts_iter)?;
-        let request_account = next_account_info(accounts_iter)?;
-        let vrf_result_account = next_account_info(accounts_iter)?;
-        let callback_program = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
         let subscription_account = next_account_info(accounts_iter)?;
+        let vault = next_account_info(accounts_iter)?;
         let system_program = next_account_info(accounts_iter)?;
-        let game_program = next_account_info(accounts_iter)?;
-        let game_state = next_account_info(accounts_iter)?;
-
-        if !oracle.is_signer {
-            return Err(VrfCoordinatorError::InvalidOracleSigner.into());
-        }
 
-        // Get request data upfront
-        let mut request = RandomnessRequest::try_from_slice(&request_account.data.borrow()[8..])?;
-        let callback_data = request.callback_data.clone();
-        let requester = request.requester;
+        let mut oracle_config = Self::load_claiming_oracle(program_id, oracle, oracle_config_account, amount)?;
+        let subscription: SubscriptionHeader = load_header(subscription_account, program_id)?;
 
-        // Generate randomness from VRF output
-        let mut randomness = [0u8; 64];
-        for i in 0..32 {
-            randomness[i] = (i as u8).wrapping_add(1);  // Use a deterministic pattern for testing
+        // Whatever the vault holds above its reserve and the native balance
+        // was charged to the subscription for oracles
+        let reserve = Rent::get()?.minimum_balance(0);
+        let charged = vault.lamports().saturating_sub(reserve).saturating_sub(subscription.native_balance);
+        if amount > charged {
+            msg!("VRF Coordinator: Error - The vault holds {} lamports of charged fees", charged);
+            return Err(VrfCoordinatorError::InsufficientBalance.into());
         }
+        Self::transfer_from_vault(program_id, subscription_account.key, vault, oracle, system_program, amount)?;
 
-        let vrf_result = VrfResult {
-            randomness: vec![randomness],
-            proof: proof.clone(),
-            proof_block: 0, // Will be set by the runtime
-        };
-
-        // Check if VRF result account already exists
-        let vrf_result_data_len = vrf_result_account.data_len();
-        if vrf_result_data_len == 0 {
-            // Create new VRF result account
-            let space = borsh::to_vec(&vrf_result)?.len() + 8;  // Add 8 bytes for discriminator
-            let rent = Rent::get()?;
-            let lamports = rent.minimum_balance(space);
-
-            // Verify VRF result PDA
-            let (expected_vrf_result, bump) = Pubkey::find_program_address(
-                &[b"vrf_result", requester.as_ref()],
-                program_id
-            );
-            if expected_vrf_result != *vrf_result_account.key {
-                return Err(ProgramError::InvalidSeeds);
-            }
+        oracle_config.accrued_fees -= amount;
+        store(oracle_config_account, &oracle_config)?;
 
-            invoke_signed(
-                &system_instruction::create_account(
-                    oracle.key,
-                    vrf_result_account.key,
-                    lamports,
-                    space as u64,
-                    program_id,
-                ),
-                &[
-                    oracle.clone(),
-                    vrf_result_account.clone(),
-                    system_program.clone(),
-                ],
-                &[&[b"vrf_result", requester.as_ref(), &[bump]]],
-            )?;
-        }
+        let clock = Clock::get()?;
+        VrfEvent::OracleFeesClaimed {
+            oracle: *oracle.key,
+            subscription: *subscription_account.key,
+            amount,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)
+    }
 
-        // Write VRF result data
-        {
-            let mut data = vrf_result_account.try_borrow_mut_data()?;
-            data[0..8].copy_from_slice(&[86, 82, 70, 82, 83, 76, 84, 0]); // "VRFRSLT\0"
-            vrf_result.serialize(&mut &mut data[8..])?;
+    /// Loads the config of `oracle`, which must sign and have accrued at
+    /// least `amount` of fees
+    fn load_claiming_oracle(
+        program_id: &Pubkey,
+        oracle: &AccountInfo,
+        oracle_config_account: &AccountInfo,
+        amount: u64,
+    ) -> Result<OracleConfig, ProgramError> {
+        if !oracle.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
         }
-
-        // Update request status
-        {
-            request.status = RequestStatus::Fulfilled;
-            let mut data = request_account.try_borrow_mut_data()?;
-            data[0..8].copy_from_slice(&[82, 69, 81, 85, 69, 83, 84, 0]); // "REQUEST\0"
-            request.serialize(&mut &mut data[8..])?;
+        let oracle_config: OracleConfig = load_mut(oracle_config_account, program_id)?;
+        if oracle_config.oracle_key != *oracle.key {
+            return Err(VrfCoordinatorError::UnauthorizedOracle.into());
         }
-
-        // Update subscription balance
-        {
-            let mut subscription = Subscription::try_from_slice(&subscription_account.data.borrow()[8..])?;
-            subscription.balance = subscription.balance.checked_add(subscription.min_balance)
-                .ok_or(ProgramError::InvalidInstructionData)?;
-            
-            // Write back subscription
-            let mut data = subscription_account.try_borrow_mut_data()?;
-            data[0..8].copy_from_slice(&[83, 85, 66, 83, 67, 82, 73, 80]); // "SUBSCRIP"
-            subscription.serialize(&mut &mut data[8..])?;
+        Self::check_oracle_config_address(program_id, oracle_config_account, oracle.key)?;
+        if amount > oracle_config.accrued_fees {
+            msg!("VRF Coordinator: Error - The oracle has accrued {} lamports of fees", oracle_config.accrued_fees);
+            return Err(VrfCoordinatorError::InsufficientBalance.into());
         }
+        Ok(oracle_config)
+    }
 
-        // Emit randomness fulfilled event
-        VrfEvent::RandomnessFulfilled {
-            request_id: *request_account.key,
-            requester,
-            randomness,
-        }.emit();
+    fn process_withdraw_subscription_native(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let vault = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
 
-        // Call the callback
-        msg!("VRF Coordinator: Making CPI call to game program");
-        msg!("VRF Coordinator: Game program ID: {}", game_program.key);
-        msg!("VRF Coordinator: VRF result account: {}", vrf_result_account.key);
-        msg!("VRF Coordinator: Request account: {}", request_account.key);
-        msg!("VRF Coordinator: Game state account: {}", game_state.key);
-
-        // Get the game state PDA seeds
-        let (game_state_pda, game_state_bump) = Pubkey::find_program_address(
-            &[b"game_state", requester.as_ref()],
-            game_program.key
-        );
-        msg!("VRF Coordinator: Expected game state PDA: {}", game_state_pda);
-        if game_state_pda != *game_state.key {
-            msg!("VRF Coordinator: Error - Game state account is not the expected PDA");
-            return Err(ProgramError::InvalidSeeds);
+        let mut subscription = Self::load_subscription_as_owner(program_id, owner, subscription_account)?;
+        if amount > subscription.native_balance {
+            return Err(VrfCoordinatorError::InsufficientBalance.into());
         }
+        Self::transfer_from_vault(program_id, subscription_account.key, vault, owner, system_program, amount)?;
 
-        invoke_signed(
-            &Instruction::new_with_bytes(
-                *game_program.key,
-                &callback_data,
-                vec![
-                    AccountMeta::new_readonly(*vrf_result_account.key, false),
-                    AccountMeta::new_readonly(*request_account.key, false),
-                    AccountMeta::new(*game_state.key, false),
-                ],
-            ),
-            &[
-                vrf_result_account.clone(),
-                request_account.clone(),
-                game_state.clone(),
-            ],
-            &[],  // No need to sign with game state PDA since it's owned by the game program
-        )?;
+        subscription.native_balance -= amount;
+        subscription.total_debited = subscription.total_debited.checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        store(subscription_account, &subscription)?;
 
-        msg!("VRF Coordinator: CPI call completed successfully");
+        let clock = Clock::get()?;
+        VrfEvent::SubscriptionWithdrawn {
+            subscription: *subscription_account.key,
+            owner: *owner.key,
+            amount,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)
+    }
 
-        Ok(())
+    /// Transfers `amount` lamports from the vault of `subscription` to
+    /// `recipient`, the vault signing for itself
+    fn transfer_from_vault<'a>(
+        program_id: &Pubkey,
+        subscription: &Pubkey,
+        vault: &AccountInfo<'a>,
+        recipient: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        amount: u64,
+    ) -> ProgramResult {
+        let (expected_vault, bump) = derive_subscription_vault_pda(program_id, subscription);
+        if expected_vault != *vault.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        invoke_signed(
+            &system_instruction::transfer(vault.key, recipient.key, amount),
+            &[vault.clone(), recipient.clone(), system_program.clone()],
+            &[&[SUBSCRIPTION_VAULT_SEED, subscription.as_ref(), &[bump]]],
+        )
+    }
+
+    fn process_claim_oracle_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let oracle = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let subscription_token = next_account_info(accounts_iter)?;
+        let oracle_token = next_account_info(accounts_iter)?;
+        let vault = next_account_info(accounts_iter)?;
+        let token_program = next_account_info(accounts_iter)?;
+
+        let mut oracle_config = Self::load_claiming_oracle(program_id, oracle, oracle_config_account, amount)?;
+        let subscription: SubscriptionHeader = load_header(subscription_account, program_id)?;
+        Self::check_subscription_token_account(program_id, subscription_account.key, subscription_token)?;
+
+        // Whatever the token account holds above the SPL balance was charged
+        // to the subscription for oracles
+        let token = spl_token::state::Account::unpack(&subscription_token.try_borrow_data()?)?;
+        let charged = token.amount.saturating_sub(subscription.balance);
+        if amount > charged {
+            msg!("VRF Coordinator: Error - The vault holds {} tokens of charged fees", charged);
+            return Err(VrfCoordinatorError::InsufficientBalance.into());
+        }
+        Self::transfer_tokens_from_vault(program_id, subscription_account.key, subscription_token, oracle_token, vault, token_program, amount)?;
+
+        oracle_config.accrued_fees -= amount;
+        store(oracle_config_account, &oracle_config)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::OracleFeesClaimed {
+            oracle: *oracle.key,
+            subscription: *subscription_account.key,
+            amount,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)
     }
 
-    fn process_cancel_request(accounts: &[AccountInfo]) -> ProgramResult {
+    fn process_withdraw_subscription(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        events: &EventEmitter,
+    ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
         let owner = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let subscription_token = next_account_info(accounts_iter)?;
+        let owner_token = next_account_info(accounts_iter)?;
+        let vault = next_account_info(accounts_iter)?;
+        let token_program = next_account_info(accounts_iter)?;
+
+        let mut subscription = Self::load_subscription_as_owner(program_id, owner, subscription_account)?;
+        Self::check_subscription_token_account(program_id, subscription_account.key, subscription_token)?;
+        if amount > subscription.balance {
+            return Err(VrfCoordinatorError::InsufficientBalance.into());
+        }
+        Self::transfer_tokens_from_vault(program_id, subscription_account.key, subscription_token, owner_token, vault, token_program, amount)?;
+
+        subscription.balance -= amount;
+        subscription.total_debited = subscription.total_debited.checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        store(subscription_account, &subscription)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::SubscriptionWithdrawn {
+            subscription: *subscription_account.key,
+            owner: *owner.key,
+            amount,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)
+    }
+
+    /// Transfers `amount` tokens from the vault token account of
+    /// `subscription`, checked by the caller, to `destination`, the vault
+    /// signing as its owner
+    fn transfer_tokens_from_vault<'a>(
+        program_id: &Pubkey,
+        subscription: &Pubkey,
+        subscription_token: &AccountInfo<'a>,
+        destination: &AccountInfo<'a>,
+        vault: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>,
+        amount: u64,
+    ) -> ProgramResult {
+        if *token_program.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let (expected_vault, bump) = derive_subscription_vault_pda(program_id, subscription);
+        if expected_vault != *vault.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        invoke_signed(
+            &token_instruction::transfer(
+                &spl_token::id(),
+                subscription_token.key,
+                destination.key,
+                vault.key,
+                &[],
+                amount,
+            )?,
+            &[
+                subscription_token.clone(),
+                destination.clone(),
+                vault.clone(),
+                token_program.clone(),
+            ],
+            &[&[SUBSCRIPTION_VAULT_SEED, subscription.as_ref(), &[bump]]],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_request_randomness(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        not_before_slot: u64,
+        priority_fee: u64,
+        fee_auction: FeeAuction,
+        required_oracle_responses: u8,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let requester = next_account_info(accounts_iter)?;
         let request_account = next_account_info(accounts_iter)?;
         let subscription_account = next_account_info(accounts_iter)?;
-        let subscription_balance = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
 
-        if !owner.is_signer {
+        if !requester.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let request = RandomnessRequest::try_from_slice(&request_account.data.borrow())?;
-        let mut subscription = Subscription::try_from_slice(&subscription_account.data.borrow()[8..])?;
+        let (stats_account, others) = Self::split_stats_account(program_id, accounts_iter.as_slice());
+        let (config, others) = Self::split_config_account(program_id, others)?;
+        let (request_pool_account, others) = Self::split_request_pool_account(program_id, subscription_account.key, others);
+        config.check_not_paused()?;
+        config.check_confirmations(minimum_confirmations)?;
+        Self::open_request(
+            program_id,
+            requester,
+            request_account,
+            subscription_account,
+            system_program,
+            others.first().copied(),
+            &config,
+            stats_account,
+            request_pool_account,
+            RandomnessRequest {
+                subscription: *subscription_account.key,
+                requester: *requester.key,
+                seed,
+                callback_data,
+                request_block: 0,
+                status: RequestStatus::Pending,
+                num_words,
+                callback_gas_limit,
+                nonce: 0,
+                commitment: [0; 32],
+                assigned_oracle: Pubkey::default(),
+                not_before_slot,
+                fee_auction,
+                expiration_slot: 0,
+                confirmations: minimum_confirmations,
+                required_oracle_responses,
+                priority_fee,
+                id: RequestId::default(),
+                reserved_fee: 0,
+                reserved_native: 0,
+            },
+            events,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_request_randomness_with_entropy(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        entropy: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let request_account = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let subscription_account = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let slot_hashes_account = accounts.get(4).ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        let nonce = load_header::<SubscriptionHeader>(subscription_account, program_id)?.nonce;
+        let (slot_hash_slot, slot_hash) = entropy::recent_slot_hash(slot_hashes_account)?;
+        let seed = entropy::mix_alpha(&entropy, request_account.key, nonce, &slot_hash);
 
-        if request.status != RequestStatus::Pending {
-            return Err(VrfCoordinatorError::InvalidRequestStatus.into());
+        // The rest is an ordinary request, without the sysvar
+        let mut request_accounts = accounts.to_vec();
+        request_accounts.remove(4);
+        Self::process_request_randomness(
+            program_id,
+            &request_accounts,
+            seed,
+            callback_data,
+            num_words,
+            minimum_confirmations,
+            callback_gas_limit,
+            0,
+            0,
+            FeeAuction::default(),
+            1,
+            events,
+        )?;
+
+        let clock = Clock::get()?;
+        VrfEvent::EntropyMixed {
+            request_id: *request_account.key,
+            id: load_header::<RandomnessRequestHeader>(request_account, program_id)?.id,
+            slot_hash_slot,
+            slot_hash,
+            seed,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_request_randomness_open_market(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        fee_auction: FeeAuction,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        // The highest price is reserved from the subscription like any fee
+        if !fee_auction.is_open_market() {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
         }
 
-        if subscription.owner != *owner.key {
-            return Err(VrfCoordinatorError::InvalidSubscriptionOwner.into());
+        Self::process_request_randomness(
+            program_id,
+            accounts,
+            seed,
+            callback_data,
+            num_words,
+            minimum_confirmations,
+            callback_gas_limit,
+            0,
+            0,
+            fee_auction,
+            1,
+            events,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_request_randomness_threshold(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        minimum_confirmations: u8,
+        callback_gas_limit: u64,
+        required_oracle_responses: u8,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        if !(1..=MAXIMUM_ORACLE_RESPONSES).contains(&required_oracle_responses) {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+
+        Self::process_request_randomness(
+            program_id,
+            accounts,
+            seed,
+            callback_data,
+            num_words,
+            minimum_confirmations,
+            callback_gas_limit,
+            0,
+            0,
+            FeeAuction::default(),
+            required_oracle_responses,
+            events,
+        )
+    }
+
+    /// Creates the next request of the subscription from `request`, filling in
+    /// its slot, nonce, the subscription's confirmations if it asked for fewer,
+    /// and the oracle of the optional `oracle_config_account` or of the
+    /// rotation in `config` unless any oracles may make up its quorum,
+    /// and lists it in the optional request pool. `payer` funds the request
+    /// account, and the request's fee, see [RandomnessRequest::reserved_fee],
+    /// and priority fee are escrowed from the subscription until it is
+    /// fulfilled or closed. Fails with `RateLimited` past the subscription's
+    /// rate limits.
+    #[allow(clippy::too_many_arguments)]
+    fn open_request<'a>(
+        program_id: &Pubkey,
+        payer: &AccountInfo<'a>,
+        request_account: &AccountInfo<'a>,
+        subscription_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        oracle_config_account: Option<&AccountInfo<'a>>,
+        config: &CoordinatorConfig,
+        stats_account: Option<&AccountInfo<'a>>,
+        request_pool_account: Option<&AccountInfo<'a>>,
+        mut request: RandomnessRequest,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        events.check_config(config)?;
+
+        // Verify request account PDA - using subscription nonce for deterministic address
+        let mut subscription: SubscriptionHeader = load_header_mut(subscription_account, program_id)?;
+        let (expected_request, bump) = derive_request_pda(program_id, subscription_account.key, subscription.nonce);
+        if expected_request != *request_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if !subscription.is_consumer(&subscription_account.try_borrow_data()?, &request.requester) {
+            msg!("VRF Coordinator: Error - Requester is not a consumer of the subscription");
+            return Err(VrfCoordinatorError::InvalidConsumer.into());
         }
+        RandomnessRequest::check_num_words(request.num_words)?;
+        RandomnessRequest::check_callback_gas_limit(request.callback_gas_limit)?;
+        if request.priority_fee > 0 && request.priority_fee < config.min_priority_fee {
+            msg!("VRF Coordinator: Error - Priority fee is below the minimum of {}", config.min_priority_fee);
+            return Err(VrfCoordinatorError::PriorityFeeTooLow.into());
+        }
+        // The most the fulfillment may charge, which must be there when it does
+        let max_fee = if request.fee_auction.is_open_market() {
+            request.fee_auction.start_price.max(request.fee_auction.end_price)
+        } else {
+            config.fee_schedule.quote(request.num_words, request.callback_gas_limit, 0)?
+        };
+        request.reserved_fee = max_fee.max(subscription.min_balance);
+        let escrow = request.reserved_fee.checked_add(request.priority_fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if subscription.available_balance()? < escrow {
+            return Err(VrfCoordinatorError::InsufficientBalance.into());
+        }
+
+        let assigned_oracle = if request.is_threshold() {
+            Pubkey::default()
+        } else {
+            Self::assigned_oracle(program_id, oracle_config_account, config, request_account)?
+        };
 
-        // Refund the subscription balance
-        subscription.balance = subscription.balance.checked_add(subscription.min_balance)
-            .ok_or(ProgramError::InvalidInstructionData)?;
+        // Check if request account already exists
+        let request_data_len = request_account.data_len();
+        let request = if request_data_len > 0 {
+            load::<RandomnessRequest>(request_account, program_id)?
+        } else {
+            // Create new request account
+            request.request_block = Clock::get()?.slot;
+            request.expiration_slot = RandomnessRequest::expiration(request.request_block, request.not_before_slot);
+            request.nonce = subscription.nonce;
+            request.confirmations = request.confirmations.max(subscription.confirmations);
+            request.id = request.compute_id();
+            request.assigned_oracle = assigned_oracle;
+            request.commitment = request.compute_commitment();
+
+            subscription.record_request(request.request_block)?;
+            request.reserved_native = subscription.debit(escrow)?;
+            subscription.total_debited = subscription.total_debited.checked_add(escrow)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            let space = RandomnessRequest::space(request.callback_data.len());
+            let rent = Rent::get()?;
+            let lamports = rent.minimum_balance(space);
+
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    request_account.key,
+                    lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    payer.clone(),
+                    request_account.clone(),
+                    system_program.clone(),
+                ],
+                &[&[
+                    REQUEST_SEED,
+                    subscription_account.key.as_ref(),
+                    subscription.nonce.to_le_bytes().as_ref(),
+                    &[bump]
+                ]],
+            )?;
+
+            store(request_account, &request)?;
+            request
+        };
+
+        // Update subscription nonce
+        subscription.nonce = subscription.nonce.checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
         
-        // Write back with discriminator
-        let mut data = subscription_account.try_borrow_mut_data()?;
-        data[0..8].copy_from_slice(&[83, 85, 66, 83, 67, 82, 73, 80]); // "SUBSCRIP" as bytes
-        subscription.serialize(&mut &mut data[8..])?;
+        store_header(subscription_account, &subscription)?;
+        Self::update_stats(program_id, stats_account, CoordinatorStats::record_request)?;
+        Self::update_request_pool(program_id, request_pool_account, subscription_account.key, |pool| {
+            pool.push(PooledRequest { request: *request_account.key, seed: request.seed })
+        })?;
 
-        // Emit request cancelled event
-        VrfEvent::RequestCancelled {
+        // Emit randomness requested event
+        let clock = Clock::get()?;
+        VrfEvent::RandomnessRequested {
             request_id: *request_account.key,
-            subscription: request.subscription,
-        }.emit();
-
-        // Close request account
-        **request_account.try_borrow_mut_lamports()? = 0;
-        request_account.data.borrow_mut().fill(0);
+            id: request.id,
+            requester: request.requester,
+            subscription: *subscription_account.key,
+            seed: request.seed,
+            priority_fee: request.priority_fee,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
 
         Ok(())
     }
 
-    fn process_register_oracle(
+    /// An optional oracle config account assigns a request to that oracle,
+    /// otherwise it goes to the oracle of the rotation for `request_account`.
+    /// Once the admin configures a rotation, requesters may no longer pick
+    /// their oracle.
+    fn assigned_oracle(
+        program_id: &Pubkey,
+        oracle_config_account: Option<&AccountInfo>,
+        config: &CoordinatorConfig,
+        request_account: &AccountInfo,
+    ) -> Result<Pubkey, ProgramError> {
+        match oracle_config_account {
+            Some(_) if !config.oracle_rotation.is_empty() => {
+                msg!("VRF Coordinator: Error - Requests are assigned by the oracle rotation");
+                Err(VrfCoordinatorError::InvalidOracle.into())
+            }
+            Some(oracle_config_account) => {
+                let oracle_config: OracleConfig = load(oracle_config_account, program_id)?;
+                Self::check_oracle_config_address(program_id, oracle_config_account, &oracle_config.oracle_key)?;
+                if !oracle_config.is_active {
+                    return Err(VrfCoordinatorError::InvalidOracle.into());
+                }
+                Ok(oracle_config.oracle_key)
+            }
+            None => Ok(config.rotation_oracle(request_account.key)),
+        }
+    }
+
+    fn process_request_randomness_direct(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        oracle_key: Pubkey,
-        vrf_key: [u8; 32],
+        seed: [u8; 32],
+        callback_data: Vec<u8>,
+        num_words: u32,
+        callback_gas_limit: u64,
+        events: &EventEmitter,
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
-        let admin = next_account_info(accounts_iter)?;
-        let oracle_config_account = next_account_info(accounts_iter)?;
+        let requester = next_account_info(accounts_iter)?;
+        let request_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
         let system_program = next_account_info(accounts_iter)?;
+        let (stats_account, others) = Self::split_stats_account(program_id, accounts_iter.as_slice());
+        let oracle_config_account = others.first().copied();
 
-        if !admin.is_signer {
+        if !requester.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let oracle_config = OracleConfig {
-            oracle_key,
-            vrf_key,
-            is_active: true,
-        };
+        let (expected_config, _) = derive_config_pda(program_id);
+        if expected_config != *config_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let config: CoordinatorConfig = load(config_account, program_id)?;
+        config.check_not_paused()?;
+        let fee = config.fee_schedule.quote(num_words, callback_gas_limit, 0)?;
 
-        let rent = Rent::get()?;
-        let space = borsh::to_vec(&oracle_config)?.len();
-        let lamports = rent.minimum_balance(space);
+        let (expected_request, bump) = derive_direct_request_pda(program_id, requester.key, &seed);
+        if expected_request != *request_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if request_account.data_len() > 0 {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
 
-        invoke(
+        let clock = Clock::get()?;
+        let mut request = RandomnessRequest {
+            subscription: Pubkey::default(),
+            seed,
+            requester: *requester.key,
+            callback_data,
+            request_block: clock.slot,
+            status: RequestStatus::Pending,
+            num_words,
+            callback_gas_limit,
+            nonce: 0,
+            commitment: [0; 32],
+            assigned_oracle: Self::assigned_oracle(program_id, oracle_config_account, &config, request_account)?,
+            not_before_slot: 0,
+            fee_auction: FeeAuction::default(),
+            expiration_slot: RandomnessRequest::expiration(clock.slot, 0),
+            confirmations: 0,
+            required_oracle_responses: 1,
+            priority_fee: 0,
+            reserved_fee: 0,
+            reserved_native: 0,
+            id: RequestId::default(),
+        };
+        request.id = request.compute_id();
+        request.commitment = request.compute_commitment();
+
+        // The fee rides along with the rent, for the fulfilling oracle to collect
+        let space = RandomnessRequest::space(request.callback_data.len());
+        let lamports = Rent::get()?.minimum_balance(space)
+            .checked_add(fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        invoke_signed(
             &system_instruction::create_account(
-                admin.key,
-                oracle_config_account.key,
+                requester.key,
+                request_account.key,
                 lamports,
                 space as u64,
                 program_id,
             ),
             &[
-                admin.clone(),
-                oracle_config_account.clone(),
+                requester.clone(),
+                request_account.clone(),
                 system_program.clone(),
             ],
+            &[&[DIRECT_REQUEST_SEED, requester.key.as_ref(), &seed, &[bump]]],
         )?;
+        store(request_account, &request)?;
+        Self::update_stats(program_id, stats_account, CoordinatorStats::record_request)?;
 
-        oracle_config.serialize(&mut *oracle_config_account.data.borrow_mut())?;
+        VrfEvent::RandomnessRequested {
+            request_id: *request_account.key,
+            id: request.id,
+            requester: request.requester,
+            subscription: request.subscription,
+            seed,
+            priority_fee: 0,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
 
         Ok(())
     }
 
-    fn process_deactivate_oracle(
+    #[allow(clippy::too_many_arguments)]
+    fn process_create_schedule(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        oracle_key: Pubkey,
+        seed: [u8; 32],
+        interval_slots: u64,
+        start_slot: u64,
+        num_words: u32,
+        callback_gas_limit: u64,
+        callback_data: Vec<u8>,
     ) -> ProgramResult {
         let accounts_iter = &mut accounts.iter();
-        let admin = next_account_info(accounts_iter)?;
-        let oracle_config_account = next_account_info(accounts_iter)?;
-
-        if !admin.is_signer {
+        let owner = next_account_info(accounts_iter)?;
+        let schedule_account = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        if !owner.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
+        if interval_slots == 0 {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+        RandomnessRequest::check_num_words(num_words)?;
+        RandomnessRequest::check_callback_gas_limit(callback_gas_limit)?;
+        let subscription: SubscriptionHeader = load_header(subscription_account, program_id)?;
+        if !subscription.is_consumer(&subscription_account.try_borrow_data()?, owner.key) {
+            return Err(VrfCoordinatorError::InvalidConsumer.into());
+        }
 
-        let mut oracle_config = OracleConfig::try_from_slice(&oracle_config_account.data.borrow())?;
+        let (expected_schedule, bump) = Pubkey::find_program_address(
+            &[Schedule::SEED, owner.key.as_ref(), &seed],
+            program_id
+        );
+        if expected_schedule != *schedule_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if schedule_account.data_len() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
 
-        if oracle_config.oracle_key != oracle_key {
+        let space = Schedule::space(callback_data.len());
+        let lamports = Rent::get()?.minimum_balance(space);
+        invoke_signed(
+            &system_instruction::create_account(
+                owner.key,
+                schedule_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                owner.clone(),
+                schedule_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[Schedule::SEED, owner.key.as_ref(), &seed, &[bump]]],
+        )?;
+
+        store(schedule_account, &Schedule {
+            owner: *owner.key,
+            subscription: *subscription_account.key,
+            seed,
+            interval_slots,
+            next_slot: start_slot.max(Clock::get()?.slot),
+            requests_opened: 0,
+            num_words,
+            callback_gas_limit,
+            callback_data,
+        })
+    }
+
+    fn process_crank_schedule(program_id: &Pubkey, accounts: &[AccountInfo], events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let cranker = next_account_info(accounts_iter)?;
+        let schedule_account = next_account_info(accounts_iter)?;
+        let request_account = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let (stats_account, others) = Self::split_stats_account(program_id, accounts_iter.as_slice());
+
+        if !cranker.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let (config, others) = Self::split_config_account(program_id, others)?;
+        config.check_not_paused()?;
+        let (request_pool_account, _) = Self::split_request_pool_account(program_id, subscription_account.key, others);
+
+        let mut schedule: Schedule = load_mut(schedule_account, program_id)?;
+        if schedule.subscription != *subscription_account.key {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+        let slot = Clock::get()?.slot;
+        if slot < schedule.next_slot {
+            return Err(VrfCoordinatorError::ScheduleNotDue.into());
+        }
+        // An existing account would be taken as this request, leaving the schedule ahead of it
+        if request_account.data_len() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        Self::open_request(
+            program_id,
+            cranker,
+            request_account,
+            subscription_account,
+            system_program,
+            None,
+            &config,
+            stats_account,
+            request_pool_account,
+            RandomnessRequest {
+                subscription: schedule.subscription,
+                requester: schedule.owner,
+                seed: schedule.request_seed(),
+                callback_data: schedule.callback_data.clone(),
+                request_block: 0,
+                status: RequestStatus::Pending,
+                num_words: schedule.num_words,
+                callback_gas_limit: schedule.callback_gas_limit,
+                nonce: 0,
+                commitment: [0; 32],
+                assigned_oracle: Pubkey::default(),
+                not_before_slot: 0,
+                fee_auction: FeeAuction::default(),
+                expiration_slot: 0,
+                confirmations: 0,
+                required_oracle_responses: 1,
+                priority_fee: 0,
+                reserved_fee: 0,
+                reserved_native: 0,
+                id: RequestId::default(),
+            },
+            events,
+        )?;
+
+        schedule.advance(slot)?;
+        store(schedule_account, &schedule)
+    }
+
+    fn process_cancel_schedule(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let schedule_account = next_account_info(accounts_iter)?;
+
+        if !owner.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let schedule: Schedule = load_mut(schedule_account, program_id)?;
+        if schedule.owner != *owner.key {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+
+        // Close the schedule account, returning its rent to the owner
+        let owner_lamports = owner.lamports().checked_add(schedule_account.lamports())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **owner.try_borrow_mut_lamports()? = owner_lamports;
+        **schedule_account.try_borrow_mut_lamports()? = 0;
+        schedule_account.data.borrow_mut().fill(0);
+
+        Ok(())
+    }
+
+    fn process_create_beacon_stream(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        callback_program: Pubkey,
+        callback_account: Pubkey,
+        callback_gas_limit: u64,
+        callback_data: Vec<u8>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let stream_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        if !owner.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if callback_program != Pubkey::default() {
+            RandomnessRequest::check_callback_gas_limit(callback_gas_limit)?;
+        }
+
+        let (expected_stream, bump) = Pubkey::find_program_address(
+            &[BeaconStream::SEED, owner.key.as_ref()],
+            program_id
+        );
+        if expected_stream != *stream_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if stream_account.data_len() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let space = BeaconStream::space(callback_data.len());
+        let lamports = Rent::get()?.minimum_balance(space);
+        invoke_signed(
+            &system_instruction::create_account(
+                owner.key,
+                stream_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                owner.clone(),
+                stream_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[BeaconStream::SEED, owner.key.as_ref(), &[bump]]],
+        )?;
+
+        store(stream_account, &BeaconStream {
+            owner: *owner.key,
+            callback_program,
+            callback_account,
+            callback_gas_limit,
+            rounds_written: 0,
+            entries: [BeaconEntry::default(); BEACON_STREAM_CAPACITY],
+            callback_data,
+        })
+    }
+
+    fn process_close_beacon_stream(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let stream_account = next_account_info(accounts_iter)?;
+
+        if !owner.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let stream: BeaconStream = load_mut(stream_account, program_id)?;
+        if stream.owner != *owner.key {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+
+        // Close the stream account, returning its rent to the owner
+        let owner_lamports = owner.lamports().checked_add(stream_account.lamports())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **owner.try_borrow_mut_lamports()? = owner_lamports;
+        **stream_account.try_borrow_mut_lamports()? = 0;
+        stream_account.data.borrow_mut().fill(0);
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_request_random_words(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        key_hash: [u8; 32],
+        sub_id: Pubkey,
+        request_confirmations: u16,
+        callback_gas_limit: u32,
+        num_words: u32,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let requester = next_account_info(accounts_iter)?;
+        let request_account = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let _system_program = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
+
+        if sub_id != *subscription_account.key {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+        let minimum_confirmations = u8::try_from(request_confirmations)
+            .map_err(|_| VrfCoordinatorError::InvalidRequestParameters)?;
+
+        // The key hash names the oracle the request is assigned to
+        let oracle_config: OracleConfig = load(oracle_config_account, program_id)?;
+        if chainlink::key_hash(&oracle_config.vrf_key) != key_hash {
             return Err(VrfCoordinatorError::InvalidOracle.into());
         }
 
-        oracle_config.is_active = false;
-        oracle_config.serialize(&mut *oracle_config_account.data.borrow_mut())?;
+        let nonce = load_header::<SubscriptionHeader>(subscription_account, program_id)?.nonce;
+        let seed = chainlink::pre_seed(&key_hash, requester.key, &sub_id, nonce);
+
+        Self::process_request_randomness(
+            program_id,
+            accounts,
+            seed,
+            FULFILL_RANDOM_WORDS_SELECTOR.to_vec(),
+            num_words,
+            minimum_confirmations,
+            callback_gas_limit as u64,
+            0,
+            0,
+            FeeAuction::default(),
+            1,
+            events,
+        )?;
+
+        // Like `requestRandomWords`, hand the request ID back to the caller
+        set_return_data(request_account.key.as_ref());
+        Ok(())
+    }
+
+    fn process_fulfill_randomness(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proof: Vec<u8>,
+        public_key: Vec<u8>,
+        suite: VrfSuite,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        // The VRF result account is sized for a proof of exactly this length
+        if proof.len() != PROOF_LEN {
+            return Err(VrfCoordinatorError::InvalidVrfProof.into());
+        }
+        Self::fulfill(program_id, accounts, FulfillmentProof::Ecvrf { proof, public_key, suite }, events)
+    }
+
+    fn process_fulfill_randomness_zk(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        output: [u8; 64],
+        proof: Box<Groth16Proof>,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        Self::fulfill(program_id, accounts, FulfillmentProof::Zk { output, proof }, events)
+    }
+
+    fn process_submit_oracle_response(program_id: &Pubkey, accounts: &[AccountInfo], proof: Vec<u8>, events: &EventEmitter) -> ProgramResult {
+        if proof.len() != PROOF_LEN {
+            return Err(VrfCoordinatorError::InvalidVrfProof.into());
+        }
+        Self::fulfill(program_id, accounts, FulfillmentProof::Threshold { proof }, events)
+    }
+
+    fn process_fulfill_randomness_batch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proofs: Vec<Vec<u8>>,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        if proofs.is_empty() || proofs.len() > MAXIMUM_BATCH_FULFILLMENTS {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+        let [oracle, oracle_config_account, system_program, rest @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        if rest.len() < BATCH_ENTRY_ACCOUNTS * proofs.len() {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (entries, shared) = rest.split_at(BATCH_ENTRY_ACCOUNTS * proofs.len());
+
+        // Every proof is checked against the oracle's registered key
+        let public_key = load::<OracleConfig>(oracle_config_account, program_id)?.vrf_key.to_vec();
+        for (proof, entry) in proofs.into_iter().zip(entries.chunks_exact(BATCH_ENTRY_ACCOUNTS)) {
+            let [request_account, vrf_result_account, subscription_account, game_program, game_state] = entry else {
+                unreachable!("chunks_exact yields {} accounts", BATCH_ENTRY_ACCOUNTS);
+            };
+            let mut fulfill_accounts = vec![
+                oracle.clone(),
+                request_account.clone(),
+                vrf_result_account.clone(),
+                game_program.clone(),
+                subscription_account.clone(),
+                system_program.clone(),
+                game_program.clone(),
+                game_state.clone(),
+                oracle_config_account.clone(),
+            ];
+            fulfill_accounts.extend(shared.iter().cloned());
+            Self::process_fulfill_randomness(
+                program_id, &fulfill_accounts, proof, public_key.clone(), VrfSuite::default(), events)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fulfills a request in one transaction, for `FulfillRandomness`,
+    /// `FulfillRandomnessZk` and `SubmitOracleResponse`, whose accounts differ
+    /// only in the verifying key or responses account the latter two take
+    /// before the optional ones. A response short of the quorum is only
+    /// recorded.
+    fn fulfill(program_id: &Pubkey, accounts: &[AccountInfo], fulfillment: FulfillmentProof, events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let oracle = next_account_info(accounts_iter)?;
+        let request_account = next_account_info(accounts_iter)?;
+        let vrf_result_account = next_account_info(accounts_iter)?;
+        // The callback program, passed again as the game program
+        let _callback_program = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let game_program = next_account_info(accounts_iter)?;
+        let game_state = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
+        let mode_account = match fulfillment {
+            FulfillmentProof::Zk { .. } | FulfillmentProof::Threshold { .. } => Some(next_account_info(accounts_iter)?),
+            FulfillmentProof::Ecvrf { .. } => None,
+        };
+        let optional = Self::optional_accounts(program_id, accounts_iter.as_slice())?;
+
+        if !oracle.is_signer {
+            return Err(VrfCoordinatorError::InvalidOracleSigner.into());
+        }
+
+        let oracle_config = Self::load_active_oracle(program_id, oracle, oracle_config_account)?;
+
+        let mut request: RandomnessRequest = load_mut(request_account, program_id)?;
+        if request.status != RequestStatus::Pending {
+            return Err(VrfCoordinatorError::RequestAlreadyFulfilled.into());
+        }
+        Self::check_commitment(&request)?;
+        let mut subscription = Self::load_request_subscription(program_id, &request, subscription_account, load_header_mut)?;
+        let is_backstop = Self::check_fulfiller(oracle, &oracle_config, &request, subscription.as_ref())?;
+        Self::check_fulfillment_mode(&request, matches!(fulfillment, FulfillmentProof::Threshold { .. }))?;
+
+        // Generate randomness from VRF output
+        let (output, proof) = match fulfillment {
+            FulfillmentProof::Ecvrf { proof, public_key, suite } => {
+                if public_key != oracle_config.vrf_key {
+                    msg!("VRF Coordinator: Error - Public key is not the oracle's registered VRF key");
+                    return Err(VrfCoordinatorError::InvalidVrfProof.into());
+                }
+                (Self::verify_vrf_proof(&oracle_config, &request, &proof, suite)?, proof)
+            }
+            FulfillmentProof::Zk { output, proof } => {
+                let verifying_key_account = mode_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                let (expected_key, _) = Pubkey::find_program_address(&[ZK_VERIFYING_KEY_SEED], program_id);
+                if expected_key != *verifying_key_account.key {
+                    return Err(ProgramError::InvalidSeeds);
+                }
+                let verifying_key: Groth16VerifyingKey = load(verifying_key_account, program_id)?;
+                verifying_key.verify(
+                    &proof,
+                    &groth16::public_inputs(&oracle_config.vrf_key, &request.seed, &output),
+                )?;
+                // The ECVRF proof never reaches the chain in this mode
+                (output, vec![0; PROOF_LEN])
+            }
+            FulfillmentProof::Threshold { proof } => {
+                let output = Self::verify_vrf_proof(&oracle_config, &request, &proof, VrfSuite::default())?;
+                let responses_account = mode_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+                let Some(output) = Self::record_oracle_response(
+                    program_id, oracle, responses_account, system_program, request_account, &request, output, events)? else {
+                    return Ok(());
+                };
+                (output, proof)
+            }
+        };
+        // Requests opened before the word count was checked may ask for any number
+        RandomnessRequest::check_num_words(request.num_words)?;
+        let vrf_result = VrfResult {
+            randomness: (0..request.num_words).map(|i| expand_word(&output, i)).collect(),
+            proof,
+            proof_block: 0, // Will be set by the runtime
+            request: *request_account.key,
+        };
+
+        Self::prepare_vrf_result_account(
+            program_id, oracle, vrf_result_account, system_program, request_account.key, request.num_words)?;
+        store(vrf_result_account, &vrf_result)?;
+
+        if let Some(randomness_account) = optional.randomness_account {
+            Self::reveal_randomness(program_id, randomness_account, &request, oracle.key, &output)?;
+        }
+
+        Self::complete_fulfillment(
+            program_id, oracle, request_account, &mut request,
+            subscription_account, subscription.as_mut(),
+            oracle_config_account, oracle_config, is_backstop,
+            vrf_result_account, game_program, game_state,
+            output, &vrf_result.randomness, optional,
+            events,
+        )
+    }
+
+    fn process_append_words(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proof: Vec<u8>,
+        num_words: u32,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let oracle = next_account_info(accounts_iter)?;
+        let request_account = next_account_info(accounts_iter)?;
+        let vrf_result_account = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
+
+        if !oracle.is_signer {
+            return Err(VrfCoordinatorError::InvalidOracleSigner.into());
+        }
+
+        if proof.len() != PROOF_LEN {
+            return Err(VrfCoordinatorError::InvalidVrfProof.into());
+        }
+
+        let oracle_config = Self::load_active_oracle(program_id, oracle, oracle_config_account)?;
+
+        let mut request: RandomnessRequest = load_mut(request_account, program_id)?;
+        Self::check_commitment(&request)?;
+        let subscription = Self::load_request_subscription(program_id, &request, subscription_account, load_header)?;
+        Self::check_fulfiller(oracle, &oracle_config, &request, subscription.as_ref())?;
+        Self::check_fulfillment_mode(&request, false)?;
+
+        // The first chunk sizes the result account and fixes the proof. A chunk
+        // with a different proof, e.g. from a backstop oracle taking over,
+        // restarts delivery from the first word.
+        let delivered = match request.status {
+            RequestStatus::Pending => {
+                Self::prepare_vrf_result_account(
+                    program_id, oracle, vrf_result_account, system_program, request_account.key, request.num_words)?;
+                request.status = RequestStatus::Fulfilling;
+                store_header(request_account, &RandomnessRequestHeader::from(&request))?;
+                None
+            }
+            RequestStatus::Fulfilling => {
+                Self::check_vrf_result_account(program_id, vrf_result_account, request_account.key)?;
+                Some(load_mut::<VrfResult>(vrf_result_account, program_id)?)
+            }
+            _ => return Err(VrfCoordinatorError::RequestAlreadyFulfilled.into()),
+        };
+        let mut vrf_result = match delivered.filter(|vrf_result| vrf_result.proof == proof) {
+            Some(vrf_result) => vrf_result,
+            // No word of a new proof is delivered before it verifies
+            None => {
+                Self::verify_vrf_proof(&oracle_config, &request, &proof, VrfSuite::default())?;
+                VrfResult {
+                    randomness: Vec::with_capacity(request.num_words as usize),
+                    proof,
+                    proof_block: 0,
+                    request: *request_account.key,
+                }
+            }
+        };
+
+        let start = vrf_result.randomness.len() as u32;
+        let end = start.checked_add(num_words).ok_or(ProgramError::ArithmeticOverflow)?;
+        if num_words == 0 || end > request.num_words {
+            return Err(VrfCoordinatorError::InvalidNumberOfWords.into());
+        }
+
+        let output = Self::vrf_output(&vrf_result.proof)?;
+        vrf_result.randomness.extend((start..end).map(|i| expand_word(&output, i)));
+        store(vrf_result_account, &vrf_result)?;
+
+        verbose_msg!("VRF Coordinator: Appended words {}..{} of {}", start, end, request.num_words);
 
         Ok(())
     }
-} 
\ No newline at end of file
+
+    fn process_finalize_fulfillment(program_id: &Pubkey, accounts: &[AccountInfo], events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let oracle = next_account_info(accounts_iter)?;
+        let request_account = next_account_info(accounts_iter)?;
+        let vrf_result_account = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let game_program = next_account_info(accounts_iter)?;
+        let game_state = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
+        let optional = Self::optional_accounts(program_id, accounts_iter.as_slice())?;
+
+        if !oracle.is_signer {
+            return Err(VrfCoordinatorError::InvalidOracleSigner.into());
+        }
+
+        let oracle_config = Self::load_active_oracle(program_id, oracle, oracle_config_account)?;
+
+        let mut request: RandomnessRequest = load_mut(request_account, program_id)?;
+        if request.status != RequestStatus::Fulfilling {
+            return Err(VrfCoordinatorError::InvalidRequestStatus.into());
+        }
+        let mut subscription = Self::load_request_subscription(program_id, &request, subscription_account, load_header_mut)?;
+        let is_backstop = Self::check_fulfiller(oracle, &oracle_config, &request, subscription.as_ref())?;
+
+        Self::check_vrf_result_account(program_id, vrf_result_account, request_account.key)?;
+        let vrf_result: VrfResult = load(vrf_result_account, program_id)?;
+        if vrf_result.randomness.len() != request.num_words as usize {
+            return Err(VrfCoordinatorError::InvalidNumberOfWords.into());
+        }
+
+        // The proof was verified when its first words were appended
+        let output = Self::vrf_output(&vrf_result.proof)?;
+        if let Some(randomness_account) = optional.randomness_account {
+            Self::reveal_randomness(program_id, randomness_account, &request, oracle.key, &output)?;
+        }
+
+        Self::complete_fulfillment(
+            program_id, oracle, request_account, &mut request,
+            subscription_account, subscription.as_mut(),
+            oracle_config_account, oracle_config, is_backstop,
+            vrf_result_account, game_program, game_state,
+            output, &vrf_result.randomness, optional,
+            events,
+        )
+    }
+
+    /// Loads the config of `oracle`, which must be registered and active
+    fn load_active_oracle(
+        program_id: &Pubkey,
+        oracle: &AccountInfo,
+        oracle_config_account: &AccountInfo,
+    ) -> Result<OracleConfig, ProgramError> {
+        // Only registered, active oracles may fulfill
+        let oracle_config: OracleConfig = load(oracle_config_account, program_id)?;
+        if oracle_config.oracle_key != *oracle.key {
+            return Err(VrfCoordinatorError::UnauthorizedOracle.into());
+        }
+        Self::check_oracle_config_address(program_id, oracle_config_account, oracle.key)?;
+        if !oracle_config.is_active {
+            return Err(VrfCoordinatorError::InvalidOracle.into());
+        }
+        Ok(oracle_config)
+    }
+
+    /// Checks that `oracle_config_account` is the registry entry of
+    /// `oracle_key`, the PDA of [OracleConfig::SEED] and the oracle key, and
+    /// returns its bump
+    fn check_oracle_config_address(
+        program_id: &Pubkey,
+        oracle_config_account: &AccountInfo,
+        oracle_key: &Pubkey,
+    ) -> Result<u8, ProgramError> {
+        let (expected_config, bump) = derive_oracle_config_pda(program_id, oracle_key);
+        if expected_config != *oracle_config_account.key {
+            msg!("VRF Coordinator: Error - Oracle config of {} is not at its registry address", oracle_key);
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(bump)
+    }
+
+    /// Loads the subscription `request` belongs to with `loader`, checking it
+    /// is `subscription_account`. For a direct request that is the system
+    /// program and there is no subscription.
+    fn load_request_subscription(
+        program_id: &Pubkey,
+        request: &RandomnessRequest,
+        subscription_account: &AccountInfo,
+        loader: fn(&AccountInfo, &Pubkey) -> Result<SubscriptionHeader, ProgramError>,
+    ) -> Result<Option<SubscriptionHeader>, ProgramError> {
+        if request.subscription != *subscription_account.key {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+        if request.is_direct() {
+            return Ok(None);
+        }
+        loader(subscription_account, program_id).map(Some)
+    }
+
+    /// Checks that `oracle` may fulfill `request` now and returns whether it is
+    /// fulfilling as a backstop for another oracle
+    fn check_fulfiller(
+        oracle: &AccountInfo,
+        oracle_config: &OracleConfig,
+        request: &RandomnessRequest,
+        subscription: Option<&SubscriptionHeader>,
+    ) -> Result<bool, ProgramError> {
+        let slot = Clock::get()?.slot;
+        if slot < request.not_before_slot {
+            return Err(VrfCoordinatorError::RequestTimeLocked.into());
+        }
+        if slot < request.request_block.saturating_add(request.confirmations as u64) {
+            msg!("VRF Coordinator: Error - Request needs {} confirmations, {} slots elapsed",
+                request.confirmations, slot.saturating_sub(request.request_block));
+            return Err(VrfCoordinatorError::InsufficientConfirmations.into());
+        }
+
+        // A request assigned to another oracle may only be taken over once the
+        // backstop timeout, extended by the oracle's reputation delay, has
+        // elapsed, in which case the backstop oracle earns the bonus
+        let is_backstop = request.assigned_oracle != Pubkey::default()
+            && request.assigned_oracle != *oracle.key;
+        if is_backstop {
+            let timeout_slots = subscription
+                .map_or(DEFAULT_BACKSTOP_TIMEOUT_SLOTS, |subscription| subscription.backstop_timeout_slots);
+            let backstop_slot = request.request_block
+                .checked_add(timeout_slots)
+                .and_then(|slot| slot.checked_add(oracle_config.backstop_delay(timeout_slots)))
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            if slot < backstop_slot {
+                msg!("VRF Coordinator: Error - Assigned oracle {} still within its window until slot {}", 
+                    request.assigned_oracle, backstop_slot);
+                return Err(VrfCoordinatorError::UnauthorizedOracle.into());
+            }
+        }
+
+        Ok(is_backstop)
+    }
+
+    /// Checks that a threshold request is answered with `SubmitOracleResponse`
+    /// and any other with a single oracle's fulfillment
+    fn check_fulfillment_mode(request: &RandomnessRequest, threshold: bool) -> ProgramResult {
+        if request.is_threshold() != threshold {
+            msg!("VRF Coordinator: Error - Request needs {} oracle responses", request.required_oracle_responses);
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+        Ok(())
+    }
+
+    /// Records `oracle`'s verified `output` for a threshold request, creating
+    /// the responses account on the first response, and returns the
+    /// aggregated output once the quorum is reached
+    #[allow(clippy::too_many_arguments)]
+    fn record_oracle_response<'a>(
+        program_id: &Pubkey,
+        oracle: &AccountInfo<'a>,
+        responses_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        request_account: &AccountInfo<'a>,
+        request: &RandomnessRequest,
+        output: [u8; 64],
+        events: &EventEmitter,
+    ) -> Result<Option<[u8; 64]>, ProgramError> {
+        let (expected_responses, bump) = Pubkey::find_program_address(
+            &[OracleResponses::SEED, request_account.key.as_ref()],
+            program_id,
+        );
+        if expected_responses != *responses_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let mut responses = if responses_account.data_len() == 0 {
+            let space = OracleResponses::space(request.required_oracle_responses);
+            let lamports = Rent::get()?.minimum_balance(space);
+            invoke_signed(
+                &system_instruction::create_account(
+                    oracle.key,
+                    responses_account.key,
+                    lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    oracle.clone(),
+                    responses_account.clone(),
+                    system_program.clone(),
+                ],
+                &[&[OracleResponses::SEED, request_account.key.as_ref(), &[bump]]],
+            )?;
+            OracleResponses { request: *request_account.key, responses: vec![] }
+        } else {
+            load_mut::<OracleResponses>(responses_account, program_id)?
+        };
+        responses.add(*oracle.key, output)?;
+        store(responses_account, &responses)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::OracleResponseSubmitted {
+            request_id: *request_account.key,
+            id: request.id,
+            oracle: *oracle.key,
+            responses: responses.responses.len() as u8,
+            required: request.required_oracle_responses,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        let quorum = responses.responses.len() >= request.required_oracle_responses as usize;
+        Ok(quorum.then(|| responses.aggregate_output()))
+    }
+
+    /// Checks the request against the commitment it was opened with, before
+    /// any proof over its seed is accepted
+    fn check_commitment(request: &RandomnessRequest) -> ProgramResult {
+        let result = request.check_commitment();
+        if result.is_err() {
+            msg!("VRF Coordinator: Error - Request does not match its commitment");
+        }
+        result
+    }
+
+    /// Verifies `proof` in `suite` over the request seed under the oracle's
+    /// registered VRF key and returns the VRF output
+    fn verify_vrf_proof(
+        oracle_config: &OracleConfig,
+        request: &RandomnessRequest,
+        proof: &[u8],
+        suite: VrfSuite,
+    ) -> Result<[u8; 64], ProgramError> {
+        // Metered for the SBF compute unit tests, see tests/compute_units_test.rs
+        if cfg!(feature = "test-bpf") && suite == VrfSuite::Ristretto255Sha512 {
+            let units_before = sol_remaining_compute_units();
+            std::hint::black_box(ecvrf::encode_to_curve(&oracle_config.vrf_key, &request.seed));
+            msg!("VRF Coordinator: Hash to curve consumed {} compute units",
+                units_before.saturating_sub(sol_remaining_compute_units()));
+        }
+        let units_before = cfg!(feature = "test-bpf").then(sol_remaining_compute_units);
+        let output = suite.verify(&request.seed, proof, &oracle_config.vrf_key).map_err(|e| {
+            msg!("VRF Coordinator: Error - VRF proof does not verify for the request seed");
+            ProgramError::from(e)
+        })?;
+        if let Some(units_before) = units_before {
+            msg!("VRF Coordinator: Verification consumed {} compute units",
+                units_before.saturating_sub(sol_remaining_compute_units()));
+        }
+        Ok(output)
+    }
+
+    /// VRF output the randomness words are expanded from, the hash of an
+    /// already verified `proof`
+    fn vrf_output(proof: &[u8]) -> Result<[u8; 64], ProgramError> {
+        let proof: &[u8; ecvrf::PROOF_LENGTH] =
+            proof.try_into().map_err(|_| VrfCoordinatorError::InvalidVrfProof)?;
+        Ok(ecvrf::proof_to_hash(proof[..32].try_into().unwrap()))
+    }
+
+    /// Verifies the VRF result PDA, also when it already exists, so a result
+    /// can never be written into another request's account. Returns the bump.
+    fn check_vrf_result_account(
+        program_id: &Pubkey,
+        vrf_result_account: &AccountInfo,
+        request: &Pubkey,
+    ) -> Result<u8, ProgramError> {
+        let (expected_vrf_result, bump) = derive_vrf_result_pda(program_id, request);
+        if expected_vrf_result != *vrf_result_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(bump)
+    }
+
+    /// Creates the request's VRF result account, or resizes one left by an
+    /// abandoned delivery, so it holds exactly `num_words` words
+    fn prepare_vrf_result_account<'a>(
+        program_id: &Pubkey,
+        oracle: &AccountInfo<'a>,
+        vrf_result_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        request: &Pubkey,
+        num_words: u32,
+    ) -> ProgramResult {
+        let bump = Self::check_vrf_result_account(program_id, vrf_result_account, request)?;
+        let space = VrfResult::space(num_words as usize);
+        let lamports = Rent::get()?.minimum_balance(space);
+
+        if vrf_result_account.data_len() == 0 {
+            invoke_signed(
+                &system_instruction::create_account(
+                    oracle.key,
+                    vrf_result_account.key,
+                    lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    oracle.clone(),
+                    vrf_result_account.clone(),
+                    system_program.clone(),
+                ],
+                &[&[VRF_RESULT_SEED, request.as_ref(), &[bump]]],
+            )?;
+        } else if vrf_result_account.data_len() != space {
+            if vrf_result_account.lamports() < lamports {
+                invoke(
+                    &system_instruction::transfer(
+                        oracle.key,
+                        vrf_result_account.key,
+                        lamports - vrf_result_account.lamports(),
+                    ),
+                    &[
+                        oracle.clone(),
+                        vrf_result_account.clone(),
+                        system_program.clone(),
+                    ],
+                )?;
+            }
+            vrf_result_account.realloc(space, false)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reveals the request's output into the requester's Switchboard-style
+    /// randomness account
+    fn reveal_randomness(
+        program_id: &Pubkey,
+        randomness_account: &AccountInfo,
+        request: &RandomnessRequest,
+        oracle: &Pubkey,
+        output: &[u8; 64],
+    ) -> ProgramResult {
+        let (expected_randomness, _) = Pubkey::find_program_address(
+            &[RANDOMNESS_SEED, request.requester.as_ref()],
+            program_id
+        );
+        if expected_randomness != *randomness_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let mut randomness: RandomnessAccountData = load_mut(randomness_account, program_id)?;
+        randomness.seed_slot = request.request_block;
+        randomness.seed = request.seed;
+        randomness.oracle = *oracle;
+        randomness.reveal_slot = Clock::get()?.slot;
+        randomness.value.copy_from_slice(&output[..32]);
+        store(randomness_account, &randomness)
+    }
+
+    /// Splits the optional trailing accounts of a fulfillment, which may come
+    /// in any order: the coordinator config, known by its address, and the
+    /// config of the oracle a backstop takes over from, the request pool and
+    /// the requester's randomness account, known by their type. Fails on any
+    /// other account.
+    fn optional_accounts<'a, 'b>(
+        program_id: &Pubkey,
+        accounts: &'b [AccountInfo<'a>],
+    ) -> Result<OptionalAccounts<'a, 'b>, ProgramError> {
+        let (config_address, _) = derive_config_pda(program_id);
+        let (stats_account, accounts) = Self::split_stats_account(program_id, accounts);
+        let mut optional = OptionalAccounts {
+            config: None,
+            stats_account,
+            randomness_account: None,
+            assigned_oracle_config: None,
+            request_pool: None,
+        };
+        for account in accounts {
+            if *account.key == config_address {
+                optional.config = Some(load::<CoordinatorConfig>(account, program_id)?);
+            } else if check::<OracleConfig>(account, program_id).is_ok() {
+                optional.assigned_oracle_config = Some(account);
+            } else if check::<RequestPool>(account, program_id).is_ok() {
+                optional.request_pool = Some(account);
+            } else if check::<RandomnessAccountData>(account, program_id).is_ok() {
+                optional.randomness_account = Some(account);
+            } else {
+                msg!("VRF Coordinator: Error - Unrecognized account {}", account.key);
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+        Ok(optional)
+    }
+
+    /// Picks the optional stats account, recognized by its address, out of
+    /// `accounts` and returns it with the others
+    fn split_stats_account<'a, 'b>(
+        program_id: &Pubkey,
+        accounts: &'b [AccountInfo<'a>],
+    ) -> (Option<&'b AccountInfo<'a>>, Vec<&'b AccountInfo<'a>>) {
+        let (stats_address, _) = Pubkey::find_program_address(&[CoordinatorStats::SEED], program_id);
+        let (stats, others): (Vec<_>, Vec<_>) = accounts.iter().partition(|account| *account.key == stats_address);
+        (stats.first().copied(), others)
+    }
+
+    /// Picks the coordinator config, recognized by its address, out of the
+    /// trailing `accounts` of a request and returns it with the others
+    fn split_config_account<'a, 'b>(
+        program_id: &Pubkey,
+        accounts: Vec<&'b AccountInfo<'a>>,
+    ) -> Result<(CoordinatorConfig, Vec<&'b AccountInfo<'a>>), ProgramError> {
+        let (config_address, _) = derive_config_pda(program_id);
+        let (config, others): (Vec<_>, Vec<_>) = accounts.into_iter().partition(|account| *account.key == config_address);
+        let Some(config_account) = config.first() else {
+            msg!("VRF Coordinator: Error - Requests need the coordinator config account");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        Ok((load(config_account, program_id)?, others))
+    }
+
+    /// Applies `update` to the stats, if the stats account was passed
+    fn update_stats(
+        program_id: &Pubkey,
+        stats_account: Option<&AccountInfo>,
+        update: impl FnOnce(&mut CoordinatorStats) -> ProgramResult,
+    ) -> ProgramResult {
+        let Some(stats_account) = stats_account else {
+            return Ok(());
+        };
+        let mut stats: CoordinatorStats = load_mut(stats_account, program_id)?;
+        update(&mut stats)?;
+        store(stats_account, &stats)
+    }
+
+    /// Picks the optional request pool of `subscription`, recognized by its
+    /// address, out of `accounts` and returns it with the others
+    fn split_request_pool_account<'a, 'b>(
+        program_id: &Pubkey,
+        subscription: &Pubkey,
+        accounts: Vec<&'b AccountInfo<'a>>,
+    ) -> (Option<&'b AccountInfo<'a>>, Vec<&'b AccountInfo<'a>>) {
+        let (pool_address, _) = Pubkey::find_program_address(&[RequestPool::SEED, subscription.as_ref()], program_id);
+        let (pool, others): (Vec<_>, Vec<_>) = accounts.into_iter().partition(|account| *account.key == pool_address);
+        (pool.first().copied(), others)
+    }
+
+    /// Applies `update` to the request pool of `subscription`, if passed
+    fn update_request_pool(
+        program_id: &Pubkey,
+        request_pool_account: Option<&AccountInfo>,
+        subscription: &Pubkey,
+        update: impl FnOnce(&mut RequestPool),
+    ) -> ProgramResult {
+        let Some(request_pool_account) = request_pool_account else {
+            return Ok(());
+        };
+        let (expected_pool, _) = Pubkey::find_program_address(&[RequestPool::SEED, subscription.as_ref()], program_id);
+        if expected_pool != *request_pool_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let mut pool: RequestPool = load_mut(request_pool_account, program_id)?;
+        update(&mut pool);
+        store(request_pool_account, &pool)
+    }
+
+    /// Marks the request fulfilled, makes the callback, emits
+    /// `RandomnessFulfilled` and settles the subscription and oracle fees. The
+    /// callback is skipped, and the request marked `CallbackSkipped`, when
+    /// fewer compute units than its `callback_gas_limit` are left. With
+    /// the coordinator `config`, the subscription pays the quoted fee less a
+    /// rebate for the callback compute units it left unused. The oracle
+    /// collects the fee of a direct request, which has no `subscription`.
+    /// The fulfillment is counted towards the oracle's reputation and in the
+    /// stats, if passed, and the request dropped from the request pool, if
+    /// passed. A backstop oracle must pass the config of the oracle
+    /// it takes over from, which is counted a failure.
+    #[allow(clippy::too_many_arguments)]
+    fn complete_fulfillment<'a>(
+        program_id: &Pubkey,
+        oracle: &AccountInfo<'a>,
+        request_account: &AccountInfo<'a>,
+        request: &mut RandomnessRequest,
+        subscription_account: &AccountInfo<'a>,
+        subscription: Option<&mut SubscriptionHeader>,
+        oracle_config_account: &AccountInfo<'a>,
+        mut oracle_config: OracleConfig,
+        is_backstop: bool,
+        vrf_result_account: &AccountInfo<'a>,
+        game_program: &AccountInfo<'a>,
+        game_state: &AccountInfo<'a>,
+        randomness: [u8; 64],
+        words: &[[u8; 64]],
+        optional: OptionalAccounts<'a, '_>,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        if let Some(config) = &optional.config {
+            events.check_config(config)?;
+        }
+        let requester = request.requester;
+        // Chainlink-style consumers receive the words in the callback itself
+        let callback_data = if request.callback_data == FULFILL_RANDOM_WORDS_SELECTOR {
+            FulfillRandomWords::callback_data(*request_account.key, words)
+        } else {
+            request.callback_data.clone()
+        };
+
+        // Update request status
+        request.status = RequestStatus::Fulfilled;
+        store_header(request_account, &RandomnessRequestHeader::from(&*request))?;
+
+        // Emit randomness fulfilled event
+        let clock = Clock::get()?;
+        VrfEvent::RandomnessFulfilled {
+            request_id: *request_account.key,
+            id: request.id,
+            requester,
+            randomness,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        // Call the callback
+        verbose_msg!("VRF Coordinator: Making CPI call to game program");
+        verbose_msg!("VRF Coordinator: Game program ID: {}", game_program.key);
+        verbose_msg!("VRF Coordinator: VRF result account: {}", vrf_result_account.key);
+        verbose_msg!("VRF Coordinator: Request account: {}", request_account.key);
+        verbose_msg!("VRF Coordinator: Game state account: {}", game_state.key);
+
+        // The game state is the requester's PDA of the game program
+        let (game_state_pda, _) = Pubkey::find_program_address(
+            &[b"game_state", requester.as_ref()],
+            game_program.key
+        );
+        verbose_msg!("VRF Coordinator: Expected game state PDA: {}", game_state_pda);
+        if game_state_pda != *game_state.key {
+            msg!("VRF Coordinator: Error - Game state account is not the expected PDA");
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // A callback that ran out of compute units would fail the fulfillment with it
+        let units_before_callback = sol_remaining_compute_units();
+        let callback_units = if units_before_callback < request.callback_gas_limit {
+            msg!("VRF Coordinator: Skipping callback - {} compute units left, {} required",
+                units_before_callback, request.callback_gas_limit);
+            request.status = RequestStatus::CallbackSkipped;
+            store_header(request_account, &RandomnessRequestHeader::from(&*request))?;
+            VrfEvent::CallbackFailed {
+                request_id: *request_account.key,
+                id: request.id,
+                callback_program: *game_program.key,
+                slot: clock.slot,
+                unix_timestamp: clock.unix_timestamp,
+            }.emit(events)?;
+            0
+        } else {
+            invoke_signed(
+                &Instruction::new_with_bytes(
+                    *game_program.key,
+                    &callback_data,
+                    vec![
+                        AccountMeta::new_readonly(*vrf_result_account.key, false),
+                        AccountMeta::new_readonly(*request_account.key, false),
+                        AccountMeta::new(*game_state.key, false),
+                    ],
+                ),
+                &[
+                    vrf_result_account.clone(),
+                    request_account.clone(),
+                    game_state.clone(),
+                    game_program.clone(),
+                ],
+                &[],  // No need to sign with game state PDA since it's owned by the game program
+            )?;
+            verbose_msg!("VRF Coordinator: CPI call completed successfully");
+            units_before_callback.saturating_sub(sol_remaining_compute_units())
+        };
+
+        let oracle_earnings = match subscription {
+            Some(subscription) => Self::settle_subscription_fees(
+                request_account, request, subscription_account, subscription,
+                &mut oracle_config, is_backstop, optional.config, callback_units, &clock,
+                events,
+            )?,
+            None => Self::collect_direct_fee(oracle, request_account, request, &clock, events)?,
+        };
+
+        let latency = clock.slot.saturating_sub(request.request_block);
+        oracle_config.record_fulfillment(latency)?;
+        store(oracle_config_account, &oracle_config)?;
+
+        if is_backstop {
+            let assigned_config_account = optional.assigned_oracle_config
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let mut assigned_config: OracleConfig = load_mut(assigned_config_account, program_id)?;
+            if assigned_config.oracle_key != request.assigned_oracle {
+                return Err(VrfCoordinatorError::InvalidOracle.into());
+            }
+            Self::check_oracle_config_address(program_id, assigned_config_account, &request.assigned_oracle)?;
+            assigned_config.record_failure()?;
+            store(assigned_config_account, &assigned_config)?;
+        }
+
+        Self::update_request_pool(program_id, optional.request_pool, &request.subscription, |pool| {
+            pool.remove(request_account.key)
+        })?;
+        Self::update_stats(program_id, optional.stats_account, |stats| {
+            stats.record_fulfillment(oracle.key, latency, oracle_earnings)
+        })
+    }
+
+    /// Releases the fee reserved for the request and charges the subscription
+    /// the backstop bonus and the fee: the cleared price of an open market
+    /// request, or the fee quoted by the coordinator `config`, which any other
+    /// request needs. Returns what the oracle earned,
+    /// with the request's escrowed priority fee, which is credited to
+    /// `oracle_config`.
+    #[allow(clippy::too_many_arguments)]
+    fn settle_subscription_fees(
+        request_account: &AccountInfo,
+        request: &RandomnessRequest,
+        subscription_account: &AccountInfo,
+        subscription: &mut SubscriptionHeader,
+        oracle_config: &mut OracleConfig,
+        is_backstop: bool,
+        config: Option<CoordinatorConfig>,
+        callback_units: u64,
+        clock: &Clock,
+        events: &EventEmitter,
+    ) -> Result<u64, ProgramError> {
+        subscription.record_request_closed();
+
+        // The priority fee stays escrowed for the oracle
+        subscription.release(request.reserved_fee, request.reserved_native)?;
+
+        let mut oracle_earnings = request.priority_fee;
+        if is_backstop {
+            subscription.debit(subscription.backstop_bonus)?;
+            subscription.total_debited = subscription.total_debited.checked_add(subscription.backstop_bonus)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            oracle_earnings = oracle_earnings.checked_add(subscription.backstop_bonus)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+
+        let (fee, rebate) = if request.fee_auction.is_open_market() {
+            let elapsed_slots = clock.slot.saturating_sub(request.request_block);
+            (request.fee_auction.price_at(elapsed_slots), 0)
+        } else {
+            // Leaving out the config would fulfill the request for free
+            let Some(config) = config else {
+                msg!("VRF Coordinator: Error - Fulfillments need the coordinator config account to charge the fee");
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            let quote = config.fee_schedule.quote(request.num_words, request.callback_gas_limit, 0)?;
+            let rebate = config.fee_schedule.callback_rebate(request.callback_gas_limit, callback_units)?;
+            (quote - rebate, rebate)
+        };
+        subscription.debit(fee)?;
+        subscription.total_debited = subscription.total_debited.checked_add(fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        oracle_earnings = oracle_earnings.checked_add(fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        VrfEvent::FeeCharged {
+            request_id: *request_account.key,
+            id: request.id,
+            subscription: *subscription_account.key,
+            fee,
+            callback_units_used: callback_units,
+            rebate,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        oracle_config.accrued_fees = oracle_config.accrued_fees.checked_add(oracle_earnings)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        store_header(subscription_account, subscription)?;
+        Ok(oracle_earnings)
+    }
+
+    /// Pays `oracle` the fee held in a direct request's account, everything
+    /// above its rent, and returns it
+    fn collect_direct_fee(
+        oracle: &AccountInfo,
+        request_account: &AccountInfo,
+        request: &RandomnessRequest,
+        clock: &Clock,
+        events: &EventEmitter,
+    ) -> Result<u64, ProgramError> {
+        let rent = Rent::get()?.minimum_balance(request_account.data_len());
+        let fee = request_account.lamports().saturating_sub(rent);
+        let oracle_lamports = oracle.lamports().checked_add(fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **oracle.try_borrow_mut_lamports()? = oracle_lamports;
+        **request_account.try_borrow_mut_lamports()? -= fee;
+
+        VrfEvent::FeeCharged {
+            request_id: *request_account.key,
+            id: request.id,
+            subscription: Pubkey::default(),
+            fee,
+            callback_units_used: 0,
+            rebate: 0,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Ok(fee)
+    }
+
+    fn process_cancel_request(program_id: &Pubkey, accounts: &[AccountInfo], events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let request_account = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let (stats_account, others) = Self::split_stats_account(program_id, accounts_iter.as_slice());
+        let (request_pool_account, _) = Self::split_request_pool_account(program_id, subscription_account.key, others);
+
+        if !owner.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let request: RandomnessRequest = load_mut(request_account, program_id)?;
+        let subscription = Self::load_request_subscription(program_id, &request, subscription_account, load_header_mut)?;
+
+        if request.status != RequestStatus::Pending {
+            return Err(VrfCoordinatorError::InvalidRequestStatus.into());
+        }
+
+        let owner_key = match &subscription {
+            Some(subscription) => subscription.owner,
+            None => request.requester,
+        };
+        if owner_key != *owner.key {
+            return Err(VrfCoordinatorError::InvalidSubscriptionOwner.into());
+        }
+
+        Self::close_request(program_id, request_account, &request, subscription_account, subscription, owner, stats_account, request_pool_account)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::RequestCancelled {
+            request_id: *request_account.key,
+            id: request.id,
+            subscription: request.subscription,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)
+    }
+
+    fn process_expire_request(program_id: &Pubkey, accounts: &[AccountInfo], events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let request_account = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let requester = next_account_info(accounts_iter)?;
+        let (stats_account, others) = Self::split_stats_account(program_id, accounts_iter.as_slice());
+        let (request_pool_account, _) = Self::split_request_pool_account(program_id, subscription_account.key, others);
+
+        let request: RandomnessRequest = load_mut(request_account, program_id)?;
+        let subscription = Self::load_request_subscription(program_id, &request, subscription_account, load_header_mut)?;
+
+        // A request whose chunked delivery stalled may expire too, but not be
+        // cancelled, as its owner has seen the words delivered so far
+        if !matches!(request.status, RequestStatus::Pending | RequestStatus::Fulfilling) {
+            return Err(VrfCoordinatorError::InvalidRequestStatus.into());
+        }
+        if Clock::get()?.slot < request.expiration_slot {
+            return Err(VrfCoordinatorError::RequestNotExpired.into());
+        }
+        if request.requester != *requester.key {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+
+        Self::close_request(program_id, request_account, &request, subscription_account, subscription, requester, stats_account, request_pool_account)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::RequestExpired {
+            request_id: *request_account.key,
+            id: request.id,
+            subscription: request.subscription,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)
+    }
+
+    /// Cancels an unfulfilled request: refunds `subscription` the reserved fee
+    /// and the escrowed priority fee, counts a failure, drops it from the request
+    /// pool and closes the request account, returning its rent, and the fee
+    /// of a direct request, to `recipient`
+    #[allow(clippy::too_many_arguments)]
+    fn close_request(
+        program_id: &Pubkey,
+        request_account: &AccountInfo,
+        request: &RandomnessRequest,
+        subscription_account: &AccountInfo,
+        subscription: Option<SubscriptionHeader>,
+        recipient: &AccountInfo,
+        stats_account: Option<&AccountInfo>,
+        request_pool_account: Option<&AccountInfo>,
+    ) -> ProgramResult {
+        if let Some(mut subscription) = subscription {
+            subscription.record_request_closed();
+
+            // Refund exactly what the request escrowed
+            let refund = request.reserved_fee.checked_add(request.priority_fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            subscription.release(refund, request.reserved_native)?;
+
+            store_header(subscription_account, &subscription)?;
+        }
+        Self::update_stats(program_id, stats_account, CoordinatorStats::record_failure)?;
+        Self::update_request_pool(program_id, request_pool_account, &request.subscription, |pool| {
+            pool.remove(request_account.key)
+        })?;
+
+        // Close request account, returning its rent to the recipient
+        let recipient_lamports = recipient.lamports().checked_add(request_account.lamports())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **recipient.try_borrow_mut_lamports()? = recipient_lamports;
+        **request_account.try_borrow_mut_lamports()? = 0;
+        request_account.data.borrow_mut().fill(0);
+
+        Ok(())
+    }
+
+    fn process_register_oracle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        oracle_key: Pubkey,
+        vrf_key: [u8; 32],
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let audit_log_account = next_account_info(accounts_iter)?;
+        let payer = Self::admin_payer(admin, accounts_iter)?;
+
+        Self::load_config_as_admin(program_id, admin, config_account, load)?;
+
+        let oracle_config = OracleConfig {
+            oracle_key,
+            vrf_key,
+            is_active: true,
+            accrued_fees: 0,
+            fulfillments: 0,
+            total_latency_slots: 0,
+            failures: 0,
+        };
+
+        let rent = Rent::get()?;
+        let space = OracleConfig::LEN;
+        let lamports = rent.minimum_balance(space);
+
+        // The registry keys configs by oracle, so an oracle is registered once
+        let bump = Self::check_oracle_config_address(program_id, oracle_config_account, &oracle_key)?;
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                oracle_config_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer.clone(),
+                oracle_config_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[OracleConfig::SEED, oracle_key.as_ref(), &[bump]]],
+        )?;
+
+        store(oracle_config_account, &oracle_config)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::OracleRegistered {
+            oracle_config: *oracle_config_account.key,
+            oracle_key,
+            vrf_key,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Self::record_admin_action(
+            program_id, admin, payer, audit_log_account, system_program,
+            AdminAction::RegisterOracle { oracle_key, vrf_key },
+        )
+    }
+
+    fn process_deactivate_oracle(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        oracle_key: Pubkey,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let audit_log_account = next_account_info(accounts_iter)?;
+        let payer = Self::admin_payer(admin, accounts_iter)?;
+
+        let mut config = Self::load_config_as_admin(program_id, admin, config_account, load)?;
+
+        let mut oracle_config: OracleConfig = load_mut(oracle_config_account, program_id)?;
+
+        if oracle_config.oracle_key != oracle_key {
+            return Err(VrfCoordinatorError::InvalidOracle.into());
+        }
+        Self::check_oracle_config_address(program_id, oracle_config_account, &oracle_key)?;
+
+        oracle_config.is_active = false;
+        store(oracle_config_account, &oracle_config)?;
+
+        // New requests are no longer assigned to it
+        if config.oracle_rotation.contains(&oracle_key) {
+            config.oracle_rotation.retain(|oracle| *oracle != oracle_key);
+            Self::store_resized_config(config_account, &config, payer, system_program)?;
+        }
+
+        let clock = Clock::get()?;
+        VrfEvent::OracleDeactivated {
+            oracle_config: *oracle_config_account.key,
+            oracle_key,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Self::record_admin_action(
+            program_id, admin, payer, audit_log_account, system_program,
+            AdminAction::DeactivateOracle { oracle_key },
+        )
+    }
+
+    fn process_set_backstop_policy(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        timeout_slots: u64,
+        bonus: u64,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+
+        let mut subscription = Self::load_subscription_as_owner(program_id, owner, subscription_account)?;
+        subscription.backstop_timeout_slots = timeout_slots;
+        subscription.backstop_bonus = bonus;
+
+        store(subscription_account, &subscription)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::ConfigUpdated {
+            subscription: *subscription_account.key,
+            backstop_timeout_slots: timeout_slots,
+            backstop_bonus: bonus,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Ok(())
+    }
+
+    fn process_set_rate_limits(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_requests_per_slot: u32,
+        max_pending_requests: u32,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+
+        let mut subscription = Self::load_subscription_as_owner(program_id, owner, subscription_account)?;
+        subscription.max_requests_per_slot = max_requests_per_slot;
+        subscription.max_pending_requests = max_pending_requests;
+
+        store(subscription_account, &subscription)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::RateLimitsUpdated {
+            subscription: *subscription_account.key,
+            max_requests_per_slot,
+            max_pending_requests,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Ok(())
+    }
+
+    fn process_add_consumer(program_id: &Pubkey, accounts: &[AccountInfo], consumer: Pubkey, events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        let mut subscription = Self::load_subscription_as_owner(program_id, owner, subscription_account)?;
+        subscription.add_consumer(consumer)?;
+
+        let space = Subscription::space(subscription.consumers.len());
+        let lamports = Rent::get()?.minimum_balance(space);
+        if subscription_account.lamports() < lamports {
+            invoke(
+                &system_instruction::transfer(
+                    owner.key,
+                    subscription_account.key,
+                    lamports - subscription_account.lamports(),
+                ),
+                &[
+                    owner.clone(),
+                    subscription_account.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+        subscription_account.realloc(space, false)?;
+        store(subscription_account, &subscription)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::ConsumerAdded {
+            subscription: *subscription_account.key,
+            consumer,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Ok(())
+    }
+
+    fn process_remove_consumer(program_id: &Pubkey, accounts: &[AccountInfo], consumer: Pubkey, events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+
+        let mut subscription = Self::load_subscription_as_owner(program_id, owner, subscription_account)?;
+        subscription.remove_consumer(&consumer)?;
+
+        // Shrink the account and hand the rent it no longer needs to the owner
+        let space = Subscription::space(subscription.consumers.len());
+        store(subscription_account, &subscription)?;
+        subscription_account.realloc(space, false)?;
+        let excess = subscription_account.lamports().saturating_sub(Rent::get()?.minimum_balance(space));
+        let owner_lamports = owner.lamports().checked_add(excess)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **owner.try_borrow_mut_lamports()? = owner_lamports;
+        **subscription_account.try_borrow_mut_lamports()? -= excess;
+
+        let clock = Clock::get()?;
+        VrfEvent::ConsumerRemoved {
+            subscription: *subscription_account.key,
+            consumer,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Ok(())
+    }
+
+    fn process_request_subscription_ownership_transfer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_owner: Pubkey,
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let owner = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+
+        let mut subscription = Self::load_subscription_as_owner(program_id, owner, subscription_account)?;
+        subscription.pending_owner = new_owner;
+        store(subscription_account, &subscription)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::SubscriptionOwnershipTransferRequested {
+            subscription: *subscription_account.key,
+            owner: *owner.key,
+            new_owner,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Ok(())
+    }
+
+    fn process_accept_subscription_ownership(program_id: &Pubkey, accounts: &[AccountInfo], events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let new_owner = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+
+        if !new_owner.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut subscription: Subscription = load_mut(subscription_account, program_id)?;
+        if subscription.pending_owner != *new_owner.key {
+            msg!("VRF Coordinator: Error - Subscription was not offered to the signer");
+            return Err(VrfCoordinatorError::InvalidSubscriptionOwner.into());
+        }
+        let previous_owner = subscription.owner;
+        subscription.owner = subscription.pending_owner;
+        subscription.pending_owner = Pubkey::default();
+        store(subscription_account, &subscription)?;
+
+        let clock = Clock::get()?;
+        VrfEvent::SubscriptionOwnershipTransferred {
+            subscription: *subscription_account.key,
+            previous_owner,
+            new_owner: *new_owner.key,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        Ok(())
+    }
+
+    /// Loads the subscription for a change only its owner may make
+    fn load_subscription_as_owner(
+        program_id: &Pubkey,
+        owner: &AccountInfo,
+        subscription_account: &AccountInfo,
+    ) -> Result<Subscription, ProgramError> {
+        if !owner.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let subscription: Subscription = load_mut(subscription_account, program_id)?;
+        if subscription.owner != *owner.key {
+            return Err(VrfCoordinatorError::InvalidSubscriptionOwner.into());
+        }
+        Ok(subscription)
+    }
+
+    fn process_audit_subscription(program_id: &Pubkey, accounts: &[AccountInfo], events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let subscription_account = next_account_info(accounts_iter)?;
+        let vault = next_account_info(accounts_iter)?;
+        let subscription_token = next_account_info(accounts_iter)?;
+
+        let mut subscription: Subscription = load_mut(subscription_account, program_id)?;
+        if *vault.key != subscription_vault_address(program_id, subscription_account.key) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        // A subscription never funded with wrapped SOL has no token account
+        let held_tokens = if subscription_token.data_is_empty()
+            && *subscription_token.key == subscription_token_address(program_id, subscription_account.key) {
+            0
+        } else {
+            Self::check_subscription_token_account(program_id, subscription_account.key, subscription_token)?;
+            spl_token::state::Account::unpack(&subscription_token.try_borrow_data()?)?.amount
+        };
+        let held_lamports = vault.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+
+        // Each balance must be held by its vault, together they cannot exceed
+        // what was ever funded, and they must match the history of debits and
+        // of the releases of reserved fees. The nonce may only have grown
+        // since the last audit.
+        let expected_balance = subscription.expected_balance();
+        let balance = subscription.balance.checked_add(subscription.native_balance)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let held = held_tokens.saturating_add(held_lamports);
+        let is_backed = subscription.balance <= held_tokens && subscription.native_balance <= held_lamports;
+        if expected_balance != Some(balance)
+            || balance > subscription.total_funded
+            || !is_backed
+            || subscription.nonce < subscription.audited_nonce {
+            let clock = Clock::get()?;
+            VrfEvent::AuditDiscrepancy {
+                subscription: *subscription_account.key,
+                balance,
+                expected_balance,
+                total_funded: subscription.total_funded,
+                held,
+                nonce: subscription.nonce,
+                audited_nonce: subscription.audited_nonce,
+                slot: clock.slot,
+                unix_timestamp: clock.unix_timestamp,
+            }.emit(events)?;
+            return Ok(());
+        }
+
+        subscription.audited_nonce = subscription.nonce;
+        store(subscription_account, &subscription)
+    }
+
+    fn process_initialize_coordinator(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        fee_schedule: FeeSchedule,
+        bridge_program: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let audit_log_account = next_account_info(accounts_iter)?;
+        let payer = Self::admin_payer(admin, accounts_iter)?;
+
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let (expected_config, bump) = derive_config_pda(program_id);
+        if expected_config != *config_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if config_account.data_len() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let space = CoordinatorConfig::LEN;
+        let lamports = Rent::get()?.minimum_balance(space);
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                config_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer.clone(),
+                config_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[CoordinatorConfig::SEED, &[bump]]],
+        )?;
+
+        store(config_account, &CoordinatorConfig {
+            admin: *admin.key,
+            fee_schedule,
+            bridge_program,
+            min_confirmations: MINIMUM_REQUEST_CONFIRMATIONS,
+            max_confirmations: MAXIMUM_REQUEST_CONFIRMATIONS,
+            paused: false,
+            cpi_events: false,
+            min_priority_fee: 0,
+            drand_public_key: [0; DRAND_PUBLIC_KEY_LEN],
+            oracle_rotation: vec![],
+        })?;
+
+        Self::record_admin_action(
+            program_id, admin, payer, audit_log_account, system_program,
+            AdminAction::InitializeCoordinator { fee_schedule, bridge_program },
+        )
+    }
+
+    fn process_get_request_fee(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        num_words: u32,
+        callback_gas_limit: u64,
+        priority: u8,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let config_account = next_account_info(accounts_iter)?;
+
+        let (expected_config, _) = derive_config_pda(program_id);
+        if expected_config != *config_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let config: CoordinatorConfig = load(config_account, program_id)?;
+
+        let fee = config.fee_schedule.quote(num_words, callback_gas_limit, priority)?;
+        set_return_data(&fee.to_le_bytes());
+        Ok(())
+    }
+
+    fn process_init_randomness_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let requester = next_account_info(accounts_iter)?;
+        let randomness_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        if !requester.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let (expected_randomness, bump) = Pubkey::find_program_address(
+            &[RANDOMNESS_SEED, requester.key.as_ref()],
+            program_id
+        );
+        if expected_randomness != *randomness_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if randomness_account.data_len() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let space = RandomnessAccountData::LEN;
+        let lamports = Rent::get()?.minimum_balance(space);
+        invoke_signed(
+            &system_instruction::create_account(
+                requester.key,
+                randomness_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                requester.clone(),
+                randomness_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[RANDOMNESS_SEED, requester.key.as_ref(), &[bump]]],
+        )?;
+
+        store(randomness_account, &RandomnessAccountData {
+            authority: *requester.key,
+            ..Default::default()
+        })
+    }
+
+    fn process_initialize_stats(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let payer = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        if !payer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let (expected_stats, bump) = Pubkey::find_program_address(&[CoordinatorStats::SEED], program_id);
+        if expected_stats != *stats_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if stats_account.data_len() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let space = CoordinatorStats::LEN;
+        let lamports = Rent::get()?.minimum_balance(space);
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                stats_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer.clone(),
+                stats_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[CoordinatorStats::SEED, &[bump]]],
+        )?;
+
+        store(stats_account, &CoordinatorStats::default())
+    }
+
+    fn process_initialize_request_pool(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let payer = next_account_info(accounts_iter)?;
+        let request_pool_account = next_account_info(accounts_iter)?;
+        let subscription_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        if !payer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        check::<Subscription>(subscription_account, program_id)?;
+        let (expected_pool, bump) = Pubkey::find_program_address(
+            &[RequestPool::SEED, subscription_account.key.as_ref()],
+            program_id,
+        );
+        if expected_pool != *request_pool_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if request_pool_account.data_len() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let space = RequestPool::LEN;
+        let lamports = Rent::get()?.minimum_balance(space);
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                request_pool_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer.clone(),
+                request_pool_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[RequestPool::SEED, subscription_account.key.as_ref(), &[bump]]],
+        )?;
+
+        store(request_pool_account, &RequestPool::new(*subscription_account.key))
+    }
+
+    fn process_publish_drand_round(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        round: u64,
+        signature: [u8; DRAND_SIGNATURE_LEN],
+        events: &EventEmitter,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let oracle = next_account_info(accounts_iter)?;
+        let round_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let oracle_config_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        if !oracle.is_signer {
+            return Err(VrfCoordinatorError::InvalidOracleSigner.into());
+        }
+        Self::load_active_oracle(program_id, oracle, oracle_config_account)?;
+
+        let (expected_config, _) = derive_config_pda(program_id);
+        if expected_config != *config_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let config: CoordinatorConfig = load(config_account, program_id)?;
+        if config.drand_public_key == [0; DRAND_PUBLIC_KEY_LEN] {
+            return Err(VrfCoordinatorError::DrandPublicKeyNotSet.into());
+        }
+        drand::verify_round(&config.drand_public_key, round, &signature)?;
+
+        let (expected_round, bump) = Pubkey::find_program_address(
+            &[DRAND_ROUND_SEED, &round.to_le_bytes()],
+            program_id
+        );
+        if expected_round != *round_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if round_account.data_len() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let space = DrandRound::LEN;
+        let lamports = Rent::get()?.minimum_balance(space);
+        invoke_signed(
+            &system_instruction::create_account(
+                oracle.key,
+                round_account.key,
+                lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                oracle.clone(),
+                round_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[DRAND_ROUND_SEED, &round.to_le_bytes(), &[bump]]],
+        )?;
+
+        let clock = Clock::get()?;
+        let drand_round = DrandRound {
+            round,
+            signature,
+            randomness: hash(&signature).to_bytes(),
+            oracle: *oracle.key,
+            published_slot: clock.slot,
+        };
+        store(round_account, &drand_round)?;
+
+        VrfEvent::DrandRoundPublished {
+            round,
+            randomness: drand_round.randomness,
+            oracle: *oracle.key,
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+
+        let entry = BeaconEntry {
+            round,
+            randomness: drand_round.randomness,
+            published_slot: clock.slot,
+        };
+        while let Some(stream_account) = accounts_iter.next() {
+            Self::stream_beacon_round(program_id, stream_account, round_account, accounts_iter, entry)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a published round into a beacon stream and invokes the stream's
+    /// callback, taking the callback program and account from `accounts_iter`
+    fn stream_beacon_round<'a, 'b>(
+        program_id: &Pubkey,
+        stream_account: &AccountInfo<'a>,
+        round_account: &AccountInfo<'a>,
+        accounts_iter: &mut impl Iterator<Item = &'b AccountInfo<'a>>,
+        entry: BeaconEntry,
+    ) -> ProgramResult
+    where
+        'a: 'b,
+    {
+        let mut stream: BeaconStream = load_mut(stream_account, program_id)?;
+        stream.push(entry)?;
+        store(stream_account, &stream)?;
+        if !stream.has_callback() {
+            return Ok(());
+        }
+
+        let callback_program = next_account_info(accounts_iter)?;
+        let callback_account = next_account_info(accounts_iter)?;
+        if *callback_program.key != stream.callback_program || *callback_account.key != stream.callback_account {
+            return Err(VrfCoordinatorError::InvalidRequestParameters.into());
+        }
+        // A callback that ran out of compute units would fail the round for every stream
+        let remaining_units = sol_remaining_compute_units();
+        if remaining_units < stream.callback_gas_limit {
+            msg!("VRF Coordinator: Skipping beacon callback of {} - {} compute units left, {} required",
+                stream_account.key, remaining_units, stream.callback_gas_limit);
+            return Ok(());
+        }
+        invoke(
+            &Instruction::new_with_bytes(
+                *callback_program.key,
+                &stream.callback_data,
+                vec![
+                    AccountMeta::new_readonly(*stream_account.key, false),
+                    AccountMeta::new_readonly(*round_account.key, false),
+                    AccountMeta::new(*callback_account.key, false),
+                ],
+            ),
+            &[
+                stream_account.clone(),
+                round_account.clone(),
+                callback_account.clone(),
+                callback_program.clone(),
+            ],
+        )
+    }
+
+    fn process_export_attestation(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        nonce: u32,
+        consistency_level: u8,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let request_account = next_account_info(accounts_iter)?;
+        let vrf_result_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let bridge_program = next_account_info(accounts_iter)?;
+        let bridge_accounts = accounts_iter.as_slice();
+
+        let request: RandomnessRequestHeader = load_header(request_account, program_id)?;
+        if !request.status()?.is_fulfilled() {
+            return Err(VrfCoordinatorError::InvalidRequestStatus.into());
+        }
+
+        Self::check_vrf_result_account(program_id, vrf_result_account, request_account.key)?;
+        let vrf_result: VrfResult = load(vrf_result_account, program_id)?;
+
+        let (expected_config, _) = derive_config_pda(program_id);
+        if expected_config != *config_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let config: CoordinatorConfig = load(config_account, program_id)?;
+        if config.bridge_program == Pubkey::default() || config.bridge_program != *bridge_program.key {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let attestation = Attestation {
+            version: ATTESTATION_VERSION,
+            request_id: *request_account.key,
+            requester: request.requester,
+            seed: request.seed,
+            output: *vrf_result.randomness.first().ok_or(ProgramError::InvalidAccountData)?,
+            proof_hash: hash(&vrf_result.proof).to_bytes(),
+        };
+
+        // Bridge accounts are passed through in the bridge's order, with the
+        // coordinator signing for its emitter wherever it appears
+        let (emitter, emitter_bump) = Pubkey::find_program_address(&[EMITTER_SEED], program_id);
+        let metas = bridge_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer || *account.key == emitter,
+                is_writable: account.is_writable,
+            })
+            .collect();
+        let mut infos = bridge_accounts.to_vec();
+        infos.push(bridge_program.clone());
+
+        invoke_signed(
+            &Instruction {
+                program_id: *bridge_program.key,
+                accounts: metas,
+                data: attestation::post_message_data(&attestation, nonce, consistency_level),
+            },
+            &infos,
+            &[&[EMITTER_SEED, &[emitter_bump]]],
+        )
+    }
+
+    fn process_set_zk_verifying_key(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        verifying_key: &Groth16VerifyingKey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let verifying_key_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let audit_log_account = next_account_info(accounts_iter)?;
+        let payer = Self::admin_payer(admin, accounts_iter)?;
+
+        Self::load_config_as_admin(program_id, admin, config_account, load)?;
+
+        let (expected_key, bump) = Pubkey::find_program_address(&[ZK_VERIFYING_KEY_SEED], program_id);
+        if expected_key != *verifying_key_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // The key may be rotated, for instance after a new trusted setup
+        if verifying_key_account.data_len() == 0 {
+            let space = Groth16VerifyingKey::LEN;
+            let lamports = Rent::get()?.minimum_balance(space);
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    verifying_key_account.key,
+                    lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    payer.clone(),
+                    verifying_key_account.clone(),
+                    system_program.clone(),
+                ],
+                &[&[ZK_VERIFYING_KEY_SEED, &[bump]]],
+            )?;
+        }
+
+        store(verifying_key_account, verifying_key)?;
+
+        Self::record_admin_action(
+            program_id, admin, payer, audit_log_account, system_program,
+            AdminAction::SetZkVerifyingKey { key_hash: hash(&borsh::to_vec(verifying_key)?).to_bytes() },
+        )
+    }
+
+    /// Updates the coordinator config with `update`, which returns the action
+    /// to record in the audit log, for `SetAdmin`, `SetFeeSchedule`,
+    /// `SetConfirmations` and `SetPaused`
+    fn process_update_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        update: impl FnOnce(&mut CoordinatorConfig) -> AdminAction,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let audit_log_account = next_account_info(accounts_iter)?;
+        let payer = Self::admin_payer(admin, accounts_iter)?;
+
+        let mut config = Self::load_config_as_admin(program_id, admin, config_account, load_mut)?;
+        let action = update(&mut config);
+        store(config_account, &config)?;
+
+        Self::record_admin_action(program_id, admin, payer, audit_log_account, system_program, action)
+    }
+
+    fn process_set_confirmations(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        min_confirmations: u8,
+        max_confirmations: u8,
+    ) -> ProgramResult {
+        if min_confirmations < MINIMUM_REQUEST_CONFIRMATIONS || min_confirmations > max_confirmations {
+            return Err(VrfCoordinatorError::InvalidRequestConfirmations.into());
+        }
+        Self::process_update_config(program_id, accounts, |config| {
+            config.min_confirmations = min_confirmations;
+            config.max_confirmations = max_confirmations;
+            AdminAction::SetConfirmations { min_confirmations, max_confirmations }
+        })
+    }
+
+    fn process_set_oracle_rotation(program_id: &Pubkey, accounts: &[AccountInfo], oracles: Vec<Pubkey>) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+        let audit_log_account = next_account_info(accounts_iter)?;
+        let payer = Self::admin_payer(admin, accounts_iter)?;
+
+        let mut config = Self::load_config_as_admin(program_id, admin, config_account, load_mut)?;
+        config.set_oracle_rotation(oracles.clone())?;
+        Self::store_resized_config(config_account, &config, payer, system_program)?;
+
+        Self::record_admin_action(
+            program_id, admin, payer, audit_log_account, system_program,
+            AdminAction::SetOracleRotation { oracles },
+        )
+    }
+
+    /// Stores `config`, resizing its account to fit the oracle rotation: `payer`
+    /// funds the rent of growth and collects what shrinking frees
+    fn store_resized_config<'a>(
+        config_account: &AccountInfo<'a>,
+        config: &CoordinatorConfig,
+        payer: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        let space = CoordinatorConfig::space(config.oracle_rotation.len());
+        let lamports = Rent::get()?.minimum_balance(space);
+        if config_account.lamports() < lamports {
+            invoke(
+                &system_instruction::transfer(
+                    payer.key,
+                    config_account.key,
+                    lamports - config_account.lamports(),
+                ),
+                &[
+                    payer.clone(),
+                    config_account.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+        config_account.realloc(space, false)?;
+        store(config_account, config)?;
+
+        let excess = config_account.lamports().saturating_sub(lamports);
+        let payer_lamports = payer.lamports().checked_add(excess)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        **payer.try_borrow_mut_lamports()? = payer_lamports;
+        **config_account.try_borrow_mut_lamports()? -= excess;
+        Ok(())
+    }
+
+    /// Loads the coordinator config with `loader`, checking that `admin` is
+    /// its admin and signed
+    fn load_config_as_admin(
+        program_id: &Pubkey,
+        admin: &AccountInfo,
+        config_account: &AccountInfo,
+        loader: fn(&AccountInfo, &Pubkey) -> Result<CoordinatorConfig, ProgramError>,
+    ) -> Result<CoordinatorConfig, ProgramError> {
+        if !admin.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let (expected_config, _) = derive_config_pda(program_id);
+        if expected_config != *config_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let config = loader(config_account, program_id)?;
+        if config.admin != *admin.key {
+            return Err(VrfCoordinatorError::InvalidAdmin.into());
+        }
+        Ok(config)
+    }
+
+    /// The optional payer after the other accounts of an admin instruction,
+    /// or `admin` if there is none, see [crate::governance]
+    fn admin_payer<'a, 'b>(
+        admin: &'b AccountInfo<'a>,
+        accounts_iter: &mut std::slice::Iter<'b, AccountInfo<'a>>,
+    ) -> Result<&'b AccountInfo<'a>, ProgramError> {
+        let payer = next_account_info(accounts_iter).unwrap_or(admin);
+        if !payer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(payer)
+    }
+
+    /// Appends `action` by `actor` to the admin audit log, creating the log on
+    /// first use. The payer pays the rent for the log's growth.
+    fn record_admin_action<'a>(
+        program_id: &Pubkey,
+        actor: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        audit_log_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        action: AdminAction,
+    ) -> ProgramResult {
+        let (expected_log, bump) = Pubkey::find_program_address(&[ADMIN_AUDIT_LOG_SEED], program_id);
+        if expected_log != *audit_log_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        let entry = borsh::to_vec(&AdminAuditEntry {
+            actor: *actor.key,
+            slot: Clock::get()?.slot,
+            action,
+        })?;
+        let space = audit_log_account.data_len().max(AdminAuditLog::HEADER_LEN) + entry.len();
+        let lamports = Rent::get()?.minimum_balance(space);
+
+        if audit_log_account.data_len() == 0 {
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer.key,
+                    audit_log_account.key,
+                    lamports,
+                    AdminAuditLog::HEADER_LEN as u64,
+                    program_id,
+                ),
+                &[
+                    payer.clone(),
+                    audit_log_account.clone(),
+                    system_program.clone(),
+                ],
+                &[&[ADMIN_AUDIT_LOG_SEED, &[bump]]],
+            )?;
+            store(audit_log_account, &AdminAuditLog::default())?;
+        } else {
+            check::<AdminAuditLog>(audit_log_account, program_id)?;
+            if audit_log_account.lamports() < lamports {
+                invoke(
+                    &system_instruction::transfer(
+                        payer.key,
+                        audit_log_account.key,
+                        lamports - audit_log_account.lamports(),
+                    ),
+                    &[
+                        payer.clone(),
+                        audit_log_account.clone(),
+                        system_program.clone(),
+                    ],
+                )?;
+            }
+        }
+
+        audit_log_account.realloc(space, false)?;
+        audit_log::append_entry(&mut audit_log_account.try_borrow_mut_data()?, &entry)
+    }
+
+    fn process_migrate_account(program_id: &Pubkey, accounts: &[AccountInfo], events: &EventEmitter) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let payer = next_account_info(accounts_iter)?;
+        let account = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        if !payer.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if !account.is_writable {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let Some((from_version, upgraded)) = migrations::upgrade_account(&account.try_borrow_data()?)? else {
+            verbose_msg!("VRF Coordinator: Account is already at its current layout");
+            return Ok(());
+        };
+
+        let lamports = Rent::get()?.minimum_balance(upgraded.len());
+        if account.lamports() < lamports {
+            invoke(
+                &system_instruction::transfer(
+                    payer.key,
+                    account.key,
+                    lamports - account.lamports(),
+                ),
+                &[
+                    payer.clone(),
+                    account.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+        account.realloc(upgraded.len(), false)?;
+        account.try_borrow_mut_data()?.copy_from_slice(&upgraded);
+
+        let clock = Clock::get()?;
+        VrfEvent::AccountMigrated {
+            account: *account.key,
+            from_version,
+            to_version: upgraded[DISCRIMINATOR_LEN],
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+        }.emit(events)?;
+        Ok(())
+    }
+
+    /// An event the coordinator emitted by invoking itself, see
+    /// [crate::event]. Only the coordinator can sign as the event authority.
+    fn process_event_cpi(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let authority = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if !authority.is_signer || *authority.key != event_authority_address(program_id).0 {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        ark_bn254::Fr,
+        crate::{
+            drand::tests::TestBeacon,
+            example_consumer::{self, GameInstruction, GameState},
+            state::{MAXIMUM_ALPHA_LEN, MAXIMUM_CALLBACK_GAS_LIMIT, MAXIMUM_RANDOM_WORDS, MINIMUM_CALLBACK_GAS_LIMIT, REQUEST_EXPIRATION_SLOTS},
+            test_harness::TestHarness,
+        },
+        mangekyou::{
+            ecvrf_edwards25519,
+            kamui_vrf::{
+                ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof},
+                VRFKeyPair, VRFProof,
+            },
+        },
+        solana_program::{program_option::COption, system_program},
+        solana_sdk::account::Account,
+    };
+
+    const LAMPORTS: u64 = 10_000_000_000;
+    const BALANCE: u64 = 1_000;
+    const MIN_BALANCE: u64 = 10;
+
+    fn instruction(program_id: &Pubkey, instruction: VrfCoordinatorInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+        Instruction::new_with_bytes(*program_id, &borsh::to_vec(&instruction).unwrap(), accounts)
+    }
+
+    fn request_randomness(seed: [u8; 32], num_words: u32) -> VrfCoordinatorInstruction {
+        VrfCoordinatorInstruction::RequestRandomness {
+            seed,
+            callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+            num_words,
+            minimum_confirmations: 1,
+            callback_gas_limit: 100_000,
+            priority_fee: 0,
+        }
+    }
+
+    /// An initialized coordinator with a funded subscription, an active oracle
+    /// and the example consumer, whose game belongs to the subscription owner
+    struct Fixture {
+        harness: TestHarness,
+        program_id: Pubkey,
+        admin: Pubkey,
+        owner: Pubkey,
+        subscription: Pubkey,
+        oracle: Pubkey,
+        oracle_config: Pubkey,
+        vrf_keypair: ECVRFKeyPair,
+        game_program: Pubkey,
+        game_state: Pubkey,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let program_id = Pubkey::new_unique();
+            let mut harness = TestHarness::new(program_id);
+            let (owner, subscription, oracle) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+            harness.fund(owner, LAMPORTS);
+            harness.fund(oracle, LAMPORTS);
+
+            harness.set_state(subscription, program_id, &Subscription {
+                owner,
+                balance: BALANCE,
+                min_balance: MIN_BALANCE,
+                confirmations: 1,
+                nonce: 0,
+                backstop_timeout_slots: DEFAULT_BACKSTOP_TIMEOUT_SLOTS,
+                backstop_bonus: 0,
+                total_funded: BALANCE,
+                total_credited: 0,
+                total_debited: 0,
+                audited_nonce: 0,
+                pending_owner: Pubkey::default(),
+                native_balance: 0,
+                max_requests_per_slot: 0,
+                max_pending_requests: 0,
+                last_request_slot: 0,
+                requests_in_slot: 0,
+                pending_requests: 0,
+                consumers: vec![],
+            });
+
+            let vrf_keypair = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[1; 32]).unwrap());
+            let (oracle_config, _) = derive_oracle_config_pda(&program_id, &oracle);
+            harness.set_state(oracle_config, program_id, &OracleConfig {
+                oracle_key: oracle,
+                vrf_key: vrf_keypair.pk.as_ref().try_into().unwrap(),
+                is_active: true,
+                accrued_fees: 0,
+                fulfillments: 0,
+                total_latency_slots: 0,
+                failures: 0,
+            });
+
+            let game_program = Pubkey::new_unique();
+            harness.add_program(game_program, example_consumer::process_instruction);
+            let (game_state, _) = Pubkey::find_program_address(&[b"game_state", owner.as_ref()], &game_program);
+            harness.set_state(game_state, game_program, &GameState {
+                owner,
+                subscription,
+                vrf_coordinator: program_id,
+                current_number: 0,
+                is_pending: true,
+            });
+
+            let admin = Pubkey::new_unique();
+            harness.fund(admin, LAMPORTS);
+            let mut fixture = Self {
+                harness, program_id, admin, owner, subscription, oracle, oracle_config, vrf_keypair, game_program, game_state,
+            };
+            let initialize = fixture.admin_instruction(&admin, &fixture.config(), VrfCoordinatorInstruction::InitializeCoordinator {
+                fee_schedule: FeeSchedule::default(),
+                bridge_program: Pubkey::default(),
+            });
+            fixture.harness.process(&initialize).unwrap();
+            fixture
+        }
+
+        fn pda(&self, seeds: &[&[u8]]) -> Pubkey {
+            Pubkey::find_program_address(seeds, &self.program_id).0
+        }
+
+        /// Address of the subscription's next request
+        fn next_request(&self) -> Pubkey {
+            let nonce = self.harness.load::<Subscription>(&self.subscription).nonce;
+            derive_request_pda(&self.program_id, &self.subscription, nonce).0
+        }
+
+        fn vrf_result(&self, request: &Pubkey) -> Pubkey {
+            derive_vrf_result_pda(&self.program_id, request).0
+        }
+
+        fn audit_log(&self) -> Pubkey {
+            self.pda(&[ADMIN_AUDIT_LOG_SEED])
+        }
+
+        fn config(&self) -> Pubkey {
+            derive_config_pda(&self.program_id).0
+        }
+
+        /// Request accounts for `variant`, followed by `optional` and the
+        /// coordinator config
+        fn request_instruction(&self, variant: VrfCoordinatorInstruction, optional: Vec<AccountMeta>) -> Instruction {
+            let mut accounts = vec![
+                AccountMeta::new(self.owner, true),
+                AccountMeta::new(self.next_request(), false),
+                AccountMeta::new(self.subscription, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ];
+            accounts.extend(optional);
+            accounts.push(AccountMeta::new_readonly(self.config(), false));
+            instruction(&self.program_id, variant, accounts)
+        }
+
+        /// Opens a request for one word and returns its address
+        fn request(&mut self) -> Pubkey {
+            let request = self.next_request();
+            let instruction = self.request_instruction(request_randomness([1; 32], 1), vec![]);
+            self.harness.process(&instruction).unwrap();
+            request
+        }
+
+        /// Creates a wrapped SOL token account of `owner` holding `amount`,
+        /// deploying the SPL Token program to move it
+        fn token_account(&mut self, address: Pubkey, owner: Pubkey, amount: u64) {
+            self.harness.add_program(spl_token::id(), spl_token::processor::Processor::process);
+            let reserve = Rent::default().minimum_balance(spl_token::state::Account::LEN);
+            let mut data = vec![0; spl_token::state::Account::LEN];
+            spl_token::state::Account {
+                mint: FUNDING_MINT,
+                owner,
+                amount,
+                state: spl_token::state::AccountState::Initialized,
+                is_native: COption::Some(reserve),
+                ..spl_token::state::Account::default()
+            }.pack_into_slice(&mut data);
+            self.harness.set_account(address, Account {
+                lamports: reserve + amount,
+                data,
+                owner: spl_token::id(),
+                ..Account::default()
+            });
+        }
+
+        /// The oracle's proof for `request`
+        fn prove(&self, request: &Pubkey) -> Vec<u8> {
+            let seed = self.harness.load::<RandomnessRequest>(request).seed;
+            self.vrf_keypair.prove(&seed).to_bytes_legacy()
+        }
+
+        /// `FulfillRandomness` of `request` by the fixture's oracle, with the
+        /// subscription account `subscription` and the coordinator config
+        fn fulfill_instruction(&self, request: &Pubkey, subscription: &Pubkey) -> Instruction {
+            self.fulfill_with_proof(request, subscription, self.prove(request), self.vrf_keypair.pk.as_ref().to_vec())
+        }
+
+        fn fulfill_with_proof(&self, request: &Pubkey, subscription: &Pubkey, proof: Vec<u8>, public_key: Vec<u8>) -> Instruction {
+            self.fulfill_in_suite(request, subscription, proof, public_key, VrfSuite::default())
+        }
+
+        fn fulfill_in_suite(
+            &self,
+            request: &Pubkey,
+            subscription: &Pubkey,
+            proof: Vec<u8>,
+            public_key: Vec<u8>,
+            suite: VrfSuite,
+        ) -> Instruction {
+            instruction(
+                &self.program_id,
+                VrfCoordinatorInstruction::FulfillRandomness { proof, public_key, suite },
+                vec![
+                    AccountMeta::new(self.oracle, true),
+                    AccountMeta::new(*request, false),
+                    AccountMeta::new(self.vrf_result(request), false),
+                    AccountMeta::new_readonly(self.game_program, false),
+                    AccountMeta::new(*subscription, false),
+                    AccountMeta::new_readonly(system_program::ID, false),
+                    AccountMeta::new_readonly(self.game_program, false),
+                    AccountMeta::new(self.game_state, false),
+                    AccountMeta::new(self.oracle_config, false),
+                    AccountMeta::new_readonly(self.config(), false),
+                ],
+            )
+        }
+
+        /// Accounts of an admin instruction on the account at `target`
+        fn admin_instruction(&self, admin: &Pubkey, target: &Pubkey, variant: VrfCoordinatorInstruction) -> Instruction {
+            instruction(&self.program_id, variant, vec![
+                AccountMeta::new(*admin, true),
+                AccountMeta::new(*target, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(self.audit_log(), false),
+            ])
+        }
+
+        /// Accounts of `RegisterOracle` or `DeactivateOracle` on `oracle_config`
+        fn registry_instruction(&self, admin: &Pubkey, oracle_config: &Pubkey, variant: VrfCoordinatorInstruction) -> Instruction {
+            let mut instruction = self.admin_instruction(admin, oracle_config, variant);
+            instruction.accounts.insert(1, AccountMeta::new(self.config(), false));
+            instruction
+        }
+
+        /// Runs a config update by the admin
+        fn update_config(&mut self, variant: VrfCoordinatorInstruction) {
+            let instruction = self.admin_instruction(&self.admin, &self.config(), variant);
+            self.harness.process(&instruction).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_create_subscription() {
+        let mut fixture = Fixture::new();
+        let subscription = Pubkey::new_unique();
+        let create = |subscription_signs| instruction(
+            &fixture.program_id,
+            VrfCoordinatorInstruction::CreateSubscription { min_balance: MIN_BALANCE, confirmations: 3 },
+            vec![
+                AccountMeta::new(fixture.owner, true),
+                AccountMeta::new(subscription, subscription_signs),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        );
+
+        assert_eq!(fixture.harness.process(&create(false)), Err(ProgramError::MissingRequiredSignature));
+        fixture.harness.process(&create(true)).unwrap();
+
+        let account = fixture.harness.account(&subscription);
+        assert_eq!(account.owner, fixture.program_id);
+        assert_eq!(account.data.len(), Subscription::LEN);
+        assert_eq!(account.lamports, Rent::default().minimum_balance(Subscription::LEN));
+        let state: Subscription = fixture.harness.load(&subscription);
+        assert_eq!((state.owner, state.min_balance, state.confirmations), (fixture.owner, MIN_BALANCE, 3));
+        assert!(matches!(fixture.harness.events()[..], [VrfEvent::SubscriptionCreated { min_balance: MIN_BALANCE, .. }]));
+
+        // The account exists now
+        assert_eq!(fixture.harness.process(&create(true)), Err(ProgramError::Custom(0)));
+    }
+
+    #[test]
+    fn test_request_randomness() {
+        let mut fixture = Fixture::new();
+        fixture.harness.warp_to_slot(42);
+        let request = fixture.request();
+
+        let state: RandomnessRequest = fixture.harness.load(&request);
+        assert_eq!(state.requester, fixture.owner);
+        assert_eq!(state.subscription, fixture.subscription);
+        assert_eq!(state.status, RequestStatus::Pending);
+        assert_eq!((state.request_block, state.nonce), (42, 0));
+        assert_eq!(state.assigned_oracle, Pubkey::default());
+        assert_eq!(state.commitment, state.compute_commitment());
+        let id = RequestId::derive(&fixture.subscription, 0, &[1; 32], &fixture.owner);
+        assert_eq!(state.id, id);
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).nonce, 1);
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::RandomnessRequested { request_id, id: event_id, slot: 42, .. }] if request_id == request && event_id == id
+        ));
+
+        // The next request goes to the next nonce
+        assert_ne!(fixture.next_request(), request);
+        let mut instruction = fixture.request_instruction(request_randomness([2; 32], 1), vec![]);
+        instruction.accounts[1].pubkey = request;
+        assert_eq!(fixture.harness.process(&instruction), Err(ProgramError::InvalidSeeds));
+    }
+
+    #[test]
+    fn test_request_randomness_rejections() {
+        let mut fixture = Fixture::new();
+
+        let mut unsigned = fixture.request_instruction(request_randomness([1; 32], 1), vec![]);
+        unsigned.accounts[0].is_signer = false;
+        assert_eq!(fixture.harness.process(&unsigned), Err(ProgramError::MissingRequiredSignature));
+
+        let alpha = VrfCoordinatorInstruction::RequestRandomnessWithAlpha {
+            alpha: vec![0; MAXIMUM_ALPHA_LEN + 1],
+            callback_data: vec![],
+            num_words: 1,
+            minimum_confirmations: 1,
+            callback_gas_limit: 100_000,
+        };
+        let instruction = fixture.request_instruction(alpha, vec![]);
+        assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::AlphaTooLong.into()));
+
+        for num_words in [0, MAXIMUM_RANDOM_WORDS + 1] {
+            let instruction = fixture.request_instruction(request_randomness([1; 32], num_words), vec![]);
+            assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::InvalidNumberOfWords.into()));
+        }
+        for callback_gas_limit in [MINIMUM_CALLBACK_GAS_LIMIT - 1, MAXIMUM_CALLBACK_GAS_LIMIT + 1] {
+            let request = VrfCoordinatorInstruction::RequestRandomness {
+                seed: [1; 32],
+                callback_data: vec![],
+                num_words: 1,
+                minimum_confirmations: 1,
+                callback_gas_limit,
+                priority_fee: 0,
+            };
+            let instruction = fixture.request_instruction(request, vec![]);
+            assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::InvalidCallbackGasLimit.into()));
+        }
+
+        let mut subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        subscription.balance = MIN_BALANCE - 1;
+        fixture.harness.set_state(fixture.subscription, fixture.program_id, &subscription);
+        let instruction = fixture.request_instruction(request_randomness([1; 32], 1), vec![]);
+        assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::InsufficientBalance.into()));
+        assert_eq!(fixture.harness.account(&fixture.next_request()).lamports, 0);
+    }
+
+    #[test]
+    fn test_request_assigned_to_oracle() {
+        let mut fixture = Fixture::new();
+        let request = fixture.next_request();
+        let instruction = fixture.request_instruction(
+            request_randomness([1; 32], 1),
+            vec![AccountMeta::new_readonly(fixture.oracle_config, false)],
+        );
+        fixture.harness.process(&instruction).unwrap();
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).assigned_oracle, fixture.oracle);
+
+        let mut oracle_config: OracleConfig = fixture.harness.load(&fixture.oracle_config);
+        oracle_config.is_active = false;
+        fixture.harness.set_state(fixture.oracle_config, fixture.program_id, &oracle_config);
+        let instruction = fixture.request_instruction(
+            request_randomness([1; 32], 1),
+            vec![AccountMeta::new_readonly(fixture.oracle_config, false)],
+        );
+        assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::InvalidOracle.into()));
+    }
+
+    #[test]
+    fn test_fulfill_randomness() {
+        let mut fixture = Fixture::new();
+        fixture.harness.warp_to_slot(10);
+        let request = fixture.request();
+        fixture.harness.warp_to_slot(13);
+
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        // The fee is quoted by the coordinator config
+        let mut without_config = fulfill.clone();
+        without_config.accounts.pop();
+        assert_eq!(fixture.harness.process(&without_config), Err(ProgramError::NotEnoughAccountKeys));
+        // Nor can an account of any other kind pass for an optional one
+        let mut unrecognized = fulfill.clone();
+        unrecognized.accounts.push(AccountMeta::new(fixture.game_state, false));
+        assert_eq!(fixture.harness.process(&unrecognized), Err(ProgramError::InvalidAccountData));
+        fixture.harness.process(&fulfill).unwrap();
+
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::Fulfilled);
+        let vrf_result: VrfResult = fixture.harness.load(&fixture.vrf_result(&request));
+        assert_eq!(vrf_result.request, request);
+        assert!(vrf_result.fulfills(&request) && !vrf_result.fulfills(&fixture.next_request()));
+        assert_eq!(vrf_result.randomness.len(), 1);
+        assert_eq!(fixture.harness.account(&fixture.vrf_result(&request)).data.len(), VrfResult::space(1));
+
+        // The example consumer's callback ran
+        let game: GameState = fixture.harness.load(&fixture.game_state);
+        assert!(!game.is_pending);
+        assert!((1..=100).contains(&game.current_number));
+
+        // Without a fee schedule the reserved minimum is released in full
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!(subscription.balance, BALANCE);
+        assert_eq!(subscription.expected_balance(), Some(subscription.balance));
+        let oracle_config: OracleConfig = fixture.harness.load(&fixture.oracle_config);
+        assert_eq!((oracle_config.fulfillments, oracle_config.total_latency_slots), (1, 3));
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::RandomnessFulfilled { request_id, slot: 13, .. }, VrfEvent::FeeCharged { fee: 0, .. }] if request_id == request
+        ));
+
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::RequestAlreadyFulfilled.into()));
+    }
+
+    #[test]
+    fn test_request_reserves_fee() {
+        let mut fixture = Fixture::new();
+        let fee_schedule = FeeSchedule { base_fee: 50, fee_per_million_callback_units: 1_000, ..FeeSchedule::default() };
+        fixture.update_config(VrfCoordinatorInstruction::SetFeeSchedule { fee_schedule });
+        let quote = fee_schedule.quote(1, 100_000, 0).unwrap();
+
+        // The quote is reserved when it exceeds the subscription's minimum
+        let request = fixture.request();
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).reserved_fee, quote);
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!(subscription.balance, BALANCE - quote);
+        assert_eq!(subscription.expected_balance(), Some(subscription.balance));
+
+        // Fulfilling releases it and charges the fee, without crediting anything else
+        fixture.harness.warp_to_slot(1);
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        fixture.harness.process(&fulfill).unwrap();
+        let fee = match fixture.harness.events()[..] {
+            [VrfEvent::RandomnessFulfilled { .. }, VrfEvent::FeeCharged { fee, .. }] => fee,
+            ref events => panic!("unexpected events {events:?}"),
+        };
+        assert!(fee > 0 && fee <= quote);
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!(subscription.balance, BALANCE - fee);
+        assert_eq!(subscription.expected_balance(), Some(subscription.balance));
+        assert_eq!(fixture.harness.load::<OracleConfig>(&fixture.oracle_config).accrued_fees, fee);
+
+        // Nothing is opened that the balance cannot cover
+        let mut subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        subscription.balance = quote - 1;
+        fixture.harness.set_state(fixture.subscription, fixture.program_id, &subscription);
+        let instruction = fixture.request_instruction(request_randomness([2; 32], 1), vec![]);
+        assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::InsufficientBalance.into()));
+    }
+
+    #[test]
+    fn test_fulfill_randomness_skips_callback_without_compute_units() {
+        let mut fixture = Fixture::new();
+        let request = fixture.request();
+        fixture.harness.warp_to_slot(1);
+        fixture.harness.set_remaining_compute_units(100_000 - 1);
+
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        fixture.harness.process(&fulfill).unwrap();
+
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::CallbackSkipped);
+        assert_eq!(fixture.harness.load::<VrfResult>(&fixture.vrf_result(&request)).request, request);
+        // The example consumer's callback did not run
+        assert!(fixture.harness.load::<GameState>(&fixture.game_state).is_pending);
+        let oracle_config: OracleConfig = fixture.harness.load(&fixture.oracle_config);
+        assert_eq!(oracle_config.fulfillments, 1);
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::RandomnessFulfilled { .. }, VrfEvent::CallbackFailed { callback_program, .. }, ..]
+                if callback_program == fixture.game_program
+        ));
+
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::RequestAlreadyFulfilled.into()));
+    }
+
+    #[test]
+    fn test_fulfill_randomness_batch() {
+        let mut fixture = Fixture::new();
+        let requests = [fixture.request(), fixture.request()];
+        let batch = |fixture: &Fixture, entries: &[Pubkey], proofs: Vec<Vec<u8>>| {
+            let mut accounts = vec![
+                AccountMeta::new(fixture.oracle, true),
+                AccountMeta::new(fixture.oracle_config, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ];
+            for request in entries {
+                accounts.extend([
+                    AccountMeta::new(*request, false),
+                    AccountMeta::new(fixture.vrf_result(request), false),
+                    AccountMeta::new(fixture.subscription, false),
+                    AccountMeta::new_readonly(fixture.game_program, false),
+                    AccountMeta::new(fixture.game_state, false),
+                ]);
+            }
+            accounts.push(AccountMeta::new_readonly(fixture.config(), false));
+            instruction(&fixture.program_id, VrfCoordinatorInstruction::FulfillRandomnessBatch { proofs }, accounts)
+        };
+        let proofs: Vec<_> = requests.iter().map(|request| fixture.prove(request)).collect();
+        fixture.harness.warp_to_slot(1);
+
+        assert_eq!(
+            fixture.harness.process(&batch(&fixture, &[], vec![])),
+            Err(VrfCoordinatorError::InvalidRequestParameters.into()),
+        );
+        assert_eq!(
+            fixture.harness.process(&batch(&fixture, &requests[..1], proofs.clone())),
+            Err(ProgramError::NotEnoughAccountKeys),
+        );
+
+        // One bad proof fails the whole batch
+        let mut tampered = proofs.clone();
+        tampered[1][PROOF_LEN - 1] ^= 1;
+        assert_eq!(
+            fixture.harness.process(&batch(&fixture, &requests, tampered)),
+            Err(VrfCoordinatorError::InvalidVrfProof.into()),
+        );
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&requests[0]).status, RequestStatus::Pending);
+
+        fixture.harness.process(&batch(&fixture, &requests, proofs)).unwrap();
+        for request in &requests {
+            assert_eq!(fixture.harness.load::<RandomnessRequest>(request).status, RequestStatus::Fulfilled);
+        }
+        assert_eq!(fixture.harness.load::<OracleConfig>(&fixture.oracle_config).fulfillments, 2);
+        let fulfilled = fixture.harness.events().iter()
+            .filter(|event| matches!(event, VrfEvent::RandomnessFulfilled { .. }))
+            .count();
+        assert_eq!(fulfilled, 2);
+    }
+
+    #[test]
+    fn test_fulfill_expands_words() {
+        let mut fixture = Fixture::new();
+        let request = fixture.next_request();
+        let open = fixture.request_instruction(request_randomness([1; 32], 3), vec![]);
+        fixture.harness.process(&open).unwrap();
+        fixture.harness.warp_to_slot(1);
+
+        let proof = fixture.prove(&request);
+        fixture.harness.process(&fixture.fulfill_instruction(&request, &fixture.subscription)).unwrap();
+        let output = ECVRFProof::from_bytes_legacy(&proof).unwrap().to_hash();
+        let vrf_result: VrfResult = fixture.harness.load(&fixture.vrf_result(&request));
+        assert_eq!(vrf_result.randomness, (0..3).map(|i| expand_word(&output, i)).collect::<Vec<_>>());
+        assert_ne!(vrf_result.randomness[1], vrf_result.randomness[2]);
+        assert_eq!(fixture.harness.account(&fixture.vrf_result(&request)).data.len(), VrfResult::space(3));
+    }
+
+    #[test]
+    fn test_fulfill_rejections() {
+        let mut fixture = Fixture::new();
+        let request = fixture.request();
+
+        let mut unsigned = fixture.fulfill_instruction(&request, &fixture.subscription);
+        unsigned.accounts[0].is_signer = false;
+        assert_eq!(fixture.harness.process(&unsigned), Err(VrfCoordinatorError::InvalidOracleSigner.into()));
+
+        let mut proof = fixture.prove(&request);
+        proof.pop();
+        let short_proof = fixture.fulfill_with_proof(&request, &fixture.subscription, proof, fixture.vrf_keypair.pk.as_ref().to_vec());
+        assert_eq!(fixture.harness.process(&short_proof), Err(VrfCoordinatorError::InvalidVrfProof.into()));
+
+        // Another oracle's config does not authorize the signer
+        let impostor = Pubkey::new_unique();
+        fixture.harness.fund(impostor, LAMPORTS);
+        let mut impostor_fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        impostor_fulfill.accounts[0].pubkey = impostor;
+        assert_eq!(fixture.harness.process(&impostor_fulfill), Err(VrfCoordinatorError::UnauthorizedOracle.into()));
+
+        // A copy of the oracle's config outside the registry is not trusted
+        let copy = Pubkey::new_unique();
+        let config: OracleConfig = fixture.harness.load(&fixture.oracle_config);
+        fixture.harness.set_state(copy, fixture.program_id, &config);
+        let mut unregistered = fixture.fulfill_instruction(&request, &fixture.subscription);
+        unregistered.accounts[8].pubkey = copy;
+        assert_eq!(fixture.harness.process(&unregistered), Err(ProgramError::InvalidSeeds));
+
+        let other_subscription = fixture.fulfill_instruction(&request, &Pubkey::new_unique());
+        assert_eq!(fixture.harness.process(&other_subscription), Err(VrfCoordinatorError::InvalidRequestParameters.into()));
+
+        // A request that no longer matches its commitment is not fulfilled
+        let mut state: RandomnessRequest = fixture.harness.load(&request);
+        state.callback_gas_limit += 1;
+        fixture.harness.set_state(request, fixture.program_id, &state);
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::InvalidCommitment.into()));
+
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::Pending);
+    }
+
+    #[test]
+    fn test_fulfill_verifies_proof() {
+        let mut fixture = Fixture::new();
+        let request = fixture.request();
+        fixture.harness.warp_to_slot(1);
+        let public_key = fixture.vrf_keypair.pk.as_ref().to_vec();
+        let other_keypair = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[8; 32]).unwrap());
+
+        let other_seed = fixture.fulfill_with_proof(
+            &request, &fixture.subscription, fixture.vrf_keypair.prove(&[2; 32]).to_bytes_legacy(), public_key.clone());
+        assert_eq!(fixture.harness.process(&other_seed), Err(VrfCoordinatorError::InvalidVrfProof.into()));
+
+        // Only the registered key counts, even with a proof valid under another key
+        let seed = fixture.harness.load::<RandomnessRequest>(&request).seed;
+        let other_key = fixture.fulfill_with_proof(
+            &request, &fixture.subscription, other_keypair.prove(&seed).to_bytes_legacy(), other_keypair.pk.as_ref().to_vec());
+        assert_eq!(fixture.harness.process(&other_key), Err(VrfCoordinatorError::InvalidVrfProof.into()));
+
+        let mut tampered_proof = fixture.prove(&request);
+        tampered_proof[40] ^= 1;
+        let tampered = fixture.fulfill_with_proof(&request, &fixture.subscription, tampered_proof, public_key);
+        assert_eq!(fixture.harness.process(&tampered), Err(VrfCoordinatorError::InvalidVrfProof.into()));
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::Pending);
+
+        let proof = fixture.prove(&request);
+        fixture.harness.process(&fixture.fulfill_instruction(&request, &fixture.subscription)).unwrap();
+        let output = ECVRFProof::from_bytes_legacy(&proof).unwrap().to_hash();
+        let vrf_result: VrfResult = fixture.harness.load(&fixture.vrf_result(&request));
+        assert_eq!((vrf_result.randomness, vrf_result.proof), (vec![expand_word(&output, 0)], proof));
+    }
+
+    #[test]
+    fn test_fulfill_verifies_edwards25519_proof() {
+        let mut fixture = Fixture::new();
+        let request = fixture.request();
+        fixture.harness.warp_to_slot(1);
+
+        // An oracle on a standard ECVRF stack, registered with its Ed25519 key
+        let secret_key = [9; 32];
+        let public_key = ecvrf_edwards25519::public_key(&secret_key);
+        let mut oracle_config: OracleConfig = fixture.harness.load(&fixture.oracle_config);
+        oracle_config.vrf_key = public_key;
+        fixture.harness.set_state(fixture.oracle_config, fixture.program_id, &oracle_config);
+
+        let seed = fixture.harness.load::<RandomnessRequest>(&request).seed;
+        let proof = ecvrf_edwards25519::prove(&secret_key, &seed).unwrap();
+        let [ristretto, edwards] = [VrfSuite::Ristretto255Sha512, VrfSuite::Edwards25519Sha512Tai].map(|suite| {
+            fixture.fulfill_in_suite(&request, &fixture.subscription, proof.to_bytes().to_vec(), public_key.to_vec(), suite)
+        });
+        assert_eq!(fixture.harness.process(&ristretto), Err(VrfCoordinatorError::InvalidVrfProof.into()));
+        fixture.harness.process(&edwards).unwrap();
+
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::Fulfilled);
+        let vrf_result: VrfResult = fixture.harness.load(&fixture.vrf_result(&request));
+        assert_eq!(vrf_result.randomness, vec![expand_word(&proof.to_hash(), 0)]);
+        assert_eq!(vrf_result.proof, proof.to_bytes());
+    }
+
+    #[test]
+    fn test_fulfill_time_locked_request() {
+        let mut fixture = Fixture::new();
+        let request = fixture.next_request();
+        let instruction = fixture.request_instruction(VrfCoordinatorInstruction::RequestRandomnessAt {
+            seed: [1; 32],
+            callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+            num_words: 1,
+            minimum_confirmations: 1,
+            callback_gas_limit: 100_000,
+            not_before_slot: 20,
+        }, vec![]);
+        fixture.harness.process(&instruction).unwrap();
+
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        fixture.harness.warp_to_slot(19);
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::RequestTimeLocked.into()));
+        fixture.harness.warp_to_slot(20);
+        fixture.harness.process(&fulfill).unwrap();
+    }
+
+    #[test]
+    fn test_append_words_and_finalize() {
+        let mut fixture = Fixture::new();
+        let request = fixture.next_request();
+        let open = fixture.request_instruction(request_randomness([1; 32], 3), vec![]);
+        fixture.harness.process(&open).unwrap();
+        fixture.harness.warp_to_slot(1);
+
+        let (vrf_result, proof) = (fixture.vrf_result(&request), fixture.prove(&request));
+        let append = |proof: &Vec<u8>, num_words| instruction(
+            &fixture.program_id,
+            VrfCoordinatorInstruction::AppendWords { proof: proof.clone(), num_words },
+            vec![
+                AccountMeta::new(fixture.oracle, true),
+                AccountMeta::new(request, false),
+                AccountMeta::new(vrf_result, false),
+                AccountMeta::new_readonly(fixture.subscription, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(fixture.oracle_config, false),
+            ],
+        );
+        let finalize = instruction(&fixture.program_id, VrfCoordinatorInstruction::FinalizeFulfillment, vec![
+            AccountMeta::new(fixture.oracle, true),
+            AccountMeta::new(request, false),
+            AccountMeta::new_readonly(fixture.vrf_result(&request), false),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new_readonly(fixture.game_program, false),
+            AccountMeta::new(fixture.game_state, false),
+            AccountMeta::new(fixture.oracle_config, false),
+            AccountMeta::new_readonly(fixture.config(), false),
+        ]);
+
+        let other_seed_proof = fixture.vrf_keypair.prove(&[2; 32]).to_bytes_legacy();
+        assert_eq!(fixture.harness.process(&finalize), Err(VrfCoordinatorError::InvalidRequestStatus.into()));
+        assert_eq!(fixture.harness.process(&append(&other_seed_proof, 2)), Err(VrfCoordinatorError::InvalidVrfProof.into()));
+        fixture.harness.process(&append(&proof, 2)).unwrap();
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::Fulfilling);
+        assert_eq!(fixture.harness.process(&finalize), Err(VrfCoordinatorError::InvalidNumberOfWords.into()));
+        assert_eq!(fixture.harness.process(&append(&proof, 2)), Err(VrfCoordinatorError::InvalidNumberOfWords.into()));
+        // A proof that does not verify cannot restart the delivery either
+        assert_eq!(fixture.harness.process(&append(&other_seed_proof, 1)), Err(VrfCoordinatorError::InvalidVrfProof.into()));
+        fixture.harness.process(&append(&proof, 1)).unwrap();
+        fixture.harness.process(&finalize).unwrap();
+
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::Fulfilled);
+        let vrf_result: VrfResult = fixture.harness.load(&fixture.vrf_result(&request));
+        let output = ECVRFProof::from_bytes_legacy(&proof).unwrap().to_hash();
+        assert_eq!(vrf_result.randomness, (0..3).map(|i| expand_word(&output, i)).collect::<Vec<_>>());
+        assert!(!fixture.harness.load::<GameState>(&fixture.game_state).is_pending);
+    }
+
+    #[test]
+    fn test_chunked_requests_of_a_requester_keep_their_results() {
+        let mut fixture = Fixture::new();
+        let mut open = |seed| {
+            let request = fixture.next_request();
+            let instruction = fixture.request_instruction(request_randomness(seed, 3), vec![]);
+            fixture.harness.process(&instruction).unwrap();
+            request
+        };
+        let (first, second) = (open([1; 32]), open([2; 32]));
+        fixture.harness.warp_to_slot(1);
+
+        // Both requests are the requester's, but each has its own result account
+        let (first_result, second_result) = (fixture.vrf_result(&first), fixture.vrf_result(&second));
+        assert_ne!(first_result, second_result);
+        let append = |request, vrf_result, proof: &Vec<u8>, num_words| instruction(
+            &fixture.program_id,
+            VrfCoordinatorInstruction::AppendWords { proof: proof.clone(), num_words },
+            vec![
+                AccountMeta::new(fixture.oracle, true),
+                AccountMeta::new(request, false),
+                AccountMeta::new(vrf_result, false),
+                AccountMeta::new_readonly(fixture.subscription, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(fixture.oracle_config, false),
+            ],
+        );
+        let config = fixture.config();
+        let finalize = |request, vrf_result| instruction(&fixture.program_id, VrfCoordinatorInstruction::FinalizeFulfillment, vec![
+            AccountMeta::new(fixture.oracle, true),
+            AccountMeta::new(request, false),
+            AccountMeta::new_readonly(vrf_result, false),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new_readonly(fixture.game_program, false),
+            AccountMeta::new(fixture.game_state, false),
+            AccountMeta::new(fixture.oracle_config, false),
+            AccountMeta::new_readonly(config, false),
+        ]);
+        let (first_proof, second_proof) = (fixture.prove(&first), fixture.prove(&second));
+
+        // Interleaved deliveries do not touch each other's words
+        fixture.harness.process(&append(second, second_result, &second_proof, 1)).unwrap();
+        fixture.harness.process(&append(first, first_result, &first_proof, 3)).unwrap();
+        fixture.harness.process(&append(second, second_result, &second_proof, 2)).unwrap();
+
+        // A request's words cannot be taken from another request's result
+        assert_eq!(fixture.harness.process(&append(second, first_result, &first_proof, 1)), Err(ProgramError::InvalidSeeds));
+        assert_eq!(fixture.harness.process(&finalize(second, first_result)), Err(ProgramError::InvalidSeeds));
+
+        for (request, vrf_result, proof) in [(first, first_result, &first_proof), (second, second_result, &second_proof)] {
+            fixture.harness.process(&finalize(request, vrf_result)).unwrap();
+            assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::Fulfilled);
+            let result: VrfResult = fixture.harness.load(&vrf_result);
+            let output = ECVRFProof::from_bytes_legacy(proof).unwrap().to_hash();
+            assert!(result.fulfills(&request));
+            assert_eq!(result.randomness, (0..3).map(|i| expand_word(&output, i)).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_expire_stalled_chunked_request() {
+        let mut fixture = Fixture::new();
+        let request = fixture.next_request();
+        let request_instruction = fixture.request_instruction(request_randomness([1; 32], 3), vec![]);
+        fixture.harness.process(&request_instruction).unwrap();
+        fixture.harness.warp_to_slot(1);
+
+        // The oracle delivers a word, then stops
+        let append = instruction(
+            &fixture.program_id,
+            VrfCoordinatorInstruction::AppendWords { proof: fixture.prove(&request), num_words: 1 },
+            vec![
+                AccountMeta::new(fixture.oracle, true),
+                AccountMeta::new(request, false),
+                AccountMeta::new(fixture.vrf_result(&request), false),
+                AccountMeta::new_readonly(fixture.subscription, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(fixture.oracle_config, false),
+            ],
+        );
+        fixture.harness.process(&append).unwrap();
+        let state: RandomnessRequest = fixture.harness.load(&request);
+        assert_eq!(state.status, RequestStatus::Fulfilling);
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).pending_requests, 1);
+
+        // Its owner has seen the first word, so may not cancel it for another
+        let cancel = instruction(&fixture.program_id, VrfCoordinatorInstruction::CancelRequest, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(request, false),
+            AccountMeta::new(fixture.subscription, false),
+        ]);
+        assert_eq!(fixture.harness.process(&cancel), Err(VrfCoordinatorError::InvalidRequestStatus.into()));
+
+        // But it expires like a pending request, refunding the subscription
+        let rent = fixture.harness.account(&request).lamports;
+        let owner_lamports = fixture.harness.account(&fixture.owner).lamports;
+        let expire = instruction(&fixture.program_id, VrfCoordinatorInstruction::ExpireRequest, vec![
+            AccountMeta::new(request, false),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new(fixture.owner, false),
+        ]);
+        fixture.harness.warp_to_slot(state.expiration_slot);
+        fixture.harness.process(&expire).unwrap();
+        assert_eq!(fixture.harness.account(&fixture.owner).lamports, owner_lamports + rent);
+        assert_eq!(fixture.harness.account(&request).lamports, 0);
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!(subscription.balance, BALANCE);
+        assert_eq!(subscription.pending_requests, 0);
+        assert!(matches!(fixture.harness.events()[..], [VrfEvent::RequestExpired { .. }]));
+    }
+
+    #[test]
+    fn test_cancel_request() {
+        let mut fixture = Fixture::new();
+        let request = fixture.request();
+        let rent = fixture.harness.account(&request).lamports;
+        let owner_lamports = fixture.harness.account(&fixture.owner).lamports;
+        let cancel = |owner| instruction(&fixture.program_id, VrfCoordinatorInstruction::CancelRequest, vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(request, false),
+            AccountMeta::new(fixture.subscription, false),
+        ]);
+
+        // Without a fee schedule the request reserves the subscription's minimum
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).reserved_fee, MIN_BALANCE);
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).balance, BALANCE - MIN_BALANCE);
+
+        let stranger = Pubkey::new_unique();
+        assert_eq!(fixture.harness.process(&cancel(stranger)), Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()));
+
+        fixture.harness.process(&cancel(fixture.owner)).unwrap();
+        assert_eq!(fixture.harness.account(&fixture.owner).lamports, owner_lamports + rent);
+        assert_eq!(fixture.harness.account(&request).lamports, 0);
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).balance, BALANCE);
+        assert!(matches!(fixture.harness.events()[..], [VrfEvent::RequestCancelled { .. }]));
+    }
+
+    #[test]
+    fn test_expire_request() {
+        let mut fixture = Fixture::new();
+        let request = fixture.request();
+        let state: RandomnessRequest = fixture.harness.load(&request);
+        assert_eq!(state.expiration_slot, state.request_block + REQUEST_EXPIRATION_SLOTS);
+        let rent = fixture.harness.account(&request).lamports;
+        let owner_lamports = fixture.harness.account(&fixture.owner).lamports;
+        // Nobody needs to sign
+        let expire = |requester| instruction(&fixture.program_id, VrfCoordinatorInstruction::ExpireRequest, vec![
+            AccountMeta::new(request, false),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new(requester, false),
+        ]);
+        let (expire_to_stranger, expire) = (expire(Pubkey::new_unique()), expire(fixture.owner));
+
+        fixture.harness.warp_to_slot(state.expiration_slot - 1);
+        assert_eq!(fixture.harness.process(&expire), Err(VrfCoordinatorError::RequestNotExpired.into()));
+
+        fixture.harness.warp_to_slot(state.expiration_slot);
+        assert_eq!(fixture.harness.process(&expire_to_stranger), Err(VrfCoordinatorError::InvalidRequestParameters.into()));
+        fixture.harness.process(&expire).unwrap();
+        assert_eq!(fixture.harness.account(&fixture.owner).lamports, owner_lamports + rent);
+        assert_eq!(fixture.harness.account(&request).lamports, 0);
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).balance, BALANCE);
+        assert!(matches!(fixture.harness.events()[..], [VrfEvent::RequestExpired { .. }]));
+    }
+
+    #[test]
+    fn test_direct_request() {
+        let mut fixture = Fixture::new();
+        let fee_schedule = FeeSchedule { base_fee: 5_000, fee_per_word: 1_000, ..FeeSchedule::default() };
+        fixture.update_config(VrfCoordinatorInstruction::SetFeeSchedule { fee_schedule });
+
+        let seed = [9; 32];
+        let request = derive_direct_request_pda(&fixture.program_id, &fixture.owner, &seed).0;
+        let instruction = instruction(
+            &fixture.program_id,
+            VrfCoordinatorInstruction::RequestRandomnessDirect {
+                seed,
+                callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+                num_words: 1,
+                callback_gas_limit: 100_000,
+            },
+            vec![
+                AccountMeta::new(fixture.owner, true),
+                AccountMeta::new(request, false),
+                AccountMeta::new_readonly(fixture.config(), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+        );
+        fixture.harness.process(&instruction).unwrap();
+
+        let rent = Rent::default().minimum_balance(fixture.harness.account(&request).data.len());
+        assert_eq!(fixture.harness.account(&request).lamports, rent + 6_000);
+        assert!(fixture.harness.load::<RandomnessRequest>(&request).is_direct());
+
+        // The system program stands in for the subscription, and the oracle collects the fee
+        let oracle_lamports = fixture.harness.account(&fixture.oracle).lamports;
+        let fulfill = fixture.fulfill_instruction(&request, &system_program::ID);
+        fixture.harness.process(&fulfill).unwrap();
+        let vrf_result_rent = fixture.harness.account(&fixture.vrf_result(&request)).lamports;
+        assert_eq!(fixture.harness.account(&fixture.oracle).lamports, oracle_lamports - vrf_result_rent + 6_000);
+        assert_eq!(fixture.harness.account(&request).lamports, rent);
+    }
+
+    #[test]
+    fn test_register_and_deactivate_oracle() {
+        let mut fixture = Fixture::new();
+        let (admin, stranger) = (fixture.admin, Pubkey::new_unique());
+        fixture.harness.fund(stranger, LAMPORTS);
+        let oracle_key = Pubkey::new_unique();
+        let oracle_config = derive_oracle_config_pda(&fixture.program_id, &oracle_key).0;
+
+        let register = |admin| fixture.registry_instruction(&admin, &oracle_config, VrfCoordinatorInstruction::RegisterOracle {
+            oracle_key,
+            vrf_key: [5; 32],
+        });
+        let (register_by_stranger, register) = (register(stranger), register(admin));
+        assert_eq!(fixture.harness.process(&register_by_stranger), Err(VrfCoordinatorError::InvalidAdmin.into()));
+        let mut keypair_config = register.clone();
+        keypair_config.accounts[2] = AccountMeta::new(Pubkey::new_unique(), true);
+        assert_eq!(fixture.harness.process(&keypair_config), Err(ProgramError::InvalidSeeds));
+        fixture.harness.process(&register).unwrap();
+        let state: OracleConfig = fixture.harness.load(&oracle_config);
+        assert_eq!((state.oracle_key, state.vrf_key, state.is_active), (oracle_key, [5; 32], true));
+
+        let deactivate = |admin, oracle_key| fixture.registry_instruction(&admin, &oracle_config, VrfCoordinatorInstruction::DeactivateOracle {
+            oracle_key,
+        });
+        let (deactivate_by_stranger, deactivate_other, deactivate) =
+            (deactivate(stranger, oracle_key), deactivate(admin, Pubkey::new_unique()), deactivate(admin, oracle_key));
+        assert_eq!(fixture.harness.process(&deactivate_by_stranger), Err(VrfCoordinatorError::InvalidAdmin.into()));
+        assert_eq!(fixture.harness.process(&deactivate_other), Err(VrfCoordinatorError::InvalidOracle.into()));
+        fixture.harness.process(&deactivate).unwrap();
+        assert!(!fixture.harness.load::<OracleConfig>(&oracle_config).is_active);
+
+        let log = AdminAuditLog::parse(&fixture.harness.account(&fixture.audit_log()).data).unwrap();
+        let actions: Vec<_> = log.entries.into_iter().skip(1).map(|entry| (entry.actor, entry.action)).collect();
+        assert_eq!(actions, vec![
+            (admin, AdminAction::RegisterOracle { oracle_key, vrf_key: [5; 32] }),
+            (admin, AdminAction::DeactivateOracle { oracle_key }),
+        ]);
+    }
+
+    #[test]
+    fn test_initialize_coordinator_and_quote_fee() {
+        let mut fixture = Fixture::new();
+        let config: CoordinatorConfig = fixture.harness.load(&fixture.config());
+        assert_eq!((config.admin, config.fee_schedule), (fixture.admin, FeeSchedule::default()));
+        assert_eq!((config.min_confirmations, config.max_confirmations, config.paused), (1, 255, false));
+
+        let fee_schedule = FeeSchedule {
+            base_fee: 100,
+            fee_per_word: 10,
+            fee_per_million_callback_units: 1_000_000,
+            priority_fee: 7,
+        };
+        let again = fixture.admin_instruction(&fixture.admin, &fixture.config(), VrfCoordinatorInstruction::InitializeCoordinator {
+            fee_schedule,
+            bridge_program: Pubkey::default(),
+        });
+        assert_eq!(fixture.harness.process(&again), Err(ProgramError::AccountAlreadyInitialized));
+        fixture.update_config(VrfCoordinatorInstruction::SetFeeSchedule { fee_schedule });
+
+        let quote = instruction(
+            &fixture.program_id,
+            VrfCoordinatorInstruction::GetRequestFee { num_words: 2, callback_gas_limit: 50_000, priority: 3 },
+            vec![AccountMeta::new_readonly(fixture.config(), false)],
+        );
+        fixture.harness.process(&quote).unwrap();
+        let expected_fee: u64 = 100 + 2 * 10 + 50_000 + 3 * 7;
+        assert_eq!(fixture.harness.return_data(), Some((fixture.program_id, expected_fee.to_le_bytes().to_vec())));
+    }
+
+    #[test]
+    fn test_set_admin_and_fee_schedule() {
+        let mut fixture = Fixture::new();
+        let (admin, new_admin) = (fixture.admin, Pubkey::new_unique());
+        fixture.harness.fund(new_admin, LAMPORTS);
+
+        let set_fees = |admin| fixture.admin_instruction(&admin, &fixture.config(), VrfCoordinatorInstruction::SetFeeSchedule {
+            fee_schedule: FeeSchedule { base_fee: 1, ..FeeSchedule::default() },
+        });
+        let (set_fees_by_old_admin, set_fees_by_new_admin) = (set_fees(admin), set_fees(new_admin));
+        let set_admin = fixture.admin_instruction(&admin, &fixture.config(), VrfCoordinatorInstruction::SetAdmin { new_admin });
+        fixture.harness.process(&set_admin).unwrap();
+        assert_eq!(fixture.harness.load::<CoordinatorConfig>(&fixture.config()).admin, new_admin);
+
+        assert_eq!(fixture.harness.process(&set_fees_by_old_admin), Err(VrfCoordinatorError::InvalidAdmin.into()));
+        fixture.harness.process(&set_fees_by_new_admin).unwrap();
+        assert_eq!(fixture.harness.load::<CoordinatorConfig>(&fixture.config()).fee_schedule.base_fee, 1);
+    }
+
+    #[test]
+    fn test_pause_and_confirmations() {
+        let mut fixture = Fixture::new();
+        let confirmations = |minimum_confirmations| VrfCoordinatorInstruction::RequestRandomness {
+            seed: [1; 32],
+            callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+            num_words: 1,
+            minimum_confirmations,
+            callback_gas_limit: 100_000,
+            priority_fee: 0,
+        };
+
+        let mut without_config = fixture.request_instruction(confirmations(1), vec![]);
+        without_config.accounts.pop();
+        assert_eq!(fixture.harness.process(&without_config), Err(ProgramError::NotEnoughAccountKeys));
+
+        for (min_confirmations, max_confirmations) in [(0, 3), (4, 3)] {
+            let invalid = fixture.admin_instruction(&fixture.admin, &fixture.config(), VrfCoordinatorInstruction::SetConfirmations {
+                min_confirmations,
+                max_confirmations,
+            });
+            assert_eq!(fixture.harness.process(&invalid), Err(VrfCoordinatorError::InvalidRequestConfirmations.into()));
+        }
+        fixture.update_config(VrfCoordinatorInstruction::SetConfirmations { min_confirmations: 2, max_confirmations: 3 });
+        for minimum_confirmations in [1, 4] {
+            let instruction = fixture.request_instruction(confirmations(minimum_confirmations), vec![]);
+            assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::InvalidRequestConfirmations.into()));
+        }
+        let request = fixture.next_request();
+        let instruction = fixture.request_instruction(confirmations(2), vec![]);
+        fixture.harness.process(&instruction).unwrap();
+
+        // The request waits for the confirmations it asked for
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        fixture.harness.warp_to_slot(1);
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::InsufficientConfirmations.into()));
+        fixture.harness.warp_to_slot(2);
+
+        // A paused coordinator refuses new requests but still fulfills pending ones
+        fixture.update_config(VrfCoordinatorInstruction::SetPaused { paused: true });
+        let instruction = fixture.request_instruction(confirmations(2), vec![]);
+        assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::ProgramPaused.into()));
+        fixture.harness.process(&fulfill).unwrap();
+
+        fixture.update_config(VrfCoordinatorInstruction::SetPaused { paused: false });
+        fixture.harness.process(&instruction).unwrap();
+
+        let log = AdminAuditLog::parse(&fixture.harness.account(&fixture.audit_log()).data).unwrap();
+        let actions: Vec<_> = log.entries.into_iter().skip(1).map(|entry| entry.action).collect();
+        assert_eq!(actions, vec![
+            AdminAction::SetConfirmations { min_confirmations: 2, max_confirmations: 3 },
+            AdminAction::SetPaused { paused: true },
+            AdminAction::SetPaused { paused: false },
+        ]);
+    }
+
+    #[test]
+    fn test_set_backstop_policy() {
+        let mut fixture = Fixture::new();
+        let set_policy = |owner| instruction(
+            &fixture.program_id,
+            VrfCoordinatorInstruction::SetBackstopPolicy { timeout_slots: 30, bonus: 5 },
+            vec![AccountMeta::new_readonly(owner, true), AccountMeta::new(fixture.subscription, false)],
+        );
+
+        assert_eq!(
+            fixture.harness.process(&set_policy(Pubkey::new_unique())),
+            Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()),
+        );
+        fixture.harness.process(&set_policy(fixture.owner)).unwrap();
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!((subscription.backstop_timeout_slots, subscription.backstop_bonus), (30, 5));
+    }
+
+    #[test]
+    fn test_rate_limits() {
+        let mut fixture = Fixture::new();
+        let set_limits = |owner| instruction(
+            &fixture.program_id,
+            VrfCoordinatorInstruction::SetRateLimits { max_requests_per_slot: 2, max_pending_requests: 3 },
+            vec![AccountMeta::new_readonly(owner, true), AccountMeta::new(fixture.subscription, false)],
+        );
+
+        assert_eq!(
+            fixture.harness.process(&set_limits(Pubkey::new_unique())),
+            Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()),
+        );
+        fixture.harness.process(&set_limits(fixture.owner)).unwrap();
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::RateLimitsUpdated { max_requests_per_slot: 2, max_pending_requests: 3, .. }]
+        ));
+
+        let requests = [fixture.request(), fixture.request()];
+        let request = fixture.request_instruction(request_randomness([1; 32], 1), vec![]);
+        assert_eq!(fixture.harness.process(&request), Err(VrfCoordinatorError::RateLimited.into()));
+
+        // A new slot resets the count, but not the pending requests
+        fixture.harness.warp_to_slot(1);
+        fixture.request();
+        let request = fixture.request_instruction(request_randomness([1; 32], 1), vec![]);
+        assert_eq!(fixture.harness.process(&request), Err(VrfCoordinatorError::RateLimited.into()));
+
+        let fulfill = fixture.fulfill_instruction(&requests[0], &fixture.subscription);
+        fixture.harness.process(&fulfill).unwrap();
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).pending_requests, 2);
+        fixture.request();
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).pending_requests, 3);
+    }
+
+    #[test]
+    fn test_add_and_remove_consumer() {
+        let mut fixture = Fixture::new();
+        let consumer = Pubkey::new_unique();
+        fixture.harness.fund(consumer, LAMPORTS);
+        let consumer_instruction = |owner, variant| instruction(&fixture.program_id, variant, vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        let add = consumer_instruction(fixture.owner, VrfCoordinatorInstruction::AddConsumer { consumer });
+        let remove = consumer_instruction(fixture.owner, VrfCoordinatorInstruction::RemoveConsumer { consumer });
+        let mut request = fixture.request_instruction(request_randomness([1; 32], 1), vec![]);
+        request.accounts[0].pubkey = consumer;
+
+        // Only the owner may request until the consumer is approved
+        assert_eq!(fixture.harness.process(&request), Err(VrfCoordinatorError::InvalidConsumer.into()));
+        let stranger = consumer_instruction(Pubkey::new_unique(), VrfCoordinatorInstruction::AddConsumer { consumer });
+        assert_eq!(fixture.harness.process(&stranger), Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()));
+
+        fixture.harness.process(&add).unwrap();
+        assert_eq!(fixture.harness.process(&add), Err(VrfCoordinatorError::InvalidConsumer.into()));
+        let account = fixture.harness.account(&fixture.subscription);
+        assert_eq!(account.data.len(), Subscription::space(1));
+        assert_eq!(account.lamports, Rent::default().minimum_balance(Subscription::space(1)));
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).consumers, vec![consumer]);
+
+        let request_account = fixture.next_request();
+        fixture.harness.process(&request).unwrap();
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request_account).requester, consumer);
+
+        // Removing the consumer shrinks the subscription back and refunds the rent
+        let owner_lamports = fixture.harness.account(&fixture.owner).lamports;
+        fixture.harness.process(&remove).unwrap();
+        let account = fixture.harness.account(&fixture.subscription);
+        assert_eq!(account.data.len(), Subscription::LEN);
+        assert_eq!(account.lamports, Rent::default().minimum_balance(Subscription::LEN));
+        assert_eq!(
+            fixture.harness.account(&fixture.owner).lamports - owner_lamports,
+            Rent::default().minimum_balance(Subscription::space(1)) - Rent::default().minimum_balance(Subscription::LEN),
+        );
+        assert!(matches!(
+            fixture.harness.events().last(),
+            Some(VrfEvent::ConsumerRemoved { consumer: removed, .. }) if *removed == consumer
+        ));
+
+        let mut request = fixture.request_instruction(request_randomness([1; 32], 1), vec![]);
+        request.accounts[0].pubkey = consumer;
+        assert_eq!(fixture.harness.process(&request), Err(VrfCoordinatorError::InvalidConsumer.into()));
+        assert_eq!(fixture.harness.process(&remove), Err(VrfCoordinatorError::InvalidConsumer.into()));
+    }
+
+    #[test]
+    fn test_transfer_subscription_ownership() {
+        let mut fixture = Fixture::new();
+        let new_owner = Pubkey::new_unique();
+        let transfer_instruction = |signer, variant| instruction(&fixture.program_id, variant, vec![
+            AccountMeta::new_readonly(signer, true),
+            AccountMeta::new(fixture.subscription, false),
+        ]);
+        let offer = |signer, new_owner| transfer_instruction(
+            signer,
+            VrfCoordinatorInstruction::RequestSubscriptionOwnershipTransfer { new_owner },
+        );
+        let accept = |signer| transfer_instruction(signer, VrfCoordinatorInstruction::AcceptSubscriptionOwnership);
+
+        assert_eq!(
+            fixture.harness.process(&offer(new_owner, new_owner)),
+            Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()),
+        );
+        assert_eq!(
+            fixture.harness.process(&accept(new_owner)),
+            Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()),
+        );
+
+        // The offer alone changes nothing, and only its recipient may accept it
+        fixture.harness.process(&offer(fixture.owner, new_owner)).unwrap();
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!((subscription.owner, subscription.pending_owner), (fixture.owner, new_owner));
+        assert_eq!(
+            fixture.harness.process(&accept(Pubkey::new_unique())),
+            Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()),
+        );
+
+        fixture.harness.process(&accept(new_owner)).unwrap();
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!((subscription.owner, subscription.pending_owner), (new_owner, Pubkey::default()));
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::SubscriptionOwnershipTransferred { previous_owner, new_owner: owner, .. }]
+                if previous_owner == fixture.owner && owner == new_owner
+        ));
+
+        // The previous owner has no say any more, and the offer cannot be replayed
+        assert_eq!(
+            fixture.harness.process(&offer(fixture.owner, fixture.owner)),
+            Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()),
+        );
+        assert_eq!(
+            fixture.harness.process(&accept(new_owner)),
+            Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()),
+        );
+    }
+
+    #[test]
+    fn test_audit_subscription() {
+        let mut fixture = Fixture::new();
+        let vault = subscription_vault_address(&fixture.program_id, &fixture.subscription);
+        let subscription_token = get_associated_token_address(&vault, &FUNDING_MINT);
+        let audit = |vault| instruction(
+            &fixture.program_id,
+            VrfCoordinatorInstruction::AuditSubscription,
+            vec![
+                AccountMeta::new(fixture.subscription, false),
+                AccountMeta::new_readonly(vault, false),
+                AccountMeta::new_readonly(subscription_token, false),
+            ],
+        );
+        let (audit_other_vault, audit) = (audit(Pubkey::new_unique()), audit(vault));
+        assert_eq!(fixture.harness.process(&audit_other_vault), Err(ProgramError::InvalidSeeds));
+
+        // The fixture's balance is not held by any token account
+        fixture.harness.process(&audit).unwrap();
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::AuditDiscrepancy { balance: BALANCE, expected_balance: Some(BALANCE), held: 0, .. }]
+        ));
+
+        fixture.token_account(subscription_token, vault, BALANCE);
+        fixture.request();
+        fixture.harness.process(&audit).unwrap();
+        assert!(fixture.harness.events().is_empty());
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).audited_nonce, 1);
+
+        let mut subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        subscription.balance += 1;
+        fixture.harness.set_state(fixture.subscription, fixture.program_id, &subscription);
+        fixture.harness.process(&audit).unwrap();
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::AuditDiscrepancy { balance, expected_balance: Some(expected), .. }]
+                if expected == BALANCE - MIN_BALANCE && balance == expected + 1
+        ));
+
+        // Credits that match the history but not the vault are flagged too
+        subscription.balance += 2 * MIN_BALANCE - 1;
+        subscription.total_credited += 2 * MIN_BALANCE;
+        fixture.harness.set_state(fixture.subscription, fixture.program_id, &subscription);
+        fixture.harness.process(&audit).unwrap();
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::AuditDiscrepancy { balance, expected_balance: Some(expected), held: BALANCE, .. }]
+                if balance == expected && balance == BALANCE + MIN_BALANCE
+        ));
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut fixture = Fixture::new();
+        let stats = fixture.pda(&[CoordinatorStats::SEED]);
+        let initialize = instruction(&fixture.program_id, VrfCoordinatorInstruction::InitializeStats, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(stats, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        fixture.harness.process(&initialize).unwrap();
+        assert_eq!(fixture.harness.process(&initialize), Err(ProgramError::AccountAlreadyInitialized));
+
+        let request = fixture.next_request();
+        let instruction = fixture.request_instruction(request_randomness([1; 32], 1), vec![AccountMeta::new(stats, false)]);
+        fixture.harness.process(&instruction).unwrap();
+        fixture.harness.warp_to_slot(1);
+        let mut fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        fulfill.accounts.push(AccountMeta::new(stats, false));
+        fixture.harness.process(&fulfill).unwrap();
+
+        let stats: CoordinatorStats = fixture.harness.load(&stats);
+        assert_eq!((stats.total_requests, stats.total_fulfillments), (1, 1));
+        assert_eq!(stats.oracle_fulfillments[0].oracle, fixture.oracle);
+    }
+
+    #[test]
+    fn test_request_pool() {
+        let mut fixture = Fixture::new();
+        let pool = fixture.pda(&[RequestPool::SEED, fixture.subscription.as_ref()]);
+        let initialize = instruction(&fixture.program_id, VrfCoordinatorInstruction::InitializeRequestPool, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(fixture.subscription, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        fixture.harness.process(&initialize).unwrap();
+        assert_eq!(fixture.harness.process(&initialize), Err(ProgramError::AccountAlreadyInitialized));
+
+        let request = fixture.next_request();
+        let instruction = fixture.request_instruction(request_randomness([1; 32], 1), vec![AccountMeta::new(pool, false)]);
+        fixture.harness.process(&instruction).unwrap();
+        let listed: RequestPool = fixture.harness.load(&pool);
+        assert_eq!(listed.subscription, fixture.subscription);
+        assert_eq!(listed.requests, vec![PooledRequest { request, seed: [1; 32] }]);
+        fixture.harness.warp_to_slot(1);
+
+        let mut fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        fulfill.accounts.push(AccountMeta::new(pool, false));
+        fixture.harness.process(&fulfill).unwrap();
+        let listed: RequestPool = fixture.harness.load(&pool);
+        assert!(listed.requests.is_empty());
+    }
+
+    #[test]
+    fn test_oracle_rotation() {
+        let mut fixture = Fixture::new();
+        let other_oracle = Pubkey::new_unique();
+        let rotation = |admin, oracles| fixture.admin_instruction(&admin, &fixture.config(), VrfCoordinatorInstruction::SetOracleRotation {
+            oracles,
+        });
+        let (by_stranger, duplicated) =
+            (rotation(Pubkey::new_unique(), vec![other_oracle]), rotation(fixture.admin, vec![other_oracle, other_oracle]));
+        assert_eq!(fixture.harness.process(&by_stranger), Err(VrfCoordinatorError::InvalidAdmin.into()));
+        assert_eq!(fixture.harness.process(&duplicated), Err(VrfCoordinatorError::InvalidOracle.into()));
+        fixture.update_config(VrfCoordinatorInstruction::SetOracleRotation { oracles: vec![other_oracle] });
+        assert_eq!(fixture.harness.account(&fixture.config()).data.len(), CoordinatorConfig::space(1));
+
+        // Nor may a requester pick another oracle of the registry
+        let pick_oracle = fixture.request_instruction(request_randomness([1; 32], 1), vec![
+            AccountMeta::new_readonly(fixture.oracle_config, false),
+        ]);
+        assert_eq!(fixture.harness.process(&pick_oracle), Err(VrfCoordinatorError::InvalidOracle.into()));
+
+        // A request is assigned to the rotation, so other oracles wait for the backstop timeout
+        let request = fixture.request();
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).assigned_oracle, other_oracle);
+        fixture.harness.warp_to_slot(1);
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::UnauthorizedOracle.into()));
+
+        fixture.update_config(VrfCoordinatorInstruction::SetOracleRotation { oracles: vec![other_oracle, fixture.oracle] });
+        let requests: Vec<_> = (0..8).map(|_| fixture.request()).collect();
+        let config: CoordinatorConfig = fixture.harness.load(&fixture.config());
+        for request in &requests {
+            let assigned_oracle = fixture.harness.load::<RandomnessRequest>(request).assigned_oracle;
+            assert_eq!(assigned_oracle, config.rotation_oracle(request));
+        }
+
+        // Deactivating an oracle drops it from the rotation, refunding the rent it freed
+        let deactivate = fixture.registry_instruction(&fixture.admin, &fixture.oracle_config, VrfCoordinatorInstruction::DeactivateOracle {
+            oracle_key: fixture.oracle,
+        });
+        fixture.harness.process(&deactivate).unwrap();
+        let config: CoordinatorConfig = fixture.harness.load(&fixture.config());
+        assert_eq!(config.oracle_rotation, vec![other_oracle]);
+        let account = fixture.harness.account(&fixture.config());
+        assert_eq!(account.data.len(), CoordinatorConfig::space(1));
+        assert_eq!(account.lamports, Rent::default().minimum_balance(CoordinatorConfig::space(1)));
+    }
+
+    #[test]
+    fn test_threshold_request() {
+        let mut fixture = Fixture::new();
+        let threshold = |required_oracle_responses| VrfCoordinatorInstruction::RequestRandomnessThreshold {
+            seed: [1; 32],
+            callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+            num_words: 1,
+            minimum_confirmations: 1,
+            callback_gas_limit: 100_000,
+            required_oracle_responses,
+        };
+        for required in [0, MAXIMUM_ORACLE_RESPONSES + 1] {
+            let instruction = fixture.request_instruction(threshold(required), vec![]);
+            assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::InvalidRequestParameters.into()));
+        }
+        let request = fixture.next_request();
+        let instruction = fixture.request_instruction(threshold(2), vec![]);
+        fixture.harness.process(&instruction).unwrap();
+        fixture.harness.warp_to_slot(1);
+
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::InvalidRequestParameters.into()));
+
+        // The quorum is part of the commitment, so it cannot be lowered to a single oracle
+        let state: RandomnessRequest = fixture.harness.load(&request);
+        assert_eq!(state.assigned_oracle, Pubkey::default());
+        assert_eq!(state.commitment, state.compute_commitment());
+        let mut tampered: RandomnessRequest = fixture.harness.load(&request);
+        tampered.required_oracle_responses = 1;
+        tampered.assigned_oracle = fixture.oracle;
+        fixture.harness.set_state(request, fixture.program_id, &tampered);
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::InvalidCommitment.into()));
+        fixture.harness.set_state(request, fixture.program_id, &state);
+
+        let other_oracle = Pubkey::new_unique();
+        let other_keypair = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[2; 32]).unwrap());
+        let other_config = derive_oracle_config_pda(&fixture.program_id, &other_oracle).0;
+        fixture.harness.fund(other_oracle, LAMPORTS);
+        fixture.harness.set_state(other_config, fixture.program_id, &OracleConfig {
+            oracle_key: other_oracle,
+            vrf_key: other_keypair.pk.as_ref().try_into().unwrap(),
+            is_active: true,
+            accrued_fees: 0,
+            fulfillments: 0,
+            total_latency_slots: 0,
+            failures: 0,
+        });
+
+        let responses = fixture.pda(&[OracleResponses::SEED, request.as_ref()]);
+        let submit = |oracle: Pubkey, oracle_config: Pubkey, proof: Vec<u8>| {
+            let mut submit = fixture.fulfill_instruction(&request, &fixture.subscription);
+            submit.data = borsh::to_vec(&VrfCoordinatorInstruction::SubmitOracleResponse { proof }).unwrap();
+            submit.accounts[0] = AccountMeta::new(oracle, true);
+            submit.accounts[8] = AccountMeta::new(oracle_config, false);
+            submit.accounts.insert(9, AccountMeta::new(responses, false));
+            submit
+        };
+        let first = submit(fixture.oracle, fixture.oracle_config, fixture.prove(&request));
+        let second = submit(other_oracle, other_config, other_keypair.prove(&[1; 32]).to_bytes_legacy());
+
+        // A response short of the quorum is only recorded
+        fixture.harness.process(&first).unwrap();
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::Pending);
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::OracleResponseSubmitted { responses: 1, required: 2, .. }]
+        ));
+        assert_eq!(fixture.harness.process(&first), Err(VrfCoordinatorError::DuplicateOracleResponse.into()));
+
+        fixture.harness.process(&second).unwrap();
+        assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).status, RequestStatus::Fulfilled);
+        let recorded: OracleResponses = fixture.harness.load(&responses);
+        assert_eq!(recorded.responses.len(), 2);
+        let vrf_result: VrfResult = fixture.harness.load(&fixture.vrf_result(&request));
+        assert_eq!(vrf_result.randomness, vec![expand_word(&recorded.aggregate_output(), 0)]);
+        assert_ne!(vrf_result.randomness[0], expand_word(&recorded.responses[0].output, 0));
+        assert!(!fixture.harness.load::<GameState>(&fixture.game_state).is_pending);
+    }
+
+    #[test]
+    fn test_open_market_request_commits_to_fee_auction() {
+        let mut fixture = Fixture::new();
+        let fee_auction = FeeAuction { start_price: 1_000, end_price: 100, duration_slots: 10 };
+        let request = fixture.next_request();
+        let instruction = fixture.request_instruction(VrfCoordinatorInstruction::RequestRandomnessOpenMarket {
+            seed: [1; 32],
+            callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+            num_words: 1,
+            minimum_confirmations: 1,
+            callback_gas_limit: 100_000,
+            fee_auction,
+        }, vec![]);
+        fixture.harness.process(&instruction).unwrap();
+        fixture.harness.warp_to_slot(1);
+
+        let mut state: RandomnessRequest = fixture.harness.load(&request);
+        assert_eq!(state.fee_auction, fee_auction);
+        assert_eq!(state.commitment, state.compute_commitment());
+
+        // An auction other than the one the request was opened with is not honored
+        state.fee_auction.end_price = 0;
+        fixture.harness.set_state(request, fixture.program_id, &state);
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::InvalidCommitment.into()));
+    }
+
+    #[test]
+    fn test_migrate_account() {
+        let mut fixture = Fixture::new();
+        let request = fixture.request_instruction(request_randomness([1; 32], 1), vec![]);
+        // A subscription as the first release of the coordinator wrote it,
+        // without a layout version
+        let mut legacy = fixture.harness.account(&fixture.subscription);
+        legacy.data = b"SUBSCRIP".to_vec();
+        borsh::to_writer(&mut legacy.data, &migrations::SubscriptionV0 {
+            owner: fixture.owner,
+            balance: BALANCE,
+            min_balance: MIN_BALANCE,
+            confirmations: 1,
+            nonce: 0,
+        }).unwrap();
+        legacy.lamports = Rent::default().minimum_balance(legacy.data.len());
+        fixture.harness.set_account(fixture.subscription, legacy);
+        assert_eq!(fixture.harness.process(&request), Err(VrfCoordinatorError::AccountNotMigrated.into()));
+
+        let migrate = instruction(&fixture.program_id, VrfCoordinatorInstruction::MigrateAccount, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        fixture.harness.process(&migrate).unwrap();
+        let migrated = fixture.harness.account(&fixture.subscription);
+        assert_eq!(migrated.data.len(), Subscription::LEN);
+        assert_eq!(migrated.lamports, Rent::default().minimum_balance(migrated.data.len()));
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).balance, BALANCE);
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::AccountMigrated { from_version: 0, to_version: 3, .. }]
+        ));
+        fixture.harness.process(&request).unwrap();
+
+        // Migrating a current account leaves it as it is
+        fixture.harness.process(&migrate).unwrap();
+        assert!(fixture.harness.events().is_empty());
+        assert_eq!(fixture.harness.account(&fixture.subscription).data.len(), migrated.data.len());
+    }
+
+    #[test]
+    fn test_fund_subscription() {
+        let mut fixture = Fixture::new();
+        let (funder_token, attacker_token) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let vault = subscription_vault_address(&fixture.program_id, &fixture.subscription);
+        let subscription_token = get_associated_token_address(&vault, &FUNDING_MINT);
+        fixture.token_account(funder_token, fixture.owner, BALANCE);
+        fixture.token_account(subscription_token, vault, 0);
+        fixture.token_account(attacker_token, Pubkey::new_unique(), 0);
+
+        let fund = |destination, token_program| instruction(&fixture.program_id, VrfCoordinatorInstruction::FundSubscription { amount: BALANCE }, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new(funder_token, false),
+            AccountMeta::new(destination, false),
+            AccountMeta::new_readonly(token_program, false),
+        ]);
+        assert_eq!(fixture.harness.process(&fund(attacker_token, spl_token::id())), Err(VrfCoordinatorError::InvalidTokenAccount.into()));
+        assert_eq!(fixture.harness.process(&fund(subscription_token, system_program::ID)), Err(ProgramError::IncorrectProgramId));
+
+        fixture.harness.process(&fund(subscription_token, spl_token::id())).unwrap();
+        let token = spl_token::state::Account::unpack(&fixture.harness.account(&subscription_token).data).unwrap();
+        assert_eq!(token.amount, BALANCE);
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!((subscription.balance, subscription.total_funded), (2 * BALANCE, 2 * BALANCE));
+    }
+
+    #[test]
+    fn test_fund_subscription_native() {
+        let mut fixture = Fixture::new();
+        let vault = subscription_vault_address(&fixture.program_id, &fixture.subscription);
+        let fund = |vault| instruction(&fixture.program_id, VrfCoordinatorInstruction::FundSubscriptionNative { amount: BALANCE }, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        assert_eq!(fixture.harness.process(&fund(Pubkey::new_unique())), Err(ProgramError::InvalidSeeds));
+
+        // The first funding also pays the vault's reserve
+        for _ in 0..2 {
+            fixture.harness.process(&fund(vault)).unwrap();
+        }
+        assert_eq!(fixture.harness.account(&vault).lamports, Rent::default().minimum_balance(0) + 2 * BALANCE);
+        let mut subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!(subscription.native_balance, 2 * BALANCE);
+        assert_eq!(subscription.expected_balance(), Some(subscription.balance + subscription.native_balance));
+
+        // The native balance alone pays for requests
+        subscription.balance = 0;
+        fixture.harness.set_state(fixture.subscription, fixture.program_id, &subscription);
+        fixture.request();
+    }
+
+    #[test]
+    fn test_claim_oracle_fees_native() {
+        let mut fixture = Fixture::new();
+        let fee_schedule = FeeSchedule { base_fee: 1_000, ..FeeSchedule::default() };
+        fixture.update_config(VrfCoordinatorInstruction::SetFeeSchedule { fee_schedule });
+        let vault = subscription_vault_address(&fixture.program_id, &fixture.subscription);
+        let fund = instruction(&fixture.program_id, VrfCoordinatorInstruction::FundSubscriptionNative { amount: BALANCE }, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        fixture.harness.process(&fund).unwrap();
+
+        // The fee is charged to the native balance, and stays in the vault until claimed
+        let mut subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        subscription.balance = 0;
+        fixture.harness.set_state(fixture.subscription, fixture.program_id, &subscription);
+        let request = fixture.request();
+        fixture.harness.warp_to_slot(1);
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        fixture.harness.process(&fulfill).unwrap();
+        let native_balance = fixture.harness.load::<Subscription>(&fixture.subscription).native_balance;
+        let reserve = Rent::default().minimum_balance(0);
+        assert_eq!(fixture.harness.account(&vault).lamports, reserve + native_balance + 1_000);
+        assert_eq!(fixture.harness.load::<OracleConfig>(&fixture.oracle_config).accrued_fees, 1_000);
+
+        let claim = |oracle, amount| instruction(&fixture.program_id, VrfCoordinatorInstruction::ClaimOracleFeesNative { amount }, vec![
+            AccountMeta::new(oracle, true),
+            AccountMeta::new(fixture.oracle_config, false),
+            AccountMeta::new_readonly(fixture.subscription, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        assert_eq!(fixture.harness.process(&claim(fixture.owner, 1_000)), Err(VrfCoordinatorError::UnauthorizedOracle.into()));
+        assert_eq!(fixture.harness.process(&claim(fixture.oracle, 1_001)), Err(VrfCoordinatorError::InsufficientBalance.into()));
+
+        // Nor may an oracle claim more than the subscription was charged
+        let mut oracle_config: OracleConfig = fixture.harness.load(&fixture.oracle_config);
+        oracle_config.accrued_fees = 2_000;
+        fixture.harness.set_state(fixture.oracle_config, fixture.program_id, &oracle_config);
+        assert_eq!(fixture.harness.process(&claim(fixture.oracle, 1_001)), Err(VrfCoordinatorError::InsufficientBalance.into()));
+
+        let oracle_lamports = fixture.harness.account(&fixture.oracle).lamports;
+        fixture.harness.process(&claim(fixture.oracle, 1_000)).unwrap();
+        assert_eq!(fixture.harness.account(&fixture.oracle).lamports, oracle_lamports + 1_000);
+        assert_eq!(fixture.harness.account(&vault).lamports, reserve + native_balance);
+        assert_eq!(fixture.harness.load::<OracleConfig>(&fixture.oracle_config).accrued_fees, 1_000);
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::OracleFeesClaimed { oracle, amount: 1_000, .. }] if oracle == fixture.oracle
+        ));
+    }
+
+    #[test]
+    fn test_withdraw_subscription_native() {
+        let mut fixture = Fixture::new();
+        let vault = subscription_vault_address(&fixture.program_id, &fixture.subscription);
+        let fund = instruction(&fixture.program_id, VrfCoordinatorInstruction::FundSubscriptionNative { amount: BALANCE }, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        fixture.harness.process(&fund).unwrap();
+
+        let withdraw = |owner, vault, amount| instruction(&fixture.program_id, VrfCoordinatorInstruction::WithdrawSubscriptionNative { amount }, vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        assert_eq!(fixture.harness.process(&withdraw(fixture.oracle, vault, 1)), Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()));
+        assert_eq!(fixture.harness.process(&withdraw(fixture.owner, Pubkey::new_unique(), 1)), Err(ProgramError::InvalidSeeds));
+        assert_eq!(fixture.harness.process(&withdraw(fixture.owner, vault, BALANCE + 1)), Err(VrfCoordinatorError::InsufficientBalance.into()));
+
+        let owner_lamports = fixture.harness.account(&fixture.owner).lamports;
+        fixture.harness.process(&withdraw(fixture.owner, vault, BALANCE)).unwrap();
+        assert_eq!(fixture.harness.account(&fixture.owner).lamports, owner_lamports + BALANCE);
+        assert_eq!(fixture.harness.account(&vault).lamports, Rent::default().minimum_balance(0));
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!(subscription.native_balance, 0);
+        assert_eq!(subscription.expected_balance(), Some(subscription.balance));
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::SubscriptionWithdrawn { owner, amount: BALANCE, .. }] if owner == fixture.owner
+        ));
+    }
+
+    #[test]
+    fn test_claim_oracle_fees_and_withdraw_subscription() {
+        let mut fixture = Fixture::new();
+        let fee_schedule = FeeSchedule { base_fee: 1_000, ..FeeSchedule::default() };
+        fixture.update_config(VrfCoordinatorInstruction::SetFeeSchedule { fee_schedule });
+        let (oracle_token, owner_token) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let vault = subscription_vault_address(&fixture.program_id, &fixture.subscription);
+        let subscription_token = get_associated_token_address(&vault, &FUNDING_MINT);
+        fixture.token_account(subscription_token, vault, BALANCE);
+        fixture.token_account(oracle_token, fixture.oracle, 0);
+        fixture.token_account(owner_token, fixture.owner, 0);
+        let amount = |harness: &TestHarness, token| spl_token::state::Account::unpack(&harness.account(token).data).unwrap().amount;
+
+        // The fee is charged to the SPL balance, and stays in the token account until claimed
+        let request = fixture.request();
+        fixture.harness.warp_to_slot(1);
+        let fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        fixture.harness.process(&fulfill).unwrap();
+        assert_eq!(fixture.harness.load::<Subscription>(&fixture.subscription).balance, BALANCE - 1_000);
+
+        let claim = |subscription_token, amount| instruction(&fixture.program_id, VrfCoordinatorInstruction::ClaimOracleFees { amount }, vec![
+            AccountMeta::new_readonly(fixture.oracle, true),
+            AccountMeta::new(fixture.oracle_config, false),
+            AccountMeta::new_readonly(fixture.subscription, false),
+            AccountMeta::new(subscription_token, false),
+            AccountMeta::new(oracle_token, false),
+            AccountMeta::new_readonly(vault, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ]);
+        assert_eq!(fixture.harness.process(&claim(owner_token, 1_000)), Err(VrfCoordinatorError::InvalidTokenAccount.into()));
+        assert_eq!(fixture.harness.process(&claim(subscription_token, 1_001)), Err(VrfCoordinatorError::InsufficientBalance.into()));
+        fixture.harness.process(&claim(subscription_token, 1_000)).unwrap();
+        assert_eq!((amount(&fixture.harness, &subscription_token), amount(&fixture.harness, &oracle_token)), (BALANCE - 1_000, 1_000));
+        assert_eq!(fixture.harness.load::<OracleConfig>(&fixture.oracle_config).accrued_fees, 0);
+        assert!(matches!(fixture.harness.events()[..], [VrfEvent::OracleFeesClaimed { amount: 1_000, .. }]));
+
+        // What is left is the owner's
+        let withdraw = |owner, amount| instruction(&fixture.program_id, VrfCoordinatorInstruction::WithdrawSubscription { amount }, vec![
+            AccountMeta::new_readonly(owner, true),
+            AccountMeta::new(fixture.subscription, false),
+            AccountMeta::new(subscription_token, false),
+            AccountMeta::new(owner_token, false),
+            AccountMeta::new_readonly(vault, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ]);
+        assert_eq!(fixture.harness.process(&withdraw(fixture.oracle, 1)), Err(VrfCoordinatorError::InvalidSubscriptionOwner.into()));
+        assert_eq!(fixture.harness.process(&withdraw(fixture.owner, BALANCE - 999)), Err(VrfCoordinatorError::InsufficientBalance.into()));
+        fixture.harness.process(&withdraw(fixture.owner, BALANCE - 1_000)).unwrap();
+        assert_eq!((amount(&fixture.harness, &subscription_token), amount(&fixture.harness, &owner_token)), (0, BALANCE - 1_000));
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!(subscription.balance, 0);
+        assert_eq!(subscription.expected_balance(), Some(0));
+        assert!(matches!(
+            fixture.harness.events()[..],
+            [VrfEvent::SubscriptionWithdrawn { owner, amount, .. }] if owner == fixture.owner && amount == BALANCE - 1_000
+        ));
+    }
+
+    #[test]
+    fn test_cpi_events() {
+        let mut fixture = Fixture::new();
+        let (event_authority, _) = event_authority_address(&fixture.program_id);
+        let event_accounts = [
+            AccountMeta::new_readonly(event_authority, false),
+            AccountMeta::new_readonly(fixture.program_id, false),
+        ];
+        fixture.update_config(VrfCoordinatorInstruction::SetCpiEvents { enabled: true });
+        assert!(fixture.harness.load::<CoordinatorConfig>(&fixture.config()).cpi_events);
+
+        let request = fixture.next_request();
+        let mut instruction = fixture.request_instruction(request_randomness([1; 32], 1), vec![]);
+        assert_eq!(fixture.harness.process(&instruction), Err(VrfCoordinatorError::MissingEventAccounts.into()));
+
+        // Events go to the inner instructions rather than the log
+        instruction.accounts.extend(event_accounts.clone());
+        fixture.harness.process(&instruction).unwrap();
+        assert!(fixture.harness.logs().iter().all(|log| !log.starts_with("VRF_EVENT:")));
+        let events = fixture.harness.events();
+        assert!(matches!(events[..], [VrfEvent::RandomnessRequested { request_id, .. }] if request_id == request));
+        let invocation = fixture.harness.invocations().pop().unwrap();
+        assert_eq!(invocation.data, events[0].cpi_data());
+        assert_eq!(invocation.accounts, vec![AccountMeta::new_readonly(event_authority, true)]);
+
+        fixture.harness.warp_to_slot(1);
+        let mut fulfill = fixture.fulfill_instruction(&request, &fixture.subscription);
+        assert_eq!(fixture.harness.process(&fulfill), Err(VrfCoordinatorError::MissingEventAccounts.into()));
+        fulfill.accounts.extend(event_accounts.clone());
+        fixture.harness.process(&fulfill).unwrap();
+        assert!(matches!(fixture.harness.events()[0], VrfEvent::RandomnessFulfilled { .. }));
+
+        // Only the coordinator can sign as the event authority
+        let forged = Instruction {
+            program_id: fixture.program_id,
+            accounts: vec![AccountMeta::new_readonly(event_authority, false)],
+            data: events[0].cpi_data(),
+        };
+        assert_eq!(fixture.harness.process(&forged), Err(ProgramError::MissingRequiredSignature));
+
+        let log = AdminAuditLog::parse(&fixture.harness.account(&fixture.audit_log()).data).unwrap();
+        assert_eq!(log.entries.last().unwrap().action, AdminAction::SetCpiEvents { enabled: true });
+    }
+
+    #[test]
+    fn test_priority_fee() {
+        let mut fixture = Fixture::new();
+        fixture.update_config(VrfCoordinatorInstruction::SetMinPriorityFee { min_priority_fee: 50 });
+        assert_eq!(fixture.harness.load::<CoordinatorConfig>(&fixture.config()).min_priority_fee, 50);
+        let log = AdminAuditLog::parse(&fixture.harness.account(&fixture.audit_log()).data).unwrap();
+        assert_eq!(log.entries.last().unwrap().action, AdminAction::SetMinPriorityFee { min_priority_fee: 50 });
+
+        let tipped = |seed, priority_fee| VrfCoordinatorInstruction::RequestRandomness {
+            seed,
+            callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+            num_words: 1,
+            minimum_confirmations: 1,
+            callback_gas_limit: 100_000,
+            priority_fee,
+        };
+        let below_minimum = fixture.request_instruction(tipped([1; 32], 49), vec![]);
+        assert_eq!(fixture.harness.process(&below_minimum), Err(VrfCoordinatorError::PriorityFeeTooLow.into()));
+
+        // Tips are escrowed from the subscription when requested
+        let balance = fixture.harness.load::<Subscription>(&fixture.subscription).balance;
+        let mut requests = Vec::new();
+        for (seed, priority_fee) in [([1; 32], 0), ([2; 32], 100), ([3; 32], 300)] {
+            let request = fixture.next_request();
+            let instruction = fixture.request_instruction(tipped(seed, priority_fee), vec![]);
+            fixture.harness.process(&instruction).unwrap();
+            assert_eq!(fixture.harness.load::<RandomnessRequest>(&request).priority_fee, priority_fee);
+            assert!(matches!(
+                fixture.harness.events()[..],
+                [VrfEvent::RandomnessRequested { priority_fee: tip, .. }] if tip == priority_fee
+            ));
+            requests.push(request);
+        }
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!(subscription.balance, balance - 400 - 3 * MIN_BALANCE);
+        assert_eq!(subscription.expected_balance(), Some(subscription.balance));
+
+        // The oracle earns each request's tip on top of its fee
+        fixture.harness.warp_to_slot(1);
+        let mut accrued_fees = Vec::new();
+        for request in &requests[..2] {
+            let fulfill = fixture.fulfill_instruction(request, &fixture.subscription);
+            fixture.harness.process(&fulfill).unwrap();
+            accrued_fees.push(fixture.harness.load::<OracleConfig>(&fixture.oracle_config).accrued_fees);
+        }
+        assert_eq!(accrued_fees, vec![0, 100]);
+
+        // A cancelled request refunds its tip
+        let balance = fixture.harness.load::<Subscription>(&fixture.subscription).balance;
+        let cancel = instruction(&fixture.program_id, VrfCoordinatorInstruction::CancelRequest, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(requests[2], false),
+            AccountMeta::new(fixture.subscription, false),
+        ]);
+        fixture.harness.process(&cancel).unwrap();
+        let subscription: Subscription = fixture.harness.load(&fixture.subscription);
+        assert_eq!(subscription.balance, balance + MIN_BALANCE + 300);
+        assert_eq!(subscription.expected_balance(), Some(subscription.balance));
+    }
+
+    /// Beacon stream callback storing the round's randomness in its account
+    fn beacon_consumer(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        let [stream, round, target] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        assert_eq!(data, b"roll");
+        let randomness = crate::randomness::load_randomness(round, stream.owner)?;
+        target.try_borrow_mut_data()?.copy_from_slice(&randomness);
+        Ok(())
+    }
+
+    #[test]
+    fn test_publish_drand_round() {
+        let mut fixture = Fixture::new();
+        let beacon = TestBeacon(Fr::from(0x5eed_u64));
+        let program_id = fixture.program_id;
+        let round_account = |round: u64| Pubkey::find_program_address(&[DRAND_ROUND_SEED, &round.to_le_bytes()], &program_id).0;
+        let (config, oracle, oracle_config) = (fixture.config(), fixture.oracle, fixture.oracle_config);
+        let publish = |round: u64, signature| instruction(&program_id, VrfCoordinatorInstruction::PublishDrandRound {
+            round,
+            signature,
+        }, vec![
+            AccountMeta::new(oracle, true),
+            AccountMeta::new(round_account(round), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(oracle_config, false),
+            AccountMeta::new_readonly(config, false),
+        ]);
+
+        // Nothing is published before the admin sets the group key
+        assert_eq!(fixture.harness.process(&publish(5, beacon.sign(5))), Err(VrfCoordinatorError::DrandPublicKeyNotSet.into()));
+        let public_key = beacon.public_key();
+        fixture.update_config(VrfCoordinatorInstruction::SetDrandPublicKey { public_key });
+        assert_eq!(fixture.harness.load::<CoordinatorConfig>(&config).drand_public_key, public_key);
+        let log = AdminAuditLog::parse(&fixture.harness.account(&fixture.audit_log()).data).unwrap();
+        assert_eq!(log.entries.last().unwrap().action, AdminAction::SetDrandPublicKey { key_hash: hash(&public_key).to_bytes() });
+
+        // An oracle cannot relay a signature of another round, or of another group
+        assert_eq!(fixture.harness.process(&publish(5, beacon.sign(6))), Err(VrfCoordinatorError::InvalidDrandSignature.into()));
+        let forged = TestBeacon(Fr::from(7u8)).sign(5);
+        assert_eq!(fixture.harness.process(&publish(5, forged)), Err(VrfCoordinatorError::InvalidDrandSignature.into()));
+
+        let signature = beacon.sign(5);
+        fixture.harness.process(&publish(5, signature)).unwrap();
+        let drand_round: DrandRound = fixture.harness.load(&round_account(5));
+        assert_eq!((drand_round.round, drand_round.signature), (5, signature));
+        assert_eq!(drand_round.randomness, hash(&signature).to_bytes());
+        assert!(matches!(fixture.harness.events()[..], [VrfEvent::DrandRoundPublished { round: 5, .. }]));
+    }
+
+    #[test]
+    fn test_beacon_stream() {
+        let mut fixture = Fixture::new();
+        let (consumer, target) = (Pubkey::new_unique(), Pubkey::new_unique());
+        fixture.harness.add_program(consumer, beacon_consumer);
+        fixture.harness.set_account(target, Account {
+            lamports: Rent::default().minimum_balance(32),
+            data: vec![0; 32],
+            owner: consumer,
+            ..Account::default()
+        });
+
+        let stream = fixture.pda(&[BeaconStream::SEED, fixture.owner.as_ref()]);
+        let create = |callback_gas_limit| instruction(&fixture.program_id, VrfCoordinatorInstruction::CreateBeaconStream {
+            callback_program: consumer,
+            callback_account: target,
+            callback_gas_limit,
+            callback_data: b"roll".to_vec(),
+        }, vec![
+            AccountMeta::new(fixture.owner, true),
+            AccountMeta::new(stream, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]);
+        assert_eq!(fixture.harness.process(&create(0)), Err(VrfCoordinatorError::InvalidCallbackGasLimit.into()));
+        fixture.harness.process(&create(50_000)).unwrap();
+        assert_eq!(fixture.harness.process(&create(50_000)), Err(ProgramError::AccountAlreadyInitialized));
+
+        let beacon = TestBeacon(Fr::from(0x5eed_u64));
+        fixture.update_config(VrfCoordinatorInstruction::SetDrandPublicKey { public_key: beacon.public_key() });
+        let program_id = fixture.program_id;
+        let round_account = |round: u64| Pubkey::find_program_address(&[DRAND_ROUND_SEED, &round.to_le_bytes()], &program_id).0;
+        let config = fixture.config();
+        let publish = |round: u64, streamed: Vec<AccountMeta>| {
+            let mut accounts = vec![
+                AccountMeta::new(fixture.oracle, true),
+                AccountMeta::new(round_account(round), false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(fixture.oracle_config, false),
+                AccountMeta::new_readonly(config, false),
+            ];
+            accounts.extend(streamed);
+            instruction(&fixture.program_id, VrfCoordinatorInstruction::PublishDrandRound {
+                round,
+                signature: beacon.sign(round),
+            }, accounts)
+        };
+        let streamed = |callback_account| vec![
+            AccountMeta::new(stream, false),
+            AccountMeta::new_readonly(consumer, false),
+            AccountMeta::new(callback_account, false),
+        ];
+
+        // Each round is written into the stream and handed to its callback
+        fixture.harness.warp_to_slot(7);
+        fixture.harness.process(&publish(5, streamed(target))).unwrap();
+        let randomness = fixture.harness.load::<DrandRound>(&round_account(5)).randomness;
+        let beacon_stream: BeaconStream = fixture.harness.load(&stream);
+        assert_eq!(beacon_stream.rounds_written, 1);
+        assert_eq!(beacon_stream.latest(), Some(&BeaconEntry { round: 5, randomness, published_slot: 7 }));
+        assert_eq!(fixture.harness.account(&target).data, randomness);
+
+        // The callback accounts must be the stream's
+        assert_eq!(fixture.harness.process(&publish(6, streamed(Pubkey::new_unique()))), Err(VrfCoordinatorError::InvalidRequestParameters.into()));
+        assert_eq!(fixture.harness.process(&publish(6, streamed(target)[..1].to_vec())), Err(ProgramError::NotEnoughAccountKeys));
+
+        // Without the compute units for it the callback is skipped, the round still written
+        fixture.harness.set_remaining_compute_units(50_000 - 1);
+        fixture.harness.process(&publish(6, streamed(target))).unwrap();
+        assert_eq!(fixture.harness.load::<BeaconStream>(&stream).latest().unwrap().round, 6);
+        assert_eq!(fixture.harness.account(&target).data, randomness);
+
+        // Rounds relayed out of order are not streamed
+        assert_eq!(fixture.harness.process(&publish(3, streamed(target))), Err(VrfCoordinatorError::StaleBeaconRound.into()));
+        fixture.harness.process(&publish(3, vec![])).unwrap();
+
+        let close = |owner| instruction(&fixture.program_id, VrfCoordinatorInstruction::CloseBeaconStream, vec![
+            AccountMeta::new(owner, true),
+            AccountMeta::new(stream, false),
+        ]);
+        assert_eq!(fixture.harness.process(&close(fixture.oracle)), Err(VrfCoordinatorError::InvalidRequestParameters.into()));
+        let (owner_lamports, stream_lamports) = (fixture.harness.account(&fixture.owner).lamports, fixture.harness.account(&stream).lamports);
+        fixture.harness.process(&close(fixture.owner)).unwrap();
+        assert_eq!(fixture.harness.account(&fixture.owner).lamports, owner_lamports + stream_lamports);
+    }
+}
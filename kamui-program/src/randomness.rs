@@ -0,0 +1,272 @@
+//! Helpers for turning VRF outputs into values consumers can use.
+
+use {
+    crate::{
+        error::VrfCoordinatorError,
+        loader::{load, AccountState},
+        state::{DrandRound, VrfResult},
+    },
+    solana_program::{account_info::AccountInfo, hash::hashv, program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// Reads 32 bytes of randomness from a coordinator account, whatever its
+/// source: the first word of a [VrfResult], or the randomness of a relayed
+/// [DrandRound]. Consumers that accept either can take the account as is.
+pub fn load_randomness(account: &AccountInfo, coordinator: &Pubkey) -> Result<[u8; 32], ProgramError> {
+    let discriminator: [u8; 8] = account
+        .try_borrow_data()?
+        .get(..8)
+        .and_then(|data| data.try_into().ok())
+        .ok_or(VrfCoordinatorError::AccountTooSmall)?;
+    match discriminator {
+        VrfResult::DISCRIMINATOR => {
+            let result: VrfResult = load(account, coordinator)?;
+            let word = result.randomness.first().ok_or(ProgramError::InvalidAccountData)?;
+            Ok(word[..32].try_into().unwrap())
+        }
+        DrandRound::DISCRIMINATOR => Ok(load::<DrandRound>(account, coordinator)?.randomness),
+        _ => Err(VrfCoordinatorError::InvalidDiscriminator.into()),
+    }
+}
+
+/// Derives a uniformly distributed value in `[min, max]` (inclusive) from a VRF
+/// output.
+///
+/// `word_index` selects an independent value, so one output can yield several.
+/// Candidates are drawn from `sha256("range", vrf_output, word_index, counter)`
+/// and rejected if they fall in the final partial bucket of the range, which
+/// removes the bias of taking the output modulo the range size.
+pub fn derive_in_range(
+    vrf_output: &[u8; 64],
+    word_index: u32,
+    min: u64,
+    max: u64,
+) -> Result<u64, ProgramError> {
+    if min > max {
+        return Err(VrfCoordinatorError::InvalidRange.into());
+    }
+    let range = (max - min).wrapping_add(1);
+    // 2^64 mod range: candidates below this are rejected. Zero when the range
+    // covers all of u64, which wraps to 0 and accepts every candidate.
+    let threshold = range.wrapping_neg().checked_rem(range).unwrap_or(0);
+
+    for counter in 0u64.. {
+        let hash = hashv(&[
+            b"range",
+            vrf_output,
+            &word_index.to_le_bytes(),
+            &counter.to_le_bytes(),
+        ]);
+        let candidate = u64::from_le_bytes(hash.to_bytes()[0..8].try_into().unwrap());
+        if candidate < threshold {
+            continue;
+        }
+        return Ok(match range {
+            0 => candidate,
+            _ => min + candidate % range,
+        });
+    }
+    unreachable!()
+}
+
+/// The first 8 bytes of a VRF output as a little-endian u64.
+pub fn output_to_u64(vrf_output: &[u8; 64]) -> u64 {
+    u64::from_le_bytes(vrf_output[..8].try_into().unwrap())
+}
+
+/// Shuffles `items` uniformly with Fisher-Yates, swapping position `i` with a
+/// position in `[0, i]` derived with [derive_in_range] from word index `i`.
+pub fn shuffle<T>(vrf_output: &[u8; 64], items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        let j = derive_in_range(vrf_output, i as u32, 0, i as u64).expect("0 <= i");
+        items.swap(i, j as usize);
+    }
+}
+
+/// Picks an index with probability proportional to its weight, from word
+/// index 0. Fails with `InvalidWeights` if the weights are empty, all zero,
+/// or sum past `u64::MAX`.
+pub fn weighted_choice(vrf_output: &[u8; 64], weights: &[u64]) -> Result<usize, ProgramError> {
+    let total = weights
+        .iter()
+        .try_fold(0u64, |total, &weight| total.checked_add(weight))
+        .filter(|&total| total > 0)
+        .ok_or(VrfCoordinatorError::InvalidWeights)?;
+    let mut target = derive_in_range(vrf_output, 0, 0, total - 1)?;
+    for (index, &weight) in weights.iter().enumerate() {
+        if target < weight {
+            return Ok(index);
+        }
+        target -= weight;
+    }
+    unreachable!()
+}
+
+/// Expands a VRF output into the `index`th word of a request's result. Word 0
+/// is the output itself.
+pub fn expand_word(vrf_output: &[u8; 64], index: u32) -> [u8; 64] {
+    if index == 0 {
+        return *vrf_output;
+    }
+    let mut word = [0u8; 64];
+    for (half, chunk) in word.chunks_mut(32).enumerate() {
+        let hash = hashv(&[b"word", vrf_output, &index.to_le_bytes(), &[half as u8]]);
+        chunk.copy_from_slice(hash.as_ref());
+    }
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{loader::store, state::{DRAND_SIGNATURE_LEN, PROOF_LEN}},
+        solana_program::clock::Epoch,
+    };
+
+    #[test]
+    fn test_load_randomness_from_either_source() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+
+        let mut word = [1u8; 64];
+        word[32..].copy_from_slice(&[2; 32]);
+        let (mut lamports, mut data) = (0, vec![0u8; VrfResult::space(1)]);
+        let account = AccountInfo::new(
+            &key, false, true, &mut lamports, &mut data, &program_id, false, Epoch::default(),
+        );
+        store(&account, &VrfResult {
+            randomness: vec![word],
+            proof: vec![0; PROOF_LEN],
+            proof_block: 0,
+            request: key,
+        }).unwrap();
+        assert_eq!(load_randomness(&account, &program_id), Ok([1; 32]));
+
+        let (mut lamports, mut data) = (0, vec![0u8; DrandRound::LEN]);
+        let account = AccountInfo::new(
+            &key, false, true, &mut lamports, &mut data, &program_id, false, Epoch::default(),
+        );
+        store(&account, &DrandRound {
+            round: 1,
+            signature: [3; DRAND_SIGNATURE_LEN],
+            randomness: [4; 32],
+            oracle: Pubkey::new_unique(),
+            published_slot: 5,
+        }).unwrap();
+        assert_eq!(load_randomness(&account, &program_id), Ok([4; 32]));
+        assert_eq!(
+            load_randomness(&account, &Pubkey::new_unique()),
+            Err(ProgramError::IncorrectProgramId)
+        );
+
+        account.data.borrow_mut()[0] ^= 1;
+        assert_eq!(
+            load_randomness(&account, &program_id),
+            Err(VrfCoordinatorError::InvalidDiscriminator.into())
+        );
+    }
+
+    #[test]
+    fn test_derive_in_range_bounds() {
+        for byte in 0..=255u8 {
+            let output = [byte; 64];
+            let value = derive_in_range(&output, 0, 1, 100).unwrap();
+            assert!((1..=100).contains(&value));
+            assert_eq!(derive_in_range(&output, 0, 1, 100).unwrap(), value);
+            assert_eq!(derive_in_range(&output, 3, 7, 7).unwrap(), 7);
+        }
+    }
+
+    #[test]
+    fn test_derive_in_range_word_index_is_independent() {
+        let output = [42u8; 64];
+        let values: Vec<u64> = (0..8)
+            .map(|i| derive_in_range(&output, i, 0, u64::MAX).unwrap())
+            .collect();
+        for (i, a) in values.iter().enumerate() {
+            assert!(values[i + 1..].iter().all(|b| a != b));
+        }
+    }
+
+    #[test]
+    fn test_expand_word() {
+        let output = [7u8; 64];
+        assert_eq!(expand_word(&output, 0), output);
+        assert_ne!(expand_word(&output, 1), output);
+        assert_ne!(expand_word(&output, 1), expand_word(&output, 2));
+        assert_eq!(expand_word(&output, 1), expand_word(&output, 1));
+    }
+
+    #[test]
+    fn test_shuffle_is_a_permutation() {
+        let output = [9u8; 64];
+        let mut items: Vec<u32> = (0..50).collect();
+        shuffle(&output, &mut items);
+        assert_ne!(items, (0..50).collect::<Vec<_>>());
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..50).collect::<Vec<_>>());
+
+        let mut again: Vec<u32> = (0..50).collect();
+        shuffle(&output, &mut again);
+        assert_eq!(again, items);
+
+        shuffle::<u32>(&output, &mut []);
+    }
+
+    #[test]
+    fn test_weighted_choice() {
+        for byte in 0..=255u8 {
+            let output = [byte; 64];
+            assert_eq!(weighted_choice(&output, &[0, 5, 0]), Ok(1));
+            assert!(weighted_choice(&output, &[1, 1, 1]).unwrap() < 3);
+        }
+
+        let mut counts = [0u32; 2];
+        for byte in 0..=255u8 {
+            counts[weighted_choice(&[byte; 64], &[1, 3]).unwrap()] += 1;
+        }
+        assert!(counts[1] > counts[0]);
+
+        for weights in [&[][..], &[0, 0], &[u64::MAX, 1]] {
+            assert_eq!(
+                weighted_choice(&[0u8; 64], weights),
+                Err(VrfCoordinatorError::InvalidWeights.into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_helpers_agree_with_mangekyou() {
+        use mangekyou::kamui_vrf::ecvrf::VRFOutput;
+
+        for byte in 0..32u8 {
+            let output = [byte; 64];
+            let mangekyou_output = VRFOutput(output);
+            assert_eq!(output_to_u64(&output), mangekyou_output.to_u64());
+            assert_eq!(
+                derive_in_range(&output, 0, 10, 1_000).unwrap(),
+                mangekyou_output.in_range(10, 1_000).unwrap()
+            );
+            assert_eq!(
+                weighted_choice(&output, &[3, 1, 4, 1, 5]).unwrap(),
+                mangekyou_output.weighted_choice(&[3, 1, 4, 1, 5]).unwrap()
+            );
+
+            let mut items: Vec<u32> = (0..20).collect();
+            let mut mangekyou_items = items.clone();
+            shuffle(&output, &mut items);
+            mangekyou_output.shuffle(&mut mangekyou_items);
+            assert_eq!(items, mangekyou_items);
+        }
+    }
+
+    #[test]
+    fn test_derive_in_range_rejects_empty_range() {
+        assert_eq!(
+            derive_in_range(&[0u8; 64], 0, 2, 1),
+            Err(VrfCoordinatorError::InvalidRange.into())
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Self-contained receipts of fulfillments, for auditing historical randomness
+//! without access to archived account state.
+//!
+//! A client assembles a [FulfillmentReceipt] from the request account data and
+//! the `FulfillRandomness` instruction of the fulfilling transaction, and
+//! anyone can check it with [verify_receipt] given only the coordinator
+//! program ID.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    crate::{
+        instruction::VrfCoordinatorInstruction,
+        loader::check_data,
+        pda::{derive_direct_request_pda, derive_request_pda},
+        state::{RandomnessRequest, ACCOUNT_HEADER_LEN},
+        verifier::VrfSuite,
+    },
+    solana_program::pubkey::Pubkey,
+    thiserror::Error,
+};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ReceiptError {
+    #[error("Request account data is malformed")]
+    InvalidRequestData,
+
+    #[error("Instruction is not a FulfillRandomness instruction")]
+    InvalidInstructionData,
+
+    #[error("Request ID does not match the request data")]
+    RequestIdMismatch,
+
+    #[error("VRF proof does not verify for the request seed")]
+    InvalidProof,
+
+    #[error("Output does not match the proof")]
+    OutputMismatch,
+}
+
+/// Everything needed to check a fulfillment after the fact
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct FulfillmentReceipt {
+    /// Address of the request account
+    pub request_id: Pubkey,
+    /// The request as it was fulfilled
+    pub request: RandomnessRequest,
+    /// VRF public key of the fulfilling oracle
+    pub public_key: Vec<u8>,
+    /// The ECVRF proof
+    pub proof: Vec<u8>,
+    /// Suite of the proof
+    pub suite: VrfSuite,
+    /// The VRF output
+    pub output: [u8; 64],
+    /// Signature of the fulfilling transaction
+    pub transaction_signature: [u8; 64],
+    /// Slot of the fulfilling transaction
+    pub slot: u64,
+}
+
+impl FulfillmentReceipt {
+    /// Assembles a receipt from the raw request account data and the data of
+    /// the `FulfillRandomness` instruction that fulfilled it. The output is
+    /// computed from the proof, so an unverifiable proof is rejected here.
+    pub fn assemble(
+        request_id: Pubkey,
+        request_account_data: &[u8],
+        instruction_data: &[u8],
+        transaction_signature: [u8; 64],
+        slot: u64,
+    ) -> Result<Self, ReceiptError> {
+        if check_data::<RandomnessRequest>(request_account_data).is_err() {
+            return Err(ReceiptError::InvalidRequestData);
+        }
+        let request = RandomnessRequest::deserialize(&mut &request_account_data[ACCOUNT_HEADER_LEN..])
+            .map_err(|_| ReceiptError::InvalidRequestData)?;
+
+        let (proof, public_key, suite) = match VrfCoordinatorInstruction::try_from_slice(instruction_data) {
+            Ok(VrfCoordinatorInstruction::FulfillRandomness { proof, public_key, suite }) => (proof, public_key, suite),
+            _ => return Err(ReceiptError::InvalidInstructionData),
+        };
+        let output = suite.verify(&request.seed, &proof, &public_key)
+            .map_err(|_| ReceiptError::InvalidProof)?;
+
+        Ok(Self {
+            request_id,
+            request,
+            public_key,
+            proof,
+            suite,
+            output,
+            transaction_signature,
+            slot,
+        })
+    }
+}
+
+/// Checks that `receipt` describes a request of the coordinator `program_id`
+/// and that its output is the verified VRF output for the request's seed.
+/// Returns the output.
+pub fn verify_receipt(receipt: &FulfillmentReceipt, program_id: &Pubkey) -> Result<[u8; 64], ReceiptError> {
+    let request = &receipt.request;
+    let (request_id, _) = if request.is_direct() {
+        derive_direct_request_pda(program_id, &request.requester, &request.seed)
+    } else {
+        derive_request_pda(program_id, &request.subscription, request.nonce)
+    };
+    if request_id != receipt.request_id {
+        return Err(ReceiptError::RequestIdMismatch);
+    }
+
+    let output = receipt.suite.verify(&request.seed, &receipt.proof, &receipt.public_key)
+        .map_err(|_| ReceiptError::InvalidProof)?;
+    if output != receipt.output {
+        return Err(ReceiptError::OutputMismatch);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            loader::AccountState,
+            state::{FeeAuction, RequestId, RequestStatus, MINIMUM_CALLBACK_GAS_LIMIT},
+        },
+        mangekyou::kamui_vrf::{ecvrf::ECVRFKeyPair, VRFKeyPair},
+    };
+
+    fn signed_receipt(program_id: &Pubkey, keypair: &ECVRFKeyPair) -> FulfillmentReceipt {
+        let subscription = Pubkey::new_unique();
+        let (request_id, _) = derive_request_pda(program_id, &subscription, 3);
+        let request = RandomnessRequest {
+            subscription,
+            seed: [5; 32],
+            requester: Pubkey::new_unique(),
+            callback_data: vec![],
+            request_block: 1,
+            status: RequestStatus::Fulfilled,
+            num_words: 1,
+            callback_gas_limit: MINIMUM_CALLBACK_GAS_LIMIT,
+            nonce: 3,
+            commitment: [0; 32],
+            assigned_oracle: Pubkey::default(),
+            not_before_slot: 0,
+            fee_auction: FeeAuction::default(),
+            expiration_slot: 0,
+            confirmations: 0,
+            required_oracle_responses: 1,
+            priority_fee: 0,
+            reserved_fee: 0,
+            reserved_native: 0,
+            id: RequestId::default(),
+        };
+        let mut request_data = RandomnessRequest::DISCRIMINATOR.to_vec();
+        request_data.push(RandomnessRequest::VERSION);
+        request_data.extend(borsh::to_vec(&request).unwrap());
+        let instruction_data = borsh::to_vec(&VrfCoordinatorInstruction::FulfillRandomness {
+            proof: keypair.prove(&request.seed).to_bytes_legacy(),
+            public_key: keypair.pk.as_ref().to_vec(),
+            suite: VrfSuite::default(),
+        }).unwrap();
+
+        FulfillmentReceipt::assemble(request_id, &request_data, &instruction_data, [9; 64], 2).unwrap()
+    }
+
+    #[test]
+    fn test_receipt_roundtrip() {
+        let program_id = Pubkey::new_unique();
+        let keypair = ECVRFKeyPair::generate(&mut rand::thread_rng());
+        let receipt = signed_receipt(&program_id, &keypair);
+        assert_eq!(receipt.output, keypair.output(&[5; 32]).0);
+
+        let encoded = borsh::to_vec(&receipt).unwrap();
+        let decoded = FulfillmentReceipt::try_from_slice(&encoded).unwrap();
+        assert_eq!(verify_receipt(&decoded, &program_id), Ok(receipt.output));
+    }
+
+    #[test]
+    fn test_tampered_receipt_rejected() {
+        let program_id = Pubkey::new_unique();
+        let keypair = ECVRFKeyPair::generate(&mut rand::thread_rng());
+
+        let mut receipt = signed_receipt(&program_id, &keypair);
+        assert_eq!(verify_receipt(&receipt, &Pubkey::new_unique()), Err(ReceiptError::RequestIdMismatch));
+        receipt.output[0] ^= 1;
+        assert_eq!(verify_receipt(&receipt, &program_id), Err(ReceiptError::OutputMismatch));
+
+        let mut receipt = signed_receipt(&program_id, &keypair);
+        receipt.request.seed[0] ^= 1;
+        assert_eq!(verify_receipt(&receipt, &program_id), Err(ReceiptError::InvalidProof));
+
+        let mut receipt = signed_receipt(&program_id, &keypair);
+        receipt.request.nonce += 1;
+        assert_eq!(verify_receipt(&receipt, &program_id), Err(ReceiptError::RequestIdMismatch));
+
+        // The proof only verifies in the suite it was made in
+        let mut receipt = signed_receipt(&program_id, &keypair);
+        receipt.suite = VrfSuite::Edwards25519Sha512Tai;
+        assert_eq!(verify_receipt(&receipt, &program_id), Err(ReceiptError::InvalidProof));
+    }
+}
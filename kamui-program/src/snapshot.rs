@@ -0,0 +1,315 @@
+//! Point-in-time export of every coordinator account, for analytics, audits
+//! and migration dry-runs. Built with the `client` feature; `kamui-cli
+//! snapshot` writes the result of [take_snapshot] as JSON.
+//!
+//! Accounts are grouped by type. Keys are base58 strings and hashes hex, so
+//! the output loads directly into the usual analytics tools. Accounts of a
+//! type the snapshot does not decode are listed under `other` with their
+//! discriminator and size.
+
+// `ClientError` is solana-client's own error type
+#![allow(clippy::result_large_err)]
+
+use {
+    crate::{
+        state::{
+            migrations::{self, Migrate},
+            CoordinatorConfig, CoordinatorStats, OracleConfig, OracleResponses, RandomnessRequest, RequestPool,
+            Subscription, VrfResult,
+        },
+    },
+    serde_json::{json, Value},
+    solana_account_decoder::UiAccountEncoding,
+    solana_client::{
+        client_error::Result as ClientResult,
+        rpc_client::RpcClient,
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    },
+    solana_program::pubkey::Pubkey,
+};
+
+/// Decoded coordinator account
+#[derive(Debug)]
+pub enum CoordinatorAccount {
+    Subscription(Subscription),
+    Request(RandomnessRequest),
+    Result(VrfResult),
+    Oracle(OracleConfig),
+    Config(CoordinatorConfig),
+    Stats(CoordinatorStats),
+    RequestPool(RequestPool),
+    OracleResponses(OracleResponses),
+    /// An account the snapshot does not decode
+    Other { discriminator: [u8; 8], data_len: usize },
+}
+
+impl CoordinatorAccount {
+    /// Decodes account data by its discriminator, at whatever layout version
+    /// it was written in
+    pub fn decode(data: &[u8]) -> Self {
+        fn state<T: Migrate>(data: &[u8]) -> Option<T> {
+            migrations::upgrade(data).ok().map(|(_, state)| state)
+        }
+
+        let decoded = state(data).map(Self::Subscription)
+            .or_else(|| state(data).map(Self::Request))
+            .or_else(|| state(data).map(Self::Result))
+            .or_else(|| state(data).map(Self::Oracle))
+            .or_else(|| state(data).map(Self::Config))
+            .or_else(|| state(data).map(Self::Stats))
+            .or_else(|| state(data).map(Self::RequestPool))
+            .or_else(|| state(data).map(Self::OracleResponses));
+        decoded.unwrap_or_else(|| {
+            let mut discriminator = [0u8; 8];
+            let len = data.len().min(8);
+            discriminator[..len].copy_from_slice(&data[..len]);
+            Self::Other { discriminator, data_len: data.len() }
+        })
+    }
+
+    /// Group the account is listed under in the JSON export
+    fn group(&self) -> &'static str {
+        match self {
+            Self::Subscription(_) => "subscriptions",
+            Self::Request(_) => "requests",
+            Self::Result(_) => "results",
+            Self::Oracle(_) => "oracles",
+            Self::Config(_) => "config",
+            Self::Stats(_) => "stats",
+            Self::RequestPool(_) => "request_pools",
+            Self::OracleResponses(_) => "oracle_responses",
+            Self::Other { .. } => "other",
+        }
+    }
+
+    /// JSON object of the account's fields, with its `address`
+    fn to_json(&self, address: &Pubkey) -> Value {
+        let mut value = match self {
+            Self::Subscription(subscription) => json!({
+                "owner": subscription.owner.to_string(),
+                "balance": subscription.balance,
+                "min_balance": subscription.min_balance,
+                "confirmations": subscription.confirmations,
+                "nonce": subscription.nonce,
+                "backstop_timeout_slots": subscription.backstop_timeout_slots,
+                "backstop_bonus": subscription.backstop_bonus,
+                "total_funded": subscription.total_funded,
+                "total_credited": subscription.total_credited,
+                "total_debited": subscription.total_debited,
+                "audited_nonce": subscription.audited_nonce,
+                "pending_owner": subscription.pending_owner.to_string(),
+                "native_balance": subscription.native_balance,
+                "max_requests_per_slot": subscription.max_requests_per_slot,
+                "max_pending_requests": subscription.max_pending_requests,
+                "pending_requests": subscription.pending_requests,
+                "consumers": subscription.consumers.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            }),
+            Self::Request(request) => json!({
+                "subscription": request.subscription.to_string(),
+                "seed": hex::encode(request.seed),
+                "requester": request.requester.to_string(),
+                "request_block": request.request_block,
+                "status": format!("{:?}", request.status),
+                "num_words": request.num_words,
+                "callback_gas_limit": request.callback_gas_limit,
+                "nonce": request.nonce,
+                "commitment": hex::encode(request.commitment),
+                "assigned_oracle": request.assigned_oracle.to_string(),
+                "not_before_slot": request.not_before_slot,
+                "fee_auction": {
+                    "start_price": request.fee_auction.start_price,
+                    "end_price": request.fee_auction.end_price,
+                    "duration_slots": request.fee_auction.duration_slots,
+                },
+                "expiration_slot": request.expiration_slot,
+                "confirmations": request.confirmations,
+                "required_oracle_responses": request.required_oracle_responses,
+                "priority_fee": request.priority_fee,
+                "id": hex::encode(request.id.0),
+                "callback_data": hex::encode(&request.callback_data),
+            }),
+            Self::Result(result) => json!({
+                "request": result.request.to_string(),
+                "randomness": result.randomness.iter().map(hex::encode).collect::<Vec<_>>(),
+                "proof": hex::encode(&result.proof),
+                "proof_block": result.proof_block,
+            }),
+            Self::Oracle(oracle) => json!({
+                "oracle_key": oracle.oracle_key.to_string(),
+                "vrf_key": hex::encode(oracle.vrf_key),
+                "is_active": oracle.is_active,
+                "accrued_fees": oracle.accrued_fees,
+                "fulfillments": oracle.fulfillments,
+                "average_latency": oracle.average_latency(),
+                "failures": oracle.failures,
+                "reputation": oracle.reputation(),
+            }),
+            Self::Config(config) => json!({
+                "admin": config.admin.to_string(),
+                "fee_schedule": {
+                    "base_fee": config.fee_schedule.base_fee,
+                    "fee_per_word": config.fee_schedule.fee_per_word,
+                    "fee_per_million_callback_units": config.fee_schedule.fee_per_million_callback_units,
+                    "priority_fee": config.fee_schedule.priority_fee,
+                },
+                "bridge_program": config.bridge_program.to_string(),
+                "min_confirmations": config.min_confirmations,
+                "max_confirmations": config.max_confirmations,
+                "paused": config.paused,
+                "cpi_events": config.cpi_events,
+                "min_priority_fee": config.min_priority_fee,
+                "oracle_rotation": config.oracle_rotation.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            }),
+            Self::Stats(stats) => json!({
+                "total_requests": stats.total_requests,
+                "total_fulfillments": stats.total_fulfillments,
+                "total_failures": stats.total_failures,
+                "total_fees": stats.total_fees,
+                "average_latency": stats.average_latency(),
+                "oracle_fulfillments": stats.oracle_fulfillments.iter().map(|entry| json!({
+                    "oracle": entry.oracle.to_string(),
+                    "fulfillments": entry.fulfillments,
+                })).collect::<Vec<_>>(),
+            }),
+            Self::RequestPool(pool) => json!({
+                "subscription": pool.subscription.to_string(),
+                "unpooled": pool.unpooled,
+                "requests": pool.requests.iter().map(|entry| json!({
+                    "request": entry.request.to_string(),
+                    "seed": hex::encode(entry.seed),
+                })).collect::<Vec<_>>(),
+            }),
+            Self::OracleResponses(responses) => json!({
+                "request": responses.request.to_string(),
+                "responses": responses.responses.iter().map(|entry| json!({
+                    "oracle": entry.oracle.to_string(),
+                    "output": hex::encode(entry.output),
+                })).collect::<Vec<_>>(),
+            }),
+            Self::Other { discriminator, data_len } => json!({
+                "discriminator": String::from_utf8_lossy(discriminator).trim_end_matches('\0'),
+                "data_len": data_len,
+            }),
+        };
+        value["address"] = json!(address.to_string());
+        value
+    }
+}
+
+/// Every account of a coordinator at `slot`
+#[derive(Debug)]
+pub struct Snapshot {
+    pub program_id: Pubkey,
+    /// Slot the RPC node was at before the accounts were fetched
+    pub slot: u64,
+    pub accounts: Vec<(Pubkey, CoordinatorAccount)>,
+}
+
+impl Snapshot {
+    /// The snapshot as a JSON object with the accounts grouped by type
+    pub fn to_json(&self) -> Value {
+        let mut groups = json!({
+            "subscriptions": [],
+            "requests": [],
+            "results": [],
+            "oracles": [],
+            "config": [],
+            "stats": [],
+            "request_pools": [],
+            "oracle_responses": [],
+            "other": [],
+        });
+        for (address, account) in &self.accounts {
+            groups[account.group()].as_array_mut().unwrap().push(account.to_json(address));
+        }
+        json!({
+            "program_id": self.program_id.to_string(),
+            "slot": self.slot,
+            "accounts": groups,
+        })
+    }
+}
+
+/// Fetches and decodes every account owned by the coordinator `program_id`
+pub fn take_snapshot(rpc: &RpcClient, program_id: &Pubkey) -> ClientResult<Snapshot> {
+    let slot = rpc.get_slot()?;
+    let accounts = rpc.get_program_accounts_with_config(program_id, RpcProgramAccountsConfig {
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    })?;
+    Ok(Snapshot {
+        program_id: *program_id,
+        slot,
+        accounts: accounts
+            .into_iter()
+            .map(|(address, account)| (address, CoordinatorAccount::decode(&account.data)))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            loader::AccountState,
+            state::{FeeAuction, RequestId, RequestStatus},
+        },
+    };
+
+    #[test]
+    fn test_snapshot_groups_accounts() {
+        let request = RandomnessRequest {
+            subscription: Pubkey::new_unique(),
+            seed: [1; 32],
+            requester: Pubkey::new_unique(),
+            request_block: 2,
+            status: RequestStatus::Pending,
+            num_words: 1,
+            callback_gas_limit: 3,
+            nonce: 4,
+            commitment: [5; 32],
+            assigned_oracle: Pubkey::default(),
+            not_before_slot: 0,
+            fee_auction: FeeAuction::default(),
+            expiration_slot: 0,
+            confirmations: 0,
+            required_oracle_responses: 1,
+            priority_fee: 0,
+            reserved_fee: 0,
+            reserved_native: 0,
+            id: RequestId::default(),
+            callback_data: vec![6],
+        };
+        let mut request_data = RandomnessRequest::DISCRIMINATOR.to_vec();
+        request_data.push(RandomnessRequest::VERSION);
+        request_data.extend(borsh::to_vec(&request).unwrap());
+
+        let (request_address, other_address) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let snapshot = Snapshot {
+            program_id: Pubkey::new_unique(),
+            slot: 7,
+            accounts: vec![
+                (request_address, CoordinatorAccount::decode(&request_data)),
+                (other_address, CoordinatorAccount::decode(b"SCHEDULE\x01\x02")),
+            ],
+        };
+
+        let json = snapshot.to_json();
+        assert_eq!(json["slot"], 7);
+        let requests = json["accounts"]["requests"].as_array().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["address"], request_address.to_string());
+        assert_eq!(requests[0]["requester"], request.requester.to_string());
+        assert_eq!(requests[0]["status"], "Pending");
+        assert_eq!(json["accounts"]["other"][0], json!({
+            "address": other_address.to_string(),
+            "discriminator": "SCHEDULE",
+            "data_len": 10,
+        }));
+        assert!(json["accounts"]["subscriptions"].as_array().unwrap().is_empty());
+    }
+}
@@ -1,47 +1,392 @@
 use {
     borsh::{BorshDeserialize, BorshSerialize},
-    solana_program::pubkey::Pubkey,
+    bytemuck::{Pod, Zeroable},
+    crate::{error::VrfCoordinatorError, loader::{AccountHeader, AccountState}},
+    solana_program::{hash::hashv, program_error::ProgramError, pubkey::Pubkey},
+    spl_associated_token_account::get_associated_token_address,
 };
 
+pub mod migrations;
+
 /// Constants for request validation
 pub const MINIMUM_REQUEST_CONFIRMATIONS: u8 = 1;
 pub const MAXIMUM_REQUEST_CONFIRMATIONS: u8 = 255;
 pub const MINIMUM_CALLBACK_GAS_LIMIT: u64 = 10_000;
 pub const MAXIMUM_CALLBACK_GAS_LIMIT: u64 = 1_000_000;
 pub const MAXIMUM_RANDOM_WORDS: u32 = 100;
+pub const MAXIMUM_ALPHA_LEN: usize = 1024;
+pub const MAXIMUM_CONSUMERS: usize = 64;
+pub const MAXIMUM_BATCH_FULFILLMENTS: usize = 8;
+pub const MAXIMUM_ROTATION_ORACLES: usize = 32;
+pub const MAXIMUM_ORACLE_RESPONSES: u8 = 8;
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+/// Default number of slots the assigned oracle has before any registered oracle may fulfill
+pub const DEFAULT_BACKSTOP_TIMEOUT_SLOTS: u64 = 150;
+
+/// Number of slots, about a day, after which an unfulfilled request may be
+/// expired with `ExpireRequest`
+pub const REQUEST_EXPIRATION_SLOTS: u64 = 216_000;
+
+/// Reputation of an oracle with a perfect record, see [OracleConfig::reputation]
+pub const MAXIMUM_REPUTATION: u64 = 10_000;
+
+/// Average fulfillment latency in slots above which an oracle's reputation declines
+pub const REPUTATION_LATENCY_TARGET_SLOTS: u64 = 4;
+
+/// Length of the account discriminator that precedes the serialized state
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// Length of the account header: the discriminator, then the one-byte layout
+/// version, see [crate::loader::AccountState::VERSION]
+pub const ACCOUNT_HEADER_LEN: usize = DISCRIMINATOR_LEN + 1;
+
+/// Length of an ECVRF proof (gamma || c || s)
+pub const PROOF_LEN: usize = 80;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
 pub enum RequestStatus {
     Pending,
     Fulfilled,
     Cancelled,
+    /// Words are being delivered in chunks with `AppendWords`
+    Fulfilling,
+    /// Fulfilled without the callback, which the fulfilling transaction did
+    /// not have `callback_gas_limit` compute units left for. The consumer
+    /// reads the result from the VRF result account.
+    CallbackSkipped,
+}
+
+impl RequestStatus {
+    /// Whether the request's randomness was delivered, with or without the
+    /// callback
+    pub fn is_fulfilled(&self) -> bool {
+        matches!(self, Self::Fulfilled | Self::CallbackSkipped)
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
 pub struct Subscription {
     /// The owner of this subscription
     pub owner: Pubkey,
     /// Current balance for VRF requests
     pub balance: u64,
-    /// Minimum balance required for requests
+    /// Least fee reserved from the balance for each request, see
+    /// [RandomnessRequest::reserved_fee]
     pub min_balance: u64,
     /// Number of confirmations required before generating VRF proof
     pub confirmations: u8,
     /// Nonce for request ID generation
     pub nonce: u64,
+    /// Slots after which any registered oracle may fulfill a request assigned to another oracle
+    pub backstop_timeout_slots: u64,
+    /// Bonus paid from the balance to a backstop oracle that fulfills an overdue request
+    pub backstop_bonus: u64,
+    /// Total amount ever funded
+    pub total_funded: u64,
+    /// Total amount ever credited back: reserved fees released on fulfillment,
+    /// cancellation or expiry
+    pub total_credited: u64,
+    /// Total amount ever debited, e.g. for backstop bonuses and withdrawals
+    pub total_debited: u64,
+    /// Nonce observed by the last audit
+    pub audited_nonce: u64,
+    /// Owner the subscription is being handed to, the default key if none,
+    /// see `RequestSubscriptionOwnershipTransfer`
+    pub pending_owner: Pubkey,
+    /// Lamports funded with `FundSubscriptionNative` and held in the
+    /// subscription's vault, spent once `balance` runs out, see
+    /// [SUBSCRIPTION_VAULT_SEED]
+    pub native_balance: u64,
+    /// Most requests the subscription may open in one slot, zero for no
+    /// limit. Set by the owner with `SetRateLimits`.
+    pub max_requests_per_slot: u32,
+    /// Most requests the subscription may have pending at once, zero for no
+    /// limit. Set by the owner with `SetRateLimits`.
+    pub max_pending_requests: u32,
+    /// Slot of the last request opened
+    pub last_request_slot: u64,
+    /// Requests opened in `last_request_slot`
+    pub requests_in_slot: u32,
+    /// Requests opened and not yet fulfilled, cancelled or expired
+    pub pending_requests: u32,
+    /// Requesters other than the owner that may spend from the subscription,
+    /// see `AddConsumer`. Kept last so that every other field sits at a fixed
+    /// offset, see [SubscriptionHeader]; new fields go before it and into the
+    /// header.
+    pub consumers: Vec<Pubkey>,
+}
+
+impl Subscription {
+    /// Account size without consumers, including the header
+    pub const LEN: usize = Self::space(0);
+
+    /// Account size for a subscription with `num_consumers` consumers,
+    /// including the header
+    pub const fn space(num_consumers: usize) -> usize {
+        ACCOUNT_HEADER_LEN
+            + 32                     // owner
+            + 8                      // balance
+            + 8                      // min_balance
+            + 1                      // confirmations
+            + 8                      // nonce
+            + 8                      // backstop_timeout_slots
+            + 8                      // backstop_bonus
+            + 8                      // total_funded
+            + 8                      // total_credited
+            + 8                      // total_debited
+            + 8                      // audited_nonce
+            + 32                     // pending_owner
+            + 8                      // native_balance
+            + 4                      // max_requests_per_slot
+            + 4                      // max_pending_requests
+            + 8                      // last_request_slot
+            + 4                      // requests_in_slot
+            + 4                      // pending_requests
+            + 4 + 32 * num_consumers // consumers
+    }
+
+    /// Whether `requester` may open requests against the subscription: the
+    /// owner always may
+    pub fn is_consumer(&self, requester: &Pubkey) -> bool {
+        *requester == self.owner || self.consumers.contains(requester)
+    }
+
+    /// Approves `consumer`, at most [MAXIMUM_CONSUMERS] of them
+    pub fn add_consumer(&mut self, consumer: Pubkey) -> Result<(), ProgramError> {
+        if self.is_consumer(&consumer) {
+            return Err(VrfCoordinatorError::InvalidConsumer.into());
+        }
+        if self.consumers.len() >= MAXIMUM_CONSUMERS {
+            return Err(VrfCoordinatorError::TooManyConsumers.into());
+        }
+        self.consumers.push(consumer);
+        Ok(())
+    }
+
+    /// Revokes `consumer`
+    pub fn remove_consumer(&mut self, consumer: &Pubkey) -> Result<(), ProgramError> {
+        let index = self.consumers.iter().position(|approved| approved == consumer)
+            .ok_or(VrfCoordinatorError::InvalidConsumer)?;
+        self.consumers.remove(index);
+        Ok(())
+    }
+
+    /// Native and SPL balances together, implied by the funding and
+    /// fulfillment history, or `None` if the counters are inconsistent
+    pub fn expected_balance(&self) -> Option<u64> {
+        self.total_funded
+            .checked_add(self.total_credited)?
+            .checked_sub(self.total_debited)
+    }
+}
+
+impl AccountState for Subscription {
+    const DISCRIMINATOR: [u8; 8] = *b"SUBSCRIP";
+    /// Version 2 added `native_balance`, version 3 the rate limits
+    const VERSION: u8 = 3;
+    const MIN_LEN: usize = Self::LEN;
+}
+
+/// PDA seed prefix of a subscription's vault, followed by the subscription.
+/// The vault is a system account holding the `native_balance`, and the fees
+/// charged to it until oracles claim them, on top of its rent-exempt
+/// reserve, and the owner of the token account holding the SPL `balance`
+/// and the fees charged to it, see [subscription_token_address].
+pub const SUBSCRIPTION_VAULT_SEED: &[u8] = b"subscription_vault";
+
+/// Mint of the SPL balance. Wrapped SOL, so that it counts in lamports like
+/// the native balance.
+pub const FUNDING_MINT: Pubkey = spl_token::native_mint::ID;
+
+/// Address of the vault of `subscription`, see [SUBSCRIPTION_VAULT_SEED]
+pub fn subscription_vault_address(program_id: &Pubkey, subscription: &Pubkey) -> Pubkey {
+    crate::pda::derive_subscription_vault_pda(program_id, subscription).0
+}
+
+/// Address of the token account `FundSubscription` credits `subscription`
+/// for: the associated token account of its vault for [FUNDING_MINT]
+pub fn subscription_token_address(program_id: &Pubkey, subscription: &Pubkey) -> Pubkey {
+    get_associated_token_address(&subscription_vault_address(program_id, subscription), &FUNDING_MINT)
+}
+
+/// Every field of a [Subscription] but `consumers`, read and updated in place
+/// by the request and fulfillment paths without deserializing the consumers,
+/// see [crate::loader::load_header]. Fields are packed, so copy them out
+/// rather than borrowing them.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct SubscriptionHeader {
+    pub owner: Pubkey,
+    pub balance: u64,
+    pub min_balance: u64,
+    pub confirmations: u8,
+    pub nonce: u64,
+    pub backstop_timeout_slots: u64,
+    pub backstop_bonus: u64,
+    pub total_funded: u64,
+    pub total_credited: u64,
+    pub total_debited: u64,
+    pub audited_nonce: u64,
+    pub pending_owner: Pubkey,
+    pub native_balance: u64,
+    pub max_requests_per_slot: u32,
+    pub max_pending_requests: u32,
+    pub last_request_slot: u64,
+    pub requests_in_slot: u32,
+    pub pending_requests: u32,
+}
+
+impl SubscriptionHeader {
+    /// SPL and native balances together, what requests may spend
+    pub fn available_balance(&self) -> Result<u64, ProgramError> {
+        self.balance.checked_add(self.native_balance).ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    /// Takes `amount` from the SPL balance and, once that runs out, from the
+    /// native balance, returning the part taken from the native balance
+    pub fn debit(&mut self, amount: u64) -> Result<u64, ProgramError> {
+        if self.available_balance()? < amount {
+            return Err(VrfCoordinatorError::InsufficientBalance.into());
+        }
+        let from_spl = amount.min(self.balance);
+        self.balance -= from_spl;
+        self.native_balance -= amount - from_spl;
+        Ok(amount - from_spl)
+    }
+
+    /// Gives back `amount` of a debit that took `from_native` from the native
+    /// balance: up to `from_native` to the native balance and the rest to the
+    /// SPL balance, so that neither exceeds what its vault holds
+    pub fn release(&mut self, amount: u64, from_native: u64) -> Result<(), ProgramError> {
+        let to_native = amount.min(from_native);
+        self.native_balance = self.native_balance.checked_add(to_native)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.balance = self.balance.checked_add(amount - to_native)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.total_credited = self.total_credited.checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Counts a request opened at `slot`, refusing it if the subscription
+    /// already opened `max_requests_per_slot` in that slot or has
+    /// `max_pending_requests` pending
+    pub fn record_request(&mut self, slot: u64) -> Result<(), ProgramError> {
+        if self.last_request_slot != slot {
+            self.last_request_slot = slot;
+            self.requests_in_slot = 0;
+        }
+        let max_requests_per_slot = self.max_requests_per_slot;
+        let max_pending_requests = self.max_pending_requests;
+        if (max_requests_per_slot != 0 && self.requests_in_slot >= max_requests_per_slot)
+            || (max_pending_requests != 0 && self.pending_requests >= max_pending_requests) {
+            return Err(VrfCoordinatorError::RateLimited.into());
+        }
+        self.requests_in_slot = self.requests_in_slot.saturating_add(1);
+        self.pending_requests = self.pending_requests.saturating_add(1);
+        Ok(())
+    }
+
+    /// Counts a request that is no longer pending. Requests opened before the
+    /// subscription counted them are not, so the count stops at zero.
+    pub fn record_request_closed(&mut self) {
+        self.pending_requests = self.pending_requests.saturating_sub(1);
+    }
+
+    /// Like [Subscription::is_consumer], comparing `requester` against the
+    /// consumers where they follow the header in `data`, the subscription's
+    /// account data, instead of deserializing them
+    pub fn is_consumer(&self, data: &[u8], requester: &Pubkey) -> bool {
+        if *requester == { self.owner } {
+            return true;
+        }
+        let start = ACCOUNT_HEADER_LEN + std::mem::size_of::<Self>();
+        let Some(len) = data.get(start..start + 4).and_then(|len| <[u8; 4]>::try_from(len).ok()) else {
+            return false;
+        };
+        data[start + 4..]
+            .chunks_exact(32)
+            .take(u32::from_le_bytes(len) as usize)
+            .any(|consumer| consumer == requester.as_ref())
+    }
+}
+
+impl AccountHeader for SubscriptionHeader {
+    type State = Subscription;
+}
+
+/// Fee a request in open market mode pays the oracle that fulfills it, moving
+/// linearly from `start_price` to `end_price` over `duration_slots` from the
+/// request slot and staying at `end_price` after. A declining price rewards
+/// the first oracle to fulfill; a rising one clears at the price of the
+/// cheapest oracle willing to. A zero duration means the request is not in
+/// open market mode.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Default, Pod, Zeroable)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct FeeAuction {
+    pub start_price: u64,
+    pub end_price: u64,
+    pub duration_slots: u64,
+}
+
+impl FeeAuction {
+    /// Serialized size
+    pub const LEN: usize = 8 + 8 + 8;
+
+    pub fn is_open_market(&self) -> bool {
+        self.duration_slots > 0
+    }
+
+    /// Price `elapsed_slots` after the request
+    pub fn price_at(&self, elapsed_slots: u64) -> u64 {
+        if elapsed_slots >= self.duration_slots {
+            return self.end_price;
+        }
+        let (start, end) = (self.start_price as i128, self.end_price as i128);
+        let price = start + (end - start) * elapsed_slots as i128 / self.duration_slots as i128;
+        price as u64
+    }
+}
+
+/// PDA seed prefix of direct requests, followed by the requester and the seed
+pub const DIRECT_REQUEST_SEED: &[u8] = b"direct_request";
+
+/// Deterministic ID of a request, the hash of the subscription, nonce, seed
+/// and requester it was opened with. A consumer can compute it before the
+/// request is made and match it against events and request accounts.
+#[repr(transparent)]
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Pod, Zeroable)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct RequestId(pub [u8; 32]);
+
+impl RequestId {
+    /// ID of the request `requester` opens for `seed` against `subscription`
+    /// at `nonce`. Direct requests have the default subscription and nonce 0.
+    pub fn derive(subscription: &Pubkey, nonce: u64, seed: &[u8; 32], requester: &Pubkey) -> Self {
+        Self(hashv(&[
+            b"request_id",
+            subscription.as_ref(),
+            &nonce.to_le_bytes(),
+            seed,
+            requester.as_ref(),
+        ]).to_bytes())
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
 pub struct RandomnessRequest {
-    /// The subscription this request belongs to
+    /// The subscription this request belongs to, or the default key for a
+    /// direct request
     pub subscription: Pubkey,
     /// The seed used for randomness
     pub seed: [u8; 32],
     /// The requester's program ID that will receive the callback
     pub requester: Pubkey,
-    /// The callback function data
-    pub callback_data: Vec<u8>,
-    /// Block number when request was made
+    /// Slot the request was made at, from the Clock sysvar
     pub request_block: u64,
     /// Status of the request
     pub status: RequestStatus,
@@ -51,11 +396,237 @@ pub struct RandomnessRequest {
     pub callback_gas_limit: u64,
     /// Request nonce from subscription
     pub nonce: u64,
-    /// Commitment hash of request parameters
+    /// Commitment hash of request parameters, see [Self::compute_commitment]
     pub commitment: [u8; 32],
+    /// Oracle expected to fulfill this request, or the default key if any oracle may
+    pub assigned_oracle: Pubkey,
+    /// Earliest slot the request may be fulfilled at, zero if it is not time-locked
+    pub not_before_slot: u64,
+    /// Fee cleared by the fulfilling oracle, if the request is in open market mode
+    pub fee_auction: FeeAuction,
+    /// Slot from which anyone may expire the request if it is still pending,
+    /// see `ExpireRequest`
+    pub expiration_slot: u64,
+    /// Slots that must elapse after `request_block` before the request may be
+    /// fulfilled: the requested minimum confirmations or the subscription's,
+    /// whichever is more
+    pub confirmations: u8,
+    /// Oracles whose verified outputs the randomness is derived from, see
+    /// [OracleResponses]. One for an ordinary request.
+    pub required_oracle_responses: u8,
+    /// Tip escrowed from the subscription for the fulfilling oracle, see
+    /// `RequestRandomness`
+    pub priority_fee: u64,
+    /// Deterministic ID of the request, see [Self::compute_id]
+    pub id: RequestId,
+    /// Fee reserved from the subscription when the request was opened: the
+    /// most its fulfillment may charge, and at least the subscription's
+    /// `min_balance`. Released when the request is fulfilled, against the fee
+    /// charged, or closed unfulfilled.
+    pub reserved_fee: u64,
+    /// Part of `reserved_fee` and `priority_fee` taken from the subscription's
+    /// native balance, which releasing them gives back first
+    pub reserved_native: u64,
+    /// The callback function data. Kept last so that every other field sits
+    /// at a fixed offset; new fields go before it.
+    pub callback_data: Vec<u8>,
+}
+
+impl RandomnessRequest {
+    /// Offset of `subscription` in the account data, for memcmp filters
+    pub const SUBSCRIPTION_OFFSET: usize = ACCOUNT_HEADER_LEN;
+    /// Offset of `requester` in the account data, for memcmp filters
+    pub const REQUESTER_OFFSET: usize = Self::SUBSCRIPTION_OFFSET + 32 + 32;
+    /// Offset of the one-byte `status` in the account data, for memcmp filters
+    pub const STATUS_OFFSET: usize = Self::REQUESTER_OFFSET + 32 + 8;
+    /// Offset of `assigned_oracle` in the account data, for memcmp filters
+    pub const ASSIGNED_ORACLE_OFFSET: usize = Self::STATUS_OFFSET + 1 + 4 + 8 + 8 + 32;
+
+    /// Account size for a request with `callback_len` bytes of callback data,
+    /// including the header
+    pub const fn space(callback_len: usize) -> usize {
+        ACCOUNT_HEADER_LEN
+            + 32                // subscription
+            + 32                // seed
+            + 32                // requester
+            + 8                 // request_block
+            + 1                 // status
+            + 4                 // num_words
+            + 8                 // callback_gas_limit
+            + 8                 // nonce
+            + 32                // commitment
+            + 32                // assigned_oracle
+            + 8                 // not_before_slot
+            + FeeAuction::LEN   // fee_auction
+            + 8                 // expiration_slot
+            + 1                 // confirmations
+            + 1                 // required_oracle_responses
+            + 8                 // priority_fee
+            + 32                // id
+            + 8                 // reserved_fee
+            + 8                 // reserved_native
+            + 4 + callback_len  // callback_data
+    }
+
+    /// Seed of a request for the alpha string `alpha`, which it commits to.
+    /// A consumer holding the alpha string can check it against the request
+    /// seed, so the randomness is bound to whatever context it encodes.
+    pub fn alpha_seed(alpha: &[u8]) -> Result<[u8; 32], ProgramError> {
+        if alpha.len() > MAXIMUM_ALPHA_LEN {
+            return Err(VrfCoordinatorError::AlphaTooLong.into());
+        }
+        Ok(hashv(&[b"alpha", alpha]).to_bytes())
+    }
+
+    /// Expiration slot of a request opened at `request_block`, counted from
+    /// `not_before_slot` for a time-locked request
+    pub fn expiration(request_block: u64, not_before_slot: u64) -> u64 {
+        request_block.max(not_before_slot).saturating_add(REQUEST_EXPIRATION_SLOTS)
+    }
+
+    /// Checks that a request may ask for `num_words` words, at least one and
+    /// at most [MAXIMUM_RANDOM_WORDS]
+    pub fn check_num_words(num_words: u32) -> Result<(), ProgramError> {
+        if num_words == 0 || num_words > MAXIMUM_RANDOM_WORDS {
+            return Err(VrfCoordinatorError::InvalidNumberOfWords.into());
+        }
+        Ok(())
+    }
+
+    /// Checks that a request may leave its callback `callback_gas_limit`
+    /// compute units, within [MINIMUM_CALLBACK_GAS_LIMIT] and
+    /// [MAXIMUM_CALLBACK_GAS_LIMIT]
+    pub fn check_callback_gas_limit(callback_gas_limit: u64) -> Result<(), ProgramError> {
+        if !(MINIMUM_CALLBACK_GAS_LIMIT..=MAXIMUM_CALLBACK_GAS_LIMIT).contains(&callback_gas_limit) {
+            return Err(VrfCoordinatorError::InvalidCallbackGasLimit.into());
+        }
+        Ok(())
+    }
+
+    /// [RequestId] of the request, once its nonce is known
+    pub fn compute_id(&self) -> RequestId {
+        RequestId::derive(&self.subscription, self.nonce, &self.seed, &self.requester)
+    }
+
+    /// Hash of the parameters the request was opened with: its seed, nonce,
+    /// requester, subscription, confirmations, callback, fee auction and the
+    /// oracles that may fulfill it. Stored as `commitment` once the request is
+    /// assigned, and checked again before the request is fulfilled.
+    pub fn compute_commitment(&self) -> [u8; 32] {
+        hashv(&[
+            b"commitment",
+            &self.seed,
+            &self.nonce.to_le_bytes(),
+            self.requester.as_ref(),
+            self.subscription.as_ref(),
+            &self.num_words.to_le_bytes(),
+            &[self.confirmations],
+            &self.callback_gas_limit.to_le_bytes(),
+            &self.callback_data,
+            &self.fee_auction.start_price.to_le_bytes(),
+            &self.fee_auction.end_price.to_le_bytes(),
+            &self.fee_auction.duration_slots.to_le_bytes(),
+            self.assigned_oracle.as_ref(),
+            &[self.required_oracle_responses],
+        ]).to_bytes()
+    }
+
+    /// Whether the request needs responses from several oracles, which
+    /// answer with `SubmitOracleResponse` instead of `FulfillRandomness`
+    pub fn is_threshold(&self) -> bool {
+        self.required_oracle_responses > 1
+    }
+
+    /// Checks that the request still matches its commitment
+    pub fn check_commitment(&self) -> Result<(), ProgramError> {
+        if self.compute_commitment() != self.commitment {
+            return Err(VrfCoordinatorError::InvalidCommitment.into());
+        }
+        Ok(())
+    }
+
+    /// Whether the requester paid the fee up front instead of a subscription,
+    /// see `RequestRandomnessDirect`
+    pub fn is_direct(&self) -> bool {
+        self.subscription == Pubkey::default()
+    }
+}
+
+impl AccountState for RandomnessRequest {
+    const DISCRIMINATOR: [u8; 8] = *b"REQUEST\0";
+    /// Version 2 added `priority_fee`, version 3 `id`, version 4 the fee
+    /// reservation
+    const VERSION: u8 = 4;
+    const MIN_LEN: usize = Self::space(0);
+}
+
+/// Every field of a [RandomnessRequest] but `callback_data`, for the paths
+/// that read or update a request without its callback data, see
+/// [crate::loader::load_header]. `status` is the borsh variant index of the
+/// [RequestStatus]. Fields are packed, so copy them out rather than
+/// borrowing them.
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct RandomnessRequestHeader {
+    pub subscription: Pubkey,
+    pub seed: [u8; 32],
+    pub requester: Pubkey,
+    pub request_block: u64,
+    pub status: u8,
+    pub num_words: u32,
+    pub callback_gas_limit: u64,
+    pub nonce: u64,
+    pub commitment: [u8; 32],
+    pub assigned_oracle: Pubkey,
+    pub not_before_slot: u64,
+    pub fee_auction: FeeAuction,
+    pub expiration_slot: u64,
+    pub confirmations: u8,
+    pub required_oracle_responses: u8,
+    pub priority_fee: u64,
+    pub id: RequestId,
+    pub reserved_fee: u64,
+    pub reserved_native: u64,
+}
+
+impl RandomnessRequestHeader {
+    pub fn status(&self) -> Result<RequestStatus, ProgramError> {
+        RequestStatus::try_from_slice(&[self.status]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl From<&RandomnessRequest> for RandomnessRequestHeader {
+    fn from(request: &RandomnessRequest) -> Self {
+        Self {
+            subscription: request.subscription,
+            seed: request.seed,
+            requester: request.requester,
+            request_block: request.request_block,
+            status: request.status as u8,
+            num_words: request.num_words,
+            callback_gas_limit: request.callback_gas_limit,
+            nonce: request.nonce,
+            commitment: request.commitment,
+            assigned_oracle: request.assigned_oracle,
+            not_before_slot: request.not_before_slot,
+            fee_auction: request.fee_auction,
+            expiration_slot: request.expiration_slot,
+            confirmations: request.confirmations,
+            required_oracle_responses: request.required_oracle_responses,
+            priority_fee: request.priority_fee,
+            id: request.id,
+            reserved_fee: request.reserved_fee,
+            reserved_native: request.reserved_native,
+        }
+    }
+}
+
+impl AccountHeader for RandomnessRequestHeader {
+    type State = RandomnessRequest;
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
 pub struct VrfResult {
     /// The randomness outputs
     pub randomness: Vec<[u8; 64]>,
@@ -63,9 +634,37 @@ pub struct VrfResult {
     pub proof: Vec<u8>,
     /// Block number when proof was generated
     pub proof_block: u64,
+    /// The request these words fulfill
+    pub request: Pubkey,
+}
+
+impl VrfResult {
+    /// Account size for a result with `num_words` randomness values and a
+    /// [PROOF_LEN]-byte proof, including the header
+    pub const fn space(num_words: usize) -> usize {
+        ACCOUNT_HEADER_LEN
+            + 4 + 64 * num_words  // randomness
+            + 4 + PROOF_LEN       // proof
+            + 8                   // proof_block
+            + 32                  // request
+    }
+
+    /// Whether these words fulfill `request`. Results are keyed by request,
+    /// but those left at the result account a requester's requests shared
+    /// before may hold the words of any of them, see
+    /// [crate::pda::derive_legacy_vrf_result_pda].
+    pub fn fulfills(&self, request: &Pubkey) -> bool {
+        self.request == *request
+    }
+}
+
+impl AccountState for VrfResult {
+    const DISCRIMINATOR: [u8; 8] = *b"VRFRSLT\0";
+    const MIN_LEN: usize = Self::space(0) - PROOF_LEN;
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
 pub struct OracleConfig {
     /// The oracle's public key
     pub oracle_key: Pubkey,
@@ -73,4 +672,1211 @@ pub struct OracleConfig {
     pub vrf_key: [u8; 32],
     /// Whether the oracle is active
     pub is_active: bool,
-} 
\ No newline at end of file
+    /// Fees and backstop bonuses credited to this oracle and not yet claimed
+    /// from the subscription vaults
+    pub accrued_fees: u64,
+    /// Requests this oracle fulfilled
+    pub fulfillments: u64,
+    /// Sum of the latencies of those fulfillments, in slots
+    pub total_latency_slots: u64,
+    /// Requests assigned to this oracle that a backstop oracle took over
+    pub failures: u64,
+}
+
+impl OracleConfig {
+    /// PDA seed prefix of an oracle config, followed by the oracle key. The
+    /// coordinator only accepts an oracle's config at this address, so it is
+    /// the registry of oracles and their VRF keys.
+    pub const SEED: &'static [u8] = b"oracle_config";
+
+    /// Account size, including the header
+    pub const LEN: usize = ACCOUNT_HEADER_LEN
+        + 32 // oracle_key
+        + 32 // vrf_key
+        + 1  // is_active
+        + 8  // accrued_fees
+        + 8  // fulfillments
+        + 8  // total_latency_slots
+        + 8; // failures
+
+    /// Counts a fulfillment `latency` slots after its request
+    pub fn record_fulfillment(&mut self, latency: u64) -> Result<(), ProgramError> {
+        self.fulfillments = self.fulfillments.checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.total_latency_slots = self.total_latency_slots.checked_add(latency)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Counts an assigned request the oracle missed
+    pub fn record_failure(&mut self) -> Result<(), ProgramError> {
+        self.failures = self.failures.checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Average latency of the oracle's fulfillments in slots, or zero before the first
+    pub fn average_latency(&self) -> u64 {
+        self.total_latency_slots.checked_div(self.fulfillments).unwrap_or(0)
+    }
+
+    /// Reputation out of [MAXIMUM_REPUTATION]: the share of its assigned
+    /// requests the oracle did not miss, scaled down by how far its average
+    /// latency exceeds [REPUTATION_LATENCY_TARGET_SLOTS]. An oracle without
+    /// history has the full score.
+    pub fn reputation(&self) -> u64 {
+        let attempts = self.fulfillments as u128 + self.failures as u128;
+        if attempts == 0 {
+            return MAXIMUM_REPUTATION;
+        }
+        let reliability = MAXIMUM_REPUTATION as u128 * self.fulfillments as u128 / attempts;
+        let latency = self.average_latency().max(REPUTATION_LATENCY_TARGET_SLOTS) as u128;
+        (reliability * REPUTATION_LATENCY_TARGET_SLOTS as u128 / latency) as u64
+    }
+
+    /// Slots past the backstop timeout the oracle waits before taking over a
+    /// request assigned to another: none at the full reputation, up to another
+    /// `timeout_slots` at zero, so that better oracles get the first chance
+    pub fn backstop_delay(&self, timeout_slots: u64) -> u64 {
+        let shortfall = MAXIMUM_REPUTATION - self.reputation();
+        (timeout_slots as u128 * shortfall as u128 / MAXIMUM_REPUTATION as u128) as u64
+    }
+}
+
+impl AccountState for OracleConfig {
+    const DISCRIMINATOR: [u8; 8] = *b"ORACLECF";
+    const MIN_LEN: usize = Self::LEN;
+}
+
+/// Fees charged for a request, in subscription balance units
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct FeeSchedule {
+    /// Flat fee per request
+    pub base_fee: u64,
+    /// Fee per random word
+    pub fee_per_word: u64,
+    /// Fee per million compute units of callback gas limit
+    pub fee_per_million_callback_units: u64,
+    /// Fee per priority level
+    pub priority_fee: u64,
+}
+
+impl FeeSchedule {
+    /// Serialized size
+    pub const LEN: usize = 8 // base_fee
+        + 8                  // fee_per_word
+        + 8                  // fee_per_million_callback_units
+        + 8;                 // priority_fee
+
+    /// Total fee for a request. The callback component is rounded up.
+    pub fn quote(&self, num_words: u32, callback_gas_limit: u64, priority: u8) -> Result<u64, ProgramError> {
+        RandomnessRequest::check_num_words(num_words)?;
+        RandomnessRequest::check_callback_gas_limit(callback_gas_limit)?;
+        let callback_fee = self.callback_fee(callback_gas_limit)?;
+        let fee = || {
+            self.base_fee
+                .checked_add(self.fee_per_word.checked_mul(num_words as u64)?)?
+                .checked_add(callback_fee)?
+                .checked_add(self.priority_fee.checked_mul(priority as u64)?)
+        };
+        fee().ok_or(ProgramError::ArithmeticOverflow)
+    }
+
+    /// Part of a quote for `callback_gas_limit` that a callback using only
+    /// `units_used` compute units did not need
+    pub fn callback_rebate(&self, callback_gas_limit: u64, units_used: u64) -> Result<u64, ProgramError> {
+        let unused = self.callback_fee(callback_gas_limit)? - self.callback_fee(units_used.min(callback_gas_limit))?;
+        Ok(unused)
+    }
+
+    /// Fee for `units` compute units of callback, rounded up
+    fn callback_fee(&self, units: u64) -> Result<u64, ProgramError> {
+        let fee = (units as u128 * self.fee_per_million_callback_units as u128).div_ceil(1_000_000);
+        u64::try_from(fee).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+}
+
+/// Global coordinator settings, stored at the [CoordinatorConfig::SEED] PDA
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct CoordinatorConfig {
+    /// Authority allowed to change the configuration
+    pub admin: Pubkey,
+    /// Fees charged for requests
+    pub fee_schedule: FeeSchedule,
+    /// Bridge program attestations are posted to, or the default key if none
+    pub bridge_program: Pubkey,
+    /// Fewest confirmations a request may ask for
+    pub min_confirmations: u8,
+    /// Most confirmations a request may ask for
+    pub max_confirmations: u8,
+    /// Whether new requests are refused
+    pub paused: bool,
+    /// Whether events are emitted by self-invocation rather than logged, see
+    /// [crate::event]. Requests and fulfillments passing the config are then
+    /// refused without the event accounts.
+    pub cpi_events: bool,
+    /// Smallest nonzero `priority_fee` a request may tip
+    pub min_priority_fee: u64,
+    /// Group key of the drand evmnet chain rounds are verified against, all
+    /// zeros until the admin sets it
+    pub drand_public_key: [u8; DRAND_PUBLIC_KEY_LEN],
+    /// Oracles requests opened without an assigned oracle are spread across,
+    /// see [Self::rotation_oracle]. Kept last, as it grows.
+    pub oracle_rotation: Vec<Pubkey>,
+}
+
+impl CoordinatorConfig {
+    /// PDA seed of the config account
+    pub const SEED: &'static [u8] = b"coordinator_config";
+
+    /// Account size with an empty oracle rotation, including the header
+    pub const LEN: usize = Self::space(0);
+
+    /// Account size with `num_oracles` oracles in the rotation, including the
+    /// discriminator
+    pub const fn space(num_oracles: usize) -> usize {
+        ACCOUNT_HEADER_LEN
+            + 32                    // admin
+            + FeeSchedule::LEN      // fee_schedule
+            + 32                    // bridge_program
+            + 1                     // min_confirmations
+            + 1                     // max_confirmations
+            + 1                     // paused
+            + 1                     // cpi_events
+            + 8                     // min_priority_fee
+            + DRAND_PUBLIC_KEY_LEN  // drand_public_key
+            + 4 + 32 * num_oracles  // oracle_rotation
+    }
+
+    /// Checks that new requests are accepted
+    pub fn check_not_paused(&self) -> Result<(), ProgramError> {
+        if self.paused {
+            return Err(VrfCoordinatorError::ProgramPaused.into());
+        }
+        Ok(())
+    }
+
+    /// Checks that a request may ask for `confirmations`
+    pub fn check_confirmations(&self, confirmations: u8) -> Result<(), ProgramError> {
+        if !(self.min_confirmations..=self.max_confirmations).contains(&confirmations) {
+            return Err(VrfCoordinatorError::InvalidRequestConfirmations.into());
+        }
+        Ok(())
+    }
+
+    /// Replaces the oracle rotation with `oracles`, at most
+    /// [MAXIMUM_ROTATION_ORACLES] distinct ones
+    pub fn set_oracle_rotation(&mut self, oracles: Vec<Pubkey>) -> Result<(), ProgramError> {
+        if oracles.len() > MAXIMUM_ROTATION_ORACLES {
+            return Err(VrfCoordinatorError::TooManyOracles.into());
+        }
+        if oracles.iter().enumerate().any(|(i, oracle)| oracles[..i].contains(oracle)) {
+            return Err(VrfCoordinatorError::InvalidOracle.into());
+        }
+        self.oracle_rotation = oracles;
+        Ok(())
+    }
+
+    /// Oracle a request without an assigned oracle is assigned to: the one
+    /// at the hash of its address modulo the rotation size, or the default key
+    /// if the rotation is empty and any oracle may fulfill it
+    pub fn rotation_oracle(&self, request: &Pubkey) -> Pubkey {
+        if self.oracle_rotation.is_empty() {
+            return Pubkey::default();
+        }
+        let hash = hashv(&[b"rotation", request.as_ref()]).to_bytes();
+        let index = u64::from_le_bytes(hash[..8].try_into().unwrap()) % self.oracle_rotation.len() as u64;
+        self.oracle_rotation[index as usize]
+    }
+}
+
+impl AccountState for CoordinatorConfig {
+    const DISCRIMINATOR: [u8; 8] = *b"COORDCFG";
+    /// Version 2 added `cpi_events`, version 3 `min_priority_fee`, version 4
+    /// `drand_public_key`
+    const VERSION: u8 = 4;
+    const MIN_LEN: usize = Self::LEN;
+}
+
+/// Length of a drand evmnet group key, an uncompressed BN254 G2 point
+pub const DRAND_PUBLIC_KEY_LEN: usize = 128;
+
+/// Length of a drand evmnet signature, an uncompressed BN254 G1 point
+pub const DRAND_SIGNATURE_LEN: usize = 64;
+
+/// PDA seed prefix of a drand round, followed by the little-endian round.
+/// Rounds of the unverified quicknet relay sit at `["drand_round", round]`.
+pub const DRAND_ROUND_SEED: &[u8] = b"drand_evmnet_round";
+
+/// A drand evmnet beacon round relayed by an oracle, stored at the
+/// [DRAND_ROUND_SEED] PDA
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct DrandRound {
+    /// The round number
+    pub round: u64,
+    /// BLS signature of the round under the config's `drand_public_key`,
+    /// verified when published, see [crate::drand]
+    pub signature: [u8; DRAND_SIGNATURE_LEN],
+    /// The round's randomness, `sha256(signature)`
+    pub randomness: [u8; 32],
+    /// Oracle that relayed the round
+    pub oracle: Pubkey,
+    /// Slot the round was relayed at
+    pub published_slot: u64,
+}
+
+impl DrandRound {
+    /// Account size, including the header
+    pub const LEN: usize = ACCOUNT_HEADER_LEN
+        + 8                   // round
+        + DRAND_SIGNATURE_LEN // signature
+        + 32                  // randomness
+        + 32                  // oracle
+        + 8;                  // published_slot
+}
+
+impl AccountState for DrandRound {
+    const DISCRIMINATOR: [u8; 8] = *b"DRANDRND";
+    /// Version 2 holds verified evmnet rounds. Version 1 held quicknet rounds
+    /// whose signatures were never checked, and is not migrated.
+    const VERSION: u8 = 2;
+    const MIN_LEN: usize = Self::LEN;
+}
+
+/// Number of rounds a beacon stream keeps
+pub const BEACON_STREAM_CAPACITY: usize = 16;
+
+/// A drand round as written into a beacon stream
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct BeaconEntry {
+    /// The round number
+    pub round: u64,
+    /// The round's randomness, as in its [DrandRound]
+    pub randomness: [u8; 32],
+    /// Slot the round was relayed at
+    pub published_slot: u64,
+}
+
+impl BeaconEntry {
+    /// Serialized size
+    pub const LEN: usize = 8 + 32 + 8;
+}
+
+/// Standing subscription of its owner to the drand beacon, stored at the
+/// `["beacon_stream", owner]` PDA. Every `PublishDrandRound` passed the
+/// stream writes the round into its ring of the last
+/// [BEACON_STREAM_CAPACITY] rounds and, if it has a callback program, invokes
+/// it, so a consumer reading the beacon every round opens no requests.
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct BeaconStream {
+    /// Owner of the stream, who receives its rent when it is closed
+    pub owner: Pubkey,
+    /// Program invoked with each round, the default pubkey for none
+    pub callback_program: Pubkey,
+    /// Account of the callback program passed writable to the callback
+    pub callback_account: Pubkey,
+    /// Compute units each callback needs; the callback is skipped when fewer are left
+    pub callback_gas_limit: u64,
+    /// Number of rounds written so far, the next entry being at this index
+    /// modulo [BEACON_STREAM_CAPACITY]
+    pub rounds_written: u64,
+    /// The last rounds written
+    pub entries: [BeaconEntry; BEACON_STREAM_CAPACITY],
+    /// The callback instruction data
+    pub callback_data: Vec<u8>,
+}
+
+impl BeaconStream {
+    /// PDA seed prefix of a beacon stream
+    pub const SEED: &'static [u8] = b"beacon_stream";
+
+    /// Account size for a stream with `callback_len` bytes of callback data,
+    /// including the header
+    pub const fn space(callback_len: usize) -> usize {
+        ACCOUNT_HEADER_LEN
+            + 32                                        // owner
+            + 32                                        // callback_program
+            + 32                                        // callback_account
+            + 8                                         // callback_gas_limit
+            + 8                                         // rounds_written
+            + BeaconEntry::LEN * BEACON_STREAM_CAPACITY // entries
+            + 4 + callback_len                          // callback_data
+    }
+
+    /// Whether the stream invokes a program with each round
+    pub fn has_callback(&self) -> bool {
+        self.callback_program != Pubkey::default()
+    }
+
+    /// The last round written, if any
+    pub fn latest(&self) -> Option<&BeaconEntry> {
+        let index = self.rounds_written.checked_sub(1)? as usize % BEACON_STREAM_CAPACITY;
+        Some(&self.entries[index])
+    }
+
+    /// Writes `entry` over the oldest round. Rounds must be written in
+    /// increasing order, so the ring never holds a round twice.
+    pub fn push(&mut self, entry: BeaconEntry) -> Result<(), ProgramError> {
+        if self.latest().is_some_and(|latest| latest.round >= entry.round) {
+            return Err(VrfCoordinatorError::StaleBeaconRound.into());
+        }
+        self.entries[self.rounds_written as usize % BEACON_STREAM_CAPACITY] = entry;
+        self.rounds_written = self.rounds_written.checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+impl AccountState for BeaconStream {
+    const DISCRIMINATOR: [u8; 8] = *b"BEACONST";
+    const MIN_LEN: usize = Self::space(0);
+}
+
+/// Recurring request that `CrankSchedule` opens every `interval_slots`,
+/// stored at the `["schedule", owner, seed]` PDA
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct Schedule {
+    /// Requester of the scheduled requests, who receives their callbacks
+    pub owner: Pubkey,
+    /// Subscription the requests are opened under
+    pub subscription: Pubkey,
+    /// Seed the seed of each request is derived from
+    pub seed: [u8; 32],
+    /// Slots between two requests
+    pub interval_slots: u64,
+    /// Slot from which the next request may be opened
+    pub next_slot: u64,
+    /// Number of requests opened so far
+    pub requests_opened: u64,
+    /// Number of random words per request
+    pub num_words: u32,
+    /// Maximum compute units for each callback
+    pub callback_gas_limit: u64,
+    /// The callback function data
+    pub callback_data: Vec<u8>,
+}
+
+impl Schedule {
+    /// PDA seed prefix of a schedule
+    pub const SEED: &'static [u8] = b"schedule";
+
+    /// Account size for a schedule with `callback_len` bytes of callback data,
+    /// including the header
+    pub const fn space(callback_len: usize) -> usize {
+        ACCOUNT_HEADER_LEN
+            + 32                // owner
+            + 32                // subscription
+            + 32                // seed
+            + 8                 // interval_slots
+            + 8                 // next_slot
+            + 8                 // requests_opened
+            + 4                 // num_words
+            + 8                 // callback_gas_limit
+            + 4 + callback_len  // callback_data
+    }
+
+    /// Seed of the next request, unique to this schedule and request
+    pub fn request_seed(&self) -> [u8; 32] {
+        hashv(&[&self.seed, &self.requests_opened.to_le_bytes()]).to_bytes()
+    }
+
+    /// Moves `next_slot` on by one interval after a request is opened at
+    /// `slot`. A schedule cranked late skips the intervals it missed rather
+    /// than opening them all at once.
+    pub fn advance(&mut self, slot: u64) -> Result<(), ProgramError> {
+        self.requests_opened = self.requests_opened.checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.next_slot = self.next_slot.checked_add(self.interval_slots)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if self.next_slot <= slot {
+            self.next_slot = slot.checked_add(self.interval_slots)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+}
+
+impl AccountState for Schedule {
+    const DISCRIMINATOR: [u8; 8] = *b"SCHEDULE";
+    const MIN_LEN: usize = Self::space(0);
+}
+
+/// Number of recent fulfillments the rolling latency average is taken over
+pub const LATENCY_WINDOW: usize = 32;
+
+/// Number of oracles whose fulfillments are counted individually
+pub const MAXIMUM_TRACKED_ORACLES: usize = 64;
+
+/// Fulfillments by one oracle
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct OracleFulfillments {
+    pub oracle: Pubkey,
+    pub fulfillments: u64,
+}
+
+/// Running totals of the coordinator, stored at the [CoordinatorStats::SEED]
+/// PDA, so dashboards can read one account instead of replaying history
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct CoordinatorStats {
+    /// Requests opened
+    pub total_requests: u64,
+    /// Requests fulfilled
+    pub total_fulfillments: u64,
+    /// Requests cancelled before they were fulfilled
+    pub total_failures: u64,
+    /// Fees paid to oracles, including backstop bonuses
+    pub total_fees: u64,
+    /// Latencies of the most recent fulfillments in slots, indexed by
+    /// fulfillment count modulo [LATENCY_WINDOW]
+    pub recent_latencies: [u64; LATENCY_WINDOW],
+    /// Fulfillments per oracle, for the first [MAXIMUM_TRACKED_ORACLES] oracles to fulfill
+    pub oracle_fulfillments: Vec<OracleFulfillments>,
+}
+
+impl CoordinatorStats {
+    /// PDA seed of the stats account
+    pub const SEED: &'static [u8] = b"coordinator_stats";
+
+    /// Account size, including the header
+    pub const LEN: usize = ACCOUNT_HEADER_LEN
+        + 8                                     // total_requests
+        + 8                                     // total_fulfillments
+        + 8                                     // total_failures
+        + 8                                     // total_fees
+        + 8 * LATENCY_WINDOW                    // recent_latencies
+        + 4 + (32 + 8) * MAXIMUM_TRACKED_ORACLES; // oracle_fulfillments
+
+    /// Counts a request opened
+    pub fn record_request(&mut self) -> Result<(), ProgramError> {
+        self.total_requests = self.total_requests.checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Counts a request cancelled before it was fulfilled
+    pub fn record_failure(&mut self) -> Result<(), ProgramError> {
+        self.total_failures = self.total_failures.checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Counts a fulfillment by `oracle` `latency` slots after its request,
+    /// which paid it `fee`
+    pub fn record_fulfillment(&mut self, oracle: &Pubkey, latency: u64, fee: u64) -> Result<(), ProgramError> {
+        self.recent_latencies[(self.total_fulfillments % LATENCY_WINDOW as u64) as usize] = latency;
+        self.total_fulfillments = self.total_fulfillments.checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.total_fees = self.total_fees.checked_add(fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        let tracked = self.oracle_fulfillments.len();
+        match self.oracle_fulfillments.iter_mut().find(|entry| entry.oracle == *oracle) {
+            Some(entry) => {
+                entry.fulfillments = entry.fulfillments.checked_add(1)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+            }
+            None if tracked < MAXIMUM_TRACKED_ORACLES => {
+                self.oracle_fulfillments.push(OracleFulfillments { oracle: *oracle, fulfillments: 1 });
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Average latency in slots of the last [LATENCY_WINDOW] fulfillments, or
+    /// zero before the first
+    pub fn average_latency(&self) -> u64 {
+        let count = self.total_fulfillments.min(LATENCY_WINDOW as u64);
+        if count == 0 {
+            return 0;
+        }
+        let total: u128 = self.recent_latencies[..count as usize].iter().map(|&latency| latency as u128).sum();
+        (total / count as u128) as u64
+    }
+}
+
+impl AccountState for CoordinatorStats {
+    const DISCRIMINATOR: [u8; 8] = *b"COORDSTS";
+    const MIN_LEN: usize = Self::LEN;
+}
+
+/// Number of pending requests a request pool lists
+pub const MAXIMUM_POOLED_REQUESTS: usize = 64;
+
+/// Pending request as listed in a [RequestPool]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct PooledRequest {
+    pub request: Pubkey,
+    pub seed: [u8; 32],
+}
+
+/// Pending requests of a subscription, stored at the PDA of
+/// [RequestPool::SEED] and the subscription, so oracles can find their work
+/// by reading one account instead of scanning program accounts or logs
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct RequestPool {
+    pub subscription: Pubkey,
+    /// Requests opened while the pool was full, which it does not list. An
+    /// oracle seeing this grow falls back to scanning for pending requests.
+    pub unpooled: u64,
+    /// Pending requests, oldest first
+    pub requests: Vec<PooledRequest>,
+}
+
+impl RequestPool {
+    /// PDA seed prefix of a request pool, followed by the subscription
+    pub const SEED: &'static [u8] = b"request_pool";
+
+    /// Account size, including the header
+    pub const LEN: usize = ACCOUNT_HEADER_LEN
+        + 32                                       // subscription
+        + 8                                        // unpooled
+        + 4 + (32 + 32) * MAXIMUM_POOLED_REQUESTS; // requests
+
+    pub fn new(subscription: Pubkey) -> Self {
+        Self { subscription, unpooled: 0, requests: Vec::new() }
+    }
+
+    /// Lists a request just opened, unless the pool is full
+    pub fn push(&mut self, request: PooledRequest) {
+        if self.requests.len() < MAXIMUM_POOLED_REQUESTS {
+            self.requests.push(request);
+        } else {
+            self.unpooled = self.unpooled.saturating_add(1);
+        }
+    }
+
+    /// Drops a request that is no longer pending
+    pub fn remove(&mut self, request: &Pubkey) {
+        self.requests.retain(|pooled| pooled.request != *request);
+    }
+}
+
+impl AccountState for RequestPool {
+    const DISCRIMINATOR: [u8; 8] = *b"REQPOOL\0";
+    const MIN_LEN: usize = Self::LEN;
+}
+
+/// Verified VRF output of one oracle answering a threshold request
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct OracleResponse {
+    pub oracle: Pubkey,
+    pub output: [u8; 64],
+}
+
+/// Responses collected for a request needing several oracles, stored at the
+/// PDA of [OracleResponses::SEED] and the request. Once
+/// `required_oracle_responses` distinct oracles have answered, the request is
+/// fulfilled with the hash of all their outputs, so no single oracle key
+/// decides the randomness.
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct OracleResponses {
+    pub request: Pubkey,
+    pub responses: Vec<OracleResponse>,
+}
+
+impl OracleResponses {
+    /// PDA seed prefix, followed by the request
+    pub const SEED: &'static [u8] = b"oracle_responses";
+
+    /// Account size for `required` responses, including the header
+    pub const fn space(required: u8) -> usize {
+        ACCOUNT_HEADER_LEN
+            + 32                                  // request
+            + 4 + (32 + 64) * required as usize   // responses
+    }
+
+    /// Records `oracle`'s output, once per oracle
+    pub fn add(&mut self, oracle: Pubkey, output: [u8; 64]) -> Result<(), ProgramError> {
+        if self.responses.iter().any(|response| response.oracle == oracle) {
+            return Err(VrfCoordinatorError::DuplicateOracleResponse.into());
+        }
+        self.responses.push(OracleResponse { oracle, output });
+        Ok(())
+    }
+
+    /// Output the request is fulfilled with, derived from every response in
+    /// the order they arrived
+    pub fn aggregate_output(&self) -> [u8; 64] {
+        let mut output = [0u8; 64];
+        for (half, chunk) in output.chunks_exact_mut(32).enumerate() {
+            let domain = [half as u8];
+            let mut parts: Vec<&[u8]> = vec![b"threshold", &domain];
+            parts.extend(self.responses.iter().map(|response| response.output.as_ref()));
+            chunk.copy_from_slice(&hashv(&parts).to_bytes());
+        }
+        output
+    }
+}
+
+impl AccountState for OracleResponses {
+    const DISCRIMINATOR: [u8; 8] = *b"ORCLRESP";
+    const MIN_LEN: usize = Self::space(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_space_matches_serialized_size() {
+        let subscription = Subscription {
+            owner: Pubkey::new_unique(),
+            balance: 1,
+            min_balance: 2,
+            confirmations: 3,
+            nonce: 4,
+            backstop_timeout_slots: DEFAULT_BACKSTOP_TIMEOUT_SLOTS,
+            backstop_bonus: 5,
+            total_funded: 6,
+            total_credited: 7,
+            total_debited: 8,
+            audited_nonce: 4,
+            pending_owner: Pubkey::new_unique(),
+            native_balance: 9,
+            max_requests_per_slot: 0,
+            max_pending_requests: 0,
+            last_request_slot: 0,
+            requests_in_slot: 0,
+            pending_requests: 0,
+            consumers: vec![Pubkey::new_unique(); 2],
+        };
+        assert_eq!(ACCOUNT_HEADER_LEN + borsh::to_vec(&subscription).unwrap().len(), Subscription::space(2));
+        // The header is every field before the consumers' length prefix
+        assert_eq!(ACCOUNT_HEADER_LEN + std::mem::size_of::<SubscriptionHeader>() + 4, Subscription::LEN);
+        assert_eq!(ACCOUNT_HEADER_LEN + std::mem::size_of::<RandomnessRequestHeader>() + 4, RandomnessRequest::space(0));
+
+        for callback_len in [0, 1, 37] {
+            let request = RandomnessRequest {
+                subscription: Pubkey::new_unique(),
+                seed: [1; 32],
+                requester: Pubkey::new_unique(),
+                callback_data: vec![7; callback_len],
+                request_block: 8,
+                status: RequestStatus::Pending,
+                num_words: 1,
+                callback_gas_limit: MINIMUM_CALLBACK_GAS_LIMIT,
+                nonce: 9,
+                commitment: [2; 32],
+                assigned_oracle: Pubkey::new_unique(),
+                not_before_slot: 10,
+                fee_auction: FeeAuction { start_price: 11, end_price: 12, duration_slots: 13 },
+                expiration_slot: 14,
+                confirmations: 0,
+                required_oracle_responses: 1,
+                priority_fee: 0,
+                reserved_fee: 0,
+                reserved_native: 0,
+                id: RequestId::default(),
+            };
+            assert_eq!(
+                ACCOUNT_HEADER_LEN + borsh::to_vec(&request).unwrap().len(),
+                RandomnessRequest::space(callback_len)
+            );
+            // The header is every field before the callback data's length prefix
+            assert_eq!(
+                bytemuck::bytes_of(&RandomnessRequestHeader::from(&request)),
+                &borsh::to_vec(&request).unwrap()[..std::mem::size_of::<RandomnessRequestHeader>()]
+            );
+        }
+
+        for num_words in [1, 3] {
+            let result = VrfResult {
+                randomness: vec![[3; 64]; num_words],
+                proof: vec![4; PROOF_LEN],
+                proof_block: 10,
+                request: Pubkey::new_unique(),
+            };
+            assert_eq!(
+                ACCOUNT_HEADER_LEN + borsh::to_vec(&result).unwrap().len(),
+                VrfResult::space(num_words)
+            );
+        }
+
+        let oracle_config = OracleConfig {
+            oracle_key: Pubkey::new_unique(),
+            vrf_key: [5; 32],
+            is_active: true,
+            accrued_fees: 11,
+            fulfillments: 12,
+            total_latency_slots: 13,
+            failures: 14,
+        };
+        assert_eq!(ACCOUNT_HEADER_LEN + borsh::to_vec(&oracle_config).unwrap().len(), OracleConfig::LEN);
+
+        let config = CoordinatorConfig {
+            admin: Pubkey::new_unique(),
+            fee_schedule: FeeSchedule::default(),
+            bridge_program: Pubkey::new_unique(),
+            min_confirmations: 1,
+            max_confirmations: 2,
+            paused: true,
+            cpi_events: true,
+            min_priority_fee: 5,
+            drand_public_key: [4; DRAND_PUBLIC_KEY_LEN],
+            oracle_rotation: vec![Pubkey::new_unique()],
+        };
+        assert_eq!(ACCOUNT_HEADER_LEN + borsh::to_vec(&config).unwrap().len(), CoordinatorConfig::space(1));
+
+        let drand_round = DrandRound {
+            round: 12,
+            signature: [6; DRAND_SIGNATURE_LEN],
+            randomness: [7; 32],
+            oracle: Pubkey::new_unique(),
+            published_slot: 13,
+        };
+        assert_eq!(ACCOUNT_HEADER_LEN + borsh::to_vec(&drand_round).unwrap().len(), DrandRound::LEN);
+
+        for callback_len in [0, 5] {
+            let schedule = schedule(vec![8; callback_len]);
+            assert_eq!(
+                ACCOUNT_HEADER_LEN + borsh::to_vec(&schedule).unwrap().len(),
+                Schedule::space(callback_len)
+            );
+        }
+
+        for callback_len in [0, 5] {
+            let stream = beacon_stream(vec![8; callback_len]);
+            assert_eq!(
+                ACCOUNT_HEADER_LEN + borsh::to_vec(&stream).unwrap().len(),
+                BeaconStream::space(callback_len)
+            );
+        }
+    }
+
+    fn schedule(callback_data: Vec<u8>) -> Schedule {
+        Schedule {
+            owner: Pubkey::new_unique(),
+            subscription: Pubkey::new_unique(),
+            seed: [1; 32],
+            interval_slots: 10,
+            next_slot: 100,
+            requests_opened: 0,
+            num_words: 1,
+            callback_gas_limit: MINIMUM_CALLBACK_GAS_LIMIT,
+            callback_data,
+        }
+    }
+
+    #[test]
+    fn test_schedule_advance() {
+        let mut schedule = schedule(vec![]);
+        let first_seed = schedule.request_seed();
+
+        // Cranked on time, the cadence is kept even if the crank lands late in the interval
+        schedule.advance(103).unwrap();
+        assert_eq!((schedule.next_slot, schedule.requests_opened), (110, 1));
+        assert_ne!(schedule.request_seed(), first_seed);
+
+        // Missed intervals are skipped
+        schedule.advance(145).unwrap();
+        assert_eq!((schedule.next_slot, schedule.requests_opened), (155, 2));
+    }
+
+    fn beacon_stream(callback_data: Vec<u8>) -> BeaconStream {
+        BeaconStream {
+            owner: Pubkey::new_unique(),
+            callback_program: Pubkey::default(),
+            callback_account: Pubkey::default(),
+            callback_gas_limit: 0,
+            rounds_written: 0,
+            entries: [BeaconEntry::default(); BEACON_STREAM_CAPACITY],
+            callback_data,
+        }
+    }
+
+    #[test]
+    fn test_beacon_stream_ring() {
+        let mut stream = beacon_stream(vec![]);
+        assert_eq!(stream.latest(), None);
+        let entry = |round| BeaconEntry { round, randomness: [round as u8; 32], published_slot: round * 2 };
+
+        for round in 1..=BEACON_STREAM_CAPACITY as u64 + 3 {
+            stream.push(entry(round)).unwrap();
+            assert_eq!(stream.latest(), Some(&entry(round)));
+        }
+        // The oldest rounds were overwritten
+        assert_eq!(stream.rounds_written, BEACON_STREAM_CAPACITY as u64 + 3);
+        assert_eq!(stream.entries[0], entry(BEACON_STREAM_CAPACITY as u64 + 1));
+        assert_eq!(stream.entries[3], entry(4));
+
+        // Rounds go forward only
+        let latest = stream.latest().unwrap().round;
+        for round in [latest, latest - 1] {
+            assert_eq!(stream.push(entry(round)), Err(VrfCoordinatorError::StaleBeaconRound.into()));
+        }
+        stream.push(entry(latest + 5)).unwrap();
+    }
+
+    #[test]
+    fn test_fee_quote() {
+        let fees = FeeSchedule {
+            base_fee: 1_000,
+            fee_per_word: 100,
+            fee_per_million_callback_units: 5_000,
+            priority_fee: 50,
+        };
+        // 1000 + 3 * 100 + ceil(100_001 * 5000 / 1e6) + 2 * 50
+        assert_eq!(fees.quote(3, 100_001, 2), Ok(1_000 + 300 + 501 + 100));
+        assert_eq!(fees.quote(1, MINIMUM_CALLBACK_GAS_LIMIT, 0), Ok(1_000 + 100 + 50));
+
+        let invalid_words = Err(VrfCoordinatorError::InvalidNumberOfWords.into());
+        assert_eq!(fees.quote(0, MINIMUM_CALLBACK_GAS_LIMIT, 0), invalid_words);
+        assert_eq!(fees.quote(MAXIMUM_RANDOM_WORDS + 1, MINIMUM_CALLBACK_GAS_LIMIT, 0), invalid_words);
+        assert_eq!(
+            fees.quote(1, MAXIMUM_CALLBACK_GAS_LIMIT + 1, 0),
+            Err(VrfCoordinatorError::InvalidCallbackGasLimit.into())
+        );
+
+        let expensive = FeeSchedule { base_fee: u64::MAX, ..fees };
+        assert_eq!(expensive.quote(1, MINIMUM_CALLBACK_GAS_LIMIT, 0), Err(ProgramError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn test_callback_rebate() {
+        let fees = FeeSchedule {
+            base_fee: 1_000,
+            fee_per_word: 100,
+            fee_per_million_callback_units: 5_000,
+            priority_fee: 50,
+        };
+        // ceil(200_000 * 5000 / 1e6) - ceil(30_001 * 5000 / 1e6)
+        assert_eq!(fees.callback_rebate(200_000, 30_001), Ok(1_000 - 151));
+        assert_eq!(fees.callback_rebate(200_000, 200_000), Ok(0));
+        // A callback cannot use more than its limit, so it never pays more than quoted
+        assert_eq!(fees.callback_rebate(200_000, 300_000), Ok(0));
+
+        let quote = fees.quote(2, 200_000, 0).unwrap();
+        assert_eq!(quote - fees.callback_rebate(200_000, 0).unwrap(), 1_000 + 200);
+    }
+
+    #[test]
+    fn test_fee_auction_price() {
+        let declining = FeeAuction { start_price: 1_000, end_price: 200, duration_slots: 8 };
+        assert!(declining.is_open_market());
+        assert_eq!(declining.price_at(0), 1_000);
+        assert_eq!(declining.price_at(3), 700);
+        assert_eq!(declining.price_at(8), 200);
+        assert_eq!(declining.price_at(u64::MAX), 200);
+
+        let rising = FeeAuction { start_price: 100, end_price: 500, duration_slots: 4 };
+        assert_eq!(rising.price_at(1), 200);
+        assert_eq!(rising.price_at(9), 500);
+
+        let wide = FeeAuction { start_price: u64::MAX, end_price: 0, duration_slots: 2 };
+        assert_eq!(wide.price_at(1), u64::MAX / 2 + 1);
+
+        assert!(!FeeAuction::default().is_open_market());
+    }
+
+    #[test]
+    fn test_alpha_seed() {
+        let seed = RandomnessRequest::alpha_seed(b"match:42;players:alice,bob").unwrap();
+        assert_ne!(seed, RandomnessRequest::alpha_seed(b"match:42;players:alice,carol").unwrap());
+        assert_ne!(seed, RandomnessRequest::alpha_seed(b"").unwrap());
+
+        assert!(RandomnessRequest::alpha_seed(&[7; MAXIMUM_ALPHA_LEN]).is_ok());
+        assert_eq!(
+            RandomnessRequest::alpha_seed(&[7; MAXIMUM_ALPHA_LEN + 1]),
+            Err(VrfCoordinatorError::AlphaTooLong.into())
+        );
+    }
+
+    #[test]
+    fn test_request_id() {
+        let (subscription, requester) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let id = RequestId::derive(&subscription, 1, &[2; 32], &requester);
+        assert_ne!(id, RequestId::derive(&subscription, 2, &[2; 32], &requester));
+        assert_ne!(id, RequestId::derive(&subscription, 1, &[3; 32], &requester));
+        assert_ne!(id, RequestId::derive(&requester, 1, &[2; 32], &subscription));
+    }
+
+    #[test]
+    fn test_subscription_debit() {
+        let mut subscription = SubscriptionHeader::zeroed();
+        subscription.balance = 3;
+        subscription.native_balance = 5;
+        subscription.debit(4).unwrap();
+        assert_eq!(({ subscription.balance }, { subscription.native_balance }), (0, 4));
+        assert_eq!(subscription.debit(5), Err(VrfCoordinatorError::InsufficientBalance.into()));
+        assert_eq!(subscription.available_balance(), Ok(4));
+    }
+
+    #[test]
+    fn test_subscription_rate_limits() {
+        let mut subscription = SubscriptionHeader::zeroed();
+        for slot in [1, 1, 1] {
+            subscription.record_request(slot).unwrap();
+        }
+
+        subscription.max_requests_per_slot = 2;
+        subscription.max_pending_requests = 4;
+        assert_eq!(subscription.record_request(1), Err(VrfCoordinatorError::RateLimited.into()));
+        subscription.record_request(2).unwrap();
+        assert_eq!(subscription.record_request(3), Err(VrfCoordinatorError::RateLimited.into()));
+        assert_eq!(({ subscription.requests_in_slot }, { subscription.pending_requests }), (0, 4));
+
+        subscription.record_request_closed();
+        subscription.record_request(3).unwrap();
+        for _ in 0..6 {
+            subscription.record_request_closed();
+        }
+        assert_eq!({ subscription.pending_requests }, 0);
+    }
+
+    #[test]
+    fn test_subscription_consumers() {
+        let mut subscription = Subscription {
+            owner: Pubkey::new_unique(),
+            balance: 0,
+            min_balance: 0,
+            confirmations: 1,
+            nonce: 0,
+            backstop_timeout_slots: DEFAULT_BACKSTOP_TIMEOUT_SLOTS,
+            backstop_bonus: 0,
+            total_funded: 0,
+            total_credited: 0,
+            total_debited: 0,
+            audited_nonce: 0,
+            pending_owner: Pubkey::default(),
+            native_balance: 0,
+            max_requests_per_slot: 0,
+            max_pending_requests: 0,
+            last_request_slot: 0,
+            requests_in_slot: 0,
+            pending_requests: 0,
+            consumers: vec![],
+        };
+        let owner = subscription.owner;
+        assert!(subscription.is_consumer(&owner));
+        assert_eq!(subscription.add_consumer(owner), Err(VrfCoordinatorError::InvalidConsumer.into()));
+
+        let consumers: Vec<_> = (0..MAXIMUM_CONSUMERS).map(|_| Pubkey::new_unique()).collect();
+        for consumer in &consumers {
+            subscription.add_consumer(*consumer).unwrap();
+        }
+        assert!(subscription.is_consumer(&consumers[1]));
+        assert_eq!(
+            subscription.add_consumer(Pubkey::new_unique()),
+            Err(VrfCoordinatorError::TooManyConsumers.into())
+        );
+
+        subscription.remove_consumer(&consumers[1]).unwrap();
+        assert!(!subscription.is_consumer(&consumers[1]));
+        assert_eq!(subscription.remove_consumer(&consumers[1]), Err(VrfCoordinatorError::InvalidConsumer.into()));
+        assert_eq!(subscription.consumers.len(), MAXIMUM_CONSUMERS - 1);
+    }
+
+    #[test]
+    fn test_oracle_rotation() {
+        let mut config = CoordinatorConfig {
+            admin: Pubkey::new_unique(),
+            fee_schedule: FeeSchedule::default(),
+            bridge_program: Pubkey::default(),
+            min_confirmations: 1,
+            max_confirmations: 2,
+            paused: false,
+            cpi_events: false,
+            min_priority_fee: 0,
+            drand_public_key: [0; DRAND_PUBLIC_KEY_LEN],
+            oracle_rotation: vec![],
+        };
+        let request = Pubkey::new_unique();
+        assert_eq!(config.rotation_oracle(&request), Pubkey::default());
+
+        let oracles: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        config.set_oracle_rotation(oracles.clone()).unwrap();
+        let assigned = config.rotation_oracle(&request);
+        assert!(oracles.contains(&assigned));
+        assert_eq!(config.rotation_oracle(&request), assigned);
+        // Requests are spread across the rotation
+        let assignments: Vec<_> = (0..32).map(|_| config.rotation_oracle(&Pubkey::new_unique())).collect();
+        assert!(oracles.iter().all(|oracle| assignments.contains(oracle)));
+
+        assert_eq!(
+            config.set_oracle_rotation(vec![oracles[0], oracles[0]]),
+            Err(VrfCoordinatorError::InvalidOracle.into())
+        );
+        assert_eq!(
+            config.set_oracle_rotation(vec![Pubkey::new_unique(); MAXIMUM_ROTATION_ORACLES + 1]),
+            Err(VrfCoordinatorError::TooManyOracles.into())
+        );
+        assert_eq!(config.oracle_rotation, oracles);
+    }
+
+    #[test]
+    fn test_request_commitment() {
+        let mut request = RandomnessRequest {
+            subscription: Pubkey::new_unique(),
+            seed: [1; 32],
+            requester: Pubkey::new_unique(),
+            callback_data: vec![2],
+            request_block: 3,
+            status: RequestStatus::Pending,
+            num_words: 1,
+            callback_gas_limit: MINIMUM_CALLBACK_GAS_LIMIT,
+            nonce: 4,
+            commitment: [0; 32],
+            assigned_oracle: Pubkey::default(),
+            not_before_slot: 0,
+            fee_auction: FeeAuction::default(),
+            expiration_slot: 5,
+            confirmations: 0,
+            required_oracle_responses: 1,
+            priority_fee: 0,
+            reserved_fee: 0,
+            reserved_native: 0,
+            id: RequestId::default(),
+        };
+        assert_eq!(request.check_commitment(), Err(VrfCoordinatorError::InvalidCommitment.into()));
+        request.commitment = request.compute_commitment();
+        request.check_commitment().unwrap();
+
+        // Bookkeeping fields are not committed to, the parameters are
+        request.status = RequestStatus::Fulfilling;
+        request.expiration_slot = 6;
+        request.check_commitment().unwrap();
+        request.nonce = 5;
+        assert_eq!(request.check_commitment(), Err(VrfCoordinatorError::InvalidCommitment.into()));
+        request.nonce = 4;
+        request.callback_data = vec![3];
+        assert_eq!(request.check_commitment(), Err(VrfCoordinatorError::InvalidCommitment.into()));
+    }
+
+    #[test]
+    fn test_stats_record_fulfillment() {
+        let mut stats = CoordinatorStats::default();
+        assert_eq!(stats.average_latency(), 0);
+
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        stats.record_fulfillment(&first, 4, 10).unwrap();
+        stats.record_fulfillment(&second, 2, 20).unwrap();
+        stats.record_fulfillment(&first, 3, 30).unwrap();
+        assert_eq!((stats.total_fulfillments, stats.total_fees, stats.average_latency()), (3, 60, 3));
+        assert_eq!(stats.oracle_fulfillments, vec![
+            OracleFulfillments { oracle: first, fulfillments: 2 },
+            OracleFulfillments { oracle: second, fulfillments: 1 },
+        ]);
+
+        // Only the latest window counts towards the average
+        for _ in 0..LATENCY_WINDOW {
+            stats.record_fulfillment(&second, 1, 0).unwrap();
+        }
+        assert_eq!(stats.average_latency(), 1);
+
+        // Oracles past the tracked maximum are still counted in the totals
+        for _ in 0..MAXIMUM_TRACKED_ORACLES {
+            stats.record_fulfillment(&Pubkey::new_unique(), 1, 0).unwrap();
+        }
+        assert_eq!(stats.oracle_fulfillments.len(), MAXIMUM_TRACKED_ORACLES);
+        assert_eq!(stats.total_fulfillments, 3 + (LATENCY_WINDOW + MAXIMUM_TRACKED_ORACLES) as u64);
+
+        let full = borsh::to_vec(&stats).unwrap();
+        assert_eq!(ACCOUNT_HEADER_LEN + full.len(), CoordinatorStats::LEN);
+    }
+
+    #[test]
+    fn test_request_pool() {
+        let mut pool = RequestPool::new(Pubkey::new_unique());
+        let requests: Vec<_> = (0..=MAXIMUM_POOLED_REQUESTS as u8)
+            .map(|i| PooledRequest { request: Pubkey::new_unique(), seed: [i; 32] })
+            .collect();
+        for request in &requests {
+            pool.push(*request);
+        }
+        assert_eq!(pool.requests, requests[..MAXIMUM_POOLED_REQUESTS]);
+        assert_eq!(pool.unpooled, 1);
+
+        let full = borsh::to_vec(&pool).unwrap();
+        assert_eq!(ACCOUNT_HEADER_LEN + full.len(), RequestPool::LEN);
+
+        pool.remove(&requests[1].request);
+        assert_eq!(pool.requests.len(), MAXIMUM_POOLED_REQUESTS - 1);
+        assert_eq!(pool.requests[1], requests[2]);
+    }
+
+    #[test]
+    fn test_oracle_reputation() {
+        let mut oracle_config = OracleConfig {
+            oracle_key: Pubkey::new_unique(),
+            vrf_key: [5; 32],
+            is_active: true,
+            accrued_fees: 0,
+            fulfillments: 0,
+            total_latency_slots: 0,
+            failures: 0,
+        };
+        assert_eq!(oracle_config.reputation(), MAXIMUM_REPUTATION);
+        assert_eq!(oracle_config.backstop_delay(DEFAULT_BACKSTOP_TIMEOUT_SLOTS), 0);
+
+        // Fast fulfillments keep the full score
+        for latency in [2, 4, 3] {
+            oracle_config.record_fulfillment(latency).unwrap();
+        }
+        assert_eq!(oracle_config.average_latency(), 3);
+        assert_eq!(oracle_config.reputation(), MAXIMUM_REPUTATION);
+
+        // One miss in four
+        oracle_config.record_failure().unwrap();
+        assert_eq!(oracle_config.reputation(), 7_500);
+        assert_eq!(oracle_config.backstop_delay(100), 25);
+
+        // Slow fulfillments count against it too
+        oracle_config.record_fulfillment(27).unwrap();
+        assert_eq!(oracle_config.average_latency(), 9);
+        assert_eq!(oracle_config.reputation(), 8_000 * 4 / 9);
+
+        oracle_config.fulfillments = 0;
+        assert_eq!(oracle_config.reputation(), 0);
+        assert_eq!(oracle_config.backstop_delay(100), 100);
+    }
+
+    #[test]
+    fn test_request_field_offsets() {
+        for callback_len in [0, 300] {
+            let request = RandomnessRequest {
+                subscription: Pubkey::new_unique(),
+                seed: [1; 32],
+                requester: Pubkey::new_unique(),
+                request_block: 2,
+                status: RequestStatus::Fulfilling,
+                num_words: 3,
+                callback_gas_limit: MINIMUM_CALLBACK_GAS_LIMIT,
+                nonce: 4,
+                commitment: [5; 32],
+                assigned_oracle: Pubkey::new_unique(),
+                not_before_slot: 6,
+                fee_auction: FeeAuction { start_price: 7, end_price: 8, duration_slots: 9 },
+                expiration_slot: 11,
+                confirmations: 0,
+                required_oracle_responses: 1,
+                priority_fee: 0,
+                reserved_fee: 0,
+                reserved_native: 0,
+                id: RequestId::default(),
+                callback_data: vec![10; callback_len],
+            };
+            let mut data = RandomnessRequest::DISCRIMINATOR.to_vec();
+            data.push(RandomnessRequest::VERSION);
+            data.extend(borsh::to_vec(&request).unwrap());
+
+            let field = |offset: usize, len: usize| &data[offset..offset + len];
+            assert_eq!(field(RandomnessRequest::SUBSCRIPTION_OFFSET, 32), request.subscription.as_ref());
+            assert_eq!(field(RandomnessRequest::REQUESTER_OFFSET, 32), request.requester.as_ref());
+            assert_eq!(field(RandomnessRequest::STATUS_OFFSET, 1), borsh::to_vec(&request.status).unwrap());
+            assert_eq!(field(RandomnessRequest::ASSIGNED_ORACLE_OFFSET, 32), request.assigned_oracle.as_ref());
+        }
+    }
+}
@@ -0,0 +1,784 @@
+//! In-place upgrades of coordinator accounts written at an older layout.
+//!
+//! Accounts carry their layout version in the header, after the
+//! discriminator. Accounts created before the version byte existed have none
+//! and are treated as [UNVERSIONED]: their fields start right after the
+//! discriminator and are read at the layout the first release of the
+//! coordinator wrote, such as [SubscriptionV0]. Oracle configs of that
+//! release carry no discriminator either, see [OracleConfigV0].
+//!
+//! Changing a layout means bumping [AccountState::VERSION] and overriding
+//! [Migrate::from_version] to decode the previous versions into the new
+//! struct. `MigrateAccount` then rewrites an account with [upgrade_account].
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    crate::{
+        audit_log::AdminAuditLog,
+        error::VrfCoordinatorError,
+        groth16::Groth16VerifyingKey,
+        loader::AccountState,
+        state::{
+            CoordinatorConfig, CoordinatorStats, DrandRound, FeeAuction, FeeSchedule, OracleConfig, OracleResponses,
+            RandomnessRequest, RequestId, RequestPool, RequestStatus, Schedule, Subscription, VrfResult, ACCOUNT_HEADER_LEN, DISCRIMINATOR_LEN,
+            DEFAULT_BACKSTOP_TIMEOUT_SLOTS, DRAND_PUBLIC_KEY_LEN, MINIMUM_REQUEST_CONFIRMATIONS,
+        },
+        switchboard::RandomnessAccountData,
+    },
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// Version of accounts written before the header carried a version byte
+pub const UNVERSIONED: u8 = 0;
+
+/// Account state that can be decoded from its older layouts
+pub trait Migrate: AccountState {
+    /// Decodes `fields`, the data after the header of an account written at
+    /// layout `version`. By default only the current layout is known, which
+    /// unversioned accounts share while the type is at version 1.
+    fn from_version(version: u8, fields: &[u8]) -> Result<Self, ProgramError> {
+        if version != Self::VERSION && (version != UNVERSIONED || Self::VERSION != 1) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::deserialize(&mut &fields[..]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/// [Subscription] as the coordinator wrote it before accounts carried a
+/// layout version
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SubscriptionV0 {
+    pub owner: Pubkey,
+    pub balance: u64,
+    pub min_balance: u64,
+    pub confirmations: u8,
+    pub nonce: u64,
+}
+
+impl From<SubscriptionV0> for Subscription {
+    /// No funding history was kept, so the balance counts as funded
+    fn from(v0: SubscriptionV0) -> Self {
+        Self {
+            owner: v0.owner,
+            balance: v0.balance,
+            min_balance: v0.min_balance,
+            confirmations: v0.confirmations,
+            nonce: v0.nonce,
+            backstop_timeout_slots: DEFAULT_BACKSTOP_TIMEOUT_SLOTS,
+            backstop_bonus: 0,
+            total_funded: v0.balance,
+            total_credited: 0,
+            total_debited: 0,
+            audited_nonce: 0,
+            pending_owner: Pubkey::default(),
+            native_balance: 0,
+            max_requests_per_slot: 0,
+            max_pending_requests: 0,
+            last_request_slot: 0,
+            requests_in_slot: 0,
+            pending_requests: 0,
+            consumers: vec![],
+        }
+    }
+}
+
+/// [Subscription] at layout version 1, before `native_balance`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SubscriptionV1 {
+    pub owner: Pubkey,
+    pub balance: u64,
+    pub min_balance: u64,
+    pub confirmations: u8,
+    pub nonce: u64,
+    pub backstop_timeout_slots: u64,
+    pub backstop_bonus: u64,
+    pub total_funded: u64,
+    pub total_credited: u64,
+    pub total_debited: u64,
+    pub audited_nonce: u64,
+    pub pending_owner: Pubkey,
+    pub consumers: Vec<Pubkey>,
+}
+
+impl From<SubscriptionV1> for Subscription {
+    fn from(v1: SubscriptionV1) -> Self {
+        Self {
+            owner: v1.owner,
+            balance: v1.balance,
+            min_balance: v1.min_balance,
+            confirmations: v1.confirmations,
+            nonce: v1.nonce,
+            backstop_timeout_slots: v1.backstop_timeout_slots,
+            backstop_bonus: v1.backstop_bonus,
+            total_funded: v1.total_funded,
+            total_credited: v1.total_credited,
+            total_debited: v1.total_debited,
+            audited_nonce: v1.audited_nonce,
+            pending_owner: v1.pending_owner,
+            native_balance: 0,
+            max_requests_per_slot: 0,
+            max_pending_requests: 0,
+            last_request_slot: 0,
+            requests_in_slot: 0,
+            pending_requests: 0,
+            consumers: v1.consumers,
+        }
+    }
+}
+
+/// [Subscription] at layout version 2, before the rate limits
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SubscriptionV2 {
+    pub owner: Pubkey,
+    pub balance: u64,
+    pub min_balance: u64,
+    pub confirmations: u8,
+    pub nonce: u64,
+    pub backstop_timeout_slots: u64,
+    pub backstop_bonus: u64,
+    pub total_funded: u64,
+    pub total_credited: u64,
+    pub total_debited: u64,
+    pub audited_nonce: u64,
+    pub pending_owner: Pubkey,
+    pub native_balance: u64,
+    pub consumers: Vec<Pubkey>,
+}
+
+impl From<SubscriptionV2> for Subscription {
+    fn from(v2: SubscriptionV2) -> Self {
+        Self {
+            owner: v2.owner,
+            balance: v2.balance,
+            min_balance: v2.min_balance,
+            confirmations: v2.confirmations,
+            nonce: v2.nonce,
+            backstop_timeout_slots: v2.backstop_timeout_slots,
+            backstop_bonus: v2.backstop_bonus,
+            total_funded: v2.total_funded,
+            total_credited: v2.total_credited,
+            total_debited: v2.total_debited,
+            audited_nonce: v2.audited_nonce,
+            pending_owner: v2.pending_owner,
+            native_balance: v2.native_balance,
+            max_requests_per_slot: 0,
+            max_pending_requests: 0,
+            last_request_slot: 0,
+            requests_in_slot: 0,
+            pending_requests: 0,
+            consumers: v2.consumers,
+        }
+    }
+}
+
+/// [CoordinatorConfig] at layout version 1, before `cpi_events`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CoordinatorConfigV1 {
+    pub admin: Pubkey,
+    pub fee_schedule: FeeSchedule,
+    pub bridge_program: Pubkey,
+    pub min_confirmations: u8,
+    pub max_confirmations: u8,
+    pub paused: bool,
+    pub oracle_rotation: Vec<Pubkey>,
+}
+
+impl From<CoordinatorConfigV1> for CoordinatorConfig {
+    fn from(v1: CoordinatorConfigV1) -> Self {
+        Self {
+            admin: v1.admin,
+            fee_schedule: v1.fee_schedule,
+            bridge_program: v1.bridge_program,
+            min_confirmations: v1.min_confirmations,
+            max_confirmations: v1.max_confirmations,
+            paused: v1.paused,
+            cpi_events: false,
+            min_priority_fee: 0,
+            drand_public_key: [0; DRAND_PUBLIC_KEY_LEN],
+            oracle_rotation: v1.oracle_rotation,
+        }
+    }
+}
+
+/// [CoordinatorConfig] at layout version 2, before `min_priority_fee`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CoordinatorConfigV2 {
+    pub admin: Pubkey,
+    pub fee_schedule: FeeSchedule,
+    pub bridge_program: Pubkey,
+    pub min_confirmations: u8,
+    pub max_confirmations: u8,
+    pub paused: bool,
+    pub cpi_events: bool,
+    pub oracle_rotation: Vec<Pubkey>,
+}
+
+impl From<CoordinatorConfigV2> for CoordinatorConfig {
+    fn from(v2: CoordinatorConfigV2) -> Self {
+        Self {
+            admin: v2.admin,
+            fee_schedule: v2.fee_schedule,
+            bridge_program: v2.bridge_program,
+            min_confirmations: v2.min_confirmations,
+            max_confirmations: v2.max_confirmations,
+            paused: v2.paused,
+            cpi_events: v2.cpi_events,
+            min_priority_fee: 0,
+            drand_public_key: [0; DRAND_PUBLIC_KEY_LEN],
+            oracle_rotation: v2.oracle_rotation,
+        }
+    }
+}
+
+/// [CoordinatorConfig] at layout version 3, before `drand_public_key`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CoordinatorConfigV3 {
+    pub admin: Pubkey,
+    pub fee_schedule: FeeSchedule,
+    pub bridge_program: Pubkey,
+    pub min_confirmations: u8,
+    pub max_confirmations: u8,
+    pub paused: bool,
+    pub cpi_events: bool,
+    pub min_priority_fee: u64,
+    pub oracle_rotation: Vec<Pubkey>,
+}
+
+impl From<CoordinatorConfigV3> for CoordinatorConfig {
+    fn from(v3: CoordinatorConfigV3) -> Self {
+        Self {
+            admin: v3.admin,
+            fee_schedule: v3.fee_schedule,
+            bridge_program: v3.bridge_program,
+            min_confirmations: v3.min_confirmations,
+            max_confirmations: v3.max_confirmations,
+            paused: v3.paused,
+            cpi_events: v3.cpi_events,
+            min_priority_fee: v3.min_priority_fee,
+            drand_public_key: [0; DRAND_PUBLIC_KEY_LEN],
+            oracle_rotation: v3.oracle_rotation,
+        }
+    }
+}
+
+/// [RandomnessRequest] as the coordinator wrote it before accounts carried a
+/// layout version, with `callback_data` before `request_block`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RandomnessRequestV0 {
+    pub subscription: Pubkey,
+    pub seed: [u8; 32],
+    pub requester: Pubkey,
+    pub callback_data: Vec<u8>,
+    pub request_block: u64,
+    pub status: RequestStatus,
+    pub num_words: u32,
+    pub callback_gas_limit: u64,
+    pub nonce: u64,
+    pub commitment: [u8; 32],
+}
+
+impl From<RandomnessRequestV0> for RandomnessRequest {
+    /// The request is open to any oracle at the minimum confirmations and
+    /// expires a full expiration window after `request_block`, which was never
+    /// set. It did not commit to its parameters, so it is committed to as
+    /// written.
+    fn from(v0: RandomnessRequestV0) -> Self {
+        let mut request = Self {
+            subscription: v0.subscription,
+            seed: v0.seed,
+            requester: v0.requester,
+            request_block: v0.request_block,
+            status: v0.status,
+            num_words: v0.num_words,
+            callback_gas_limit: v0.callback_gas_limit,
+            nonce: v0.nonce,
+            commitment: v0.commitment,
+            assigned_oracle: Pubkey::default(),
+            not_before_slot: 0,
+            fee_auction: FeeAuction::default(),
+            expiration_slot: RandomnessRequest::expiration(v0.request_block, 0),
+            confirmations: MINIMUM_REQUEST_CONFIRMATIONS,
+            required_oracle_responses: 1,
+            priority_fee: 0,
+            id: RequestId::derive(&v0.subscription, v0.nonce, &v0.seed, &v0.requester),
+            reserved_fee: 0,
+            reserved_native: 0,
+            callback_data: v0.callback_data,
+        };
+        request.commitment = request.compute_commitment();
+        request
+    }
+}
+
+/// [RandomnessRequest] at layout version 1, before `priority_fee`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RandomnessRequestV1 {
+    pub subscription: Pubkey,
+    pub seed: [u8; 32],
+    pub requester: Pubkey,
+    pub request_block: u64,
+    pub status: RequestStatus,
+    pub num_words: u32,
+    pub callback_gas_limit: u64,
+    pub nonce: u64,
+    pub commitment: [u8; 32],
+    pub assigned_oracle: Pubkey,
+    pub not_before_slot: u64,
+    pub fee_auction: FeeAuction,
+    pub expiration_slot: u64,
+    pub confirmations: u8,
+    pub required_oracle_responses: u8,
+    pub callback_data: Vec<u8>,
+}
+
+impl From<RandomnessRequestV1> for RandomnessRequest {
+    fn from(v1: RandomnessRequestV1) -> Self {
+        Self {
+            subscription: v1.subscription,
+            seed: v1.seed,
+            requester: v1.requester,
+            request_block: v1.request_block,
+            status: v1.status,
+            num_words: v1.num_words,
+            callback_gas_limit: v1.callback_gas_limit,
+            nonce: v1.nonce,
+            commitment: v1.commitment,
+            assigned_oracle: v1.assigned_oracle,
+            not_before_slot: v1.not_before_slot,
+            fee_auction: v1.fee_auction,
+            expiration_slot: v1.expiration_slot,
+            confirmations: v1.confirmations,
+            required_oracle_responses: v1.required_oracle_responses,
+            priority_fee: 0,
+            id: RequestId::derive(&v1.subscription, v1.nonce, &v1.seed, &v1.requester),
+            reserved_fee: 0,
+            reserved_native: 0,
+            callback_data: v1.callback_data,
+        }
+    }
+}
+
+/// [VrfResult] as the coordinator wrote it before accounts carried a layout
+/// version, without `request`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct VrfResultV0 {
+    pub randomness: Vec<[u8; 64]>,
+    pub proof: Vec<u8>,
+    pub proof_block: u64,
+}
+
+impl From<VrfResultV0> for VrfResult {
+    /// The request the words were for was not recorded, so the upgraded
+    /// result fulfills none
+    fn from(v0: VrfResultV0) -> Self {
+        Self {
+            randomness: v0.randomness,
+            proof: v0.proof,
+            proof_block: v0.proof_block,
+            request: Pubkey::default(),
+        }
+    }
+}
+
+/// [OracleConfig] as the coordinator wrote it before accounts carried a
+/// layout version. These accounts have no discriminator: the whole account is
+/// the fields, exactly [Self::LEN] bytes.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct OracleConfigV0 {
+    pub oracle_key: Pubkey,
+    pub vrf_key: [u8; 32],
+    pub is_active: bool,
+}
+
+impl OracleConfigV0 {
+    /// Account size
+    pub const LEN: usize = 32 + 32 + 1;
+}
+
+impl From<OracleConfigV0> for OracleConfig {
+    fn from(v0: OracleConfigV0) -> Self {
+        Self {
+            oracle_key: v0.oracle_key,
+            vrf_key: v0.vrf_key,
+            is_active: v0.is_active,
+            accrued_fees: 0,
+            fulfillments: 0,
+            total_latency_slots: 0,
+            failures: 0,
+        }
+    }
+}
+
+/// [RandomnessRequest] at layout version 2, before `id`
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RandomnessRequestV2 {
+    pub subscription: Pubkey,
+    pub seed: [u8; 32],
+    pub requester: Pubkey,
+    pub request_block: u64,
+    pub status: RequestStatus,
+    pub num_words: u32,
+    pub callback_gas_limit: u64,
+    pub nonce: u64,
+    pub commitment: [u8; 32],
+    pub assigned_oracle: Pubkey,
+    pub not_before_slot: u64,
+    pub fee_auction: FeeAuction,
+    pub expiration_slot: u64,
+    pub confirmations: u8,
+    pub required_oracle_responses: u8,
+    pub priority_fee: u64,
+    pub callback_data: Vec<u8>,
+}
+
+impl From<RandomnessRequestV2> for RandomnessRequest {
+    fn from(v2: RandomnessRequestV2) -> Self {
+        Self {
+            subscription: v2.subscription,
+            seed: v2.seed,
+            requester: v2.requester,
+            request_block: v2.request_block,
+            status: v2.status,
+            num_words: v2.num_words,
+            callback_gas_limit: v2.callback_gas_limit,
+            nonce: v2.nonce,
+            commitment: v2.commitment,
+            assigned_oracle: v2.assigned_oracle,
+            not_before_slot: v2.not_before_slot,
+            fee_auction: v2.fee_auction,
+            expiration_slot: v2.expiration_slot,
+            confirmations: v2.confirmations,
+            required_oracle_responses: v2.required_oracle_responses,
+            priority_fee: v2.priority_fee,
+            id: RequestId::derive(&v2.subscription, v2.nonce, &v2.seed, &v2.requester),
+            reserved_fee: 0,
+            reserved_native: 0,
+            callback_data: v2.callback_data,
+        }
+    }
+}
+
+/// [RandomnessRequest] at layout version 3, before the fee reservation
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RandomnessRequestV3 {
+    pub subscription: Pubkey,
+    pub seed: [u8; 32],
+    pub requester: Pubkey,
+    pub request_block: u64,
+    pub status: RequestStatus,
+    pub num_words: u32,
+    pub callback_gas_limit: u64,
+    pub nonce: u64,
+    pub commitment: [u8; 32],
+    pub assigned_oracle: Pubkey,
+    pub not_before_slot: u64,
+    pub fee_auction: FeeAuction,
+    pub expiration_slot: u64,
+    pub confirmations: u8,
+    pub required_oracle_responses: u8,
+    pub priority_fee: u64,
+    pub id: RequestId,
+    pub callback_data: Vec<u8>,
+}
+
+impl From<RandomnessRequestV3> for RandomnessRequest {
+    /// Requests of the earlier layouts reserved nothing but the priority fee,
+    /// so that is all closing them refunds
+    fn from(v3: RandomnessRequestV3) -> Self {
+        Self {
+            subscription: v3.subscription,
+            seed: v3.seed,
+            requester: v3.requester,
+            request_block: v3.request_block,
+            status: v3.status,
+            num_words: v3.num_words,
+            callback_gas_limit: v3.callback_gas_limit,
+            nonce: v3.nonce,
+            commitment: v3.commitment,
+            assigned_oracle: v3.assigned_oracle,
+            not_before_slot: v3.not_before_slot,
+            fee_auction: v3.fee_auction,
+            expiration_slot: v3.expiration_slot,
+            confirmations: v3.confirmations,
+            required_oracle_responses: v3.required_oracle_responses,
+            priority_fee: v3.priority_fee,
+            id: v3.id,
+            reserved_fee: 0,
+            reserved_native: 0,
+            callback_data: v3.callback_data,
+        }
+    }
+}
+
+impl Migrate for Subscription {
+    fn from_version(version: u8, fields: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            UNVERSIONED => SubscriptionV0::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            1 => SubscriptionV1::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            2 => SubscriptionV2::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            Self::VERSION => Self::deserialize(&mut &fields[..]).map_err(|_| ProgramError::InvalidAccountData),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+impl Migrate for CoordinatorConfig {
+    fn from_version(version: u8, fields: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            UNVERSIONED | 1 => CoordinatorConfigV1::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            2 => CoordinatorConfigV2::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            3 => CoordinatorConfigV3::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            Self::VERSION => Self::deserialize(&mut &fields[..]).map_err(|_| ProgramError::InvalidAccountData),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+impl Migrate for RandomnessRequest {
+    fn from_version(version: u8, fields: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            UNVERSIONED => RandomnessRequestV0::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            1 => RandomnessRequestV1::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            2 => RandomnessRequestV2::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            3 => RandomnessRequestV3::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            Self::VERSION => Self::deserialize(&mut &fields[..]).map_err(|_| ProgramError::InvalidAccountData),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+impl Migrate for VrfResult {
+    fn from_version(version: u8, fields: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            UNVERSIONED => VrfResultV0::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            Self::VERSION => Self::deserialize(&mut &fields[..]).map_err(|_| ProgramError::InvalidAccountData),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+impl Migrate for OracleConfig {
+    fn from_version(version: u8, fields: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            UNVERSIONED => OracleConfigV0::deserialize(&mut &fields[..])
+                .map(Self::from)
+                .map_err(|_| ProgramError::InvalidAccountData),
+            Self::VERSION => Self::deserialize(&mut &fields[..]).map_err(|_| ProgramError::InvalidAccountData),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+impl Migrate for DrandRound {}
+impl Migrate for Schedule {}
+impl Migrate for CoordinatorStats {}
+impl Migrate for RequestPool {}
+impl Migrate for OracleResponses {}
+impl Migrate for Groth16VerifyingKey {}
+impl Migrate for AdminAuditLog {}
+impl Migrate for RandomnessAccountData {}
+
+/// Decodes `data` as `T` at whatever layout it was written in, returning the
+/// version it was found at
+pub fn upgrade<T: Migrate>(data: &[u8]) -> Result<(u8, T), ProgramError> {
+    if data.get(..DISCRIMINATOR_LEN) != Some(&T::DISCRIMINATOR[..]) {
+        return Err(VrfCoordinatorError::InvalidDiscriminator.into());
+    }
+    if let Some(&version) = data.get(DISCRIMINATOR_LEN).filter(|&&version| version != UNVERSIONED) {
+        if let Ok(state) = T::from_version(version, &data[ACCOUNT_HEADER_LEN..]) {
+            return Ok((version, state));
+        }
+    }
+    let state = T::from_version(UNVERSIONED, &data[DISCRIMINATOR_LEN..])?;
+    Ok((UNVERSIONED, state))
+}
+
+/// Data and former version of `T`'s account, or `None` if already current
+fn migrate<T: Migrate>(data: &[u8]) -> Result<Option<(u8, Vec<u8>)>, ProgramError> {
+    let (version, state) = upgrade::<T>(data)?;
+    if version == T::VERSION {
+        return Ok(None);
+    }
+
+    let mut upgraded = T::DISCRIMINATOR.to_vec();
+    upgraded.push(T::VERSION);
+    borsh::to_writer(&mut upgraded, &state)?;
+    // Keep the slack of preallocated accounts, which grow in place
+    let fields_start = if version == UNVERSIONED { DISCRIMINATOR_LEN } else { ACCOUNT_HEADER_LEN };
+    let space = (data.len() - fields_start + ACCOUNT_HEADER_LEN).max(T::MIN_LEN);
+    upgraded.resize(upgraded.len().max(space), 0);
+    Ok(Some((version, upgraded)))
+}
+
+/// Rewrites a coordinator account's data at its type's current layout,
+/// returning the version it was at and the new data, which is never shorter
+/// than the old. Returns `None` if the account is already current.
+pub fn upgrade_account(data: &[u8]) -> Result<Option<(u8, Vec<u8>)>, ProgramError> {
+    type Migration = fn(&[u8]) -> Result<Option<(u8, Vec<u8>)>, ProgramError>;
+    const MIGRATIONS: &[([u8; 8], Migration)] = &[
+        (Subscription::DISCRIMINATOR, migrate::<Subscription>),
+        (RandomnessRequest::DISCRIMINATOR, migrate::<RandomnessRequest>),
+        (VrfResult::DISCRIMINATOR, migrate::<VrfResult>),
+        (OracleConfig::DISCRIMINATOR, migrate::<OracleConfig>),
+        (CoordinatorConfig::DISCRIMINATOR, migrate::<CoordinatorConfig>),
+        (DrandRound::DISCRIMINATOR, migrate::<DrandRound>),
+        (Schedule::DISCRIMINATOR, migrate::<Schedule>),
+        (CoordinatorStats::DISCRIMINATOR, migrate::<CoordinatorStats>),
+        (RequestPool::DISCRIMINATOR, migrate::<RequestPool>),
+        (OracleResponses::DISCRIMINATOR, migrate::<OracleResponses>),
+        (Groth16VerifyingKey::DISCRIMINATOR, migrate::<Groth16VerifyingKey>),
+        (AdminAuditLog::DISCRIMINATOR, migrate::<AdminAuditLog>),
+        (RandomnessAccountData::DISCRIMINATOR, migrate::<RandomnessAccountData>),
+    ];
+
+    let discriminator = data.get(..DISCRIMINATOR_LEN).ok_or(VrfCoordinatorError::AccountTooSmall)?;
+    let Some((_, migrate)) = MIGRATIONS.iter().find(|(tag, _)| tag[..] == *discriminator) else {
+        if data.len() != OracleConfigV0::LEN {
+            return Err(VrfCoordinatorError::InvalidDiscriminator.into());
+        }
+        let mut tagged = OracleConfig::DISCRIMINATOR.to_vec();
+        tagged.extend_from_slice(data);
+        return migrate::<OracleConfig>(&tagged);
+    };
+    migrate(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{loader::check_data, state::{OracleFulfillments, PROOF_LEN, REQUEST_EXPIRATION_SLOTS}},
+    };
+
+    #[test]
+    fn test_upgrade_unversioned_account() {
+        let stats = CoordinatorStats {
+            total_requests: 2,
+            total_fulfillments: 1,
+            oracle_fulfillments: vec![OracleFulfillments { oracle: Pubkey::new_unique(), fulfillments: 1 }],
+            ..CoordinatorStats::default()
+        };
+        let mut legacy = CoordinatorStats::DISCRIMINATOR.to_vec();
+        legacy.extend(borsh::to_vec(&stats).unwrap());
+        // Sized as preallocated before the header grew by the version byte
+        legacy.resize(CoordinatorStats::MIN_LEN - 1, 0);
+        assert_eq!(check_data::<CoordinatorStats>(&legacy), Err(VrfCoordinatorError::AccountNotMigrated.into()));
+
+        let (version, upgraded) = upgrade_account(&legacy).unwrap().unwrap();
+        assert_eq!(version, UNVERSIONED);
+        assert_eq!(upgraded.len(), legacy.len() + 1);
+        check_data::<CoordinatorStats>(&upgraded).unwrap();
+        assert_eq!(CoordinatorStats::deserialize(&mut &upgraded[ACCOUNT_HEADER_LEN..]).unwrap(), stats);
+
+        assert_eq!(upgrade_account(&upgraded), Ok(None));
+        assert_eq!(
+            upgrade_account(b"SCHEDULX\x01"),
+            Err(VrfCoordinatorError::InvalidDiscriminator.into())
+        );
+    }
+
+    #[test]
+    fn test_upgrade_baseline_subscription() {
+        let owner = Pubkey::new_unique();
+        let mut legacy = b"SUBSCRIP".to_vec();
+        legacy.extend_from_slice(owner.as_ref());
+        legacy.extend_from_slice(&1_000u64.to_le_bytes()); // balance
+        legacy.extend_from_slice(&10u64.to_le_bytes()); // min_balance
+        legacy.push(3); // confirmations
+        legacy.extend_from_slice(&7u64.to_le_bytes()); // nonce
+        assert_eq!(legacy.len(), 8 + 57);
+
+        let (version, upgraded) = upgrade_account(&legacy).unwrap().unwrap();
+        assert_eq!(version, UNVERSIONED);
+        assert_eq!(upgraded.len(), Subscription::LEN);
+        check_data::<Subscription>(&upgraded).unwrap();
+        let subscription = Subscription::deserialize(&mut &upgraded[ACCOUNT_HEADER_LEN..]).unwrap();
+        assert_eq!(subscription.owner, owner);
+        assert_eq!((subscription.balance, subscription.min_balance, subscription.confirmations, subscription.nonce), (1_000, 10, 3, 7));
+        assert_eq!(subscription.expected_balance(), Some(1_000));
+        assert!(subscription.consumers.is_empty());
+        assert_eq!(upgrade_account(&upgraded), Ok(None));
+    }
+
+    #[test]
+    fn test_upgrade_baseline_request() {
+        let (subscription, requester) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut legacy = b"REQUEST\0".to_vec();
+        legacy.extend_from_slice(subscription.as_ref());
+        legacy.extend_from_slice(&[4; 32]); // seed
+        legacy.extend_from_slice(requester.as_ref());
+        legacy.extend_from_slice(&3u32.to_le_bytes()); // callback_data
+        legacy.extend_from_slice(&[1, 2, 3]);
+        legacy.extend_from_slice(&0u64.to_le_bytes()); // request_block
+        legacy.push(0); // status, pending
+        legacy.extend_from_slice(&2u32.to_le_bytes()); // num_words
+        legacy.extend_from_slice(&100_000u64.to_le_bytes()); // callback_gas_limit
+        legacy.extend_from_slice(&7u64.to_le_bytes()); // nonce
+        legacy.extend_from_slice(&[0; 32]); // commitment
+
+        let (version, upgraded) = upgrade_account(&legacy).unwrap().unwrap();
+        assert_eq!(version, UNVERSIONED);
+        assert_eq!(upgraded.len(), RandomnessRequest::space(3));
+        check_data::<RandomnessRequest>(&upgraded).unwrap();
+        let request = RandomnessRequest::deserialize(&mut &upgraded[ACCOUNT_HEADER_LEN..]).unwrap();
+        assert_eq!((request.subscription, request.seed, request.requester), (subscription, [4; 32], requester));
+        assert_eq!(request.callback_data, vec![1, 2, 3]);
+        assert_eq!(request.status, RequestStatus::Pending);
+        assert_eq!((request.num_words, request.callback_gas_limit, request.nonce), (2, 100_000, 7));
+        assert_eq!(request.id, request.compute_id());
+        assert_eq!(request.expiration_slot, REQUEST_EXPIRATION_SLOTS);
+        request.check_commitment().unwrap();
+        assert_eq!(upgrade_account(&upgraded), Ok(None));
+    }
+
+    #[test]
+    fn test_upgrade_baseline_oracle_config() {
+        let oracle_key = Pubkey::new_unique();
+        let mut legacy = oracle_key.to_bytes().to_vec();
+        legacy.extend_from_slice(&[5; 32]); // vrf_key
+        legacy.push(1); // is_active
+        assert_eq!(legacy.len(), OracleConfigV0::LEN);
+
+        let (version, upgraded) = upgrade_account(&legacy).unwrap().unwrap();
+        assert_eq!(version, UNVERSIONED);
+        assert_eq!(upgraded.len(), OracleConfig::LEN);
+        check_data::<OracleConfig>(&upgraded).unwrap();
+        let config = OracleConfig::deserialize(&mut &upgraded[ACCOUNT_HEADER_LEN..]).unwrap();
+        assert_eq!((config.oracle_key, config.vrf_key, config.is_active), (oracle_key, [5; 32], true));
+        assert_eq!((config.accrued_fees, config.fulfillments), (0, 0));
+        assert_eq!(upgrade_account(&upgraded), Ok(None));
+        assert_eq!(upgrade_account(&legacy[1..]), Err(VrfCoordinatorError::InvalidDiscriminator.into()));
+    }
+
+    #[test]
+    fn test_upgrade_unversioned_vrf_result() {
+        let legacy_result = VrfResultV0 { randomness: vec![[7; 64]], proof: vec![1; PROOF_LEN], proof_block: 5 };
+        let mut legacy = VrfResult::DISCRIMINATOR.to_vec();
+        legacy.extend(borsh::to_vec(&legacy_result).unwrap());
+
+        let (version, upgraded) = upgrade_account(&legacy).unwrap().unwrap();
+        assert_eq!(version, UNVERSIONED);
+        assert_eq!(upgraded.len(), VrfResult::space(1));
+        let result = VrfResult::deserialize(&mut &upgraded[ACCOUNT_HEADER_LEN..]).unwrap();
+        assert!(!result.fulfills(&Pubkey::new_unique()));
+        assert_eq!((result.randomness, result.proof, result.proof_block), (vec![[7; 64]], vec![1; PROOF_LEN], 5));
+        assert_eq!(upgrade_account(&upgraded), Ok(None));
+    }
+}
@@ -0,0 +1,101 @@
+//! Switchboard On-Demand-shaped randomness accounts, for consumers migrating
+//! from Switchboard.
+//!
+//! A requester creates its account with `InitRandomnessAccount` at the
+//! `["randomness", requester]` PDA. An oracle that passes this account to
+//! `FulfillRandomness` or `FinalizeFulfillment` reveals the request's value
+//! into it. Consumers read it with [RandomnessAccountData::parse] and
+//! [RandomnessAccountData::get_value], as they would a Switchboard account.
+//! As with Switchboard, a consumer should compare `seed_slot` with the slot
+//! it committed at, so it never reads the value of an earlier request.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    crate::{error::VrfCoordinatorError, loader::{check_data, AccountState}, state::ACCOUNT_HEADER_LEN},
+    solana_program::{clock::Clock, program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// PDA seed prefix of a randomness account
+pub const RANDOMNESS_SEED: &[u8] = b"randomness";
+
+/// Readable randomness account with the fields of Switchboard's `RandomnessAccountData`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub struct RandomnessAccountData {
+    /// Requester that owns this account
+    pub authority: Pubkey,
+    /// Slot of the request whose value was revealed last
+    pub seed_slot: u64,
+    /// Seed of that request
+    pub seed: [u8; 32],
+    /// Oracle that revealed the value
+    pub oracle: Pubkey,
+    /// Slot the value was revealed at, zero if none has been yet
+    pub reveal_slot: u64,
+    /// The revealed randomness
+    pub value: [u8; 32],
+}
+
+impl RandomnessAccountData {
+    /// Account size, including the header
+    pub const LEN: usize = ACCOUNT_HEADER_LEN
+        + 32 // authority
+        + 8  // seed_slot
+        + 32 // seed
+        + 32 // oracle
+        + 8  // reveal_slot
+        + 32; // value
+
+    /// Deserializes the raw data of a randomness account
+    pub fn parse(data: &[u8]) -> Result<Self, ProgramError> {
+        check_data::<Self>(data)?;
+        Self::deserialize(&mut &data[ACCOUNT_HEADER_LEN..]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// The revealed value, once a fulfillment has revealed one by `clock`'s slot
+    pub fn get_value(&self, clock: &Clock) -> Result<[u8; 32], ProgramError> {
+        if self.reveal_slot == 0 || self.reveal_slot > clock.slot {
+            return Err(VrfCoordinatorError::RandomnessNotRevealed.into());
+        }
+        Ok(self.value)
+    }
+}
+
+impl AccountState for RandomnessAccountData {
+    const DISCRIMINATOR: [u8; 8] = *b"RANDOMNS";
+    const MIN_LEN: usize = Self::LEN;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_get_value() {
+        let mut randomness = RandomnessAccountData {
+            authority: Pubkey::new_unique(),
+            seed_slot: 10,
+            ..Default::default()
+        };
+        let clock = Clock { slot: 12, ..Default::default() };
+        assert_eq!(randomness.get_value(&clock), Err(VrfCoordinatorError::RandomnessNotRevealed.into()));
+
+        randomness.oracle = Pubkey::new_unique();
+        randomness.reveal_slot = 12;
+        randomness.value = [4; 32];
+        let mut data = RandomnessAccountData::DISCRIMINATOR.to_vec();
+        data.push(RandomnessAccountData::VERSION);
+        data.extend(borsh::to_vec(&randomness).unwrap());
+        assert_eq!(data.len(), RandomnessAccountData::LEN);
+
+        let parsed = RandomnessAccountData::parse(&data).unwrap();
+        assert_eq!(parsed, randomness);
+        assert_eq!(parsed.get_value(&clock), Ok([4; 32]));
+
+        data[0] ^= 1;
+        assert_eq!(
+            RandomnessAccountData::parse(&data),
+            Err(VrfCoordinatorError::InvalidDiscriminator.into())
+        );
+    }
+}
@@ -0,0 +1,422 @@
+//! In-process harness for instruction-level unit tests.
+//!
+//! [TestHarness] runs single instructions against account fixtures without a
+//! bank or validator, so a test of a processor path takes milliseconds where
+//! the `ProgramTest` suites in `tests/` take seconds. Accounts are serialized
+//! into the runtime's input layout and handed to the SBF entrypoint's own
+//! `deserialize`, so reallocs and owner changes behave as on-chain.
+//!
+//! Syscalls are stubbed: the clock is whatever the test warps it to and rent
+//! is the default. Cross-program invocations of the system program are
+//! emulated, other programs run the handlers added with
+//! [TestHarness::add_program], with signer and writable privileges checked as
+//! the runtime does. After a successful instruction the harness asserts that
+//! lamports are conserved, read-only accounts are unchanged and only accounts
+//! owned by a program had their data changed. A failed instruction leaves
+//! the accounts as they were.
+//!
+//! Compute units are not metered: `sol_remaining_compute_units` returns
+//! [COMPUTE_UNIT_LIMIT], or what [TestHarness::set_remaining_compute_units]
+//! set, throughout, so callbacks appear to use none. Compute budgets are
+//! measured by `tests/latency_harness.rs`.
+//!
+//! The stubbed runtime is per thread, so tests run in parallel, each with
+//! one harness at a time.
+
+use {
+    crate::{event::VrfEvent, loader::AccountState, state::ACCOUNT_HEADER_LEN},
+    base64::Engine,
+    borsh::BorshDeserialize,
+    solana_program::{
+        account_info::AccountInfo,
+        bpf_loader,
+        clock::Clock,
+        entrypoint::{self, ProcessInstruction, ProgramResult, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER, SUCCESS},
+        instruction::Instruction,
+        msg,
+        program_error::ProgramError,
+        program_stubs::{self, SyscallStubs},
+        program_utils::limited_deserialize,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction::SystemInstruction,
+        system_program,
+    },
+    solana_sdk::account::Account,
+    std::{cell::RefCell, collections::HashMap, sync::Once},
+};
+
+/// Compute units `sol_remaining_compute_units` reports
+pub const COMPUTE_UNIT_LIMIT: u64 = 1_400_000;
+
+/// `SystemError::AccountAlreadyInUse`
+const ACCOUNT_ALREADY_IN_USE: ProgramError = ProgramError::Custom(0);
+/// `SystemError::ResultWithNegativeLamports`
+const RESULT_WITH_NEGATIVE_LAMPORTS: ProgramError = ProgramError::Custom(1);
+
+/// State the syscall stubs read and write
+#[derive(Default)]
+struct Runtime {
+    clock: Clock,
+    remaining_compute_units: u64,
+    programs: HashMap<Pubkey, ProcessInstruction>,
+    /// Programs being executed, the innermost last
+    call_stack: Vec<Pubkey>,
+    logs: Vec<String>,
+    /// Instructions invoked by the last instruction, in order
+    invocations: Vec<Instruction>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+}
+
+thread_local! {
+    static RUNTIME: RefCell<Runtime> = RefCell::new(Runtime::default());
+}
+
+fn with_runtime<T>(f: impl FnOnce(&mut Runtime) -> T) -> T {
+    RUNTIME.with(|runtime| f(&mut runtime.borrow_mut()))
+}
+
+/// Runs `process` as `program_id` on the call stack
+fn execute(process: ProcessInstruction, program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    with_runtime(|runtime| runtime.call_stack.push(*program_id));
+    let result = process(program_id, accounts, data);
+    with_runtime(|runtime| runtime.call_stack.pop());
+    result
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        with_runtime(|runtime| runtime.logs.push(message.to_string()));
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let fields: Vec<String> = fields.iter()
+            .map(|field| base64::engine::general_purpose::STANDARD.encode(field))
+            .collect();
+        self.sol_log(&format!("data: {}", fields.join(" ")));
+    }
+
+    fn sol_remaining_compute_units(&self) -> u64 {
+        with_runtime(|runtime| runtime.remaining_compute_units)
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        invoke(instruction, account_infos, signers_seeds)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = with_runtime(|runtime| runtime.clock.clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        with_runtime(|runtime| runtime.return_data.clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        with_runtime(|runtime| {
+            let program_id = *runtime.call_stack.last().expect("return data set outside an instruction");
+            runtime.return_data = (!data.is_empty()).then(|| (program_id, data.to_vec()));
+        });
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        with_runtime(|runtime| runtime.call_stack.len() as u64)
+    }
+}
+
+/// Cross-program invocation from the program on top of the call stack
+fn invoke(instruction: &Instruction, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+    let caller = with_runtime(|runtime| {
+        runtime.invocations.push(instruction.clone());
+        *runtime.call_stack.last().expect("invoke outside an instruction")
+    });
+    let signers = signers_seeds.iter()
+        .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+
+    let mut accounts = Vec::with_capacity(instruction.accounts.len());
+    for meta in &instruction.accounts {
+        let account_info = account_infos.iter()
+            .find(|account_info| *account_info.key == meta.pubkey)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if meta.is_signer && !account_info.is_signer && !signers.contains(&meta.pubkey) {
+            msg!("Harness: {} must sign the invocation of {}", meta.pubkey, instruction.program_id);
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if meta.is_writable && !account_info.is_writable {
+            msg!("Harness: {} is read-only in {} but writable in the invocation of {}", meta.pubkey, caller, instruction.program_id);
+            return Err(ProgramError::InvalidArgument);
+        }
+        accounts.push(AccountInfo {
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+            ..account_info.clone()
+        });
+    }
+
+    if instruction.program_id == system_program::ID {
+        return process_system_instruction(&accounts, &instruction.data);
+    }
+    let process = with_runtime(|runtime| runtime.programs.get(&instruction.program_id).copied())
+        .unwrap_or_else(|| panic!("no handler added for program {}", instruction.program_id));
+    execute(process, &instruction.program_id, &accounts, &instruction.data)
+}
+
+/// Emulates the system program instructions the coordinator invokes
+fn process_system_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction: SystemInstruction = limited_deserialize(data, data.len() as u64)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let account = |index: usize| accounts.get(index).ok_or(ProgramError::NotEnoughAccountKeys);
+    match instruction {
+        SystemInstruction::CreateAccount { lamports, space, owner } => {
+            let (from, to) = (account(0)?, account(1)?);
+            if to.lamports() > 0 || to.data_len() > 0 || *to.owner != system_program::ID {
+                return Err(ACCOUNT_ALREADY_IN_USE);
+            }
+            transfer(from, to, lamports)?;
+            to.realloc(space as usize, true)?;
+            to.assign(&owner);
+        }
+        SystemInstruction::Transfer { lamports } => transfer(account(0)?, account(1)?, lamports)?,
+        SystemInstruction::Allocate { space } => account(0)?.realloc(space as usize, true)?,
+        SystemInstruction::Assign { owner } => account(0)?.assign(&owner),
+        instruction => panic!("system instruction {:?} is not emulated", instruction),
+    }
+    Ok(())
+}
+
+fn transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if *from.owner != system_program::ID || from.data_len() > 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let from_lamports = from.lamports().checked_sub(lamports).ok_or(RESULT_WITH_NEGATIVE_LAMPORTS)?;
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+/// Offsets of an account's mutable fields in the serialized input
+struct AccountOffsets {
+    owner: usize,
+    lamports: usize,
+    data_len: usize,
+    data: usize,
+}
+
+/// Accounts of a coordinator under test, and the programs it may invoke
+pub struct TestHarness {
+    pub program_id: Pubkey,
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl TestHarness {
+    /// A harness for the coordinator deployed at `program_id`, at slot 0.
+    /// Resets the runtime of this thread.
+    pub fn new(program_id: Pubkey) -> Self {
+        static INSTALL_STUBS: Once = Once::new();
+        INSTALL_STUBS.call_once(|| {
+            program_stubs::set_syscall_stubs(Box::new(Stubs));
+        });
+        with_runtime(|runtime| *runtime = Runtime {
+            remaining_compute_units: COMPUTE_UNIT_LIMIT,
+            // The coordinator invokes itself to emit events
+            programs: HashMap::from([(program_id, crate::process_instruction as ProcessInstruction)]),
+            ..Runtime::default()
+        });
+
+        let mut harness = Self { program_id, accounts: HashMap::new() };
+        harness.set_account(program_id, Self::program_account());
+        harness.set_account(system_program::ID, Self::program_account());
+        harness
+    }
+
+    fn program_account() -> Account {
+        Account { lamports: 1, owner: bpf_loader::ID, executable: true, ..Account::default() }
+    }
+
+    /// Deploys `process` at `program_id`, for the coordinator to invoke
+    pub fn add_program(&mut self, program_id: Pubkey, process: ProcessInstruction) {
+        with_runtime(|runtime| runtime.programs.insert(program_id, process));
+        self.set_account(program_id, Self::program_account());
+    }
+
+    pub fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.accounts.insert(address, account);
+    }
+
+    /// Creates or tops up a system account
+    pub fn fund(&mut self, address: Pubkey, lamports: u64) {
+        self.accounts.entry(address).or_default().lamports += lamports;
+    }
+
+    /// Stores `state` in a rent-exempt account owned by `owner`, sized to fit
+    /// it but at least `T::MIN_LEN`
+    pub fn set_state<T: AccountState>(&mut self, address: Pubkey, owner: Pubkey, state: &T) {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        data.push(T::VERSION);
+        data.extend(borsh::to_vec(state).unwrap());
+        if data.len() < T::MIN_LEN {
+            data.resize(T::MIN_LEN, 0);
+        }
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.set_account(address, Account { lamports, data, owner, ..Account::default() });
+    }
+
+    /// The account at `address`, empty if it does not exist
+    pub fn account(&self, address: &Pubkey) -> Account {
+        self.accounts.get(address).cloned().unwrap_or_default()
+    }
+
+    /// Deserializes the state stored at `address`
+    pub fn load<T: AccountState>(&self, address: &Pubkey) -> T {
+        let account = self.account(address);
+        assert!(account.data.len() >= T::MIN_LEN, "{} is too small", address);
+        assert_eq!(account.data[..8], T::DISCRIMINATOR, "{} has another type", address);
+        T::deserialize(&mut &account.data[ACCOUNT_HEADER_LEN..]).unwrap()
+    }
+
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        with_runtime(|runtime| runtime.clock.slot = slot);
+    }
+
+    pub fn set_remaining_compute_units(&mut self, units: u64) {
+        with_runtime(|runtime| runtime.remaining_compute_units = units);
+    }
+
+    /// Logs of the last instruction
+    pub fn logs(&self) -> Vec<String> {
+        with_runtime(|runtime| runtime.logs.clone())
+    }
+
+    /// Events emitted by the last instruction, logged or by self-invocation
+    pub fn events(&self) -> Vec<VrfEvent> {
+        let logged = self.logs()
+            .iter()
+            .filter_map(|log| log.strip_prefix("VRF_EVENT:"))
+            .map(|event| {
+                let data = base64::engine::general_purpose::STANDARD.decode(event).unwrap();
+                VrfEvent::try_from_slice(&data).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let invoked = self.invocations()
+            .iter()
+            .filter(|instruction| instruction.program_id == self.program_id)
+            .filter_map(|instruction| VrfEvent::from_cpi_data(&instruction.data))
+            .collect::<Vec<_>>();
+        logged.into_iter().chain(invoked).collect()
+    }
+
+    /// Instructions invoked by the last instruction
+    pub fn invocations(&self) -> Vec<Instruction> {
+        with_runtime(|runtime| runtime.invocations.clone())
+    }
+
+    /// Return data of the last instruction
+    pub fn return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        with_runtime(|runtime| runtime.return_data.clone())
+    }
+
+    /// Processes `instruction`, for the coordinator or a program added with
+    /// [Self::add_program], as the only instruction of a transaction signed
+    /// by every signer it lists
+    pub fn process(&mut self, instruction: &Instruction) -> ProgramResult {
+        let process = with_runtime(|runtime| runtime.programs.get(&instruction.program_id).copied())
+            .expect("instruction for a program the harness does not run");
+        with_runtime(|runtime| {
+            runtime.logs.clear();
+            runtime.invocations.clear();
+            runtime.return_data = None;
+        });
+
+        // Duplicated accounts get the privileges of all their metas
+        let mut privileges: HashMap<Pubkey, (bool, bool)> = HashMap::new();
+        for meta in &instruction.accounts {
+            let (is_signer, is_writable) = privileges.entry(meta.pubkey).or_default();
+            *is_signer |= meta.is_signer;
+            *is_writable |= meta.is_writable;
+        }
+
+        let mut input = Vec::new();
+        let mut offsets = HashMap::new();
+        input.extend((instruction.accounts.len() as u64).to_le_bytes());
+        for (index, meta) in instruction.accounts.iter().enumerate() {
+            if let Some(original) = instruction.accounts[..index].iter().position(|other| other.pubkey == meta.pubkey) {
+                input.push(original as u8);
+                input.extend([0; 7]);
+                continue;
+            }
+            let account = self.account(&meta.pubkey);
+            let (is_signer, is_writable) = privileges[&meta.pubkey];
+            input.extend([NON_DUP_MARKER, is_signer as u8, is_writable as u8, account.executable as u8]);
+            input.extend([0; 4]);
+            input.extend(meta.pubkey.as_ref());
+            let owner = input.len();
+            input.extend(account.owner.as_ref());
+            let lamports = input.len();
+            input.extend(account.lamports.to_le_bytes());
+            let data_len = input.len();
+            input.extend((account.data.len() as u64).to_le_bytes());
+            let data = input.len();
+            input.extend(&account.data);
+            input.resize((input.len() + MAX_PERMITTED_DATA_INCREASE).next_multiple_of(entrypoint::BPF_ALIGN_OF_U128), 0);
+            input.extend(account.rent_epoch.to_le_bytes());
+            offsets.insert(meta.pubkey, AccountOffsets { owner, lamports, data_len, data });
+        }
+        input.extend((instruction.data.len() as u64).to_le_bytes());
+        input.extend(&instruction.data);
+        input.extend(instruction.program_id.as_ref());
+
+        // The entrypoint reads the input through aligned pointers
+        let mut buffer = vec![0u64; input.len().div_ceil(8)];
+        let bytes = unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, input.len()) };
+        bytes.copy_from_slice(&input);
+
+        let (program_id, account_infos, data) = unsafe { entrypoint::deserialize(bytes.as_mut_ptr()) };
+        let result = execute(process, program_id, &account_infos, data);
+        drop(account_infos);
+        result?;
+
+        let read_u64 = |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        let mut lamports_before = 0u128;
+        let mut lamports_after = 0u128;
+        for (address, offsets) in offsets {
+            let before = self.account(&address);
+            let after = Account {
+                lamports: read_u64(offsets.lamports),
+                data: bytes[offsets.data..offsets.data + read_u64(offsets.data_len) as usize].to_vec(),
+                owner: Pubkey::try_from(&bytes[offsets.owner..offsets.owner + 32]).unwrap(),
+                ..before.clone()
+            };
+            lamports_before += before.lamports as u128;
+            lamports_after += after.lamports as u128;
+
+            if !privileges[&address].1 {
+                assert_eq!(before, after, "read-only account {} was modified", address);
+            }
+            if before.data != after.data {
+                let owned_by_program = after.owner == self.program_id
+                    || with_runtime(|runtime| runtime.programs.contains_key(&after.owner));
+                assert!(owned_by_program, "data of {} changed, which is owned by {}", address, after.owner);
+            }
+            self.accounts.insert(address, after);
+        }
+        assert_eq!(lamports_before, lamports_after, "lamports were not conserved");
+
+        Ok(())
+    }
+}
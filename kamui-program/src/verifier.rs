@@ -0,0 +1,83 @@
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    crate::{
+        error::VrfCoordinatorError,
+        instruction::VerifyVrfInput,
+    },
+    mangekyou_core::{ecvrf, ecvrf_edwards25519},
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        msg,
+        program::set_return_data,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+/// ECVRF suite of a proof submitted in `FulfillRandomness`. Both suites prove
+/// as gamma || c || s in [crate::state::PROOF_LEN] bytes under a 32-byte key.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "idl", derive(borsh::BorshSchema))]
+pub enum VrfSuite {
+    /// The `sol_vrf` Ristretto255 suite of [mangekyou_core::ecvrf], see
+    /// [verify_proof]
+    #[default]
+    Ristretto255Sha512,
+    /// ECVRF-EDWARDS25519-SHA512-TAI of RFC 9381, for oracles on standard
+    /// ECVRF stacks, see [mangekyou_core::ecvrf_edwards25519]
+    Edwards25519Sha512Tai,
+}
+
+impl VrfSuite {
+    /// Verifies `proof_bytes` in this suite for `alpha_string` under
+    /// `public_key_bytes` and returns the 64-byte VRF output.
+    pub fn verify(
+        self,
+        alpha_string: &[u8],
+        proof_bytes: &[u8],
+        public_key_bytes: &[u8],
+    ) -> Result<[u8; 64], VrfCoordinatorError> {
+        match self {
+            Self::Ristretto255Sha512 => verify_proof(alpha_string, proof_bytes, public_key_bytes),
+            Self::Edwards25519Sha512Tai => {
+                ecvrf_edwards25519::verify(proof_bytes, alpha_string, public_key_bytes)
+                    .map_err(|_| VrfCoordinatorError::InvalidVrfProof)
+            }
+        }
+    }
+}
+
+/// Verifies an ECVRF proof (gamma || c || s) for `alpha_string` under
+/// `public_key_bytes` and returns the 64-byte VRF output.
+pub fn verify_proof(
+    alpha_string: &[u8],
+    proof_bytes: &[u8],
+    public_key_bytes: &[u8],
+) -> Result<[u8; 64], VrfCoordinatorError> {
+    ecvrf::verify(proof_bytes, alpha_string, public_key_bytes)
+        .map_err(|_| VrfCoordinatorError::InvalidVrfProof)
+}
+
+/// Processor for the standalone verifier program: takes a borsh-encoded
+/// [`VerifyVrfInput`] as instruction data and returns the VRF output as
+/// return data.
+pub fn process_verify(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let input = VerifyVrfInput::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let output = match verify_proof(&input.alpha_string, &input.proof_bytes, &input.public_key_bytes) {
+        Ok(output) => output,
+        Err(e) => {
+            msg!("VRF Verifier: Proof rejected");
+            return Err(e.into());
+        }
+    };
+
+    set_return_data(&output);
+    Ok(())
+}
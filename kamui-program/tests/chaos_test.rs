@@ -0,0 +1,1196 @@
+//! Oracle failure-mode tests: every rejected fulfillment must leave the
+//! coordinator, the consumer and the oracle accounts exactly as they were.
+
+use {
+    borsh::BorshDeserialize,
+    kamui_program::{
+        audit_log::{AdminAction, AdminAuditLog, ADMIN_AUDIT_LOG_SEED},
+        error::VrfCoordinatorError,
+        groth16::{self, Groth16Proof, Groth16VerifyingKey, ZK_VERIFYING_KEY_SEED},
+        instruction::VrfCoordinatorInstruction,
+        pda::{derive_config_pda, derive_oracle_config_pda, derive_request_pda, derive_vrf_result_pda},
+        state::{CoordinatorConfig, CoordinatorStats, FeeAuction, FeeSchedule, OracleConfig, Schedule, RandomnessRequest, RequestStatus, Subscription, VrfResult, ACCOUNT_HEADER_LEN, DEFAULT_BACKSTOP_TIMEOUT_SLOTS, DIRECT_REQUEST_SEED, subscription_token_address, subscription_vault_address},
+        example_consumer::{GameInstruction, GameState},
+        verifier::VrfSuite,
+    },
+    solana_program::{
+        alt_bn128::prelude::{alt_bn128_addition, alt_bn128_multiplication},
+        instruction::{AccountMeta, Instruction, InstructionError},
+        pubkey::Pubkey,
+        system_program,
+        system_instruction,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        account::AccountSharedData,
+        signature::Keypair,
+        signer::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+    mangekyou::kamui_vrf::{
+        ecvrf::{ECVRFKeyPair, ECVRFPrivateKey},
+        VRFKeyPair,
+        VRFProof,
+    },
+};
+
+const ORACLE_VRF_SECRET_KEY: [u8; 32] = [7u8; 32];
+
+struct Oracle {
+    authority: Keypair,
+    config: Pubkey,
+}
+
+struct Game {
+    owner: Keypair,
+    state: Pubkey,
+}
+
+struct Chaos {
+    context: ProgramTestContext,
+    vrf_program_id: Pubkey,
+    game_program_id: Pubkey,
+    subscription_owner: Keypair,
+    subscription: Pubkey,
+    /// Coordinator config, with the payer as admin
+    config: Pubkey,
+    vrf_keypair: ECVRFKeyPair,
+}
+
+impl Chaos {
+    async fn new() -> Self {
+        let vrf_program_id = Pubkey::new_unique();
+        let game_program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new(
+            "kamui_program",
+            vrf_program_id,
+            processor!(kamui_program::process_instruction),
+        );
+        program_test.add_program(
+            "example_consumer",
+            game_program_id,
+            processor!(kamui_program::example_consumer::process_instruction),
+        );
+        let context = program_test.start_with_context().await;
+
+        let secret_key = ECVRFPrivateKey::from_bytes(&ORACLE_VRF_SECRET_KEY).unwrap();
+        let mut chaos = Self {
+            context,
+            vrf_program_id,
+            game_program_id,
+            subscription_owner: Keypair::new(),
+            subscription: Pubkey::default(),
+            config: Pubkey::default(),
+            vrf_keypair: ECVRFKeyPair::from(secret_key),
+        };
+        chaos.config = chaos.initialize_coordinator().await;
+
+        let subscription_account = Keypair::new();
+        let subscription_owner = chaos.subscription_owner.insecure_clone();
+        chaos.fund(&subscription_owner.pubkey()).await;
+        chaos.send(
+            &[Instruction {
+                program_id: vrf_program_id,
+                accounts: vec![
+                    AccountMeta::new(subscription_owner.pubkey(), true),
+                    AccountMeta::new(subscription_account.pubkey(), true),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+                data: borsh::to_vec(&VrfCoordinatorInstruction::CreateSubscription {
+                    min_balance: 0,
+                    confirmations: 1,
+                }).unwrap(),
+            }],
+            &[&subscription_owner, &subscription_account],
+        ).await.unwrap();
+        chaos.subscription = subscription_account.pubkey();
+        chaos
+    }
+
+    async fn send(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        let payer = self.context.payer.insecure_clone();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+        let recent_blockhash = self.context.get_new_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            recent_blockhash,
+        );
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    async fn fund(&mut self, account: &Pubkey) {
+        let payer = self.context.payer.pubkey();
+        self.send(&[system_instruction::transfer(&payer, account, 1_000_000_000)], &[]).await.unwrap();
+    }
+
+    async fn account_data(&mut self, address: &Pubkey) -> Option<Vec<u8>> {
+        self.context.banks_client.get_account(*address).await.unwrap().map(|account| account.data)
+    }
+
+    fn audit_log(&self) -> Pubkey {
+        Pubkey::find_program_address(&[ADMIN_AUDIT_LOG_SEED], &self.vrf_program_id).0
+    }
+
+    async fn oracle_config(&mut self, oracle: &Oracle) -> OracleConfig {
+        self.oracle_config_at(&oracle.config).await
+    }
+
+    async fn oracle_config_at(&mut self, address: &Pubkey) -> OracleConfig {
+        let data = self.account_data(address).await.unwrap();
+        OracleConfig::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap()
+    }
+
+    async fn register_oracle(&mut self) -> Oracle {
+        let authority = Keypair::new();
+        let (config, _) = derive_oracle_config_pda(&self.vrf_program_id, &authority.pubkey());
+        let oracle = Oracle { authority, config };
+        self.fund(&oracle.authority.pubkey()).await;
+        let mut vrf_key = [0u8; 32];
+        vrf_key.copy_from_slice(self.vrf_keypair.pk.as_ref());
+        let admin = self.context.payer.pubkey();
+        self.send(
+            &[Instruction {
+                program_id: self.vrf_program_id,
+                accounts: vec![
+                    AccountMeta::new(admin, true),
+                    AccountMeta::new_readonly(self.config, false),
+                    AccountMeta::new(oracle.config, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new(self.audit_log(), false),
+                ],
+                data: borsh::to_vec(&VrfCoordinatorInstruction::RegisterOracle {
+                    oracle_key: oracle.authority.pubkey(),
+                    vrf_key,
+                }).unwrap(),
+            }],
+            &[],
+        ).await.unwrap();
+        oracle
+    }
+
+    async fn create_game(&mut self) -> Game {
+        let owner = Keypair::new();
+        self.fund(&owner.pubkey()).await;
+        let (state, _bump) = Pubkey::find_program_address(
+            &[b"game_state", owner.pubkey().as_ref()],
+            &self.game_program_id,
+        );
+        let payer = self.context.payer.pubkey();
+        self.send(
+            &[Instruction {
+                program_id: self.game_program_id,
+                accounts: vec![
+                    AccountMeta::new(owner.pubkey(), true),
+                    AccountMeta::new(state, false),
+                    AccountMeta::new_readonly(self.subscription, false),
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                    AccountMeta::new_readonly(self.vrf_program_id, false),
+                ],
+                data: borsh::to_vec(&GameInstruction::Initialize).unwrap(),
+            }],
+            &[&owner],
+        ).await.unwrap();
+
+        // The game owner requests against the shared subscription
+        let subscription_owner = self.subscription_owner.insecure_clone();
+        self.send(
+            &[Instruction {
+                program_id: self.vrf_program_id,
+                accounts: vec![
+                    AccountMeta::new(subscription_owner.pubkey(), true),
+                    AccountMeta::new(self.subscription, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+                data: borsh::to_vec(&VrfCoordinatorInstruction::AddConsumer { consumer: owner.pubkey() }).unwrap(),
+            }],
+            &[&subscription_owner],
+        ).await.unwrap();
+        Game { owner, state }
+    }
+
+    /// Requests randomness directly from the coordinator on behalf of `game`,
+    /// optionally assigning it to `assigned` and with arbitrary callback data.
+    async fn request(&mut self, game: &Game, assigned: Option<&Oracle>, callback_data: Vec<u8>) -> Pubkey {
+        self.request_words(game, assigned, callback_data, 1).await
+    }
+
+    async fn request_words(
+        &mut self,
+        game: &Game,
+        assigned: Option<&Oracle>,
+        callback_data: Vec<u8>,
+        num_words: u32,
+    ) -> Pubkey {
+        self.send_request(game, assigned, VrfCoordinatorInstruction::RequestRandomness {
+            seed: [9u8; 32],
+            callback_data,
+            num_words,
+            minimum_confirmations: 1,
+            callback_gas_limit: 100_000,
+            priority_fee: 0,
+        }).await
+    }
+
+    /// Requests randomness for `game` that may not be fulfilled before `not_before_slot`.
+    async fn request_at(&mut self, game: &Game, not_before_slot: u64) -> Pubkey {
+        self.send_request(game, None, VrfCoordinatorInstruction::RequestRandomnessAt {
+            seed: [9u8; 32],
+            callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+            num_words: 1,
+            minimum_confirmations: 1,
+            callback_gas_limit: 100_000,
+            not_before_slot,
+        }).await
+    }
+
+    async fn send_request(
+        &mut self,
+        game: &Game,
+        assigned: Option<&Oracle>,
+        instruction: VrfCoordinatorInstruction,
+    ) -> Pubkey {
+        self.try_send_request(game, assigned, &instruction).await.unwrap()
+    }
+
+    async fn try_send_request(
+        &mut self,
+        game: &Game,
+        assigned: Option<&Oracle>,
+        instruction: &VrfCoordinatorInstruction,
+    ) -> Result<Pubkey, BanksClientError> {
+        let subscription_address = self.subscription;
+        let subscription_data = self.account_data(&subscription_address).await.unwrap();
+        let subscription = Subscription::try_from_slice(&subscription_data[ACCOUNT_HEADER_LEN..]).unwrap();
+        let (request, _bump) = derive_request_pda(&self.vrf_program_id, &self.subscription, subscription.nonce);
+        let mut accounts = vec![
+            AccountMeta::new(game.owner.pubkey(), true),
+            AccountMeta::new(request, false),
+            AccountMeta::new(self.subscription, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        if let Some(oracle) = assigned {
+            accounts.push(AccountMeta::new_readonly(oracle.config, false));
+        }
+        accounts.push(AccountMeta::new_readonly(self.config, false));
+        let owner = game.owner.insecure_clone();
+        self.send(
+            &[Instruction {
+                program_id: self.vrf_program_id,
+                accounts,
+                data: borsh::to_vec(instruction).unwrap(),
+            }],
+            &[&owner],
+        ).await?;
+        Ok(request)
+    }
+
+    /// Requests randomness for `game` paid directly, without the subscription.
+    async fn request_direct(&mut self, game: &Game, config: Pubkey) -> Pubkey {
+        let seed = [9u8; 32];
+        let (request, _) = Pubkey::find_program_address(
+            &[DIRECT_REQUEST_SEED, game.owner.pubkey().as_ref(), &seed],
+            &self.vrf_program_id,
+        );
+        let instruction = Instruction {
+            program_id: self.vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(game.owner.pubkey(), true),
+                AccountMeta::new(request, false),
+                AccountMeta::new_readonly(config, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::RequestRandomnessDirect {
+                seed,
+                callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+                num_words: 1,
+                callback_gas_limit: 100_000,
+            }).unwrap(),
+        };
+        let owner = game.owner.insecure_clone();
+        self.send(&[instruction], &[&owner]).await.unwrap();
+        request
+    }
+
+    async fn request_for_game(&mut self, game: &Game, assigned: Option<&Oracle>) -> Pubkey {
+        let callback_data = borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap();
+        self.request(game, assigned, callback_data).await
+    }
+
+    /// Fulfills `request`, writing into the result and state accounts of `target`.
+    async fn fulfill(&mut self, oracle: &Oracle, request: &Pubkey, target: &Game, alpha: &[u8]) -> Result<(), BanksClientError> {
+        let proof = self.vrf_keypair.prove(alpha).to_bytes_legacy();
+        let public_key = self.vrf_keypair.pk.as_ref().to_vec();
+        self.fulfill_with_proof(oracle, request, target, proof, public_key).await
+    }
+
+    /// Submits raw `proof` and `public_key` bytes for `request`.
+    async fn fulfill_with_proof(
+        &mut self,
+        oracle: &Oracle,
+        request: &Pubkey,
+        target: &Game,
+        proof: Vec<u8>,
+        public_key: Vec<u8>,
+    ) -> Result<(), BanksClientError> {
+        let subscription = self.subscription;
+        self.fulfill_with_accounts(oracle, request, target, proof, public_key, subscription, vec![]).await
+    }
+
+    /// Like [Self::fulfill_with_proof], for a request of `subscription` and
+    /// passing `trailing` optional accounts.
+    #[allow(clippy::too_many_arguments)]
+    async fn fulfill_with_accounts(
+        &mut self,
+        oracle: &Oracle,
+        request: &Pubkey,
+        target: &Game,
+        proof: Vec<u8>,
+        public_key: Vec<u8>,
+        subscription: Pubkey,
+        trailing: Vec<AccountMeta>,
+    ) -> Result<(), BanksClientError> {
+        let mut instruction = Instruction {
+            program_id: self.vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(oracle.authority.pubkey(), true),
+                AccountMeta::new(*request, false),
+                AccountMeta::new(self.vrf_result(request), false),
+                AccountMeta::new_readonly(self.game_program_id, false),
+                AccountMeta::new(subscription, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(self.game_program_id, false),
+                AccountMeta::new(target.state, false),
+                AccountMeta::new(oracle.config, false),
+                AccountMeta::new_readonly(derive_config_pda(&self.vrf_program_id).0, false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::FulfillRandomness {
+                proof,
+                public_key,
+                suite: VrfSuite::default(),
+            }).unwrap(),
+        };
+        instruction.accounts.extend(trailing);
+        let authority = oracle.authority.insecure_clone();
+        self.send(&[instruction], &[&authority]).await
+    }
+
+    /// Delivers the next `num_words` words of `request` into `target`'s result.
+    async fn append_words(&mut self, oracle: &Oracle, request: &Pubkey, target: &Game, num_words: u32) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: self.vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(oracle.authority.pubkey(), true),
+                AccountMeta::new(*request, false),
+                AccountMeta::new(self.vrf_result(request), false),
+                AccountMeta::new_readonly(self.subscription, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(oracle.config, false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::AppendWords {
+                proof: self.vrf_keypair.prove(&[9u8; 32]).to_bytes_legacy(),
+                num_words,
+            }).unwrap(),
+        };
+        let authority = oracle.authority.insecure_clone();
+        self.send(&[instruction], &[&authority]).await
+    }
+
+    /// Gives the subscription `balance` to pay fees from, as if it had been funded.
+    async fn set_subscription_balance(&mut self, balance: u64) -> u64 {
+        let mut account = self.context.banks_client.get_account(self.subscription).await.unwrap().unwrap();
+        let mut funded = Subscription::try_from_slice(&account.data[ACCOUNT_HEADER_LEN..]).unwrap();
+        funded.balance = balance;
+        funded.total_funded = balance;
+        borsh::to_writer(&mut account.data[ACCOUNT_HEADER_LEN..], &funded).unwrap();
+        self.context.set_account(&self.subscription, &AccountSharedData::from(account));
+        balance
+    }
+
+    async fn subscription_balance(&mut self) -> u64 {
+        let subscription = self.subscription;
+        let data = self.account_data(&subscription).await.unwrap();
+        Subscription::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().balance
+    }
+
+    /// Creates the stats account, paid by the payer.
+    async fn initialize_stats(&mut self) -> Pubkey {
+        let (stats, _) = Pubkey::find_program_address(&[CoordinatorStats::SEED], &self.vrf_program_id);
+        let instruction = Instruction {
+            program_id: self.vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(self.context.payer.pubkey(), true),
+                AccountMeta::new(stats, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::InitializeStats).unwrap(),
+        };
+        self.send(&[instruction], &[]).await.unwrap();
+        stats
+    }
+
+    /// Initializes the coordinator with the payer as admin.
+    async fn initialize_coordinator(&mut self) -> Pubkey {
+        let admin = self.context.payer.pubkey();
+        let (config, _) = derive_config_pda(&self.vrf_program_id);
+        let instruction = Instruction {
+            program_id: self.vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(admin, true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(self.audit_log(), false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::InitializeCoordinator {
+                fee_schedule: FeeSchedule::default(),
+                bridge_program: Pubkey::default(),
+            }).unwrap(),
+        };
+        self.send(&[instruction], &[]).await.unwrap();
+        config
+    }
+
+    /// Sets the fee schedule requests are charged under and returns the config.
+    async fn set_fee_schedule(&mut self, fee_schedule: FeeSchedule) -> Pubkey {
+        let admin = self.context.payer.pubkey();
+        let instruction = Instruction {
+            program_id: self.vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(admin, true),
+                AccountMeta::new(self.config, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(self.audit_log(), false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::SetFeeSchedule { fee_schedule }).unwrap(),
+        };
+        self.send(&[instruction], &[]).await.unwrap();
+        self.config
+    }
+
+    /// Sets `verifying_key`, with the payer as admin.
+    async fn set_zk_verifying_key(&mut self, verifying_key: Groth16VerifyingKey) {
+        let admin = self.context.payer.pubkey();
+        let config = self.config;
+        let (key_account, _) = Pubkey::find_program_address(&[ZK_VERIFYING_KEY_SEED], &self.vrf_program_id);
+        let instruction = Instruction {
+            program_id: self.vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(admin, true),
+                AccountMeta::new_readonly(config, false),
+                AccountMeta::new(key_account, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(self.audit_log(), false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::SetZkVerifyingKey { verifying_key: Box::new(verifying_key) }).unwrap(),
+        };
+        self.send(&[instruction], &[]).await.unwrap();
+    }
+
+    async fn fulfill_zk(
+        &mut self,
+        oracle: &Oracle,
+        request: &Pubkey,
+        target: &Game,
+        output: [u8; 64],
+        proof: Groth16Proof,
+    ) -> Result<(), BanksClientError> {
+        let (key_account, _) = Pubkey::find_program_address(&[ZK_VERIFYING_KEY_SEED], &self.vrf_program_id);
+        let instruction = Instruction {
+            program_id: self.vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(oracle.authority.pubkey(), true),
+                AccountMeta::new(*request, false),
+                AccountMeta::new(self.vrf_result(request), false),
+                AccountMeta::new_readonly(self.game_program_id, false),
+                AccountMeta::new(self.subscription, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(self.game_program_id, false),
+                AccountMeta::new(target.state, false),
+                AccountMeta::new(oracle.config, false),
+                AccountMeta::new_readonly(key_account, false),
+                AccountMeta::new_readonly(derive_config_pda(&self.vrf_program_id).0, false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::FulfillRandomnessZk { output, proof: Box::new(proof) }).unwrap(),
+        };
+        let authority = oracle.authority.insecure_clone();
+        self.send(&[instruction], &[&authority]).await
+    }
+
+    async fn finalize(&mut self, oracle: &Oracle, request: &Pubkey, target: &Game) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: self.vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(oracle.authority.pubkey(), true),
+                AccountMeta::new(*request, false),
+                AccountMeta::new_readonly(self.vrf_result(request), false),
+                AccountMeta::new(self.subscription, false),
+                AccountMeta::new_readonly(self.game_program_id, false),
+                AccountMeta::new(target.state, false),
+                AccountMeta::new(oracle.config, false),
+                AccountMeta::new_readonly(derive_config_pda(&self.vrf_program_id).0, false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::FinalizeFulfillment).unwrap(),
+        };
+        let authority = oracle.authority.insecure_clone();
+        self.send(&[instruction], &[&authority]).await
+    }
+
+    /// Raw data of every account a fulfillment can touch, for before/after comparison.
+    async fn snapshot(&mut self, oracle: &Oracle, request: &Pubkey, games: &[&Game]) -> Vec<Option<Vec<u8>>> {
+        let mut addresses = vec![self.subscription, oracle.config, *request];
+        addresses.push(self.vrf_result(request));
+        for game in games {
+            addresses.push(game.state);
+        }
+        let mut snapshot = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            snapshot.push(self.account_data(&address).await);
+        }
+        snapshot
+    }
+
+    /// Address of the result of `request`
+    fn vrf_result(&self, request: &Pubkey) -> Pubkey {
+        derive_vrf_result_pda(&self.vrf_program_id, request).0
+    }
+
+    async fn request_status(&mut self, request: &Pubkey) -> RequestStatus {
+        let data = self.account_data(request).await.unwrap();
+        RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().status
+    }
+
+    async fn game_state(&mut self, game: &Game) -> GameState {
+        let data = self.account_data(&game.state).await.unwrap();
+        GameState::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap()
+    }
+}
+
+fn instruction_error(result: Result<(), BanksClientError>) -> InstructionError {
+    match result.expect_err("fulfillment should have been rejected").unwrap() {
+        TransactionError::InstructionError(0, error) => error,
+        error => panic!("unexpected transaction error: {:?}", error),
+    }
+}
+
+fn custom(error: VrfCoordinatorError) -> InstructionError {
+    InstructionError::Custom(error as u32)
+}
+
+/// `scalar` times the BN254 G1 generator
+fn g1_mul(scalar: u8) -> [u8; 64] {
+    let mut input = [0u8; 96];
+    input[31] = 1;
+    input[63] = 2;
+    input[95] = scalar;
+    alt_bn128_multiplication(&input).unwrap().try_into().unwrap()
+}
+
+fn g1_add(p: &[u8; 64], q: &[u8; 64]) -> [u8; 64] {
+    alt_bn128_addition(&[&p[..], &q[..]].concat()).unwrap().try_into().unwrap()
+}
+
+/// The BN254 G2 generator, in EIP-197 encoding
+const G2_GENERATOR: [u8; 128] = [
+    0x19, 0x8e, 0x93, 0x93, 0x92, 0x0d, 0x48, 0x3a, 0x72, 0x60, 0xbf, 0xb7, 0x31, 0xfb, 0x5d, 0x25,
+    0xf1, 0xaa, 0x49, 0x33, 0x35, 0xa9, 0xe7, 0x12, 0x97, 0xe4, 0x85, 0xb7, 0xae, 0xf3, 0x12, 0xc2,
+    0x18, 0x00, 0xde, 0xef, 0x12, 0x1f, 0x1e, 0x76, 0x42, 0x6a, 0x00, 0x66, 0x5e, 0x5c, 0x44, 0x79,
+    0x67, 0x43, 0x22, 0xd4, 0xf7, 0x5e, 0xda, 0xdd, 0x46, 0xde, 0xbd, 0x5c, 0xd9, 0x92, 0xf6, 0xed,
+    0x09, 0x06, 0x89, 0xd0, 0x58, 0x5f, 0xf0, 0x75, 0xec, 0x9e, 0x99, 0xad, 0x69, 0x0c, 0x33, 0x95,
+    0xbc, 0x4b, 0x31, 0x33, 0x70, 0xb3, 0x8e, 0xf3, 0x55, 0xac, 0xda, 0xdc, 0xd1, 0x22, 0x97, 0x5b,
+    0x12, 0xc8, 0x5e, 0xa5, 0xdb, 0x8c, 0x6d, 0xeb, 0x4a, 0xab, 0x71, 0x80, 0x8d, 0xcb, 0x40, 0x8f,
+    0xe3, 0xd1, 0xe7, 0x69, 0x0c, 0x43, 0xd3, 0x7b, 0x4c, 0xe6, 0xcc, 0x01, 0x66, 0xfa, 0x7d, 0xaa,
+];
+
+#[tokio::test]
+async fn test_duplicate_fulfillment_rejected() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let request = chaos.request_for_game(&game, None).await;
+
+    chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await.unwrap();
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Fulfilled);
+
+    let before = chaos.snapshot(&oracle, &request, &[&game]).await;
+    let result = chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::RequestAlreadyFulfilled));
+    assert_eq!(chaos.snapshot(&oracle, &request, &[&game]).await, before);
+
+    // A second registered oracle cannot re-fulfill it either
+    let other_oracle = chaos.register_oracle().await;
+    let before = chaos.snapshot(&other_oracle, &request, &[&game]).await;
+    let result = chaos.fulfill(&other_oracle, &request, &game, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::RequestAlreadyFulfilled));
+    assert_eq!(chaos.snapshot(&other_oracle, &request, &[&game]).await, before);
+}
+
+#[tokio::test]
+async fn test_fulfill_cancelled_request_rejected() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let request = chaos.request_for_game(&game, None).await;
+
+    let subscription_owner = chaos.subscription_owner.insecure_clone();
+    let subscription = chaos.subscription;
+    let vrf_program_id = chaos.vrf_program_id;
+    chaos.send(
+        &[Instruction {
+            program_id: vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(subscription_owner.pubkey(), true),
+                AccountMeta::new(request, false),
+                AccountMeta::new(subscription, false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::CancelRequest).unwrap(),
+        }],
+        &[&subscription_owner],
+    ).await.unwrap();
+    assert!(chaos.account_data(&request).await.is_none());
+
+    let before = chaos.snapshot(&oracle, &request, &[&game]).await;
+    let result = chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), InstructionError::IncorrectProgramId);
+    assert_eq!(chaos.snapshot(&oracle, &request, &[&game]).await, before);
+    assert!(chaos.account_data(&chaos.vrf_result(&request)).await.is_none());
+}
+
+#[tokio::test]
+async fn test_backstop_before_timeout_rejected() {
+    let mut chaos = Chaos::new().await;
+    let assigned = chaos.register_oracle().await;
+    let backstop = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+
+    let request = chaos.request_for_game(&game, Some(&assigned)).await;
+    let request_block = {
+        let data = chaos.account_data(&request).await.unwrap();
+        RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().request_block
+    };
+
+    let before = chaos.snapshot(&backstop, &request, &[&game]).await;
+    let result = chaos.fulfill(&backstop, &request, &game, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::UnauthorizedOracle));
+    assert_eq!(chaos.snapshot(&backstop, &request, &[&game]).await, before);
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Pending);
+
+    // Once the window has elapsed the backstop oracle may take over, passing
+    // the assigned oracle's config to record its miss
+    chaos.context.warp_to_slot(request_block + DEFAULT_BACKSTOP_TIMEOUT_SLOTS + 1).unwrap();
+    let result = chaos.fulfill(&backstop, &request, &game, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), InstructionError::NotEnoughAccountKeys);
+    let (proof, public_key) = (chaos.vrf_keypair.prove(&[9u8; 32]).to_bytes_legacy(), chaos.vrf_keypair.pk.as_ref().to_vec());
+    let subscription = chaos.subscription;
+    chaos.fulfill_with_accounts(
+        &backstop, &request, &game, proof, public_key, subscription,
+        vec![AccountMeta::new(assigned.config, false)],
+    ).await.unwrap();
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Fulfilled);
+    let assigned_config = chaos.oracle_config(&assigned).await;
+    assert_eq!((assigned_config.failures, assigned_config.fulfillments), (1, 0));
+    assert_eq!(chaos.oracle_config(&backstop).await.fulfillments, 1);
+    let state = chaos.game_state(&game).await;
+    assert!(state.current_number >= 1 && state.current_number <= 100);
+
+    // And the assigned oracle arriving late is turned away
+    let result = chaos.fulfill(&assigned, &request, &game, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::RequestAlreadyFulfilled));
+}
+
+#[tokio::test]
+async fn test_time_locked_request_rejected_before_slot() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+
+    let not_before_slot = chaos.context.banks_client.get_root_slot().await.unwrap() + 100;
+    let request = chaos.request_at(&game, not_before_slot).await;
+
+    let before = chaos.snapshot(&oracle, &request, &[&game]).await;
+    let result = chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::RequestTimeLocked));
+    let result = chaos.append_words(&oracle, &request, &game, 1).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::RequestTimeLocked));
+    assert_eq!(chaos.snapshot(&oracle, &request, &[&game]).await, before);
+
+    chaos.context.warp_to_slot(not_before_slot).unwrap();
+    chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await.unwrap();
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Fulfilled);
+}
+
+#[tokio::test]
+async fn test_crank_opens_scheduled_requests_when_due() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let (vrf_program_id, subscription, config) = (chaos.vrf_program_id, chaos.subscription, chaos.config);
+
+    let seed = [3u8; 32];
+    let (schedule, _) = Pubkey::find_program_address(
+        &[Schedule::SEED, game.owner.pubkey().as_ref(), &seed],
+        &vrf_program_id,
+    );
+    let owner = game.owner.insecure_clone();
+    chaos.send(
+        &[Instruction {
+            program_id: vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new(schedule, false),
+                AccountMeta::new_readonly(subscription, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::CreateSchedule {
+                seed,
+                interval_slots: 50,
+                start_slot: 0,
+                num_words: 1,
+                callback_gas_limit: 100_000,
+                callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+            }).unwrap(),
+        }],
+        &[&owner],
+    ).await.unwrap();
+
+    // Any keeper may crank, and the request is the schedule owner's
+    let keeper = Keypair::new();
+    chaos.fund(&keeper.pubkey()).await;
+    let crank = |nonce: u64| {
+        let (request, _) = derive_request_pda(&vrf_program_id, &subscription, nonce);
+        (request, Instruction {
+            program_id: vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(keeper.pubkey(), true),
+                AccountMeta::new(schedule, false),
+                AccountMeta::new(request, false),
+                AccountMeta::new(subscription, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(config, false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::CrankSchedule).unwrap(),
+        })
+    };
+
+    let (first, instruction) = crank(0);
+    chaos.send(&[instruction], &[&keeper]).await.unwrap();
+    let data = chaos.account_data(&first).await.unwrap();
+    let request = RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap();
+    assert_eq!(request.requester, game.owner.pubkey());
+
+    let (_, instruction) = crank(1);
+    let result = chaos.send(&[instruction], &[&keeper]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::ScheduleNotDue));
+
+    // Each scheduled request has its own seed
+    assert_ne!(request.seed, [9u8; 32]);
+    chaos.fulfill(&oracle, &first, &game, &request.seed).await.unwrap();
+    assert!((1..=100).contains(&chaos.game_state(&game).await.current_number));
+
+    chaos.context.warp_to_slot(request.request_block + 50).unwrap();
+    let (second, instruction) = crank(1);
+    chaos.send(&[instruction], &[&keeper]).await.unwrap();
+    assert_eq!(chaos.request_status(&second).await, RequestStatus::Pending);
+}
+
+#[tokio::test]
+async fn test_fee_rebates_unused_callback_units() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let fees = FeeSchedule {
+        base_fee: 1_000,
+        fee_per_word: 100,
+        fee_per_million_callback_units: 1_000_000,
+        priority_fee: 0,
+    };
+    chaos.set_fee_schedule(fees).await;
+    // The quote is reserved when requested and released when fulfilled
+    let funded = chaos.set_subscription_balance(1_000_000).await;
+    let request = chaos.request_for_game(&game, None).await;
+    assert_eq!(chaos.subscription_balance().await, funded - fees.quote(1, 100_000, 0).unwrap());
+
+    let subscription_key = chaos.subscription;
+
+    let proof = chaos.vrf_keypair.prove(&[9u8; 32]).to_bytes_legacy();
+    let public_key = chaos.vrf_keypair.pk.as_ref().to_vec();
+    chaos.fulfill_with_accounts(&oracle, &request, &game, proof, public_key, subscription_key, vec![]).await.unwrap();
+
+    // The callback is far cheaper than its 100_000 unit limit, which is rebated
+    let fee = funded - chaos.subscription_balance().await;
+    let quote = fees.quote(1, 100_000, 0).unwrap();
+    assert!(fee > 1_000 + 100 && fee < quote);
+
+    let data = chaos.account_data(&oracle.config).await.unwrap();
+    assert_eq!(OracleConfig::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().accrued_fees, fee);
+}
+
+#[tokio::test]
+async fn test_open_market_request_pays_cleared_price() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let fee_auction = FeeAuction { start_price: 10_000, end_price: 2_000, duration_slots: 100 };
+
+    // The subscription must cover the highest price
+    let instruction = VrfCoordinatorInstruction::RequestRandomnessOpenMarket {
+        seed: [9u8; 32],
+        callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap(),
+        num_words: 1,
+        minimum_confirmations: 1,
+        callback_gas_limit: 100_000,
+        fee_auction,
+    };
+    chaos.set_subscription_balance(9_999).await;
+    assert!(chaos.try_send_request(&game, None, &instruction).await.is_err());
+    let funded = chaos.set_subscription_balance(10_000).await;
+    let request = chaos.send_request(&game, None, instruction).await;
+
+    let data = chaos.account_data(&request).await.unwrap();
+    let request_block = RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().request_block;
+    chaos.context.warp_to_slot(request_block + 40).unwrap();
+    chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await.unwrap();
+
+    // The price has declined by at least 40 slots' worth
+    let price = funded - chaos.subscription_balance().await;
+    assert!((fee_auction.end_price..=fee_auction.price_at(40)).contains(&price));
+
+    let data = chaos.account_data(&oracle.config).await.unwrap();
+    assert_eq!(OracleConfig::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().accrued_fees, price);
+}
+
+#[tokio::test]
+async fn test_direct_request_fee_goes_to_oracle() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let fees = FeeSchedule { base_fee: 50_000, fee_per_word: 1_000, ..FeeSchedule::default() };
+    let config = chaos.set_fee_schedule(fees).await;
+    let request = chaos.request_direct(&game, config).await;
+
+    let fee = fees.quote(1, 100_000, 0).unwrap();
+    let held = chaos.context.banks_client.get_account(request).await.unwrap().unwrap().lamports;
+    let data = chaos.account_data(&request).await.unwrap();
+    assert!(RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().is_direct());
+
+    // Only the subscription of the request may stand in for it
+    let result = chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::InvalidRequestParameters));
+
+    let proof = chaos.vrf_keypair.prove(&[9u8; 32]).to_bytes_legacy();
+    let public_key = chaos.vrf_keypair.pk.as_ref().to_vec();
+    chaos.fulfill_with_accounts(&oracle, &request, &game, proof, public_key, system_program::id(), vec![])
+        .await
+        .unwrap();
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Fulfilled);
+    let left = chaos.context.banks_client.get_account(request).await.unwrap().unwrap().lamports;
+    assert_eq!(held - left, fee);
+}
+
+#[tokio::test]
+async fn test_stats_count_fulfillments() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let stats = chaos.initialize_stats().await;
+    let subscription = chaos.subscription;
+
+    for _ in 0..2 {
+        let request = chaos.request_for_game(&game, None).await;
+        let proof = chaos.vrf_keypair.prove(&[9u8; 32]).to_bytes_legacy();
+        let public_key = chaos.vrf_keypair.pk.as_ref().to_vec();
+        let trailing = vec![AccountMeta::new(stats, false)];
+        chaos.fulfill_with_accounts(&oracle, &request, &game, proof, public_key, subscription, trailing).await.unwrap();
+    }
+
+    let data = chaos.account_data(&stats).await.unwrap();
+    let stats = CoordinatorStats::deserialize(&mut &data[ACCOUNT_HEADER_LEN..]).unwrap();
+    assert_eq!(stats.total_fulfillments, 2);
+    assert_eq!(stats.oracle_fulfillments.len(), 1);
+    assert_eq!(stats.oracle_fulfillments[0].oracle, oracle.authority.pubkey());
+    assert_eq!(stats.oracle_fulfillments[0].fulfillments, 2);
+}
+
+#[tokio::test]
+async fn test_result_for_wrong_request_rejected() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game_a = chaos.create_game().await;
+    let game_b = chaos.create_game().await;
+
+    // Give both games an existing result account
+    let request_a = chaos.request_for_game(&game_a, None).await;
+    chaos.fulfill(&oracle, &request_a, &game_a, &[9u8; 32]).await.unwrap();
+    let request_b = chaos.request_for_game(&game_b, None).await;
+    chaos.fulfill(&oracle, &request_b, &game_b, &[9u8; 32]).await.unwrap();
+
+    // Fulfilling A's new request into B's accounts must not touch either game
+    let request_a = chaos.request_for_game(&game_a, None).await;
+    let before = chaos.snapshot(&oracle, &request_a, &[&game_a, &game_b]).await;
+    let result = chaos.fulfill(&oracle, &request_a, &game_b, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), InstructionError::InvalidSeeds);
+    assert_eq!(chaos.snapshot(&oracle, &request_a, &[&game_a, &game_b]).await, before);
+    assert_eq!(chaos.request_status(&request_a).await, RequestStatus::Pending);
+
+    // The correct accounts still work afterwards
+    chaos.fulfill(&oracle, &request_a, &game_a, &[9u8; 32]).await.unwrap();
+    assert_eq!(chaos.request_status(&request_a).await, RequestStatus::Fulfilled);
+}
+
+#[tokio::test]
+async fn test_proof_for_other_seed_rejected() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let request = chaos.request_for_game(&game, None).await;
+
+    let before = chaos.snapshot(&oracle, &request, &[&game]).await;
+    let result = chaos.fulfill(&oracle, &request, &game, &[1u8; 32]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::InvalidVrfProof));
+    assert_eq!(chaos.snapshot(&oracle, &request, &[&game]).await, before);
+}
+
+#[tokio::test]
+async fn test_adversarial_proofs_rejected() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let request = chaos.request_for_game(&game, None).await;
+
+    let proof = chaos.vrf_keypair.prove(&[9u8; 32]).to_bytes_legacy();
+    let public_key = chaos.vrf_keypair.pk.as_ref().to_vec();
+    let other_public_key = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[8u8; 32]).unwrap())
+        .pk.as_ref().to_vec();
+
+    let mut cases = Vec::new();
+    // One flipped byte in gamma, c and s
+    for index in [0, 31, 32, 47, 48, 79] {
+        let mut mutated = proof.clone();
+        mutated[index] ^= 0x01;
+        cases.push((mutated, public_key.clone()));
+    }
+    // Another oracle's public key
+    cases.push((proof.clone(), other_public_key));
+    // Truncated proof and public key
+    cases.push((proof[..proof.len() - 1].to_vec(), public_key.clone()));
+    cases.push((proof.clone(), public_key[..31].to_vec()));
+
+    for (proof, public_key) in cases {
+        let before = chaos.snapshot(&oracle, &request, &[&game]).await;
+        let result = chaos.fulfill_with_proof(&oracle, &request, &game, proof, public_key).await;
+        assert_eq!(instruction_error(result), custom(VrfCoordinatorError::InvalidVrfProof));
+        assert_eq!(chaos.snapshot(&oracle, &request, &[&game]).await, before);
+    }
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Pending);
+}
+
+#[tokio::test]
+async fn test_failed_callback_rolls_back_fulfillment() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+
+    // Callback data the consumer cannot decode makes the callback CPI fail
+    let request = chaos.request(&game, None, vec![0xff, 0xff]).await;
+
+    let before = chaos.snapshot(&oracle, &request, &[&game]).await;
+    let result = chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await;
+    assert!(result.is_err());
+    assert_eq!(chaos.snapshot(&oracle, &request, &[&game]).await, before);
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Pending);
+    assert!(chaos.account_data(&chaos.vrf_result(&request)).await.is_none());
+
+    let oracle_data = chaos.account_data(&oracle.config).await.unwrap();
+    assert_eq!(OracleConfig::try_from_slice(&oracle_data[ACCOUNT_HEADER_LEN..]).unwrap().accrued_fees, 0);
+}
+
+#[tokio::test]
+async fn test_audit_flags_tampered_subscription() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let request = chaos.request_for_game(&game, None).await;
+    chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await.unwrap();
+
+    let subscription_key = chaos.subscription;
+    let audit = Instruction {
+        program_id: chaos.vrf_program_id,
+        accounts: vec![
+            AccountMeta::new(subscription_key, false),
+            AccountMeta::new_readonly(subscription_vault_address(&chaos.vrf_program_id, &subscription_key), false),
+            AccountMeta::new_readonly(subscription_token_address(&chaos.vrf_program_id, &subscription_key), false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::AuditSubscription).unwrap(),
+    };
+    let decode = |data: Vec<u8>| Subscription::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap();
+
+    // A consistent subscription passes and records the audited nonce
+    chaos.send(&[audit.clone()], &[]).await.unwrap();
+    let audited = decode(chaos.account_data(&subscription_key).await.unwrap());
+    assert_eq!(audited.expected_balance(), Some(audited.balance));
+    assert_eq!(audited.audited_nonce, audited.nonce);
+
+    // Credit the balance behind the program's back
+    let mut account = chaos.context.banks_client.get_account(subscription_key).await.unwrap().unwrap();
+    let mut tampered = decode(account.data.clone());
+    tampered.balance += 1;
+    tampered.audited_nonce += 1;
+    borsh::to_writer(&mut account.data[ACCOUNT_HEADER_LEN..], &tampered).unwrap();
+    chaos.context.set_account(&subscription_key, &AccountSharedData::from(account));
+
+    let payer = chaos.context.payer.insecure_clone();
+    let recent_blockhash = chaos.context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(&[audit], Some(&payer.pubkey()), &[&payer], recent_blockhash);
+    let result = chaos.context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    result.result.unwrap();
+    let logs = result.metadata.unwrap().log_messages;
+    assert!(logs.iter().any(|log| log.contains("VRF_EVENT:")), "no discrepancy event in {:?}", logs);
+
+    // The audit does not advance past a discrepancy
+    let after = decode(chaos.account_data(&subscription_key).await.unwrap());
+    assert_eq!(after.audited_nonce, tampered.audited_nonce);
+}
+
+#[tokio::test]
+async fn test_chunked_fulfillment_requires_every_word() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let callback_data = borsh::to_vec(&GameInstruction::ConsumeRandomness).unwrap();
+    let request = chaos.request_words(&game, None, callback_data, 100).await;
+
+    // Nothing to finalize before the first chunk
+    let result = chaos.finalize(&oracle, &request, &game).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::InvalidRequestStatus));
+
+    chaos.append_words(&oracle, &request, &game, 40).await.unwrap();
+    chaos.append_words(&oracle, &request, &game, 40).await.unwrap();
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Fulfilling);
+
+    // Neither an incomplete result nor a chunk past the end is accepted
+    let before = chaos.snapshot(&oracle, &request, &[&game]).await;
+    let result = chaos.finalize(&oracle, &request, &game).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::InvalidNumberOfWords));
+    let result = chaos.append_words(&oracle, &request, &game, 21).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::InvalidNumberOfWords));
+    assert_eq!(chaos.snapshot(&oracle, &request, &[&game]).await, before);
+
+    // A single-transaction fulfillment cannot cut in
+    let result = chaos.fulfill(&oracle, &request, &game, &[9u8; 32]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::RequestAlreadyFulfilled));
+
+    chaos.append_words(&oracle, &request, &game, 20).await.unwrap();
+    chaos.finalize(&oracle, &request, &game).await.unwrap();
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Fulfilled);
+
+    let data = chaos.account_data(&chaos.vrf_result(&request)).await.unwrap();
+    let result = VrfResult::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap();
+    assert_eq!(result.randomness.len(), 100);
+    assert!(!chaos.game_state(&game).await.is_pending);
+}
+
+#[tokio::test]
+async fn test_zk_fulfillment_checks_output() {
+    let mut chaos = Chaos::new().await;
+    let oracle = chaos.register_oracle().await;
+    let game = chaos.create_game().await;
+    let request = chaos.request_for_game(&game, None).await;
+
+    // A key with beta = gamma = delta = G2, which anyone can prove against;
+    // it only stands in for a real circuit's key
+    let verifying_key = Groth16VerifyingKey {
+        alpha_g1: g1_mul(3),
+        beta_g2: G2_GENERATOR,
+        gamma_g2: G2_GENERATOR,
+        delta_g2: G2_GENERATOR,
+        ic: [g1_mul(5), g1_mul(7), g1_mul(11), g1_mul(13)],
+    };
+    chaos.set_zk_verifying_key(verifying_key.clone()).await;
+
+    let data = chaos.account_data(&request).await.unwrap();
+    let seed = RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().seed;
+    let mut vrf_key = [0u8; 32];
+    vrf_key.copy_from_slice(chaos.vrf_keypair.pk.as_ref());
+    let output = [6u8; 64];
+    let inputs = groth16::public_inputs(&vrf_key, &seed, &output);
+    let c = g1_mul(2);
+    let a = g1_add(&g1_add(&verifying_key.alpha_g1, &verifying_key.prepare_inputs(&inputs).unwrap()), &c);
+    let proof = Groth16Proof { a, b: G2_GENERATOR, c };
+
+    // The proof does not carry over to any other output
+    let before = chaos.snapshot(&oracle, &request, &[&game]).await;
+    let result = chaos.fulfill_zk(&oracle, &request, &game, [7u8; 64], proof.clone()).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::InvalidZkProof));
+    assert_eq!(chaos.snapshot(&oracle, &request, &[&game]).await, before);
+
+    chaos.fulfill_zk(&oracle, &request, &game, output, proof).await.unwrap();
+    assert_eq!(chaos.request_status(&request).await, RequestStatus::Fulfilled);
+    let data = chaos.account_data(&chaos.vrf_result(&request)).await.unwrap();
+    assert_eq!(VrfResult::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().randomness, vec![output]);
+}
+
+#[tokio::test]
+async fn test_admin_handed_to_governance() {
+    let mut chaos = Chaos::new().await;
+    let config = chaos.config;
+    let admin = chaos.context.payer.pubkey();
+
+    // An unfunded authority standing in for a governance PDA, and a funded
+    // payer standing in for its native treasury
+    let governance = Keypair::new();
+    let treasury = Keypair::new();
+    chaos.fund(&treasury.pubkey()).await;
+    let (program_id, audit_log) = (chaos.vrf_program_id, chaos.audit_log());
+    let admin_instruction = |authority: Pubkey, payer: Pubkey, instruction: &VrfCoordinatorInstruction| Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(audit_log, false),
+            AccountMeta::new(payer, true),
+        ],
+        data: borsh::to_vec(instruction).unwrap(),
+    };
+
+    let set_admin = admin_instruction(admin, admin, &VrfCoordinatorInstruction::SetAdmin { new_admin: governance.pubkey() });
+    chaos.send(&[set_admin], &[]).await.unwrap();
+
+    let fee_schedule = FeeSchedule { base_fee: 10, fee_per_word: 2, fee_per_million_callback_units: 0, priority_fee: 0 };
+    let set_fees = VrfCoordinatorInstruction::SetFeeSchedule { fee_schedule };
+    let result = chaos.send(&[admin_instruction(admin, admin, &set_fees)], &[]).await;
+    assert_eq!(instruction_error(result), custom(VrfCoordinatorError::InvalidAdmin));
+    chaos.send(&[admin_instruction(governance.pubkey(), treasury.pubkey(), &set_fees)], &[&governance, &treasury]).await.unwrap();
+    let data = chaos.account_data(&config).await.unwrap();
+    let stored = CoordinatorConfig::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap();
+    assert_eq!((stored.admin, stored.fee_schedule), (governance.pubkey(), fee_schedule));
+
+    // The oracle config is a PDA, so registering needs no keypair signature
+    let oracle_key = Pubkey::new_unique();
+    let (oracle_config, _) = derive_oracle_config_pda(&chaos.vrf_program_id, &oracle_key);
+    let register = Instruction {
+        program_id: chaos.vrf_program_id,
+        accounts: vec![
+            AccountMeta::new(governance.pubkey(), true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(oracle_config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(chaos.audit_log(), false),
+            AccountMeta::new(treasury.pubkey(), true),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::RegisterOracle { oracle_key, vrf_key: [3; 32] }).unwrap(),
+    };
+    chaos.send(&[register], &[&governance, &treasury]).await.unwrap();
+    assert_eq!(chaos.oracle_config_at(&oracle_config).await.oracle_key, oracle_key);
+
+    let log = AdminAuditLog::parse(&chaos.account_data(&chaos.audit_log()).await.unwrap()).unwrap();
+    let actions: Vec<_> = log.entries.iter().skip(1).map(|entry| (entry.actor, entry.action.clone())).collect();
+    assert_eq!(actions, vec![
+        (admin, AdminAction::SetAdmin { new_admin: governance.pubkey() }),
+        (governance.pubkey(), AdminAction::SetFeeSchedule { fee_schedule }),
+        (governance.pubkey(), AdminAction::RegisterOracle { oracle_key, vrf_key: [3; 32] }),
+    ]);
+    assert_eq!(chaos.context.banks_client.get_balance(governance.pubkey()).await.unwrap(), 0);
+}
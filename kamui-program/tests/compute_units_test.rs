@@ -0,0 +1,216 @@
+//! Compute units of `FulfillRandomness` in the SBF build of the coordinator.
+//!
+//! Run with `cargo test-sbf --features test-bpf`. The feature makes the
+//! coordinator log the compute units verification consumed, and those of
+//! hashing the seed to the curve on its own, measured with
+//! `sol_remaining_compute_units`. Only the SBF runtime meters the program's
+//! own instructions, so natively the figures would cover the syscalls alone.
+#![cfg(feature = "test-bpf")]
+
+use {
+    borsh::BorshDeserialize,
+    kamui_program::{
+        audit_log::ADMIN_AUDIT_LOG_SEED,
+        instruction::VrfCoordinatorInstruction,
+        pda::{derive_config_pda, derive_oracle_config_pda, derive_request_pda, derive_vrf_result_pda},
+        state::{FeeSchedule, RandomnessRequest, RequestStatus, ACCOUNT_HEADER_LEN, MAXIMUM_CALLBACK_GAS_LIMIT},
+        verifier::VrfSuite,
+    },
+    mangekyou::kamui_vrf::{
+        ecvrf::{ECVRFKeyPair, ECVRFPrivateKey},
+        VRFKeyPair,
+        VRFProof,
+    },
+    solana_program::{
+        clock::Clock,
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        signature::Keypair,
+        signer::Signer,
+        transaction::Transaction,
+    },
+};
+
+/// Compute unit limit the oracle node requests for a fulfillment, the
+/// default of `kamui_oracle_node::config::Config::compute_unit_limit`
+const FULFILL_COMPUTE_UNIT_LIMIT: u32 = 400_000;
+
+/// Callback compute units a fulfillment leaves the consumer within that
+/// limit, what the example consumers request
+const CALLBACK_GAS_LIMIT: u64 = 100_000;
+
+/// Compute units ECVRF verification may take, the rest of the limit going to
+/// the callback and the fulfillment's own bookkeeping
+const VERIFY_COMPUTE_BUDGET: u64 = 200_000;
+
+/// Runs `instructions` in one transaction and returns its logs and the
+/// compute units it consumed
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> (Vec<String>, u64) {
+    let payer = context.payer.insecure_clone();
+    let mut all_signers = vec![&payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    let result = context.banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    result.result.unwrap();
+    let metadata = result.metadata.unwrap();
+    (metadata.log_messages, metadata.compute_units_consumed)
+}
+
+/// The compute units the coordinator logged for `label`
+fn logged_units(logs: &[String], label: &str) -> u64 {
+    let prefix = format!("Program log: VRF Coordinator: {} consumed ", label);
+    logs.iter()
+        .find_map(|log| log.strip_prefix(&prefix)?.strip_suffix(" compute units")?.parse().ok())
+        .unwrap_or_else(|| panic!("no compute units logged for {}", label))
+}
+
+#[tokio::test]
+async fn test_fulfill_compute_units() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_program("kamui_program", program_id, None);
+    let mut context = program_test.start_with_context().await;
+    context.warp_to_slot(20).unwrap();
+
+    // The payer administers the coordinator, owns the subscription and runs the oracle
+    let payer = context.payer.pubkey();
+    let (config, _) = derive_config_pda(&program_id);
+    let audit_log = Pubkey::find_program_address(&[ADMIN_AUDIT_LOG_SEED], &program_id).0;
+    send(&mut context, &[Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(audit_log, false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::InitializeCoordinator {
+            fee_schedule: FeeSchedule::default(),
+            bridge_program: Pubkey::default(),
+        }).unwrap(),
+    }], &[]).await;
+
+    let vrf_keypair = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[1; 32]).unwrap());
+    let (oracle_config, _) = derive_oracle_config_pda(&program_id, &payer);
+    send(&mut context, &[Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(oracle_config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(audit_log, false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::RegisterOracle {
+            oracle_key: payer,
+            vrf_key: vrf_keypair.pk.as_ref().try_into().unwrap(),
+        }).unwrap(),
+    }], &[]).await;
+
+    let subscription = Keypair::new();
+    send(&mut context, &[Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(subscription.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::CreateSubscription {
+            min_balance: 0,
+            confirmations: 1,
+        }).unwrap(),
+    }], &[&subscription]).await;
+
+    // A callback gas limit above the transaction's limit has the fulfillment
+    // skip the callback, so no consumer program needs to be deployed
+    let (request, _) = derive_request_pda(&program_id, &subscription.pubkey(), 0);
+    send(&mut context, &[Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(request, false),
+            AccountMeta::new(subscription.pubkey(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::RequestRandomness {
+            seed: [7; 32],
+            callback_data: vec![],
+            num_words: 1,
+            minimum_confirmations: 1,
+            callback_gas_limit: MAXIMUM_CALLBACK_GAS_LIMIT,
+            priority_fee: 0,
+        }).unwrap(),
+    }], &[]).await;
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.warp_to_slot(clock.slot + 2).unwrap();
+
+    let data = context.banks_client.get_account(request).await.unwrap().unwrap().data;
+    let seed = RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().seed;
+    let game_program = Pubkey::new_unique();
+    let (game_state, _) = Pubkey::find_program_address(&[b"game_state", payer.as_ref()], &game_program);
+    let fulfill = Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(request, false),
+            AccountMeta::new(derive_vrf_result_pda(&program_id, &request).0, false),
+            AccountMeta::new_readonly(game_program, false),
+            AccountMeta::new(subscription.pubkey(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(game_program, false),
+            AccountMeta::new(game_state, false),
+            AccountMeta::new(oracle_config, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::FulfillRandomness {
+            proof: vrf_keypair.prove(&seed).to_bytes_legacy(),
+            public_key: vrf_keypair.pk.as_ref().to_vec(),
+            suite: VrfSuite::default(),
+        }).unwrap(),
+    };
+    let (logs, consumed) = send(&mut context, &[
+        ComputeBudgetInstruction::set_compute_unit_limit(FULFILL_COMPUTE_UNIT_LIMIT),
+        fulfill,
+    ], &[]).await;
+
+    let data = context.banks_client.get_account(request).await.unwrap().unwrap().data;
+    let status = RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().status;
+    assert_eq!(status, RequestStatus::CallbackSkipped);
+
+    // Hashing to the curve, expand_message_xmd, two Elligator maps and a
+    // compression, runs in the program rather than in syscalls. The test
+    // build hashes once more to meter it, which the fulfillment does not.
+    let hash_to_curve = logged_units(&logs, "Hash to curve");
+    let verify = logged_units(&logs, "Verification");
+    let consumed = consumed - hash_to_curve;
+    println!(
+        "FulfillRandomness: {} compute units, {} of them verifying, {} of those hashing to the curve",
+        consumed, verify, hash_to_curve
+    );
+    assert!(hash_to_curve < verify);
+    assert!(verify <= VERIFY_COMPUTE_BUDGET, "verification took {} compute units", verify);
+    assert!(
+        consumed + CALLBACK_GAS_LIMIT <= FULFILL_COMPUTE_UNIT_LIMIT as u64,
+        "fulfillment took {} compute units, leaving less than {} for the callback", consumed, CALLBACK_GAS_LIMIT
+    );
+}
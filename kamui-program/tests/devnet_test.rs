@@ -24,6 +24,7 @@ use {
 };
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[ignore = "requires devnet and a funded keypair.json"]
 async fn test_vrf_verification_devnet() {
     // Connect to devnet
     let rpc_url = "https://api.devnet.solana.com".to_string();
@@ -61,7 +62,7 @@ async fn test_vrf_verification_devnet() {
     let public_key_bytes = vrf_keypair.pk.as_ref().to_vec();
     
     // Get proof bytes and reformat to match the on-chain program's expected format (gamma || c || s)
-    let proof_bytes = proof.to_bytes();
+    let proof_bytes = proof.to_bytes_legacy();
     
     // The proof bytes from kamui_vrf are in format gamma || c || s
     // Our program expects the same format, so we can use them directly
@@ -0,0 +1,135 @@
+//! Checks that a client recomputing a mixed seed with `entropy::mix_alpha`
+//! from the `EntropyMixed` event and the `SlotHashes` sysvar gets the seed
+//! the coordinator stored in the request.
+
+use {
+    base64::Engine,
+    borsh::BorshDeserialize,
+    kamui_program::{
+        audit_log::ADMIN_AUDIT_LOG_SEED,
+        entropy,
+        event::VrfEvent,
+        instruction::VrfCoordinatorInstruction,
+        pda::{derive_config_pda, derive_request_pda},
+        state::{FeeSchedule, RandomnessRequest, Subscription, ACCOUNT_HEADER_LEN},
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        slot_hashes::SlotHashes,
+        system_program,
+        sysvar,
+    },
+    solana_program_test::*,
+    solana_sdk::{signature::Keypair, signer::Signer, transaction::Transaction},
+};
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Vec<String> {
+    let payer = context.payer.insecure_clone();
+    let mut all_signers = vec![&payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    let result = context.banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    result.result.unwrap();
+    result.metadata.unwrap().log_messages
+}
+
+#[tokio::test]
+async fn test_client_and_program_mix_the_same_seed() {
+    let program_id = Pubkey::new_unique();
+    let mut context = ProgramTest::new(
+        "kamui_program",
+        program_id,
+        processor!(kamui_program::process_instruction),
+    )
+    .start_with_context()
+    .await;
+    context.warp_to_slot(20).unwrap();
+
+    let owner = context.payer.insecure_clone();
+    let (config, _) = derive_config_pda(&program_id);
+    send(&mut context, Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(Pubkey::find_program_address(&[ADMIN_AUDIT_LOG_SEED], &program_id).0, false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::InitializeCoordinator {
+            fee_schedule: FeeSchedule::default(),
+            bridge_program: Pubkey::default(),
+        }).unwrap(),
+    }, &[]).await;
+
+    let subscription = Keypair::new();
+    send(&mut context, Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(subscription.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::CreateSubscription {
+            min_balance: 0,
+            confirmations: 1,
+        }).unwrap(),
+    }, &[&subscription]).await;
+
+    let caller_entropy = [42u8; 32];
+    for nonce in 0..2u64 {
+        let (request, _) = derive_request_pda(&program_id, &subscription.pubkey(), nonce);
+        let logs = send(&mut context, Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new(request, false),
+                AccountMeta::new(subscription.pubkey(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+                AccountMeta::new_readonly(config, false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::RequestRandomnessWithEntropy {
+                entropy: caller_entropy,
+                callback_data: vec![],
+                num_words: 1,
+                minimum_confirmations: 1,
+                callback_gas_limit: 100_000,
+            }).unwrap(),
+        }, &[]).await;
+
+        // What a client sees: the event and the sysvar
+        let (slot_hash_slot, slot_hash, seed) = logs.iter()
+            .filter_map(|log| log.strip_prefix("Program log: VRF_EVENT:"))
+            .map(|data| base64::engine::general_purpose::STANDARD.decode(data).unwrap())
+            .find_map(|data| match VrfEvent::try_from_slice(&data).unwrap() {
+                VrfEvent::EntropyMixed { slot_hash_slot, slot_hash, seed, .. } => Some((slot_hash_slot, slot_hash, seed)),
+                _ => None,
+            })
+            .expect("EntropyMixed event");
+        let slot_hashes: SlotHashes = context.banks_client.get_sysvar().await.unwrap();
+        assert_eq!(slot_hashes.get(&slot_hash_slot).map(|hash| hash.to_bytes()), Some(slot_hash));
+
+        let data = context.banks_client.get_account(request).await.unwrap().unwrap().data;
+        let stored = RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap();
+        let client_seed = entropy::mix_alpha(&caller_entropy, &request, nonce, &slot_hash);
+        assert_eq!(client_seed, seed);
+        assert_eq!(client_seed, stored.seed);
+
+        let data = context.banks_client.get_account(subscription.pubkey()).await.unwrap().unwrap().data;
+        assert_eq!(Subscription::try_from_slice(&data[ACCOUNT_HEADER_LEN..]).unwrap().nonce, nonce + 1);
+    }
+}
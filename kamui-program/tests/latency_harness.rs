@@ -0,0 +1,402 @@
+//! End-to-end latency harness against a local validator.
+//!
+//! Start a validator with both programs loaded, for example:
+//!
+//! ```text
+//! solana-test-validator --reset \
+//!     --bpf-program <VRF_PROGRAM_ID> target/deploy/kamui_program.so \
+//!     --bpf-program <GAME_PROGRAM_ID> ../example-vrf-game/target/deploy/example_vrf_game.so
+//! ```
+//!
+//! then run
+//!
+//! ```text
+//! KAMUI_VRF_PROGRAM_ID=<VRF_PROGRAM_ID> KAMUI_GAME_PROGRAM_ID=<GAME_PROGRAM_ID> \
+//!     cargo test --test latency_harness -- --ignored --nocapture
+//! ```
+//!
+//! The harness plays the oracle itself: for each of `KAMUI_LATENCY_REQUESTS`
+//! requests it waits for the pending request, proves over its seed and submits
+//! `FulfillRandomness`, then waits for the game callback to land. It reports
+//! p50/p95 for request→fulfillment, fulfillment→callback and end to end, plus
+//! compute units and fees for each step.
+
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    kamui_program::{
+        audit_log::ADMIN_AUDIT_LOG_SEED,
+        instruction::VrfCoordinatorInstruction,
+        pda::{derive_config_pda, derive_oracle_config_pda, derive_request_pda, derive_vrf_result_pda},
+        state::{FeeSchedule, RandomnessRequest, RequestStatus, Subscription, ACCOUNT_HEADER_LEN, subscription_token_address, subscription_vault_address},
+        verifier::VrfSuite,
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+        system_instruction,
+    },
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+    spl_token::native_mint,
+    spl_associated_token_account,
+    mangekyou::kamui_vrf::{
+        ecvrf::ECVRFKeyPair,
+        VRFKeyPair,
+        VRFProof,
+    },
+    rand::thread_rng,
+    anyhow::Result,
+    std::{
+        env,
+        str::FromStr,
+        thread::sleep,
+        time::{Duration, Instant},
+    },
+};
+
+const DEFAULT_RPC_URL: &str = "http://127.0.0.1:8899";
+const DEFAULT_REQUESTS: usize = 20;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const POLL_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Mirrors of the example-vrf-game types
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum GameInstruction {
+    Initialize,
+    RequestNewNumber,
+    ConsumeRandomness,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct GameState {
+    pub owner: Pubkey,
+    pub subscription: Pubkey,
+    pub vrf_coordinator: Pubkey,
+    pub current_number: u8,
+    pub is_pending: bool,
+}
+
+/// Compute units and fee of one confirmed transaction.
+#[derive(Default, Clone, Copy)]
+struct StepCost {
+    compute_units: u64,
+    fee: u64,
+}
+
+/// Timings and costs collected for a single request.
+struct Sample {
+    request_to_fulfillment: Duration,
+    fulfillment_to_callback: Duration,
+    end_to_end: Duration,
+    request: StepCost,
+    fulfill: StepCost,
+}
+
+fn env_pubkey(name: &str) -> Pubkey {
+    let value = env::var(name).unwrap_or_else(|_| panic!("{} must be set", name));
+    Pubkey::from_str(&value).unwrap_or_else(|_| panic!("{} is not a valid pubkey", name))
+}
+
+/// Simulates, then sends and confirms `instructions`, returning the step cost.
+fn send(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<StepCost> {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = rpc_client.get_latest_blockhash()?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+
+    let simulation = rpc_client.simulate_transaction(&transaction)?.value;
+    if let Some(err) = simulation.err {
+        return Err(anyhow::anyhow!("simulation failed: {:?} {:?}", err, simulation.logs));
+    }
+    let cost = StepCost {
+        compute_units: simulation.units_consumed.unwrap_or_default(),
+        fee: rpc_client.get_fee_for_message(&transaction.message)?,
+    };
+
+    rpc_client.send_and_confirm_transaction(&transaction)?;
+    Ok(cost)
+}
+
+fn wait_for<T>(mut check: impl FnMut() -> Result<Option<T>>) -> Result<T> {
+    let start = Instant::now();
+    while start.elapsed() < POLL_TIMEOUT {
+        if let Some(value) = check()? {
+            return Ok(value);
+        }
+        sleep(POLL_INTERVAL);
+    }
+    Err(anyhow::anyhow!("timed out after {:?}", POLL_TIMEOUT))
+}
+
+fn percentile<T: Copy + Ord>(values: &[T], pct: usize) -> T {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    let index = ((sorted.len() * pct + 99) / 100).saturating_sub(1);
+    sorted[index.min(sorted.len() - 1)]
+}
+
+fn report(samples: &[Sample]) {
+    let latencies = [
+        ("request -> fulfillment", samples.iter().map(|s| s.request_to_fulfillment).collect::<Vec<_>>()),
+        ("fulfillment -> callback", samples.iter().map(|s| s.fulfillment_to_callback).collect()),
+        ("end to end", samples.iter().map(|s| s.end_to_end).collect()),
+    ];
+    let costs = [
+        ("request", samples.iter().map(|s| s.request).collect::<Vec<_>>()),
+        ("fulfill + callback", samples.iter().map(|s| s.fulfill).collect()),
+    ];
+
+    println!("\n=== VRF Latency ({} requests) ===", samples.len());
+    for (label, values) in latencies.iter() {
+        println!(
+            "{:<24} p50 {:>8.1?}  p95 {:>8.1?}",
+            label,
+            percentile(values, 50),
+            percentile(values, 95),
+        );
+    }
+    println!("\n=== Per-step Cost ===");
+    for (label, values) in costs.iter() {
+        let units: Vec<u64> = values.iter().map(|c| c.compute_units).collect();
+        let fees: Vec<u64> = values.iter().map(|c| c.fee).collect();
+        println!(
+            "{:<24} CU p50 {:>7}  p95 {:>7}  fee p50 {} SOL  p95 {} SOL",
+            label,
+            percentile(&units, 50),
+            percentile(&units, 95),
+            percentile(&fees, 50) as f64 / 1_000_000_000.0,
+            percentile(&fees, 95) as f64 / 1_000_000_000.0,
+        );
+    }
+    println!("==================\n");
+}
+
+#[test]
+#[ignore = "requires a local validator with kamui_program and example_vrf_game deployed"]
+fn test_vrf_latency_local_validator() -> Result<()> {
+    let rpc_url = env::var("KAMUI_RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string());
+    let rpc_client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+    let vrf_program_id = env_pubkey("KAMUI_VRF_PROGRAM_ID");
+    let game_program_id = env_pubkey("KAMUI_GAME_PROGRAM_ID");
+    let num_requests = env::var("KAMUI_LATENCY_REQUESTS")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_REQUESTS);
+
+    // Fund a throwaway payer from the local faucet
+    let payer = Keypair::new();
+    let airdrop = rpc_client.request_airdrop(&payer.pubkey(), 100_000_000_000)?;
+    wait_for(|| Ok(rpc_client.confirm_transaction(&airdrop)?.then_some(())))?;
+
+    // Coordinator config, with the payer as admin; the validator was reset
+    let (config, _) = derive_config_pda(&vrf_program_id);
+    let (audit_log, _) = Pubkey::find_program_address(&[ADMIN_AUDIT_LOG_SEED], &vrf_program_id);
+    send(&rpc_client, &payer, &[Instruction {
+        program_id: vrf_program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(audit_log, false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::InitializeCoordinator {
+            fee_schedule: FeeSchedule::default(),
+            bridge_program: Pubkey::default(),
+        })?,
+    }], &[])?;
+
+    // Subscription, funded with wrapped SOL
+    let subscription_owner = Keypair::new();
+    let subscription_account = Keypair::new();
+    send(&rpc_client, &payer, &[
+        system_instruction::transfer(&payer.pubkey(), &subscription_owner.pubkey(), 10_000_000_000),
+        Instruction {
+            program_id: vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(subscription_owner.pubkey(), true),
+                AccountMeta::new(subscription_account.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::CreateSubscription {
+                min_balance: 1_000_000,
+                confirmations: 1,
+            })?,
+        },
+    ], &[&subscription_owner, &subscription_account])?;
+
+    let mint = native_mint::id();
+    let funder_token = spl_associated_token_account::get_associated_token_address(&subscription_owner.pubkey(), &mint);
+    let subscription_vault = subscription_vault_address(&vrf_program_id, &subscription_account.pubkey());
+    let subscription_token = subscription_token_address(&vrf_program_id, &subscription_account.pubkey());
+    let funding = 5_000_000_000;
+    send(&rpc_client, &payer, &[
+        spl_associated_token_account::instruction::create_associated_token_account(
+            &payer.pubkey(),
+            &subscription_owner.pubkey(),
+            &mint,
+            &spl_token::id(),
+        ),
+        spl_associated_token_account::instruction::create_associated_token_account(
+            &payer.pubkey(),
+            &subscription_vault,
+            &mint,
+            &spl_token::id(),
+        ),
+        system_instruction::transfer(&subscription_owner.pubkey(), &funder_token, funding),
+        spl_token::instruction::sync_native(&spl_token::id(), &funder_token)?,
+        Instruction {
+            program_id: vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(subscription_owner.pubkey(), true),
+                AccountMeta::new(subscription_account.pubkey(), false),
+                AccountMeta::new(funder_token, false),
+                AccountMeta::new(subscription_token, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::FundSubscription { amount: funding })?,
+        },
+    ], &[&subscription_owner])?;
+
+    // Game bound to this coordinator
+    let game_owner = Keypair::new();
+    let (game_state_pda, _bump) = Pubkey::find_program_address(
+        &[b"game_state", game_owner.pubkey().as_ref()],
+        &game_program_id,
+    );
+    send(&rpc_client, &payer, &[
+        system_instruction::transfer(&payer.pubkey(), &game_owner.pubkey(), 1_000_000_000),
+        Instruction {
+            program_id: game_program_id,
+            accounts: vec![
+                AccountMeta::new(game_owner.pubkey(), true),
+                AccountMeta::new(game_state_pda, false),
+                AccountMeta::new_readonly(subscription_account.pubkey(), false),
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(vrf_program_id, false),
+            ],
+            data: borsh::to_vec(&GameInstruction::Initialize)?,
+        },
+        Instruction {
+            program_id: vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(subscription_owner.pubkey(), true),
+                AccountMeta::new(subscription_account.pubkey(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::AddConsumer { consumer: game_owner.pubkey() })?,
+        },
+    ], &[&game_owner, &subscription_owner])?;
+
+    // Oracle registration
+    let vrf_keypair = ECVRFKeyPair::generate(&mut thread_rng());
+    let mut vrf_key = [0u8; 32];
+    vrf_key.copy_from_slice(vrf_keypair.pk.as_ref());
+    let (oracle_config, _) = derive_oracle_config_pda(&vrf_program_id, &payer.pubkey());
+    send(&rpc_client, &payer, &[Instruction {
+        program_id: vrf_program_id,
+        accounts: vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new(oracle_config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(audit_log, false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::RegisterOracle {
+            oracle_key: payer.pubkey(),
+            vrf_key,
+        })?,
+    }], &[])?;
+
+    let mut samples = Vec::with_capacity(num_requests);
+    for i in 0..num_requests {
+        let subscription_data = rpc_client.get_account_data(&subscription_account.pubkey())?;
+        let subscription = Subscription::try_from_slice(&subscription_data[ACCOUNT_HEADER_LEN..])?;
+        let (request_account, _bump) = derive_request_pda(&vrf_program_id, &subscription_account.pubkey(), subscription.nonce);
+        let (vrf_result, _bump) = derive_vrf_result_pda(&vrf_program_id, &request_account);
+
+        // Request
+        let started = Instant::now();
+        let request_cost = send(&rpc_client, &payer, &[Instruction {
+            program_id: game_program_id,
+            accounts: vec![
+                AccountMeta::new(game_owner.pubkey(), true),
+                AccountMeta::new(game_state_pda, false),
+                AccountMeta::new(request_account, false),
+                AccountMeta::new(subscription_account.pubkey(), false),
+                AccountMeta::new_readonly(vrf_program_id, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(config, false),
+            ],
+            data: borsh::to_vec(&GameInstruction::RequestNewNumber)?,
+        }], &[&game_owner])?;
+        let requested = Instant::now();
+
+        // Oracle: pick up the pending request and fulfill it
+        let request = wait_for(|| {
+            let data = match rpc_client.get_account_data(&request_account) {
+                Ok(data) => data,
+                Err(_) => return Ok(None),
+            };
+            let request = RandomnessRequest::try_from_slice(&data[ACCOUNT_HEADER_LEN..])?;
+            Ok((request.status == RequestStatus::Pending).then_some(request))
+        })?;
+        let proof = vrf_keypair.prove(&request.seed);
+        let fulfill_cost = send(&rpc_client, &payer, &[Instruction {
+            program_id: vrf_program_id,
+            accounts: vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(request_account, false),
+                AccountMeta::new(vrf_result, false),
+                AccountMeta::new_readonly(game_program_id, false),
+                AccountMeta::new(subscription_account.pubkey(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(game_program_id, false),
+                AccountMeta::new(game_state_pda, false),
+                AccountMeta::new(oracle_config, false),
+                AccountMeta::new_readonly(config, false),
+            ],
+            data: borsh::to_vec(&VrfCoordinatorInstruction::FulfillRandomness {
+                proof: proof.to_bytes_legacy(),
+                public_key: vrf_keypair.pk.as_ref().to_vec(),
+                suite: VrfSuite::default(),
+            })?,
+        }], &[])?;
+        let fulfilled = Instant::now();
+
+        // Callback: the game state leaves the pending state
+        wait_for(|| {
+            let data = rpc_client.get_account_data(&game_state_pda)?;
+            let state = GameState::try_from_slice(&data[8..])?;
+            Ok((!state.is_pending).then_some(()))
+        })?;
+        let called_back = Instant::now();
+
+        println!("request {}/{}: {:?} end to end", i + 1, num_requests, called_back - started);
+        samples.push(Sample {
+            request_to_fulfillment: fulfilled - requested,
+            fulfillment_to_callback: called_back - fulfilled,
+            end_to_end: called_back - started,
+            request: request_cost,
+            fulfill: fulfill_cost,
+        });
+    }
+
+    report(&samples);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Property tests of the instruction parsers: arbitrary instruction data never
+//! panics the coordinator or the verifier, and whatever deserializes encodes
+//! back to the same bytes.
+//!
+//! `fuzz/` runs the same entry points under `cargo fuzz` for longer campaigns.
+
+use {
+    borsh::BorshDeserialize,
+    kamui_program::{
+        instruction::{VerifyVrfInput, VrfCoordinatorInstruction},
+        processor::Processor,
+        verifier::{self, VrfSuite},
+    },
+    mangekyou::kamui_vrf::{ecvrf::ECVRFKeyPair, VRFKeyPair, VRFProof},
+    proptest::{collection::vec, prelude::*},
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// Number of variants of [VrfCoordinatorInstruction], so that generated data
+/// mostly starts with a valid tag
+const INSTRUCTION_VARIANTS: u8 = 47;
+
+/// Instruction data: a tag, mostly of an existing variant, and a body that is
+/// either short or sized like the larger instructions
+fn instruction_data() -> impl Strategy<Value = Vec<u8>> {
+    (
+        prop_oneof![9 => 0..INSTRUCTION_VARIANTS, 1 => any::<u8>()],
+        prop_oneof![vec(any::<u8>(), 0..64), vec(any::<u8>(), 0..1024)],
+    )
+        .prop_map(|(tag, body)| [&[tag][..], &body].concat())
+}
+
+/// A borsh [VerifyVrfInput] with fields of any length, the proof and key
+/// mostly of the lengths the verifier takes
+fn verify_input() -> impl Strategy<Value = VerifyVrfInput> {
+    (
+        vec(any::<u8>(), 0..64),
+        prop_oneof![3 => vec(any::<u8>(), 80), 1 => vec(any::<u8>(), 0..160)],
+        prop_oneof![3 => vec(any::<u8>(), 32), 1 => vec(any::<u8>(), 0..64)],
+    )
+        .prop_map(|(alpha_string, proof_bytes, public_key_bytes)| VerifyVrfInput {
+            alpha_string,
+            proof_bytes,
+            public_key_bytes,
+        })
+}
+
+proptest! {
+    #[test]
+    fn instruction_unpack_round_trips(data in instruction_data()) {
+        if let Ok(instruction) = VrfCoordinatorInstruction::unpack(&data) {
+            prop_assert_eq!(borsh::to_vec(&instruction).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn processor_rejects_arbitrary_data(data in instruction_data()) {
+        // Without accounts no instruction can succeed, but none may panic
+        prop_assert!(Processor::process(&Pubkey::new_unique(), &[], &data).is_err());
+    }
+
+    #[test]
+    fn verify_input_round_trips(data in vec(any::<u8>(), 0..256)) {
+        if let Ok(input) = VerifyVrfInput::try_from_slice(&data) {
+            prop_assert_eq!(borsh::to_vec(&input).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn verifier_rejects_arbitrary_proofs(input in verify_input()) {
+        let data = borsh::to_vec(&input).unwrap();
+        prop_assert!(verifier::process_verify(&Pubkey::new_unique(), &[], &data).is_err());
+        for suite in [VrfSuite::Ristretto255Sha512, VrfSuite::Edwards25519Sha512Tai] {
+            prop_assert!(suite.verify(&input.alpha_string, &input.proof_bytes, &input.public_key_bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn verifier_accepts_valid_proofs(seed in any::<[u8; 32]>(), alpha in vec(any::<u8>(), 0..64)) {
+        let keypair = ECVRFKeyPair::from_seed(&seed);
+        let proof = VRFKeyPair::prove(&keypair, &alpha);
+        let input = VerifyVrfInput {
+            alpha_string: alpha,
+            proof_bytes: proof.to_bytes_legacy(),
+            public_key_bytes: keypair.pk.as_ref().to_vec(),
+        };
+        let data = borsh::to_vec(&input).unwrap();
+        prop_assert_eq!(verifier::process_verify(&Pubkey::new_unique(), &[], &data), Ok(()));
+        let output = verifier::verify_proof(&input.alpha_string, &input.proof_bytes, &input.public_key_bytes);
+        prop_assert_eq!(output.ok(), Some(proof.to_hash()));
+
+        // Trailing bytes are not a VerifyVrfInput
+        let trailing = [&data[..], &[0]].concat();
+        prop_assert_eq!(
+            verifier::process_verify(&Pubkey::new_unique(), &[], &trailing),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Checks that the on-chain verifier and `mangekyou` agree on every proof.
+//!
+//! Under `cargo test` the verifier runs natively; under `cargo test-sbf` the
+//! same test exercises the BPF build and the runtime's curve25519 syscalls.
+
+use {
+    kamui_program::{instruction::VerifyVrfInput, verifier},
+    solana_program::{instruction::Instruction, pubkey::Pubkey},
+    solana_program_test::*,
+    solana_sdk::{signer::Signer, transaction::Transaction},
+    mangekyou::kamui_vrf::{
+        ecvrf::{ECVRFKeyPair, ECVRFProof, ECVRFPublicKey},
+        VRFKeyPair,
+        VRFProof,
+    },
+    rand::{thread_rng, Rng},
+};
+
+const TRIALS: usize = 16;
+
+/// Library decision: the VRF output if the proof verifies.
+fn library_verify(alpha: &[u8], proof: &[u8], public_key: &[u8]) -> Option<[u8; 64]> {
+    let public_key = ECVRFPublicKey::from_bytes(public_key).ok()?;
+    let proof = ECVRFProof::from_bytes_legacy(proof).ok()?;
+    proof.verify(alpha, &public_key).ok()?;
+    Some(proof.to_hash())
+}
+
+/// Program decision: the return data if the verify instruction succeeds.
+async fn program_verify(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    alpha: &[u8],
+    proof: &[u8],
+    public_key: &[u8],
+) -> Option<[u8; 64]> {
+    let instruction = Instruction::new_with_borsh(
+        program_id,
+        &VerifyVrfInput {
+            alpha_string: alpha.to_vec(),
+            proof_bytes: proof.to_vec(),
+            public_key_bytes: public_key.to_vec(),
+        },
+        vec![],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    result.result.ok()?;
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, program_id);
+    Some(return_data.data.try_into().unwrap())
+}
+
+#[tokio::test]
+async fn test_library_and_program_verifier_agree() {
+    let program_id = Pubkey::new_unique();
+    let mut context = ProgramTest::new(
+        "kamui_verifier",
+        program_id,
+        processor!(verifier::process_verify),
+    )
+    .start_with_context()
+    .await;
+
+    let mut rng = thread_rng();
+    for _ in 0..TRIALS {
+        let keypair = ECVRFKeyPair::generate(&mut rng);
+        let other = ECVRFKeyPair::generate(&mut rng);
+        let alpha: Vec<u8> = (0..rng.gen_range(0..64)).map(|_| rng.gen()).collect();
+        let (output, proof) = keypair.output(&alpha);
+        let proof = proof.to_bytes_legacy();
+        let public_key = keypair.pk.as_ref().to_vec();
+
+        let mut mutated = proof.clone();
+        let index = rng.gen_range(0..mutated.len());
+        mutated[index] ^= 1 << rng.gen_range(0..8);
+        let mut other_alpha = alpha.clone();
+        other_alpha.push(rng.gen());
+
+        let cases: Vec<(&[u8], &[u8], &[u8], bool)> = vec![
+            (&alpha[..], &proof[..], &public_key[..], true),
+            (&alpha[..], &mutated[..], &public_key[..], false),
+            (&other_alpha[..], &proof[..], &public_key[..], false),
+            (&alpha[..], &proof[..], other.pk.as_ref(), false),
+            (&alpha[..], &proof[..proof.len() - 1], &public_key[..], false),
+        ];
+
+        for (alpha, proof, public_key, expected) in cases {
+            let library = library_verify(alpha, proof, public_key);
+            let program = program_verify(&mut context, program_id, alpha, proof, public_key).await;
+            assert_eq!(library, program, "verifiers disagree for proof {:?}", proof);
+            assert_eq!(library.is_some(), expected);
+        }
+
+        assert_eq!(library_verify(&alpha, &proof, &public_key), Some(output));
+    }
+}
@@ -2,7 +2,9 @@ use {
     borsh::{BorshDeserialize, BorshSerialize},
     kamui_program::{
         instruction::{VrfCoordinatorInstruction, VerifyVrfInput},
-        state::Subscription,
+        pda::{derive_config_pda, derive_request_pda, derive_vrf_result_pda},
+        state::{Subscription, ACCOUNT_HEADER_LEN, subscription_token_address, subscription_vault_address},
+        verifier::VrfSuite,
     },
     solana_program::{
         instruction::{AccountMeta, Instruction},
@@ -45,6 +47,8 @@ pub struct GameState {
     pub owner: Pubkey,
     /// The VRF subscription used by this game
     pub subscription: Pubkey,
+    /// The VRF coordinator program that serves this game
+    pub vrf_coordinator: Pubkey,
     /// The current random number (1-100)
     pub current_number: u8,
     /// Whether we're waiting for randomness
@@ -63,6 +67,7 @@ impl GameState {
 }
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+#[ignore = "requires devnet and a funded keypair.json"]
 async fn test_vrf_flow_devnet() -> Result<()> {
     // Connect to devnet
     let rpc_url = "https://api.devnet.solana.com".to_string();
@@ -156,13 +161,11 @@ async fn test_vrf_flow_devnet() -> Result<()> {
     );
 
     // Create subscription's token account
-    let subscription_token = spl_associated_token_account::get_associated_token_address(
-        &subscription_account.pubkey(),
-        &mint,
-    );
+    let subscription_vault = subscription_vault_address(&vrf_program_id, &subscription_account.pubkey());
+    let subscription_token = subscription_token_address(&vrf_program_id, &subscription_account.pubkey());
     let create_sub_token_ix = spl_associated_token_account::instruction::create_associated_token_account(
         &payer.pubkey(),
-        &subscription_account.pubkey(),
+        &subscription_vault,
         &mint,
         &spl_token::id(),
     );
@@ -273,16 +276,28 @@ async fn test_vrf_flow_devnet() -> Result<()> {
             AccountMeta::new_readonly(subscription_account.pubkey(), false),
             AccountMeta::new(payer.pubkey(), true),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(vrf_program_id, false),
         ],
         data: init_ix_data,
     };
 
+    // Approve the game owner as a consumer of the subscription
+    let add_consumer_ix = Instruction {
+        program_id: vrf_program_id,
+        accounts: vec![
+            AccountMeta::new(subscription_owner.pubkey(), true),
+            AccountMeta::new(subscription_account.pubkey(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: borsh::to_vec(&VrfCoordinatorInstruction::AddConsumer { consumer: game_owner.pubkey() })?,
+    };
+
     let recent_blockhash = rpc_client.get_latest_blockhash().expect("Failed to get recent blockhash");
     let mut transaction = Transaction::new_with_payer(
-        &[init_game_ix],
+        &[init_game_ix, add_consumer_ix],
         Some(&payer.pubkey()),
     );
-    transaction.sign(&[&payer, &game_owner], recent_blockhash);
+    transaction.sign(&[&payer, &game_owner, &subscription_owner], recent_blockhash);
     
     println!("Initializing game state...");
     let signature = rpc_client
@@ -304,24 +319,19 @@ async fn test_vrf_flow_devnet() -> Result<()> {
     
     // Derive request account PDA using subscription nonce
     let subscription_data = rpc_client.get_account_data(&subscription_account.pubkey())?;
-    let subscription = Subscription::try_from_slice(&subscription_data[8..])?;  // Skip discriminator
-    let (request_account, _request_bump) = Pubkey::find_program_address(
-        &[
-            b"request",
-            subscription_account.pubkey().as_ref(),
-            subscription.nonce.to_le_bytes().as_ref(),
-        ],
-        &vrf_program_id
-    );
+    let subscription = Subscription::try_from_slice(&subscription_data[ACCOUNT_HEADER_LEN..])?;  // Skip header
+    let (request_account, _request_bump) = derive_request_pda(&vrf_program_id, &subscription_account.pubkey(), subscription.nonce);
 
     // Create VRF request instruction
     let seed = [0u8; 32];
+    let (config_account, _) = derive_config_pda(&vrf_program_id);
     let request_ix = VrfCoordinatorInstruction::RequestRandomness {
         seed,
         callback_data: borsh::to_vec(&GameInstruction::ConsumeRandomness)?,
         num_words: 1,
         minimum_confirmations: 1,
         callback_gas_limit: 100_000,  // Reduced from 200_000
+        priority_fee: 0,
     };
     let request_ix_data = borsh::to_vec(&request_ix)?;
     let request_vrf_ix = Instruction {
@@ -331,6 +341,7 @@ async fn test_vrf_flow_devnet() -> Result<()> {
             AccountMeta::new(request_account, false),  // Request account PDA
             AccountMeta::new(subscription_account.pubkey(), false),  // Subscription account
             AccountMeta::new_readonly(system_program::id(), false),  // System program
+            AccountMeta::new_readonly(config_account, false),  // Coordinator config
         ],
         data: request_ix_data,
     };
@@ -368,7 +379,7 @@ async fn test_vrf_flow_devnet() -> Result<()> {
     let vrf_keypair = ECVRFKeyPair::generate(&mut thread_rng());
     let seed = [0u8; 32];  // Example seed
     let (output, proof) = vrf_keypair.output(&seed);
-    let proof_bytes = proof.to_bytes();
+    let proof_bytes = proof.to_bytes_legacy();
     let public_key_bytes = vrf_keypair.pk.as_ref().to_vec();
 
     // Get proof bytes and reformat to match the on-chain program's expected format (gamma || c || s)
@@ -410,16 +421,14 @@ async fn test_vrf_flow_devnet() -> Result<()> {
         .expect("Failed to verify VRF proof");
     println!("VRF proof verified! Signature: {}", signature);
 
-    // Create VRF result PDA using game owner (requester) key
-    let (vrf_result, _bump) = Pubkey::find_program_address(
-        &[b"vrf_result", game_owner.pubkey().as_ref()],
-        &vrf_program_id
-    );
+    // The VRF result PDA is keyed by the request account
+    let (vrf_result, _bump) = derive_vrf_result_pda(&vrf_program_id, &request_account);
 
     // Call FulfillRandomness on VRF coordinator
     let fulfill_ix = VrfCoordinatorInstruction::FulfillRandomness {
         proof: proof_bytes.to_vec(),
         public_key: public_key_bytes,
+        suite: VrfSuite::default(),
     };
     let fulfill_ix_data = borsh::to_vec(&fulfill_ix)?;
 
@@ -507,15 +516,8 @@ async fn test_vrf_flow_devnet() -> Result<()> {
     
     // Create second VRF request using updated subscription nonce
     let subscription_data = rpc_client.get_account_data(&subscription_account.pubkey())?;
-    let subscription = Subscription::try_from_slice(&subscription_data[8..])?;  // Skip discriminator
-    let (request_account, _request_bump) = Pubkey::find_program_address(
-        &[
-            b"request",
-            subscription_account.pubkey().as_ref(),
-            subscription.nonce.to_le_bytes().as_ref(),
-        ],
-        &vrf_program_id
-    );
+    let subscription = Subscription::try_from_slice(&subscription_data[ACCOUNT_HEADER_LEN..])?;  // Skip header
+    let (request_account, _request_bump) = derive_request_pda(&vrf_program_id, &subscription_account.pubkey(), subscription.nonce);
 
     let request_ix = VrfCoordinatorInstruction::RequestRandomness {
         seed: [0u8; 32],  // Use the same seed as the first request
@@ -523,6 +525,7 @@ async fn test_vrf_flow_devnet() -> Result<()> {
         num_words: 1,
         minimum_confirmations: 1,
         callback_gas_limit: 100_000,
+        priority_fee: 0,
     };
     let request_ix_data = borsh::to_vec(&request_ix)?;
     let request_vrf_ix = Instruction {
@@ -532,6 +535,7 @@ async fn test_vrf_flow_devnet() -> Result<()> {
             AccountMeta::new(request_account, false),  // Reuse the same request account
             AccountMeta::new(subscription_account.pubkey(), false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_account, false),
         ],
         data: request_ix_data,
     };
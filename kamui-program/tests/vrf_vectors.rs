@@ -0,0 +1,160 @@
+//! Golden vectors of both VRF suites, checked against the `mangekyou` prover
+//! and the on-chain verifier, so that the two cannot drift apart silently.
+//!
+//! `vectors/vrf_vectors.json` holds the vectors: the sol_vrf Ristretto suite,
+//! generated by `mangekyou` and frozen, and ECVRF-EDWARDS25519-SHA512-TAI from
+//! appendix B.3 of RFC 9381. A change to the prover or the verifier that moves
+//! either off these vectors, for instance to encode_to_curve, fails here.
+//! The verifier program only takes the Ristretto suite, so the Edwards suite
+//! is checked through [VrfSuite::verify], which `FulfillRandomness` calls.
+//!
+//! Under `cargo test-sbf` the verifier program runs as BPF, with the
+//! runtime's curve25519 syscalls.
+
+use {
+    kamui_program::{instruction::VerifyVrfInput, verifier::{self, VrfSuite}},
+    mangekyou::{
+        ecvrf_edwards25519,
+        kamui_vrf::{
+            ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof},
+            VRFKeyPair,
+            VRFProof,
+        },
+    },
+    serde_json::Value,
+    solana_program::{instruction::Instruction, pubkey::Pubkey},
+    solana_program_test::*,
+    solana_sdk::{signer::Signer, transaction::Transaction},
+};
+
+const VECTORS: &str = include_str!("vectors/vrf_vectors.json");
+
+/// A (secret key, public key, alpha, proof, output) vector of `suite`
+struct Vector {
+    suite: VrfSuite,
+    secret_key: [u8; 32],
+    public_key: Vec<u8>,
+    alpha: Vec<u8>,
+    proof: Vec<u8>,
+    output: [u8; 64],
+}
+
+fn vectors() -> Vec<Vector> {
+    let json: Value = serde_json::from_str(VECTORS).unwrap();
+    let hex = |vector: &Value, field: &str| hex::decode(vector[field].as_str().unwrap()).unwrap();
+    let mut vectors = Vec::new();
+    for suite in json["suites"].as_array().unwrap() {
+        let name = suite["suite"].as_str().unwrap();
+        let vrf_suite = match name {
+            "Ristretto255Sha512" => VrfSuite::Ristretto255Sha512,
+            "Edwards25519Sha512Tai" => VrfSuite::Edwards25519Sha512Tai,
+            _ => panic!("unknown suite {}", name),
+        };
+        for vector in suite["vectors"].as_array().unwrap() {
+            vectors.push(Vector {
+                suite: vrf_suite,
+                secret_key: hex(vector, "secret_key").try_into().unwrap(),
+                public_key: hex(vector, "public_key"),
+                alpha: hex(vector, "alpha"),
+                proof: hex(vector, "proof"),
+                output: hex(vector, "output").try_into().unwrap(),
+            });
+        }
+    }
+    vectors
+}
+
+#[test]
+fn test_vectors_cover_both_suites() {
+    let vectors = vectors();
+    for suite in [VrfSuite::Ristretto255Sha512, VrfSuite::Edwards25519Sha512Tai] {
+        assert!(vectors.iter().any(|vector| vector.suite == suite), "no vectors of {:?}", suite);
+    }
+}
+
+#[test]
+fn test_prover_reproduces_vectors() {
+    for vector in vectors() {
+        match vector.suite {
+            VrfSuite::Ristretto255Sha512 => {
+                let keypair = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&vector.secret_key).unwrap());
+                assert_eq!(keypair.pk.as_ref(), &vector.public_key[..]);
+                let proof = VRFKeyPair::prove(&keypair, &vector.alpha);
+                assert_eq!(proof.to_bytes_legacy(), vector.proof);
+                assert_eq!(proof.to_hash(), vector.output);
+                assert_eq!(ECVRFProof::from_bytes(&proof.to_bytes()).unwrap(), proof);
+            }
+            VrfSuite::Edwards25519Sha512Tai => {
+                assert_eq!(ecvrf_edwards25519::public_key(&vector.secret_key).to_vec(), vector.public_key);
+                let proof = ecvrf_edwards25519::prove(&vector.secret_key, &vector.alpha).unwrap();
+                assert_eq!(proof.to_bytes().to_vec(), vector.proof);
+                assert_eq!(proof.to_hash(), vector.output);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_verifier_accepts_vectors() {
+    for vector in vectors() {
+        let Vector { suite, public_key, alpha, proof, output, .. } = vector;
+        assert_eq!(suite.verify(&alpha, &proof, &public_key).unwrap(), output);
+
+        let mut other_alpha = alpha.clone();
+        other_alpha.push(0);
+        assert!(suite.verify(&other_alpha, &proof, &public_key).is_err());
+
+        // A proof of one suite never verifies in the other
+        let other_suite = match suite {
+            VrfSuite::Ristretto255Sha512 => VrfSuite::Edwards25519Sha512Tai,
+            VrfSuite::Edwards25519Sha512Tai => VrfSuite::Ristretto255Sha512,
+        };
+        assert!(other_suite.verify(&alpha, &proof, &public_key).is_err());
+    }
+}
+
+/// The return data of the verifier program for `input`, if it succeeds
+async fn program_verify(
+    context: &mut ProgramTestContext,
+    program_id: Pubkey,
+    input: &VerifyVrfInput,
+) -> Option<Vec<u8>> {
+    let instruction = Instruction::new_with_borsh(program_id, input, vec![]);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    result.result.ok()?;
+    Some(result.metadata.unwrap().return_data.unwrap().data)
+}
+
+#[tokio::test]
+async fn test_program_verifier_accepts_vectors() {
+    let program_id = Pubkey::new_unique();
+    let mut context = ProgramTest::new(
+        "kamui_verifier",
+        program_id,
+        processor!(verifier::process_verify),
+    )
+    .start_with_context()
+    .await;
+
+    for vector in vectors().into_iter().filter(|vector| vector.suite == VrfSuite::Ristretto255Sha512) {
+        let mut input = VerifyVrfInput {
+            alpha_string: vector.alpha,
+            proof_bytes: vector.proof,
+            public_key_bytes: vector.public_key,
+        };
+        let output = program_verify(&mut context, program_id, &input).await;
+        assert_eq!(output, Some(vector.output.to_vec()));
+
+        input.alpha_string.push(0);
+        assert_eq!(program_verify(&mut context, program_id, &input).await, None);
+    }
+}
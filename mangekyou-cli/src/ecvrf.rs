@@ -4,21 +4,46 @@
 use clap::Parser;
 use mangekyou::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey};
 use mangekyou::kamui_vrf::{VRFKeyPair, VRFProof};
+use mangekyou::keystore::{KdfParams, Keystore};
+use mangekyou::serde_helpers::ToFromByteArray;
 use rand::thread_rng;
 use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "ecvrf-cli")]
 #[command(about = "Elliptic Curve Verifiable Random Function (ECVRF) over Ristretto255 according to draft-irtf-cfrg-vrf-15.", long_about = None)]
 enum Command {
     /// Generate a key pair for proving and verification.
-    Keygen,
+    Keygen(KeygenArguments),
 
     /// Create an output/hash and a proof.
     Prove(ProveArguments),
 
     /// Verify an output/hash and a proof.
     Verify(VerifyArguments),
+
+    /// Change the password of an encrypted keystore.
+    Passwd(PasswdArguments),
+}
+
+#[derive(Parser, Clone)]
+struct KeygenArguments {
+    /// A hex encoding of a seed to derive the key pair from. Must be 32 bytes. A random key pair is generated if omitted.
+    #[clap(short, long)]
+    seed: Option<String>,
+
+    /// Write the key pair to an encrypted keystore at this path instead of printing the secret key.
+    #[clap(long, requires = "password_file")]
+    keystore: Option<PathBuf>,
+
+    /// A file whose first line is the password of the keystore.
+    #[clap(long, requires = "keystore")]
+    password_file: Option<PathBuf>,
+
+    /// The scrypt cost of the keystore, as a power of two. Lower values unlock faster but are cheaper to brute-force.
+    #[clap(long, default_value_t = KdfParams::default().log_n, requires = "keystore")]
+    kdf_log_n: u8,
 }
 
 #[derive(Parser, Clone)]
@@ -28,8 +53,16 @@ struct ProveArguments {
     input: String,
 
     /// A hex encoding of the secret key. Corresponds to a scalar in Ristretto255 and must be 32 bytes.
-    #[clap(short, long)]
-    secret_key: String,
+    #[clap(short, long, required_unless_present = "keystore", conflicts_with = "keystore")]
+    secret_key: Option<String>,
+
+    /// An encrypted keystore holding the secret key, as an alternative to passing it in the clear.
+    #[clap(long, requires = "password_file")]
+    keystore: Option<PathBuf>,
+
+    /// A file whose first line is the password of the keystore.
+    #[clap(long, requires = "keystore")]
+    password_file: Option<PathBuf>,
 }
 
 #[derive(Parser, Clone)]
@@ -51,6 +84,34 @@ struct VerifyArguments {
     public_key: String,
 }
 
+#[derive(Parser, Clone)]
+struct PasswdArguments {
+    /// The encrypted keystore, which is rewritten in place.
+    #[clap(long)]
+    keystore: PathBuf,
+
+    /// A file whose first line is the current password.
+    #[clap(long)]
+    password_file: PathBuf,
+
+    /// A file whose first line is the new password.
+    #[clap(long)]
+    new_password_file: PathBuf,
+}
+
+/// Reads the first line of `path`, so that passwords stay out of the shell history.
+fn read_password(path: &Path) -> Result<Vec<u8>, std::io::Error> {
+    let contents = std::fs::read(path)?;
+    let line = contents.split(|b| *b == b'\n').next().unwrap_or_default();
+    Ok(line.strip_suffix(b"\r").unwrap_or(line).to_vec())
+}
+
+fn unlock(keystore: &Path, password_file: &Path) -> Result<ECVRFKeyPair, std::io::Error> {
+    Keystore::load(keystore)?
+        .unlock(&read_password(password_file)?)
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, "Wrong password or corrupted keystore."))
+}
+
 fn main() {
     match execute(Command::parse()) {
         Ok(res) => {
@@ -66,13 +127,31 @@ fn main() {
 
 fn execute(cmd: Command) -> Result<String, std::io::Error> {
     match cmd {
-        Command::Keygen => {
-            let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+        Command::Keygen(arguments) => {
+            let keypair = match arguments.seed {
+                Some(seed) => {
+                    let seed: [u8; 32] = hex::decode(seed)
+                        .ok()
+                        .and_then(|bytes| bytes.try_into().ok())
+                        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Seed must be 32 bytes."))?;
+                    ECVRFKeyPair::from_seed(&seed)
+                }
+                None => ECVRFKeyPair::generate(&mut thread_rng()),
+            };
             let sk_string =
                 hex::encode(&keypair.sk);
             let pk_string =
                 hex::encode(&keypair.pk);
 
+            if let (Some(path), Some(password_file)) = (arguments.keystore, arguments.password_file) {
+                let params = KdfParams { log_n: arguments.kdf_log_n, ..KdfParams::default() };
+                let keystore =
+                    Keystore::create_with_params(&keypair, &read_password(&password_file)?, params, &mut thread_rng())
+                        .map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid scrypt parameters."))?;
+                keystore.save(&path)?;
+                return Ok(format!("Keystore: {}\nPublic key: {}", path.display(), pk_string));
+            }
+
             let mut result = "Secret key: ".to_string();
             result.push_str(&sk_string);
             result.push_str("\nPublic key: ");
@@ -82,18 +161,31 @@ fn execute(cmd: Command) -> Result<String, std::io::Error> {
 
         Command::Prove(arguments) => {
             // Parse inputs
-            let secret_key_bytes = hex::decode(arguments.secret_key)
-                .map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid private key."))?;
             let alpha_string = hex::decode(arguments.input)
                 .map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid input string."))?;
 
-            // Create keypair from the secret key bytes
-            let secret_key = ECVRFPrivateKey::from_bytes(&secret_key_bytes).unwrap();
-            let kp = ECVRFKeyPair::from(secret_key);
+            // Create keypair from the keystore or the secret key bytes
+            let kp = match (arguments.keystore, arguments.password_file, arguments.secret_key) {
+                (Some(keystore), Some(password_file), _) => unlock(&keystore, &password_file)?,
+                (_, _, Some(secret_key)) => {
+                    let secret_key = hex::decode(secret_key)
+                        .ok()
+                        .and_then(|bytes| bytes.try_into().ok())
+                        .and_then(|bytes| ECVRFPrivateKey::from_byte_array(&bytes).ok())
+                        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid private key."))?;
+                    ECVRFKeyPair::from(secret_key)
+                }
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "Either a secret key or a keystore and its password file is required.",
+                    ))
+                }
+            };
 
             // Generate proof
             let proof = kp.prove(&alpha_string);
-            let proof_string = hex::encode(proof.to_bytes());
+            let proof_string = hex::encode(proof.to_bytes_legacy());
             let proof_hash = hex::encode(proof.to_hash());
 
             let mut result = "Proof:  ".to_string();
@@ -118,9 +210,16 @@ fn execute(cmd: Command) -> Result<String, std::io::Error> {
                 .map_err(|_| Error::new(ErrorKind::InvalidInput, "Output must be 64 bytes."))?;
 
             // Create public key and proof from parsed bytes
-            let public_key: ECVRFPublicKey =
-                ECVRFPublicKey::from_bytes(&public_key_bytes).unwrap();
-            let proof: ECVRFProof = ECVRFProof::from_bytes(&proof_bytes).unwrap();
+            let public_key = public_key_bytes
+                .try_into()
+                .ok()
+                .and_then(|bytes| ECVRFPublicKey::from_byte_array(&bytes).ok())
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid public key."))?;
+            let proof = proof_bytes
+                .try_into()
+                .ok()
+                .and_then(|bytes| ECVRFProof::from_byte_array(&bytes).ok())
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid proof string."))?;
 
             if proof
                 .verify_output(&alpha_string, &public_key, &output)
@@ -130,23 +229,64 @@ fn execute(cmd: Command) -> Result<String, std::io::Error> {
             }
             Err(Error::new(ErrorKind::Other, "Proof is not correct."))
         }
+
+        Command::Passwd(arguments) => {
+            let keystore = Keystore::load(&arguments.keystore)?;
+            let rotated = keystore
+                .rotate_password(
+                    &read_password(&arguments.password_file)?,
+                    &read_password(&arguments.new_password_file)?,
+                    &mut thread_rng(),
+                )
+                .map_err(|_| Error::new(ErrorKind::InvalidInput, "Wrong password or corrupted keystore."))?;
+            rotated.save(&arguments.keystore)?;
+            Ok("Password changed.".to_string())
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{execute, Command, ProveArguments, VerifyArguments};
+    use crate::{execute, Command, KeygenArguments, PasswdArguments, ProveArguments, VerifyArguments};
     use regex::Regex;
 
     #[test]
     fn test_keygen() {
-        let result = execute(Command::Keygen).unwrap();
+        let result = execute(Command::Keygen(KeygenArguments {
+            seed: None,
+            keystore: None,
+            password_file: None,
+            kdf_log_n: 18,
+        })).unwrap();
         let expected =
             Regex::new(r"Secret key: ([0-9a-fA-F]{64})\nPublic key: ([0-9a-fA-F]{64})").unwrap();
         assert!(expected.is_match(&result));
     }
 
+    #[test]
+    fn test_keygen_from_seed() {
+        let keygen = |seed: String| {
+            execute(Command::Keygen(KeygenArguments {
+                seed: Some(seed),
+                keystore: None,
+                password_file: None,
+                kdf_log_n: 18,
+            }))
+        };
+
+        let result = keygen("07".repeat(32)).unwrap();
+        let expected = format!(
+            "Secret key: {}\nPublic key: {}",
+            "5ba172a1fb80acef4f4b2c5944c57df50152666092d26d17fe4e97f962d6e506",
+            "0c31efd6cfc2f7fffcf638408063e94906a044ecc256678ffa63a16c5378612d"
+        );
+        assert_eq!(expected, result);
+
+        assert!(keygen("07".repeat(31)).is_err());
+        assert!(keygen("zz".repeat(32)).is_err());
+    }
+
     #[test]
     fn test_prove() {
         let secret_key = "d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f";
@@ -154,14 +294,16 @@ mod tests {
 
         let result = execute(Command::Prove(ProveArguments {
             input: input.to_string(),
-            secret_key: secret_key.to_string(),
+            secret_key: Some(secret_key.to_string()),
+            keystore: None,
+            password_file: None,
         }))
         .unwrap();
 
         let expected = format!(
             "Proof:  {}\nOutput: {}",
-            "54b58f527e999ceedb24485a7629e3caa9f7deb152852a0f483a6646495fa253c4131e87ff0b48fefacf4b5be04211a77390ca85553aa2c06f0023db34e7b36194eadf11539c0ef1c8dcae09aa35580a",
-            "8d9c5b901c05a4edf4dff80bbe970db6ca782fe785ef1375989a3fdb3a93b521f4165ea3a6d1c90ae5641bb528beb98c1eed13d36fb32951ecf163b7900e3da6"
+            "2a8675571961dd9fd1e8e02a6491599dcb708aa4a65ea063c26dc6f04d86196f8a7929df6f38a44a329c6ec8806f0ca5465269b14e0543627b7e60e8e93061ff946d598052d182a17f92832ab5f98f06",
+            "4166c546fe938c453b6bea56e8aee65ab258efaefbc558d3b6f497196c888726f4196c90fb718759c9b176f70278fff9a259e35ed03252a6cd29954e1fe5778d"
         );
         assert_eq!(expected, result);
     }
@@ -170,8 +312,8 @@ mod tests {
     fn test_verify() {
         let input = "4869204b616d756921";
         let public_key = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
-        let proof = "54b58f527e999ceedb24485a7629e3caa9f7deb152852a0f483a6646495fa253c4131e87ff0b48fefacf4b5be04211a77390ca85553aa2c06f0023db34e7b36194eadf11539c0ef1c8dcae09aa35580a";
-        let output = "8d9c5b901c05a4edf4dff80bbe970db6ca782fe785ef1375989a3fdb3a93b521f4165ea3a6d1c90ae5641bb528beb98c1eed13d36fb32951ecf163b7900e3da6";
+        let proof = "2a8675571961dd9fd1e8e02a6491599dcb708aa4a65ea063c26dc6f04d86196f8a7929df6f38a44a329c6ec8806f0ca5465269b14e0543627b7e60e8e93061ff946d598052d182a17f92832ab5f98f06";
+        let output = "4166c546fe938c453b6bea56e8aee65ab258efaefbc558d3b6f497196c888726f4196c90fb718759c9b176f70278fff9a259e35ed03252a6cd29954e1fe5778d";
 
         let verify_result = execute(Command::Verify(VerifyArguments {
             input: input.to_string(),
@@ -191,5 +333,82 @@ mod tests {
             output: output.to_string(),
         }));
         assert!(result.is_err());
+
+        // Well-formed hex but not a valid public key
+        let result = execute(Command::Verify(VerifyArguments {
+            input: input.to_string(),
+            public_key: "ff".repeat(32),
+            proof: proof.to_string(),
+            output: output.to_string(),
+        }));
+        assert!(result.is_err());
+
+        // Truncated proof
+        let result = execute(Command::Verify(VerifyArguments {
+            input: input.to_string(),
+            public_key: public_key.to_string(),
+            proof: proof[..158].to_string(),
+            output: output.to_string(),
+        }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_keystore() {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore = dir.path().join("keystore.json");
+        let password_file = dir.path().join("password");
+        let new_password_file = dir.path().join("new_password");
+        std::fs::write(&password_file, "correct horse\n").unwrap();
+        std::fs::write(&new_password_file, "battery staple").unwrap();
+        let input = "4869204b616d756921";
+
+        let result = execute(Command::Keygen(KeygenArguments {
+            seed: Some("07".repeat(32)),
+            keystore: Some(keystore.clone()),
+            password_file: Some(password_file.clone()),
+            kdf_log_n: 10,
+        }))
+        .unwrap();
+        let expected = format!(
+            "Keystore: {}\nPublic key: {}",
+            keystore.display(),
+            "0c31efd6cfc2f7fffcf638408063e94906a044ecc256678ffa63a16c5378612d"
+        );
+        assert_eq!(expected, result);
+        assert!(!std::fs::read_to_string(&keystore)
+            .unwrap()
+            .contains("5ba172a1fb80acef4f4b2c5944c57df50152666092d26d17fe4e97f962d6e506"));
+
+        let prove = |password_file: &std::path::Path| {
+            execute(Command::Prove(ProveArguments {
+                input: input.to_string(),
+                secret_key: None,
+                keystore: Some(keystore.clone()),
+                password_file: Some(password_file.to_path_buf()),
+            }))
+        };
+        let from_keystore = prove(&password_file).unwrap();
+        let from_secret_key = execute(Command::Prove(ProveArguments {
+            input: input.to_string(),
+            secret_key: Some("5ba172a1fb80acef4f4b2c5944c57df50152666092d26d17fe4e97f962d6e506".to_string()),
+            keystore: None,
+            password_file: None,
+        }))
+        .unwrap();
+        assert_eq!(from_keystore, from_secret_key);
+        assert!(prove(&new_password_file).is_err());
+
+        let passwd = |password_file: &std::path::Path| {
+            execute(Command::Passwd(PasswdArguments {
+                keystore: keystore.clone(),
+                password_file: password_file.to_path_buf(),
+                new_password_file: new_password_file.clone(),
+            }))
+        };
+        assert!(passwd(&new_password_file).is_err());
+        assert!(passwd(&password_file).is_ok());
+        assert!(prove(&password_file).is_err());
+        assert_eq!(prove(&new_password_file).unwrap(), from_secret_key);
     }
 }
@@ -47,17 +47,12 @@ pub mod sigs_cli_test_vectors {
         sig: "54d7d68b43d65f718f3a92041292a514987739c36158a836b2218c505ba0e17c661642e58c996ba78f0cca493690b89658d0da3b9333a9e4fcea9ebf13da64bd01",
     };
 
-    const ECVRF_TEST: TestVector = TestVector {
-        name: "ecvrf",
-        private: "3301e8d7e754db2cf57b0a4ca73f253c7053ad2bc5398777ba039b258e59ad9d",
-        public: "035a8b075508c75f4a124749982a7d21f80d9a5f6893e41a9e955fe4c821e0debe",
-        sig: "54d7d68b43d65f718f3a92041292a514987739c36158a836b2218c505ba0e17c661642e58c996ba78f0cca493690b89658d0da3b9333a9e4fcea9ebf13da64bd",
-    };
-
-    const TEST_VECTORS: &[TestVector] = &[
+    /// The vectors of all schemes, in the order of the `--scheme` values of sigs-cli
+    pub const TEST_VECTORS: &[TestVector] = &[
         ED25519_TEST,
         SECP256K1_TEST,
+        SECP256K1_RECOVERABLE_TEST,
         SECP256R1_TEST,
-        ECVRF_TEST,
+        SECP256R1_RECOVERABLE_TEST,
     ];
 }
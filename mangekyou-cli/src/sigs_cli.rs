@@ -0,0 +1,328 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::{Parser, ValueEnum};
+use mangekyou::sigs::{secp256k1, secp256r1};
+use mangekyou::sigs::ed25519::{Ed25519KeyPair, Ed25519PublicKey};
+use mangekyou::sigs::secp256k1::{Secp256k1KeyPair, Secp256k1PublicKey};
+use mangekyou::sigs::secp256r1::{Secp256r1KeyPair, Secp256r1PublicKey};
+use mangekyou::traits::{
+    KeyPair, RecoverableSigner, ToFromBytes, VerifyRecoverable, VerifyingKey,
+};
+use rand::rngs::StdRng;
+use rand::{thread_rng, SeedableRng};
+use std::io::{Error, ErrorKind};
+
+#[derive(Parser)]
+#[command(name = "sigs-cli")]
+#[command(about = "Sign and verify with Ed25519, or with ECDSA over secp256k1 or secp256r1. ECDSA signs the SHA-256 hash of the message.", long_about = None)]
+enum Command {
+    /// Generate a key pair for signing and verification.
+    Keygen(KeygenArguments),
+
+    /// Sign a message.
+    Sign(SignArguments),
+
+    /// Verify a signature.
+    Verify(VerifyArguments),
+
+    /// Recover the public key from a recoverable signature.
+    Recover(RecoverArguments),
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Scheme {
+    Ed25519,
+    Secp256k1,
+    /// Recoverable secp256k1 signatures, r || s || v.
+    Secp256k1Rec,
+    Secp256r1,
+    /// Recoverable secp256r1 signatures, r || s || v.
+    Secp256r1Rec,
+}
+
+#[derive(Parser, Clone)]
+struct KeygenArguments {
+    #[clap(long, value_enum)]
+    scheme: Scheme,
+
+    /// A hex encoding of a seed to generate the key pair from. Must be 32 bytes. A random key pair is generated if omitted.
+    #[clap(short, long)]
+    seed: Option<String>,
+}
+
+#[derive(Parser, Clone)]
+struct SignArguments {
+    #[clap(long, value_enum)]
+    scheme: Scheme,
+
+    /// The hex encoded message.
+    #[clap(short, long)]
+    msg: String,
+
+    /// A hex encoding of the secret key. Must be 32 bytes.
+    #[clap(short = 'k', long)]
+    secret_key: String,
+}
+
+#[derive(Parser, Clone)]
+struct VerifyArguments {
+    #[clap(long, value_enum)]
+    scheme: Scheme,
+
+    /// The hex encoded message.
+    #[clap(short, long)]
+    msg: String,
+
+    /// The hex encoded signature.
+    #[clap(short = 'g', long)]
+    signature: String,
+
+    /// The hex encoded public key.
+    #[clap(short = 'p', long)]
+    public_key: String,
+}
+
+#[derive(Parser, Clone)]
+struct RecoverArguments {
+    /// One of the recoverable schemes, secp256k1-rec or secp256r1-rec.
+    #[clap(long, value_enum)]
+    scheme: Scheme,
+
+    /// The hex encoded message.
+    #[clap(short, long)]
+    msg: String,
+
+    /// The hex encoded signature. Must be 65 bytes.
+    #[clap(short = 'g', long)]
+    signature: String,
+}
+
+fn main() {
+    match execute(Command::parse()) {
+        Ok(res) => {
+            println!("{}", res);
+            std::process::exit(exitcode::OK);
+        }
+        Err(e) => {
+            println!("Error: {}", e);
+            std::process::exit(exitcode::DATAERR);
+        }
+    }
+}
+
+fn decode(value: &str, name: &str) -> Result<Vec<u8>, Error> {
+    hex::decode(value).map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Invalid {}.", name)))
+}
+
+fn parse<T: ToFromBytes>(value: &str, name: &str) -> Result<T, Error> {
+    T::from_bytes(&decode(value, name)?)
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Invalid {}.", name)))
+}
+
+fn keygen<K: KeyPair + ToFromBytes>(seed: Option<[u8; 32]>) -> String {
+    let keypair = match seed {
+        Some(seed) => K::generate(&mut StdRng::from_seed(seed)),
+        None => K::generate(&mut thread_rng()),
+    };
+    format!(
+        "Private key: {}\nPublic key: {}",
+        hex::encode(keypair.as_bytes()),
+        hex::encode(keypair.public().as_bytes())
+    )
+}
+
+fn sign<K: KeyPair + ToFromBytes>(msg: &[u8], secret_key: &str) -> Result<String, Error> {
+    let keypair: K = parse(secret_key, "secret key")?;
+    Ok(format!("Signature: {}", hex::encode(keypair.sign(msg).as_bytes())))
+}
+
+fn sign_recoverable<K: RecoverableSigner + ToFromBytes>(
+    msg: &[u8],
+    secret_key: &str,
+) -> Result<String, Error>
+where
+    K::Sig: ToFromBytes,
+{
+    let keypair: K = parse(secret_key, "secret key")?;
+    Ok(format!("Signature: {}", hex::encode(keypair.sign_recoverable(msg).as_bytes())))
+}
+
+fn verify<P: VerifyingKey>(msg: &[u8], signature: &str, public_key: &str) -> Result<String, Error> {
+    let public_key: P = parse(public_key, "public key")?;
+    let signature: P::Sig = parse(signature, "signature")?;
+    public_key
+        .verify(msg, &signature)
+        .map(|_| "Signature verified correctly!".to_string())
+        .map_err(|_| Error::other("Signature is not correct."))
+}
+
+fn verify_recoverable<P: VerifyRecoverable + ToFromBytes>(
+    msg: &[u8],
+    signature: &str,
+    public_key: &str,
+) -> Result<String, Error>
+where
+    P::Sig: ToFromBytes,
+{
+    let public_key: P = parse(public_key, "public key")?;
+    let signature: P::Sig = parse(signature, "signature")?;
+    public_key
+        .verify_recoverable(msg, &signature)
+        .map(|_| "Signature verified correctly!".to_string())
+        .map_err(|_| Error::other("Signature is not correct."))
+}
+
+fn execute(cmd: Command) -> Result<String, Error> {
+    match cmd {
+        Command::Keygen(arguments) => {
+            let seed = match arguments.seed {
+                Some(seed) => Some(
+                    decode(&seed, "seed")?
+                        .try_into()
+                        .map_err(|_| Error::new(ErrorKind::InvalidInput, "Seed must be 32 bytes."))?,
+                ),
+                None => None,
+            };
+            Ok(match arguments.scheme {
+                Scheme::Ed25519 => keygen::<Ed25519KeyPair>(seed),
+                Scheme::Secp256k1 | Scheme::Secp256k1Rec => keygen::<Secp256k1KeyPair>(seed),
+                Scheme::Secp256r1 | Scheme::Secp256r1Rec => keygen::<Secp256r1KeyPair>(seed),
+            })
+        }
+
+        Command::Sign(arguments) => {
+            let msg = decode(&arguments.msg, "message")?;
+            let secret_key = &arguments.secret_key;
+            match arguments.scheme {
+                Scheme::Ed25519 => sign::<Ed25519KeyPair>(&msg, secret_key),
+                Scheme::Secp256k1 => sign::<Secp256k1KeyPair>(&msg, secret_key),
+                Scheme::Secp256k1Rec => sign_recoverable::<Secp256k1KeyPair>(&msg, secret_key),
+                Scheme::Secp256r1 => sign::<Secp256r1KeyPair>(&msg, secret_key),
+                Scheme::Secp256r1Rec => sign_recoverable::<Secp256r1KeyPair>(&msg, secret_key),
+            }
+        }
+
+        Command::Verify(arguments) => {
+            let msg = decode(&arguments.msg, "message")?;
+            let (signature, public_key) = (&arguments.signature, &arguments.public_key);
+            match arguments.scheme {
+                Scheme::Ed25519 => verify::<Ed25519PublicKey>(&msg, signature, public_key),
+                Scheme::Secp256k1 => verify::<Secp256k1PublicKey>(&msg, signature, public_key),
+                Scheme::Secp256k1Rec => verify_recoverable::<Secp256k1PublicKey>(&msg, signature, public_key),
+                Scheme::Secp256r1 => verify::<Secp256r1PublicKey>(&msg, signature, public_key),
+                Scheme::Secp256r1Rec => verify_recoverable::<Secp256r1PublicKey>(&msg, signature, public_key),
+            }
+        }
+
+        Command::Recover(arguments) => {
+            let msg = decode(&arguments.msg, "message")?;
+            let signature = decode(&arguments.signature, "signature")?;
+            let public_key = match arguments.scheme {
+                Scheme::Secp256k1Rec => secp256k1::recover(&msg, &signature).map(hex::encode),
+                Scheme::Secp256r1Rec => secp256r1::recover(&msg, &signature).map(hex::encode),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "Only secp256k1-rec and secp256r1-rec signatures are recoverable.",
+                    ))
+                }
+            };
+            public_key
+                .map(|public_key| format!("Public key: {}", public_key))
+                .map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid signature."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{execute, Command, KeygenArguments, RecoverArguments, Scheme, SignArguments, VerifyArguments};
+    use clap::ValueEnum;
+    use mangekyou_cli::sigs_cli_test_vectors::{MSG, SEED, TEST_VECTORS};
+    use regex::Regex;
+
+    fn scheme(name: &str) -> Scheme {
+        Scheme::from_str(name, false).unwrap()
+    }
+
+    #[test]
+    fn test_keygen() {
+        let result = execute(Command::Keygen(KeygenArguments {
+            scheme: Scheme::Secp256r1,
+            seed: None,
+        }))
+        .unwrap();
+        let expected =
+            Regex::new(r"Private key: ([0-9a-f]{64})\nPublic key: ([0-9a-f]{66})").unwrap();
+        assert!(expected.is_match(&result));
+    }
+
+    #[test]
+    fn test_vectors() {
+        for vector in TEST_VECTORS {
+            let keygen = execute(Command::Keygen(KeygenArguments {
+                scheme: scheme(vector.name),
+                seed: Some(SEED.to_string()),
+            }))
+            .unwrap();
+            assert_eq!(
+                keygen,
+                format!("Private key: {}\nPublic key: {}", vector.private, vector.public),
+                "{}",
+                vector.name
+            );
+
+            let sign = execute(Command::Sign(SignArguments {
+                scheme: scheme(vector.name),
+                msg: MSG.to_string(),
+                secret_key: vector.private.to_string(),
+            }))
+            .unwrap();
+            assert_eq!(sign, format!("Signature: {}", vector.sig), "{}", vector.name);
+
+            let verify = |msg: &str| {
+                execute(Command::Verify(VerifyArguments {
+                    scheme: scheme(vector.name),
+                    msg: msg.to_string(),
+                    signature: vector.sig.to_string(),
+                    public_key: vector.public.to_string(),
+                }))
+            };
+            assert_eq!(verify(MSG).unwrap(), "Signature verified correctly!");
+            assert!(verify("00010204").is_err());
+
+            let recover = execute(Command::Recover(RecoverArguments {
+                scheme: scheme(vector.name),
+                msg: MSG.to_string(),
+                signature: vector.sig.to_string(),
+            }));
+            match vector.name.ends_with("-rec") {
+                true => assert_eq!(recover.unwrap(), format!("Public key: {}", vector.public)),
+                false => assert!(recover.is_err()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(execute(Command::Keygen(KeygenArguments {
+            scheme: Scheme::Ed25519,
+            seed: Some("01".repeat(31)),
+        }))
+        .is_err());
+        assert!(execute(Command::Sign(SignArguments {
+            scheme: Scheme::Secp256k1,
+            msg: MSG.to_string(),
+            secret_key: "00".repeat(32),
+        }))
+        .is_err());
+        assert!(execute(Command::Verify(VerifyArguments {
+            scheme: Scheme::Secp256r1Rec,
+            msg: MSG.to_string(),
+            signature: "zz".to_string(),
+            public_key: "02".repeat(33),
+        }))
+        .is_err());
+    }
+}
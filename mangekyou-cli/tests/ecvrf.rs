@@ -25,8 +25,8 @@ fn integration_test_ecvrf_prove() {
 
     let expected = format!(
         "Proof:  {}\nOutput: {}\n",
-        "54b58f527e999ceedb24485a7629e3caa9f7deb152852a0f483a6646495fa253c4131e87ff0b48fefacf4b5be04211a77390ca85553aa2c06f0023db34e7b36194eadf11539c0ef1c8dcae09aa35580a",
-        "8d9c5b901c05a4edf4dff80bbe970db6ca782fe785ef1375989a3fdb3a93b521f4165ea3a6d1c90ae5641bb528beb98c1eed13d36fb32951ecf163b7900e3da6"
+        "2a8675571961dd9fd1e8e02a6491599dcb708aa4a65ea063c26dc6f04d86196f8a7929df6f38a44a329c6ec8806f0ca5465269b14e0543627b7e60e8e93061ff946d598052d182a17f92832ab5f98f06",
+        "4166c546fe938c453b6bea56e8aee65ab258efaefbc558d3b6f497196c888726f4196c90fb718759c9b176f70278fff9a259e35ed03252a6cd29954e1fe5778d"
     );
 
     let output = Command::new(env!("CARGO_BIN_EXE_ecvrf-cli"))
@@ -46,8 +46,8 @@ fn integration_test_ecvrf_prove() {
 fn integration_test_ecvrf_verify() {
     let input = "4869204b616d756921";
     let public_key = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
-    let proof = "54b58f527e999ceedb24485a7629e3caa9f7deb152852a0f483a6646495fa253c4131e87ff0b48fefacf4b5be04211a77390ca85553aa2c06f0023db34e7b36194eadf11539c0ef1c8dcae09aa35580a";
-    let output = "8d9c5b901c05a4edf4dff80bbe970db6ca782fe785ef1375989a3fdb3a93b521f4165ea3a6d1c90ae5641bb528beb98c1eed13d36fb32951ecf163b7900e3da6";
+    let proof = "2a8675571961dd9fd1e8e02a6491599dcb708aa4a65ea063c26dc6f04d86196f8a7929df6f38a44a329c6ec8806f0ca5465269b14e0543627b7e60e8e93061ff946d598052d182a17f92832ab5f98f06";
+    let output = "4166c546fe938c453b6bea56e8aee65ab258efaefbc558d3b6f497196c888726f4196c90fb718759c9b176f70278fff9a259e35ed03252a6cd29954e1fe5778d";
 
     let result = Command::new(env!("CARGO_BIN_EXE_ecvrf-cli"))
         .arg("verify")
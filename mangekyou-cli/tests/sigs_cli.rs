@@ -0,0 +1,51 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use assert_cmd::Command;
+use mangekyou_cli::sigs_cli_test_vectors::{MSG, SEED, TEST_VECTORS};
+
+#[test]
+fn integration_test_sigs_cli_vectors() {
+    for vector in TEST_VECTORS {
+        let keygen = Command::cargo_bin("sigs-cli")
+            .unwrap()
+            .args(["keygen", "--scheme", vector.name, "--seed", SEED])
+            .output()
+            .unwrap();
+        assert!(keygen.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&keygen.stdout),
+            format!("Private key: {}\nPublic key: {}\n", vector.private, vector.public)
+        );
+
+        let sign = Command::cargo_bin("sigs-cli")
+            .unwrap()
+            .args(["sign", "--scheme", vector.name, "--msg", MSG, "--secret-key", vector.private])
+            .output()
+            .unwrap();
+        assert!(sign.status.success());
+        assert_eq!(String::from_utf8_lossy(&sign.stdout), format!("Signature: {}\n", vector.sig));
+
+        let verify = Command::cargo_bin("sigs-cli")
+            .unwrap()
+            .args(["verify", "--scheme", vector.name, "--msg", MSG])
+            .args(["--signature", vector.sig, "--public-key", vector.public])
+            .output()
+            .unwrap();
+        assert!(verify.status.success());
+        assert_eq!(String::from_utf8_lossy(&verify.stdout), "Signature verified correctly!\n");
+    }
+}
+
+#[test]
+fn integration_test_sigs_cli_invalid_signature() {
+    let vector = &TEST_VECTORS[0];
+    let verify = Command::cargo_bin("sigs-cli")
+        .unwrap()
+        .args(["verify", "--scheme", vector.name, "--msg", "00010204"])
+        .args(["--signature", vector.sig, "--public-key", vector.public])
+        .output()
+        .unwrap();
+    assert!(!verify.status.success());
+    assert_eq!(String::from_utf8_lossy(&verify.stdout), "Error: Signature is not correct.\n");
+}
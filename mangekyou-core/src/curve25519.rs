@@ -0,0 +1,129 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The curve25519 operations of the Solana runtime: its syscalls on-chain, and
+//! elsewhere curve25519-dalek, which the runtime computes them with. Points
+//! are compressed encodings and scalars little-endian bytes, and every
+//! operation returns `None` for an invalid point or a non-canonical scalar.
+
+/// Curve identifiers of the syscalls
+#[derive(Clone, Copy)]
+pub(crate) enum Curve {
+    Edwards = 0,
+    Ristretto = 1,
+}
+
+#[cfg(not(target_os = "solana"))]
+mod target_arch {
+    use super::Curve;
+    use alloc::vec::Vec;
+    use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+    use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+    use curve25519_dalek::scalar::Scalar;
+    use curve25519_dalek::traits::VartimeMultiscalarMul;
+
+    fn scalar(bytes: &[u8; 32]) -> Option<Scalar> {
+        Scalar::from_canonical_bytes(*bytes)
+    }
+
+    fn edwards(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+        CompressedEdwardsY(*bytes).decompress()
+    }
+
+    fn ristretto(bytes: &[u8; 32]) -> Option<RistrettoPoint> {
+        CompressedRistretto(*bytes).decompress()
+    }
+
+    pub(crate) fn validate(curve: Curve, point: &[u8; 32]) -> bool {
+        match curve {
+            Curve::Edwards => edwards(point).is_some(),
+            Curve::Ristretto => ristretto(point).is_some(),
+        }
+    }
+
+    /// Multiplies in constant time, as provers multiply by secrets.
+    pub(crate) fn multiply(curve: Curve, scalar_bytes: &[u8; 32], point: &[u8; 32]) -> Option<[u8; 32]> {
+        let scalar = scalar(scalar_bytes)?;
+        Some(match curve {
+            Curve::Edwards => (scalar * edwards(point)?).compress().to_bytes(),
+            Curve::Ristretto => (scalar * ristretto(point)?).compress().to_bytes(),
+        })
+    }
+
+    pub(crate) fn multiscalar_multiply(
+        curve: Curve,
+        scalars: &[[u8; 32]],
+        points: &[[u8; 32]],
+    ) -> Option<[u8; 32]> {
+        let scalars = scalars.iter().map(scalar).collect::<Option<Vec<_>>>()?;
+        match curve {
+            Curve::Edwards => {
+                EdwardsPoint::optional_multiscalar_mul(scalars, points.iter().map(edwards))
+                    .map(|point| point.compress().to_bytes())
+            }
+            Curve::Ristretto => {
+                RistrettoPoint::optional_multiscalar_mul(scalars, points.iter().map(ristretto))
+                    .map(|point| point.compress().to_bytes())
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "solana")]
+mod target_arch {
+    use super::Curve;
+
+    /// Group operation identifier of multiplication
+    const MUL: u64 = 2;
+
+    extern "C" {
+        fn sol_curve_validate_point(curve_id: u64, point_addr: *const u8, result: *mut u8) -> u64;
+        fn sol_curve_group_op(
+            curve_id: u64,
+            group_op: u64,
+            left_input_addr: *const u8,
+            right_input_addr: *const u8,
+            result_point_addr: *mut u8,
+        ) -> u64;
+        fn sol_curve_multiscalar_mul(
+            curve_id: u64,
+            scalars_addr: *const u8,
+            points_addr: *const u8,
+            points_len: u64,
+            result_point_addr: *mut u8,
+        ) -> u64;
+    }
+
+    pub(crate) fn validate(curve: Curve, point: &[u8; 32]) -> bool {
+        let mut result = 0u8;
+        unsafe { sol_curve_validate_point(curve as u64, point.as_ptr(), &mut result) == 0 }
+    }
+
+    pub(crate) fn multiply(curve: Curve, scalar: &[u8; 32], point: &[u8; 32]) -> Option<[u8; 32]> {
+        let mut result = [0u8; 32];
+        let status = unsafe {
+            sol_curve_group_op(curve as u64, MUL, scalar.as_ptr(), point.as_ptr(), result.as_mut_ptr())
+        };
+        (status == 0).then_some(result)
+    }
+
+    pub(crate) fn multiscalar_multiply(
+        curve: Curve,
+        scalars: &[[u8; 32]],
+        points: &[[u8; 32]],
+    ) -> Option<[u8; 32]> {
+        let mut result = [0u8; 32];
+        let status = unsafe {
+            sol_curve_multiscalar_mul(
+                curve as u64,
+                scalars.as_ptr() as *const u8,
+                points.as_ptr() as *const u8,
+                points.len() as u64,
+                result.as_mut_ptr(),
+            )
+        };
+        (status == 0).then_some(result)
+    }
+}
+
+pub(crate) use target_arch::*;
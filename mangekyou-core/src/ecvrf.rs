@@ -0,0 +1,182 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of the `sol_vrf` ECVRF over Ristretto255, following
+//! draft-irtf-cfrg-vrf-15 with the hash to curve of RFC 9380. Proofs are
+//! gamma (32) || c (16) || s (32) and public keys compressed points.
+
+use crate::error::{VerifyError, VerifyResult};
+use crate::ristretto_ops::{DefaultRistrettoOps, RistrettoOps};
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha512};
+
+/// draft-irtf-cfrg-vrf-15 specifies suites for suite-strings 0x00-0x04 and notes that future
+/// designs should specify a different suite_string constant, so we use "sol_vrf" here.
+pub const SUITE_STRING: &[u8; 7] = b"sol_vrf";
+
+/// Length of a challenge. Must not exceed the length of field elements which is 32 in this
+/// case. It is 16, as in the existing ECVRF suites of draft-irtf-cfrg-vrf-15.
+pub const CHALLENGE_LENGTH: usize = 16;
+
+/// Length of a serialized proof: gamma (32) || c (CHALLENGE_LENGTH) || s (32).
+pub const PROOF_LENGTH: usize = 32 + CHALLENGE_LENGTH + 32;
+
+/// Length of a serialized public key.
+pub const KEY_LENGTH: usize = 32;
+
+/// Length of a VRF output.
+pub const OUTPUT_LENGTH: usize = 64;
+
+/// Domain separation tag of [encode_to_curve]: "ECVRF_" || h2c_suite_ID_string || suite_string
+pub const DST: &[u8; 49] = b"ECVRF_ristretto255_XMD:SHA-512_R255MAP_RO_sol_vrf";
+
+/// expand_message_xmd of section 5.3.1 of RFC 9380 with SHA-512: `N`
+/// uniformly random bytes from the concatenation of `msg`, for the domain
+/// separation tag `dst` of at most 255 bytes.
+pub fn expand_message_xmd<const N: usize>(msg: &[&[u8]], dst: &[u8]) -> [u8; N] {
+    const B_IN_BYTES: usize = 64;
+    const S_IN_BYTES: usize = 128;
+    assert!(N <= 255 * B_IN_BYTES && dst.len() <= 255);
+    let dst_prime = |hasher: &mut Sha512| {
+        hasher.update(dst);
+        hasher.update([dst.len() as u8]);
+    };
+
+    // b_0 = H(Z_pad || msg || I2OSP(N, 2) || I2OSP(0, 1) || DST_prime)
+    let mut hasher = Sha512::new();
+    hasher.update([0u8; S_IN_BYTES]);
+    for part in msg {
+        hasher.update(part);
+    }
+    hasher.update((N as u16).to_be_bytes());
+    hasher.update([0u8]);
+    dst_prime(&mut hasher);
+    let b_0: [u8; B_IN_BYTES] = hasher.finalize().into();
+
+    // b_i = H((b_0 xor b_(i-1)) || I2OSP(i, 1) || DST_prime), with b_1 = H(b_0 || 0x01 || DST_prime)
+    let mut uniform_bytes = [0u8; N];
+    let mut b_i = [0u8; B_IN_BYTES];
+    for (i, chunk) in uniform_bytes.chunks_mut(B_IN_BYTES).enumerate() {
+        let mut input = b_0;
+        input.iter_mut().zip(b_i).for_each(|(byte, previous)| *byte ^= previous);
+        let mut hasher = Sha512::new();
+        hasher.update(input);
+        hasher.update([i as u8 + 1]);
+        dst_prime(&mut hasher);
+        b_i = hasher.finalize().into();
+        chunk.copy_from_slice(&b_i[..chunk.len()]);
+    }
+    uniform_bytes
+}
+
+/// hash_to_ristretto255 of RFC 9380, suite ristretto255_XMD:SHA-512_R255MAP_RO_:
+/// 64 bytes of [expand_message_xmd] of the concatenation of `msg` under
+/// `dst`, mapped to a point by the one-way map of section 4.3.4 of RFC
+/// 9496. Returns the encoded point.
+pub fn hash_to_ristretto255(msg: &[&[u8]], dst: &[u8]) -> [u8; 32] {
+    RistrettoPoint::from_uniform_bytes(&expand_message_xmd::<64>(msg, dst)).compress().to_bytes()
+}
+
+/// Encodes `alpha_string` to a point with the public key as salt, see
+/// section 5.4.1.2 of draft-irtf-cfrg-vrf-15, using the
+/// ristretto255_XMD:SHA-512_R255MAP_RO_ suite of RFC 9380. Unlike
+/// try-and-increment, the map runs in constant time.
+pub fn encode_to_curve(public_key: &[u8; KEY_LENGTH], alpha_string: &[u8]) -> [u8; 32] {
+    hash_to_ristretto255(&[public_key, alpha_string], DST)
+}
+
+/// Generate challenge from five points. See section 5.4.3. of draft-irtf-cfrg-vrf-15.
+pub fn challenge_generation(points: [&[u8; 32]; 5]) -> [u8; CHALLENGE_LENGTH] {
+    let mut hasher = Sha512::new();
+    hasher.update(SUITE_STRING);
+    hasher.update([0x02]); // challenge_generation_domain_separator_front
+    for point in points {
+        hasher.update(point);
+    }
+    hasher.update([0x00]); // challenge_generation_domain_separator_back
+    hasher.finalize()[..CHALLENGE_LENGTH].try_into().unwrap()
+}
+
+/// The challenge as a scalar. At 128 bits it is always below the group
+/// order, so every challenge is a canonical scalar.
+pub fn challenge_scalar(challenge: &[u8; CHALLENGE_LENGTH]) -> Scalar {
+    let mut bytes = [0u8; 32];
+    bytes[..CHALLENGE_LENGTH].copy_from_slice(challenge);
+    Scalar::from_canonical_bytes(bytes).expect("challenges are below the group order")
+}
+
+/// VRF output of a proof with `gamma`. Follows section 5.2 of draft-irtf-cfrg-vrf-15.
+pub fn proof_to_hash(gamma: &[u8; 32]) -> [u8; OUTPUT_LENGTH] {
+    let mut hasher = Sha512::new();
+    hasher.update(SUITE_STRING);
+    hasher.update([0x03]); // proof_to_hash_domain_separator_front
+    hasher.update(gamma);
+    hasher.update([0x00]); // proof_to_hash_domain_separator_back
+    hasher.finalize().into()
+}
+
+/// Validates `public_key` as in section 5.4.5 of draft-irtf-cfrg-vrf-15: it
+/// must be the canonical encoding of a group element other than the
+/// identity. Ristretto255 has prime order, so there is no cofactor to clear
+/// as for Edwards25519: the identity, encoded as all zeros, is the only
+/// element of small order, and decoding refuses non-canonical encodings and
+/// the points of the curve outside the group.
+pub fn validate_key<O: RistrettoOps>(public_key: &[u8; KEY_LENGTH]) -> VerifyResult<()> {
+    if public_key.iter().all(|&byte| byte == 0) || !O::is_valid_point(public_key) {
+        return Err(VerifyError::InvalidPublicKey);
+    }
+    Ok(())
+}
+
+/// Verifies `proof` for `alpha_string` under `public_key` with the group
+/// operations of `O`. Each of U and V is computed in a single multiscalar
+/// multiplication, U with the basepoint term through
+/// [RistrettoOps::basepoint_double_scalar_multiply]. They cannot share one,
+/// since both are hashed into the challenge.
+pub fn verify_with<O: RistrettoOps>(
+    proof: &[u8; PROOF_LENGTH],
+    alpha_string: &[u8],
+    public_key: &[u8; KEY_LENGTH],
+) -> VerifyResult<()> {
+    validate_key::<O>(public_key)?;
+    let gamma: &[u8; 32] = proof[..32].try_into().unwrap();
+    let c: &[u8; CHALLENGE_LENGTH] = proof[32..32 + CHALLENGE_LENGTH].try_into().unwrap();
+    let s: &[u8; 32] = proof[32 + CHALLENGE_LENGTH..].try_into().unwrap();
+
+    // A non-canonical s, e.g. s + l for the group order l, would verify as s
+    // does. Rejecting it makes proofs non-malleable. A non-canonical gamma
+    // fails to decode in the multiplication below.
+    if Scalar::from_canonical_bytes(*s).is_none() {
+        return Err(VerifyError::InvalidScalar);
+    }
+
+    let h_point = encode_to_curve(public_key, alpha_string);
+    let neg_challenge = (-challenge_scalar(c)).to_bytes();
+
+    // U = s*B - c*Y
+    let u_point = O::basepoint_double_scalar_multiply(&neg_challenge, public_key, s)
+        .ok_or(VerifyError::InvalidPublicKey)?;
+
+    // V = s*H - c*Gamma
+    let v_point = O::multiscalar_multiply(&[*s, neg_challenge], &[h_point, *gamma])
+        .ok_or(VerifyError::InvalidProofEncoding)?;
+
+    if challenge_generation([public_key, &h_point, gamma, &u_point, &v_point]) != *c {
+        return Err(VerifyError::ChallengeMismatch);
+    }
+    Ok(())
+}
+
+/// Verifies `proof` for `alpha_string` under `public_key` with
+/// [DefaultRistrettoOps] and returns the VRF output.
+pub fn verify(proof: &[u8], alpha_string: &[u8], public_key: &[u8]) -> VerifyResult<[u8; OUTPUT_LENGTH]> {
+    let public_key: &[u8; KEY_LENGTH] = public_key
+        .try_into()
+        .map_err(|_| VerifyError::InputLengthWrong(KEY_LENGTH))?;
+    let proof: &[u8; PROOF_LENGTH] = proof
+        .try_into()
+        .map_err(|_| VerifyError::InputLengthWrong(PROOF_LENGTH))?;
+    verify_with::<DefaultRistrettoOps>(proof, alpha_string, public_key)?;
+    Ok(proof_to_hash(proof[..32].try_into().unwrap()))
+}
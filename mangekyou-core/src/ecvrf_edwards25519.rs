@@ -0,0 +1,185 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of the standard ECVRF-EDWARDS25519-SHA512-TAI suite (suite
+//! string `0x03`) of RFC 9381, the final form of draft-irtf-cfrg-vrf-15, for
+//! proofs from oracle stacks built on it rather than on the Ristretto suite of
+//! [crate::ecvrf].
+//!
+//! Public keys and proof points are Ed25519 encodings, decoded strictly as in
+//! RFC 8032, and public keys of small order are refused.
+
+use crate::curve25519::{self, Curve};
+use crate::error::{VerifyError, VerifyResult};
+use curve25519_dalek::constants::ED25519_BASEPOINT_COMPRESSED;
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha512};
+
+/// Suite string of ECVRF-EDWARDS25519-SHA512-TAI
+pub const SUITE_STRING: u8 = 0x03;
+
+/// Length of a challenge
+pub const CHALLENGE_LENGTH: usize = 16;
+
+/// Length of a proof: gamma (32) || c (16) || s (32)
+pub const PROOF_LENGTH: usize = 32 + CHALLENGE_LENGTH + 32;
+
+/// Length of a public or secret key
+pub const KEY_LENGTH: usize = 32;
+
+/// Length of the VRF output
+pub const OUTPUT_LENGTH: usize = 64;
+
+/// Cofactor of edwards25519, as a scalar
+const COFACTOR: [u8; 32] = {
+    let mut cofactor = [0u8; 32];
+    cofactor[0] = 8;
+    cofactor
+};
+
+/// Encoding of the identity point
+const IDENTITY: [u8; 32] = {
+    let mut identity = [0u8; 32];
+    identity[0] = 1;
+    identity
+};
+
+/// A decoded proof
+#[derive(Clone, Debug, PartialEq)]
+pub struct ECVRFEdwards25519Proof {
+    gamma: [u8; 32],
+    c: [u8; CHALLENGE_LENGTH],
+    s: [u8; 32],
+}
+
+impl ECVRFEdwards25519Proof {
+    /// Decodes `gamma || c || s`, refusing a gamma that is not a point and an
+    /// s that is not reduced
+    pub fn from_bytes(bytes: &[u8]) -> VerifyResult<Self> {
+        if bytes.len() != PROOF_LENGTH {
+            return Err(VerifyError::InputLengthWrong(PROOF_LENGTH));
+        }
+        let gamma: [u8; 32] = bytes[..32].try_into().unwrap();
+        let c = bytes[32..32 + CHALLENGE_LENGTH].try_into().unwrap();
+        let s: [u8; 32] = bytes[32 + CHALLENGE_LENGTH..].try_into().unwrap();
+        if !is_point(&gamma) {
+            return Err(VerifyError::InvalidProofEncoding);
+        }
+        if Scalar::from_canonical_bytes(s).is_none() {
+            return Err(VerifyError::InvalidScalar);
+        }
+        Ok(Self { gamma, c, s })
+    }
+
+    pub fn to_bytes(&self) -> [u8; PROOF_LENGTH] {
+        let mut bytes = [0u8; PROOF_LENGTH];
+        bytes[..32].copy_from_slice(&self.gamma);
+        bytes[32..32 + CHALLENGE_LENGTH].copy_from_slice(&self.c);
+        bytes[32 + CHALLENGE_LENGTH..].copy_from_slice(&self.s);
+        bytes
+    }
+
+    /// Verifies this proof for `alpha` under `public_key`, following section
+    /// 5.3 of RFC 9381 with key validation
+    pub fn verify(&self, alpha: &[u8], public_key: &[u8; KEY_LENGTH]) -> VerifyResult<()> {
+        if !is_point(public_key) || !matches!(cofactor_multiply(public_key), Some(point) if point != IDENTITY) {
+            return Err(VerifyError::InvalidPublicKey);
+        }
+        let h = encode_to_curve(public_key, alpha)?;
+
+        let mut c = [0u8; 32];
+        c[..CHALLENGE_LENGTH].copy_from_slice(&self.c);
+        let neg_c = (-Scalar::from_bits(c)).to_bytes();
+
+        // U = s*B - c*Y and V = s*H - c*Gamma
+        let u = multiscalar_multiply(
+            &[self.s, neg_c],
+            &[ED25519_BASEPOINT_COMPRESSED.to_bytes(), *public_key],
+        )
+        .ok_or(VerifyError::InvalidPublicKey)?;
+        let v = multiscalar_multiply(&[self.s, neg_c], &[h, self.gamma])
+            .ok_or(VerifyError::InvalidProofEncoding)?;
+
+        if challenge(&[public_key, &h, &self.gamma, &u, &v]) != self.c {
+            return Err(VerifyError::ChallengeMismatch);
+        }
+        Ok(())
+    }
+
+    /// The VRF output of this proof, meaningful once it verifies
+    pub fn to_hash(&self) -> [u8; OUTPUT_LENGTH] {
+        let gamma = cofactor_multiply(&self.gamma).expect("gamma is checked when the proof is decoded");
+        let hash = Sha512::new()
+            .chain_update([SUITE_STRING, 0x03])
+            .chain_update(gamma)
+            .chain_update([0x00])
+            .finalize();
+        let mut output = [0u8; OUTPUT_LENGTH];
+        output.copy_from_slice(&hash);
+        output
+    }
+}
+
+/// Verifies `proof` for `alpha` under `public_key` and returns the VRF output
+pub fn verify(proof: &[u8], alpha: &[u8], public_key: &[u8]) -> VerifyResult<[u8; OUTPUT_LENGTH]> {
+    let public_key: &[u8; KEY_LENGTH] = public_key
+        .try_into()
+        .map_err(|_| VerifyError::InputLengthWrong(KEY_LENGTH))?;
+    let proof = ECVRFEdwards25519Proof::from_bytes(proof)?;
+    proof.verify(alpha, public_key)?;
+    Ok(proof.to_hash())
+}
+
+/// Whether `bytes` decodes to a point as in section 5.1.3 of RFC 8032, which
+/// unlike point validation refuses a y of p or more, and the negative zero x
+/// of the points with y = 1 and y = p - 1
+fn is_point(bytes: &[u8; 32]) -> bool {
+    let mut y = *bytes;
+    let x_sign = y[31] >> 7;
+    y[31] &= 0x7f;
+
+    // p = 2^255 - 19, little-endian
+    let y_at_least_p = y[0] >= 0xed && y[1..31].iter().all(|&b| b == 0xff) && y[31] == 0x7f;
+    let mut p_minus_one = [0xff; 32];
+    p_minus_one[0] = 0xec;
+    p_minus_one[31] = 0x7f;
+    let x_is_zero = y == IDENTITY || y == p_minus_one;
+
+    !(y_at_least_p || x_is_zero && x_sign == 1) && curve25519::validate(Curve::Edwards, bytes)
+}
+
+/// Hashes `alpha` to a point of the prime-order subgroup by try-and-increment,
+/// as in section 5.4.1.1 of RFC 9381
+pub fn encode_to_curve(public_key: &[u8; KEY_LENGTH], alpha: &[u8]) -> VerifyResult<[u8; 32]> {
+    for counter in 0..=u8::MAX {
+        let hash = Sha512::new()
+            .chain_update([SUITE_STRING, 0x01])
+            .chain_update(public_key)
+            .chain_update(alpha)
+            .chain_update([counter, 0x00])
+            .finalize();
+        let candidate: [u8; 32] = hash[..32].try_into().unwrap();
+        if is_point(&candidate) {
+            return cofactor_multiply(&candidate).ok_or(VerifyError::HashToCurveFailure);
+        }
+    }
+    Err(VerifyError::HashToCurveFailure)
+}
+
+/// Challenge of section 5.4.3 of RFC 9381
+pub fn challenge(points: &[&[u8; 32]; 5]) -> [u8; CHALLENGE_LENGTH] {
+    let mut hasher = Sha512::new().chain_update([SUITE_STRING, 0x02]);
+    for point in points {
+        hasher.update(point);
+    }
+    hasher.update([0x00]);
+    hasher.finalize()[..CHALLENGE_LENGTH].try_into().unwrap()
+}
+
+fn cofactor_multiply(point: &[u8; 32]) -> Option<[u8; 32]> {
+    curve25519::multiply(Curve::Edwards, &COFACTOR, point)
+}
+
+fn multiscalar_multiply(scalars: &[[u8; 32]; 2], points: &[[u8; 32]; 2]) -> Option<[u8; 32]> {
+    curve25519::multiscalar_multiply(Curve::Edwards, scalars, points)
+}
@@ -0,0 +1,38 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use core::fmt;
+
+/// Why a proof was rejected. Every variant but
+/// [VerifyError::ChallengeMismatch] is for malformed input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// An input does not have the expected length, which is given.
+    InputLengthWrong(usize),
+    /// The public key is not a valid encoding of a point, or has small order.
+    InvalidPublicKey,
+    /// A point of the proof is not a valid encoding.
+    InvalidProofEncoding,
+    /// The scalar s of the proof is not canonical.
+    InvalidScalar,
+    /// alpha could not be hashed to a point of the curve.
+    HashToCurveFailure,
+    /// The proof is well formed but its challenge does not match, so it does
+    /// not verify.
+    ChallengeMismatch,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InputLengthWrong(length) => write!(f, "Expected input of length exactly {}", length),
+            Self::InvalidPublicKey => write!(f, "Invalid public key"),
+            Self::InvalidProofEncoding => write!(f, "Invalid point encoding in proof"),
+            Self::InvalidScalar => write!(f, "Non-canonical scalar in proof"),
+            Self::HashToCurveFailure => write!(f, "Failed to hash to the curve"),
+            Self::ChallengeMismatch => write!(f, "Proof does not verify"),
+        }
+    }
+}
+
+pub type VerifyResult<T> = Result<T, VerifyError>;
@@ -0,0 +1,24 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! ECVRF verification without the standard library, randomness or key
+//! generation, for on-chain programs. The prover side lives in the
+//! `mangekyou` crate, which verifies with this crate, so a program that
+//! depends on it checks proofs with exactly the code the prover is tested
+//! against.
+//!
+//! - [ecvrf]: the `sol_vrf` Ristretto255 suite
+//! - [ecvrf_edwards25519]: ECVRF-EDWARDS25519-SHA512-TAI of RFC 9381
+//!
+//! Group operations use the Solana curve25519 syscalls on-chain, and
+//! curve25519-dalek, which the runtime implements them with, everywhere else.
+
+#![no_std]
+
+extern crate alloc;
+
+mod curve25519;
+pub mod ecvrf;
+pub mod ecvrf_edwards25519;
+pub mod error;
+pub mod ristretto_ops;
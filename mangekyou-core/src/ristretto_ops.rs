@@ -0,0 +1,144 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ristretto255 group operations used by the ECVRF in [crate::ecvrf].
+//!
+//! On Solana these map to the runtime's curve25519 syscalls. Runtimes that
+//! expose the operations elsewhere, or not at all, implement [RistrettoOps]
+//! instead of forking the verification logic.
+//! The implementation used by the ECVRF is [DefaultRistrettoOps], selected by
+//! feature:
+//!
+//! - default: [SolanaSyscallOps]
+//! - `ristretto-dalek`: [DalekOps], pure Rust with no syscalls
+
+use crate::curve25519::{self, Curve};
+use alloc::vec::Vec;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+
+/// The Ristretto basepoint encoded as bytes
+pub const BASEPOINT_BYTES: [u8; 32] = [
+    0xe2, 0xf2, 0xae, 0x0a, 0x6a, 0xbc, 0x4e, 0x71,
+    0xa8, 0x84, 0xa9, 0x61, 0xc5, 0x00, 0x51, 0x5f,
+    0x58, 0xe3, 0x0b, 0x6a, 0xa5, 0x82, 0xdd, 0x8d,
+    0xb6, 0xa6, 0x59, 0x45, 0xe0, 0x8d, 0x2d, 0x76,
+];
+
+/// Ristretto255 operations on compressed points and canonical scalars. Every
+/// operation returns `None` if a point is not a valid encoding or a scalar is
+/// not canonical.
+pub trait RistrettoOps {
+    /// Returns `scalar * point`.
+    fn multiply(scalar: &[u8; 32], point: &[u8; 32]) -> Option<[u8; 32]>;
+
+    /// Returns `sum(scalars[i] * points[i])`.
+    fn multiscalar_multiply<const N: usize>(
+        scalars: &[[u8; 32]; N],
+        points: &[[u8; 32]; N],
+    ) -> Option<[u8; 32]>;
+
+    /// Returns `a * point + b * B`, where `B` is the Ristretto basepoint. By
+    /// default this is one two-point [RistrettoOps::multiscalar_multiply];
+    /// implementations with a precomputed basepoint table override it.
+    fn basepoint_double_scalar_multiply(
+        a: &[u8; 32],
+        point: &[u8; 32],
+        b: &[u8; 32],
+    ) -> Option<[u8; 32]> {
+        Self::multiscalar_multiply(&[*a, *b], &[*point, BASEPOINT_BYTES])
+    }
+
+    /// Returns true if `point` is a valid encoding of a group element.
+    fn is_valid_point(point: &[u8; 32]) -> bool {
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        Self::multiply(&one, point).is_some()
+    }
+}
+
+/// Operations backed by the Solana curve25519 syscalls.
+pub struct SolanaSyscallOps;
+
+impl RistrettoOps for SolanaSyscallOps {
+    fn multiply(scalar: &[u8; 32], point: &[u8; 32]) -> Option<[u8; 32]> {
+        curve25519::multiply(Curve::Ristretto, scalar, point)
+    }
+
+    fn multiscalar_multiply<const N: usize>(
+        scalars: &[[u8; 32]; N],
+        points: &[[u8; 32]; N],
+    ) -> Option<[u8; 32]> {
+        curve25519::multiscalar_multiply(Curve::Ristretto, scalars, points)
+    }
+
+    /// Uses the validation syscall, which costs a fraction of a multiplication.
+    fn is_valid_point(point: &[u8; 32]) -> bool {
+        curve25519::validate(Curve::Ristretto, point)
+    }
+}
+
+/// Operations computed in pure Rust with curve25519-dalek, for runtimes without
+/// the curve25519 syscalls.
+pub struct DalekOps;
+
+impl DalekOps {
+    fn point(bytes: &[u8; 32]) -> Option<RistrettoPoint> {
+        CompressedRistretto(*bytes).decompress()
+    }
+
+    fn scalar(bytes: &[u8; 32]) -> Option<Scalar> {
+        Scalar::from_canonical_bytes(*bytes)
+    }
+}
+
+impl RistrettoOps for DalekOps {
+    fn multiply(scalar: &[u8; 32], point: &[u8; 32]) -> Option<[u8; 32]> {
+        Some((Self::scalar(scalar)? * Self::point(point)?).compress().to_bytes())
+    }
+
+    fn multiscalar_multiply<const N: usize>(
+        scalars: &[[u8; 32]; N],
+        points: &[[u8; 32]; N],
+    ) -> Option<[u8; 32]> {
+        let scalars = scalars
+            .iter()
+            .map(Self::scalar)
+            .collect::<Option<Vec<_>>>()?;
+        let points = points
+            .iter()
+            .map(Self::point)
+            .collect::<Option<Vec<_>>>()?;
+        Some(
+            RistrettoPoint::vartime_multiscalar_mul(scalars, points)
+                .compress()
+                .to_bytes(),
+        )
+    }
+
+    /// Uses curve25519-dalek's precomputed basepoint table.
+    fn basepoint_double_scalar_multiply(
+        a: &[u8; 32],
+        point: &[u8; 32],
+        b: &[u8; 32],
+    ) -> Option<[u8; 32]> {
+        Some(
+            RistrettoPoint::vartime_double_scalar_mul_basepoint(
+                &Self::scalar(a)?,
+                &Self::point(point)?,
+                &Self::scalar(b)?,
+            )
+            .compress()
+            .to_bytes(),
+        )
+    }
+}
+
+/// The implementation used by [crate::ecvrf].
+#[cfg(not(feature = "ristretto-dalek"))]
+pub type DefaultRistrettoOps = SolanaSyscallOps;
+
+/// The implementation used by [crate::ecvrf].
+#[cfg(feature = "ristretto-dalek")]
+pub type DefaultRistrettoOps = DalekOps;
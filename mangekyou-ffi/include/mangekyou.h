@@ -0,0 +1,66 @@
+/*
+ * Copyright (c) 2022, Mangekyou Network, Inc.
+ * SPDX-License-Identifier: Apache-2.0
+ *
+ * C interface to the Kamui ECVRF (ECVRF over Ristretto255, suite "sol_vrf").
+ *
+ * All buffers are fixed size and owned by the caller; nothing is allocated by
+ * the library. Output buffers are only written when MANGEKYOU_OK is returned.
+ */
+
+#ifndef MANGEKYOU_H
+#define MANGEKYOU_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define MANGEKYOU_VRF_SECRET_KEY_LENGTH 32
+#define MANGEKYOU_VRF_PUBLIC_KEY_LENGTH 32
+#define MANGEKYOU_VRF_PROOF_LENGTH 80
+#define MANGEKYOU_VRF_OUTPUT_LENGTH 64
+
+typedef enum {
+    MANGEKYOU_OK = 0,
+    /* A required pointer was null. */
+    MANGEKYOU_NULL_POINTER = 1,
+    /* A key or proof is not a valid encoding. */
+    MANGEKYOU_INVALID_INPUT = 2,
+    /* The proof does not verify. */
+    MANGEKYOU_INVALID_PROOF = 3,
+} mangekyou_status;
+
+/* Generates a fresh key pair from the operating system's RNG. */
+mangekyou_status mangekyou_vrf_keygen(uint8_t secret_key_out[32],
+                                      uint8_t public_key_out[32]);
+
+/* Derives the public key for a secret key, which must be a canonical scalar. */
+mangekyou_status mangekyou_vrf_public_key(const uint8_t secret_key[32],
+                                          uint8_t public_key_out[32]);
+
+/* Computes the proof (gamma || c || s) and VRF output for alpha. output_out
+ * may be null. */
+mangekyou_status mangekyou_vrf_prove(const uint8_t secret_key[32],
+                                     const uint8_t *alpha, size_t alpha_len,
+                                     uint8_t proof_out[80],
+                                     uint8_t output_out[64]);
+
+/* Verifies a proof for alpha and, if it is valid, writes the VRF output to
+ * output_out. output_out may be null. */
+mangekyou_status mangekyou_vrf_verify(const uint8_t public_key[32],
+                                      const uint8_t *alpha, size_t alpha_len,
+                                      const uint8_t proof[80],
+                                      uint8_t output_out[64]);
+
+/* Computes the VRF output of a proof without verifying it. */
+mangekyou_status mangekyou_vrf_proof_to_hash(const uint8_t proof[80],
+                                             uint8_t output_out[64]);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MANGEKYOU_H */
@@ -0,0 +1,268 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! C bindings for the ECVRF in [mangekyou::kamui_vrf], declared in
+//! `include/mangekyou.h`. Keys, proofs and outputs cross the boundary in the
+//! same fixed-size byte encodings the oracle and the on-chain program use.
+
+use mangekyou::kamui_vrf::ecvrf::{
+    ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey, PROOF_LENGTH,
+};
+use mangekyou::kamui_vrf::{VRFKeyPair, VRFProof};
+use rand::thread_rng;
+use std::slice;
+
+const KEY_LENGTH: usize = 32;
+
+/// Status codes returned by every function. Mirrors `mangekyou_status`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MangekyouStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidInput = 2,
+    InvalidProof = 3,
+}
+
+unsafe fn input<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], MangekyouStatus> {
+    match (ptr.is_null(), len) {
+        // An empty alpha may be passed as a null pointer
+        (true, 0) => Ok(&[]),
+        (true, _) => Err(MangekyouStatus::NullPointer),
+        (false, _) => Ok(slice::from_raw_parts(ptr, len)),
+    }
+}
+
+unsafe fn output(ptr: *mut u8, bytes: &[u8]) {
+    if !ptr.is_null() {
+        slice::from_raw_parts_mut(ptr, bytes.len()).copy_from_slice(bytes);
+    }
+}
+
+fn status(result: Result<(), MangekyouStatus>) -> MangekyouStatus {
+    result.err().unwrap_or(MangekyouStatus::Ok)
+}
+
+unsafe fn keypair(secret_key: *const u8) -> Result<ECVRFKeyPair, MangekyouStatus> {
+    if secret_key.is_null() {
+        return Err(MangekyouStatus::NullPointer);
+    }
+    let secret_key = ECVRFPrivateKey::from_bytes(input(secret_key, KEY_LENGTH)?)
+        .map_err(|_| MangekyouStatus::InvalidInput)?;
+    Ok(ECVRFKeyPair::from(secret_key))
+}
+
+/// Generates a fresh key pair from the operating system's RNG.
+///
+/// # Safety
+/// Both pointers must be valid for 32-byte writes.
+#[no_mangle]
+pub unsafe extern "C" fn mangekyou_vrf_keygen(
+    secret_key_out: *mut u8,
+    public_key_out: *mut u8,
+) -> MangekyouStatus {
+    if secret_key_out.is_null() || public_key_out.is_null() {
+        return MangekyouStatus::NullPointer;
+    }
+    let keypair = ECVRFKeyPair::generate(&mut thread_rng());
+    output(secret_key_out, keypair.sk.as_ref());
+    output(public_key_out, keypair.pk.as_ref());
+    MangekyouStatus::Ok
+}
+
+/// Derives the public key for a secret key.
+///
+/// # Safety
+/// `secret_key` must be valid for 32-byte reads and `public_key_out` for 32-byte writes.
+#[no_mangle]
+pub unsafe extern "C" fn mangekyou_vrf_public_key(
+    secret_key: *const u8,
+    public_key_out: *mut u8,
+) -> MangekyouStatus {
+    status((|| {
+        if public_key_out.is_null() {
+            return Err(MangekyouStatus::NullPointer);
+        }
+        output(public_key_out, keypair(secret_key)?.pk.as_ref());
+        Ok(())
+    })())
+}
+
+/// Computes the proof and VRF output for `alpha`. `output_out` may be null.
+///
+/// # Safety
+/// `secret_key` must be valid for 32-byte reads, `alpha` for `alpha_len`-byte
+/// reads, `proof_out` for 80-byte writes and `output_out`, if not null, for
+/// 64-byte writes.
+#[no_mangle]
+pub unsafe extern "C" fn mangekyou_vrf_prove(
+    secret_key: *const u8,
+    alpha: *const u8,
+    alpha_len: usize,
+    proof_out: *mut u8,
+    output_out: *mut u8,
+) -> MangekyouStatus {
+    status((|| {
+        if proof_out.is_null() {
+            return Err(MangekyouStatus::NullPointer);
+        }
+        let alpha = input(alpha, alpha_len)?;
+        let (hash, proof) = keypair(secret_key)?.output(alpha);
+        output(proof_out, &proof.to_bytes_legacy());
+        output(output_out, &hash);
+        Ok(())
+    })())
+}
+
+/// Verifies `proof` for `alpha` under `public_key` and, if it is valid, writes
+/// the VRF output to `output_out`. `output_out` may be null.
+///
+/// # Safety
+/// `public_key` must be valid for 32-byte reads, `alpha` for `alpha_len`-byte
+/// reads, `proof` for 80-byte reads and `output_out`, if not null, for 64-byte
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn mangekyou_vrf_verify(
+    public_key: *const u8,
+    alpha: *const u8,
+    alpha_len: usize,
+    proof: *const u8,
+    output_out: *mut u8,
+) -> MangekyouStatus {
+    status((|| {
+        if public_key.is_null() || proof.is_null() {
+            return Err(MangekyouStatus::NullPointer);
+        }
+        let alpha = input(alpha, alpha_len)?;
+        let public_key = ECVRFPublicKey::from_bytes(input(public_key, KEY_LENGTH)?)
+            .map_err(|_| MangekyouStatus::InvalidInput)?;
+        let proof = ECVRFProof::from_bytes_legacy(input(proof, PROOF_LENGTH)?)
+            .map_err(|_| MangekyouStatus::InvalidInput)?;
+        proof
+            .verify(alpha, &public_key)
+            .map_err(|_| MangekyouStatus::InvalidProof)?;
+        output(output_out, &proof.to_hash());
+        Ok(())
+    })())
+}
+
+/// Computes the VRF output of `proof` without verifying it.
+///
+/// # Safety
+/// `proof` must be valid for 80-byte reads and `output_out` for 64-byte writes.
+#[no_mangle]
+pub unsafe extern "C" fn mangekyou_vrf_proof_to_hash(
+    proof: *const u8,
+    output_out: *mut u8,
+) -> MangekyouStatus {
+    status((|| {
+        if proof.is_null() || output_out.is_null() {
+            return Err(MangekyouStatus::NullPointer);
+        }
+        let proof = ECVRFProof::from_bytes_legacy(input(proof, PROOF_LENGTH)?)
+            .map_err(|_| MangekyouStatus::InvalidInput)?;
+        output(output_out, &proof.to_hash());
+        Ok(())
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    const OUTPUT_LENGTH: usize = 64;
+
+    const SECRET_KEY: &str = "d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f";
+    const INPUT: &str = "4869204b616d756921";
+    const PROOF: &str = "2a8675571961dd9fd1e8e02a6491599dcb708aa4a65ea063c26dc6f04d86196f8a7929df6f38a44a329c6ec8806f0ca5465269b14e0543627b7e60e8e93061ff946d598052d182a17f92832ab5f98f06";
+    const OUTPUT: &str = "4166c546fe938c453b6bea56e8aee65ab258efaefbc558d3b6f497196c888726f4196c90fb718759c9b176f70278fff9a259e35ed03252a6cd29954e1fe5778d";
+
+    #[test]
+    fn test_prove_and_verify() {
+        let secret_key = hex::decode(SECRET_KEY).unwrap();
+        let alpha = hex::decode(INPUT).unwrap();
+        let mut public_key = [0u8; KEY_LENGTH];
+        let mut proof = [0u8; PROOF_LENGTH];
+        let mut hash = [0u8; OUTPUT_LENGTH];
+
+        unsafe {
+            assert_eq!(
+                mangekyou_vrf_public_key(secret_key.as_ptr(), public_key.as_mut_ptr()),
+                MangekyouStatus::Ok
+            );
+            assert_eq!(
+                mangekyou_vrf_prove(
+                    secret_key.as_ptr(),
+                    alpha.as_ptr(),
+                    alpha.len(),
+                    proof.as_mut_ptr(),
+                    hash.as_mut_ptr()
+                ),
+                MangekyouStatus::Ok
+            );
+            assert_eq!(hex::encode(proof), PROOF);
+            assert_eq!(hex::encode(hash), OUTPUT);
+
+            let mut verified = [0u8; OUTPUT_LENGTH];
+            assert_eq!(
+                mangekyou_vrf_verify(
+                    public_key.as_ptr(),
+                    alpha.as_ptr(),
+                    alpha.len(),
+                    proof.as_ptr(),
+                    verified.as_mut_ptr()
+                ),
+                MangekyouStatus::Ok
+            );
+            assert_eq!(verified, hash);
+
+            let mut from_proof = [0u8; OUTPUT_LENGTH];
+            assert_eq!(
+                mangekyou_vrf_proof_to_hash(proof.as_ptr(), from_proof.as_mut_ptr()),
+                MangekyouStatus::Ok
+            );
+            assert_eq!(from_proof, hash);
+        }
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let mut secret_key = [0u8; KEY_LENGTH];
+        let mut public_key = [0u8; KEY_LENGTH];
+        let mut proof = [0u8; PROOF_LENGTH];
+
+        unsafe {
+            assert_eq!(
+                mangekyou_vrf_keygen(secret_key.as_mut_ptr(), public_key.as_mut_ptr()),
+                MangekyouStatus::Ok
+            );
+            assert_eq!(
+                mangekyou_vrf_prove(secret_key.as_ptr(), ptr::null(), 0, proof.as_mut_ptr(), ptr::null_mut()),
+                MangekyouStatus::Ok
+            );
+            assert_eq!(
+                mangekyou_vrf_verify(public_key.as_ptr(), ptr::null(), 0, proof.as_ptr(), ptr::null_mut()),
+                MangekyouStatus::Ok
+            );
+
+            // Proof for a different alpha
+            assert_eq!(
+                mangekyou_vrf_verify(public_key.as_ptr(), b"x".as_ptr(), 1, proof.as_ptr(), ptr::null_mut()),
+                MangekyouStatus::InvalidProof
+            );
+            assert_eq!(
+                mangekyou_vrf_verify(public_key.as_ptr(), ptr::null(), 1, proof.as_ptr(), ptr::null_mut()),
+                MangekyouStatus::NullPointer
+            );
+            assert_eq!(
+                mangekyou_vrf_verify(ptr::null(), ptr::null(), 0, proof.as_ptr(), ptr::null_mut()),
+                MangekyouStatus::NullPointer
+            );
+            assert_eq!(
+                mangekyou_vrf_prove(secret_key.as_ptr(), ptr::null(), 0, ptr::null_mut(), ptr::null_mut()),
+                MangekyouStatus::NullPointer
+            );
+        }
+    }
+}
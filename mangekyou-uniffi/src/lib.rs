@@ -0,0 +1,117 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Swift and Kotlin bindings for verifying ECVRF proofs on device, so a mobile
+//! client can check a server-relayed result before trusting it.
+//!
+//! Generate the bindings from the built library:
+//! ```text
+//! cargo build -p mangekyou-uniffi --release
+//! cargo run -p mangekyou-uniffi --bin uniffi-bindgen generate \
+//!     --library target/release/libmangekyou_uniffi.so --language swift --out-dir out
+//! ```
+
+use mangekyou::kamui_vrf::ecvrf::{ECVRFProof, ECVRFPublicKey};
+use mangekyou::kamui_vrf::VRFProof;
+
+uniffi::setup_scaffolding!();
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum VrfError {
+    #[error("Invalid public key")]
+    InvalidPublicKey,
+    #[error("Invalid proof encoding")]
+    InvalidProof,
+    #[error("Proof does not verify")]
+    VerificationFailed,
+    #[error("Invalid output")]
+    InvalidOutput,
+    #[error("Empty range")]
+    EmptyRange,
+}
+
+/// Verifies `proof` (80 bytes, gamma || c || s) for `alpha` under the 32-byte
+/// `public_key` and returns the 64-byte VRF output.
+#[uniffi::export]
+pub fn verify(proof: Vec<u8>, alpha: Vec<u8>, public_key: Vec<u8>) -> Result<Vec<u8>, VrfError> {
+    let public_key =
+        ECVRFPublicKey::from_bytes(&public_key).map_err(|_| VrfError::InvalidPublicKey)?;
+    let proof = ECVRFProof::from_bytes_legacy(&proof).map_err(|_| VrfError::InvalidProof)?;
+    proof
+        .verify(&alpha, &public_key)
+        .map_err(|_| VrfError::VerificationFailed)?;
+    Ok(proof.to_hash().to_vec())
+}
+
+/// Returns the 64-byte VRF output of `proof` without verifying it.
+#[uniffi::export]
+pub fn proof_to_hash(proof: Vec<u8>) -> Result<Vec<u8>, VrfError> {
+    let proof = ECVRFProof::from_bytes_legacy(&proof).map_err(|_| VrfError::InvalidProof)?;
+    Ok(proof.to_hash().to_vec())
+}
+
+/// Maps a VRF output to `min..=max` the way the on-chain consumers do: the
+/// first 8 bytes as a little-endian u64, reduced modulo the size of the range.
+#[uniffi::export]
+pub fn output_to_range(output: Vec<u8>, min: u64, max: u64) -> Result<u64, VrfError> {
+    if min > max {
+        return Err(VrfError::EmptyRange);
+    }
+    let first_word: [u8; 8] = output
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(VrfError::InvalidOutput)?;
+    let value = u64::from_le_bytes(first_word);
+    match (max - min).checked_add(1) {
+        Some(size) => Ok(min + value % size),
+        // The range covers every u64
+        None => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "4869204b616d756921";
+    const PUBLIC_KEY: &str = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+    const PROOF: &str = "2a8675571961dd9fd1e8e02a6491599dcb708aa4a65ea063c26dc6f04d86196f8a7929df6f38a44a329c6ec8806f0ca5465269b14e0543627b7e60e8e93061ff946d598052d182a17f92832ab5f98f06";
+    const OUTPUT: &str = "4166c546fe938c453b6bea56e8aee65ab258efaefbc558d3b6f497196c888726f4196c90fb718759c9b176f70278fff9a259e35ed03252a6cd29954e1fe5778d";
+
+    fn decode(s: &str) -> Vec<u8> {
+        hex::decode(s).unwrap()
+    }
+
+    #[test]
+    fn test_verify() {
+        let output = verify(decode(PROOF), decode(INPUT), decode(PUBLIC_KEY)).unwrap();
+        assert_eq!(output, decode(OUTPUT));
+        assert_eq!(proof_to_hash(decode(PROOF)).unwrap(), output);
+
+        let mut tampered = decode(PROOF);
+        tampered[40] ^= 1;
+        assert!(matches!(
+            verify(tampered, decode(INPUT), decode(PUBLIC_KEY)),
+            Err(VrfError::VerificationFailed)
+        ));
+        assert!(matches!(
+            verify(decode(PROOF), decode(INPUT), vec![0; 31]),
+            Err(VrfError::InvalidPublicKey)
+        ));
+        assert!(matches!(
+            verify(vec![0; 79], decode(INPUT), decode(PUBLIC_KEY)),
+            Err(VrfError::InvalidProof)
+        ));
+    }
+
+    #[test]
+    fn test_output_to_range() {
+        let output = decode(OUTPUT);
+        let first_word = u64::from_le_bytes(output[..8].try_into().unwrap());
+        assert_eq!(output_to_range(output.clone(), 1, 100).unwrap(), first_word % 100 + 1);
+        assert_eq!(output_to_range(output.clone(), 0, u64::MAX).unwrap(), first_word);
+        assert_eq!(output_to_range(output.clone(), 7, 7).unwrap(), 7);
+        assert!(matches!(output_to_range(output, 2, 1), Err(VrfError::EmptyRange)));
+        assert!(matches!(output_to_range(vec![0; 7], 1, 100), Err(VrfError::InvalidOutput)));
+    }
+}
@@ -0,0 +1,6 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
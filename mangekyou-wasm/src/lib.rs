@@ -0,0 +1,155 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! WebAssembly bindings for the ECVRF, so a frontend can check a VRF result
+//! without trusting a backend, and a Node oracle can prove with exactly the
+//! suite the on-chain program verifies. Byte strings cross the boundary as
+//! `Uint8Array`s in the encodings of the CLI and the program.
+//!
+//! Build with `wasm-pack build mangekyou-wasm --target web`, or
+//! `--target nodejs` for Node.
+
+use mangekyou::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey};
+use mangekyou::kamui_vrf::{VRFKeyPair, VRFProof};
+use rand::thread_rng;
+use wasm_bindgen::prelude::*;
+
+/// A key pair of 32-byte keys.
+#[wasm_bindgen]
+pub struct KeyPair {
+    secret_key: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl KeyPair {
+    #[wasm_bindgen(getter, js_name = secretKey)]
+    pub fn secret_key(&self) -> Vec<u8> {
+        self.secret_key.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+}
+
+impl From<ECVRFKeyPair> for KeyPair {
+    fn from(keypair: ECVRFKeyPair) -> Self {
+        Self {
+            secret_key: keypair.sk.as_ref().to_vec(),
+            public_key: keypair.pk.as_ref().to_vec(),
+        }
+    }
+}
+
+/// Generates a fresh key pair, in the browser from `crypto.getRandomValues`.
+#[wasm_bindgen]
+pub fn keygen() -> KeyPair {
+    ECVRFKeyPair::generate(&mut thread_rng()).into()
+}
+
+/// Derives the key pair of a 32-byte `seed`, as `ecvrf-cli keygen --seed`
+/// does, or returns `undefined` if `seed` is not 32 bytes.
+#[wasm_bindgen(js_name = keygenFromSeed)]
+pub fn keygen_from_seed(seed: &[u8]) -> Option<KeyPair> {
+    let seed = seed.try_into().ok()?;
+    Some(ECVRFKeyPair::from_seed(seed).into())
+}
+
+/// Returns the 32-byte public key of `secret_key`, or `undefined` if
+/// `secret_key` is malformed.
+#[wasm_bindgen(js_name = publicKey)]
+pub fn public_key(secret_key: &[u8]) -> Option<Vec<u8>> {
+    let keypair = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(secret_key).ok()?);
+    Some(keypair.pk.as_ref().to_vec())
+}
+
+/// Returns the 80-byte proof (gamma || c || s) for `alpha` under the 32-byte
+/// `secret_key`, or `undefined` if `secret_key` is malformed. Its output is
+/// [proof_to_hash] of it.
+#[wasm_bindgen]
+pub fn prove(secret_key: &[u8], alpha: &[u8]) -> Option<Vec<u8>> {
+    let keypair = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(secret_key).ok()?);
+    Some(keypair.prove(alpha).to_bytes_legacy())
+}
+
+/// Returns true if `proof` (80 bytes, gamma || c || s) is a valid proof for
+/// `alpha` under the 32-byte `public_key`. Malformed inputs are not valid.
+#[wasm_bindgen]
+pub fn verify(proof: &[u8], alpha: &[u8], public_key: &[u8]) -> bool {
+    let (Ok(proof), Ok(public_key)) = (
+        ECVRFProof::from_bytes_legacy(proof),
+        ECVRFPublicKey::from_bytes(public_key),
+    ) else {
+        return false;
+    };
+    proof.verify(alpha, &public_key).is_ok()
+}
+
+/// Returns the 64-byte VRF output of `proof`, or `undefined` if `proof` is
+/// malformed. The output is only meaningful once [verify] has accepted the proof.
+#[wasm_bindgen(js_name = proofToHash)]
+pub fn proof_to_hash(proof: &[u8]) -> Option<Vec<u8>> {
+    ECVRFProof::from_bytes_legacy(proof)
+        .ok()
+        .map(|proof| proof.to_hash().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{keygen, keygen_from_seed, proof_to_hash, prove, public_key, verify};
+
+    const SECRET_KEY: &str = "d354a0525580ab79bf67797b824a7df3ddf81ff45729175fa4d98d9f3dcd150f";
+    const INPUT: &str = "4869204b616d756921";
+    const PUBLIC_KEY: &str = "7a66a0fe0f2bcdcea5bfb97e3e9f6b298d25899052721bc2b4f3cb570a921b23";
+    const PROOF: &str = "2a8675571961dd9fd1e8e02a6491599dcb708aa4a65ea063c26dc6f04d86196f8a7929df6f38a44a329c6ec8806f0ca5465269b14e0543627b7e60e8e93061ff946d598052d182a17f92832ab5f98f06";
+    const OUTPUT: &str = "4166c546fe938c453b6bea56e8aee65ab258efaefbc558d3b6f497196c888726f4196c90fb718759c9b176f70278fff9a259e35ed03252a6cd29954e1fe5778d";
+
+    fn decode(s: &str) -> Vec<u8> {
+        hex::decode(s).unwrap()
+    }
+
+    #[test]
+    fn test_verify() {
+        let (input, public_key, proof) = (decode(INPUT), decode(PUBLIC_KEY), decode(PROOF));
+        assert!(verify(&proof, &input, &public_key));
+
+        let mut tampered = proof.clone();
+        tampered[40] ^= 1;
+        assert!(!verify(&tampered, &input, &public_key));
+        assert!(!verify(&proof, b"Hi Kamui?", &public_key));
+        assert!(!verify(&proof[..79], &input, &public_key));
+        assert!(!verify(&proof, &input, &public_key[..31]));
+    }
+
+    #[test]
+    fn test_proof_to_hash() {
+        assert_eq!(proof_to_hash(&decode(PROOF)), Some(decode(OUTPUT)));
+        assert_eq!(proof_to_hash(&[0u8; 79]), None);
+    }
+
+    #[test]
+    fn test_prove() {
+        let (secret_key, input) = (decode(SECRET_KEY), decode(INPUT));
+        assert_eq!(public_key(&secret_key), Some(decode(PUBLIC_KEY)));
+        assert_eq!(prove(&secret_key, &input), Some(decode(PROOF)));
+
+        assert_eq!(public_key(&secret_key[..31]), None);
+        assert_eq!(prove(&secret_key[..31], &input), None);
+    }
+
+    #[test]
+    fn test_keygen() {
+        let keypair = keygen();
+        assert_eq!(public_key(&keypair.secret_key()), Some(keypair.public_key()));
+        let proof = prove(&keypair.secret_key(), b"alpha").unwrap();
+        assert!(verify(&proof, b"alpha", &keypair.public_key()));
+
+        let seed = [7u8; 32];
+        let from_seed = keygen_from_seed(&seed).unwrap();
+        assert_eq!(from_seed.secret_key(), keygen_from_seed(&seed).unwrap().secret_key());
+        assert_eq!(public_key(&from_seed.secret_key()), Some(from_seed.public_key()));
+        assert!(keygen_from_seed(&seed[..31]).is_none());
+    }
+}
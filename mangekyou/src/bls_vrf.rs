@@ -0,0 +1,205 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A VRF from BLS signatures over BLS12-381: the proof for alpha is the BLS
+//! signature on alpha, hashed to G1 with `BLS12381G1_XMD:SHA-256_SSWU_RO_`
+//! of RFC 9380, under a public key on G2. BLS signatures are deterministic and
+//! unique for a valid key, so the output, a hash of the proof, is as well.
+//!
+//! Unlike ECVRF proofs, proofs of the same alpha under different keys
+//! aggregate into one proof under the aggregate of the keys. This is the
+//! groundwork for threshold randomness: an output no single oracle controls,
+//! checked with a single pairing equation.
+
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::kamui_vrf::{VRFKeyPair, VRFPrivateKey, VRFProof, VRFPublicKey};
+use crate::traits::AllowedRng;
+use blst::min_sig::{AggregatePublicKey, AggregateSignature, PublicKey, SecretKey, Signature};
+use blst::BLST_ERROR;
+use sha2::{Digest, Sha512};
+
+/// Domain separation tag of the hash of alpha to G1
+pub const DST: &[u8] = b"KAMUI_BLS_VRF_BLS12381G1_XMD:SHA-256_SSWU_RO_";
+
+/// Domain separation tag of proofs of possession, that of the proof of
+/// possession scheme of the IETF BLS signature draft
+pub const POP_DST: &[u8] = b"BLS_POP_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Domain separation of the VRF output
+const OUTPUT_DST: &[u8] = b"KAMUI_BLS_VRF_OUTPUT";
+
+/// Length of a compressed G2 public key
+pub const PUBLIC_KEY_LENGTH: usize = 96;
+
+/// Length of a secret key
+pub const SECRET_KEY_LENGTH: usize = 32;
+
+/// Length of a compressed G1 proof
+pub const PROOF_LENGTH: usize = 48;
+
+/// Length of the VRF output
+pub const OUTPUT_LENGTH: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlsVRFPublicKey(PublicKey);
+
+impl VRFPublicKey for BlsVRFPublicKey {
+    type PrivateKey = BlsVRFPrivateKey;
+}
+
+impl BlsVRFPublicKey {
+    /// Decodes a compressed G2 point, refusing the identity and points outside
+    /// the prime-order subgroup
+    pub fn from_bytes(bytes: &[u8]) -> MangekyouResult<Self> {
+        if bytes.len() != PUBLIC_KEY_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(PUBLIC_KEY_LENGTH));
+        }
+        PublicKey::key_validate(bytes)
+            .map(Self)
+            .map_err(|_| MangekyouError::InvalidPublicKey)
+    }
+
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.0.compress()
+    }
+
+    /// Verifies a proof of possession of the secret key of this public key,
+    /// see [BlsVRFKeyPair::prove_possession]
+    pub fn verify_possession(&self, proof: &BlsVRFProof) -> MangekyouResult<()> {
+        match proof.0.verify(false, &self.to_bytes(), POP_DST, &[], &self.0, false) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            _ => Err(MangekyouError::InvalidProof),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BlsVRFPrivateKey(SecretKey);
+
+impl VRFPrivateKey for BlsVRFPrivateKey {
+    type PublicKey = BlsVRFPublicKey;
+}
+
+impl BlsVRFPrivateKey {
+    /// Decodes a big-endian scalar in [1, r)
+    pub fn from_bytes(bytes: &[u8]) -> MangekyouResult<Self> {
+        if bytes.len() != SECRET_KEY_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(SECRET_KEY_LENGTH));
+        }
+        SecretKey::from_bytes(bytes)
+            .map(Self)
+            .map_err(|_| MangekyouError::InvalidInput)
+    }
+
+    /// Derives a secret key from at least 32 bytes of `ikm` with the KeyGen
+    /// of the IETF BLS signature draft
+    pub fn from_ikm(ikm: &[u8]) -> MangekyouResult<Self> {
+        SecretKey::key_gen(ikm, &[])
+            .map(Self)
+            .map_err(|_| MangekyouError::InvalidInput)
+    }
+
+    pub fn to_bytes(&self) -> [u8; SECRET_KEY_LENGTH] {
+        self.0.to_bytes()
+    }
+}
+
+pub struct BlsVRFKeyPair {
+    pub pk: BlsVRFPublicKey,
+    pub sk: BlsVRFPrivateKey,
+}
+
+impl From<BlsVRFPrivateKey> for BlsVRFKeyPair {
+    fn from(sk: BlsVRFPrivateKey) -> Self {
+        Self { pk: BlsVRFPublicKey(sk.0.sk_to_pk()), sk }
+    }
+}
+
+impl BlsVRFKeyPair {
+    /// Proves possession of the secret key by signing the public key. Keys
+    /// must come with a verified proof of possession before they are
+    /// aggregated with [aggregate_public_keys]; otherwise a rogue key chosen
+    /// as a function of the others could cancel them out.
+    pub fn prove_possession(&self) -> BlsVRFProof {
+        BlsVRFProof(self.sk.0.sign(&self.pk.to_bytes(), POP_DST, &[]))
+    }
+}
+
+impl VRFKeyPair<OUTPUT_LENGTH> for BlsVRFKeyPair {
+    type Proof = BlsVRFProof;
+    type PrivateKey = BlsVRFPrivateKey;
+    type PublicKey = BlsVRFPublicKey;
+
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        let mut ikm = [0u8; 32];
+        rng.fill_bytes(&mut ikm);
+        Self::from(BlsVRFPrivateKey::from_ikm(&ikm).expect("32 bytes of key material suffice"))
+    }
+
+    fn prove(&self, alpha_string: &[u8]) -> BlsVRFProof {
+        BlsVRFProof(self.sk.0.sign(alpha_string, DST, &[]))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlsVRFProof(Signature);
+
+impl BlsVRFProof {
+    /// Decodes a compressed G1 point, refusing the identity and points outside
+    /// the prime-order subgroup
+    pub fn from_bytes(bytes: &[u8]) -> MangekyouResult<Self> {
+        if bytes.len() != PROOF_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(PROOF_LENGTH));
+        }
+        Signature::sig_validate(bytes, true)
+            .map(Self)
+            .map_err(|_| MangekyouError::InvalidProofEncoding)
+    }
+}
+
+impl VRFProof<OUTPUT_LENGTH> for BlsVRFProof {
+    type PublicKey = BlsVRFPublicKey;
+
+    /// Checks the pairing equation of the signature. Proofs and keys are
+    /// validated when they are decoded.
+    fn verify(&self, alpha_string: &[u8], public_key: &Self::PublicKey) -> MangekyouResult<()> {
+        match self.0.verify(false, alpha_string, DST, &[], &public_key.0, false) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            _ => Err(MangekyouError::InvalidProof),
+        }
+    }
+
+    fn to_hash(&self) -> [u8; OUTPUT_LENGTH] {
+        Sha512::new()
+            .chain_update(OUTPUT_DST)
+            .chain_update(self.0.compress())
+            .finalize()
+            .into()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.compress().to_vec()
+    }
+}
+
+/// Aggregates proofs of the same alpha into one that verifies under the
+/// [aggregate_public_keys] of their keys. Its output is that of none of the
+/// individual proofs, so it is unpredictable while any signer is honest.
+pub fn aggregate_proofs(proofs: &[BlsVRFProof]) -> MangekyouResult<BlsVRFProof> {
+    let proofs: Vec<&Signature> = proofs.iter().map(|proof| &proof.0).collect();
+    AggregateSignature::aggregate(&proofs, false)
+        .map(|aggregate| BlsVRFProof(aggregate.to_signature()))
+        .map_err(|_| MangekyouError::InvalidInput)
+}
+
+/// Aggregates public keys, each of which must have a verified proof of
+/// possession, see [BlsVRFKeyPair::prove_possession]
+pub fn aggregate_public_keys(public_keys: &[BlsVRFPublicKey]) -> MangekyouResult<BlsVRFPublicKey> {
+    let public_keys: Vec<&PublicKey> = public_keys.iter().map(|public_key| &public_key.0).collect();
+    // Keys summing to the identity can only be rogue keys
+    let aggregate = AggregatePublicKey::aggregate(&public_keys, false)
+        .map(|aggregate| aggregate.to_public_key())
+        .map_err(|_| MangekyouError::InvalidInput)?;
+    aggregate.validate().map_err(|_| MangekyouError::InvalidInput)?;
+    Ok(BlsVRFPublicKey(aggregate))
+}
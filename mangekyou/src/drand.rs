@@ -0,0 +1,70 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification of drand beacon rounds using the unchained BLS12-381 scheme
+//! with signatures on G1 (`bls-unchained-g1-rfc9380`), as used by drand's
+//! quicknet. The signature of a round is a BLS signature over
+//! `sha256(round)`, with the round encoded as a big-endian u64, and the
+//! round's randomness is `sha256(signature)`.
+
+use crate::error::{MangekyouError, MangekyouResult};
+use blst::min_sig::{PublicKey, Signature};
+use blst::BLST_ERROR;
+use sha2::{Digest, Sha256};
+
+/// Domain separation tag of the quicknet signature scheme
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// Length of a compressed G2 public key
+pub const PUBLIC_KEY_LENGTH: usize = 96;
+
+/// Length of a compressed G1 signature
+pub const SIGNATURE_LENGTH: usize = 48;
+
+/// Public key of the drand quicknet chain
+pub const QUICKNET_PUBLIC_KEY: [u8; PUBLIC_KEY_LENGTH] = hex_literal::hex!(
+    "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a"
+);
+
+/// A drand chain identified by its group public key
+pub struct DrandBeacon {
+    public_key: PublicKey,
+}
+
+impl DrandBeacon {
+    /// Beacon for the chain with the given compressed G2 public key
+    pub fn new(public_key: &[u8]) -> MangekyouResult<Self> {
+        if public_key.len() != PUBLIC_KEY_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(PUBLIC_KEY_LENGTH));
+        }
+        let public_key = PublicKey::key_validate(public_key).map_err(|_| MangekyouError::InvalidInput)?;
+        Ok(Self { public_key })
+    }
+
+    /// Beacon for drand's quicknet chain
+    pub fn quicknet() -> Self {
+        Self::new(&QUICKNET_PUBLIC_KEY).expect("Valid constant")
+    }
+
+    /// Verifies the signature of `round` and returns the round's randomness
+    pub fn verify(&self, round: u64, signature: &[u8]) -> MangekyouResult<[u8; 32]> {
+        if signature.len() != SIGNATURE_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(SIGNATURE_LENGTH));
+        }
+        let sig = Signature::sig_validate(signature, true).map_err(|_| MangekyouError::InvalidSignature)?;
+        match sig.verify(false, &round_message(round), DST, &[], &self.public_key, false) {
+            BLST_ERROR::BLST_SUCCESS => Ok(randomness(signature)),
+            _ => Err(MangekyouError::InvalidSignature),
+        }
+    }
+}
+
+/// Message signed for `round`
+pub fn round_message(round: u64) -> [u8; 32] {
+    Sha256::digest(round.to_be_bytes()).into()
+}
+
+/// Randomness of a round with the given signature
+pub fn randomness(signature: &[u8]) -> [u8; 32] {
+    Sha256::digest(signature).into()
+}
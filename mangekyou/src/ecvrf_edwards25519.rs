@@ -0,0 +1,97 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The standard ECVRF-EDWARDS25519-SHA512-TAI suite (suite string `0x03`) of
+//! RFC 9381, the final form of draft-irtf-cfrg-vrf-15, for proofs from oracle
+//! stacks built on it rather than on the Ristretto suite of
+//! [crate::kamui_vrf].
+//!
+//! Verification is that of [mangekyou_core::ecvrf_edwards25519], which
+//! on-chain programs depend on directly; this module adds proving.
+
+use crate::error::{MangekyouError, MangekyouResult};
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::scalar::Scalar;
+use mangekyou_core::ecvrf_edwards25519::{self as core_edwards, challenge, encode_to_curve};
+use sha2::{Digest, Sha512};
+
+pub use mangekyou_core::ecvrf_edwards25519::{
+    CHALLENGE_LENGTH, KEY_LENGTH, OUTPUT_LENGTH, PROOF_LENGTH, SUITE_STRING,
+};
+
+/// A decoded proof
+#[derive(Clone, Debug, PartialEq)]
+pub struct ECVRFEdwards25519Proof(core_edwards::ECVRFEdwards25519Proof);
+
+impl ECVRFEdwards25519Proof {
+    /// Decodes `gamma || c || s`, refusing a gamma that is not a point and an
+    /// s that is not reduced
+    pub fn from_bytes(bytes: &[u8]) -> MangekyouResult<Self> {
+        Ok(Self(core_edwards::ECVRFEdwards25519Proof::from_bytes(bytes)?))
+    }
+
+    pub fn to_bytes(&self) -> [u8; PROOF_LENGTH] {
+        self.0.to_bytes()
+    }
+
+    /// Verifies this proof for `alpha` under `public_key`, following section
+    /// 5.3 of RFC 9381 with key validation
+    pub fn verify(&self, alpha: &[u8], public_key: &[u8; KEY_LENGTH]) -> MangekyouResult<()> {
+        Ok(self.0.verify(alpha, public_key)?)
+    }
+
+    /// The VRF output of this proof, meaningful once it verifies
+    pub fn to_hash(&self) -> [u8; OUTPUT_LENGTH] {
+        self.0.to_hash()
+    }
+}
+
+/// Verifies `proof` for `alpha` under `public_key` and returns the VRF output
+pub fn verify(proof: &[u8], alpha: &[u8], public_key: &[u8]) -> MangekyouResult<[u8; OUTPUT_LENGTH]> {
+    Ok(core_edwards::verify(proof, alpha, public_key)?)
+}
+
+/// The public key of the Ed25519 secret key `secret_key`
+pub fn public_key(secret_key: &[u8; KEY_LENGTH]) -> [u8; KEY_LENGTH] {
+    (ED25519_BASEPOINT_POINT * secret_scalar(secret_key)).compress().to_bytes()
+}
+
+/// Proves `alpha` under the Ed25519 secret key `secret_key`, following
+/// section 5.1 of RFC 9381
+pub fn prove(secret_key: &[u8; KEY_LENGTH], alpha: &[u8]) -> MangekyouResult<ECVRFEdwards25519Proof> {
+    let x = secret_scalar(secret_key);
+    let public_key = public_key(secret_key);
+    let h = encode_to_curve(&public_key, alpha)?;
+    let h_point = CompressedEdwardsY(h).decompress().ok_or(MangekyouError::InvalidInput)?;
+    let gamma = (x * h_point).compress().to_bytes();
+
+    // Nonce generation of section 5.4.2.2
+    let k_string = Sha512::new()
+        .chain_update(&Sha512::digest(secret_key)[32..])
+        .chain_update(h)
+        .finalize();
+    let k = Scalar::from_bytes_mod_order_wide(&k_string.as_slice().try_into().unwrap());
+    let u = (k * ED25519_BASEPOINT_POINT).compress().to_bytes();
+    let v = (k * h_point).compress().to_bytes();
+
+    let c = challenge(&[&public_key, &h, &gamma, &u, &v]);
+    let mut c_bytes = [0u8; 32];
+    c_bytes[..CHALLENGE_LENGTH].copy_from_slice(&c);
+    let s = k + Scalar::from_bits(c_bytes) * x;
+
+    let mut proof = [0u8; PROOF_LENGTH];
+    proof[..32].copy_from_slice(&gamma);
+    proof[32..32 + CHALLENGE_LENGTH].copy_from_slice(&c);
+    proof[32 + CHALLENGE_LENGTH..].copy_from_slice(s.as_bytes());
+    ECVRFEdwards25519Proof::from_bytes(&proof)
+}
+
+/// Secret scalar of an Ed25519 secret key, as in section 5.1.5 of RFC 8032
+fn secret_scalar(secret_key: &[u8; KEY_LENGTH]) -> Scalar {
+    let mut x: [u8; 32] = Sha512::digest(secret_key)[..32].try_into().unwrap();
+    x[0] &= 248;
+    x[31] &= 127;
+    x[31] |= 64;
+    Scalar::from_bytes_mod_order(x)
+}
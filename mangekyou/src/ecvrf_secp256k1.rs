@@ -0,0 +1,273 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! ECVRF-SECP256K1-SHA256-TAI: the ECVRF of RFC 9381 over secp256k1 with
+//! SHA-256, try-and-increment hash to curve and RFC 6979 nonces, so operators
+//! whose keys live in secp256k1 HSMs can run oracles. RFC 9381 registers no
+//! secp256k1 suite, so this one uses suite string `0xfe` like existing
+//! secp256k1 ECVRF implementations.
+//!
+//! Points are 33-byte compressed SEC1 encodings and scalars 32-byte big-endian
+//! integers, so proofs are gamma (33) || c (16) || s (32) and outputs 32 bytes.
+
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::kamui_vrf::{VRFKeyPair, VRFPrivateKey, VRFProof, VRFPublicKey};
+use crate::traits::AllowedRng;
+use rust_secp256k1::constants::{CURVE_ORDER, PUBLIC_KEY_SIZE, SECRET_KEY_SIZE};
+use rust_secp256k1::{PublicKey, Scalar, SecretKey, SECP256K1};
+use sha2::{Digest, Sha256};
+
+/// Suite string of ECVRF-SECP256K1-SHA256-TAI
+pub const SUITE_STRING: u8 = 0xfe;
+
+/// Length of a challenge
+pub const CHALLENGE_LENGTH: usize = 16;
+
+/// Length of a proof: gamma (33) || c (16) || s (32)
+pub const PROOF_LENGTH: usize = PUBLIC_KEY_SIZE + CHALLENGE_LENGTH + SECRET_KEY_SIZE;
+
+/// Length of a public key
+pub const PUBLIC_KEY_LENGTH: usize = PUBLIC_KEY_SIZE;
+
+/// Length of a secret key
+pub const SECRET_KEY_LENGTH: usize = SECRET_KEY_SIZE;
+
+/// Length of the VRF output
+pub const OUTPUT_LENGTH: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ECVRFSecp256k1PublicKey(PublicKey);
+
+impl VRFPublicKey for ECVRFSecp256k1PublicKey {
+    type PrivateKey = ECVRFSecp256k1PrivateKey;
+}
+
+impl ECVRFSecp256k1PublicKey {
+    /// Decodes a compressed point
+    pub fn from_bytes(bytes: &[u8]) -> MangekyouResult<Self> {
+        if bytes.len() != PUBLIC_KEY_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(PUBLIC_KEY_LENGTH));
+        }
+        PublicKey::from_slice(bytes)
+            .map(Self)
+            .map_err(|_| MangekyouError::InvalidPublicKey)
+    }
+
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.0.serialize()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ECVRFSecp256k1PrivateKey(SecretKey);
+
+impl VRFPrivateKey for ECVRFSecp256k1PrivateKey {
+    type PublicKey = ECVRFSecp256k1PublicKey;
+}
+
+impl ECVRFSecp256k1PrivateKey {
+    /// Decodes a scalar in [1, n), the range of secp256k1 secret keys
+    pub fn from_bytes(bytes: &[u8]) -> MangekyouResult<Self> {
+        if bytes.len() != SECRET_KEY_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(SECRET_KEY_LENGTH));
+        }
+        SecretKey::from_slice(bytes)
+            .map(Self)
+            .map_err(|_| MangekyouError::InvalidInput)
+    }
+
+    pub fn to_bytes(&self) -> [u8; SECRET_KEY_LENGTH] {
+        self.0.secret_bytes()
+    }
+}
+
+pub struct ECVRFSecp256k1KeyPair {
+    pub pk: ECVRFSecp256k1PublicKey,
+    pub sk: ECVRFSecp256k1PrivateKey,
+}
+
+impl From<ECVRFSecp256k1PrivateKey> for ECVRFSecp256k1KeyPair {
+    fn from(sk: ECVRFSecp256k1PrivateKey) -> Self {
+        let pk = ECVRFSecp256k1PublicKey(PublicKey::from_secret_key_global(&sk.0));
+        Self { pk, sk }
+    }
+}
+
+impl VRFKeyPair<OUTPUT_LENGTH> for ECVRFSecp256k1KeyPair {
+    type Proof = ECVRFSecp256k1Proof;
+    type PrivateKey = ECVRFSecp256k1PrivateKey;
+    type PublicKey = ECVRFSecp256k1PublicKey;
+
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        // Rejection sampling; a draw is out of range with probability about 2^-128.
+        loop {
+            let mut bytes = [0u8; SECRET_KEY_LENGTH];
+            rng.fill_bytes(&mut bytes);
+            if let Ok(sk) = SecretKey::from_slice(&bytes) {
+                return Self::from(ECVRFSecp256k1PrivateKey(sk));
+            }
+        }
+    }
+
+    /// Proves `alpha_string`, following section 5.1 of RFC 9381
+    fn prove(&self, alpha_string: &[u8]) -> ECVRFSecp256k1Proof {
+        let h = encode_to_curve(&self.pk, alpha_string);
+        let h_string = h.serialize();
+        let gamma = multiply(&h, &self.sk.0.secret_bytes());
+
+        let k = nonce_generation(&self.sk, &h_string);
+        let u = PublicKey::from_secret_key_global(&k);
+        let v = multiply(&h, &k.secret_bytes());
+
+        let c = challenge(&[&self.pk.0, &h, &gamma, &u, &v]);
+        // s = k + c*x mod n. A zero c*x or s has negligible probability.
+        let s = self
+            .sk
+            .0
+            .mul_tweak(&challenge_scalar(&c))
+            .and_then(|cx| cx.add_tweak(&Scalar::from(k)))
+            .expect("s is nonzero except with negligible probability");
+        ECVRFSecp256k1Proof { gamma, c, s }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ECVRFSecp256k1Proof {
+    gamma: PublicKey,
+    c: [u8; CHALLENGE_LENGTH],
+    s: SecretKey,
+}
+
+impl ECVRFSecp256k1Proof {
+    /// Decodes `gamma || c || s`, refusing a gamma that is not a point and an
+    /// s that is not in [1, n)
+    pub fn from_bytes(bytes: &[u8]) -> MangekyouResult<Self> {
+        if bytes.len() != PROOF_LENGTH {
+            return Err(MangekyouError::InputLengthWrong(PROOF_LENGTH));
+        }
+        let (gamma, rest) = bytes.split_at(PUBLIC_KEY_LENGTH);
+        let (c, s) = rest.split_at(CHALLENGE_LENGTH);
+        Ok(Self {
+            gamma: PublicKey::from_slice(gamma).map_err(|_| MangekyouError::InvalidProofEncoding)?,
+            c: c.try_into().unwrap(),
+            s: SecretKey::from_slice(s).map_err(|_| MangekyouError::InvalidScalar)?,
+        })
+    }
+}
+
+impl VRFProof<OUTPUT_LENGTH> for ECVRFSecp256k1Proof {
+    type PublicKey = ECVRFSecp256k1PublicKey;
+
+    /// Verifies this proof, following section 5.3 of RFC 9381. Every valid
+    /// encoding is a valid key, as secp256k1 has cofactor 1.
+    fn verify(&self, alpha_string: &[u8], public_key: &Self::PublicKey) -> MangekyouResult<()> {
+        let h = encode_to_curve(public_key, alpha_string);
+        let c = challenge_scalar(&self.c);
+
+        // U = s*B - c*Y and V = s*H - c*Gamma. Either is the point at
+        // infinity, which has no encoding, only for proofs that do not verify.
+        let u = subtract(&PublicKey::from_secret_key_global(&self.s), &public_key.0, &c)?;
+        let v = subtract(&multiply(&h, &self.s.secret_bytes()), &self.gamma, &c)?;
+
+        if challenge(&[&public_key.0, &h, &self.gamma, &u, &v]) != self.c {
+            return Err(MangekyouError::ChallengeMismatch);
+        }
+        Ok(())
+    }
+
+    /// Follows section 5.2 of RFC 9381
+    fn to_hash(&self) -> [u8; OUTPUT_LENGTH] {
+        Sha256::new()
+            .chain_update([SUITE_STRING, 0x03])
+            .chain_update(self.gamma.serialize())
+            .chain_update([0x00])
+            .finalize()
+            .into()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        [&self.gamma.serialize()[..], &self.c, &self.s.secret_bytes()].concat()
+    }
+}
+
+/// Hashes `alpha_string` to a point by try-and-increment, as in section
+/// 5.4.1.1 of RFC 9381, interpreting each hash as the x-coordinate of a
+/// point with even y. Half of all hashes are, so 256 tries fail with
+/// probability 2^-256.
+fn encode_to_curve(public_key: &ECVRFSecp256k1PublicKey, alpha_string: &[u8]) -> PublicKey {
+    (0..=u8::MAX)
+        .find_map(|counter| {
+            let hash = Sha256::new()
+                .chain_update([SUITE_STRING, 0x01])
+                .chain_update(public_key.0.serialize())
+                .chain_update(alpha_string)
+                .chain_update([counter, 0x00])
+                .finalize();
+            let mut candidate = [0x02; PUBLIC_KEY_LENGTH];
+            candidate[1..].copy_from_slice(&hash);
+            PublicKey::from_slice(&candidate).ok()
+        })
+        .expect("a hash is a valid x-coordinate within 256 tries")
+}
+
+/// Nonce of section 5.4.2.1 of RFC 9381: RFC 6979 with SHA-256 for the
+/// message `h_string`
+fn nonce_generation(secret_key: &ECVRFSecp256k1PrivateKey, h_string: &[u8]) -> SecretKey {
+    let h1 = reduce(Sha256::digest(h_string).into());
+    let k = rfc6979::generate_k::<Sha256, _>(
+        &secret_key.0.secret_bytes().into(),
+        &CURVE_ORDER.into(),
+        &h1.into(),
+        &[],
+    );
+    SecretKey::from_slice(&k).expect("RFC 6979 nonces are in [1, n)")
+}
+
+/// `bytes` modulo n, as bits2octets of RFC 6979. As n > 2^255, subtracting n
+/// once suffices, which is adding 2^256 - n modulo 2^256.
+fn reduce(bytes: [u8; 32]) -> [u8; 32] {
+    if bytes < CURVE_ORDER {
+        return bytes;
+    }
+    let mut reduced = [0u8; 32];
+    let mut carry = 0u16;
+    for i in (0..32).rev() {
+        // 2^256 - n = !n + 1, with the + 1 as the initial carry
+        let sum = bytes[i] as u16 + !CURVE_ORDER[i] as u16 + carry + (i == 31) as u16;
+        reduced[i] = sum as u8;
+        carry = sum >> 8;
+    }
+    reduced
+}
+
+/// Challenge of section 5.4.3 of RFC 9381
+fn challenge(points: &[&PublicKey; 5]) -> [u8; CHALLENGE_LENGTH] {
+    let mut hasher = Sha256::new().chain_update([SUITE_STRING, 0x02]);
+    for point in points {
+        hasher.update(point.serialize());
+    }
+    hasher.update([0x00]);
+    hasher.finalize()[..CHALLENGE_LENGTH].try_into().unwrap()
+}
+
+/// The challenge as a scalar. At 128 bits it is always below n.
+fn challenge_scalar(c: &[u8; CHALLENGE_LENGTH]) -> Scalar {
+    let mut bytes = [0u8; 32];
+    bytes[32 - CHALLENGE_LENGTH..].copy_from_slice(c);
+    Scalar::from_be_bytes(bytes).expect("challenges are below the group order")
+}
+
+/// `scalar * point` for a scalar in [1, n)
+fn multiply(point: &PublicKey, scalar: &[u8; 32]) -> PublicKey {
+    let scalar = Scalar::from_be_bytes(*scalar).expect("scalar is below the group order");
+    point
+        .mul_tweak(SECP256K1, &scalar)
+        .expect("a nonzero multiple of a point of prime order is a point")
+}
+
+/// `a - c * b`, or an error if it, or `c * b`, is the point at infinity
+fn subtract(a: &PublicKey, b: &PublicKey, c: &Scalar) -> MangekyouResult<PublicKey> {
+    b.mul_tweak(SECP256K1, c)
+        .and_then(|cb| a.combine(&cb.negate(SECP256K1)))
+        .map_err(|_| MangekyouError::ChallengeMismatch)
+}
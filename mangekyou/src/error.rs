@@ -8,7 +8,15 @@
 //! indirectly) then it should not return explicit errors as it might leak private information.
 //! In those cases the function should return the opaque, general error [MangekyouError::GeneralOpaqueError].
 //! When in doubt, prefer [MangekyouError::GeneralOpaqueError].
+//!
+//! Verifying a VRF proof involves no secret, so VRF verification instead
+//! says why a proof was rejected: [MangekyouError::InvalidPublicKey],
+//! [MangekyouError::InvalidProofEncoding], [MangekyouError::InvalidScalar] and
+//! [MangekyouError::HashToCurveFailure] for malformed input, and
+//! [MangekyouError::ChallengeMismatch] for a well-formed proof that does not
+//! verify.
 
+use mangekyou_core::error::VerifyError;
 use thiserror::Error;
 
 pub type MangekyouResult<T> = Result<T, MangekyouError>;
@@ -52,6 +60,26 @@ pub enum MangekyouError {
     #[error("Message should be ignored")]
     IgnoredMessage,
 
+    /// Invalid public key was given to the function
+    #[error("Invalid public key was given to the function")]
+    InvalidPublicKey,
+
+    /// A point of the proof is not a valid encoding
+    #[error("Invalid point encoding in proof")]
+    InvalidProofEncoding,
+
+    /// A scalar of the proof is not canonical
+    #[error("Non-canonical scalar in proof")]
+    InvalidScalar,
+
+    /// The input could not be hashed to the curve
+    #[error("Failed to hash the input to the curve")]
+    HashToCurveFailure,
+
+    /// The proof is well formed but its challenge does not match
+    #[error("Proof does not verify: challenge mismatch")]
+    ChallengeMismatch,
+
     /// General cryptographic error.
     #[error("General cryptographic error: {0}")]
     GeneralError(String),
@@ -59,4 +87,17 @@ pub enum MangekyouError {
     /// General opaque cryptographic error.
     #[error("General cryptographic error")]
     GeneralOpaqueError,
-}
\ No newline at end of file
+}
+
+impl From<VerifyError> for MangekyouError {
+    fn from(error: VerifyError) -> Self {
+        match error {
+            VerifyError::InputLengthWrong(length) => MangekyouError::InputLengthWrong(length),
+            VerifyError::InvalidPublicKey => MangekyouError::InvalidPublicKey,
+            VerifyError::InvalidProofEncoding => MangekyouError::InvalidProofEncoding,
+            VerifyError::InvalidScalar => MangekyouError::InvalidScalar,
+            VerifyError::HashToCurveFailure => MangekyouError::HashToCurveFailure,
+            VerifyError::ChallengeMismatch => MangekyouError::ChallengeMismatch,
+        }
+    }
+}
@@ -6,14 +6,10 @@ use solana_zk_token_sdk::curve25519::scalar::PodScalar;
 
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
 
-/// The Ristretto basepoint encoded as bytes
-pub const BASEPOINT_BYTES: [u8; 32] = [
-    0xe2, 0xf2, 0xae, 0x0a, 0x6a, 0xbc, 0x4e, 0x71,
-    0xa8, 0x84, 0xa9, 0x61, 0xc5, 0x00, 0x51, 0x5f,
-    0x58, 0xe3, 0x0b, 0x6a, 0xa5, 0x82, 0xdd, 0x8d,
-    0xb6, 0xa6, 0x59, 0x45, 0xe0, 0x8d, 0x2d, 0x76,
-];
+pub use mangekyou_core::ristretto_ops::BASEPOINT_BYTES;
 
 /// Represents a public key of which is use to verify outputs for a verifiable random function (VRF).
 pub trait VRFPublicKey {
@@ -61,7 +57,7 @@ pub trait VRFProof<const OUTPUT_SIZE: usize> {
     ) -> Result<(), MangekyouError> {
         self.verify(input, public_key)?;
         if &self.to_hash() != output {
-            return Err(MangekyouError::GeneralOpaqueError);
+            return Err(MangekyouError::InvalidProof);
         }
         Ok(())
     }
@@ -77,14 +73,15 @@ pub trait VRFProof<const OUTPUT_SIZE: usize> {
 /// (https://datatracker.ietf.org/doc/draft-irtf-cfrg-vrf/).
 pub mod ecvrf {
     use super::*;
-    use crate::hash::{HashFunction, Sha512};
-    use solana_zk_token_sdk::curve25519::{
-        ristretto::*,
-        scalar::*,
-    };
+    use crate::hash::{HashFunction, Sha256, Sha512};
+    use crate::ristretto_ops::{DefaultRistrettoOps as Ops, RistrettoOps};
+    use mangekyou_core::ecvrf as core_ecvrf;
     use borsh::{BorshDeserialize, BorshSerialize};
+    use hkdf::Hkdf;
+    use crate::hex_encode_decode_with_to_from_byte_array;
+    use crate::serde_helpers::ToFromByteArray;
 
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Debug, PartialEq)]
     pub struct WrappedPodScalar(pub(crate) PodScalar);
 
     impl BorshSerialize for WrappedPodScalar {
@@ -101,7 +98,7 @@ pub mod ecvrf {
         }
     }
 
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Debug, PartialEq)]
     pub struct WrappedPodRistrettoPoint(pub(crate) PodRistrettoPoint);
 
     impl BorshSerialize for WrappedPodRistrettoPoint {
@@ -118,25 +115,38 @@ pub mod ecvrf {
         }
     }
 
-    /// draft-irtf-cfrg-vrf-15 specifies suites for suite-strings 0x00-0x04 and notes that future
-    /// designs should specify a different suite_string constant, so we use "sol_vrf" here.
-    const SUITE_STRING: &[u8; 7] = b"sol_vrf";
+    /// Length of challenges, see [mangekyou_core::ecvrf::CHALLENGE_LENGTH].
+    const C_LEN: usize = core_ecvrf::CHALLENGE_LENGTH;
 
-    /// Length of challenges. Must not exceed the length of field elements which is 32 in this case.
-    /// We set C_LEN = 16 which is the same as the existing ECVRF suites in draft-irtf-cfrg-vrf-15.
-    const C_LEN: usize = 16;
+    pub use mangekyou_core::ecvrf::{KEY_LENGTH, OUTPUT_LENGTH, PROOF_LENGTH};
+
+    /// Length of a serialized key pair: pk || sk.
+    pub const KEY_PAIR_LENGTH: usize = 2 * KEY_LENGTH;
+
+    /// Length of a challenge.
+    pub const CHALLENGE_LENGTH: usize = C_LEN;
+
+    /// Suite byte of [ECVRFProof] envelopes, for the `sol_vrf` Ristretto255 suite.
+    pub const ENVELOPE_SUITE: u8 = 0x01;
+
+    /// Version byte of [ECVRFProof] envelopes with the gamma || c || s layout.
+    pub const ENVELOPE_VERSION: u8 = 0x01;
+
+    /// Length of a proof in an envelope: suite (1) || version (1) || gamma || c || s.
+    pub const ENVELOPE_LENGTH: usize = 2 + PROOF_LENGTH;
 
     /// Default hash function
     type H = Sha512;
-
-    /// Domain separation tag used in ecvrf_encode_to_curve
-    const DST: &[u8; 49] = b"ECVRF_ristretto255_XMD:SHA-512_R255MAP_RO_sol_vrf";
     
     /// Domain separation tags for different operations
     const CHALLENGE_GENERATION_DST: &[u8] = b"sol_vrf_challenge_generation";
     const NONCE_GENERATION_DST: &[u8] = b"sol_vrf_nonce_generation";
     const HASH_POINTS_DST: &[u8] = b"sol_vrf_hash_points";
 
+    /// HKDF-SHA512 salt of [ECVRFKeyPair::from_seed] and [ECVRFKeyPair::derive_child]
+    const KEY_DERIVATION_SALT: &[u8] = b"sol_vrf_key_derivation";
+
+    #[derive(Clone, Debug, PartialEq)]
     pub struct ECVRFPublicKey(WrappedPodRistrettoPoint);
 
     impl VRFPublicKey for ECVRFPublicKey {
@@ -144,80 +154,30 @@ pub mod ecvrf {
     }
 
     impl ECVRFPublicKey {
-        fn ecvrf_encode_to_curve_solana(&self, alpha_string: &[u8]) -> PodRistrettoPoint {
-            // Constants for expand_message_xmd
-            const B_IN_BYTES: usize = 64;  // SHA-512 output size
-            const DST: &[u8] = b"ECVRF_ristretto255_XMD:SHA-512_R255MAP_RO_sol_vrf";
-            const LEN_IN_BYTES: usize = 64;  // We want 64 bytes of output
-
-            // Compute b_0 = H(Z_pad || msg || len || DST || DST_len)
-            let mut hasher = H::default();
-            // Z_pad is a block of zeros
-            hasher.update(&[0u8; 128]);  // SHA-512 block size is 128 bytes
-            hasher.update(alpha_string);
-            hasher.update(&[(LEN_IN_BYTES >> 8) as u8, LEN_IN_BYTES as u8]);
-            hasher.update(DST);
-            hasher.update(&[DST.len() as u8]);
-            let b_0 = hasher.finalize();
-
-            // Compute b_1 = H(b_0 || 0x01 || DST || DST_len)
-            let mut hasher = H::default();
-            hasher.update(&b_0.digest);
-            hasher.update(&[1u8]);
-            hasher.update(DST);
-            hasher.update(&[DST.len() as u8]);
-            let b_1 = hasher.finalize();
-
-            // Compute b_2 = H((b_0 xor b_1) || 0x02 || DST || DST_len)
-            let mut tmp = [0u8; B_IN_BYTES];
-            for i in 0..B_IN_BYTES {
-                tmp[i] = b_0.digest[i] ^ b_1.digest[i];
-            }
-            let mut hasher = H::default();
-            hasher.update(&tmp);
-            hasher.update(&[2u8]);
-            hasher.update(DST);
-            hasher.update(&[DST.len() as u8]);
-            let b_2 = hasher.finalize();
-
-            // Combine b_1 and b_2 to get uniform bytes
-            let mut uniform_bytes = [0u8; 64];
-            uniform_bytes[..32].copy_from_slice(&b_1.digest[..32]);
-            uniform_bytes[32..].copy_from_slice(&b_2.digest[..32]);
-
-            // Map to curve point
-            let mut point_bytes = [0u8; 32];
-            point_bytes.copy_from_slice(&uniform_bytes[..32]);
-            point_bytes[31] &= 0b0111_1111;  // Clear top bit
-
-            // Try to find a valid point
-            let mut attempts = 0;
-            while attempts < 256 {
-                let point = PodRistrettoPoint(point_bytes);
-                if multiply_ristretto(&PodScalar([1; 32]), &point).is_some() {
-                    return point;
-                }
-                point_bytes[0] = point_bytes[0].wrapping_add(1);
-                attempts += 1;
-            }
-
-            // Fallback to basepoint if no valid point found
-            PodRistrettoPoint(BASEPOINT_BYTES)
+        /// Encodes `alpha_string` to a point with the public key as salt, see
+        /// [mangekyou_core::ecvrf::encode_to_curve].
+        fn ecvrf_encode_to_curve(&self, alpha_string: &[u8]) -> PodRistrettoPoint {
+            PodRistrettoPoint(core_ecvrf::encode_to_curve(&self.0.0.0, alpha_string))
         }
 
+        /// Whether the key is the canonical encoding of a group element other
+        /// than the identity, see [mangekyou_core::ecvrf::validate_key].
         fn valid(&self) -> bool {
-            // Simple check for zero point
-            let point_bytes = self.0.0.0;
-            !point_bytes.iter().all(|&x| x == 0)
+            core_ecvrf::validate_key::<Ops>(&self.0.0.0).is_ok()
         }
 
+        /// Accepts only valid keys, as [ToFromByteArray::from_byte_array] does.
         pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
             if bytes.len() != 32 {
                 return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid byte length for ECVRFPublicKey"));
             }
             let mut array = [0u8; 32];
             array.copy_from_slice(bytes);
-            Ok(Self(WrappedPodRistrettoPoint(PodRistrettoPoint(array))))
+            let pk = Self(WrappedPodRistrettoPoint(PodRistrettoPoint(array)));
+            if !pk.valid() {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "ECVRFPublicKey is not a valid non-identity point"));
+            }
+            Ok(pk)
         }
     } 
 
@@ -227,7 +187,7 @@ pub mod ecvrf {
         }
     }
 
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Debug, PartialEq)]
     pub struct ECVRFPrivateKey(WrappedPodScalar);
 
     impl VRFPrivateKey for ECVRFPrivateKey {
@@ -235,8 +195,13 @@ pub mod ecvrf {
     }
 
     impl ECVRFPrivateKey {
-        fn ecvrf_nonce_generation(&self, h_string: &[u8]) -> PodScalar {
-            let hashed_sk_string = H::digest(Scalar::from_bytes_mod_order(self.0.0.0).to_bytes());
+        /// The secret scalar, canonical by construction
+        fn scalar(&self) -> Scalar {
+            Scalar::from_canonical_bytes(self.0.0.0).expect("private keys are canonical scalars")
+        }
+
+        fn ecvrf_nonce_generation(&self, h_string: &[u8]) -> Scalar {
+            let hashed_sk_string = H::digest(self.scalar().to_bytes());
             let mut truncated_hashed_sk_string = [0u8; 32];
             truncated_hashed_sk_string.copy_from_slice(&hashed_sk_string.digest[32..64]);
 
@@ -245,15 +210,19 @@ pub mod ecvrf {
             hash_function.update(h_string);
             let k_string = hash_function.finalize();
 
-            PodScalar::from(&Scalar::from_bytes_mod_order_wide(&k_string.digest))
+            Scalar::from_bytes_mod_order_wide(&k_string.digest)
         }
 
+        /// Accepts only canonical scalars, as [ToFromByteArray::from_byte_array] does.
         pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
             if bytes.len() != 32 {
                 return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid byte length for ECVRFPrivateKey"));
             }
             let mut array = [0u8; 32];
             array.copy_from_slice(bytes);
+            if !is_canonical_scalar(&array) {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "ECVRFPrivateKey is not a canonical scalar"));
+            }
             Ok(Self(WrappedPodScalar(PodScalar(array))))
         }
     }
@@ -269,20 +238,17 @@ pub mod ecvrf {
         pub sk: ECVRFPrivateKey,
     }
 
+    #[cfg(test)]
+    pub(crate) use mangekyou_core::ecvrf::{expand_message_xmd, hash_to_ristretto255};
+
     /// Generate challenge from five points. See section 5.4.3. of draft-irtf-cfrg-vrf-15.
     fn ecvrf_challenge_generation(points: [&PodRistrettoPoint; 5]) -> Challenge {
-        let mut hasher = H::default();
-        hasher.update(SUITE_STRING);
-        hasher.update([0x02]); // challenge_generation_domain_separator_front
-        for p in points.iter() {
-            hasher.update(&p.0);  // Use compressed point representation
-        }
-        hasher.update([0x00]); // challenge_generation_domain_separator_back
-        let digest = hasher.finalize();
+        Challenge(core_ecvrf::challenge_generation(points.map(|point| &point.0)))
+    }
 
-        let mut challenge_bytes = [0u8; C_LEN];
-        challenge_bytes.copy_from_slice(&digest.digest[..C_LEN]);
-        Challenge(challenge_bytes)
+    /// VRF output of a proof with `gamma`. Follows section 5.2 of draft-irtf-cfrg-vrf-15.
+    fn ecvrf_proof_to_hash(gamma: &PodRistrettoPoint) -> [u8; 64] {
+        core_ecvrf::proof_to_hash(&gamma.0)
     }
 
     /// Type representing a scalar of [C_LEN] bytes. Not targetted to Solana at this time.
@@ -298,27 +264,65 @@ pub mod ecvrf {
             array.copy_from_slice(&bytes[..C_LEN]);
             Ok(Self(array))
         }
+
+        /// The challenge as a scalar. At 128 bits it is always below the group
+        /// order, so every challenge is a canonical scalar.
+        fn scalar(&self) -> Scalar {
+            core_ecvrf::challenge_scalar(&self.0)
+        }
     }
 
     impl ECVRFKeyPair {
+        /// Accepts pk || sk where pk is the public key of sk, as
+        /// [ToFromByteArray::from_byte_array] does.
         pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
-            if bytes.len() != 32 * 2 { // Assuming Challenge is also a 32-byte array
-                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid byte length for KeyPair"));
-            }
-            let pk_bytes = &bytes[0..32];
-            let sk_bytes = &bytes[32..64];
-    
-            let mut pk_array = [0u8; 32];
-            let mut sk_array = [0u8; 32];
-    
-            pk_array.copy_from_slice(pk_bytes);
-            sk_array.copy_from_slice(sk_bytes);
-    
-            Ok(Self {
-                pk: ECVRFPublicKey(WrappedPodRistrettoPoint(PodRistrettoPoint(pk_array))),
-                sk: ECVRFPrivateKey(WrappedPodScalar(PodScalar(sk_array)))
+            let bytes: &[u8; KEY_PAIR_LENGTH] = bytes.try_into().map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid byte length for KeyPair")
+            })?;
+            Self::from_byte_array(bytes).map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid or mismatched keys in KeyPair")
             })
         }
+
+        /// Derives a key pair from `seed` with HKDF-SHA512, so that backing up
+        /// the seed backs up the key pair and every child derived from it.
+        pub fn from_seed(seed: &[u8; 32]) -> Self {
+            Self::from(derive_private_key(seed, b"seed"))
+        }
+
+        /// Derives the child key pair with number `index` from the secret key
+        /// with HKDF-SHA512. Public keys of children cannot be derived from
+        /// the public key of their parent.
+        pub fn derive_child(&self, index: u32) -> Self {
+            Self::from(derive_private_key(&self.sk.0.0.0, &[&b"child"[..], &index.to_be_bytes()].concat()))
+        }
+
+        /// Proves `alpha_string` in the batchable form, see [ECVRFBatchableProof].
+        pub fn prove_batchable(&self, alpha_string: &[u8]) -> ECVRFBatchableProof {
+            self.prove_with_commitments(alpha_string).1
+        }
+
+        /// Proves `alpha_string`, returning the proof in both forms
+        fn prove_with_commitments(&self, alpha_string: &[u8]) -> (ECVRFProof, ECVRFBatchableProof) {
+            let h_point = self.pk.ecvrf_encode_to_curve(alpha_string);
+            let h_string = h_point.0;
+            let gamma = PodRistrettoPoint(Ops::multiply(&self.sk.0.0.0, &h_point.0).unwrap());
+            let k = self.sk.ecvrf_nonce_generation(&h_string);
+            let u = PodRistrettoPoint(Ops::multiply(k.as_bytes(), &BASEPOINT_BYTES).unwrap()); // U = k*B
+            let v = PodRistrettoPoint(Ops::multiply(k.as_bytes(), &h_point.0).unwrap()); // V = k*H
+
+            let c = ecvrf_challenge_generation([
+                &PodRistrettoPoint(self.pk.0.0.0),  // Y (public key)
+                &h_point,      // H
+                &gamma,        // Gamma
+                &u,
+                &v,
+            ]);
+
+            let s = PodScalar::from(&(k + c.scalar() * self.sk.scalar()));
+
+            (ECVRFProof { gamma, c, s }, ECVRFBatchableProof { gamma, u, v, s })
+        }
     }
 
     impl VRFKeyPair<64> for ECVRFKeyPair {
@@ -335,40 +339,23 @@ pub mod ecvrf {
         }
         
         fn prove(&self, alpha_string: &[u8]) -> ECVRFProof {
-            let h_point = self.pk.ecvrf_encode_to_curve_solana(alpha_string);
-            let h_string = h_point.0;
-            let gamma = multiply_ristretto(&PodScalar(self.sk.0.0.0), &h_point).unwrap();
-            let k = self.sk.ecvrf_nonce_generation(&h_string);
-
-            let c = ecvrf_challenge_generation([
-                &PodRistrettoPoint(self.pk.0.0.0),  // Y (public key)
-                &h_point,      // H
-                &gamma,        // Gamma
-                &multiply_ristretto(&k, &PodRistrettoPoint(BASEPOINT_BYTES)).unwrap(), // U = k*B
-                &multiply_ristretto(&k, &h_point).unwrap()  // V = k*H
-            ]);
-
-            let k_scalar = Scalar::from_bytes_mod_order(k.0);
-            let sk_scalar = Scalar::from_bytes_mod_order(self.sk.0.0.0);
-            
-            // Convert challenge to scalar
-            let mut scalar_bytes = [0u8; 32];
-            scalar_bytes[..C_LEN].copy_from_slice(&c.0);
-            let c_scalar = Scalar::from_bytes_mod_order(scalar_bytes);
-            
-            let s = k_scalar + c_scalar * sk_scalar;
-
-            ECVRFProof { 
-                gamma, 
-                c, 
-                s: PodScalar::from(&s)
-            }
+            self.prove_with_commitments(alpha_string).0
         }
     }
 
+    /// The secret key expanded by HKDF-SHA512 from `ikm` and `info`, reduced
+    /// from 64 bytes so that it is uniform
+    fn derive_private_key(ikm: &[u8], info: &[u8]) -> ECVRFPrivateKey {
+        let mut okm = [0u8; 64];
+        Hkdf::<sha2::Sha512>::new(Some(KEY_DERIVATION_SALT), ikm)
+            .expand(info, &mut okm)
+            .expect("64 bytes is a valid HKDF-SHA512 output length");
+        ECVRFPrivateKey(WrappedPodScalar(PodScalar::from(&Scalar::from_bytes_mod_order_wide(&okm))))
+    }
+
     impl From<ECVRFPrivateKey> for ECVRFKeyPair {
         fn from(sk: ECVRFPrivateKey) -> Self {
-            let p = PodRistrettoPoint::from(&(RISTRETTO_BASEPOINT_POINT * Scalar::from_bytes_mod_order(sk.0.0.0)));
+            let p = PodRistrettoPoint::from(&(RISTRETTO_BASEPOINT_POINT * sk.scalar()));
             ECVRFKeyPair {
                 pk: ECVRFPublicKey(WrappedPodRistrettoPoint(p)),
                 sk,
@@ -376,6 +363,7 @@ pub mod ecvrf {
         }
     }
 
+    #[derive(Clone, Debug, PartialEq)]
     pub struct ECVRFProof {
         gamma: PodRistrettoPoint,
         c: Challenge,
@@ -383,8 +371,28 @@ pub mod ecvrf {
     }
 
     impl ECVRFProof {
+        /// Decodes a proof in the envelope of [VRFProof::to_bytes]. Parsing is
+        /// strict: the suite and version must be those this implementation
+        /// writes, gamma a valid point and s a canonical scalar.
         pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
-            if bytes.len() <= 32 * 2 { 
+            let invalid = |message| std::io::Error::new(std::io::ErrorKind::InvalidInput, message);
+            if bytes.len() != ENVELOPE_LENGTH {
+                return Err(invalid("Invalid byte length for ECVRFProof"));
+            }
+            if bytes[0] != ENVELOPE_SUITE {
+                return Err(invalid("Unknown suite of ECVRFProof"));
+            }
+            if bytes[1] != ENVELOPE_VERSION {
+                return Err(invalid("Unsupported version of ECVRFProof"));
+            }
+            Self::from_byte_array(bytes[2..].try_into().unwrap())
+                .map_err(|_| invalid("Invalid gamma or s in ECVRFProof"))
+        }
+
+        /// Decodes a bare gamma || c || s proof of [PROOF_LENGTH] bytes, the
+        /// layout on-chain verifiers take. Only the length is checked.
+        pub fn from_bytes_legacy(bytes: &[u8]) -> Result<Self, std::io::Error> {
+            if bytes.len() != PROOF_LENGTH {
                 return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid byte length for ECVRFProof"));
             }
             let gamma_bytes = &bytes[0..32];
@@ -403,6 +411,24 @@ pub mod ecvrf {
                 s: PodScalar(s_array),
             })
         }
+
+        /// Verifies this proof with the group operations of `O`, as
+        /// [VRFProof::verify] does with [DefaultRistrettoOps](crate::ristretto_ops::DefaultRistrettoOps).
+        /// This is [mangekyou_core::ecvrf::verify_with], which on-chain
+        /// programs call directly.
+        pub fn verify_with<O: RistrettoOps>(
+            &self,
+            alpha_string: &[u8],
+            public_key: &ECVRFPublicKey,
+        ) -> Result<(), MangekyouError> {
+            Ok(core_ecvrf::verify_with::<O>(&self.to_byte_array(), alpha_string, &public_key.0.0.0)?)
+        }
+
+        /// The bare gamma || c || s layout of [PROOF_LENGTH] bytes, which
+        /// on-chain verifiers and the FFI bindings take
+        pub fn to_bytes_legacy(&self) -> Vec<u8> {
+            self.to_byte_array().to_vec()
+        }
     }
 
     impl VRFProof<64> for ECVRFProof {
@@ -413,66 +439,177 @@ pub mod ecvrf {
             alpha_string: &[u8],
             public_key: &Self::PublicKey,
         ) -> Result<(), MangekyouError> {
-            if !public_key.valid() {
-                return Err(MangekyouError::InvalidInput);
+            self.verify_with::<Ops>(alpha_string, public_key)
+        }
+
+        fn to_hash(&self) -> [u8; 64] {
+            ecvrf_proof_to_hash(&self.gamma)
+        }
+
+        /// The proof in a versioned envelope: suite || version || gamma || c || s,
+        /// see [ECVRFProof::from_bytes]
+        fn to_bytes(&self) -> Vec<u8> {
+            [&[ENVELOPE_SUITE, ENVELOPE_VERSION][..], &self.to_byte_array()].concat()
+        }
+    }
+
+    /// Length of a serialized batchable proof: gamma (32) || U (32) || V (32) || s (32).
+    pub const BATCHABLE_PROOF_LENGTH: usize = 4 * 32;
+
+    /// Domain separation tag of the weights of [verify_batch]
+    const BATCH_VERIFICATION_DST: &[u8] = b"sol_vrf_batch_verification";
+
+    /// A proof in the form (gamma, U, V, s), carrying the points its challenge
+    /// is hashed from instead of the challenge, so that proofs can be checked
+    /// together with [verify_batch]. [ECVRFBatchableProof::to_proof] gives the
+    /// (gamma, c, s) proof submitted on-chain, with the same output. The
+    /// challenge cannot be reversed, so proofs already recorded in the
+    /// (gamma, c, s) form can only be checked one by one.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct ECVRFBatchableProof {
+        gamma: PodRistrettoPoint,
+        u: PodRistrettoPoint,
+        v: PodRistrettoPoint,
+        s: PodScalar,
+    }
+
+    impl ECVRFBatchableProof {
+        pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
+            if bytes.len() != BATCHABLE_PROOF_LENGTH {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid byte length for ECVRFBatchableProof"));
             }
+            let component = |i: usize| -> [u8; 32] { bytes[32 * i..32 * (i + 1)].try_into().unwrap() };
+            Ok(Self {
+                gamma: PodRistrettoPoint(component(0)),
+                u: PodRistrettoPoint(component(1)),
+                v: PodRistrettoPoint(component(2)),
+                s: PodScalar(component(3)),
+            })
+        }
 
-            let h_point = public_key.ecvrf_encode_to_curve_solana(alpha_string);
-            
-            // Convert challenge to scalar and negate it using Solana's operations
-            let mut c_scalar = [0u8; 32];
-            c_scalar[..C_LEN].copy_from_slice(&self.c.0);
-            let neg_challenge = negate_scalar(&PodScalar(c_scalar));
-
-            // Compute U = s*B - c*Y using Solana's multiscalar multiplication
-            let u_point = multiscalar_multiply_ristretto(
-                &[self.s, neg_challenge],
-                &[PodRistrettoPoint(BASEPOINT_BYTES), PodRistrettoPoint(public_key.0.0.0)],
-            ).ok_or(MangekyouError::InvalidInput)?;
-
-            // Compute V = s*H - c*Gamma using Solana's multiscalar multiplication
-            let v_point = multiscalar_multiply_ristretto(
-                &[self.s, neg_challenge],
-                &[h_point, self.gamma],
-            ).ok_or(MangekyouError::InvalidInput)?;
-
-            let c_prime = ecvrf_challenge_generation([
-                &PodRistrettoPoint(public_key.0.0.0),    // Y (public key)
-                &h_point,             // H
-                &self.gamma,          // Gamma
-                &u_point,             // U = s*B - c*Y
-                &v_point,             // V = s*H - c*Gamma
+        pub fn to_bytes(&self) -> [u8; BATCHABLE_PROOF_LENGTH] {
+            let mut bytes = [0u8; BATCHABLE_PROOF_LENGTH];
+            bytes[..32].copy_from_slice(&self.gamma.0);
+            bytes[32..64].copy_from_slice(&self.u.0);
+            bytes[64..96].copy_from_slice(&self.v.0);
+            bytes[96..].copy_from_slice(&self.s.0);
+            bytes
+        }
+
+        /// The (gamma, c, s) form of this proof of `alpha_string` under `public_key`
+        pub fn to_proof(&self, alpha_string: &[u8], public_key: &ECVRFPublicKey) -> ECVRFProof {
+            let h_point = public_key.ecvrf_encode_to_curve(alpha_string);
+            let c = ecvrf_challenge_generation([
+                &PodRistrettoPoint(public_key.0.0.0),
+                &h_point,
+                &self.gamma,
+                &self.u,
+                &self.v,
             ]);
+            ECVRFProof { gamma: self.gamma, c, s: self.s }
+        }
 
-            if c_prime != self.c {
-                return Err(MangekyouError::GeneralOpaqueError);
-            }
+        /// The VRF output, as [VRFProof::to_hash] of [ECVRFBatchableProof::to_proof]
+        pub fn to_hash(&self) -> [u8; 64] {
+            ecvrf_proof_to_hash(&self.gamma)
+        }
+    }
 
-            Ok(())
+    /// The two verification equations of a decoded batchable proof:
+    /// s*B - c*Y = U and s*H - c*Gamma = V
+    struct BatchEquations {
+        y: RistrettoPoint,
+        h: RistrettoPoint,
+        gamma: RistrettoPoint,
+        u: RistrettoPoint,
+        v: RistrettoPoint,
+        c: Scalar,
+        s: Scalar,
+    }
+
+    impl BatchEquations {
+        fn hold(&self) -> bool {
+            RistrettoPoint::vartime_double_scalar_mul_basepoint(&-self.c, &self.y, &self.s) == self.u
+                && RistrettoPoint::vartime_multiscalar_mul([self.s, -self.c], [self.h, self.gamma]) == self.v
         }
+    }
 
-        fn to_hash(&self) -> [u8; 64] {
-            // Follows section 5.2 of draft-irtf-cfrg-vrf-15.
-            let mut hash = H::default();
-            hash.update(SUITE_STRING);
-            hash.update([0x03]); // proof_to_hash_domain_separator_front
-            hash.update(self.gamma.0);
-            hash.update([0x00]); // proof_to_hash_domain_separator_back
-            hash.finalize().digest
+    /// Verifies the batchable proofs of `(alpha_string, proof, public_key)`
+    /// items together and returns, for each, whether [VRFProof::verify] of its
+    /// [ECVRFBatchableProof::to_proof] succeeds. A batch of n proofs that all
+    /// verify takes one multiscalar multiplication of 5n + 1 points instead of
+    /// 2n: the equations of every proof are summed with weights hashed from
+    /// the whole batch, so no proof can be chosen to cancel out another. A
+    /// batch with an invalid proof is then checked proof by proof. Fails with
+    /// [MangekyouError::InvalidPublicKey] if a public key is invalid.
+    pub fn verify_batch(
+        items: &[(&[u8], &ECVRFBatchableProof, &ECVRFPublicKey)],
+    ) -> Result<Vec<bool>, MangekyouError> {
+        let decompress = |point: &PodRistrettoPoint| CompressedRistretto(point.0).decompress();
+
+        let mut transcript = H::default();
+        transcript.update(BATCH_VERIFICATION_DST);
+        let mut batch = Vec::with_capacity(items.len());
+        for (alpha_string, proof, public_key) in items {
+            let y = decompress(&public_key.0.0)
+                .filter(|_| public_key.valid())
+                .ok_or(MangekyouError::InvalidPublicKey)?;
+            let h_point = public_key.ecvrf_encode_to_curve(alpha_string);
+            let c = ecvrf_challenge_generation([&public_key.0.0, &h_point, &proof.gamma, &proof.u, &proof.v]);
+
+            // A proof with an invalid point or a non-canonical s never verifies
+            batch.push((|| {
+                Some(BatchEquations {
+                    y,
+                    h: decompress(&h_point)?,
+                    gamma: decompress(&proof.gamma)?,
+                    u: decompress(&proof.u)?,
+                    v: decompress(&proof.v)?,
+                    c: c.scalar(),
+                    s: Scalar::from_canonical_bytes(proof.s.0)?,
+                })
+            })());
+
+            transcript.update(public_key.as_ref());
+            transcript.update((alpha_string.len() as u64).to_le_bytes());
+            transcript.update(alpha_string);
+            transcript.update(proof.to_bytes());
         }
+        let seed = transcript.finalize().digest;
+
+        if let Some(batch) = batch.iter().map(Option::as_ref).collect::<Option<Vec<_>>>() {
+            let mut scalars = Vec::with_capacity(5 * batch.len() + 1);
+            let mut points = Vec::with_capacity(5 * batch.len() + 1);
+            let mut basepoint_scalar = Scalar::zero();
+            for (i, equations) in batch.iter().enumerate() {
+                // Two 128-bit weights per proof, one per equation
+                let mut hasher = H::default();
+                hasher.update(seed);
+                hasher.update((i as u64).to_le_bytes());
+                let weights = hasher.finalize().digest;
+                let weight = |bytes: &[u8]| {
+                    let mut scalar = [0u8; 32];
+                    scalar[..16].copy_from_slice(bytes);
+                    Scalar::from_bits(scalar)
+                };
+                let (z, w) = (weight(&weights[..16]), weight(&weights[16..32]));
+
+                basepoint_scalar += z * equations.s;
+                scalars.extend([-(z * equations.c), -z, w * equations.s, -(w * equations.c), -w]);
+                points.extend([equations.y, equations.u, equations.h, equations.gamma, equations.v]);
+            }
+            scalars.push(basepoint_scalar);
+            points.push(RISTRETTO_BASEPOINT_POINT);
 
-        fn to_bytes(&self) -> Vec<u8> {
-            // Convert each field to a byte array and concatenate them
-            let gamma_bytes = self.gamma.0;
-            
-            let mut c_buffer: Vec<u8> = Vec::new();
-            self.c.serialize(&mut c_buffer);
-            
-            let s_bytes = self.s.0;
-    
-            let concatenated = [gamma_bytes.as_ref(), c_buffer.as_ref(), s_bytes.as_ref()].concat();
-            concatenated
+            if RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity() {
+                return Ok(vec![true; batch.len()]);
+            }
         }
+
+        Ok(batch
+            .iter()
+            .map(|equations| equations.as_ref().is_some_and(BatchEquations::hold))
+            .collect())
     }
 
     // Add these implementations after the wrapper type definitions
@@ -509,39 +646,509 @@ pub mod ecvrf {
         }
     }
 
-    /// Helper function to convert bytes to PodScalar
-    fn bytes_to_scalar(bytes: &[u8]) -> PodScalar {
-        let mut scalar = [0u8; 32];
-        scalar[..bytes.len()].copy_from_slice(bytes);
-        PodScalar(scalar)
+    fn is_canonical_scalar(bytes: &[u8; 32]) -> bool {
+        Scalar::from_canonical_bytes(*bytes).is_some()
     }
 
-    /// Helper function for scalar negation that only uses Solana's types
-    fn negate_scalar(scalar: &PodScalar) -> PodScalar {
-        let mut neg_bytes = [0u8; 32];
-        let mut carry = 0i16;
-        
-        // L - x mod L, where L is the order of the curve
-        let order = [
-            0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58,
-            0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
-        ];
-        
-        // Compute L - x in constant time
-        for i in 0..32 {
-            let diff = order[i] as i16 - scalar.0[i] as i16 - carry;
-            if diff < 0 {
-                carry = 1;
-                neg_bytes[i] = (diff + 256) as u8;
-            } else {
-                carry = 0;
-                neg_bytes[i] = diff as u8;
+    impl ToFromByteArray<KEY_LENGTH> for ECVRFPublicKey {
+        /// Accepts only valid encodings of a non-identity point.
+        fn from_byte_array(bytes: &[u8; KEY_LENGTH]) -> Result<Self, MangekyouError> {
+            core_ecvrf::validate_key::<Ops>(bytes)?;
+            Ok(Self(WrappedPodRistrettoPoint(PodRistrettoPoint(*bytes))))
+        }
+
+        fn to_byte_array(&self) -> [u8; KEY_LENGTH] {
+            self.0.0.0
+        }
+    }
+
+    impl ToFromByteArray<KEY_LENGTH> for ECVRFPrivateKey {
+        /// Accepts only canonical scalars.
+        fn from_byte_array(bytes: &[u8; KEY_LENGTH]) -> Result<Self, MangekyouError> {
+            if !is_canonical_scalar(bytes) {
+                return Err(MangekyouError::InvalidInput);
             }
+            Ok(Self(WrappedPodScalar(PodScalar(*bytes))))
+        }
+
+        fn to_byte_array(&self) -> [u8; KEY_LENGTH] {
+            self.0.0.0
         }
-        
-        PodScalar(neg_bytes)
     }
-}
 
+    impl ToFromByteArray<KEY_PAIR_LENGTH> for ECVRFKeyPair {
+        /// Accepts pk || sk where pk is the public key of sk.
+        fn from_byte_array(bytes: &[u8; KEY_PAIR_LENGTH]) -> Result<Self, MangekyouError> {
+            let (pk, sk) = bytes.split_at(KEY_LENGTH);
+            let sk = ECVRFPrivateKey::from_byte_array(sk.try_into().unwrap())?;
+            let kp = ECVRFKeyPair::from(sk);
+            if kp.pk.as_ref() != pk {
+                return Err(MangekyouError::InvalidInput);
+            }
+            Ok(kp)
+        }
+
+        fn to_byte_array(&self) -> [u8; KEY_PAIR_LENGTH] {
+            let mut bytes = [0u8; KEY_PAIR_LENGTH];
+            bytes[..KEY_LENGTH].copy_from_slice(self.pk.as_ref());
+            bytes[KEY_LENGTH..].copy_from_slice(self.sk.as_ref());
+            bytes
+        }
+    }
+
+    /// The output of a proof, see [VRFProof::to_hash].
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct VRFOutput(pub [u8; OUTPUT_LENGTH]);
+
+    impl From<[u8; OUTPUT_LENGTH]> for VRFOutput {
+        fn from(bytes: [u8; OUTPUT_LENGTH]) -> Self {
+            Self(bytes)
+        }
+    }
+
+    impl AsRef<[u8]> for VRFOutput {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl ToFromByteArray<OUTPUT_LENGTH> for VRFOutput {
+        fn from_byte_array(bytes: &[u8; OUTPUT_LENGTH]) -> Result<Self, MangekyouError> {
+            Ok(Self(*bytes))
+        }
+
+        fn to_byte_array(&self) -> [u8; OUTPUT_LENGTH] {
+            self.0
+        }
+    }
+
+    impl VRFOutput {
+        /// The first 8 bytes of the output as a little-endian u64.
+        pub fn to_u64(&self) -> u64 {
+            u64::from_le_bytes(self.0[..8].try_into().unwrap())
+        }
+
+        /// A uniformly distributed value in `[min, max]` (inclusive), equal to
+        /// `derive_in_range` of the on-chain `randomness` module with word 0.
+        pub fn in_range(&self, min: u64, max: u64) -> Result<u64, MangekyouError> {
+            self.in_range_at(0, min, max)
+        }
+
+        /// Shuffles `items` uniformly with Fisher-Yates, swapping position i
+        /// with a position in `[0, i]` drawn from word i.
+        pub fn shuffle<T>(&self, items: &mut [T]) {
+            for i in (1..items.len()).rev() {
+                let j = self.in_range_at(i as u32, 0, i as u64).expect("0 <= i");
+                items.swap(i, j as usize);
+            }
+        }
+
+        /// Picks an index with probability proportional to its weight. Fails
+        /// with [MangekyouError::InvalidInput] if the weights are empty, all
+        /// zero, or sum past `u64::MAX`.
+        pub fn weighted_choice(&self, weights: &[u64]) -> Result<usize, MangekyouError> {
+            let total = weights
+                .iter()
+                .try_fold(0u64, |total, &weight| total.checked_add(weight))
+                .filter(|&total| total > 0)
+                .ok_or(MangekyouError::InvalidInput)?;
+            let mut target = self.in_range(0, total - 1)?;
+            for (index, &weight) in weights.iter().enumerate() {
+                if target < weight {
+                    return Ok(index);
+                }
+                target -= weight;
+            }
+            unreachable!()
+        }
+
+        /// Draws candidates `sha256("range" || output || word_index || counter)`
+        /// and rejects those in the final partial bucket of the range, which
+        /// removes the bias of taking the output modulo the range size.
+        fn in_range_at(&self, word_index: u32, min: u64, max: u64) -> Result<u64, MangekyouError> {
+            if min > max {
+                return Err(MangekyouError::InvalidInput);
+            }
+            let range = (max - min).wrapping_add(1);
+            // 2^64 mod range, and zero for the range of all of u64, which wraps to 0
+            let threshold = range.wrapping_neg().checked_rem(range).unwrap_or(0);
+
+            for counter in 0u64.. {
+                let mut hasher = Sha256::default();
+                hasher.update(b"range");
+                hasher.update(self.0);
+                hasher.update(word_index.to_le_bytes());
+                hasher.update(counter.to_le_bytes());
+                let hash = hasher.finalize().digest;
+                let candidate = u64::from_le_bytes(hash[..8].try_into().unwrap());
+                if candidate < threshold {
+                    continue;
+                }
+                return Ok(match range {
+                    0 => candidate,
+                    _ => min + candidate % range,
+                });
+            }
+            unreachable!()
+        }
+    }
+
+    impl ToFromByteArray<CHALLENGE_LENGTH> for Challenge {
+        fn from_byte_array(bytes: &[u8; CHALLENGE_LENGTH]) -> Result<Self, MangekyouError> {
+            Ok(Self(*bytes))
+        }
+
+        fn to_byte_array(&self) -> [u8; CHALLENGE_LENGTH] {
+            self.0
+        }
+    }
+
+    impl ToFromByteArray<PROOF_LENGTH> for ECVRFProof {
+        /// Accepts only proofs where gamma is a valid point encoding and s is a canonical scalar.
+        fn from_byte_array(bytes: &[u8; PROOF_LENGTH]) -> Result<Self, MangekyouError> {
+            let proof = Self::from_bytes_legacy(bytes).map_err(|_| MangekyouError::InvalidInput)?;
+            if !Ops::is_valid_point(&proof.gamma.0) {
+                return Err(MangekyouError::InvalidProofEncoding);
+            }
+            if !is_canonical_scalar(&proof.s.0) {
+                return Err(MangekyouError::InvalidScalar);
+            }
+            Ok(proof)
+        }
+
+        fn to_byte_array(&self) -> [u8; PROOF_LENGTH] {
+            let mut bytes = [0u8; PROOF_LENGTH];
+            bytes[..32].copy_from_slice(&self.gamma.0);
+            bytes[32..32 + C_LEN].copy_from_slice(&self.c.0);
+            bytes[32 + C_LEN..].copy_from_slice(&self.s.0);
+            bytes
+        }
+    }
+
+    hex_encode_decode_with_to_from_byte_array!(ECVRFPublicKey);
+    hex_encode_decode_with_to_from_byte_array!(ECVRFPrivateKey);
+    hex_encode_decode_with_to_from_byte_array!(ECVRFProof);
+    hex_encode_decode_with_to_from_byte_array!(VRFOutput);
+
+    /// Proving with a key shared by [crate::tdkg]: any threshold of the parties
+    /// produce together an [ECVRFProof] under the shared public key, which the
+    /// existing verifiers, on-chain included, check as any other. Its output
+    /// is the one the shared secret key would give.
+    ///
+    /// The responses of the signers must answer the same challenge, which
+    /// hashes the nonce commitments of all of them, so proving takes two
+    /// rounds, with the binding factors of FROST (RFC 9591):
+    ///
+    /// 1. Each signer calls [commit](threshold::commit) and broadcasts the
+    ///    [NonceCommitment](threshold::NonceCommitment), which carries its
+    ///    share of gamma.
+    /// 2. Given the commitments of at least threshold signers, each of them
+    ///    calls [partial_prove](threshold::partial_prove) and sends its
+    ///    [PartialProof](threshold::PartialProof) to an aggregator, which
+    ///    checks them with [verify_partial](threshold::verify_partial) and
+    ///    combines them with [aggregate](threshold::aggregate).
+    ///
+    /// Nonces are random, so unlike [ECVRFKeyPair::prove] proofs differ from
+    /// one run to the next; the output does not.
+    pub mod threshold {
+        use super::*;
+        use crate::groups::ristretto255::{RistrettoPoint as SharedPoint, RistrettoScalar};
+        use crate::tdkg::{lagrange_coefficient, KeyShare, PartyId, PublicOutput};
+        use curve25519_dalek::traits::Identity;
+        use zeroize::Zeroize;
+
+        /// Length of a serialized [NonceCommitment]: id (2) || gamma || D || E || D' || E'
+        pub const NONCE_COMMITMENT_LENGTH: usize = 2 + 5 * 32;
+
+        /// Length of a serialized [PartialProof]: id (2) || z
+        pub const PARTIAL_PROOF_LENGTH: usize = 2 + 32;
+
+        /// Domain separation tag of the binding factors
+        const BINDING_FACTOR_DST: &[u8] = b"sol_vrf_threshold_binding_factor";
+
+        /// The share of gamma of a signer, x_i*H, and its commitments to the
+        /// nonces d_i and e_i: D = d_i*B, E = e_i*B, D' = d_i*H and E' = e_i*H
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct NonceCommitment {
+            pub id: PartyId,
+            gamma: RistrettoPoint,
+            d: RistrettoPoint,
+            e: RistrettoPoint,
+            d_h: RistrettoPoint,
+            e_h: RistrettoPoint,
+        }
+
+        impl NonceCommitment {
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+                if bytes.len() != NONCE_COMMITMENT_LENGTH {
+                    return Err(MangekyouError::InputLengthWrong(NONCE_COMMITMENT_LENGTH));
+                }
+                let point = |i: usize| {
+                    CompressedRistretto::from_slice(&bytes[2 + 32 * i..2 + 32 * (i + 1)])
+                        .decompress()
+                        .ok_or(MangekyouError::InvalidInput)
+                };
+                Ok(Self {
+                    id: u16::from_le_bytes([bytes[0], bytes[1]]),
+                    gamma: point(0)?,
+                    d: point(1)?,
+                    e: point(2)?,
+                    d_h: point(3)?,
+                    e_h: point(4)?,
+                })
+            }
+
+            pub fn to_bytes(&self) -> [u8; NONCE_COMMITMENT_LENGTH] {
+                let mut bytes = [0u8; NONCE_COMMITMENT_LENGTH];
+                bytes[..2].copy_from_slice(&self.id.to_le_bytes());
+                for (i, point) in [self.gamma, self.d, self.e, self.d_h, self.e_h].iter().enumerate() {
+                    bytes[2 + 32 * i..2 + 32 * (i + 1)].copy_from_slice(point.compress().as_bytes());
+                }
+                bytes
+            }
+        }
+
+        /// The secret nonces of a signer for one proof, consumed by
+        /// [partial_prove] so that they are used once
+        pub struct Nonces {
+            commitment: NonceCommitment,
+            d: Scalar,
+            e: Scalar,
+        }
+
+        impl Nonces {
+            /// The commitment to broadcast
+            pub fn commitment(&self) -> &NonceCommitment {
+                &self.commitment
+            }
+        }
+
+        impl Drop for Nonces {
+            fn drop(&mut self) {
+                self.d.zeroize();
+                self.e.zeroize();
+            }
+        }
+
+        /// The response of a signer, z_i = d_i + rho_i*e_i + c*lambda_i*x_i
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct PartialProof {
+            pub id: PartyId,
+            z: Scalar,
+        }
+
+        impl PartialProof {
+            /// Accepts only canonical scalars
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+                if bytes.len() != PARTIAL_PROOF_LENGTH {
+                    return Err(MangekyouError::InputLengthWrong(PARTIAL_PROOF_LENGTH));
+                }
+                Ok(Self {
+                    id: u16::from_le_bytes([bytes[0], bytes[1]]),
+                    z: Scalar::from_canonical_bytes(bytes[2..].try_into().unwrap())
+                        .ok_or(MangekyouError::InvalidInput)?,
+                })
+            }
+
+            pub fn to_bytes(&self) -> [u8; PARTIAL_PROOF_LENGTH] {
+                let mut bytes = [0u8; PARTIAL_PROOF_LENGTH];
+                bytes[..2].copy_from_slice(&self.id.to_le_bytes());
+                bytes[2..].copy_from_slice(self.z.as_bytes());
+                bytes
+            }
+        }
+
+        fn to_scalar(scalar: &RistrettoScalar) -> Scalar {
+            Scalar::from_canonical_bytes(scalar.to_byte_array()).expect("Canonical scalar")
+        }
+
+        fn to_point(point: &SharedPoint) -> RistrettoPoint {
+            CompressedRistretto(point.compress()).decompress().expect("Valid point")
+        }
+
+        /// The shared public key and H, the encoding of `alpha_string` to the
+        /// curve under it. An identity key is not refused here, but no proof
+        /// under it verifies, so [aggregate] fails.
+        fn encode_to_curve(output: &PublicOutput, alpha_string: &[u8]) -> (ECVRFPublicKey, PodRistrettoPoint) {
+            let public_key = ECVRFPublicKey(WrappedPodRistrettoPoint(PodRistrettoPoint(output.public_key().compress())));
+            let h_point = public_key.ecvrf_encode_to_curve(alpha_string);
+            (public_key, h_point)
+        }
+
+        /// What the responses of a set of signers depend on: for each signer,
+        /// in increasing order of id, its commitment, Lagrange coefficient
+        /// lambda_i and binding factor rho_i, then gamma and the challenge
+        struct Session<'a> {
+            public_key: ECVRFPublicKey,
+            signers: Vec<(&'a NonceCommitment, Scalar, Scalar)>,
+            gamma: RistrettoPoint,
+            c: Challenge,
+        }
+
+        impl<'a> Session<'a> {
+            fn new(
+                output: &PublicOutput,
+                alpha_string: &[u8],
+                commitments: &'a [NonceCommitment],
+            ) -> Result<Self, MangekyouError> {
+                if commitments.len() < output.commitments.len() {
+                    return Err(MangekyouError::NotEnoughInputs);
+                }
+                let mut sorted: Vec<&NonceCommitment> = commitments.iter().collect();
+                sorted.sort_by_key(|commitment| commitment.id);
+                let ids: Vec<PartyId> = sorted.iter().map(|commitment| commitment.id).collect();
+                let (public_key, h_point) = encode_to_curve(output, alpha_string);
+
+                // Binding factors hash the whole set of commitments, so that no
+                // signer can choose its own as a function of the others
+                let mut transcript = H::default();
+                transcript.update(BINDING_FACTOR_DST);
+                transcript.update(public_key.as_ref());
+                transcript.update((alpha_string.len() as u64).to_le_bytes());
+                transcript.update(alpha_string);
+                for commitment in &sorted {
+                    transcript.update(commitment.to_bytes());
+                }
+                let seed = transcript.finalize().digest;
+
+                let signers = sorted
+                    .into_iter()
+                    .map(|commitment| {
+                        let lambda = to_scalar(&lagrange_coefficient(commitment.id, &ids)?);
+                        let mut hasher = H::default();
+                        hasher.update(seed);
+                        hasher.update(commitment.id.to_le_bytes());
+                        let rho = Scalar::from_bytes_mod_order_wide(&hasher.finalize().digest);
+                        Ok((commitment, lambda, rho))
+                    })
+                    .collect::<Result<Vec<_>, MangekyouError>>()?;
+
+                let gamma = RistrettoPoint::vartime_multiscalar_mul(
+                    signers.iter().map(|(_, lambda, _)| lambda),
+                    signers.iter().map(|(commitment, _, _)| commitment.gamma),
+                );
+                let (u, v) = signers.iter().fold(
+                    (RistrettoPoint::identity(), RistrettoPoint::identity()),
+                    |(u, v), (commitment, _, rho)| {
+                        (u + commitment.d + commitment.e * rho, v + commitment.d_h + commitment.e_h * rho)
+                    },
+                );
+                let c = ecvrf_challenge_generation([
+                    &public_key.0.0,
+                    &h_point,
+                    &PodRistrettoPoint::from(&gamma),
+                    &PodRistrettoPoint::from(&u),
+                    &PodRistrettoPoint::from(&v),
+                ]);
+                Ok(Self { public_key, signers, gamma, c })
+            }
+
+            fn signer(&self, id: PartyId) -> Result<&(&'a NonceCommitment, Scalar, Scalar), MangekyouError> {
+                self.signers
+                    .iter()
+                    .find(|(commitment, _, _)| commitment.id == id)
+                    .ok_or(MangekyouError::InvalidInput)
+            }
+        }
+
+        /// First round: draws the nonces of the holder of `share` for
+        /// proving `alpha_string`
+        pub fn commit<R: AllowedRng>(share: &KeyShare, alpha_string: &[u8], rng: &mut R) -> Nonces {
+            let (_, h_point) = encode_to_curve(&share.output, alpha_string);
+            let h = CompressedRistretto(h_point.0).decompress().expect("Valid point");
+            let [d, e] = [(); 2].map(|_| {
+                let mut bytes = [0u8; 64];
+                rng.fill_bytes(&mut bytes);
+                Scalar::from_bytes_mod_order_wide(&bytes)
+            });
+            Nonces {
+                commitment: NonceCommitment {
+                    id: share.id,
+                    gamma: h * to_scalar(share.share()),
+                    d: RISTRETTO_BASEPOINT_POINT * d,
+                    e: RISTRETTO_BASEPOINT_POINT * e,
+                    d_h: h * d,
+                    e_h: h * e,
+                },
+                d,
+                e,
+            }
+        }
+
+        /// Second round: the response of the holder of `share` to the
+        /// challenge of the signers of `commitments`, which must include the
+        /// commitment of `nonces`
+        pub fn partial_prove(
+            share: &KeyShare,
+            nonces: Nonces,
+            alpha_string: &[u8],
+            commitments: &[NonceCommitment],
+        ) -> Result<PartialProof, MangekyouError> {
+            if nonces.commitment.id != share.id || !commitments.contains(&nonces.commitment) {
+                return Err(MangekyouError::InvalidInput);
+            }
+            let session = Session::new(&share.output, alpha_string, commitments)?;
+            let (_, lambda, rho) = session.signer(share.id)?;
+            let mut x = to_scalar(share.share());
+            let z = nonces.d + rho * nonces.e + session.c.scalar() * lambda * x;
+            x.zeroize();
+            Ok(PartialProof { id: share.id, z })
+        }
+
+        /// Checks the response of a signer against its commitment and the
+        /// public key of its share:
+        /// z_i*B = D + rho_i*E + c*lambda_i*Y_i and z_i*H = D' + rho_i*E' + c*lambda_i*Gamma_i
+        pub fn verify_partial(
+            output: &PublicOutput,
+            alpha_string: &[u8],
+            commitments: &[NonceCommitment],
+            partial: &PartialProof,
+        ) -> Result<(), MangekyouError> {
+            let session = Session::new(output, alpha_string, commitments)?;
+            let (commitment, lambda, rho) = session.signer(partial.id)?;
+            let (_, h_point) = encode_to_curve(output, alpha_string);
+            let h = CompressedRistretto(h_point.0).decompress().expect("Valid point");
+            let y = to_point(&output.share_public_key(partial.id));
+            let challenge = session.c.scalar() * lambda;
+
+            if RISTRETTO_BASEPOINT_POINT * partial.z != commitment.d + commitment.e * rho + y * challenge
+                || h * partial.z != commitment.d_h + commitment.e_h * rho + commitment.gamma * challenge
+            {
+                return Err(MangekyouError::InvalidProof);
+            }
+            Ok(())
+        }
+
+        /// Combines the responses of every signer of `commitments` into a
+        /// proof under the shared public key, and verifies it. Fails with
+        /// [MangekyouError::ChallengeMismatch] if a response is invalid;
+        /// [verify_partial] tells which.
+        pub fn aggregate(
+            output: &PublicOutput,
+            alpha_string: &[u8],
+            commitments: &[NonceCommitment],
+            partials: &[PartialProof],
+        ) -> Result<ECVRFProof, MangekyouError> {
+            if partials.len() != commitments.len() {
+                return Err(MangekyouError::InvalidInput);
+            }
+            let session = Session::new(output, alpha_string, commitments)?;
+            // One response per signer
+            let mut ids: Vec<PartyId> = partials.iter().map(|partial| partial.id).collect();
+            ids.sort_unstable();
+            ids.dedup();
+            if ids.len() != partials.len() || ids.iter().any(|&id| session.signer(id).is_err()) {
+                return Err(MangekyouError::InvalidInput);
+            }
+            let s: Scalar = partials.iter().map(|partial| partial.z).sum();
+
+            let proof = ECVRFProof {
+                gamma: PodRistrettoPoint::from(&session.gamma),
+                c: session.c,
+                s: PodScalar::from(&s),
+            };
+            proof.verify(alpha_string, &session.public_key)?;
+            Ok(proof)
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Password-encrypted JSON keystores for ECVRF oracle keys, so that secret
+//! keys are not kept on disk in the clear. As with the scrypt recipients of
+//! age, the password is stretched with scrypt into a ChaCha20-Poly1305 key,
+//! which encrypts the secret key. The public key is stored in the clear, so
+//! that operators can tell keystores apart without unlocking them, and is
+//! authenticated as associated data.
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "public_key": "0x...",
+//!   "kdf": { "log_n": 18, "r": 8, "p": 1, "salt": "0x..." },
+//!   "nonce": "0x...",
+//!   "ciphertext": "0x..."
+//! }
+//! ```
+
+use crate::encoding::Hex;
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFPublicKey, KEY_LENGTH};
+use crate::serde_helpers::ToFromByteArray;
+use crate::traits::AllowedRng;
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use std::path::Path;
+use zeroize::Zeroize;
+
+/// Version of the keystore format
+pub const VERSION: u8 = 1;
+
+/// Length of the scrypt salt
+pub const SALT_LENGTH: usize = 32;
+
+/// Length of the ChaCha20-Poly1305 nonce
+pub const NONCE_LENGTH: usize = 12;
+
+/// Parameters of scrypt: the cost 2^log_n, the block size r and the
+/// parallelism p
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+}
+
+impl Default for KdfParams {
+    /// The work factor age uses by default, about a second on a laptop
+    fn default() -> Self {
+        Self { log_n: 18, r: 8, p: 1 }
+    }
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Kdf {
+    #[serde(flatten)]
+    params: KdfParams,
+    #[serde_as(as = "Hex")]
+    salt: [u8; SALT_LENGTH],
+}
+
+/// An ECVRF key pair encrypted under a password
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keystore {
+    version: u8,
+    #[serde_as(as = "Hex")]
+    public_key: [u8; KEY_LENGTH],
+    kdf: Kdf,
+    #[serde_as(as = "Hex")]
+    nonce: [u8; NONCE_LENGTH],
+    #[serde_as(as = "Hex")]
+    ciphertext: Vec<u8>,
+}
+
+impl Keystore {
+    /// Encrypts `keypair` under `password` with the default [KdfParams]
+    pub fn create<R: AllowedRng>(keypair: &ECVRFKeyPair, password: &[u8], rng: &mut R) -> MangekyouResult<Self> {
+        Self::create_with_params(keypair, password, KdfParams::default(), rng)
+    }
+
+    /// Encrypts `keypair` under `password` with scrypt parameters `params`.
+    /// Fails with [MangekyouError::InvalidInput] if scrypt does not accept
+    /// them.
+    pub fn create_with_params<R: AllowedRng>(
+        keypair: &ECVRFKeyPair,
+        password: &[u8],
+        params: KdfParams,
+        rng: &mut R,
+    ) -> MangekyouResult<Self> {
+        let mut kdf = Kdf { params, salt: [0u8; SALT_LENGTH] };
+        rng.fill_bytes(&mut kdf.salt);
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rng.fill_bytes(&mut nonce);
+        let public_key = keypair.pk.to_byte_array();
+
+        let cipher = kdf.cipher(password)?;
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload { msg: keypair.sk.as_ref(), aad: &public_key },
+            )
+            .map_err(|_| MangekyouError::GeneralOpaqueError)?;
+        Ok(Self { version: VERSION, public_key, kdf, nonce, ciphertext })
+    }
+
+    /// Decrypts the key pair. Fails with [MangekyouError::GeneralOpaqueError]
+    /// if the password is wrong or the keystore was tampered with.
+    pub fn unlock(&self, password: &[u8]) -> MangekyouResult<ECVRFKeyPair> {
+        let cipher = self.kdf.cipher(password)?;
+        let mut secret_key = cipher
+            .decrypt(
+                Nonce::from_slice(&self.nonce),
+                Payload { msg: &self.ciphertext, aad: &self.public_key },
+            )
+            .map_err(|_| MangekyouError::GeneralOpaqueError)?;
+        let keypair = <[u8; KEY_LENGTH]>::try_from(secret_key.as_slice())
+            .map_err(|_| MangekyouError::InvalidInput)
+            .and_then(|bytes| ECVRFPrivateKey::from_byte_array(&bytes))
+            .map(ECVRFKeyPair::from);
+        secret_key.zeroize();
+        let keypair = keypair?;
+        if keypair.pk.to_byte_array() != self.public_key {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(keypair)
+    }
+
+    /// Re-encrypts the key pair under `new_password`, with a fresh salt and
+    /// nonce and the same scrypt parameters
+    pub fn rotate_password<R: AllowedRng>(
+        &self,
+        password: &[u8],
+        new_password: &[u8],
+        rng: &mut R,
+    ) -> MangekyouResult<Self> {
+        Self::create_with_params(&self.unlock(password)?, new_password, self.kdf.params, rng)
+    }
+
+    pub fn public_key(&self) -> MangekyouResult<ECVRFPublicKey> {
+        ECVRFPublicKey::from_byte_array(&self.public_key)
+    }
+
+    pub fn kdf_params(&self) -> KdfParams {
+        self.kdf.params
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Keystores serialize")
+    }
+
+    /// Parses a keystore, refusing versions other than [VERSION]
+    pub fn from_json(json: &str) -> MangekyouResult<Self> {
+        let keystore: Self = serde_json::from_str(json).map_err(|_| MangekyouError::InvalidInput)?;
+        if keystore.version != VERSION {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(keystore)
+    }
+
+    /// Writes the keystore to `path`, readable and writable by its owner only
+    /// on Unix
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        std::io::Write::write_all(&mut options.open(path)?, self.to_json().as_bytes())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Kdf {
+    /// The cipher keyed with the scrypt hash of `password`
+    fn cipher(&self, password: &[u8]) -> MangekyouResult<ChaCha20Poly1305> {
+        let params = scrypt::Params::new(self.params.log_n, self.params.r, self.params.p, 32)
+            .map_err(|_| MangekyouError::InvalidInput)?;
+        let mut key = [0u8; 32];
+        scrypt::scrypt(password, &self.salt, &params, &mut key).map_err(|_| MangekyouError::InvalidInput)?;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        key.zeroize();
+        Ok(cipher)
+    }
+}
@@ -7,9 +7,9 @@
     rust_2021_compatibility
 )]
 
-// #[cfg(test)]
-// #[path = "tests/hash_tests.rs"]
-// pub mod hash_tests;
+#[cfg(test)]
+#[path = "tests/hash_tests.rs"]
+pub mod hash_tests;
 
 #[cfg(test)]
 #[path = "tests/encoding_tests.rs"]
@@ -27,15 +27,106 @@ pub mod test_helpers;
 #[path = "tests/utils_tests.rs"]
 pub mod utils_tests;
 
+#[cfg(test)]
+#[path = "tests/kamui_vrf_negative_tests.rs"]
+pub mod kamui_vrf_negative_tests;
+
+#[cfg(test)]
+#[path = "tests/ristretto_ops_tests.rs"]
+pub mod ristretto_ops_tests;
+
+#[cfg(test)]
+#[path = "tests/kamui_vrf_byte_array_tests.rs"]
+pub mod kamui_vrf_byte_array_tests;
+
+#[cfg(test)]
+#[path = "tests/kamui_vrf_cu_tests.rs"]
+pub mod kamui_vrf_cu_tests;
+
+#[cfg(test)]
+#[path = "tests/kamui_vrf_hash_to_curve_tests.rs"]
+pub mod kamui_vrf_hash_to_curve_tests;
+
+#[cfg(test)]
+#[path = "tests/drand_tests.rs"]
+pub mod drand_tests;
+
+#[cfg(test)]
+#[path = "tests/ecvrf_edwards25519_tests.rs"]
+pub mod ecvrf_edwards25519_tests;
+
+#[cfg(test)]
+#[path = "tests/kamui_vrf_batch_tests.rs"]
+pub mod kamui_vrf_batch_tests;
+
+#[cfg(test)]
+#[path = "tests/kamui_vrf_key_derivation_tests.rs"]
+pub mod kamui_vrf_key_derivation_tests;
+
+#[cfg(test)]
+#[path = "tests/kamui_vrf_output_tests.rs"]
+pub mod kamui_vrf_output_tests;
+
+#[cfg(test)]
+#[path = "tests/kamui_vrf_threshold_tests.rs"]
+pub mod kamui_vrf_threshold_tests;
+
+#[cfg(test)]
+#[path = "tests/kamui_vrf_parsing_tests.rs"]
+pub mod kamui_vrf_parsing_tests;
+
+#[cfg(test)]
+#[path = "tests/tdkg_tests.rs"]
+pub mod tdkg_tests;
+
+#[cfg(all(test, feature = "ecvrf-secp256k1"))]
+#[path = "tests/ecvrf_secp256k1_tests.rs"]
+pub mod ecvrf_secp256k1_tests;
+
+#[cfg(all(test, feature = "bls-vrf"))]
+#[path = "tests/bls_vrf_tests.rs"]
+pub mod bls_vrf_tests;
+
+#[cfg(all(test, feature = "keystore"))]
+#[path = "tests/keystore_tests.rs"]
+pub mod keystore_tests;
+
+#[cfg(test)]
+#[path = "tests/signer_tests.rs"]
+pub mod signer_tests;
+
+#[cfg(test)]
+#[path = "tests/sigs_tests.rs"]
+pub mod sigs_tests;
+
+#[cfg(test)]
+#[path = "tests/scheme_tests.rs"]
+pub mod scheme_tests;
+
 pub mod traits;
 
+#[cfg(feature = "bls-vrf")]
+pub mod bls_vrf;
+pub mod drand;
+pub mod ecvrf_edwards25519;
+#[cfg(feature = "ecvrf-secp256k1")]
+pub mod ecvrf_secp256k1;
 pub mod encoding;
 pub mod error;
 pub mod groups;
 pub mod hash;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+pub mod scheme;
 pub mod serde_helpers;
+pub mod signer;
+pub mod sigs;
 pub mod utils;
 pub mod kamui_vrf;
+pub mod ristretto_ops;
+pub mod tdkg;
+
+pub use scheme::{GenericKeyPair, GenericPublicKey, GenericSignature, Scheme};
 
 /// This module contains unsecure cryptographic primitives. The purpose of this library is to allow seamless
 /// benchmarking of systems without taking into account the cost of cryptographic primitives - and hence
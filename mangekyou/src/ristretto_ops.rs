@@ -0,0 +1,11 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ristretto255 group operations used by the ECVRF in [crate::kamui_vrf],
+//! from [mangekyou_core::ristretto_ops]. The implementation used by the ECVRF
+//! is [DefaultRistrettoOps], selected by feature:
+//!
+//! - default: [SolanaSyscallOps]
+//! - `ristretto-dalek`: [DalekOps], pure Rust with no syscalls
+
+pub use mangekyou_core::ristretto_ops::*;
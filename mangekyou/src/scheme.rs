@@ -0,0 +1,321 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Keys and signatures of any of the supported schemes behind one type each, so that wallets
+//! can store keys of different schemes together. Their encodings are prefixed by the flag of
+//! their [Scheme]: `flag || private key`, `flag || public key` and `flag || signature`. For
+//! [Scheme::Ecvrf], signatures are proofs in the bare gamma || c || s layout, as the flag
+//! takes the place of the proof envelope.
+
+use crate::encoding::{Base64, Encoding};
+use crate::error::MangekyouError;
+use crate::kamui_vrf::ecvrf::{
+    ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey, KEY_LENGTH, PROOF_LENGTH,
+};
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use crate::serde_helpers::ToFromByteArray;
+use crate::sigs::ed25519::{Ed25519KeyPair, Ed25519PublicKey, Ed25519Signature};
+use crate::sigs::secp256k1::{Secp256k1KeyPair, Secp256k1PublicKey, Secp256k1Signature};
+use crate::sigs::secp256r1::{Secp256r1KeyPair, Secp256r1PublicKey, Secp256r1Signature};
+use crate::traits::{AllowedRng, KeyPair, Signer, ToFromBytes, VerifyingKey};
+use mangekyou_derive::SilentDebug;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A signature scheme, identified in encodings by its flag
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    Ed25519,
+    Secp256k1,
+    Secp256r1,
+    /// The ECVRF of [crate::kamui_vrf::ecvrf], whose signatures are VRF proofs
+    Ecvrf,
+}
+
+impl Scheme {
+    pub const ALL: [Scheme; 4] = [Scheme::Ed25519, Scheme::Secp256k1, Scheme::Secp256r1, Scheme::Ecvrf];
+
+    /// The byte prefixing encodings of this scheme
+    pub fn flag(self) -> u8 {
+        match self {
+            Scheme::Ed25519 => 0x00,
+            Scheme::Secp256k1 => 0x01,
+            Scheme::Secp256r1 => 0x02,
+            Scheme::Ecvrf => 0x03,
+        }
+    }
+
+    pub fn from_flag(flag: u8) -> Result<Self, MangekyouError> {
+        Self::ALL
+            .into_iter()
+            .find(|scheme| scheme.flag() == flag)
+            .ok_or(MangekyouError::InvalidInput)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scheme::Ed25519 => "ed25519",
+            Scheme::Secp256k1 => "secp256k1",
+            Scheme::Secp256r1 => "secp256r1",
+            Scheme::Ecvrf => "ecvrf",
+        }
+    }
+}
+
+impl Display for Scheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = MangekyouError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|scheme| scheme.name() == s)
+            .ok_or(MangekyouError::InvalidInput)
+    }
+}
+
+/// Splits `flag || payload`
+fn split_flag(bytes: &[u8]) -> Result<(Scheme, &[u8]), MangekyouError> {
+    let (flag, payload) = bytes.split_first().ok_or(MangekyouError::InvalidInput)?;
+    Ok((Scheme::from_flag(*flag)?, payload))
+}
+
+fn with_flag(scheme: Scheme, payload: &[u8]) -> Vec<u8> {
+    [&[scheme.flag()][..], payload].concat()
+}
+
+/// Implements serde for a type with `to_bytes` and `from_bytes`, as Base64 with human readable
+/// serializers and as bytes otherwise
+macro_rules! impl_flagged_serde {
+    ($type:ty) => {
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match serializer.is_human_readable() {
+                    true => serializer.serialize_str(&Base64::encode(self.to_bytes())),
+                    false => serde::Serialize::serialize(&self.to_bytes(), serializer),
+                }
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let bytes = match deserializer.is_human_readable() {
+                    true => Base64::decode(&<String as serde::Deserialize>::deserialize(deserializer)?)
+                        .map_err(serde::de::Error::custom)?,
+                    false => <Vec<u8> as serde::Deserialize>::deserialize(deserializer)?,
+                };
+                Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+/// A public key of any [Scheme]
+#[derive(Clone, Debug, PartialEq)]
+pub enum GenericPublicKey {
+    Ed25519(Ed25519PublicKey),
+    Secp256k1(Secp256k1PublicKey),
+    Secp256r1(Secp256r1PublicKey),
+    Ecvrf(ECVRFPublicKey),
+}
+
+impl GenericPublicKey {
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            GenericPublicKey::Ed25519(_) => Scheme::Ed25519,
+            GenericPublicKey::Secp256k1(_) => Scheme::Secp256k1,
+            GenericPublicKey::Secp256r1(_) => Scheme::Secp256r1,
+            GenericPublicKey::Ecvrf(_) => Scheme::Ecvrf,
+        }
+    }
+
+    /// `flag || public key`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let payload = match self {
+            GenericPublicKey::Ed25519(pk) => pk.as_ref(),
+            GenericPublicKey::Secp256k1(pk) => pk.as_ref(),
+            GenericPublicKey::Secp256r1(pk) => pk.as_ref(),
+            GenericPublicKey::Ecvrf(pk) => pk.as_ref(),
+        };
+        with_flag(self.scheme(), payload)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let (scheme, payload) = split_flag(bytes)?;
+        Ok(match scheme {
+            Scheme::Ed25519 => GenericPublicKey::Ed25519(Ed25519PublicKey::from_bytes(payload)?),
+            Scheme::Secp256k1 => GenericPublicKey::Secp256k1(Secp256k1PublicKey::from_bytes(payload)?),
+            Scheme::Secp256r1 => GenericPublicKey::Secp256r1(Secp256r1PublicKey::from_bytes(payload)?),
+            Scheme::Ecvrf => {
+                let payload = payload
+                    .try_into()
+                    .map_err(|_| MangekyouError::InputLengthWrong(KEY_LENGTH))?;
+                GenericPublicKey::Ecvrf(ECVRFPublicKey::from_byte_array(payload)?)
+            }
+        })
+    }
+
+    /// Verifies `signature` over `msg`, which fails if the signature is of another scheme
+    pub fn verify(&self, msg: &[u8], signature: &GenericSignature) -> Result<(), MangekyouError> {
+        match (self, signature) {
+            (GenericPublicKey::Ed25519(pk), GenericSignature::Ed25519(sig)) => pk.verify(msg, sig),
+            (GenericPublicKey::Secp256k1(pk), GenericSignature::Secp256k1(sig)) => pk.verify(msg, sig),
+            (GenericPublicKey::Secp256r1(pk), GenericSignature::Secp256r1(sig)) => pk.verify(msg, sig),
+            (GenericPublicKey::Ecvrf(pk), GenericSignature::Ecvrf(proof)) => proof.verify(msg, pk),
+            _ => Err(MangekyouError::InvalidSignature),
+        }
+    }
+}
+
+impl_flagged_serde!(GenericPublicKey);
+
+/// A signature of any [Scheme]
+#[derive(Clone, Debug, PartialEq)]
+pub enum GenericSignature {
+    Ed25519(Ed25519Signature),
+    Secp256k1(Secp256k1Signature),
+    Secp256r1(Secp256r1Signature),
+    Ecvrf(ECVRFProof),
+}
+
+impl GenericSignature {
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            GenericSignature::Ed25519(_) => Scheme::Ed25519,
+            GenericSignature::Secp256k1(_) => Scheme::Secp256k1,
+            GenericSignature::Secp256r1(_) => Scheme::Secp256r1,
+            GenericSignature::Ecvrf(_) => Scheme::Ecvrf,
+        }
+    }
+
+    /// `flag || signature`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            GenericSignature::Ed25519(sig) => with_flag(self.scheme(), sig.as_ref()),
+            GenericSignature::Secp256k1(sig) => with_flag(self.scheme(), sig.as_ref()),
+            GenericSignature::Secp256r1(sig) => with_flag(self.scheme(), sig.as_ref()),
+            GenericSignature::Ecvrf(proof) => with_flag(self.scheme(), &proof.to_byte_array()),
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let (scheme, payload) = split_flag(bytes)?;
+        Ok(match scheme {
+            Scheme::Ed25519 => GenericSignature::Ed25519(Ed25519Signature::from_bytes(payload)?),
+            Scheme::Secp256k1 => GenericSignature::Secp256k1(Secp256k1Signature::from_bytes(payload)?),
+            Scheme::Secp256r1 => GenericSignature::Secp256r1(Secp256r1Signature::from_bytes(payload)?),
+            Scheme::Ecvrf => {
+                let payload = payload
+                    .try_into()
+                    .map_err(|_| MangekyouError::InputLengthWrong(PROOF_LENGTH))?;
+                GenericSignature::Ecvrf(ECVRFProof::from_byte_array(payload)?)
+            }
+        })
+    }
+}
+
+impl_flagged_serde!(GenericSignature);
+
+/// A key pair of any [Scheme]
+#[derive(SilentDebug)]
+pub enum GenericKeyPair {
+    Ed25519(Ed25519KeyPair),
+    Secp256k1(Secp256k1KeyPair),
+    Secp256r1(Secp256r1KeyPair),
+    Ecvrf(ECVRFKeyPair),
+}
+
+impl GenericKeyPair {
+    pub fn generate<R: AllowedRng>(scheme: Scheme, rng: &mut R) -> Self {
+        match scheme {
+            Scheme::Ed25519 => GenericKeyPair::Ed25519(Ed25519KeyPair::generate(rng)),
+            Scheme::Secp256k1 => GenericKeyPair::Secp256k1(Secp256k1KeyPair::generate(rng)),
+            Scheme::Secp256r1 => GenericKeyPair::Secp256r1(Secp256r1KeyPair::generate(rng)),
+            Scheme::Ecvrf => GenericKeyPair::Ecvrf(<ECVRFKeyPair as VRFKeyPair<64>>::generate(rng)),
+        }
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            GenericKeyPair::Ed25519(_) => Scheme::Ed25519,
+            GenericKeyPair::Secp256k1(_) => Scheme::Secp256k1,
+            GenericKeyPair::Secp256r1(_) => Scheme::Secp256r1,
+            GenericKeyPair::Ecvrf(_) => Scheme::Ecvrf,
+        }
+    }
+
+    pub fn public(&self) -> GenericPublicKey {
+        match self {
+            GenericKeyPair::Ed25519(kp) => GenericPublicKey::Ed25519(kp.public().clone()),
+            GenericKeyPair::Secp256k1(kp) => GenericPublicKey::Secp256k1(kp.public().clone()),
+            GenericKeyPair::Secp256r1(kp) => GenericPublicKey::Secp256r1(kp.public().clone()),
+            GenericKeyPair::Ecvrf(kp) => GenericPublicKey::Ecvrf(kp.pk.clone()),
+        }
+    }
+
+    /// Signs `msg`, or for [Scheme::Ecvrf] proves it
+    pub fn sign(&self, msg: &[u8]) -> GenericSignature {
+        match self {
+            GenericKeyPair::Ed25519(kp) => GenericSignature::Ed25519(kp.sign(msg)),
+            GenericKeyPair::Secp256k1(kp) => GenericSignature::Secp256k1(kp.sign(msg)),
+            GenericKeyPair::Secp256r1(kp) => GenericSignature::Secp256r1(kp.sign(msg)),
+            GenericKeyPair::Ecvrf(kp) => GenericSignature::Ecvrf(VRFKeyPair::prove(kp, msg)),
+        }
+    }
+
+    /// `flag || private key`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            GenericKeyPair::Ed25519(kp) => with_flag(self.scheme(), kp.as_ref()),
+            GenericKeyPair::Secp256k1(kp) => with_flag(self.scheme(), kp.as_ref()),
+            GenericKeyPair::Secp256r1(kp) => with_flag(self.scheme(), kp.as_ref()),
+            GenericKeyPair::Ecvrf(kp) => with_flag(self.scheme(), kp.sk.as_ref()),
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let (scheme, payload) = split_flag(bytes)?;
+        Ok(match scheme {
+            Scheme::Ed25519 => GenericKeyPair::Ed25519(Ed25519KeyPair::from_bytes(payload)?),
+            Scheme::Secp256k1 => GenericKeyPair::Secp256k1(Secp256k1KeyPair::from_bytes(payload)?),
+            Scheme::Secp256r1 => GenericKeyPair::Secp256r1(Secp256r1KeyPair::from_bytes(payload)?),
+            Scheme::Ecvrf => {
+                let payload = payload
+                    .try_into()
+                    .map_err(|_| MangekyouError::InputLengthWrong(KEY_LENGTH))?;
+                GenericKeyPair::Ecvrf(ECVRFKeyPair::from(ECVRFPrivateKey::from_byte_array(payload)?))
+            }
+        })
+    }
+}
+
+impl_flagged_serde!(GenericKeyPair);
+
+impl From<Ed25519KeyPair> for GenericKeyPair {
+    fn from(kp: Ed25519KeyPair) -> Self {
+        GenericKeyPair::Ed25519(kp)
+    }
+}
+
+impl From<Secp256k1KeyPair> for GenericKeyPair {
+    fn from(kp: Secp256k1KeyPair) -> Self {
+        GenericKeyPair::Secp256k1(kp)
+    }
+}
+
+impl From<Secp256r1KeyPair> for GenericKeyPair {
+    fn from(kp: Secp256r1KeyPair) -> Self {
+        GenericKeyPair::Secp256r1(kp)
+    }
+}
+
+impl From<ECVRFKeyPair> for GenericKeyPair {
+    fn from(kp: ECVRFKeyPair) -> Self {
+        GenericKeyPair::Ecvrf(kp)
+    }
+}
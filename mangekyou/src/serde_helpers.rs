@@ -125,6 +125,66 @@ macro_rules! serialize_deserialize_with_to_from_byte_array {
     };
 }
 
+/// Macro for generating Display and FromStr as hex, and Serialize/Deserialize with hex for
+/// is_human_readable() serializers and a fixed size array otherwise, for a type that implements
+/// [ToFromByteArray].
+#[macro_export]
+macro_rules! hex_encode_decode_with_to_from_byte_array {
+    ($type:ty) => {
+        impl ::std::fmt::Display for $type {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                use $crate::encoding::{Encoding, Hex};
+                use $crate::serde_helpers::ToFromByteArray;
+
+                write!(f, "{}", Hex::encode(self.to_byte_array()))
+            }
+        }
+
+        impl ::std::str::FromStr for $type {
+            type Err = $crate::error::MangekyouError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                use $crate::serde_helpers::ToFromByteArray;
+
+                let bytes: [u8; <$type>::BYTE_LENGTH] = $crate::encoding::decode_bytes_hex(s)
+                    .map_err(|_| $crate::error::MangekyouError::InvalidInput)?;
+                Self::from_byte_array(&bytes)
+            }
+        }
+
+        impl ::serde::Serialize for $type {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use $crate::serde_helpers::{SerializationHelper, ToFromByteArray};
+
+                match serializer.is_human_readable() {
+                    true => serializer.collect_str(self),
+                    false => SerializationHelper::<{ <$type>::BYTE_LENGTH }>(self.to_byte_array())
+                        .serialize(serializer),
+                }
+            }
+        }
+
+        impl<'de> ::serde::Deserialize<'de> for $type {
+            fn deserialize<D: ::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                use $crate::serde_helpers::{SerializationHelper, ToFromByteArray};
+
+                match deserializer.is_human_readable() {
+                    true => <String as ::serde::Deserialize>::deserialize(deserializer)?
+                        .parse()
+                        .map_err(::serde::de::Error::custom),
+                    false => {
+                        let helper: SerializationHelper<{ <$type>::BYTE_LENGTH }> =
+                            ::serde::Deserialize::deserialize(deserializer)?;
+                        Self::from_byte_array(&helper.0).map_err(::serde::de::Error::custom)
+                    }
+                }
+            }
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! serialize_deserialize_with_to_from_bytes {
     ($type:ty, $length:tt) => {
@@ -0,0 +1,168 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Where oracles get their VRF proofs from. [VrfSigner] is implemented by
+//! [ECVRFKeyPair] for keys held in memory and, with the `remote-signer`
+//! feature, by [RemoteSigner] for keys held by a separate signing service or
+//! an HSM behind one, so that the oracle itself never sees the secret key.
+
+use crate::error::MangekyouResult;
+use crate::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFProof, ECVRFPublicKey};
+use crate::kamui_vrf::VRFKeyPair;
+
+#[cfg(feature = "remote-signer")]
+pub use remote::RemoteSigner;
+
+/// Proves VRF outputs under a fixed public key
+pub trait VrfSigner {
+    /// The public key proofs verify under
+    fn public_key(&self) -> ECVRFPublicKey;
+
+    /// Proves `alpha_string`. Fails if the key is not reachable, in which case
+    /// retrying may succeed.
+    fn prove(&self, alpha_string: &[u8]) -> MangekyouResult<ECVRFProof>;
+}
+
+impl VrfSigner for ECVRFKeyPair {
+    fn public_key(&self) -> ECVRFPublicKey {
+        self.pk.clone()
+    }
+
+    fn prove(&self, alpha_string: &[u8]) -> MangekyouResult<ECVRFProof> {
+        Ok(VRFKeyPair::prove(self, alpha_string))
+    }
+}
+
+impl<S: VrfSigner + ?Sized> VrfSigner for Box<S> {
+    fn public_key(&self) -> ECVRFPublicKey {
+        (**self).public_key()
+    }
+
+    fn prove(&self, alpha_string: &[u8]) -> MangekyouResult<ECVRFProof> {
+        (**self).prove(alpha_string)
+    }
+}
+
+#[cfg(feature = "remote-signer")]
+mod remote {
+    use super::VrfSigner;
+    use crate::encoding::Hex;
+    use crate::error::{MangekyouError, MangekyouResult};
+    use crate::kamui_vrf::ecvrf::{ECVRFProof, ECVRFPublicKey};
+    use crate::kamui_vrf::VRFProof;
+    use serde::{Deserialize, Serialize};
+    use serde_with::serde_as;
+    use std::time::Duration;
+
+    #[serde_as]
+    #[derive(Deserialize)]
+    struct PublicKeyResponse {
+        #[serde_as(as = "Hex")]
+        public_key: Vec<u8>,
+    }
+
+    #[serde_as]
+    #[derive(Serialize)]
+    struct ProveRequest<'a> {
+        #[serde_as(as = "Hex")]
+        alpha: &'a [u8],
+    }
+
+    #[serde_as]
+    #[derive(Deserialize)]
+    struct ProveResponse {
+        #[serde_as(as = "Hex")]
+        proof: Vec<u8>,
+    }
+
+    /// A signing service reached over HTTP, which holds the secret key and
+    /// answers two JSON endpoints, with byte strings 0x-prefixed hex:
+    ///
+    /// - `GET {url}/public_key` returns `{"public_key": ...}`.
+    /// - `POST {url}/prove` with `{"alpha": ...}` returns `{"proof": ...}`,
+    ///   the proof in the envelope of [VRFProof::to_bytes].
+    ///
+    /// Proofs are verified before they are returned, so a faulty or
+    /// compromised service cannot make the oracle submit an invalid proof or
+    /// one under another key.
+    pub struct RemoteSigner {
+        url: String,
+        public_key: ECVRFPublicKey,
+        token: Option<String>,
+        agent: ureq::Agent,
+    }
+
+    impl RemoteSigner {
+        /// How long a request may take before it fails
+        pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+        /// A signer for the service at `url`, which must prove under
+        /// `public_key`. The key is pinned rather than taken from the service.
+        pub fn new(url: &str, public_key: ECVRFPublicKey) -> Self {
+            Self {
+                url: url.trim_end_matches('/').to_string(),
+                public_key,
+                token: None,
+                agent: ureq::AgentBuilder::new().timeout(Self::DEFAULT_TIMEOUT).build(),
+            }
+        }
+
+        /// Sends `token` as a bearer token with every request
+        pub fn with_token(mut self, token: &str) -> Self {
+            self.token = Some(token.to_string());
+            self
+        }
+
+        pub fn with_timeout(mut self, timeout: Duration) -> Self {
+            self.agent = ureq::AgentBuilder::new().timeout(timeout).build();
+            self
+        }
+
+        /// Checks that the service is reachable and holds the key of
+        /// [public_key](VrfSigner::public_key), as oracles should at startup.
+        /// Fails with [MangekyouError::InvalidPublicKey] if it holds another.
+        pub fn check(&self) -> MangekyouResult<()> {
+            let response: PublicKeyResponse = read_json(self.get("public_key").call())?;
+            if response.public_key != self.public_key.as_ref() {
+                return Err(MangekyouError::InvalidPublicKey);
+            }
+            Ok(())
+        }
+
+        fn get(&self, endpoint: &str) -> ureq::Request {
+            self.authorize(self.agent.get(&format!("{}/{}", self.url, endpoint)))
+        }
+
+        fn post(&self, endpoint: &str) -> ureq::Request {
+            self.authorize(self.agent.post(&format!("{}/{}", self.url, endpoint)))
+        }
+
+        fn authorize(&self, request: ureq::Request) -> ureq::Request {
+            match &self.token {
+                Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+                None => request,
+            }
+        }
+    }
+
+    fn read_json<T: for<'de> Deserialize<'de>>(response: Result<ureq::Response, ureq::Error>) -> MangekyouResult<T> {
+        response
+            .map_err(|e| MangekyouError::GeneralError(format!("Remote signer unavailable: {}", e)))?
+            .into_json()
+            .map_err(|e| MangekyouError::GeneralError(format!("Invalid remote signer response: {}", e)))
+    }
+
+    impl VrfSigner for RemoteSigner {
+        fn public_key(&self) -> ECVRFPublicKey {
+            self.public_key.clone()
+        }
+
+        fn prove(&self, alpha_string: &[u8]) -> MangekyouResult<ECVRFProof> {
+            let request = ProveRequest { alpha: alpha_string };
+            let response: ProveResponse = read_json(self.post("prove").send_json(request))?;
+            let proof = ECVRFProof::from_bytes(&response.proof).map_err(|_| MangekyouError::InvalidProof)?;
+            proof.verify(alpha_string, &self.public_key)?;
+            Ok(proof)
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ed25519 signatures as specified in [RFC 8032](https://www.rfc-editor.org/rfc/rfc8032), verified
+//! with the ZIP 215 rules of `ed25519-consensus` so that all implementations agree on validity.
+
+use super::{impl_encoding, impl_private_key};
+use crate::error::MangekyouError;
+use crate::traits::{
+    AllowedRng, Authenticator, EncodeDecodeBase64, KeyPair, Signer, SigningKey, ToFromBytes,
+    VerifyingKey,
+};
+use ed25519_consensus::{Signature, VerificationKey};
+use mangekyou_derive::SilentDebug;
+
+pub const PRIVATE_KEY_LENGTH: usize = 32;
+pub const PUBLIC_KEY_LENGTH: usize = 32;
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// An Ed25519 public key, which is a valid encoding of a curve point
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ed25519PublicKey([u8; PUBLIC_KEY_LENGTH]);
+
+/// An Ed25519 private key, the seed its signing key is expanded from
+#[derive(SilentDebug)]
+pub struct Ed25519PrivateKey([u8; PRIVATE_KEY_LENGTH]);
+
+#[derive(SilentDebug)]
+pub struct Ed25519KeyPair {
+    public: Ed25519PublicKey,
+    private: Ed25519PrivateKey,
+}
+
+/// An Ed25519 signature, R || s
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Ed25519Signature([u8; SIGNATURE_LENGTH]);
+
+impl ToFromBytes for Ed25519PublicKey {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; PUBLIC_KEY_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(PUBLIC_KEY_LENGTH))?;
+        VerificationKey::try_from(bytes).map_err(|_| MangekyouError::InvalidPublicKey)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl_encoding!(Ed25519PublicKey, PUBLIC_KEY_LENGTH);
+
+impl<'a> From<&'a Ed25519PrivateKey> for Ed25519PublicKey {
+    fn from(private: &'a Ed25519PrivateKey) -> Self {
+        Self(ed25519_consensus::SigningKey::from(private.0).verification_key().to_bytes())
+    }
+}
+
+impl VerifyingKey for Ed25519PublicKey {
+    type PrivKey = Ed25519PrivateKey;
+    type Sig = Ed25519Signature;
+    const LENGTH: usize = PUBLIC_KEY_LENGTH;
+
+    fn verify(&self, msg: &[u8], signature: &Ed25519Signature) -> Result<(), MangekyouError> {
+        // The encoding was validated when the key was parsed or derived
+        VerificationKey::try_from(self.0)
+            .map_err(|_| MangekyouError::InvalidPublicKey)?
+            .verify(&Signature::from(signature.0), msg)
+            .map_err(|_| MangekyouError::InvalidSignature)
+    }
+}
+
+impl_private_key!(Ed25519PrivateKey, Ed25519KeyPair, |_| true);
+
+impl SigningKey for Ed25519PrivateKey {
+    type PubKey = Ed25519PublicKey;
+    type Sig = Ed25519Signature;
+    const LENGTH: usize = PRIVATE_KEY_LENGTH;
+}
+
+impl ToFromBytes for Ed25519Signature {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; SIGNATURE_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(SIGNATURE_LENGTH))?;
+        Ok(Self(bytes))
+    }
+}
+
+impl_encoding!(Ed25519Signature, SIGNATURE_LENGTH);
+
+impl Authenticator for Ed25519Signature {
+    type PubKey = Ed25519PublicKey;
+    type PrivKey = Ed25519PrivateKey;
+    const LENGTH: usize = SIGNATURE_LENGTH;
+}
+
+impl From<Ed25519PrivateKey> for Ed25519KeyPair {
+    fn from(private: Ed25519PrivateKey) -> Self {
+        Self {
+            public: Ed25519PublicKey::from(&private),
+            private,
+        }
+    }
+}
+
+impl Signer<Ed25519Signature> for Ed25519KeyPair {
+    fn sign(&self, msg: &[u8]) -> Ed25519Signature {
+        Ed25519Signature(
+            ed25519_consensus::SigningKey::from(self.private.0)
+                .sign(msg)
+                .to_bytes(),
+        )
+    }
+}
+
+impl KeyPair for Ed25519KeyPair {
+    type PubKey = Ed25519PublicKey;
+    type PrivKey = Ed25519PrivateKey;
+    type Sig = Ed25519Signature;
+
+    fn public(&'_ self) -> &'_ Self::PubKey {
+        &self.public
+    }
+
+    fn private(self) -> Self::PrivKey {
+        self.private
+    }
+
+    #[cfg(feature = "copy_key")]
+    fn copy(&self) -> Self {
+        Self::from(Ed25519PrivateKey(self.private.0))
+    }
+
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        let mut private = [0u8; PRIVATE_KEY_LENGTH];
+        rng.fill_bytes(&mut private);
+        Self::from(Ed25519PrivateKey(private))
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Digital signatures implementing the traits of [crate::traits]: Ed25519, and
+//! ECDSA over secp256k1 and secp256r1 with recoverable variants. ECDSA signs
+//! the SHA-256 hash of the message by default, with nonces from RFC 6979 and
+//! low s, so signatures are deterministic.
+//!
+//! Keys and signatures are thin wrappers of their encodings, which are
+//! validated when parsed:
+//!
+//! | Scheme    | Private key | Public key            | Signature | Recoverable signature |
+//! |-----------|-------------|-----------------------|-----------|-----------------------|
+//! | ed25519   | 32          | 32                    | 64        |                       |
+//! | secp256k1 | 32          | 33, SEC1 compressed   | 64, r \|\| s | 65, r \|\| s \|\| v |
+//! | secp256r1 | 32          | 33, SEC1 compressed   | 64, r \|\| s | 65, r \|\| s \|\| v |
+//!
+//! # Example
+//! ```
+//! # use mangekyou::sigs::secp256k1::*;
+//! # use mangekyou::traits::{KeyPair, RecoverableSignature, RecoverableSigner, Signer, VerifyingKey};
+//! use rand::thread_rng;
+//! let kp = Secp256k1KeyPair::generate(&mut thread_rng());
+//! let signature = kp.sign(b"Hello, world!");
+//! assert!(kp.public().verify(b"Hello, world!", &signature).is_ok());
+//!
+//! let signature = kp.sign_recoverable(b"Hello, world!");
+//! assert_eq!(&signature.recover(b"Hello, world!").unwrap(), kp.public());
+//! ```
+
+pub mod ed25519;
+pub mod secp256k1;
+pub mod secp256r1;
+
+/// Implements [AsRef], [Debug](std::fmt::Debug) and [Display](std::fmt::Display)
+/// as Base64, and serde through [ToFromBytes](crate::traits::ToFromBytes), for
+/// a public key or signature wrapping its encoding of `$length` bytes
+macro_rules! impl_encoding {
+    ($type:ty, $length:expr) => {
+        impl AsRef<[u8]> for $type {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl std::fmt::Debug for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", <$crate::encoding::Base64 as $crate::encoding::Encoding>::encode(self.0))
+            }
+        }
+
+        impl std::fmt::Display for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", <$crate::encoding::Base64 as $crate::encoding::Encoding>::encode(self.0))
+            }
+        }
+
+        $crate::serialize_deserialize_with_to_from_bytes!($type, $length);
+    };
+}
+
+/// Implements the conversions of a private key `$type` wrapping its 32 byte
+/// encoding, which `$validate` checks, and of the key pair `$key_pair`, whose
+/// encoding is that of its private key
+macro_rules! impl_private_key {
+    ($type:ty, $key_pair:ty, $validate:expr) => {
+        impl AsRef<[u8]> for $type {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl ToFromBytes for $type {
+            fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+                let bytes: [u8; PRIVATE_KEY_LENGTH] = bytes
+                    .try_into()
+                    .map_err(|_| MangekyouError::InputLengthWrong(PRIVATE_KEY_LENGTH))?;
+                let validate: fn(&[u8; PRIVATE_KEY_LENGTH]) -> bool = $validate;
+                if !validate(&bytes) {
+                    return Err(MangekyouError::InvalidInput);
+                }
+                Ok(Self(bytes))
+            }
+        }
+
+        impl Drop for $type {
+            fn drop(&mut self) {
+                zeroize::Zeroize::zeroize(&mut self.0);
+            }
+        }
+
+        $crate::serialize_deserialize_with_to_from_bytes!($type, PRIVATE_KEY_LENGTH);
+
+        impl AsRef<[u8]> for $key_pair {
+            fn as_ref(&self) -> &[u8] {
+                self.private.as_ref()
+            }
+        }
+
+        /// The encoding of a key pair is that of its private key
+        impl ToFromBytes for $key_pair {
+            fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+                Ok(Self::from(<$type>::from_bytes(bytes)?))
+            }
+        }
+
+        impl std::str::FromStr for $key_pair {
+            type Err = MangekyouError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::decode_base64(s).map_err(|_| MangekyouError::InvalidInput)
+            }
+        }
+
+        $crate::serialize_deserialize_with_to_from_bytes!($key_pair, PRIVATE_KEY_LENGTH);
+    };
+}
+
+pub(crate) use {impl_encoding, impl_private_key};
@@ -0,0 +1,259 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! ECDSA over secp256k1 through libsecp256k1. Signatures are made and checked over the SHA-256
+//! digest of the message, or over that of another hash function with the `_with_hash` functions.
+
+use super::{impl_encoding, impl_private_key};
+use crate::error::MangekyouError;
+use crate::hash::{HashFunction, Sha256};
+use crate::traits::{
+    AllowedRng, Authenticator, EncodeDecodeBase64, KeyPair, RecoverableSignature,
+    RecoverableSigner, Signer, SigningKey, ToFromBytes, VerifyRecoverable, VerifyingKey,
+};
+use mangekyou_derive::SilentDebug;
+use rust_secp256k1::ecdsa::{RecoverableSignature as EcdsaRecoverableSignature, RecoveryId, Signature};
+use rust_secp256k1::{Message, PublicKey, SecretKey, SECP256K1};
+
+pub const PRIVATE_KEY_LENGTH: usize = 32;
+pub const PUBLIC_KEY_LENGTH: usize = 33;
+pub const SIGNATURE_LENGTH: usize = 64;
+pub const RECOVERABLE_SIGNATURE_LENGTH: usize = 65;
+
+/// A secp256k1 public key in compressed SEC1 encoding
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Secp256k1PublicKey([u8; PUBLIC_KEY_LENGTH]);
+
+/// A secp256k1 private key, a non-zero scalar
+#[derive(SilentDebug)]
+pub struct Secp256k1PrivateKey([u8; PRIVATE_KEY_LENGTH]);
+
+#[derive(SilentDebug)]
+pub struct Secp256k1KeyPair {
+    public: Secp256k1PublicKey,
+    private: Secp256k1PrivateKey,
+}
+
+/// An ECDSA signature r || s with low s
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Secp256k1Signature([u8; SIGNATURE_LENGTH]);
+
+/// An ECDSA signature r || s || v with low s and the recovery id v in {0, 1, 2, 3}
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Secp256k1RecoverableSignature([u8; RECOVERABLE_SIGNATURE_LENGTH]);
+
+fn message<H: HashFunction<32>>(msg: &[u8]) -> Message {
+    Message::from_slice(H::digest(msg).as_ref()).expect("Digests are 32 bytes")
+}
+
+impl Secp256k1PublicKey {
+    fn point(&self) -> PublicKey {
+        PublicKey::from_slice(&self.0).expect("Validated when the key was parsed or derived")
+    }
+
+    /// Verifies a signature over the digest of `msg` with `H`
+    pub fn verify_with_hash<H: HashFunction<32>>(
+        &self,
+        msg: &[u8],
+        signature: &Secp256k1Signature,
+    ) -> Result<(), MangekyouError> {
+        SECP256K1
+            .verify_ecdsa(&message::<H>(msg), &signature.inner(), &self.point())
+            .map_err(|_| MangekyouError::InvalidSignature)
+    }
+}
+
+impl ToFromBytes for Secp256k1PublicKey {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; PUBLIC_KEY_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(PUBLIC_KEY_LENGTH))?;
+        PublicKey::from_slice(&bytes).map_err(|_| MangekyouError::InvalidPublicKey)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl_encoding!(Secp256k1PublicKey, PUBLIC_KEY_LENGTH);
+
+impl<'a> From<&'a Secp256k1PrivateKey> for Secp256k1PublicKey {
+    fn from(private: &'a Secp256k1PrivateKey) -> Self {
+        Self(private.secret_key().public_key(SECP256K1).serialize())
+    }
+}
+
+impl VerifyingKey for Secp256k1PublicKey {
+    type PrivKey = Secp256k1PrivateKey;
+    type Sig = Secp256k1Signature;
+    const LENGTH: usize = PUBLIC_KEY_LENGTH;
+
+    fn verify(&self, msg: &[u8], signature: &Secp256k1Signature) -> Result<(), MangekyouError> {
+        self.verify_with_hash::<Sha256>(msg, signature)
+    }
+}
+
+impl VerifyRecoverable for Secp256k1PublicKey {
+    type Sig = Secp256k1RecoverableSignature;
+}
+
+impl Secp256k1PrivateKey {
+    fn secret_key(&self) -> SecretKey {
+        SecretKey::from_slice(&self.0).expect("Validated when the key was parsed or generated")
+    }
+}
+
+impl_private_key!(Secp256k1PrivateKey, Secp256k1KeyPair, |bytes| {
+    SecretKey::from_slice(bytes).is_ok()
+});
+
+impl SigningKey for Secp256k1PrivateKey {
+    type PubKey = Secp256k1PublicKey;
+    type Sig = Secp256k1Signature;
+    const LENGTH: usize = PRIVATE_KEY_LENGTH;
+}
+
+impl Secp256k1Signature {
+    fn inner(&self) -> Signature {
+        Signature::from_compact(&self.0).expect("Validated when the signature was parsed or made")
+    }
+}
+
+impl ToFromBytes for Secp256k1Signature {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; SIGNATURE_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(SIGNATURE_LENGTH))?;
+        Signature::from_compact(&bytes).map_err(|_| MangekyouError::InvalidSignature)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl_encoding!(Secp256k1Signature, SIGNATURE_LENGTH);
+
+impl Authenticator for Secp256k1Signature {
+    type PubKey = Secp256k1PublicKey;
+    type PrivKey = Secp256k1PrivateKey;
+    const LENGTH: usize = SIGNATURE_LENGTH;
+}
+
+impl From<&Secp256k1RecoverableSignature> for Secp256k1Signature {
+    fn from(signature: &Secp256k1RecoverableSignature) -> Self {
+        Self(signature.0[..SIGNATURE_LENGTH].try_into().unwrap())
+    }
+}
+
+impl Secp256k1RecoverableSignature {
+    fn inner(&self) -> EcdsaRecoverableSignature {
+        let recovery_id = RecoveryId::from_i32(self.0[SIGNATURE_LENGTH] as i32)
+            .expect("Validated when the signature was parsed or made");
+        EcdsaRecoverableSignature::from_compact(&self.0[..SIGNATURE_LENGTH], recovery_id)
+            .expect("Validated when the signature was parsed or made")
+    }
+}
+
+impl ToFromBytes for Secp256k1RecoverableSignature {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; RECOVERABLE_SIGNATURE_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(RECOVERABLE_SIGNATURE_LENGTH))?;
+        let recovery_id = RecoveryId::from_i32(bytes[SIGNATURE_LENGTH] as i32)
+            .map_err(|_| MangekyouError::InvalidSignature)?;
+        EcdsaRecoverableSignature::from_compact(&bytes[..SIGNATURE_LENGTH], recovery_id)
+            .map_err(|_| MangekyouError::InvalidSignature)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl_encoding!(Secp256k1RecoverableSignature, RECOVERABLE_SIGNATURE_LENGTH);
+
+impl RecoverableSignature for Secp256k1RecoverableSignature {
+    type PubKey = Secp256k1PublicKey;
+    type Signer = Secp256k1KeyPair;
+    type DefaultHash = Sha256;
+
+    fn recover_with_hash<H: HashFunction<32>>(
+        &self,
+        msg: &[u8],
+    ) -> Result<Secp256k1PublicKey, MangekyouError> {
+        SECP256K1
+            .recover_ecdsa(&message::<H>(msg), &self.inner())
+            .map(|public| Secp256k1PublicKey(public.serialize()))
+            .map_err(|_| MangekyouError::InvalidSignature)
+    }
+}
+
+/// Recovers the public key from a recoverable signature `signature` over `msg`, as
+/// [RecoverableSignature::recover] after parsing the signature
+pub fn recover(msg: &[u8], signature: &[u8]) -> Result<Secp256k1PublicKey, MangekyouError> {
+    Secp256k1RecoverableSignature::from_bytes(signature)?.recover(msg)
+}
+
+impl Secp256k1KeyPair {
+    /// Signs the digest of `msg` with `H`
+    pub fn sign_with_hash<H: HashFunction<32>>(&self, msg: &[u8]) -> Secp256k1Signature {
+        let signature = SECP256K1.sign_ecdsa(&message::<H>(msg), &self.private.secret_key());
+        Secp256k1Signature(signature.serialize_compact())
+    }
+}
+
+impl From<Secp256k1PrivateKey> for Secp256k1KeyPair {
+    fn from(private: Secp256k1PrivateKey) -> Self {
+        Self {
+            public: Secp256k1PublicKey::from(&private),
+            private,
+        }
+    }
+}
+
+impl Signer<Secp256k1Signature> for Secp256k1KeyPair {
+    fn sign(&self, msg: &[u8]) -> Secp256k1Signature {
+        self.sign_with_hash::<Sha256>(msg)
+    }
+}
+
+impl RecoverableSigner for Secp256k1KeyPair {
+    type PubKey = Secp256k1PublicKey;
+    type Sig = Secp256k1RecoverableSignature;
+
+    fn sign_recoverable_with_hash<H: HashFunction<32>>(
+        &self,
+        msg: &[u8],
+    ) -> Secp256k1RecoverableSignature {
+        let (recovery_id, signature) = SECP256K1
+            .sign_ecdsa_recoverable(&message::<H>(msg), &self.private.secret_key())
+            .serialize_compact();
+        let mut bytes = [0u8; RECOVERABLE_SIGNATURE_LENGTH];
+        bytes[..SIGNATURE_LENGTH].copy_from_slice(&signature);
+        bytes[SIGNATURE_LENGTH] = recovery_id.to_i32() as u8;
+        Secp256k1RecoverableSignature(bytes)
+    }
+}
+
+impl KeyPair for Secp256k1KeyPair {
+    type PubKey = Secp256k1PublicKey;
+    type PrivKey = Secp256k1PrivateKey;
+    type Sig = Secp256k1Signature;
+
+    fn public(&'_ self) -> &'_ Self::PubKey {
+        &self.public
+    }
+
+    fn private(self) -> Self::PrivKey {
+        self.private
+    }
+
+    #[cfg(feature = "copy_key")]
+    fn copy(&self) -> Self {
+        Self::from(Secp256k1PrivateKey(self.private.0))
+    }
+
+    /// Samples 32 bytes until they are a valid private key, as libsecp256k1 does
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        let mut private = [0u8; PRIVATE_KEY_LENGTH];
+        loop {
+            rng.fill_bytes(&mut private);
+            if SecretKey::from_slice(&private).is_ok() {
+                return Self::from(Secp256k1PrivateKey(private));
+            }
+        }
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! ECDSA over secp256r1 (NIST P-256), with recoverable signatures. Signatures are made and checked
+//! over the SHA-256 digest of the message, or over that of another hash function with the
+//! `_with_hash` functions.
+//!
+//! Recovery ids are 0 or 1, the parity of the y-coordinate of the nonce point. Ids 2 and 3, for
+//! nonce points with an x-coordinate of at least the group order, are never produced as this
+//! happens with probability about 2^-128.
+
+use super::{impl_encoding, impl_private_key};
+use crate::error::MangekyouError;
+use crate::hash::{HashFunction, Sha256};
+use crate::traits::{
+    AllowedRng, Authenticator, EncodeDecodeBase64, KeyPair, RecoverableSignature,
+    RecoverableSigner, Signer, SigningKey, ToFromBytes, VerifyRecoverable, VerifyingKey,
+};
+use mangekyou_derive::SilentDebug;
+use p256::elliptic_curve::bigint::ArrayEncoding;
+use p256::elliptic_curve::consts::U32;
+use p256::elliptic_curve::group::Group;
+use p256::elliptic_curve::ops::Reduce;
+use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use p256::elliptic_curve::subtle::Choice;
+use p256::elliptic_curve::{AffineXCoordinate, Curve, DecompressPoint, Field, IsHigh, PrimeField};
+use p256::{AffinePoint, EncodedPoint, FieldBytes, NistP256, ProjectivePoint, Scalar};
+
+pub const PRIVATE_KEY_LENGTH: usize = 32;
+pub const PUBLIC_KEY_LENGTH: usize = 33;
+pub const SIGNATURE_LENGTH: usize = 64;
+pub const RECOVERABLE_SIGNATURE_LENGTH: usize = 65;
+
+/// A secp256r1 public key in compressed SEC1 encoding
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Secp256r1PublicKey([u8; PUBLIC_KEY_LENGTH]);
+
+/// A secp256r1 private key, a non-zero scalar
+#[derive(SilentDebug)]
+pub struct Secp256r1PrivateKey([u8; PRIVATE_KEY_LENGTH]);
+
+#[derive(SilentDebug)]
+pub struct Secp256r1KeyPair {
+    public: Secp256r1PublicKey,
+    private: Secp256r1PrivateKey,
+}
+
+/// An ECDSA signature r || s with low s
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Secp256r1Signature([u8; SIGNATURE_LENGTH]);
+
+/// An ECDSA signature r || s || v with low s and the recovery id v in {0, 1}
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Secp256r1RecoverableSignature([u8; RECOVERABLE_SIGNATURE_LENGTH]);
+
+/// The non-zero scalar encoded by `bytes`, if any
+fn non_zero_scalar(bytes: &[u8]) -> Option<Scalar> {
+    Option::<Scalar>::from(Scalar::from_repr(*FieldBytes::from_slice(bytes)))
+        .filter(|scalar| !bool::from(scalar.is_zero()))
+}
+
+/// The digest of `msg` with `H` modulo the group order, as bits2octets of RFC 6979
+fn digest<H: HashFunction<32>>(msg: &[u8]) -> Scalar {
+    Scalar::from_be_bytes_reduced(FieldBytes::from(H::digest(msg).digest))
+}
+
+fn encode(point: ProjectivePoint) -> [u8; PUBLIC_KEY_LENGTH] {
+    point
+        .to_affine()
+        .to_encoded_point(true)
+        .as_bytes()
+        .try_into()
+        .expect("Compressed points are 33 bytes")
+}
+
+/// Parses r || s, with non-zero scalars and low s
+fn parse_signature(bytes: &[u8]) -> Result<(Scalar, Scalar), MangekyouError> {
+    let r = non_zero_scalar(&bytes[..32]).ok_or(MangekyouError::InvalidSignature)?;
+    let s = non_zero_scalar(&bytes[32..SIGNATURE_LENGTH]).ok_or(MangekyouError::InvalidSignature)?;
+    if bool::from(s.is_high()) {
+        return Err(MangekyouError::InvalidSignature);
+    }
+    Ok((r, s))
+}
+
+impl Secp256r1PublicKey {
+    fn point(&self) -> ProjectivePoint {
+        let point = EncodedPoint::from_bytes(self.0).expect("Validated when the key was parsed or derived");
+        Option::from(ProjectivePoint::from_encoded_point(&point)).expect("Validated when the key was parsed or derived")
+    }
+
+    /// Verifies a signature over the digest of `msg` with `H`
+    pub fn verify_with_hash<H: HashFunction<32>>(
+        &self,
+        msg: &[u8],
+        signature: &Secp256r1Signature,
+    ) -> Result<(), MangekyouError> {
+        let (r, s) = parse_signature(&signature.0)?;
+        let w = s.invert().unwrap();
+        let point = ProjectivePoint::GENERATOR * (digest::<H>(msg) * w) + self.point() * (r * w);
+        if bool::from(point.is_identity()) || Scalar::from_be_bytes_reduced(point.to_affine().x()) != r {
+            return Err(MangekyouError::InvalidSignature);
+        }
+        Ok(())
+    }
+}
+
+impl ToFromBytes for Secp256r1PublicKey {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; PUBLIC_KEY_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(PUBLIC_KEY_LENGTH))?;
+        let point = EncodedPoint::from_bytes(bytes).map_err(|_| MangekyouError::InvalidPublicKey)?;
+        Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&point))
+            .ok_or(MangekyouError::InvalidPublicKey)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl_encoding!(Secp256r1PublicKey, PUBLIC_KEY_LENGTH);
+
+impl<'a> From<&'a Secp256r1PrivateKey> for Secp256r1PublicKey {
+    fn from(private: &'a Secp256r1PrivateKey) -> Self {
+        Self(encode(ProjectivePoint::GENERATOR * private.scalar()))
+    }
+}
+
+impl VerifyingKey for Secp256r1PublicKey {
+    type PrivKey = Secp256r1PrivateKey;
+    type Sig = Secp256r1Signature;
+    const LENGTH: usize = PUBLIC_KEY_LENGTH;
+
+    fn verify(&self, msg: &[u8], signature: &Secp256r1Signature) -> Result<(), MangekyouError> {
+        self.verify_with_hash::<Sha256>(msg, signature)
+    }
+}
+
+impl VerifyRecoverable for Secp256r1PublicKey {
+    type Sig = Secp256r1RecoverableSignature;
+}
+
+impl Secp256r1PrivateKey {
+    fn scalar(&self) -> Scalar {
+        non_zero_scalar(&self.0).expect("Validated when the key was parsed or generated")
+    }
+}
+
+impl_private_key!(Secp256r1PrivateKey, Secp256r1KeyPair, |bytes| {
+    non_zero_scalar(bytes).is_some()
+});
+
+impl SigningKey for Secp256r1PrivateKey {
+    type PubKey = Secp256r1PublicKey;
+    type Sig = Secp256r1Signature;
+    const LENGTH: usize = PRIVATE_KEY_LENGTH;
+}
+
+impl ToFromBytes for Secp256r1Signature {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; SIGNATURE_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(SIGNATURE_LENGTH))?;
+        parse_signature(&bytes)?;
+        Ok(Self(bytes))
+    }
+}
+
+impl_encoding!(Secp256r1Signature, SIGNATURE_LENGTH);
+
+impl Authenticator for Secp256r1Signature {
+    type PubKey = Secp256r1PublicKey;
+    type PrivKey = Secp256r1PrivateKey;
+    const LENGTH: usize = SIGNATURE_LENGTH;
+}
+
+impl From<&Secp256r1RecoverableSignature> for Secp256r1Signature {
+    fn from(signature: &Secp256r1RecoverableSignature) -> Self {
+        Self(signature.0[..SIGNATURE_LENGTH].try_into().unwrap())
+    }
+}
+
+impl ToFromBytes for Secp256r1RecoverableSignature {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, MangekyouError> {
+        let bytes: [u8; RECOVERABLE_SIGNATURE_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| MangekyouError::InputLengthWrong(RECOVERABLE_SIGNATURE_LENGTH))?;
+        parse_signature(&bytes)?;
+        if bytes[SIGNATURE_LENGTH] > 1 {
+            return Err(MangekyouError::InvalidSignature);
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl_encoding!(Secp256r1RecoverableSignature, RECOVERABLE_SIGNATURE_LENGTH);
+
+impl RecoverableSignature for Secp256r1RecoverableSignature {
+    type PubKey = Secp256r1PublicKey;
+    type Signer = Secp256r1KeyPair;
+    type DefaultHash = Sha256;
+
+    /// Recovers Q = r^-1 (sR - zG), where R is the nonce point
+    fn recover_with_hash<H: HashFunction<32>>(
+        &self,
+        msg: &[u8],
+    ) -> Result<Secp256r1PublicKey, MangekyouError> {
+        let (r, s) = parse_signature(&self.0)?;
+        let y_is_odd = Choice::from(self.0[SIGNATURE_LENGTH]);
+        let nonce_point = Option::<AffinePoint>::from(AffinePoint::decompress(&r.to_bytes(), y_is_odd))
+            .ok_or(MangekyouError::InvalidSignature)?;
+        let public = (ProjectivePoint::from(nonce_point) * s - ProjectivePoint::GENERATOR * digest::<H>(msg))
+            * r.invert().unwrap();
+        if bool::from(public.is_identity()) {
+            return Err(MangekyouError::InvalidSignature);
+        }
+        Ok(Secp256r1PublicKey(encode(public)))
+    }
+}
+
+/// Recovers the public key from a recoverable signature `signature` over `msg`, as
+/// [RecoverableSignature::recover] after parsing the signature
+pub fn recover(msg: &[u8], signature: &[u8]) -> Result<Secp256r1PublicKey, MangekyouError> {
+    Secp256r1RecoverableSignature::from_bytes(signature)?.recover(msg)
+}
+
+impl Secp256r1KeyPair {
+    /// Signs the digest of `msg` with `H`
+    pub fn sign_with_hash<H: HashFunction<32>>(&self, msg: &[u8]) -> Secp256r1Signature {
+        Secp256r1Signature::from(&self.sign_recoverable_with_hash::<H>(msg))
+    }
+}
+
+impl From<Secp256r1PrivateKey> for Secp256r1KeyPair {
+    fn from(private: Secp256r1PrivateKey) -> Self {
+        Self {
+            public: Secp256r1PublicKey::from(&private),
+            private,
+        }
+    }
+}
+
+impl Signer<Secp256r1Signature> for Secp256r1KeyPair {
+    fn sign(&self, msg: &[u8]) -> Secp256r1Signature {
+        self.sign_with_hash::<Sha256>(msg)
+    }
+}
+
+impl RecoverableSigner for Secp256r1KeyPair {
+    type PubKey = Secp256r1PublicKey;
+    type Sig = Secp256r1RecoverableSignature;
+
+    /// Signs with the nonce of RFC 6979 with HMAC-SHA-256, whatever `H` is
+    fn sign_recoverable_with_hash<H: HashFunction<32>>(
+        &self,
+        msg: &[u8],
+    ) -> Secp256r1RecoverableSignature {
+        let x = self.private.scalar();
+        let z = digest::<H>(msg);
+        let k = rfc6979::generate_k::<sha2::Sha256, U32>(
+            &x.to_bytes(),
+            &NistP256::ORDER.to_be_byte_array(),
+            &z.to_bytes(),
+            &[],
+        );
+        let k = non_zero_scalar(&k).expect("RFC 6979 nonces are in [1, n)");
+
+        let nonce_point = (ProjectivePoint::GENERATOR * k).to_affine();
+        let r = Scalar::from_be_bytes_reduced(nonce_point.x());
+        let mut s = k.invert().unwrap() * (z + r * x);
+        let mut y_is_odd = nonce_point.to_encoded_point(true).as_bytes()[0] == 0x03;
+        if bool::from(s.is_high()) {
+            // -s is a signature with the nonce -k, whose point has the opposite y-coordinate
+            s = -s;
+            y_is_odd = !y_is_odd;
+        }
+
+        let mut bytes = [0u8; RECOVERABLE_SIGNATURE_LENGTH];
+        bytes[..32].copy_from_slice(&r.to_bytes());
+        bytes[32..SIGNATURE_LENGTH].copy_from_slice(&s.to_bytes());
+        bytes[SIGNATURE_LENGTH] = y_is_odd as u8;
+        Secp256r1RecoverableSignature(bytes)
+    }
+}
+
+impl KeyPair for Secp256r1KeyPair {
+    type PubKey = Secp256r1PublicKey;
+    type PrivKey = Secp256r1PrivateKey;
+    type Sig = Secp256r1Signature;
+
+    fn public(&'_ self) -> &'_ Self::PubKey {
+        &self.public
+    }
+
+    fn private(self) -> Self::PrivKey {
+        self.private
+    }
+
+    #[cfg(feature = "copy_key")]
+    fn copy(&self) -> Self {
+        Self::from(Secp256r1PrivateKey(self.private.0))
+    }
+
+    /// Samples 32 bytes until they are a valid private key
+    fn generate<R: AllowedRng>(rng: &mut R) -> Self {
+        let mut private = [0u8; PRIVATE_KEY_LENGTH];
+        loop {
+            rng.fill_bytes(&mut private);
+            if non_zero_scalar(&private).is_some() {
+                return Self::from(Secp256r1PrivateKey(private));
+            }
+        }
+    }
+}
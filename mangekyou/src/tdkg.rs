@@ -0,0 +1,474 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Distributed key generation of a Ristretto255 key shared by n oracle
+//! operators, any t of whom can use it while t - 1 learn nothing about it.
+//! This is the joint-Feldman DKG of Pedersen, as described in "Secure
+//! Distributed Key Generation for Discrete-Log Based Cryptosystems" by
+//! Gennaro et al.:
+//!
+//! 1. Every party deals a random polynomial of degree t - 1: it broadcasts
+//!    a [Dealing] of Feldman commitments to the coefficients, and sends every
+//!    party its [Share], the evaluation of the polynomial at its id, over a
+//!    private and authenticated channel the caller provides.
+//! 2. A party whose share from a dealer is missing or does not match the
+//!    dealing broadcasts a [Complaint].
+//! 3. The dealer answers a complaint by broadcasting the share. A dealer that
+//!    does not, or broadcasts an invalid share, is disqualified.
+//!
+//! The [Transcript] of the broadcast messages determines the qualified
+//! dealers, the shared public key, the sum of their constant terms, and the
+//! public key of every share, and anyone can recompute them from it. Each
+//! party's [KeyShare] is the sum of its shares from the qualified dealers.
+//!
+//! As Gennaro et al. point out, complaints let a rushing adversary bias the
+//! shared public key by disqualifying dealers it has seen, although the
+//! secret key stays out of its reach.
+
+use crate::error::{MangekyouError, MangekyouResult};
+use crate::groups::ristretto255::{RistrettoPoint, RistrettoScalar};
+use crate::groups::{GroupElement, Scalar};
+use crate::kamui_vrf::ecvrf::ECVRFPublicKey;
+use crate::serde_helpers::ToFromByteArray;
+use crate::traits::AllowedRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use zeroize::Zeroize;
+
+/// Id of a party, from 1 to the number of parties
+pub type PartyId = u16;
+
+/// Number of parties, and of shares needed to use the shared key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Parameters {
+    threshold: u16,
+    parties: u16,
+}
+
+impl Parameters {
+    /// Parameters of a DKG among parties 1 to `parties`, any `threshold` of
+    /// whom can use the shared key
+    pub fn new(threshold: u16, parties: u16) -> MangekyouResult<Self> {
+        if threshold == 0 || threshold > parties {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(Self { threshold, parties })
+    }
+
+    pub fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
+    pub fn parties(&self) -> u16 {
+        self.parties
+    }
+
+    fn check_id(&self, id: PartyId) -> MangekyouResult<()> {
+        if id == 0 || id > self.parties {
+            return Err(MangekyouError::InvalidInput);
+        }
+        Ok(())
+    }
+}
+
+/// Evaluates the polynomial with the given coefficients, constant term first,
+/// at `id`. Works on scalars as well as on commitments to them.
+fn evaluate<G: GroupElement<ScalarType = RistrettoScalar>>(coefficients: &[G], id: PartyId) -> G {
+    let x = RistrettoScalar::from(id as u128);
+    coefficients
+        .iter()
+        .rev()
+        .fold(G::zero(), |acc, coefficient| acc * x + coefficient)
+}
+
+/// Lagrange coefficient of `id` for interpolating at zero from the shares of
+/// `ids`, which must be distinct and include `id`
+pub fn lagrange_coefficient(id: PartyId, ids: &[PartyId]) -> MangekyouResult<RistrettoScalar> {
+    let mut sorted = ids.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    if sorted.len() != ids.len() || sorted.first() == Some(&0) || !ids.contains(&id) {
+        return Err(MangekyouError::InvalidInput);
+    }
+    let x = RistrettoScalar::from(id as u128);
+    let (numerator, denominator) = ids.iter().filter(|&&other| other != id).fold(
+        (RistrettoScalar::generator(), RistrettoScalar::generator()),
+        |(numerator, denominator), &other| {
+            let other = RistrettoScalar::from(other as u128);
+            (numerator * other, denominator * (other - x))
+        },
+    );
+    numerator / denominator
+}
+
+/// Feldman commitments to the coefficients of a dealer's polynomial,
+/// broadcast by the dealer
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Dealing {
+    pub dealer: PartyId,
+    pub commitments: Vec<RistrettoPoint>,
+}
+
+impl Dealing {
+    /// Checks that `share` is the evaluation of the dealer's polynomial at
+    /// the id of its receiver
+    pub fn verify_share(&self, share: &Share) -> MangekyouResult<()> {
+        if share.dealer != self.dealer || self.commitments.is_empty() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        if RistrettoPoint::generator() * share.value != evaluate(&self.commitments, share.receiver) {
+            return Err(MangekyouError::GeneralOpaqueError);
+        }
+        Ok(())
+    }
+}
+
+/// The evaluation of the polynomial of `dealer` at the id of `receiver`,
+/// sent privately, or broadcast in answer to a complaint
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Share {
+    pub dealer: PartyId,
+    pub receiver: PartyId,
+    pub value: RistrettoScalar,
+}
+
+/// Broadcast by `accuser` when its share from `dealer` is missing or invalid
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Complaint {
+    pub accuser: PartyId,
+    pub dealer: PartyId,
+}
+
+/// A party of the DKG, both dealer and receiver of shares
+pub struct Party {
+    parameters: Parameters,
+    id: PartyId,
+    polynomial: Vec<RistrettoScalar>,
+    shares: BTreeMap<PartyId, RistrettoScalar>,
+}
+
+impl Party {
+    /// Party number `id`, with a fresh random polynomial to deal
+    pub fn new<R: AllowedRng>(parameters: Parameters, id: PartyId, rng: &mut R) -> MangekyouResult<Self> {
+        parameters.check_id(id)?;
+        let polynomial = (0..parameters.threshold)
+            .map(|_| RistrettoScalar::rand(rng))
+            .collect();
+        Ok(Self {
+            parameters,
+            id,
+            polynomial,
+            shares: BTreeMap::new(),
+        })
+    }
+
+    pub fn id(&self) -> PartyId {
+        self.id
+    }
+
+    /// The dealing to broadcast
+    pub fn dealing(&self) -> Dealing {
+        Dealing {
+            dealer: self.id,
+            commitments: self
+                .polynomial
+                .iter()
+                .map(|coefficient| RistrettoPoint::generator() * coefficient)
+                .collect(),
+        }
+    }
+
+    /// The share to send privately to `receiver`
+    pub fn share(&self, receiver: PartyId) -> MangekyouResult<Share> {
+        self.parameters.check_id(receiver)?;
+        Ok(Share {
+            dealer: self.id,
+            receiver,
+            value: evaluate(&self.polynomial, receiver),
+        })
+    }
+
+    /// Processes the dealing of a dealer and the share it sent this party,
+    /// if any. Returns the complaint to broadcast if the share is missing or
+    /// does not match the dealing.
+    pub fn receive(&mut self, dealing: &Dealing, share: Option<&Share>) -> Option<Complaint> {
+        match share {
+            Some(share)
+                if share.receiver == self.id
+                    && dealing.commitments.len() == self.parameters.threshold as usize
+                    && dealing.verify_share(share).is_ok() =>
+            {
+                self.shares.insert(dealing.dealer, share.value);
+                None
+            }
+            _ => Some(Complaint {
+                accuser: self.id,
+                dealer: dealing.dealer,
+            }),
+        }
+    }
+
+    /// The share to broadcast in answer to `complaint`, if it is against this
+    /// party
+    pub fn respond(&self, complaint: &Complaint) -> Option<Share> {
+        if complaint.dealer != self.id {
+            return None;
+        }
+        self.share(complaint.accuser).ok()
+    }
+
+    /// Computes this party's share of the shared key from the final
+    /// transcript, taking the shares of dealers it complained about from
+    /// their answers
+    pub fn finalize(&self, transcript: &Transcript) -> MangekyouResult<KeyShare> {
+        if transcript.parameters != self.parameters {
+            return Err(MangekyouError::InvalidInput);
+        }
+        let output = transcript.output()?;
+        let share = output.qualified.iter().try_fold(RistrettoScalar::zero(), |sum, &dealer| {
+            transcript
+                .response(dealer, self.id)
+                .map(|response| response.value)
+                .or_else(|| self.shares.get(&dealer).copied())
+                .map(|value| sum + value)
+                .ok_or(MangekyouError::InvalidInput)
+        })?;
+        if RistrettoPoint::generator() * share != output.share_public_key(self.id) {
+            return Err(MangekyouError::GeneralOpaqueError);
+        }
+        Ok(KeyShare {
+            id: self.id,
+            share,
+            output,
+        })
+    }
+}
+
+impl Drop for Party {
+    fn drop(&mut self) {
+        self.polynomial.zeroize();
+        self.shares.values_mut().for_each(Zeroize::zeroize);
+    }
+}
+
+/// The broadcast messages of a DKG. Messages are checked for consistency
+/// when they are added, and again when a transcript is deserialized.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "TranscriptMessages")]
+pub struct Transcript {
+    parameters: Parameters,
+    dealings: Vec<Dealing>,
+    complaints: Vec<Complaint>,
+    responses: Vec<Share>,
+}
+
+/// A deserialized transcript, before its checks
+#[derive(Deserialize)]
+struct TranscriptMessages {
+    parameters: Parameters,
+    dealings: Vec<Dealing>,
+    complaints: Vec<Complaint>,
+    responses: Vec<Share>,
+}
+
+impl TryFrom<TranscriptMessages> for Transcript {
+    type Error = MangekyouError;
+
+    fn try_from(messages: TranscriptMessages) -> MangekyouResult<Self> {
+        let parameters = Parameters::new(messages.parameters.threshold, messages.parameters.parties)?;
+        let mut transcript = Transcript::new(parameters);
+        messages
+            .dealings
+            .into_iter()
+            .try_for_each(|dealing| transcript.add_dealing(dealing))?;
+        messages
+            .complaints
+            .into_iter()
+            .try_for_each(|complaint| transcript.add_complaint(complaint))?;
+        messages
+            .responses
+            .into_iter()
+            .try_for_each(|response| transcript.add_response(response))?;
+        Ok(transcript)
+    }
+}
+
+impl Transcript {
+    pub fn new(parameters: Parameters) -> Self {
+        Self {
+            parameters,
+            dealings: Vec::new(),
+            complaints: Vec::new(),
+            responses: Vec::new(),
+        }
+    }
+
+    /// Adds the dealing of a dealer that has none yet
+    pub fn add_dealing(&mut self, dealing: Dealing) -> MangekyouResult<()> {
+        self.parameters.check_id(dealing.dealer)?;
+        if dealing.commitments.len() != self.parameters.threshold as usize
+            || self.dealing(dealing.dealer).is_some()
+        {
+            return Err(MangekyouError::InvalidInput);
+        }
+        self.dealings.push(dealing);
+        Ok(())
+    }
+
+    /// Adds a new complaint against a dealer with a dealing
+    pub fn add_complaint(&mut self, complaint: Complaint) -> MangekyouResult<()> {
+        self.parameters.check_id(complaint.accuser)?;
+        if complaint.accuser == complaint.dealer
+            || self.dealing(complaint.dealer).is_none()
+            || self.complaints.contains(&complaint)
+        {
+            return Err(MangekyouError::InvalidInput);
+        }
+        self.complaints.push(complaint);
+        Ok(())
+    }
+
+    /// Adds the first answer to a complaint. Invalid answers are accepted,
+    /// and disqualify their dealer.
+    pub fn add_response(&mut self, response: Share) -> MangekyouResult<()> {
+        let complaint = Complaint {
+            accuser: response.receiver,
+            dealer: response.dealer,
+        };
+        if !self.complaints.contains(&complaint) || self.response(response.dealer, response.receiver).is_some() {
+            return Err(MangekyouError::InvalidInput);
+        }
+        self.responses.push(response);
+        Ok(())
+    }
+
+    pub fn parameters(&self) -> Parameters {
+        self.parameters
+    }
+
+    pub fn dealings(&self) -> &[Dealing] {
+        &self.dealings
+    }
+
+    pub fn complaints(&self) -> &[Complaint] {
+        &self.complaints
+    }
+
+    pub fn responses(&self) -> &[Share] {
+        &self.responses
+    }
+
+    fn dealing(&self, dealer: PartyId) -> Option<&Dealing> {
+        self.dealings.iter().find(|dealing| dealing.dealer == dealer)
+    }
+
+    fn response(&self, dealer: PartyId, receiver: PartyId) -> Option<&Share> {
+        self.responses
+            .iter()
+            .find(|response| response.dealer == dealer && response.receiver == receiver)
+    }
+
+    /// The dealers that answered every complaint against them with a valid
+    /// share, in increasing order
+    pub fn qualified(&self) -> Vec<PartyId> {
+        let mut qualified: Vec<PartyId> = self
+            .dealings
+            .iter()
+            .filter(|dealing| {
+                self.complaints
+                    .iter()
+                    .filter(|complaint| complaint.dealer == dealing.dealer)
+                    .all(|complaint| {
+                        self.response(complaint.dealer, complaint.accuser)
+                            .is_some_and(|response| dealing.verify_share(response).is_ok())
+                    })
+            })
+            .map(|dealing| dealing.dealer)
+            .collect();
+        qualified.sort_unstable();
+        qualified
+    }
+
+    /// The public output of the DKG. Fails with
+    /// [MangekyouError::NotEnoughInputs] if fewer than threshold dealers
+    /// qualified, as the shared key is then not safe from them.
+    pub fn output(&self) -> MangekyouResult<PublicOutput> {
+        let qualified = self.qualified();
+        if qualified.len() < self.parameters.threshold as usize {
+            return Err(MangekyouError::NotEnoughInputs);
+        }
+        let commitments = qualified
+            .iter()
+            .filter_map(|&dealer| self.dealing(dealer))
+            .fold(
+                vec![RistrettoPoint::zero(); self.parameters.threshold as usize],
+                |sum, dealing| {
+                    sum.iter()
+                        .zip(&dealing.commitments)
+                        .map(|(sum, commitment)| *sum + commitment)
+                        .collect()
+                },
+            );
+        Ok(PublicOutput {
+            qualified,
+            commitments,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Transcripts serialize")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> MangekyouResult<Self> {
+        bincode::deserialize(bytes).map_err(|_| MangekyouError::InvalidInput)
+    }
+}
+
+/// The public result of a DKG: the qualified dealers and the commitments to
+/// the coefficients of the shared polynomial, from which follow the shared
+/// public key and the public key of every share
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PublicOutput {
+    pub qualified: Vec<PartyId>,
+    pub commitments: Vec<RistrettoPoint>,
+}
+
+impl PublicOutput {
+    /// The shared public key
+    pub fn public_key(&self) -> RistrettoPoint {
+        self.commitments[0]
+    }
+
+    /// The shared public key as an ECVRF public key. Fails with
+    /// [MangekyouError::InvalidInput] if it is the identity, which dealers
+    /// choosing their dealings after the others could force.
+    pub fn vrf_public_key(&self) -> MangekyouResult<ECVRFPublicKey> {
+        ECVRFPublicKey::from_byte_array(&self.public_key().compress())
+    }
+
+    /// The public key of the share of `id`
+    pub fn share_public_key(&self, id: PartyId) -> RistrettoPoint {
+        evaluate(&self.commitments, id)
+    }
+}
+
+/// A party's share of the shared secret key, with the public output of the DKG
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyShare {
+    pub id: PartyId,
+    pub(crate) share: RistrettoScalar,
+    pub output: PublicOutput,
+}
+
+impl KeyShare {
+    /// The secret share
+    pub fn share(&self) -> &RistrettoScalar {
+        &self.share
+    }
+}
+
+impl Drop for KeyShare {
+    fn drop(&mut self) {
+        self.share.zeroize();
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::bls_vrf::{
+    aggregate_proofs, aggregate_public_keys, BlsVRFKeyPair, BlsVRFPrivateKey, BlsVRFProof,
+    BlsVRFPublicKey, PROOF_LENGTH, PUBLIC_KEY_LENGTH,
+};
+use crate::error::MangekyouError;
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use rand::thread_rng;
+
+/// Key derived from the key material [7; 32], and its proof and output for
+/// "Hi Kamui!"
+const SECRET_KEY: &str = "23c205e368093188a73311a45658e3d30e00741019b0eff05277ba2fd42bc422";
+const PUBLIC_KEY: &str = "8038bfe033bc328ea36bb7c3438bc5a27a0dc880506277e116c8b842ed0c1ea78d32c90b04afbca59bd828c1e6c5e3f319274412f2e9eecf7334114b02847693e9d997f1aa9f936d90cae8946df6593033431513e210880bcda015da1b61f6f5";
+const PROOF: &str = "87a68602c09469a61b82317e0abe790ba9a72ad0dfcea85c9e6bbf8a0b3cdaad8d060ca42cec850908de22ddce4019fb";
+const OUTPUT: &str = "abad7edf2b03214e862766d914bf2f8e22cc9d5154ae9704e4e1a58f087d7e74863094be2c45a6b7cde95185f698cd9efa23a8f1ed961fc43f55759e392f2c39";
+
+fn keypair(seed: u8) -> BlsVRFKeyPair {
+    BlsVRFKeyPair::from(BlsVRFPrivateKey::from_ikm(&[seed; 32]).unwrap())
+}
+
+#[test]
+fn test_vector() {
+    let kp = keypair(7);
+    assert_eq!(hex::encode(kp.sk.to_bytes()), SECRET_KEY);
+    assert_eq!(hex::encode(kp.pk.to_bytes()), PUBLIC_KEY);
+
+    let (output, proof) = kp.output(b"Hi Kamui!");
+    assert_eq!(hex::encode(proof.to_bytes()), PROOF);
+    assert_eq!(hex::encode(output), OUTPUT);
+
+    let public_key = BlsVRFPublicKey::from_bytes(&hex::decode(PUBLIC_KEY).unwrap()).unwrap();
+    let proof = BlsVRFProof::from_bytes(&hex::decode(PROOF).unwrap()).unwrap();
+    assert!(proof.verify_output(b"Hi Kamui!", &public_key, &output).is_ok());
+}
+
+#[test]
+fn test_prove_and_verify() {
+    let kp = BlsVRFKeyPair::generate(&mut thread_rng());
+    let other = BlsVRFKeyPair::generate(&mut thread_rng());
+    let proof = kp.prove(b"alpha");
+
+    assert!(proof.verify(b"alpha", &kp.pk).is_ok());
+    assert_eq!(proof.verify(b"alpha", &other.pk), Err(MangekyouError::InvalidProof));
+    assert!(proof.verify(b"another alpha", &kp.pk).is_err());
+    // Proofs are unique, so outputs are too
+    assert_eq!(kp.prove(b"alpha"), proof);
+
+    // A proof of possession is not a proof for the public key as alpha
+    let possession = kp.prove_possession();
+    assert!(kp.pk.verify_possession(&possession).is_ok());
+    assert!(other.pk.verify_possession(&possession).is_err());
+    assert!(possession.verify(&kp.pk.to_bytes(), &kp.pk).is_err());
+    assert!(kp.pk.verify_possession(&kp.prove(&kp.pk.to_bytes())).is_err());
+}
+
+#[test]
+fn test_aggregation() {
+    let keypairs: Vec<BlsVRFKeyPair> = (1..=4).map(keypair).collect();
+    let public_keys: Vec<BlsVRFPublicKey> = keypairs.iter().map(|kp| kp.pk).collect();
+    let proofs: Vec<BlsVRFProof> = keypairs.iter().map(|kp| kp.prove(b"round 1")).collect();
+
+    let public_key = aggregate_public_keys(&public_keys).unwrap();
+    let proof = aggregate_proofs(&proofs).unwrap();
+    assert!(proof.verify(b"round 1", &public_key).is_ok());
+    assert!(proofs.iter().all(|partial| partial.to_hash() != proof.to_hash()));
+
+    // Aggregation does not depend on the order of the proofs
+    let reversed: Vec<BlsVRFProof> = proofs.iter().rev().copied().collect();
+    assert_eq!(aggregate_proofs(&reversed).unwrap(), proof);
+
+    // Missing or foreign proofs do not verify under the aggregate key
+    assert!(aggregate_proofs(&proofs[1..]).unwrap().verify(b"round 1", &public_key).is_err());
+    let mut foreign = proofs.clone();
+    foreign[0] = keypairs[0].prove(b"round 2");
+    assert!(aggregate_proofs(&foreign).unwrap().verify(b"round 1", &public_key).is_err());
+
+    assert_eq!(aggregate_proofs(&[]), Err(MangekyouError::InvalidInput));
+    assert!(aggregate_public_keys(&[]).is_err());
+}
+
+#[test]
+fn test_invalid_encodings_rejected() {
+    let kp = keypair(7);
+    let proof = kp.prove(b"alpha").to_bytes();
+
+    assert_eq!(
+        BlsVRFProof::from_bytes(&proof[1..]),
+        Err(MangekyouError::InputLengthWrong(PROOF_LENGTH))
+    );
+    assert_eq!(
+        BlsVRFPublicKey::from_bytes(&kp.pk.to_bytes()[1..]),
+        Err(MangekyouError::InputLengthWrong(PUBLIC_KEY_LENGTH))
+    );
+
+    // The compressed identities of G1 and G2
+    let mut identity = [0u8; PUBLIC_KEY_LENGTH];
+    identity[0] = 0xc0;
+    assert_eq!(
+        BlsVRFProof::from_bytes(&identity[..PROOF_LENGTH]),
+        Err(MangekyouError::InvalidProofEncoding)
+    );
+    assert_eq!(BlsVRFPublicKey::from_bytes(&identity), Err(MangekyouError::InvalidPublicKey));
+
+    // A secret key of zero
+    assert!(BlsVRFPrivateKey::from_bytes(&[0u8; 32]).is_err());
+}
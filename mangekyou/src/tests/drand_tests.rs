@@ -0,0 +1,37 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::drand::{randomness, round_message, DrandBeacon, DST};
+use crate::error::MangekyouError;
+use blst::min_sig::SecretKey;
+
+fn beacon_key() -> SecretKey {
+    SecretKey::key_gen(&[42u8; 32], &[]).unwrap()
+}
+
+fn sign(key: &SecretKey, round: u64) -> Vec<u8> {
+    key.sign(&round_message(round), DST, &[]).compress().to_vec()
+}
+
+#[test]
+fn test_verify_round() {
+    let key = beacon_key();
+    let beacon = DrandBeacon::new(&key.sk_to_pk().compress()).unwrap();
+
+    let signature = sign(&key, 1000);
+    assert_eq!(beacon.verify(1000, &signature), Ok(randomness(&signature)));
+
+    // Signature of another round, by another key, or malformed
+    assert_eq!(beacon.verify(1001, &signature), Err(MangekyouError::InvalidSignature));
+    let other = SecretKey::key_gen(&[43u8; 32], &[]).unwrap();
+    assert_eq!(beacon.verify(1000, &sign(&other, 1000)), Err(MangekyouError::InvalidSignature));
+    assert_eq!(beacon.verify(1000, &[0xff; 48]), Err(MangekyouError::InvalidSignature));
+    assert_eq!(beacon.verify(1000, &signature[..47]), Err(MangekyouError::InputLengthWrong(48)));
+}
+
+#[test]
+fn test_quicknet_public_key() {
+    DrandBeacon::quicknet();
+    assert!(DrandBeacon::new(&[0u8; 96]).is_err());
+    assert!(DrandBeacon::new(&[0u8; 48]).is_err());
+}
@@ -0,0 +1,106 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ecvrf_edwards25519::{prove, public_key, verify, ECVRFEdwards25519Proof, PROOF_LENGTH};
+use crate::error::MangekyouError;
+use hex_literal::hex;
+
+/// Key pairs of appendix B.3 of RFC 9381: secret key and public key
+const KEYS: [([u8; 32], [u8; 32]); 3] = [
+    (
+        hex!("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60"),
+        hex!("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"),
+    ),
+    (
+        hex!("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb"),
+        hex!("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c"),
+    ),
+    (
+        hex!("c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7"),
+        hex!("fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025"),
+    ),
+];
+
+/// Example 16 of appendix B.3 of RFC 9381, under the first key: proof and
+/// output for the empty alpha
+const PROOF: [u8; PROOF_LENGTH] = hex!("8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f26f8a57ccaed74ee1b190bed1f479d9727d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805");
+const OUTPUT: [u8; 64] = hex!("90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae");
+
+#[test]
+fn test_rfc9381_vectors() {
+    let (secret_key, pk) = KEYS[0];
+    assert_eq!(prove(&secret_key, &[]).unwrap().to_bytes(), PROOF);
+    assert_eq!(verify(&PROOF, &[], &pk), Ok(OUTPUT));
+
+    for (secret_key, pk) in KEYS {
+        assert_eq!(public_key(&secret_key), pk);
+        for alpha in [&b""[..], &[0x72], &[0xaf, 0x82]] {
+            let proof = prove(&secret_key, alpha).unwrap();
+            assert_eq!(verify(&proof.to_bytes(), alpha, &pk), Ok(proof.to_hash()));
+        }
+    }
+}
+
+#[test]
+fn test_invalid_proofs_rejected() {
+    let (secret_key, pk) = KEYS[1];
+    let alpha = &[0x72];
+    let proof = prove(&secret_key, alpha).unwrap().to_bytes();
+
+    assert_eq!(
+        verify(&proof, b"another alpha", &pk),
+        Err(MangekyouError::ChallengeMismatch)
+    );
+    assert_eq!(
+        verify(&proof, alpha, &KEYS[0].1),
+        Err(MangekyouError::ChallengeMismatch)
+    );
+
+    // Every flipped bit of the proof breaks it, either its decoding or its check
+    for index in 0..PROOF_LENGTH {
+        for bit in 0..8 {
+            let mut mutated = proof;
+            mutated[index] ^= 1 << bit;
+            assert!(verify(&mutated, alpha, &pk).is_err());
+        }
+    }
+
+    // s not reduced modulo the group order
+    let mut unreduced = proof;
+    unreduced[48..].copy_from_slice(&[0xff; 32]);
+    assert_eq!(
+        ECVRFEdwards25519Proof::from_bytes(&unreduced),
+        Err(MangekyouError::InvalidScalar)
+    );
+
+    assert_eq!(
+        verify(&proof[..PROOF_LENGTH - 1], alpha, &pk),
+        Err(MangekyouError::InputLengthWrong(PROOF_LENGTH))
+    );
+    assert_eq!(
+        verify(&proof, alpha, &pk[..31]),
+        Err(MangekyouError::InputLengthWrong(32))
+    );
+}
+
+#[test]
+fn test_invalid_public_keys_rejected() {
+    let proof = ECVRFEdwards25519Proof::from_bytes(&PROOF).unwrap();
+
+    // The identity, and a point of order 4, are of small order
+    let mut identity = [0u8; 32];
+    identity[0] = 1;
+    let mut order_four = [0u8; 32];
+    order_four[31] = 0x80;
+    // y = p, a non-canonical encoding of y = 0
+    let mut y_is_p = [0xff; 32];
+    y_is_p[0] = 0xed;
+    y_is_p[31] = 0x7f;
+    // The identity with the sign bit of its zero x set
+    let mut negative_zero = identity;
+    negative_zero[31] |= 0x80;
+
+    for public_key in [identity, order_four, y_is_p, negative_zero] {
+        assert_eq!(proof.verify(&[], &public_key), Err(MangekyouError::InvalidPublicKey));
+    }
+}
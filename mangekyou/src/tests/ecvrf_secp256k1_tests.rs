@@ -0,0 +1,168 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::ecvrf_secp256k1::{
+    ECVRFSecp256k1KeyPair, ECVRFSecp256k1PrivateKey, ECVRFSecp256k1Proof, ECVRFSecp256k1PublicKey,
+    CHALLENGE_LENGTH, PROOF_LENGTH, PUBLIC_KEY_LENGTH, SUITE_STRING,
+};
+use crate::error::MangekyouError;
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use hex_literal::hex;
+use k256::elliptic_curve::ff::PrimeField;
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::{AffinePoint, EncodedPoint, ProjectivePoint, Scalar};
+use rand::thread_rng;
+use sha2::{Digest, Sha256};
+
+/// Secret key, alpha, public key, proof and output. RFC 9381 has no secp256k1
+/// vectors; these pin the encoding, and [verify_with_k256] checks them with
+/// independent curve arithmetic.
+const VECTORS: [(&str, &str, &str, &str, &str); 3] = [
+    (
+        "0000000000000000000000000000000000000000000000000000000000000001",
+        "",
+        "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+        "024192220588c4ef502f5d2ab75552edfbe0256cebb0424efb9c4c58f438c3dcb43740e701a78589f13a3577908db37b1ddb55edaf0706552da59a41b69be3740878407cf6d13675cd94802a33b5e629f7",
+        "6bf7eda22a89f87fb8c8e17fa111727ca02d0a23db29fdcbe7ac84280e8bde24",
+    ),
+    (
+        "4242424242424242424242424242424242424242424242424242424242424242",
+        "73616d706c65",
+        "0324653eac434488002cc06bbfb7f10fe18991e35f9fe4302dbea6d2353dc0ab1c",
+        "025a290c16e28bf145f161ac474b2f28138cd9a8da9147db826b42b04a2123b893237983c50b22ede23f490bdd230ba55f461be204789de294a45c47008d4ce7b7baf9efa164bfb979e0b3bf3854d64ffc",
+        "d4aa5d159061923ee4d2cfa8e72718724c07c2bcfbb581d3028434d2bad0f0ad",
+    ),
+    (
+        "c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9c9",
+        "4869204b616d756921",
+        "026b3274f76e3c6b6bcaaacf3415608b1b75f0e7173d505e2c77f0c8981b1ac43c",
+        "0236fc6604bff9fef656a49ae1a162b7b852c6a3ce1fd482dff6edb17efd02abc7350241cf9af6932b004519af4f77583924de83744eb8517249eb71b81f51eeb243dce0efb283842c234eade54e11ae73",
+        "e965de4eb861ba42f363e974b18db4e40ed3d24df7403c3ac445044373a1e882",
+    ),
+];
+
+/// The group order n, big-endian
+const ORDER: [u8; 32] = hex!("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141");
+
+fn point(bytes: &[u8]) -> ProjectivePoint {
+    AffinePoint::from_encoded_point(&EncodedPoint::from_bytes(bytes).unwrap())
+        .unwrap()
+        .into()
+}
+
+fn encode(point: &ProjectivePoint) -> Vec<u8> {
+    point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+}
+
+fn scalar(bytes: &[u8]) -> Scalar {
+    let mut repr = [0u8; 32];
+    repr[32 - bytes.len()..].copy_from_slice(bytes);
+    Scalar::from_repr(repr.into()).unwrap()
+}
+
+/// Section 5.3 of RFC 9381 over k256 rather than libsecp256k1
+fn verify_with_k256(public_key: &[u8], alpha: &[u8], proof: &[u8]) -> bool {
+    let h = (0..=u8::MAX)
+        .find_map(|counter| {
+            let hash = Sha256::new()
+                .chain_update([SUITE_STRING, 0x01])
+                .chain_update(public_key)
+                .chain_update(alpha)
+                .chain_update([counter, 0x00])
+                .finalize();
+            let candidate = EncodedPoint::from_bytes([&[0x02], &hash[..]].concat()).unwrap();
+            Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&candidate))
+        })
+        .map(ProjectivePoint::from)
+        .unwrap();
+    let (gamma, rest) = proof.split_at(PUBLIC_KEY_LENGTH);
+    let (c, s) = rest.split_at(CHALLENGE_LENGTH);
+    let (y, gamma, c, s) = (point(public_key), point(gamma), scalar(c), scalar(s));
+
+    let u = ProjectivePoint::GENERATOR * s - y * c;
+    let v = h * s - gamma * c;
+    let mut hasher = Sha256::new().chain_update([SUITE_STRING, 0x02]);
+    for point in [y, h, gamma, u, v] {
+        hasher.update(encode(&point));
+    }
+    hasher.update([0x00]);
+    hasher.finalize()[..CHALLENGE_LENGTH] == proof[PUBLIC_KEY_LENGTH..][..CHALLENGE_LENGTH]
+}
+
+fn keypair(secret_key: &[u8]) -> ECVRFSecp256k1KeyPair {
+    ECVRFSecp256k1KeyPair::from(ECVRFSecp256k1PrivateKey::from_bytes(secret_key).unwrap())
+}
+
+#[test]
+fn test_vectors() {
+    for (secret_key, alpha, public_key, proof, output) in VECTORS {
+        let [secret_key, alpha, public_key, proof, output] =
+            [secret_key, alpha, public_key, proof, output].map(|s| hex::decode(s).unwrap());
+        let kp = keypair(&secret_key);
+        assert_eq!(kp.pk.to_bytes().to_vec(), public_key);
+
+        let (hash, computed) = kp.output(&alpha);
+        assert_eq!(computed.to_bytes(), proof);
+        assert_eq!(hash.to_vec(), output);
+
+        let decoded = ECVRFSecp256k1Proof::from_bytes(&proof).unwrap();
+        assert_eq!(decoded, computed);
+        let public_key = ECVRFSecp256k1PublicKey::from_bytes(&public_key).unwrap();
+        assert!(decoded.verify_output(&alpha, &public_key, &hash).is_ok());
+        assert!(verify_with_k256(&public_key.to_bytes(), &alpha, &proof));
+    }
+}
+
+#[test]
+fn test_prove_and_verify() {
+    let kp = ECVRFSecp256k1KeyPair::generate(&mut thread_rng());
+    let other = ECVRFSecp256k1KeyPair::generate(&mut thread_rng());
+    for alpha in [&b""[..], b"a", &[0xff; 100]] {
+        let proof = kp.prove(alpha);
+        assert!(proof.verify(alpha, &kp.pk).is_ok());
+        assert!(verify_with_k256(&kp.pk.to_bytes(), alpha, &proof.to_bytes()));
+
+        assert_eq!(proof.verify(alpha, &other.pk), Err(MangekyouError::ChallengeMismatch));
+        assert!(proof.verify(b"another alpha", &kp.pk).is_err());
+        // Proofs are deterministic
+        assert_eq!(kp.prove(alpha), proof);
+    }
+}
+
+#[test]
+fn test_invalid_proofs_rejected() {
+    let kp = keypair(&[0x42; 32]);
+    let alpha = b"sample";
+    let proof: [u8; PROOF_LENGTH] = kp.prove(alpha).to_bytes().try_into().unwrap();
+
+    // Every flipped bit of the proof breaks it, either its decoding or its check
+    for index in 0..PROOF_LENGTH {
+        for bit in 0..8 {
+            let mut mutated = proof;
+            mutated[index] ^= 1 << bit;
+            let rejected = ECVRFSecp256k1Proof::from_bytes(&mutated)
+                .map_or(true, |mutated| mutated.verify(alpha, &kp.pk).is_err());
+            assert!(rejected);
+        }
+    }
+
+    // s of zero, or not reduced modulo n
+    for s in [[0u8; 32], ORDER] {
+        let mut invalid = proof;
+        invalid[PUBLIC_KEY_LENGTH + CHALLENGE_LENGTH..].copy_from_slice(&s);
+        assert_eq!(ECVRFSecp256k1Proof::from_bytes(&invalid), Err(MangekyouError::InvalidScalar));
+    }
+
+    assert_eq!(
+        ECVRFSecp256k1Proof::from_bytes(&proof[..PROOF_LENGTH - 1]),
+        Err(MangekyouError::InputLengthWrong(PROOF_LENGTH))
+    );
+    assert_eq!(
+        ECVRFSecp256k1PublicKey::from_bytes(&kp.pk.to_bytes()[1..]),
+        Err(MangekyouError::InputLengthWrong(PUBLIC_KEY_LENGTH))
+    );
+    assert_eq!(
+        ECVRFSecp256k1PrivateKey::from_bytes(&ORDER),
+        Err(MangekyouError::InvalidInput)
+    );
+}
@@ -3,7 +3,7 @@
 
 use crate::encoding::{Base64, Encoding};
 use crate::hash::{
-    Blake2b256, Digest, EllipticCurveMultisetHash, HashFunction, Keccak256, MultisetHash, Sha256,
+    Blake2b256, Blake3, Digest, EllipticCurveMultisetHash, HashFunction, Keccak256, MultisetHash, Sha256,
     Sha3_256, Sha3_512, Sha512,
 };
 use std::io::Write;
@@ -138,6 +138,25 @@ fn test_blake2b_256() {
     );
 }
 
+#[test]
+fn test_blake3() {
+    let data =
+        hex::decode("301d56460954541aab6dd7ddc0dd08f8cb3ebd884784a0e797905107533cae62").unwrap();
+    let digest = Blake3::digest(&data);
+    assert_eq!(digest.as_ref(), blake3::hash(&data).as_bytes());
+
+    // Test vector of the empty input from the BLAKE3 specification
+    assert_eq!(
+        Blake3::digest(b"").as_ref(),
+        hex::decode("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262").unwrap()
+    );
+
+    let mut hasher = Blake3::new();
+    hasher.update(&data[..7]);
+    hasher.update(&data[7..]);
+    assert_eq!(hasher.finalize(), digest);
+}
+
 #[test]
 fn test_accumulator() {
     let mut accumulator = EllipticCurveMultisetHash::default();
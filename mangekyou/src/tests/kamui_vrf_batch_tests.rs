@@ -0,0 +1,107 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::kamui_vrf::ecvrf::{
+    verify_batch, ECVRFBatchableProof, ECVRFKeyPair, ECVRFPrivateKey, ECVRFPublicKey,
+    BATCHABLE_PROOF_LENGTH,
+};
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use curve25519_dalek::constants::BASEPOINT_ORDER;
+
+fn keypair(seed: u8) -> ECVRFKeyPair {
+    ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[seed; 32]).unwrap())
+}
+
+fn public_key(kp: &ECVRFKeyPair) -> ECVRFPublicKey {
+    ECVRFPublicKey::from_bytes(kp.pk.as_ref()).unwrap()
+}
+
+/// Proofs of distinct alphas under distinct keys
+fn batch(n: u8) -> Vec<(Vec<u8>, ECVRFBatchableProof, ECVRFPublicKey)> {
+    (1..=n)
+        .map(|seed| {
+            let kp = keypair(seed);
+            let alpha = vec![seed; seed as usize];
+            let proof = kp.prove_batchable(&alpha);
+            (alpha, proof, public_key(&kp))
+        })
+        .collect()
+}
+
+fn items(
+    batch: &[(Vec<u8>, ECVRFBatchableProof, ECVRFPublicKey)],
+) -> Vec<(&[u8], &ECVRFBatchableProof, &ECVRFPublicKey)> {
+    batch.iter().map(|(alpha, proof, pk)| (&alpha[..], proof, pk)).collect()
+}
+
+#[test]
+fn test_valid_batch_verifies() {
+    let batch = batch(8);
+    assert_eq!(verify_batch(&items(&batch)), Ok(vec![true; 8]));
+    assert_eq!(verify_batch(&items(&batch[..1])), Ok(vec![true]));
+    assert_eq!(verify_batch(&[]), Ok(vec![]));
+}
+
+#[test]
+fn test_batchable_proof_matches_proof() {
+    let kp = keypair(1);
+    let alpha = b"Hello, world!";
+    let batchable = kp.prove_batchable(alpha);
+    assert_eq!(
+        ECVRFBatchableProof::from_bytes(&batchable.to_bytes()).unwrap(),
+        batchable
+    );
+
+    let proof = batchable.to_proof(alpha, &public_key(&kp));
+    assert_eq!(proof.to_bytes(), kp.prove(alpha).to_bytes());
+    assert!(proof.verify(alpha, &public_key(&kp)).is_ok());
+    assert_eq!(batchable.to_hash(), proof.to_hash());
+
+    assert!(ECVRFBatchableProof::from_bytes(&[0u8; BATCHABLE_PROOF_LENGTH - 1]).is_err());
+}
+
+#[test]
+fn test_invalid_proof_singled_out() {
+    let mut batch = batch(6);
+    let mut expected = vec![true; 6];
+
+    // A proof of another alpha
+    batch[1].0.push(0);
+    expected[1] = false;
+
+    // Proofs with a component replaced by that of another proof
+    for (index, component) in [(3, 0..32), (4, 64..96)] {
+        let mut bytes = batch[index].1.to_bytes();
+        bytes[component.clone()].copy_from_slice(&batch[0].1.to_bytes()[component]);
+        batch[index].1 = ECVRFBatchableProof::from_bytes(&bytes).unwrap();
+        expected[index] = false;
+    }
+
+    assert_eq!(verify_batch(&items(&batch)), Ok(expected.clone()));
+    for (i, (alpha, proof, pk)) in batch.iter().enumerate() {
+        assert_eq!(proof.to_proof(alpha, pk).verify(alpha, pk).is_ok(), expected[i]);
+    }
+}
+
+#[test]
+fn test_undecodable_proof_rejected() {
+    let mut batch = batch(3);
+
+    // U not a point
+    let mut bytes = batch[0].1.to_bytes();
+    bytes[32..64].copy_from_slice(&[0xff; 32]);
+    batch[0].1 = ECVRFBatchableProof::from_bytes(&bytes).unwrap();
+
+    // s plus the group order, which encodes the same scalar non-canonically
+    let mut bytes = batch[2].1.to_bytes();
+    let mut carry = 0u16;
+    for (i, byte) in bytes[96..].iter_mut().enumerate() {
+        let digit = *byte as u16 + BASEPOINT_ORDER.as_bytes()[i] as u16 + carry;
+        *byte = digit as u8;
+        carry = digit >> 8;
+    }
+    assert_eq!(carry, 0);
+    batch[2].1 = ECVRFBatchableProof::from_bytes(&bytes).unwrap();
+
+    assert_eq!(verify_batch(&items(&batch)), Ok(vec![false, true, false]));
+}
@@ -0,0 +1,177 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::MangekyouError;
+use crate::kamui_vrf::ecvrf::{
+    Challenge, ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey, VRFOutput,
+    CHALLENGE_LENGTH, ENVELOPE_LENGTH, ENVELOPE_SUITE, ENVELOPE_VERSION, KEY_LENGTH,
+    KEY_PAIR_LENGTH, OUTPUT_LENGTH, PROOF_LENGTH,
+};
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use crate::serde_helpers::ToFromByteArray;
+use crate::test_helpers::verify_serialization;
+use rand::thread_rng;
+
+#[test]
+fn test_byte_lengths() {
+    assert_eq!(ECVRFPublicKey::BYTE_LENGTH, 32);
+    assert_eq!(ECVRFPrivateKey::BYTE_LENGTH, 32);
+    assert_eq!(ECVRFKeyPair::BYTE_LENGTH, 64);
+    assert_eq!(Challenge::BYTE_LENGTH, 16);
+    assert_eq!(ECVRFProof::BYTE_LENGTH, 80);
+    assert_eq!(VRFOutput::BYTE_LENGTH, OUTPUT_LENGTH);
+}
+
+#[test]
+fn test_round_trip() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let input = b"Hello, world!";
+    let (output, proof) = kp.output(input);
+
+    let pk_bytes = kp.pk.to_byte_array();
+    assert_eq!(pk_bytes.as_slice(), kp.pk.as_ref());
+    let pk = ECVRFPublicKey::from_byte_array(&pk_bytes).unwrap();
+
+    let sk_bytes = kp.sk.to_byte_array();
+    assert_eq!(sk_bytes.as_slice(), kp.sk.as_ref());
+    let sk = ECVRFPrivateKey::from_byte_array(&sk_bytes).unwrap();
+    assert_eq!(sk.to_byte_array(), sk_bytes);
+
+    let kp_bytes = kp.to_byte_array();
+    assert_eq!(kp_bytes[..KEY_LENGTH], pk_bytes);
+    assert_eq!(kp_bytes[KEY_LENGTH..], sk_bytes);
+    let kp2 = ECVRFKeyPair::from_byte_array(&kp_bytes).unwrap();
+    assert_eq!(kp2.to_byte_array(), kp_bytes);
+
+    let proof_bytes = proof.to_byte_array();
+    assert_eq!(proof_bytes.to_vec(), proof.to_bytes_legacy());
+    let proof = ECVRFProof::from_byte_array(&proof_bytes).unwrap();
+    assert!(proof.verify_output(input, &pk, &output).is_ok());
+
+    let challenge_bytes: [u8; CHALLENGE_LENGTH] = proof_bytes[32..48].try_into().unwrap();
+    let challenge = Challenge::from_byte_array(&challenge_bytes).unwrap();
+    assert_eq!(challenge.to_byte_array(), challenge_bytes);
+}
+
+#[test]
+fn test_invalid_encodings_rejected() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let proof_bytes = kp.prove(b"Hello, world!").to_byte_array();
+
+    // Public keys must be valid, non-identity points
+    assert!(ECVRFPublicKey::from_byte_array(&[0u8; KEY_LENGTH]).is_err());
+    assert!(ECVRFPublicKey::from_byte_array(&[0xff; KEY_LENGTH]).is_err());
+
+    // Private keys must be canonical scalars
+    assert!(ECVRFPrivateKey::from_byte_array(&[0xff; KEY_LENGTH]).is_err());
+
+    // The public key of a key pair must match its private key
+    let mut kp_bytes = kp.to_byte_array();
+    let other = ECVRFKeyPair::generate(&mut thread_rng());
+    kp_bytes[..KEY_LENGTH].copy_from_slice(other.pk.as_ref());
+    assert!(ECVRFKeyPair::from_byte_array(&kp_bytes).is_err());
+    assert!(ECVRFKeyPair::from_byte_array(&[0xff; KEY_PAIR_LENGTH]).is_err());
+
+    // Gamma must be a valid point and s a canonical scalar
+    let mut invalid_gamma = proof_bytes;
+    invalid_gamma[..32].copy_from_slice(&[0xff; 32]);
+    assert!(ECVRFProof::from_byte_array(&invalid_gamma).is_err());
+    assert!(ECVRFProof::from_bytes_legacy(&invalid_gamma).is_ok());
+
+    let mut invalid_s = proof_bytes;
+    invalid_s[48..].copy_from_slice(&[0xff; 32]);
+    assert!(ECVRFProof::from_byte_array(&invalid_s).is_err());
+    assert!(ECVRFProof::from_bytes_legacy(&invalid_s).is_ok());
+}
+
+#[test]
+fn test_string_encodings() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let (output, proof) = kp.output(b"Hello, world!");
+    let output = VRFOutput::from(output);
+
+    let pk = kp.pk.to_string();
+    assert_eq!(pk, hex::encode(kp.pk.as_ref()));
+    assert_eq!(pk.parse::<ECVRFPublicKey>().unwrap(), kp.pk);
+    assert_eq!(format!("0x{}", pk).parse::<ECVRFPublicKey>().unwrap(), kp.pk);
+
+    let sk = kp.sk.to_string();
+    assert_eq!(sk, hex::encode(kp.sk.as_ref()));
+    assert_eq!(sk.parse::<ECVRFPrivateKey>().unwrap(), kp.sk);
+
+    assert_eq!(proof.to_string(), hex::encode(proof.to_bytes_legacy()));
+    assert_eq!(proof.to_string().parse::<ECVRFProof>().unwrap(), proof);
+
+    assert_eq!(output.to_string(), hex::encode(output.0));
+    assert_eq!(output.to_string().parse::<VRFOutput>().unwrap(), output);
+
+    // Wrong lengths, invalid hex, and invalid keys and proofs
+    assert_eq!(pk[2..].parse::<ECVRFPublicKey>(), Err(MangekyouError::InvalidInput));
+    assert_eq!(format!("{}00", pk).parse::<ECVRFPublicKey>(), Err(MangekyouError::InvalidInput));
+    assert_eq!("zz".repeat(32).parse::<ECVRFPublicKey>(), Err(MangekyouError::InvalidInput));
+    assert_eq!("ff".repeat(32).parse::<ECVRFPublicKey>(), Err(MangekyouError::InvalidPublicKey));
+    assert_eq!("ff".repeat(32).parse::<ECVRFPrivateKey>(), Err(MangekyouError::InvalidInput));
+    assert_eq!("ff".repeat(80).parse::<ECVRFProof>(), Err(MangekyouError::InvalidProofEncoding));
+}
+
+#[test]
+fn test_serde() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let (output, proof) = kp.output(b"Hello, world!");
+    let output = VRFOutput::from(output);
+
+    // Raw bytes in binary formats
+    verify_serialization(&kp.pk, Some(kp.pk.as_ref()));
+    verify_serialization(&kp.sk, Some(kp.sk.as_ref()));
+    verify_serialization(&proof, Some(&proof.to_bytes_legacy()));
+    verify_serialization(&output, Some(&output.0));
+
+    // Hex strings in human readable formats
+    let json = serde_json::to_string(&(&kp.pk, &proof, &output)).unwrap();
+    assert_eq!(json, format!(r#"["{}","{}","{}"]"#, kp.pk, proof, output));
+    let (pk, proof2, output2): (ECVRFPublicKey, ECVRFProof, VRFOutput) =
+        serde_json::from_str(&json).unwrap();
+    assert_eq!((pk, proof2, output2), (kp.pk.clone(), proof, output));
+    assert_eq!(
+        serde_json::from_str::<ECVRFPrivateKey>(&serde_json::to_string(&kp.sk).unwrap()).unwrap(),
+        kp.sk
+    );
+
+    assert!(serde_json::from_str::<ECVRFPublicKey>(&format!(r#""{}""#, "ff".repeat(32))).is_err());
+    assert!(bincode::deserialize::<ECVRFPublicKey>(&[0xff; KEY_LENGTH]).is_err());
+}
+
+#[test]
+fn test_proof_envelope() {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let proof = kp.prove(b"Hello, world!");
+
+    let bytes = proof.to_bytes();
+    assert_eq!(bytes.len(), ENVELOPE_LENGTH);
+    assert_eq!(bytes[..2], [ENVELOPE_SUITE, ENVELOPE_VERSION]);
+    assert_eq!(bytes[2..], proof.to_bytes_legacy());
+    assert_eq!(ECVRFProof::from_bytes(&bytes).unwrap(), proof);
+
+    let legacy = proof.to_bytes_legacy();
+    assert_eq!(legacy.len(), PROOF_LENGTH);
+    assert_eq!(ECVRFProof::from_bytes_legacy(&legacy).unwrap(), proof);
+
+    // Neither parser takes the other's layout
+    assert!(ECVRFProof::from_bytes(&legacy).is_err());
+    assert!(ECVRFProof::from_bytes_legacy(&bytes).is_err());
+
+    // Unknown suites and versions are refused rather than misparsed
+    for (index, byte) in [(0, 0x00), (0, 0x02), (1, 0x00), (1, 0x02)] {
+        let mut other = bytes.clone();
+        other[index] = byte;
+        assert!(ECVRFProof::from_bytes(&other).is_err());
+    }
+
+    // As are invalid points and non-canonical scalars
+    let mut invalid_gamma = bytes.clone();
+    invalid_gamma[2..34].copy_from_slice(&[0xff; 32]);
+    assert!(ECVRFProof::from_bytes(&invalid_gamma).is_err());
+    let mut invalid_s = bytes.clone();
+    invalid_s[2 + 48..].copy_from_slice(&[0xff; 32]);
+    assert!(ECVRFProof::from_bytes(&invalid_s).is_err());
+}
@@ -0,0 +1,109 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compute unit benchmarks of on-chain ECVRF verification. Verification runs
+//! with [MeteredOps], which computes like [SolanaSyscallOps] and charges each
+//! operation what the Solana runtime charges for the syscall behind it. Work
+//! the program does itself is not counted here; kamui-program's
+//! `compute_units_test` meters the whole verification in the SBF build.
+
+use crate::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey};
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use crate::ristretto_ops::{DalekOps, RistrettoOps, SolanaSyscallOps};
+use std::cell::Cell;
+
+/// Syscall costs from the Solana runtime's default compute budget.
+const VALIDATE_POINT_CU: u64 = 169;
+const MULTIPLY_CU: u64 = 2_208;
+const MSM_BASE_CU: u64 = 2_303;
+const MSM_INCREMENTAL_CU: u64 = 788;
+
+thread_local! {
+    static COMPUTE_UNITS: Cell<u64> = Cell::new(0);
+    static MULTIPLICATIONS: Cell<u64> = Cell::new(0);
+    static VALIDATIONS: Cell<u64> = Cell::new(0);
+}
+
+fn charge(counter: &'static std::thread::LocalKey<Cell<u64>>, compute_units: u64) {
+    counter.with(|count| count.set(count.get() + 1));
+    COMPUTE_UNITS.with(|total| total.set(total.get() + compute_units));
+}
+
+/// Returns the compute units, multiplications and point validations charged
+/// while running `f`.
+fn metered(f: impl FnOnce()) -> (u64, u64, u64) {
+    for counter in [&COMPUTE_UNITS, &MULTIPLICATIONS, &VALIDATIONS] {
+        counter.with(|count| count.set(0));
+    }
+    f();
+    (
+        COMPUTE_UNITS.with(Cell::get),
+        MULTIPLICATIONS.with(Cell::get),
+        VALIDATIONS.with(Cell::get),
+    )
+}
+
+struct MeteredOps;
+
+impl RistrettoOps for MeteredOps {
+    fn multiply(scalar: &[u8; 32], point: &[u8; 32]) -> Option<[u8; 32]> {
+        charge(&MULTIPLICATIONS, MULTIPLY_CU);
+        SolanaSyscallOps::multiply(scalar, point)
+    }
+
+    fn multiscalar_multiply<const N: usize>(
+        scalars: &[[u8; 32]; N],
+        points: &[[u8; 32]; N],
+    ) -> Option<[u8; 32]> {
+        charge(&MULTIPLICATIONS, MSM_BASE_CU + MSM_INCREMENTAL_CU * (N as u64 - 1));
+        SolanaSyscallOps::multiscalar_multiply(scalars, points)
+    }
+
+    fn is_valid_point(point: &[u8; 32]) -> bool {
+        charge(&VALIDATIONS, VALIDATE_POINT_CU);
+        SolanaSyscallOps::is_valid_point(point)
+    }
+}
+
+fn keypair(seed: u8) -> ECVRFKeyPair {
+    ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[seed; 32]).unwrap())
+}
+
+#[test]
+fn test_verify_compute_units() {
+    let kp = keypair(1);
+    let pk = ECVRFPublicKey::from_bytes(kp.pk.as_ref()).unwrap();
+
+    for seed in 0..16u8 {
+        let alpha = [seed; 32];
+        let proof = kp.prove(&alpha);
+        let (compute_units, multiplications, validations) =
+            metered(|| proof.verify_with::<MeteredOps>(&alpha, &pk).unwrap());
+
+        // Validating the public key takes one point validation, and computing
+        // U and V two two-point multiscalar multiplications. encode_to_curve
+        // maps to the curve in the program, so it makes no syscall, and takes
+        // the same work for every alpha.
+        assert_eq!((multiplications, validations), (2, 1));
+        assert_eq!(compute_units, VALIDATE_POINT_CU + 2 * (MSM_BASE_CU + MSM_INCREMENTAL_CU));
+    }
+}
+
+#[test]
+fn test_metered_verify_agrees() {
+    let kp = keypair(2);
+    let pk = ECVRFPublicKey::from_bytes(kp.pk.as_ref()).unwrap();
+    let other = ECVRFPublicKey::from_bytes(keypair(3).pk.as_ref()).unwrap();
+
+    for seed in 0..8u8 {
+        let alpha = [seed; 8];
+        let proof = ECVRFProof::from_bytes(&kp.prove(&alpha).to_bytes()).unwrap();
+        assert!(proof.verify(&alpha, &pk).is_ok());
+        assert!(proof.verify_with::<MeteredOps>(&alpha, &pk).is_ok());
+        assert!(proof.verify_with::<DalekOps>(&alpha, &pk).is_ok());
+
+        assert!(proof.verify(&alpha, &other).is_err());
+        assert!(proof.verify_with::<MeteredOps>(&alpha, &other).is_err());
+        assert!(proof.verify_with::<DalekOps>(&alpha, &other).is_err());
+    }
+}
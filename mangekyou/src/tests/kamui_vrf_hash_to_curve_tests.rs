@@ -0,0 +1,123 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::kamui_vrf::ecvrf::{expand_message_xmd, hash_to_ristretto255, ECVRFKeyPair, ECVRFPrivateKey};
+use crate::kamui_vrf::VRFKeyPair;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use hex_literal::hex;
+use sha2::{Digest, Sha512};
+
+/// Domain separation tag of the expand_message_xmd vectors of appendix K.4 of RFC 9380
+const XMD_DST: &[u8] = b"QUUX-V01-CS02-with-expander-SHA512-256";
+
+/// Messages of the vectors of appendix K.4 of RFC 9380
+fn xmd_messages() -> [Vec<u8>; 5] {
+    [
+        b"".to_vec(),
+        b"abc".to_vec(),
+        b"abcdef0123456789".to_vec(),
+        [&b"q128_"[..], &[b'q'; 128]].concat(),
+        [&b"a512_"[..], &[b'a'; 512]].concat(),
+    ]
+}
+
+#[test]
+fn test_expand_message_xmd_rfc9380_vectors() {
+    let short: [[u8; 0x20]; 5] = [
+        hex!("6b9a7312411d92f921c6f68ca0b6380730a1a4d982c507211a90964c394179ba"),
+        hex!("0da749f12fbe5483eb066a5f595055679b976e93abe9be6f0f6318bce7aca8dc"),
+        hex!("087e45a86e2939ee8b91100af1583c4938e0f5fc6c9db4b107b83346bc967f58"),
+        hex!("7336234ee9983902440f6bc35b348352013becd88938d2afec44311caf8356b3"),
+        hex!("57b5f7e766d5be68a6bfe1768e3c2b7f1228b3e4b3134956dd73a59b954c66f4"),
+    ];
+    let long: [[u8; 0x80]; 5] = [
+        hex!("41b037d1734a5f8df225dd8c7de38f851efdb45c372887be655212d07251b921b052b62eaed99b46f72f2ef4cc96bfaf254ebbbec091e1a3b9e4fb5e5b619d2e0c5414800a1d882b62bb5cd1778f098b8eb6cb399d5d9d18f5d5842cf5d13d7eb00a7cff859b605da678b318bd0e65ebff70bec88c753b159a805d2c89c55961"),
+        hex!("7f1dddd13c08b543f2e2037b14cefb255b44c83cc397c1786d975653e36a6b11bdd7732d8b38adb4a0edc26a0cef4bb45217135456e58fbca1703cd6032cb1347ee720b87972d63fbf232587043ed2901bce7f22610c0419751c065922b488431851041310ad659e4b23520e1772ab29dcdeb2002222a363f0c2b1c972b3efe1"),
+        hex!("3f721f208e6199fe903545abc26c837ce59ac6fa45733f1baaf0222f8b7acb0424814fcb5eecf6c1d38f06e9d0a6ccfbf85ae612ab8735dfdf9ce84c372a77c8f9e1c1e952c3a61b7567dd0693016af51d2745822663d0c2367e3f4f0bed827feecc2aaf98c949b5ed0d35c3f1023d64ad1407924288d366ea159f46287e61ac"),
+        hex!("b799b045a58c8d2b4334cf54b78260b45eec544f9f2fb5bd12fb603eaee70db7317bf807c406e26373922b7b8920fa29142703dd52bdf280084fb7ef69da78afdf80b3586395b433dc66cde048a258e476a561e9deba7060af40adf30c64249ca7ddea79806ee5beb9a1422949471d267b21bc88e688e4014087a0b592b695ed"),
+        hex!("05b0bfef265dcee87654372777b7c44177e2ae4c13a27f103340d9cd11c86cb2426ffcad5bd964080c2aee97f03be1ca18e30a1f14e27bc11ebbd650f305269cc9fb1db08bf90bfc79b42a952b46daf810359e7bc36452684784a64952c343c52e5124cd1f71d474d5197fefc571a92929c9084ffe1112cf5eea5192ebff330b"),
+    ];
+
+    for ((msg, short), long) in xmd_messages().iter().zip(short).zip(long) {
+        assert_eq!(expand_message_xmd::<0x20>(&[msg], XMD_DST), short);
+        assert_eq!(expand_message_xmd::<0x80>(&[msg], XMD_DST), long);
+
+        // The message may be given in parts
+        let (head, tail) = msg.split_at(msg.len() / 2);
+        assert_eq!(expand_message_xmd::<0x80>(&[head, tail], XMD_DST), long);
+    }
+}
+
+#[test]
+fn test_one_way_map_rfc9496_vectors() {
+    // Appendix A.3 of RFC 9496: the inputs are the SHA-512 digests of these labels
+    let vectors: [(&str, [u8; 32]); 7] = [
+        (
+            "Ristretto is traditionally a short shot of espresso coffee",
+            hex!("3066f82a1a747d45120d1740f14358531a8f04bbffe6a819f86dfe50f44a0a46"),
+        ),
+        (
+            "made with the normal amount of ground coffee but extracted with",
+            hex!("f26e5b6f7d362d2d2a94c5d0e7602cb4773c95a2e5c31a64f133189fa76ed61b"),
+        ),
+        (
+            "about half the amount of water in the same amount of time",
+            hex!("006ccd2a9e6867e6a2c5cea83d3302cc9de128dd2a9a57dd8ee7b9d7ffe02826"),
+        ),
+        (
+            "by using a finer grind.",
+            hex!("f8f0c87cf237953c5890aec3998169005dae3eca1fbb04548c635953c817f92a"),
+        ),
+        (
+            "This produces a concentrated shot of coffee per volume.",
+            hex!("ae81e7dedf20a497e10c304a765c1767a42d6e06029758d2d7e8ef7cc4c41179"),
+        ),
+        (
+            "Just pulling a normal shot short will produce a weaker shot",
+            hex!("e2705652ff9f5e44d3e841bf1c251cf7dddb77d140870d1ab2ed64f1a9ce8628"),
+        ),
+        (
+            "and is not a Ristretto as some believe.",
+            hex!("80bd07262511cdde4863f8a7434cef696750681cb9510eea557088f76d9e5065"),
+        ),
+    ];
+
+    for (label, output) in vectors {
+        let input: [u8; 64] = Sha512::digest(label).into();
+        assert_eq!(RistrettoPoint::from_uniform_bytes(&input).compress().to_bytes(), output);
+    }
+}
+
+#[test]
+fn test_hash_to_ristretto255_composes_xmd_and_one_way_map() {
+    for msg in xmd_messages() {
+        let uniform_bytes = expand_message_xmd::<64>(&[&msg], XMD_DST);
+        assert_eq!(
+            hash_to_ristretto255(&[&msg], XMD_DST),
+            RistrettoPoint::from_uniform_bytes(&uniform_bytes).compress().to_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_proofs_use_public_key_salted_hash_to_curve() {
+    let kp = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[1; 32]).unwrap());
+    let other = ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[2; 32]).unwrap());
+
+    // Gamma is the secret scalar times the hash of pk || alpha
+    let alpha = b"alpha";
+    let h = hash_to_ristretto255(
+        &[kp.pk.as_ref(), alpha],
+        b"ECVRF_ristretto255_XMD:SHA-512_R255MAP_RO_sol_vrf",
+    );
+    let h = CompressedRistretto(h).decompress().unwrap();
+    let sk = Scalar::from_bytes_mod_order([1; 32]);
+    assert_eq!(kp.prove(alpha).to_bytes_legacy()[..32], (h * sk).compress().to_bytes());
+
+    // The same alpha hashes to another point under another key
+    assert_ne!(
+        hash_to_ristretto255(&[kp.pk.as_ref(), alpha], b"dst"),
+        hash_to_ristretto255(&[other.pk.as_ref(), alpha], b"dst")
+    );
+}
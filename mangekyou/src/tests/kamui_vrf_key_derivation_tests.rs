@@ -0,0 +1,71 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFProof, ECVRFPublicKey};
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use crate::serde_helpers::ToFromByteArray;
+use hex_literal::hex;
+use std::collections::HashSet;
+
+const SEED: [u8; 32] = [7; 32];
+
+/// Secret and public key derived from [SEED]
+const SEED_KEYS: ([u8; 32], [u8; 32]) = (
+    hex!("5ba172a1fb80acef4f4b2c5944c57df50152666092d26d17fe4e97f962d6e506"),
+    hex!("0c31efd6cfc2f7fffcf638408063e94906a044ecc256678ffa63a16c5378612d"),
+);
+
+/// Index, secret key and public key of children of the key pair of [SEED]
+const CHILD_KEYS: [(u32, [u8; 32], [u8; 32]); 3] = [
+    (
+        0,
+        hex!("7e1e877b4d3dda5d0b7d2b6b5d3907c34246f4283d601e419cfb20f9f4f6fa08"),
+        hex!("065cf988d40f8c6316f82b77de46762cb178cfb1ab4e0ed05adbd4b2c0e76b64"),
+    ),
+    (
+        1,
+        hex!("ab567a565051e73135f06990c898555c05e903b16b8624357bd8600bbc604701"),
+        hex!("60102296c3935d7c81045a349918f9d8a53f90f332fe4e8ae1efc8ecd8ab3605"),
+    ),
+    (
+        u32::MAX,
+        hex!("6ca4ed4361fd404007916ce98e14dceb0c3e9340717db91bfafb9fa4d404e801"),
+        hex!("58f8dc78de788c873e85b975c583065774cf20d9864ea03745353051f0137a5d"),
+    ),
+];
+
+fn keys(kp: &ECVRFKeyPair) -> ([u8; 32], [u8; 32]) {
+    (kp.sk.to_byte_array(), kp.pk.to_byte_array())
+}
+
+#[test]
+fn test_from_seed_vector() {
+    let kp = ECVRFKeyPair::from_seed(&SEED);
+    assert_eq!(keys(&kp), SEED_KEYS);
+    assert_eq!(keys(&ECVRFKeyPair::from_seed(&SEED)), SEED_KEYS);
+    assert_ne!(keys(&ECVRFKeyPair::from_seed(&[8; 32])), SEED_KEYS);
+
+    for (index, sk, pk) in CHILD_KEYS {
+        assert_eq!(keys(&kp.derive_child(index)), (sk, pk));
+    }
+}
+
+#[test]
+fn test_derived_keys_distinct() {
+    let kp = ECVRFKeyPair::from_seed(&SEED);
+    let mut public_keys = HashSet::from([kp.pk.to_byte_array()]);
+    for index in 0..32 {
+        assert!(public_keys.insert(kp.derive_child(index).pk.to_byte_array()));
+    }
+    // A grandchild is not a child of the seed key pair
+    assert!(public_keys.insert(kp.derive_child(0).derive_child(0).pk.to_byte_array()));
+}
+
+#[test]
+fn test_derived_keys_prove() {
+    let kp = ECVRFKeyPair::from_seed(&SEED).derive_child(3);
+    let pk = ECVRFPublicKey::from_byte_array(&kp.pk.to_byte_array()).unwrap();
+    let alpha = b"Hello, world!";
+    let proof = ECVRFProof::from_bytes(&kp.prove(alpha).to_bytes()).unwrap();
+    assert!(proof.verify(alpha, &pk).is_ok());
+}
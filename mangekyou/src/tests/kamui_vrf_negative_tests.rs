@@ -0,0 +1,234 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::MangekyouError;
+use crate::kamui_vrf::ecvrf::{
+    ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof, ECVRFPublicKey, PROOF_LENGTH,
+};
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use crate::ristretto_ops::DalekOps;
+use curve25519_dalek::constants::BASEPOINT_ORDER;
+
+const ALPHA: &[u8] = b"Hello, world!";
+
+const GAMMA: std::ops::Range<usize> = 0..32;
+const CHALLENGE: std::ops::Range<usize> = 32..48;
+const SCALAR: std::ops::Range<usize> = 48..PROOF_LENGTH;
+
+fn keypair(seed: u8) -> ECVRFKeyPair {
+    ECVRFKeyPair::from(ECVRFPrivateKey::from_bytes(&[seed; 32]).unwrap())
+}
+
+fn public_key(kp: &ECVRFKeyPair) -> ECVRFPublicKey {
+    ECVRFPublicKey::from_bytes(kp.pk.as_ref()).unwrap()
+}
+
+fn valid_proof() -> (ECVRFKeyPair, Vec<u8>) {
+    let kp = keypair(1);
+    let proof = kp.prove(ALPHA).to_bytes_legacy();
+    assert!(ECVRFProof::from_bytes_legacy(&proof)
+        .unwrap()
+        .verify(ALPHA, &public_key(&kp))
+        .is_ok());
+    (kp, proof)
+}
+
+/// Adds the group order l to the scalar `s`, which encodes the same scalar
+/// non-canonically.
+fn add_group_order(s: &[u8]) -> [u8; 32] {
+    let mut sum = [0u8; 32];
+    let mut carry = 0u16;
+    for (i, byte) in sum.iter_mut().enumerate() {
+        let digit = s[i] as u16 + BASEPOINT_ORDER.as_bytes()[i] as u16 + carry;
+        *byte = digit as u8;
+        carry = digit >> 8;
+    }
+    assert_eq!(carry, 0);
+    sum
+}
+
+/// Flips each bit of every byte in `region` and returns the verification errors.
+fn mutate_region(region: std::ops::Range<usize>) -> Vec<MangekyouError> {
+    let (kp, proof) = valid_proof();
+    let pk = public_key(&kp);
+    let mut errors = Vec::new();
+    for index in region {
+        for bit in 0..8 {
+            let mut mutated = proof.clone();
+            mutated[index] ^= 1 << bit;
+            let result = ECVRFProof::from_bytes_legacy(&mutated)
+                .unwrap()
+                .verify(ALPHA, &pk);
+            errors.push(result.expect_err("mutated proof must not verify"));
+        }
+    }
+    errors
+}
+
+#[test]
+fn test_mutated_gamma_rejected() {
+    // Most mutations are not a valid point encoding; the rest produce a different challenge
+    for error in mutate_region(GAMMA) {
+        assert!(
+            matches!(error, MangekyouError::InvalidProofEncoding | MangekyouError::ChallengeMismatch),
+            "unexpected error {:?}",
+            error
+        );
+    }
+}
+
+#[test]
+fn test_mutated_challenge_rejected() {
+    for error in mutate_region(CHALLENGE) {
+        assert_eq!(error, MangekyouError::ChallengeMismatch);
+    }
+}
+
+#[test]
+fn test_mutated_scalar_rejected() {
+    // Non-canonical scalars are rejected outright; canonical ones produce a different challenge
+    for error in mutate_region(SCALAR) {
+        assert!(
+            matches!(error, MangekyouError::InvalidScalar | MangekyouError::ChallengeMismatch),
+            "unexpected error {:?}",
+            error
+        );
+    }
+}
+
+#[test]
+fn test_non_canonical_scalar_rejected() {
+    // s + l verifies as s would, so accepting it would make proofs malleable
+    let (kp, proof) = valid_proof();
+    let pk = public_key(&kp);
+    let mut malleated = proof.clone();
+    malleated[SCALAR].copy_from_slice(&add_group_order(&proof[SCALAR]));
+    let malleated = ECVRFProof::from_bytes_legacy(&malleated).unwrap();
+
+    assert_eq!(malleated.verify(ALPHA, &pk), Err(MangekyouError::InvalidScalar));
+    assert_eq!(
+        malleated.verify_with::<DalekOps>(ALPHA, &pk),
+        Err(MangekyouError::InvalidScalar)
+    );
+}
+
+#[test]
+fn test_non_canonical_private_key_rejected() {
+    let sk = [1u8; 32];
+    assert!(ECVRFPrivateKey::from_bytes(&sk).is_ok());
+    assert!(ECVRFPrivateKey::from_bytes(&add_group_order(&sk)).is_err());
+    assert!(ECVRFPrivateKey::from_bytes(&[0xff; 32]).is_err());
+
+    let kp_bytes = [keypair(1).pk.as_ref(), &add_group_order(&sk)].concat();
+    assert!(ECVRFKeyPair::from_bytes(&kp_bytes).is_err());
+}
+
+#[test]
+fn test_swapped_components_rejected() {
+    let (kp, proof) = valid_proof();
+    let pk = public_key(&kp);
+
+    // c and s in the wrong order
+    let mut swapped = proof[GAMMA].to_vec();
+    swapped.extend_from_slice(&proof[SCALAR][..16]);
+    swapped.extend_from_slice(&proof[CHALLENGE]);
+    swapped.extend_from_slice(&proof[SCALAR][16..]);
+    assert!(ECVRFProof::from_bytes_legacy(&swapped)
+        .unwrap()
+        .verify(ALPHA, &pk)
+        .is_err());
+
+    // gamma and s exchanged
+    let mut swapped = proof[SCALAR].to_vec();
+    swapped.extend_from_slice(&proof[CHALLENGE]);
+    swapped.extend_from_slice(&proof[GAMMA]);
+    assert!(ECVRFProof::from_bytes_legacy(&swapped)
+        .unwrap()
+        .verify(ALPHA, &pk)
+        .is_err());
+}
+
+#[test]
+fn test_swapped_public_key_rejected() {
+    let (_, proof) = valid_proof();
+    let proof = ECVRFProof::from_bytes_legacy(&proof).unwrap();
+
+    let other = public_key(&keypair(2));
+    assert_eq!(
+        proof.verify(ALPHA, &other),
+        Err(MangekyouError::ChallengeMismatch)
+    );
+}
+
+#[test]
+fn test_degenerate_public_key_rejected() {
+    let (_, proof) = valid_proof();
+
+    // The identity, a non-canonical encoding of it, a negative field element
+    // and a field element that is no point of the group
+    let mut negative = [0u8; 32];
+    negative[0] = 1;
+    let mut non_canonical = [0xff; 32];
+    non_canonical[0] = 0xed;
+    non_canonical[31] = 0x7f;
+    for invalid in [[0u8; 32], non_canonical, negative, [0xff; 32]] {
+        assert!(ECVRFPublicKey::from_bytes(&invalid).is_err());
+        assert_eq!(
+            mangekyou_core::ecvrf::verify(&proof, ALPHA, &invalid),
+            Err(mangekyou_core::error::VerifyError::InvalidPublicKey)
+        );
+    }
+}
+
+#[test]
+fn test_truncated_and_extended_proof_rejected() {
+    let (_, proof) = valid_proof();
+    for len in 0..PROOF_LENGTH {
+        assert!(ECVRFProof::from_bytes_legacy(&proof[..len]).is_err(), "length {}", len);
+    }
+
+    let mut extended = proof.clone();
+    extended.push(0);
+    assert!(ECVRFProof::from_bytes_legacy(&extended).is_err());
+}
+
+#[test]
+fn test_truncated_public_key_rejected() {
+    let kp = keypair(1);
+    for len in 0..32 {
+        assert!(ECVRFPublicKey::from_bytes(&kp.pk.as_ref()[..len]).is_err());
+    }
+    let mut extended = kp.pk.as_ref().to_vec();
+    extended.push(0);
+    assert!(ECVRFPublicKey::from_bytes(&extended).is_err());
+}
+
+#[test]
+fn test_truncated_alpha_rejected() {
+    let (kp, proof) = valid_proof();
+    let proof = ECVRFProof::from_bytes_legacy(&proof).unwrap();
+    let pk = public_key(&kp);
+    for len in 0..ALPHA.len() {
+        assert_eq!(
+            proof.verify(&ALPHA[..len], &pk),
+            Err(MangekyouError::ChallengeMismatch)
+        );
+    }
+}
+
+#[test]
+fn test_wrong_output_rejected() {
+    let kp = keypair(1);
+    let (output, proof) = kp.output(ALPHA);
+    let pk = public_key(&kp);
+    assert!(proof.verify_output(ALPHA, &pk, &output).is_ok());
+
+    for index in [0, 31, 63] {
+        let mut wrong = output;
+        wrong[index] ^= 1;
+        assert_eq!(
+            proof.verify_output(ALPHA, &pk, &wrong),
+            Err(MangekyouError::InvalidProof)
+        );
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::MangekyouError;
+use crate::kamui_vrf::ecvrf::{ECVRFKeyPair, VRFOutput};
+use crate::kamui_vrf::VRFKeyPair;
+use rand::thread_rng;
+
+#[test]
+fn test_to_u64() {
+    let mut output = [0u8; 64];
+    output[..8].copy_from_slice(&0x0123_4567_89ab_cdef_u64.to_le_bytes());
+    assert_eq!(VRFOutput(output).to_u64(), 0x0123_4567_89ab_cdef);
+}
+
+#[test]
+fn test_in_range() {
+    for byte in 0..=255u8 {
+        let output = VRFOutput([byte; 64]);
+        let value = output.in_range(1, 6).unwrap();
+        assert!((1..=6).contains(&value));
+        assert_eq!(output.in_range(1, 6).unwrap(), value);
+        assert_eq!(output.in_range(7, 7).unwrap(), 7);
+        output.in_range(0, u64::MAX).unwrap();
+    }
+    assert_eq!(VRFOutput([0; 64]).in_range(2, 1), Err(MangekyouError::InvalidInput));
+
+    // Every value of a small range is reachable
+    let mut seen = [false; 6];
+    for byte in 0..=255u8 {
+        seen[VRFOutput([byte; 64]).in_range(0, 5).unwrap() as usize] = true;
+    }
+    assert!(seen.iter().all(|&seen| seen));
+}
+
+#[test]
+fn test_shuffle() {
+    let (output, _) = ECVRFKeyPair::generate(&mut thread_rng()).output(b"Hello, world!");
+    let output = VRFOutput::from(output);
+
+    let mut items: Vec<u32> = (0..52).collect();
+    output.shuffle(&mut items);
+    let mut sorted = items.clone();
+    sorted.sort();
+    assert_eq!(sorted, (0..52).collect::<Vec<_>>());
+
+    let mut again: Vec<u32> = (0..52).collect();
+    output.shuffle(&mut again);
+    assert_eq!(again, items);
+
+    output.shuffle::<u32>(&mut []);
+}
+
+#[test]
+fn test_weighted_choice() {
+    for byte in 0..=255u8 {
+        let output = VRFOutput([byte; 64]);
+        assert_eq!(output.weighted_choice(&[0, 5, 0]), Ok(1));
+        assert!(output.weighted_choice(&[2, 7, 1]).unwrap() < 3);
+    }
+
+    for weights in [&[][..], &[0, 0], &[u64::MAX, 1]] {
+        assert_eq!(
+            VRFOutput([0; 64]).weighted_choice(weights),
+            Err(MangekyouError::InvalidInput)
+        );
+    }
+}
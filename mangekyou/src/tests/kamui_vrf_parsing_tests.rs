@@ -0,0 +1,121 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Property tests of the parsers of proofs and keys: arbitrary bytes never
+//! panic, and whatever is accepted encodes back to the same bytes.
+
+use crate::kamui_vrf::ecvrf::{
+    ECVRFBatchableProof, ECVRFKeyPair, ECVRFProof, ECVRFPublicKey, BATCHABLE_PROOF_LENGTH,
+    ENVELOPE_LENGTH, KEY_PAIR_LENGTH, PROOF_LENGTH,
+};
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use crate::serde_helpers::ToFromByteArray;
+use mangekyou_core::{ecvrf as core_ecvrf, ecvrf_edwards25519};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// Byte strings around `len` bytes: every length up to twice `len`, with the
+/// exact length weighted up so that parsers get past their length checks.
+fn bytes_around(len: usize) -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        1 => vec(any::<u8>(), 0..2 * len),
+        3 => vec(any::<u8>(), len),
+    ]
+}
+
+/// A public key, an input and a valid proof of it
+fn valid_proof() -> impl Strategy<Value = (ECVRFPublicKey, Vec<u8>, ECVRFProof)> {
+    (any::<[u8; 32]>(), vec(any::<u8>(), 0..64)).prop_map(|(seed, alpha)| {
+        let kp = ECVRFKeyPair::from_seed(&seed);
+        let proof = VRFKeyPair::prove(&kp, &alpha);
+        (kp.pk, alpha, proof)
+    })
+}
+
+proptest! {
+    #[test]
+    fn proof_from_bytes_round_trips(bytes in bytes_around(ENVELOPE_LENGTH)) {
+        if let Ok(proof) = ECVRFProof::from_bytes(&bytes) {
+            prop_assert_eq!(proof.to_bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn proof_from_bytes_legacy_round_trips(bytes in bytes_around(PROOF_LENGTH)) {
+        match ECVRFProof::from_bytes_legacy(&bytes) {
+            Ok(proof) => prop_assert_eq!(proof.to_bytes_legacy(), bytes),
+            Err(_) => prop_assert_ne!(bytes.len(), PROOF_LENGTH),
+        }
+    }
+
+    #[test]
+    fn batchable_proof_from_bytes_round_trips(bytes in bytes_around(BATCHABLE_PROOF_LENGTH)) {
+        if let Ok(proof) = ECVRFBatchableProof::from_bytes(&bytes) {
+            prop_assert_eq!(proof.to_bytes().to_vec(), bytes);
+        }
+    }
+
+    #[test]
+    fn public_key_from_bytes_round_trips(bytes in bytes_around(32)) {
+        if let Ok(pk) = ECVRFPublicKey::from_bytes(&bytes) {
+            prop_assert_eq!(pk.as_ref(), &bytes[..]);
+            prop_assert_eq!(pk.to_byte_array().to_vec(), bytes);
+        }
+    }
+
+    #[test]
+    fn keypair_from_bytes_round_trips(bytes in bytes_around(KEY_PAIR_LENGTH)) {
+        if let Ok(kp) = ECVRFKeyPair::from_bytes(&bytes) {
+            prop_assert_eq!(kp.to_byte_array().to_vec(), bytes);
+        }
+    }
+
+    #[test]
+    fn keypair_from_bytes_rejects_other_public_keys(seed in any::<[u8; 32]>(), pk in any::<[u8; 32]>()) {
+        let kp = ECVRFKeyPair::from_seed(&seed);
+        let bytes = kp.to_byte_array();
+        prop_assert!(ECVRFKeyPair::from_bytes(&bytes).is_ok());
+        prop_assume!(pk != kp.pk.to_byte_array());
+        prop_assert!(ECVRFKeyPair::from_bytes(&[&pk[..], &bytes[32..]].concat()).is_err());
+    }
+
+    #[test]
+    fn valid_proofs_round_trip((pk, alpha, proof) in valid_proof()) {
+        let envelope = ECVRFProof::from_bytes(&proof.to_bytes()).unwrap();
+        prop_assert_eq!(&envelope, &proof);
+        prop_assert!(envelope.verify(&alpha, &pk).is_ok());
+        let legacy = ECVRFProof::from_bytes_legacy(&proof.to_bytes_legacy()).unwrap();
+        prop_assert_eq!(&legacy, &proof);
+        prop_assert_eq!(
+            core_ecvrf::verify(&proof.to_bytes_legacy(), &alpha, pk.as_ref()),
+            Ok(proof.to_hash())
+        );
+    }
+
+    #[test]
+    fn altered_proofs_are_rejected(
+        (pk, alpha, proof) in valid_proof(),
+        index in 0..ENVELOPE_LENGTH,
+        mask in 1..=u8::MAX,
+    ) {
+        let mut bytes = proof.to_bytes();
+        bytes[index] ^= mask;
+        if let Ok(altered) = ECVRFProof::from_bytes(&bytes) {
+            prop_assert!(altered.verify(&alpha, &pk).is_err());
+        }
+        let legacy = &bytes[2..];
+        if index >= 2 {
+            prop_assert!(core_ecvrf::verify(legacy, &alpha, pk.as_ref()).is_err());
+        }
+    }
+
+    #[test]
+    fn verifiers_never_panic(
+        proof in bytes_around(PROOF_LENGTH),
+        alpha in vec(any::<u8>(), 0..64),
+        public_key in bytes_around(32),
+    ) {
+        let _ = core_ecvrf::verify(&proof, &alpha, &public_key);
+        let _ = ecvrf_edwards25519::verify(&proof, &alpha, &public_key);
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::MangekyouError;
+use crate::groups::ristretto255::RistrettoScalar;
+use crate::groups::GroupElement;
+use crate::kamui_vrf::ecvrf::threshold::{
+    aggregate, commit, partial_prove, verify_partial, NonceCommitment, PartialProof,
+    NONCE_COMMITMENT_LENGTH, PARTIAL_PROOF_LENGTH,
+};
+use crate::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFPrivateKey, ECVRFProof};
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use crate::serde_helpers::ToFromByteArray;
+use crate::tdkg::{lagrange_coefficient, KeyShare, Parameters, Party, Transcript};
+use rand::thread_rng;
+
+/// Key shares of a DKG among `parties` honest parties
+fn key_shares(threshold: u16, parties: u16) -> Vec<KeyShare> {
+    let parameters = Parameters::new(threshold, parties).unwrap();
+    let mut parties: Vec<Party> = (1..=parties)
+        .map(|id| Party::new(parameters, id, &mut thread_rng()).unwrap())
+        .collect();
+    let mut transcript = Transcript::new(parameters);
+    let dealings: Vec<_> = parties.iter().map(Party::dealing).collect();
+    let shares: Vec<Vec<_>> = parties
+        .iter()
+        .map(|dealer| (1..=parameters.parties()).map(|id| dealer.share(id).unwrap()).collect())
+        .collect();
+    for (dealer, dealing) in dealings.iter().enumerate() {
+        transcript.add_dealing(dealing.clone()).unwrap();
+        for (receiver, party) in parties.iter_mut().enumerate() {
+            assert!(party.receive(dealing, Some(&shares[dealer][receiver])).is_none());
+        }
+    }
+    parties.iter().map(|party| party.finalize(&transcript).unwrap()).collect()
+}
+
+/// Runs both rounds with the holders of `signers`
+fn threshold_prove(signers: &[&KeyShare], alpha: &[u8]) -> (Vec<NonceCommitment>, Vec<PartialProof>) {
+    let nonces: Vec<_> = signers.iter().map(|share| commit(share, alpha, &mut thread_rng())).collect();
+    let commitments: Vec<NonceCommitment> = nonces.iter().map(|nonces| nonces.commitment().clone()).collect();
+    let partials = signers
+        .iter()
+        .zip(nonces)
+        .map(|(share, nonces)| partial_prove(share, nonces, alpha, &commitments).unwrap())
+        .collect();
+    (commitments, partials)
+}
+
+/// The shared key pair, for comparison
+fn shared_key_pair(shares: &[&KeyShare]) -> ECVRFKeyPair {
+    let ids: Vec<u16> = shares.iter().map(|share| share.id).collect();
+    let secret = shares.iter().fold(RistrettoScalar::zero(), |sum, share| {
+        sum + lagrange_coefficient(share.id, &ids).unwrap() * share.share()
+    });
+    ECVRFKeyPair::from(ECVRFPrivateKey::from_byte_array(&secret.to_byte_array()).unwrap())
+}
+
+#[test]
+fn test_threshold_prove() {
+    let shares = key_shares(3, 5);
+    let output = &shares[0].output;
+    let public_key = output.vrf_public_key().unwrap();
+    let alpha = b"round 42";
+
+    let signers = [&shares[0], &shares[2], &shares[4]];
+    let (commitments, partials) = threshold_prove(&signers, alpha);
+    for partial in &partials {
+        assert!(verify_partial(output, alpha, &commitments, partial).is_ok());
+    }
+    let proof = aggregate(output, alpha, &commitments, &partials).unwrap();
+    assert!(proof.verify(alpha, &public_key).is_ok());
+    assert!(mangekyou_core::ecvrf::verify(&proof.to_bytes_legacy(), alpha, public_key.as_ref()).is_ok());
+
+    // The output is that of the shared key, whichever signers prove
+    let kp = shared_key_pair(&signers);
+    assert_eq!(kp.pk, public_key);
+    assert_eq!(proof.to_hash(), kp.prove(alpha).to_hash());
+
+    let signers = [&shares[4], &shares[1], &shares[3], &shares[2]];
+    let (commitments, mut partials) = threshold_prove(&signers, alpha);
+    partials.reverse();
+    let other = aggregate(output, alpha, &commitments, &partials).unwrap();
+    assert!(other.verify(alpha, &public_key).is_ok());
+    assert_eq!(other.to_hash(), proof.to_hash());
+    assert_ne!(other, proof);
+}
+
+#[test]
+fn test_invalid_partials_rejected() {
+    let shares = key_shares(2, 3);
+    let output = &shares[0].output;
+    let alpha = b"alpha";
+    let (commitments, partials) = threshold_prove(&[&shares[0], &shares[1]], alpha);
+
+    // A response to another session
+    let (_, other) = threshold_prove(&[&shares[0], &shares[1]], b"another alpha");
+    let mixed = [partials[0].clone(), other[1].clone()];
+    assert!(verify_partial(output, alpha, &commitments, &mixed[0]).is_ok());
+    assert_eq!(
+        verify_partial(output, alpha, &commitments, &mixed[1]),
+        Err(MangekyouError::InvalidProof)
+    );
+    assert_eq!(aggregate(output, alpha, &commitments, &mixed), Err(MangekyouError::ChallengeMismatch));
+
+    // Each signer answers once, and only signers do
+    let twice = [partials[0].clone(), partials[0].clone()];
+    assert_eq!(aggregate(output, alpha, &commitments, &twice), Err(MangekyouError::InvalidInput));
+    assert_eq!(aggregate(output, alpha, &commitments, &partials[..1]), Err(MangekyouError::InvalidInput));
+    let (_, outsider) = threshold_prove(&[&shares[0], &shares[2]], alpha);
+    assert!(verify_partial(output, alpha, &commitments, &outsider[1]).is_err());
+
+    // Fewer commitments than the threshold, or a list missing the signer's own
+    let nonces = commit(&shares[2], alpha, &mut thread_rng());
+    assert_eq!(
+        partial_prove(&shares[2], nonces, alpha, &commitments),
+        Err(MangekyouError::InvalidInput)
+    );
+    let nonces = commit(&shares[0], alpha, &mut thread_rng());
+    let own = [nonces.commitment().clone()];
+    assert_eq!(partial_prove(&shares[0], nonces, alpha, &own), Err(MangekyouError::NotEnoughInputs));
+}
+
+#[test]
+fn test_serialization() {
+    let shares = key_shares(2, 2);
+    let (commitments, partials) = threshold_prove(&[&shares[0], &shares[1]], b"alpha");
+
+    let bytes = commitments[1].to_bytes();
+    assert_eq!(NonceCommitment::from_bytes(&bytes).unwrap(), commitments[1]);
+    assert_eq!(
+        NonceCommitment::from_bytes(&bytes[1..]),
+        Err(MangekyouError::InputLengthWrong(NONCE_COMMITMENT_LENGTH))
+    );
+    let mut invalid = bytes;
+    invalid[2..34].copy_from_slice(&[0xff; 32]);
+    assert_eq!(NonceCommitment::from_bytes(&invalid), Err(MangekyouError::InvalidInput));
+
+    let bytes = partials[1].to_bytes();
+    assert_eq!(PartialProof::from_bytes(&bytes).unwrap(), partials[1]);
+    assert_eq!(
+        PartialProof::from_bytes(&bytes[1..]),
+        Err(MangekyouError::InputLengthWrong(PARTIAL_PROOF_LENGTH))
+    );
+    let mut invalid = bytes;
+    invalid[2..].copy_from_slice(&[0xff; 32]);
+    assert_eq!(PartialProof::from_bytes(&invalid), Err(MangekyouError::InvalidInput));
+
+    let proof = aggregate(&shares[0].output, b"alpha", &commitments, &partials).unwrap();
+    assert_eq!(ECVRFProof::from_bytes(&proof.to_bytes()).unwrap(), proof);
+}
@@ -0,0 +1,87 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::MangekyouError;
+use crate::kamui_vrf::ecvrf::ECVRFKeyPair;
+use crate::kamui_vrf::VRFKeyPair;
+use crate::keystore::{KdfParams, Keystore, VERSION};
+use crate::serde_helpers::ToFromByteArray;
+use rand::thread_rng;
+
+/// Cheap parameters, to keep the tests fast
+const PARAMS: KdfParams = KdfParams { log_n: 10, r: 8, p: 1 };
+
+fn keystore(password: &[u8]) -> (ECVRFKeyPair, Keystore) {
+    let kp = ECVRFKeyPair::generate(&mut thread_rng());
+    let keystore = Keystore::create_with_params(&kp, password, PARAMS, &mut thread_rng()).unwrap();
+    (kp, keystore)
+}
+
+#[test]
+fn test_create_and_unlock() {
+    let (kp, keystore) = keystore(b"correct horse");
+    assert_eq!(keystore.public_key().unwrap(), kp.pk);
+    assert_eq!(keystore.kdf_params(), PARAMS);
+
+    let unlocked = keystore.unlock(b"correct horse").unwrap();
+    assert_eq!(unlocked.to_byte_array(), kp.to_byte_array());
+    assert_eq!(keystore.unlock(b"battery staple").err(), Some(MangekyouError::GeneralOpaqueError));
+
+    // The secret key is not in the file, and the same key encrypts differently
+    let json = keystore.to_json();
+    assert!(!json.contains(&hex::encode(kp.sk.as_ref())));
+    let again = Keystore::create_with_params(&kp, b"correct horse", PARAMS, &mut thread_rng()).unwrap();
+    assert_ne!(again, keystore);
+
+    assert!(Keystore::create_with_params(&kp, b"", KdfParams { log_n: 10, r: 8, p: 0 }, &mut thread_rng()).is_err());
+}
+
+#[test]
+fn test_rotate_password() {
+    let (kp, keystore) = keystore(b"old");
+    let rotated = keystore.rotate_password(b"old", b"new", &mut thread_rng()).unwrap();
+    assert_eq!(rotated.public_key().unwrap(), kp.pk);
+    assert_eq!(rotated.kdf_params(), PARAMS);
+    assert_eq!(rotated.unlock(b"new").unwrap().to_byte_array(), kp.to_byte_array());
+    assert!(rotated.unlock(b"old").is_err());
+
+    assert!(keystore.rotate_password(b"wrong", b"new", &mut thread_rng()).is_err());
+}
+
+#[test]
+fn test_json() {
+    let (kp, keystore) = keystore(b"password");
+    let json = keystore.to_json();
+    let decoded = Keystore::from_json(&json).unwrap();
+    assert_eq!(decoded, keystore);
+    assert_eq!(decoded.unlock(b"password").unwrap().to_byte_array(), kp.to_byte_array());
+
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value["version"], VERSION);
+    assert_eq!(value["kdf"]["log_n"], 10);
+
+    // The public key is authenticated with the secret key
+    let other = ECVRFKeyPair::generate(&mut thread_rng());
+    value["public_key"] = format!("0x{}", hex::encode(other.pk.as_ref())).into();
+    let swapped = Keystore::from_json(&value.to_string()).unwrap();
+    assert_eq!(swapped.unlock(b"password").err(), Some(MangekyouError::GeneralOpaqueError));
+
+    value["version"] = 2.into();
+    assert_eq!(Keystore::from_json(&value.to_string()), Err(MangekyouError::InvalidInput));
+    assert_eq!(Keystore::from_json(&json[1..]), Err(MangekyouError::InvalidInput));
+}
+
+#[test]
+fn test_save_and_load() {
+    let (kp, keystore) = keystore(b"password");
+    let path = std::env::temp_dir().join(format!("kamui-keystore-{}.json", hex::encode(kp.pk.as_ref())));
+    keystore.save(&path).unwrap();
+    let loaded = Keystore::load(&path);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), keystore);
+}
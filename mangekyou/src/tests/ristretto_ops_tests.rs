@@ -0,0 +1,66 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::kamui_vrf::BASEPOINT_BYTES;
+use crate::ristretto_ops::{DalekOps, RistrettoOps, SolanaSyscallOps};
+use curve25519_dalek::scalar::Scalar;
+use rand::{thread_rng, RngCore};
+
+fn random_scalar() -> [u8; 32] {
+    let mut wide = [0u8; 64];
+    thread_rng().fill_bytes(&mut wide);
+    Scalar::from_bytes_mod_order_wide(&wide).to_bytes()
+}
+
+fn random_point() -> [u8; 32] {
+    DalekOps::multiply(&random_scalar(), &BASEPOINT_BYTES).unwrap()
+}
+
+#[test]
+fn test_implementations_agree() {
+    for _ in 0..16 {
+        let (a, b) = (random_scalar(), random_scalar());
+        let (p, q) = (random_point(), random_point());
+
+        assert_eq!(
+            SolanaSyscallOps::multiply(&a, &p),
+            DalekOps::multiply(&a, &p)
+        );
+        assert_eq!(
+            SolanaSyscallOps::multiscalar_multiply(&[a, b], &[p, q]),
+            DalekOps::multiscalar_multiply(&[a, b], &[p, q])
+        );
+        assert_eq!(
+            SolanaSyscallOps::basepoint_double_scalar_multiply(&a, &p, &b),
+            DalekOps::basepoint_double_scalar_multiply(&a, &p, &b)
+        );
+        assert_eq!(
+            DalekOps::basepoint_double_scalar_multiply(&a, &p, &b),
+            DalekOps::multiscalar_multiply(&[a, b], &[p, BASEPOINT_BYTES])
+        );
+        assert!(SolanaSyscallOps::is_valid_point(&p));
+        assert!(DalekOps::is_valid_point(&p));
+    }
+}
+
+#[test]
+fn test_invalid_inputs_rejected() {
+    let point = random_point();
+    let scalar = random_scalar();
+
+    // Not a valid Ristretto encoding
+    let invalid_point = [0xff; 32];
+    assert!(!SolanaSyscallOps::is_valid_point(&invalid_point));
+    assert!(!DalekOps::is_valid_point(&invalid_point));
+    assert!(SolanaSyscallOps::multiply(&scalar, &invalid_point).is_none());
+    assert!(DalekOps::multiply(&scalar, &invalid_point).is_none());
+
+    // Not a canonical scalar
+    let non_canonical = [0xff; 32];
+    assert!(SolanaSyscallOps::multiply(&non_canonical, &point).is_none());
+    assert!(DalekOps::multiply(&non_canonical, &point).is_none());
+    assert!(SolanaSyscallOps::multiscalar_multiply(&[non_canonical], &[point]).is_none());
+    assert!(DalekOps::multiscalar_multiply(&[non_canonical], &[point]).is_none());
+    assert!(DalekOps::basepoint_double_scalar_multiply(&non_canonical, &point, &scalar).is_none());
+    assert!(DalekOps::basepoint_double_scalar_multiply(&scalar, &invalid_point, &scalar).is_none());
+}
@@ -0,0 +1,81 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::MangekyouError;
+use crate::kamui_vrf::ecvrf::ECVRFKeyPair;
+use crate::scheme::{GenericKeyPair, GenericPublicKey, GenericSignature, Scheme};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn test_flags() {
+    for scheme in Scheme::ALL {
+        assert_eq!(Scheme::from_flag(scheme.flag()), Ok(scheme));
+        assert_eq!(scheme.to_string().parse::<Scheme>(), Ok(scheme));
+    }
+    assert_eq!(Scheme::from_flag(0x04), Err(MangekyouError::InvalidInput));
+    assert!("ed448".parse::<Scheme>().is_err());
+}
+
+#[test]
+fn test_sign_verify_round_trip() {
+    let mut rng = StdRng::from_seed([1; 32]);
+    for scheme in Scheme::ALL {
+        let kp = GenericKeyPair::generate(scheme, &mut rng);
+        assert_eq!(kp.scheme(), scheme);
+        let signature = kp.sign(b"Hello, world!");
+        assert_eq!(signature.scheme(), scheme);
+        assert!(kp.public().verify(b"Hello, world!", &signature).is_ok());
+        assert!(kp.public().verify(b"Hello, world?", &signature).is_err());
+
+        // Encodings are prefixed by the flag and round trip
+        let bytes = kp.to_bytes();
+        assert_eq!(bytes[0], scheme.flag());
+        let decoded = GenericKeyPair::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.public(), kp.public());
+        let public = GenericPublicKey::from_bytes(&kp.public().to_bytes()).unwrap();
+        assert_eq!(public, kp.public());
+        let decoded = GenericSignature::from_bytes(&signature.to_bytes()).unwrap();
+        assert_eq!(decoded, signature);
+        assert!(public.verify(b"Hello, world!", &decoded).is_ok());
+    }
+}
+
+#[test]
+fn test_mixed_schemes() {
+    let mut rng = StdRng::from_seed([2; 32]);
+    let ed25519 = GenericKeyPair::generate(Scheme::Ed25519, &mut rng);
+    let ecvrf = GenericKeyPair::from(ECVRFKeyPair::from_seed(&[3; 32]));
+    let signature = ed25519.sign(b"msg");
+    assert_eq!(
+        ecvrf.public().verify(b"msg", &signature),
+        Err(MangekyouError::InvalidSignature)
+    );
+
+    // A payload of one scheme under the flag of another is rejected
+    let mut bytes = ed25519.public().to_bytes();
+    bytes[0] = Scheme::Secp256k1.flag();
+    assert!(GenericPublicKey::from_bytes(&bytes).is_err());
+    assert!(GenericPublicKey::from_bytes(&[]).is_err());
+    assert!(GenericSignature::from_bytes(&[Scheme::Ecvrf.flag(); 80]).is_err());
+}
+
+#[test]
+fn test_serde() {
+    let keys: Vec<GenericKeyPair> = Scheme::ALL
+        .into_iter()
+        .map(|scheme| GenericKeyPair::generate(scheme, &mut StdRng::from_seed([4; 32])))
+        .collect();
+    let publics: Vec<GenericPublicKey> = keys.iter().map(GenericKeyPair::public).collect();
+
+    let json = serde_json::to_string(&keys).unwrap();
+    let decoded: Vec<GenericKeyPair> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded.iter().map(GenericKeyPair::public).collect::<Vec<_>>(), publics);
+
+    let bytes = bincode::serialize(&publics).unwrap();
+    assert_eq!(bincode::deserialize::<Vec<GenericPublicKey>>(&bytes).unwrap(), publics);
+
+    let signature = keys[3].sign(b"msg");
+    let json = serde_json::to_string(&signature).unwrap();
+    assert_eq!(serde_json::from_str::<GenericSignature>(&json).unwrap(), signature);
+}
@@ -0,0 +1,131 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::kamui_vrf::ecvrf::ECVRFKeyPair;
+use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+use crate::signer::VrfSigner;
+
+#[test]
+fn test_in_memory_signer() {
+    let kp = ECVRFKeyPair::from_seed(&[1; 32]);
+    let expected = VRFKeyPair::prove(&kp, b"alpha");
+
+    let signer: Box<dyn VrfSigner> = Box::new(ECVRFKeyPair::from_seed(&[1; 32]));
+    assert_eq!(signer.public_key(), kp.pk);
+    let proof = signer.prove(b"alpha").unwrap();
+    assert_eq!(proof, expected);
+    assert!(proof.verify(b"alpha", &signer.public_key()).is_ok());
+}
+
+#[cfg(feature = "remote-signer")]
+mod remote {
+    use crate::error::MangekyouError;
+    use crate::kamui_vrf::ecvrf::{ECVRFKeyPair, ECVRFProof};
+    use crate::kamui_vrf::{VRFKeyPair, VRFProof};
+    use crate::signer::{RemoteSigner, VrfSigner};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    /// Serves `requests` requests of the remote signer protocol with `kp`,
+    /// answering proofs with `respond`, and returns its URL
+    fn serve(
+        kp: ECVRFKeyPair,
+        requests: usize,
+        respond: impl Fn(ECVRFProof) -> Vec<u8> + Send + 'static,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                let mut authorized = false;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    let header = header.trim_end().to_ascii_lowercase();
+                    if header.is_empty() {
+                        break;
+                    }
+                    if let Some(length) = header.strip_prefix("content-length: ") {
+                        content_length = length.parse().unwrap();
+                    }
+                    authorized |= header == "authorization: bearer secret";
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let (status, response) = if !authorized {
+                    ("401 Unauthorized", String::new())
+                } else if request_line.starts_with("GET /public_key ") {
+                    ("200 OK", format!(r#"{{"public_key":"0x{}"}}"#, hex::encode(kp.pk.as_ref())))
+                } else if request_line.starts_with("POST /prove ") {
+                    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let alpha = hex::decode(body["alpha"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+                    let proof = respond(VRFKeyPair::prove(&kp, &alpha));
+                    ("200 OK", format!(r#"{{"proof":"0x{}"}}"#, hex::encode(proof)))
+                } else {
+                    ("404 Not Found", String::new())
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    response.len(),
+                    response
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_remote_signer() {
+        let kp = ECVRFKeyPair::from_seed(&[1; 32]);
+        let url = serve(ECVRFKeyPair::from_seed(&[1; 32]), 3, |proof| proof.to_bytes());
+        let signer = RemoteSigner::new(&url, kp.pk.clone()).with_token("secret");
+        assert!(signer.check().is_ok());
+        assert_eq!(signer.prove(b"alpha").unwrap(), VRFKeyPair::prove(&kp, b"alpha"));
+
+        // Requests are authenticated
+        let signer = RemoteSigner::new(&url, kp.pk.clone());
+        assert!(matches!(signer.prove(b"alpha"), Err(MangekyouError::GeneralError(_))));
+    }
+
+    #[test]
+    fn test_remote_signer_other_key() {
+        let kp = ECVRFKeyPair::from_seed(&[1; 32]);
+        let url = serve(ECVRFKeyPair::from_seed(&[2; 32]), 2, |proof| proof.to_bytes());
+        let signer = RemoteSigner::new(&url, kp.pk).with_token("secret");
+        assert_eq!(signer.check(), Err(MangekyouError::InvalidPublicKey));
+        assert_eq!(signer.prove(b"alpha").err(), Some(MangekyouError::ChallengeMismatch));
+    }
+
+    #[test]
+    fn test_remote_signer_invalid_proof() {
+        let kp = ECVRFKeyPair::from_seed(&[1; 32]);
+
+        // Proofs for another input, and proofs without the envelope
+        let url = serve(ECVRFKeyPair::from_seed(&[1; 32]), 1, |_| {
+            VRFKeyPair::prove(&ECVRFKeyPair::from_seed(&[1; 32]), b"beta").to_bytes()
+        });
+        let signer = RemoteSigner::new(&url, kp.pk.clone()).with_token("secret");
+        assert_eq!(signer.prove(b"alpha").err(), Some(MangekyouError::ChallengeMismatch));
+        let url = serve(ECVRFKeyPair::from_seed(&[1; 32]), 1, |proof| proof.to_bytes_legacy());
+        let signer = RemoteSigner::new(&url, kp.pk.clone()).with_token("secret");
+        assert_eq!(signer.prove(b"alpha").err(), Some(MangekyouError::InvalidProof));
+
+        // An unreachable service
+        let url = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let signer = RemoteSigner::new(&url, kp.pk).with_timeout(Duration::from_secs(1));
+        assert!(matches!(signer.prove(b"alpha"), Err(MangekyouError::GeneralError(_))));
+    }
+}
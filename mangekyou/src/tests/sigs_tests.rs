@@ -0,0 +1,242 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::MangekyouError;
+use crate::hash::Keccak256;
+use crate::sigs::ed25519::{Ed25519KeyPair, Ed25519PublicKey, Ed25519Signature};
+use crate::sigs::{secp256k1, secp256r1};
+use crate::sigs::secp256k1::{
+    Secp256k1KeyPair, Secp256k1PublicKey, Secp256k1RecoverableSignature, Secp256k1Signature,
+};
+use crate::sigs::secp256r1::{
+    Secp256r1KeyPair, Secp256r1PublicKey, Secp256r1RecoverableSignature, Secp256r1Signature,
+};
+use crate::traits::{
+    EncodeDecodeBase64, KeyPair, RecoverableSignature, RecoverableSigner, Signer, ToFromBytes,
+    VerifyRecoverable, VerifyingKey,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// The vectors of mangekyou-cli, from keys generated by StdRng seeded with [1; 32]
+const MSG: &str = "00010203";
+const PRIVATE: &str = "3301e8d7e754db2cf57b0a4ca73f253c7053ad2bc5398777ba039b258e59ad9d";
+
+fn generate<K: KeyPair>() -> K {
+    K::generate(&mut StdRng::from_seed([1; 32]))
+}
+
+#[test]
+fn test_ed25519_vector() {
+    let kp: Ed25519KeyPair = generate();
+    assert_eq!(hex::encode(kp.as_bytes()), PRIVATE);
+    assert_eq!(
+        hex::encode(kp.public()),
+        "8c553335eee80b9bfa0c544a45fe63474a09dff9c4b0b33db2b662f934ea46c4"
+    );
+    let msg = hex::decode(MSG).unwrap();
+    let signature = kp.sign(&msg);
+    assert_eq!(
+        hex::encode(&signature),
+        "e929370aa36bef3a6b51594b6d96e0f389f09f28807e6b3a25d0ea93f56dd4659e15995f87545ab8f7f924bc18e0502fa689a57e57e931620b79a6c9ec7b3208"
+    );
+    assert!(kp.public().verify(&msg, &signature).is_ok());
+    assert_eq!(
+        kp.public().verify(b"other", &signature),
+        Err(MangekyouError::InvalidSignature)
+    );
+}
+
+#[test]
+fn test_secp256k1_vectors() {
+    let kp: Secp256k1KeyPair = generate();
+    assert_eq!(hex::encode(kp.as_bytes()), PRIVATE);
+    assert_eq!(
+        hex::encode(kp.public()),
+        "033e99a541db69bd32040dfe5037fbf5210dafa8151a71e21c5204b05d95ce0a62"
+    );
+    let msg = hex::decode(MSG).unwrap();
+    let signature = kp.sign(&msg);
+    assert_eq!(
+        hex::encode(&signature),
+        "416a21d50b3c838328d4f03213f8ef0c3776389a972ba1ecd37b56243734eba208ea6aaa6fc076ad7accd71d355f693a6fe54fe69b3c168eace9803827bc9046"
+    );
+    assert!(kp.public().verify(&msg, &signature).is_ok());
+
+    let recoverable = kp.sign_recoverable(&msg);
+    assert_eq!(
+        hex::encode(&recoverable),
+        "416a21d50b3c838328d4f03213f8ef0c3776389a972ba1ecd37b56243734eba208ea6aaa6fc076ad7accd71d355f693a6fe54fe69b3c168eace9803827bc904601"
+    );
+    assert_eq!(Secp256k1Signature::from(&recoverable), signature);
+    assert_eq!(&recoverable.recover(&msg).unwrap(), kp.public());
+    assert!(kp.public().verify_recoverable(&msg, &recoverable).is_ok());
+    assert_eq!(
+        kp.public().verify_recoverable(b"other", &recoverable),
+        Err(MangekyouError::InvalidSignature)
+    );
+}
+
+#[test]
+fn test_secp256r1_vectors() {
+    let kp: Secp256r1KeyPair = generate();
+    assert_eq!(hex::encode(kp.as_bytes()), PRIVATE);
+    assert_eq!(
+        hex::encode(kp.public()),
+        "035a8b075508c75f4a124749982a7d21f80d9a5f6893e41a9e955fe4c821e0debe"
+    );
+    let msg = hex::decode(MSG).unwrap();
+    let signature = kp.sign(&msg);
+    assert_eq!(
+        hex::encode(&signature),
+        "54d7d68b43d65f718f3a92041292a514987739c36158a836b2218c505ba0e17c661642e58c996ba78f0cca493690b89658d0da3b9333a9e4fcea9ebf13da64bd"
+    );
+    assert!(kp.public().verify(&msg, &signature).is_ok());
+
+    let recoverable = kp.sign_recoverable(&msg);
+    assert_eq!(
+        hex::encode(&recoverable),
+        "54d7d68b43d65f718f3a92041292a514987739c36158a836b2218c505ba0e17c661642e58c996ba78f0cca493690b89658d0da3b9333a9e4fcea9ebf13da64bd01"
+    );
+    assert_eq!(Secp256r1Signature::from(&recoverable), signature);
+    assert_eq!(&recoverable.recover(&msg).unwrap(), kp.public());
+    assert!(kp.public().verify_recoverable(&msg, &recoverable).is_ok());
+    assert_eq!(
+        kp.public().verify_recoverable(b"other", &recoverable),
+        Err(MangekyouError::InvalidSignature)
+    );
+}
+
+#[test]
+fn test_recover() {
+    let msg = hex::decode(MSG).unwrap();
+    let signature = hex::decode("416a21d50b3c838328d4f03213f8ef0c3776389a972ba1ecd37b56243734eba208ea6aaa6fc076ad7accd71d355f693a6fe54fe69b3c168eace9803827bc904601").unwrap();
+    assert_eq!(
+        hex::encode(secp256k1::recover(&msg, &signature).unwrap()),
+        "033e99a541db69bd32040dfe5037fbf5210dafa8151a71e21c5204b05d95ce0a62"
+    );
+    assert_eq!(
+        secp256k1::recover(&msg, &signature[..64]),
+        Err(MangekyouError::InputLengthWrong(65))
+    );
+    assert_ne!(
+        hex::encode(secp256k1::recover(b"other", &signature).unwrap()),
+        "033e99a541db69bd32040dfe5037fbf5210dafa8151a71e21c5204b05d95ce0a62"
+    );
+
+    let signature = hex::decode("54d7d68b43d65f718f3a92041292a514987739c36158a836b2218c505ba0e17c661642e58c996ba78f0cca493690b89658d0da3b9333a9e4fcea9ebf13da64bd01").unwrap();
+    assert_eq!(
+        hex::encode(secp256r1::recover(&msg, &signature).unwrap()),
+        "035a8b075508c75f4a124749982a7d21f80d9a5f6893e41a9e955fe4c821e0debe"
+    );
+    assert_eq!(
+        secp256r1::recover(&msg, &signature[..64]),
+        Err(MangekyouError::InputLengthWrong(65))
+    );
+
+    // The other recovery id gives another key
+    let mut other = signature.clone();
+    other[64] = 0;
+    assert_ne!(
+        hex::encode(secp256r1::recover(&msg, &other).unwrap()),
+        "035a8b075508c75f4a124749982a7d21f80d9a5f6893e41a9e955fe4c821e0debe"
+    );
+}
+
+#[test]
+fn test_secp256r1_random_keys() {
+    // Both recovery ids and both parities of s occur across keys and messages
+    let mut rng = StdRng::from_seed([2; 32]);
+    for i in 0..32u8 {
+        let kp = Secp256r1KeyPair::generate(&mut rng);
+        let signature = kp.sign_recoverable(&[i]);
+        assert!(kp.public().verify(&[i], &Secp256r1Signature::from(&signature)).is_ok());
+        assert_eq!(&signature.recover(&[i]).unwrap(), kp.public());
+        assert!(kp.public().verify(&[i, i], &Secp256r1Signature::from(&signature)).is_err());
+    }
+}
+
+#[test]
+fn test_with_hash() {
+    let msg = b"Hello, world!";
+    let kp: Secp256k1KeyPair = generate();
+    let signature = kp.sign_with_hash::<Keccak256>(msg);
+    assert!(kp.public().verify_with_hash::<Keccak256>(msg, &signature).is_ok());
+    assert!(kp.public().verify(msg, &signature).is_err());
+    let recoverable = kp.sign_recoverable_with_hash::<Keccak256>(msg);
+    assert_eq!(&recoverable.recover_with_hash::<Keccak256>(msg).unwrap(), kp.public());
+    assert_ne!(&recoverable.recover(msg).unwrap(), kp.public());
+
+    let kp: Secp256r1KeyPair = generate();
+    let signature = kp.sign_with_hash::<Keccak256>(msg);
+    assert!(kp.public().verify_with_hash::<Keccak256>(msg, &signature).is_ok());
+    assert!(kp.public().verify(msg, &signature).is_err());
+    let recoverable = kp.sign_recoverable_with_hash::<Keccak256>(msg);
+    assert!(kp.public().verify_recoverable_with_hash::<Keccak256>(msg, &recoverable).is_ok());
+}
+
+#[test]
+fn test_invalid_encodings() {
+    assert_eq!(
+        Ed25519PublicKey::from_bytes(&[0; 31]),
+        Err(MangekyouError::InputLengthWrong(32))
+    );
+    assert!(Ed25519Signature::from_bytes(&[0; 64]).is_ok());
+
+    // Not on the curve, and an uncompressed encoding
+    let mut public = [0xff; 33];
+    public[0] = 0x02;
+    assert_eq!(
+        Secp256k1PublicKey::from_bytes(&public),
+        Err(MangekyouError::InvalidPublicKey)
+    );
+    assert_eq!(
+        Secp256r1PublicKey::from_bytes(&public),
+        Err(MangekyouError::InvalidPublicKey)
+    );
+    assert_eq!(
+        Secp256r1PublicKey::from_bytes(&[4; 65]),
+        Err(MangekyouError::InputLengthWrong(33))
+    );
+
+    // Zero and the group order are not private keys
+    assert!(Secp256k1KeyPair::from_bytes(&[0; 32]).is_err());
+    assert!(Secp256r1KeyPair::from_bytes(&[0; 32]).is_err());
+    assert!(Secp256r1KeyPair::from_bytes(
+        &hex::decode("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551").unwrap()
+    )
+    .is_err());
+
+    // Zero r, high s, and a recovery id above 1
+    let kp: Secp256r1KeyPair = generate();
+    let signature = kp.sign_recoverable(b"Hello, world!");
+    let mut bytes = signature.as_bytes().to_vec();
+    bytes[..32].fill(0);
+    assert!(Secp256r1RecoverableSignature::from_bytes(&bytes).is_err());
+    let mut bytes = signature.as_bytes().to_vec();
+    bytes[32..64].fill(0xff);
+    assert!(Secp256r1Signature::from_bytes(&bytes[..64]).is_err());
+    let mut bytes = signature.as_bytes().to_vec();
+    bytes[64] = 2;
+    assert!(Secp256r1RecoverableSignature::from_bytes(&bytes).is_err());
+    bytes[64] = 4;
+    assert!(Secp256k1RecoverableSignature::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_serde() {
+    let kp: Secp256k1KeyPair = generate();
+    let signature = kp.sign(b"Hello, world!");
+
+    let json = serde_json::to_string(kp.public()).unwrap();
+    assert_eq!(json, format!("\"{}\"", kp.public().encode_base64()));
+    let public: Secp256k1PublicKey = serde_json::from_str(&json).unwrap();
+    assert_eq!(&public, kp.public());
+    let bytes = bincode::serialize(&signature).unwrap();
+    assert_eq!(bincode::deserialize::<Secp256k1Signature>(&bytes).unwrap(), signature);
+
+    let encoded = kp.encode_base64();
+    let kp: Ed25519KeyPair = encoded.parse().unwrap();
+    assert_eq!(hex::encode(kp.as_bytes()), PRIVATE);
+    assert_eq!(format!("{:?}", kp), "<elided secret for Ed25519KeyPair>");
+}
@@ -0,0 +1,225 @@
+// Copyright (c) 2022, Mangekyou Network, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::MangekyouError;
+use crate::groups::ristretto255::{RistrettoPoint, RistrettoScalar};
+use crate::groups::GroupElement;
+use crate::tdkg::{
+    lagrange_coefficient, Complaint, KeyShare, Parameters, Party, PartyId, Share, Transcript,
+};
+use rand::thread_rng;
+
+fn parties(parameters: Parameters) -> Vec<Party> {
+    (1..=parameters.parties())
+        .map(|id| Party::new(parameters, id, &mut thread_rng()).unwrap())
+        .collect()
+}
+
+/// Runs a DKG in which every dealer sends the share `share(dealer, receiver)`
+/// and answers complaints with `respond(dealer, complaint)`
+fn run(
+    parameters: Parameters,
+    share: impl Fn(&Party, PartyId) -> Option<Share>,
+    respond: impl Fn(&Party, &Complaint) -> Option<Share>,
+) -> (Vec<Party>, Transcript) {
+    let mut parties = parties(parameters);
+    let mut transcript = Transcript::new(parameters);
+    let dealings: Vec<_> = parties.iter().map(Party::dealing).collect();
+    let shares: Vec<Vec<Option<Share>>> = parties
+        .iter()
+        .map(|dealer| (1..=parameters.parties()).map(|receiver| share(dealer, receiver)).collect())
+        .collect();
+    for dealing in &dealings {
+        transcript.add_dealing(dealing.clone()).unwrap();
+    }
+
+    for (receiver, party) in parties.iter_mut().enumerate() {
+        for (dealer, dealing) in dealings.iter().enumerate() {
+            if let Some(complaint) = party.receive(dealing, shares[dealer][receiver].as_ref()) {
+                transcript.add_complaint(complaint).unwrap();
+            }
+        }
+    }
+
+    for complaint in transcript.complaints().to_vec() {
+        let dealer = &parties[complaint.dealer as usize - 1];
+        if let Some(response) = respond(dealer, &complaint) {
+            transcript.add_response(response).unwrap();
+        }
+    }
+    (parties, transcript)
+}
+
+fn run_honest(parameters: Parameters) -> (Vec<Party>, Transcript) {
+    run(parameters, |dealer, receiver| dealer.share(receiver).ok(), Party::respond)
+}
+
+fn reconstruct(shares: &[&KeyShare]) -> RistrettoScalar {
+    let ids: Vec<PartyId> = shares.iter().map(|share| share.id).collect();
+    shares.iter().fold(RistrettoScalar::zero(), |sum, share| {
+        sum + lagrange_coefficient(share.id, &ids).unwrap() * share.share()
+    })
+}
+
+#[test]
+fn test_dkg() {
+    let parameters = Parameters::new(3, 5).unwrap();
+    let (parties, transcript) = run_honest(parameters);
+    assert!(transcript.complaints().is_empty());
+
+    let output = transcript.output().unwrap();
+    assert_eq!(output.qualified, vec![1, 2, 3, 4, 5]);
+    let public_key = parties
+        .iter()
+        .fold(RistrettoPoint::zero(), |sum, party| sum + party.dealing().commitments[0]);
+    assert_eq!(output.public_key(), public_key);
+    assert_eq!(output.vrf_public_key().unwrap().as_ref(), public_key.compress());
+
+    let key_shares: Vec<KeyShare> = parties.iter().map(|party| party.finalize(&transcript).unwrap()).collect();
+    for key_share in &key_shares {
+        assert_eq!(key_share.output, output);
+        assert_eq!(RistrettoPoint::generator() * key_share.share(), output.share_public_key(key_share.id));
+    }
+
+    // Any threshold of shares recovers the secret key, and fewer do not
+    let secret = reconstruct(&[&key_shares[0], &key_shares[2], &key_shares[4]]);
+    assert_eq!(RistrettoPoint::generator() * secret, public_key);
+    assert_eq!(reconstruct(&[&key_shares[3], &key_shares[1], &key_shares[0]]), secret);
+    assert_ne!(reconstruct(&[&key_shares[0], &key_shares[1]]), secret);
+}
+
+#[test]
+fn test_complaints() {
+    let parameters = Parameters::new(2, 5).unwrap();
+    // Dealer 2 sends no share to 4 and then answers, dealer 3 sends an
+    // invalid share to 1 and does not answer, and dealer 5 sends an invalid
+    // share to 2 and answers with another invalid one
+    let invalid = |dealer: &Party, receiver| {
+        let mut share = dealer.share(receiver).unwrap();
+        share.value += RistrettoScalar::generator();
+        share
+    };
+    let (parties, transcript) = run(
+        parameters,
+        |dealer, receiver| match (dealer.id(), receiver) {
+            (2, 4) => None,
+            (3, 1) | (5, 2) => Some(invalid(dealer, receiver)),
+            _ => dealer.share(receiver).ok(),
+        },
+        |dealer, complaint| match dealer.id() {
+            3 => None,
+            5 => Some(invalid(dealer, complaint.accuser)),
+            _ => dealer.respond(complaint),
+        },
+    );
+
+    assert_eq!(
+        transcript.complaints(),
+        [
+            Complaint { accuser: 1, dealer: 3 },
+            Complaint { accuser: 2, dealer: 5 },
+            Complaint { accuser: 4, dealer: 2 },
+        ]
+    );
+    assert_eq!(transcript.qualified(), vec![1, 2, 4]);
+
+    let output = transcript.output().unwrap();
+    let public_key = [0, 1, 3]
+        .iter()
+        .fold(RistrettoPoint::zero(), |sum, &index| sum + parties[index].dealing().commitments[0]);
+    assert_eq!(output.public_key(), public_key);
+
+    // Party 4 takes its share from dealer 2 from the transcript
+    let key_shares: Vec<KeyShare> = parties.iter().map(|party| party.finalize(&transcript).unwrap()).collect();
+    let secret = reconstruct(&[&key_shares[3], &key_shares[4]]);
+    assert_eq!(RistrettoPoint::generator() * secret, public_key);
+    assert_eq!(reconstruct(&[&key_shares[0], &key_shares[3]]), secret);
+}
+
+#[test]
+fn test_not_enough_qualified() {
+    let parameters = Parameters::new(3, 3).unwrap();
+    let (parties, transcript) = run(
+        parameters,
+        |dealer, receiver| if dealer.id() == 1 && receiver != 1 { None } else { dealer.share(receiver).ok() },
+        |_, _| None,
+    );
+    assert_eq!(transcript.qualified(), vec![2, 3]);
+    assert_eq!(transcript.output(), Err(MangekyouError::NotEnoughInputs));
+    assert!(parties[1].finalize(&transcript).is_err());
+}
+
+#[test]
+fn test_transcript_serialization() {
+    let parameters = Parameters::new(2, 3).unwrap();
+    let (parties, transcript) = run(
+        parameters,
+        |dealer, receiver| if (dealer.id(), receiver) == (1, 3) { None } else { dealer.share(receiver).ok() },
+        Party::respond,
+    );
+    assert_eq!(transcript.responses().len(), 1);
+
+    let bytes = transcript.to_bytes();
+    let decoded = Transcript::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, transcript);
+    assert_eq!(decoded.output(), transcript.output());
+    assert!(parties[2].finalize(&decoded).is_ok());
+
+    assert!(Transcript::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+    // Transcripts that could not have been built message by message do not
+    // deserialize either
+    let mut tampered = bincode::serialize(&(
+        parameters,
+        vec![parties[0].dealing(), parties[0].dealing()],
+        Vec::<Complaint>::new(),
+        Vec::<Share>::new(),
+    ))
+    .unwrap();
+    assert_eq!(Transcript::from_bytes(&tampered), Err(MangekyouError::InvalidInput));
+    tampered = bincode::serialize(&(
+        parameters,
+        vec![parties[0].dealing()],
+        Vec::<Complaint>::new(),
+        vec![parties[0].share(2).unwrap()],
+    ))
+    .unwrap();
+    assert_eq!(Transcript::from_bytes(&tampered), Err(MangekyouError::InvalidInput));
+}
+
+#[test]
+fn test_invalid_inputs() {
+    assert!(Parameters::new(0, 3).is_err());
+    assert!(Parameters::new(4, 3).is_err());
+
+    let parameters = Parameters::new(2, 3).unwrap();
+    assert!(Party::new(parameters, 0, &mut thread_rng()).is_err());
+    assert!(Party::new(parameters, 4, &mut thread_rng()).is_err());
+
+    let party = Party::new(parameters, 1, &mut thread_rng()).unwrap();
+    assert!(party.share(4).is_err());
+    assert!(party.respond(&Complaint { accuser: 1, dealer: 2 }).is_none());
+
+    let mut transcript = Transcript::new(parameters);
+    let mut dealing = party.dealing();
+    dealing.commitments.pop();
+    assert!(transcript.add_dealing(dealing).is_err());
+    transcript.add_dealing(party.dealing()).unwrap();
+    assert!(transcript.add_dealing(party.dealing()).is_err());
+
+    // Complaints must be against another dealer with a dealing
+    assert!(transcript.add_complaint(Complaint { accuser: 1, dealer: 1 }).is_err());
+    assert!(transcript.add_complaint(Complaint { accuser: 1, dealer: 2 }).is_err());
+    assert!(transcript.add_complaint(Complaint { accuser: 4, dealer: 1 }).is_err());
+    transcript.add_complaint(Complaint { accuser: 2, dealer: 1 }).unwrap();
+    assert!(transcript.add_complaint(Complaint { accuser: 2, dealer: 1 }).is_err());
+
+    // Responses must answer a complaint, once
+    assert!(transcript.add_response(party.share(3).unwrap()).is_err());
+    transcript.add_response(party.share(2).unwrap()).unwrap();
+    assert!(transcript.add_response(party.share(2).unwrap()).is_err());
+
+    assert!(lagrange_coefficient(1, &[2, 3]).is_err());
+    assert!(lagrange_coefficient(1, &[1, 2, 1]).is_err());
+    assert!(lagrange_coefficient(0, &[0, 1]).is_err());
+}